@@ -176,6 +176,12 @@ impl From<nostr::nips::nip59::Error> for NostrError {
     }
 }
 
+impl From<nostr::nips::nip94::FileMetadataError> for NostrError {
+    fn from(e: nostr::nips::nip94::FileMetadataError) -> NostrError {
+        Self::Generic(e.to_string())
+    }
+}
+
 impl From<nostr::nips::nip90::Error> for NostrError {
     fn from(e: nostr::nips::nip90::Error) -> NostrError {
         Self::Generic(e.to_string())
@@ -141,6 +141,14 @@ impl EventBuilder {
         })
     }
 
+    #[uniffi::constructor]
+    pub fn draft(content: &str, tags: &[Arc<Tag>]) -> Self {
+        let tags = tags.iter().map(|t| t.as_ref().deref().clone());
+        Self {
+            inner: nostr::EventBuilder::draft(content, tags),
+        }
+    }
+
     #[uniffi::constructor]
     pub fn contact_list(list: &[Arc<Contact>]) -> Self {
         let list: Vec<ContactSdk> = list.iter().map(|c| c.as_ref().deref().clone()).collect();
@@ -555,9 +563,12 @@ impl EventBuilder {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
     #[uniffi::constructor]
-    pub fn follow_sets(publick_key: Vec<Arc<PublicKey>>) -> Self {
+    pub fn follow_sets(identifier: String, public_keys: Vec<Arc<PublicKey>>) -> Self {
         Self {
-            inner: nostr::EventBuilder::follow_sets(publick_key.into_iter().map(|p| **p)),
+            inner: nostr::EventBuilder::follow_sets(
+                identifier,
+                public_keys.into_iter().map(|p| **p),
+            ),
         }
     }
 
@@ -565,9 +576,12 @@ impl EventBuilder {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
     #[uniffi::constructor]
-    pub fn relay_sets(relay: Vec<String>) -> Self {
+    pub fn relay_sets(identifier: String, relays: Vec<String>) -> Self {
         Self {
-            inner: nostr::EventBuilder::relay_sets(relay.into_iter().map(UncheckedUrl::from)),
+            inner: nostr::EventBuilder::relay_sets(
+                identifier,
+                relays.into_iter().map(UncheckedUrl::from),
+            ),
         }
     }
 
@@ -575,9 +589,9 @@ impl EventBuilder {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
     #[uniffi::constructor]
-    pub fn bookmarks_sets(list: Bookmarks) -> Result<Self> {
+    pub fn bookmarks_sets(identifier: String, list: Bookmarks) -> Result<Self> {
         Ok(Self {
-            inner: nostr::EventBuilder::bookmarks_sets(list.try_into()?),
+            inner: nostr::EventBuilder::bookmarks_sets(identifier, list.try_into()?),
         })
     }
 
@@ -585,9 +599,9 @@ impl EventBuilder {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
     #[uniffi::constructor]
-    pub fn articles_curation_sets(list: ArticlesCuration) -> Self {
+    pub fn articles_curation_sets(identifier: String, list: ArticlesCuration) -> Self {
         Self {
-            inner: nostr::EventBuilder::articles_curation_sets(list.into()),
+            inner: nostr::EventBuilder::articles_curation_sets(identifier, list.into()),
         }
     }
 
@@ -595,10 +609,11 @@ impl EventBuilder {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
     #[uniffi::constructor]
-    pub fn videos_curation_sets(video: Vec<Arc<Coordinate>>) -> Self {
+    pub fn videos_curation_sets(identifier: String, videos: Vec<Arc<Coordinate>>) -> Self {
         Self {
             inner: nostr::EventBuilder::videos_curation_sets(
-                video.into_iter().map(|c| c.as_ref().deref().clone()),
+                identifier,
+                videos.into_iter().map(|c| c.as_ref().deref().clone()),
             ),
         }
     }
@@ -607,9 +622,12 @@ impl EventBuilder {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
     #[uniffi::constructor]
-    pub fn emoji_sets(emojis: Vec<EmojiInfo>) -> Self {
+    pub fn emoji_sets(identifier: String, emojis: Vec<EmojiInfo>) -> Self {
         Self {
-            inner: nostr::EventBuilder::emoji_sets(emojis.into_iter().map(|e| e.into())),
+            inner: nostr::EventBuilder::emoji_sets(
+                identifier,
+                emojis.into_iter().map(|e| e.into()),
+            ),
         }
     }
 
@@ -226,8 +226,34 @@ pub enum KindEnum {
     SealedDirect,
     /// Long-form Text Note (NIP23)
     LongFormTextNote,
+    /// Long-form Text Note Draft (NIP23)
+    LongFormTextNoteDraft,
     /// Application-specific Data (NIP78)
     ApplicationSpecificData,
+    /// Draft (NIP37)
+    Draft,
+    /// Classified Listing (NIP99)
+    ClassifiedListing,
+    /// Draft or inactive Classified Listing (NIP99)
+    DraftClassifiedListing,
+    /// Git Repository Announcement (NIP34)
+    GitRepoAnnouncement,
+    /// Git Patch (NIP34)
+    GitPatch,
+    /// Git Issue (NIP34)
+    GitIssue,
+    /// Git Status Open (NIP34)
+    GitStatusOpen,
+    /// Git Status Applied / Merged / Resolved (NIP34)
+    GitStatusApplied,
+    /// Git Status Closed (NIP34)
+    GitStatusClosed,
+    /// Git Status Draft (NIP34)
+    GitStatusDraft,
+    /// Handler Recommendation (NIP89)
+    HandlerRecommendation,
+    /// Handler Information (NIP89)
+    HandlerInformation,
     /// File Metadata (NIP94)
     FileMetadata,
     /// HTTP Auth (NIP98)
@@ -322,7 +348,20 @@ impl From<nostr::Kind> for KindEnum {
             nostr::Kind::GiftWrap => Self::GiftWrap,
             nostr::Kind::SealedDirect => Self::SealedDirect,
             nostr::Kind::LongFormTextNote => Self::LongFormTextNote,
+            nostr::Kind::LongFormTextNoteDraft => Self::LongFormTextNoteDraft,
             nostr::Kind::ApplicationSpecificData => Self::ApplicationSpecificData,
+            nostr::Kind::Draft => Self::Draft,
+            nostr::Kind::ClassifiedListing => Self::ClassifiedListing,
+            nostr::Kind::DraftClassifiedListing => Self::DraftClassifiedListing,
+            nostr::Kind::GitRepoAnnouncement => Self::GitRepoAnnouncement,
+            nostr::Kind::GitPatch => Self::GitPatch,
+            nostr::Kind::GitIssue => Self::GitIssue,
+            nostr::Kind::GitStatusOpen => Self::GitStatusOpen,
+            nostr::Kind::GitStatusApplied => Self::GitStatusApplied,
+            nostr::Kind::GitStatusClosed => Self::GitStatusClosed,
+            nostr::Kind::GitStatusDraft => Self::GitStatusDraft,
+            nostr::Kind::HandlerRecommendation => Self::HandlerRecommendation,
+            nostr::Kind::HandlerInformation => Self::HandlerInformation,
             nostr::Kind::FileMetadata => Self::FileMetadata,
             nostr::Kind::HttpAuth => Self::HttpAuth,
             nostr::Kind::SetStall => Self::SetStall,
@@ -400,7 +439,20 @@ impl From<KindEnum> for nostr::Kind {
             KindEnum::GiftWrap => Self::GiftWrap,
             KindEnum::SealedDirect => Self::SealedDirect,
             KindEnum::LongFormTextNote => Self::LongFormTextNote,
+            KindEnum::LongFormTextNoteDraft => Self::LongFormTextNoteDraft,
             KindEnum::ApplicationSpecificData => Self::ApplicationSpecificData,
+            KindEnum::Draft => Self::Draft,
+            KindEnum::ClassifiedListing => Self::ClassifiedListing,
+            KindEnum::DraftClassifiedListing => Self::DraftClassifiedListing,
+            KindEnum::GitRepoAnnouncement => Self::GitRepoAnnouncement,
+            KindEnum::GitPatch => Self::GitPatch,
+            KindEnum::GitIssue => Self::GitIssue,
+            KindEnum::GitStatusOpen => Self::GitStatusOpen,
+            KindEnum::GitStatusApplied => Self::GitStatusApplied,
+            KindEnum::GitStatusClosed => Self::GitStatusClosed,
+            KindEnum::GitStatusDraft => Self::GitStatusDraft,
+            KindEnum::HandlerRecommendation => Self::HandlerRecommendation,
+            KindEnum::HandlerInformation => Self::HandlerInformation,
             KindEnum::FileMetadata => Self::FileMetadata,
             KindEnum::HttpAuth => Self::HttpAuth,
             KindEnum::SetStall => Self::SetStall,
@@ -64,6 +64,8 @@ impl From<tag::Marker> for Marker {
 pub enum Report {
     /// Depictions of nudity, porn, etc
     Nudity,
+    /// Virus, trojan horse, worm, robot, spyware, adware, back door, ransomware, rootkit, kidnapper, etc.
+    Malware,
     /// Profanity, hateful speech, etc.
     Profanity,
     /// Something which may be illegal in some jurisdiction
@@ -82,6 +84,7 @@ impl From<Report> for tag::Report {
     fn from(value: Report) -> Self {
         match value {
             Report::Nudity => Self::Nudity,
+            Report::Malware => Self::Malware,
             Report::Profanity => Self::Profanity,
             Report::Illegal => Self::Illegal,
             Report::Spam => Self::Spam,
@@ -95,6 +98,7 @@ impl From<tag::Report> for Report {
     fn from(value: tag::Report) -> Self {
         match value {
             tag::Report::Nudity => Self::Nudity,
+            tag::Report::Malware => Self::Malware,
             tag::Report::Profanity => Self::Profanity,
             tag::Report::Illegal => Self::Illegal,
             tag::Report::Spam => Self::Spam,
@@ -110,6 +114,7 @@ pub enum HttpMethod {
     Post,
     Put,
     Patch,
+    Delete,
 }
 
 impl From<HttpMethod> for tag::HttpMethod {
@@ -119,6 +124,7 @@ impl From<HttpMethod> for tag::HttpMethod {
             HttpMethod::Post => Self::POST,
             HttpMethod::Put => Self::PUT,
             HttpMethod::Patch => Self::PATCH,
+            HttpMethod::Delete => Self::DELETE,
         }
     }
 }
@@ -130,6 +136,7 @@ impl From<tag::HttpMethod> for HttpMethod {
             tag::HttpMethod::POST => Self::Post,
             tag::HttpMethod::PUT => Self::Put,
             tag::HttpMethod::PATCH => Self::Patch,
+            tag::HttpMethod::DELETE => Self::Delete,
         }
     }
 }
@@ -238,8 +245,14 @@ pub enum TagKind {
     Emoji,
     /// Encrypted
     Encrypted,
+    /// Protected event (NIP70)
+    Protected,
     Request,
     Word,
+    Imeta,
+    Zap,
+    Client,
+    Alt,
     Unknown {
         unknown: String,
     },
@@ -289,8 +302,13 @@ impl From<tag::TagKind> for TagKind {
             tag::TagKind::Proxy => Self::Proxy,
             tag::TagKind::Emoji => Self::Emoji,
             tag::TagKind::Encrypted => Self::Encrypted,
+            tag::TagKind::Protected => Self::Protected,
             tag::TagKind::Request => Self::Request,
             tag::TagKind::Word => Self::Word,
+            tag::TagKind::Imeta => Self::Imeta,
+            tag::TagKind::Zap => Self::Zap,
+            tag::TagKind::Client => Self::Client,
+            tag::TagKind::Alt => Self::Alt,
             tag::TagKind::Custom(unknown) => Self::Unknown { unknown },
         }
     }
@@ -338,8 +356,13 @@ impl From<TagKind> for tag::TagKind {
             TagKind::Proxy => Self::Proxy,
             TagKind::Emoji => Self::Emoji,
             TagKind::Encrypted => Self::Encrypted,
+            TagKind::Protected => Self::Protected,
             TagKind::Request => Self::Request,
             TagKind::Word => Self::Word,
+            TagKind::Imeta => Self::Imeta,
+            TagKind::Zap => Self::Zap,
+            TagKind::Client => Self::Client,
+            TagKind::Alt => Self::Alt,
             TagKind::Unknown { unknown } => Self::Custom(unknown),
         }
     }
@@ -534,6 +557,7 @@ pub enum TagEnum {
         url: String,
     },
     Encrypted,
+    Protected,
     Request {
         event: Arc<Event>,
     },
@@ -550,6 +574,22 @@ pub enum TagEnum {
     Label {
         label: Vec<String>,
     },
+    Imeta {
+        entries: Vec<String>,
+    },
+    Zap {
+        public_key: Arc<PublicKey>,
+        relay_url: String,
+        weight: Option<u64>,
+    },
+    Client {
+        name: String,
+        address: Option<Arc<Coordinate>>,
+        relay_url: Option<String>,
+    },
+    Alt {
+        summary: String,
+    },
 }
 
 impl From<tag::Tag> for TagEnum {
@@ -709,6 +749,7 @@ impl From<tag::Tag> for TagEnum {
                 url: url.to_string(),
             },
             tag::Tag::Encrypted => Self::Encrypted,
+            tag::Tag::Protected => Self::Protected,
             tag::Tag::Request(event) => Self::Request {
                 event: Arc::new(event.into()),
             },
@@ -721,6 +762,26 @@ impl From<tag::Tag> for TagEnum {
             tag::Tag::Word(word) => Self::Word { word },
             tag::Tag::LabelNamespace(label) => Self::LabelNamespace { namespace: label },
             tag::Tag::Label(labels) => Self::Label { label: labels },
+            tag::Tag::Imeta(entries) => Self::Imeta { entries },
+            tag::Tag::Zap {
+                public_key,
+                relay_url,
+                weight,
+            } => Self::Zap {
+                public_key: Arc::new(public_key.into()),
+                relay_url: relay_url.to_string(),
+                weight,
+            },
+            tag::Tag::Client {
+                name,
+                address,
+                relay_url,
+            } => Self::Client {
+                name,
+                address: address.map(|a| Arc::new(a.into())),
+                relay_url: relay_url.map(|u| u.to_string()),
+            },
+            tag::Tag::Alt(summary) => Self::Alt { summary },
         }
     }
 }
@@ -856,6 +917,7 @@ impl TryFrom<TagEnum> for tag::Tag {
                 url: UncheckedUrl::from(url),
             }),
             TagEnum::Encrypted => Ok(Self::Encrypted),
+            TagEnum::Protected => Ok(Self::Protected),
             TagEnum::Request { event } => Ok(Self::Request(event.as_ref().deref().clone())),
             TagEnum::DataVendingMachineStatusTag { status, extra_info } => {
                 Ok(Self::DataVendingMachineStatus {
@@ -866,6 +928,26 @@ impl TryFrom<TagEnum> for tag::Tag {
             TagEnum::Word { word } => Ok(Self::Word(word)),
             TagEnum::LabelNamespace { namespace } => Ok(Self::LabelNamespace(namespace)),
             TagEnum::Label { label } => Ok(Self::Label(label)),
+            TagEnum::Imeta { entries } => Ok(Self::Imeta(entries)),
+            TagEnum::Zap {
+                public_key,
+                relay_url,
+                weight,
+            } => Ok(Self::Zap {
+                public_key: **public_key,
+                relay_url: UncheckedUrl::from(relay_url),
+                weight,
+            }),
+            TagEnum::Client {
+                name,
+                address,
+                relay_url,
+            } => Ok(Self::Client {
+                name,
+                address: address.map(|a| a.as_ref().deref().clone()),
+                relay_url: relay_url.map(UncheckedUrl::from),
+            }),
+            TagEnum::Alt { summary } => Ok(Self::Alt(summary)),
         }
     }
 }
@@ -8,12 +8,12 @@ use std::sync::Arc;
 
 use nostr::hashes::sha256::Hash as Sha256Hash;
 use nostr::nips::nip94;
-use nostr::Url;
+use nostr::{UncheckedUrl, Url};
 use uniffi::Object;
 
 use crate::error::Result;
 use crate::helper::unwrap_or_clone_arc;
-use crate::ImageDimensions;
+use crate::{Event, ImageDimensions};
 
 #[derive(Clone, Object)]
 pub struct FileMetadata {
@@ -45,6 +45,14 @@ impl FileMetadata {
         })
     }
 
+    /// Extract file metadata from a file metadata event (kind `1063`)
+    #[uniffi::constructor]
+    pub fn from_event(event: &Event) -> Result<Self> {
+        Ok(Self {
+            inner: nip94::FileMetadata::try_from(event.deref())?,
+        })
+    }
+
     pub fn aes_256_gcm(self: Arc<Self>, key: String, iv: String) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.aes_256_gcm(key, iv);
@@ -78,4 +86,13 @@ impl FileMetadata {
         builder.inner = builder.inner.blurhash(blurhash);
         builder
     }
+
+    /// Add thumbnail
+    pub fn thumb(self: Arc<Self>, url: String, dim: Option<Arc<ImageDimensions>>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder
+            .inner
+            .thumb(UncheckedUrl::from(url), dim.map(|d| **d));
+        builder
+    }
 }
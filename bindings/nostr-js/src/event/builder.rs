@@ -147,6 +147,13 @@ impl JsEventBuilder {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn draft(content: &str, tags: Vec<JsTag>) -> Self {
+        Self {
+            inner: EventBuilder::draft(content, tags.into_iter().map(|t| t.into())),
+        }
+    }
+
     #[wasm_bindgen(js_name = contactList)]
     pub fn contact_list(list: Vec<JsContact>) -> Self {
         let list = list.into_iter().map(|c| c.inner());
@@ -15,6 +15,7 @@ pub enum JsHttpMethod {
     POST,
     PUT,
     PATCH,
+    DELETE,
 }
 
 impl From<HttpMethod> for JsHttpMethod {
@@ -24,6 +25,7 @@ impl From<HttpMethod> for JsHttpMethod {
             HttpMethod::POST => Self::POST,
             HttpMethod::PUT => Self::PUT,
             HttpMethod::PATCH => Self::PATCH,
+            HttpMethod::DELETE => Self::DELETE,
         }
     }
 }
@@ -35,6 +37,7 @@ impl From<JsHttpMethod> for HttpMethod {
             JsHttpMethod::POST => Self::POST,
             JsHttpMethod::PUT => Self::PUT,
             JsHttpMethod::PATCH => Self::PATCH,
+            JsHttpMethod::DELETE => Self::DELETE,
         }
     }
 }
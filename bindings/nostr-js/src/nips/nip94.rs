@@ -114,4 +114,9 @@ impl JsFileMetadata {
     pub fn blurhash(&self) -> Option<String> {
         self.inner.blurhash.clone()
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn thumb(&self) -> Option<String> {
+        self.inner.thumb.as_ref().map(|(url, ..)| url.to_string())
+    }
 }
@@ -28,6 +28,7 @@ pub use self::signer::NostrSigner;
 use self::zapper::{ZapDetails, ZapEntity};
 use crate::abortable::AbortHandle;
 use crate::error::Result;
+use crate::pool::Output;
 use crate::relay::options::{NegentropyOptions, SubscribeAutoCloseOptions};
 use crate::relay::RelayOptions;
 use crate::{HandleNotification, NostrDatabase, Relay};
@@ -295,25 +296,23 @@ impl Client {
         })
     }
 
-    pub fn send_event(&self, event: Arc<Event>) -> Result<Arc<EventId>> {
+    pub fn send_event(&self, event: Arc<Event>) -> Result<Output> {
         block_on(async move {
-            Ok(Arc::new(
-                self.inner
-                    .send_event(event.as_ref().deref().clone())
-                    .await?
-                    .into(),
-            ))
+            Ok(self
+                .inner
+                .send_event(event.as_ref().deref().clone())
+                .await?
+                .into())
         })
     }
 
-    pub fn send_event_to(&self, urls: Vec<String>, event: Arc<Event>) -> Result<Arc<EventId>> {
+    pub fn send_event_to(&self, urls: Vec<String>, event: Arc<Event>) -> Result<Output> {
         block_on(async move {
-            Ok(Arc::new(
-                self.inner
-                    .send_event_to(urls, event.as_ref().deref().clone())
-                    .await?
-                    .into(),
-            ))
+            Ok(self
+                .inner
+                .send_event_to(urls, event.as_ref().deref().clone())
+                .await?
+                .into())
         })
     }
 
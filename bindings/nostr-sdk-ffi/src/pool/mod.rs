@@ -8,7 +8,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use async_utility::thread;
-use nostr_ffi::{ClientMessage, Event, EventId, Filter};
+use nostr_ffi::{ClientMessage, Event, Filter};
 use nostr_sdk::database::DynNostrDatabase;
 use nostr_sdk::{block_on, spawn_blocking, RelayPoolOptions, SubscriptionId};
 use uniffi::Object;
@@ -19,6 +19,10 @@ use crate::relay::options::{FilterOptions, NegentropyOptions};
 use crate::relay::{RelayOptions, RelaySendOptions, SubscribeOptions};
 use crate::{HandleNotification, NostrDatabase, Relay};
 
+mod output;
+
+pub use self::output::Output;
+
 #[derive(Object)]
 pub struct RelayPool {
     inner: nostr_sdk::RelayPool,
@@ -185,14 +189,13 @@ impl RelayPool {
     }
 
     /// Send event to **all connected relays** and wait for `OK` message
-    pub fn send_event(&self, event: &Event, opts: &RelaySendOptions) -> Result<Arc<EventId>> {
+    pub fn send_event(&self, event: &Event, opts: &RelaySendOptions) -> Result<Output> {
         block_on(async move {
-            Ok(Arc::new(
-                self.inner
-                    .send_event(event.deref().clone(), **opts)
-                    .await?
-                    .into(),
-            ))
+            Ok(self
+                .inner
+                .send_event(event.deref().clone(), **opts)
+                .await?
+                .into())
         })
     }
 
@@ -211,14 +214,13 @@ impl RelayPool {
         urls: Vec<String>,
         event: &Event,
         opts: &RelaySendOptions,
-    ) -> Result<Arc<EventId>> {
+    ) -> Result<Output> {
         block_on(async move {
-            Ok(Arc::new(
-                self.inner
-                    .send_event_to(urls, event.deref().clone(), **opts)
-                    .await?
-                    .into(),
-            ))
+            Ok(self
+                .inner
+                .send_event_to(urls, event.deref().clone(), **opts)
+                .await?
+                .into())
         })
     }
 
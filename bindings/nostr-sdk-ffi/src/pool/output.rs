@@ -0,0 +1,35 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use nostr_ffi::EventId;
+use uniffi::Record;
+
+/// Publish output, per relay
+#[derive(Record)]
+pub struct Output {
+    /// Relays that accepted the event, with the ID of the accepted event
+    pub success: HashMap<String, Arc<EventId>>,
+    /// Relays that rejected the event or failed, with the reason
+    pub failed: HashMap<String, String>,
+}
+
+impl From<nostr_sdk::Output> for Output {
+    fn from(output: nostr_sdk::Output) -> Self {
+        Self {
+            success: output
+                .success
+                .into_iter()
+                .map(|(url, id)| (url.to_string(), Arc::new(id.into())))
+                .collect(),
+            failed: output
+                .failed
+                .into_iter()
+                .map(|(url, reason)| (url.to_string(), reason))
+                .collect(),
+        }
+    }
+}
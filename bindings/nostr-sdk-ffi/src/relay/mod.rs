@@ -286,13 +286,13 @@ impl Relay {
     }
 
     /// Count events of filters
-    pub fn count_events_of(&self, filters: Vec<Arc<Filter>>, timeout: Duration) -> Result<u64> {
+    pub fn count_events(&self, filters: Vec<Arc<Filter>>, timeout: Duration) -> Result<u64> {
         block_on(async move {
             let filters = filters
                 .into_iter()
                 .map(|f| f.as_ref().deref().clone())
                 .collect();
-            Ok(self.inner.count_events_of(filters, timeout).await? as u64)
+            Ok(self.inner.count_events(filters, timeout).await? as u64)
         })
     }
 
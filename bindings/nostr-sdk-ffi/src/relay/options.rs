@@ -239,12 +239,26 @@ impl SubscribeAutoCloseOptions {
         builder
     }
 
+    /// Don't close subscription on EOSE
+    pub fn ignore_eose(self: Arc<Self>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.ignore_eose();
+        builder
+    }
+
     /// Automatically close subscription after `Duration`
     pub fn timeout(self: Arc<Self>, timeout: Option<Duration>) -> Self {
         let mut builder = unwrap_or_clone_arc(self);
         builder.inner = builder.inner.timeout(timeout);
         builder
     }
+
+    /// Automatically close subscription after receiving N events
+    pub fn max_events(self: Arc<Self>, max: Option<u16>) -> Self {
+        let mut builder = unwrap_or_clone_arc(self);
+        builder.inner = builder.inner.max_events(max);
+        builder
+    }
 }
 
 /// Subscribe options
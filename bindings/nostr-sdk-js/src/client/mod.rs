@@ -275,11 +275,15 @@ impl JsClient {
     /// If you not want to wait for the `OK` message, use `sendMsg` method instead.
     #[wasm_bindgen(js_name = sendEvent)]
     pub async fn send_event(&self, event: &JsEvent) -> Result<JsEventId> {
-        self.inner
+        let output = self
+            .inner
             .send_event(event.deref().clone())
             .await
-            .map_err(into_err)
-            .map(|id| id.into())
+            .map_err(into_err)?;
+        match output.success.into_values().next() {
+            Some(id) => Ok(id.into()),
+            None => Err(into_err(nostr_sdk::pool::Error::EventNotPublished)),
+        }
     }
 
     /// Send event to specific relay
@@ -288,11 +292,15 @@ impl JsClient {
     /// If you not want to wait for the `OK` message, use `sendMsgTo` method instead.
     #[wasm_bindgen(js_name = sendEventTo)]
     pub async fn send_event_to(&self, urls: Vec<String>, event: &JsEvent) -> Result<JsEventId> {
-        self.inner
+        let output = self
+            .inner
             .send_event_to(urls, event.deref().clone())
             .await
-            .map_err(into_err)
-            .map(|id| id.into())
+            .map_err(into_err)?;
+        match output.success.into_values().next() {
+            Some(id) => Ok(id.into()),
+            None => Err(into_err(nostr_sdk::pool::Error::EventNotPublished)),
+        }
     }
 
     /// Signs the `EventBuilder` into an `Event` using the `NostrSigner`
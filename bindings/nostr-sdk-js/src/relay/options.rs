@@ -79,10 +79,22 @@ impl JsSubscribeAutoCloseOptions {
         self.inner.filter(filter.inner).into()
     }
 
+    /// Don't close subscription on EOSE
+    #[wasm_bindgen(js_name = ignoreEose)]
+    pub fn ignore_eose(self) -> Self {
+        self.inner.ignore_eose().into()
+    }
+
     /// Automatically close subscription after `Duration`
     pub fn timeout(self, timeout: Option<JsDuration>) -> Self {
         self.inner.timeout(timeout.map(|t| *t)).into()
     }
+
+    /// Automatically close subscription after receiving N events
+    #[wasm_bindgen(js_name = maxEvents)]
+    pub fn max_events(self, max: Option<u16>) -> Self {
+        self.inner.max_events(max).into()
+    }
 }
 
 /// Subscribe options
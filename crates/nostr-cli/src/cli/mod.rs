@@ -84,6 +84,15 @@ pub enum DatabaseCommand {
         /// Path of JSON file
         path: PathBuf,
     },
+    /// Import the export of another client or relay
+    ///
+    /// Supported formats (autodetected): JSONL of events or `EVENT` messages
+    /// (i.e. strfry, Damus or Amethyst dumps), JSON array of events and nos2x key export.
+    #[command(arg_required_else_help = true)]
+    Import {
+        /// Path of the file
+        path: PathBuf,
+    },
     /// Database stats
     Stats,
 }
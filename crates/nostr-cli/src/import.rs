@@ -0,0 +1,246 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Import the exports of other clients and relays
+//!
+//! Supported formats (autodetected):
+//! * JSONL: one event per line (i.e. `strfry export`) or one `EVENT` message per line
+//!   (i.e. relay dumps, Damus and Amethyst debug exports);
+//! * JSON array of events;
+//! * nos2x key export: JSON object with a `private_key` (hex or bech32) field.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use nostr_sdk::prelude::*;
+use serde_json::Value;
+
+/// Export format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// One event or `EVENT` message per line
+    Jsonl,
+    /// JSON array of events
+    JsonArray,
+    /// nos2x key export
+    Nos2x,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Jsonl => write!(f, "JSONL"),
+            Self::JsonArray => write!(f, "JSON array"),
+            Self::Nos2x => write!(f, "nos2x"),
+        }
+    }
+}
+
+/// Import summary
+#[derive(Debug)]
+pub struct Summary {
+    pub format: Format,
+    /// Events imported in the database
+    pub imported: usize,
+    /// Events already in the database or duplicated in the file
+    pub duplicated: usize,
+    /// Lines or items that are not events or with invalid ID or signature
+    pub invalid: usize,
+    /// Keys found in the export
+    pub keys: Vec<Keys>,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Format: {}", self.format)?;
+        writeln!(f, "Imported: {}", self.imported)?;
+        writeln!(f, "Skipped (duplicated): {}", self.duplicated)?;
+        write!(f, "Skipped (invalid): {}", self.invalid)?;
+        for keys in self.keys.iter() {
+            let public_key: String = keys.public_key().to_bech32().map_err(|_| fmt::Error)?;
+            write!(f, "\nKey: {public_key}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Detect the format from the first non-whitespace chars
+pub fn detect<R>(reader: &mut R) -> Result<Format>
+where
+    R: Read + Seek,
+{
+    let mut buf = [0u8; 4096];
+    let len: usize = reader.read(&mut buf)?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let head = String::from_utf8_lossy(&buf[..len]);
+    let head: &str = head.trim_start();
+
+    if head.starts_with('{') {
+        // A JSONL file starts with a complete object on the first line
+        let first_line: &str = head.lines().next().unwrap_or_default();
+        return match serde_json::from_str::<Value>(first_line) {
+            Ok(value) if !is_key_export(&value) => Ok(Format::Jsonl),
+            _ => Ok(Format::Nos2x),
+        };
+    }
+
+    if head.starts_with('[') {
+        // `[{...},` is an array of events, `["EVENT", ...]` a JSONL of messages
+        let after: &str = head[1..].trim_start();
+        if after.starts_with('{') || after.starts_with(']') {
+            return Ok(Format::JsonArray);
+        }
+    }
+
+    Ok(Format::Jsonl)
+}
+
+/// Import a file in the database
+pub async fn import<P>(client: &Client, path: P) -> Result<Summary>
+where
+    P: AsRef<Path>,
+{
+    let mut file = File::open(path)?;
+    let format: Format = detect(&mut file)?;
+
+    let mut invalid: usize = 0;
+    let mut total: usize = 0;
+    let mut events: BTreeSet<Event> = BTreeSet::new();
+    let mut keys: Vec<Keys> = Vec::new();
+
+    match format {
+        Format::Jsonl => {
+            let reader = BufReader::new(file);
+            for line in reader.lines() {
+                let line: String = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                total += 1;
+                match parse_value(serde_json::from_str(&line).unwrap_or(Value::Null)) {
+                    Some(event) => {
+                        events.insert(event);
+                    }
+                    None => invalid += 1,
+                }
+            }
+        }
+        Format::JsonArray => {
+            let values: Vec<Value> = serde_json::from_reader(BufReader::new(file))?;
+            for value in values.into_iter() {
+                total += 1;
+                match parse_value(value) {
+                    Some(event) => {
+                        events.insert(event);
+                    }
+                    None => invalid += 1,
+                }
+            }
+        }
+        Format::Nos2x => {
+            let value: Value = serde_json::from_reader(BufReader::new(file))?;
+            match key_export_secret_key(&value) {
+                Some(secret_key) => keys.push(Keys::parse(secret_key)?),
+                None => invalid += 1,
+            }
+        }
+    }
+
+    // Skip the events already in the database
+    let database = client.database();
+    let mut new_events: BTreeSet<Event> = BTreeSet::new();
+    for event in events.into_iter() {
+        if !database.has_event_already_been_saved(&event.id()).await? {
+            new_events.insert(event);
+        }
+    }
+
+    let imported: usize = new_events.len();
+    if !new_events.is_empty() {
+        database.bulk_import(new_events).await?;
+    }
+
+    Ok(Summary {
+        format,
+        imported,
+        duplicated: total - invalid - imported,
+        invalid,
+        keys,
+    })
+}
+
+fn key_export_secret_key(value: &Value) -> Option<&str> {
+    value
+        .get("private_key")
+        .or_else(|| value.get("privateKey"))
+        .and_then(|v| v.as_str())
+}
+
+#[inline]
+fn is_key_export(value: &Value) -> bool {
+    key_export_secret_key(value).is_some()
+}
+
+/// Parse an event or an `EVENT` message (client or relay), checking ID and signature
+fn parse_value(value: Value) -> Option<Event> {
+    let value: Value = match value {
+        Value::Object(..) => value,
+        Value::Array(mut array) => {
+            if array.first().and_then(|v| v.as_str()) != Some("EVENT") {
+                return None;
+            }
+            array.pop()?
+        }
+        _ => return None,
+    };
+
+    let event: Event = serde_json::from_value(value).ok()?;
+    event.verify().ok()?;
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let mut jsonl = Cursor::new("{\"id\":\"a\"}\n{\"id\":\"b\"}\n");
+        assert_eq!(detect(&mut jsonl).unwrap(), Format::Jsonl);
+
+        let mut messages = Cursor::new("[\"EVENT\",\"sub\",{\"id\":\"a\"}]\n");
+        assert_eq!(detect(&mut messages).unwrap(), Format::Jsonl);
+
+        let mut array = Cursor::new("[\n  {\"id\": \"a\"},\n  {\"id\": \"b\"}\n]");
+        assert_eq!(detect(&mut array).unwrap(), Format::JsonArray);
+
+        let mut nos2x = Cursor::new("{\n  \"private_key\": \"abc\"\n}");
+        assert_eq!(detect(&mut nos2x).unwrap(), Format::Nos2x);
+
+        let mut nos2x = Cursor::new("{\"private_key\":\"abc\",\"relays\":{}}");
+        assert_eq!(detect(&mut nos2x).unwrap(), Format::Nos2x);
+    }
+
+    #[test]
+    fn test_parse_value() {
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap();
+
+        let value: Value = serde_json::from_str(&event.as_json()).unwrap();
+        assert_eq!(parse_value(value.clone()), Some(event.clone()));
+
+        let message = Value::Array(vec![Value::from("EVENT"), Value::from("sub"), value]);
+        assert_eq!(parse_value(message), Some(event));
+
+        assert_eq!(parse_value(Value::from("hello")), None);
+    }
+}
@@ -16,6 +16,7 @@ use tokio::time::Instant;
 use tracing_subscriber::fmt::format::FmtSpan;
 
 mod cli;
+mod import;
 mod util;
 
 use self::cli::{io, parser, Cli, CliCommand, Command};
@@ -220,6 +221,21 @@ async fn handle_command(command: Command, client: &Client) -> Result<()> {
 
                 Ok(())
             }
+            DatabaseCommand::Import { path } => {
+                if path.exists() && path.is_file() {
+                    let now = Instant::now();
+                    let summary = import::import(client, path).await?;
+                    println!("{summary}");
+                    if !summary.keys.is_empty() {
+                        println!("Keys aren't stored: add them to your signer");
+                    }
+                    println!("Imported in {:.6} secs", now.elapsed().as_secs_f64());
+                } else {
+                    println!("File not found")
+                }
+
+                Ok(())
+            }
             DatabaseCommand::Stats => {
                 println!("TODO");
                 Ok(())
@@ -0,0 +1,167 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event delivery records
+
+use std::collections::HashMap;
+
+use nostr::serde_json::{self, json, Value};
+use nostr::{EventId, Timestamp, Url};
+
+const VERSION: u64 = 1;
+
+/// Delivery of an event to a relay (`OK` message)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryRecord {
+    /// Whether the relay accepted the event
+    pub accepted: bool,
+    /// Message of the relay
+    pub message: String,
+    /// When the `OK` message was received
+    pub timestamp: Timestamp,
+}
+
+/// Delivery status of an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryStatus {
+    /// Event ID
+    pub event_id: EventId,
+    /// Last delivery record, per relay
+    pub relays: HashMap<Url, DeliveryRecord>,
+}
+
+impl DeliveryStatus {
+    /// New empty delivery status
+    pub fn new(event_id: EventId) -> Self {
+        Self {
+            event_id,
+            relays: HashMap::new(),
+        }
+    }
+
+    /// Update the delivery record of a relay
+    ///
+    /// Once accepted, a later rejection (i.e. a failed resend) doesn't overwrite the record.
+    pub fn update(&mut self, relay_url: Url, record: DeliveryRecord) {
+        match self.relays.get(&relay_url) {
+            Some(current) if current.accepted && !record.accepted => (),
+            _ => {
+                self.relays.insert(relay_url, record);
+            }
+        }
+    }
+
+    /// Relays that accepted the event
+    pub fn accepted_by(&self) -> impl Iterator<Item = &Url> {
+        self.relays
+            .iter()
+            .filter(|(_, r)| r.accepted)
+            .map(|(url, _)| url)
+    }
+
+    /// Relays that rejected the event
+    pub fn rejected_by(&self) -> impl Iterator<Item = &Url> {
+        self.relays
+            .iter()
+            .filter(|(_, r)| !r.accepted)
+            .map(|(url, _)| url)
+    }
+
+    /// Check if at least one relay accepted the event
+    pub fn is_delivered(&self) -> bool {
+        self.relays.values().any(|r| r.accepted)
+    }
+
+    pub(crate) fn key(event_id: &EventId) -> String {
+        format!("nostr-database/delivery/{event_id}")
+    }
+
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let relays: Vec<Value> = self
+            .relays
+            .iter()
+            .map(|(url, r)| {
+                json!({
+                    "url": url.to_string(),
+                    "accepted": r.accepted,
+                    "message": r.message,
+                    "timestamp": r.timestamp.as_u64(),
+                })
+            })
+            .collect();
+        json!({ "version": VERSION, "relays": relays })
+            .to_string()
+            .into_bytes()
+    }
+
+    pub(crate) fn from_bytes(event_id: EventId, bytes: &[u8]) -> Option<Self> {
+        let value: Value = serde_json::from_slice(bytes).ok()?;
+        if value.get("version")?.as_u64()? != VERSION {
+            return None;
+        }
+
+        let mut status = Self::new(event_id);
+        for relay in value.get("relays")?.as_array()?.iter() {
+            let url: Url = Url::parse(relay.get("url")?.as_str()?).ok()?;
+            status.relays.insert(
+                url,
+                DeliveryRecord {
+                    accepted: relay.get("accepted")?.as_bool()?,
+                    message: relay.get("message")?.as_str()?.to_string(),
+                    timestamp: Timestamp::from(relay.get("timestamp")?.as_u64()?),
+                },
+            );
+        }
+        Some(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delivery_status() {
+        let relay_a = Url::parse("wss://relay.damus.io").unwrap();
+        let relay_b = Url::parse("wss://nos.lol").unwrap();
+
+        let mut status = DeliveryStatus::new(EventId::all_zeros());
+        status.update(
+            relay_a.clone(),
+            DeliveryRecord {
+                accepted: true,
+                message: String::new(),
+                timestamp: Timestamp::from(1),
+            },
+        );
+        status.update(
+            relay_b.clone(),
+            DeliveryRecord {
+                accepted: false,
+                message: String::from("blocked: not allowed"),
+                timestamp: Timestamp::from(2),
+            },
+        );
+
+        // A later rejection doesn't overwrite the acceptance
+        status.update(
+            relay_a.clone(),
+            DeliveryRecord {
+                accepted: false,
+                message: String::from("error: could not save the event"),
+                timestamp: Timestamp::from(3),
+            },
+        );
+
+        assert!(status.is_delivered());
+        assert_eq!(status.accepted_by().collect::<Vec<_>>(), vec![&relay_a]);
+        assert_eq!(status.rejected_by().collect::<Vec<_>>(), vec![&relay_b]);
+
+        let bytes = status.to_bytes();
+        assert_eq!(
+            DeliveryStatus::from_bytes(status.event_id, &bytes),
+            Some(status)
+        );
+    }
+}
@@ -0,0 +1,435 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Encrypted-at-rest database wrapper
+//!
+//! Encrypt the content of sensitive events (i.e. DMs and gift wraps) with `XChaCha20-Poly1305`
+//! before saving them into the wrapped database, and transparently decrypt it on query.
+//!
+//! Only the content is encrypted: tags, kind, author and timestamps are still stored in clear,
+//! so the events can be indexed and queried as usual.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+use async_trait::async_trait;
+use base64::engine::{general_purpose, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use nostr::hashes::hmac::{Hmac, HmacEngine};
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::{Hash, HashEngine};
+use nostr::nips::nip01::Coordinate;
+use nostr::{Event, EventId, Filter, Kind, SecretKey, SingleLetterTag, Timestamp, Url};
+use scrypt::Params as ScryptParams;
+use thiserror::Error;
+
+use crate::{Backend, DatabaseError, EventMetadata, NostrDatabase, Order, VerificationStatus};
+
+const PREFIX: &str = "enc1:";
+const NONCE_SIZE: usize = 24;
+const KEY_SIZE: usize = 32;
+const KEY_DERIVATION_CONTEXT: &[u8] = b"nostr-database/encrypted-at-rest";
+const SCRYPT_LOG_N: u8 = 16;
+
+/// Encrypted database error
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    /// ChaCha20Poly1305 error
+    #[error("chacha20poly1305: {0}")]
+    ChaCha20Poly1305(chacha20poly1305::Error),
+    /// Base64 decode error
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    /// Scrypt error
+    #[error("invalid scrypt params: {0}")]
+    Scrypt(String),
+    /// Invalid payload
+    #[error("invalid encrypted payload")]
+    InvalidPayload,
+    /// Invalid UTF-8
+    #[error(transparent)]
+    Utf8(#[from] std::string::FromUtf8Error),
+}
+
+impl From<chacha20poly1305::Error> for EncryptionError {
+    fn from(e: chacha20poly1305::Error) -> Self {
+        Self::ChaCha20Poly1305(e)
+    }
+}
+
+impl From<EncryptionError> for DatabaseError {
+    fn from(e: EncryptionError) -> Self {
+        Self::backend(e)
+    }
+}
+
+/// Encryption key
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; KEY_SIZE]);
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EncryptionKey(<sensitive>)")
+    }
+}
+
+impl EncryptionKey {
+    /// Construct from raw bytes
+    pub fn from_bytes(bytes: [u8; KEY_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    /// Derive key from user's [`SecretKey`]
+    ///
+    /// `HMAC-SHA256(secret_key, "nostr-database/encrypted-at-rest")`
+    pub fn from_secret_key(secret_key: &SecretKey) -> Self {
+        let mut engine: HmacEngine<Sha256Hash> = HmacEngine::new(&secret_key.secret_bytes());
+        engine.input(KEY_DERIVATION_CONTEXT);
+        let hmac: Hmac<Sha256Hash> = Hmac::from_engine(engine);
+        Self(hmac.to_byte_array())
+    }
+
+    /// Derive key from a passphrase with `scrypt`
+    ///
+    /// The `salt` must be random and stored by the app: the same salt is needed to derive again the key.
+    pub fn from_passphrase<S>(passphrase: S, salt: &[u8; 16]) -> Result<Self, EncryptionError>
+    where
+        S: AsRef<str>,
+    {
+        let params: ScryptParams = ScryptParams::new(SCRYPT_LOG_N, 8, 1, KEY_SIZE)
+            .map_err(|e| EncryptionError::Scrypt(e.to_string()))?;
+        let mut key: [u8; KEY_SIZE] = [0u8; KEY_SIZE];
+        scrypt::scrypt(passphrase.as_ref().as_bytes(), salt, &params, &mut key)
+            .map_err(|e| EncryptionError::Scrypt(e.to_string()))?;
+        Ok(Self(key))
+    }
+}
+
+/// Database wrapper that encrypts the content of sensitive events at rest
+///
+/// By default, the content of the following kinds is encrypted:
+/// [`Kind::EncryptedDirectMessage`], [`Kind::Seal`], [`Kind::SealedDirect`] and [`Kind::GiftWrap`].
+pub struct EncryptedDatabase<D> {
+    inner: D,
+    cipher: XChaCha20Poly1305,
+    kinds: HashSet<Kind>,
+}
+
+impl<D> fmt::Debug for EncryptedDatabase<D>
+where
+    D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedDatabase")
+            .field("inner", &self.inner)
+            .field("kinds", &self.kinds)
+            .finish()
+    }
+}
+
+impl<D> EncryptedDatabase<D>
+where
+    D: NostrDatabase,
+{
+    /// Wrap database
+    pub fn new(inner: D, key: EncryptionKey) -> Self {
+        Self {
+            inner,
+            cipher: XChaCha20Poly1305::new(&key.0.into()),
+            kinds: [
+                Kind::EncryptedDirectMessage,
+                Kind::Seal,
+                Kind::SealedDirect,
+                Kind::GiftWrap,
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+
+    /// Set the kinds to encrypt (overwrite the defaults)
+    pub fn kinds<I>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        self.kinds = kinds.into_iter().collect();
+        self
+    }
+
+    /// Get inner database
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    fn encrypt(&self, event: &Event) -> Result<Event, EncryptionError> {
+        let nonce: XNonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext: Vec<u8> = self.cipher.encrypt(&nonce, event.content().as_bytes())?;
+
+        let mut payload: Vec<u8> = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        payload.extend_from_slice(nonce.as_slice());
+        payload.extend(ciphertext);
+
+        let content: String = format!("{PREFIX}{}", general_purpose::STANDARD.encode(payload));
+        Ok(with_content(event, content))
+    }
+
+    fn decrypt(&self, event: Event) -> Result<Event, EncryptionError> {
+        if !self.kinds.contains(&event.kind) {
+            return Ok(event);
+        }
+
+        match event.content().strip_prefix(PREFIX) {
+            Some(payload) => {
+                let payload: Vec<u8> = general_purpose::STANDARD.decode(payload)?;
+                if payload.len() < NONCE_SIZE {
+                    return Err(EncryptionError::InvalidPayload);
+                }
+                let (nonce, ciphertext) = payload.split_at(NONCE_SIZE);
+                let plaintext: Vec<u8> =
+                    self.cipher.decrypt(XNonce::from_slice(nonce), ciphertext)?;
+                Ok(with_content(&event, String::from_utf8(plaintext)?))
+            }
+            // Saved before enabling the encryption
+            None => Ok(event),
+        }
+    }
+
+    fn prepare(&self, event: &Event) -> Result<Option<Event>, EncryptionError> {
+        if self.kinds.contains(&event.kind) {
+            Ok(Some(self.encrypt(event)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Rebuild the event with a different content (id and signature are kept)
+fn with_content(event: &Event, content: String) -> Event {
+    Event::new(
+        event.id,
+        event.pubkey,
+        event.created_at,
+        event.kind,
+        event.tags.clone(),
+        content,
+        event.signature(),
+    )
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<D> NostrDatabase for EncryptedDatabase<D>
+where
+    D: NostrDatabase,
+{
+    type Err = DatabaseError;
+
+    fn backend(&self) -> Backend {
+        self.inner.backend()
+    }
+
+    async fn save_event(&self, event: &Event) -> Result<bool, Self::Err> {
+        match self.prepare(event)? {
+            Some(encrypted) => self.inner.save_event(&encrypted).await.map_err(Into::into),
+            None => self.inner.save_event(event).await.map_err(Into::into),
+        }
+    }
+
+    async fn bulk_import(&self, events: BTreeSet<Event>) -> Result<(), Self::Err> {
+        let mut prepared: BTreeSet<Event> = BTreeSet::new();
+        for event in events.into_iter() {
+            match self.prepare(&event)? {
+                Some(encrypted) => prepared.insert(encrypted),
+                None => prepared.insert(event),
+            };
+        }
+        self.inner.bulk_import(prepared).await.map_err(Into::into)
+    }
+
+    async fn has_event_already_been_saved(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.inner
+            .has_event_already_been_saved(event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn has_event_already_been_seen(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.inner
+            .has_event_already_been_seen(event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn has_event_id_been_deleted(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.inner
+            .has_event_id_been_deleted(event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn has_coordinate_been_deleted(
+        &self,
+        coordinate: &Coordinate,
+        timestamp: Timestamp,
+    ) -> Result<bool, Self::Err> {
+        self.inner
+            .has_coordinate_been_deleted(coordinate, timestamp)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn event_id_seen(&self, event_id: EventId, relay_url: Url) -> Result<(), Self::Err> {
+        self.inner
+            .event_id_seen(event_id, relay_url)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn event_seen_on_relays(
+        &self,
+        event_id: EventId,
+    ) -> Result<Option<HashSet<Url>>, Self::Err> {
+        self.inner
+            .event_seen_on_relays(event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
+        self.inner
+            .event_metadata(event_id)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        self.inner
+            .set_event_verification(event_id, status)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        self.inner
+            .save_checkpoint(key, value)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.inner.load_checkpoint(key).await.map_err(Into::into)
+    }
+
+    async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
+        let event: Event = self.inner.event_by_id(event_id).await.map_err(Into::into)?;
+        Ok(self.decrypt(event)?)
+    }
+
+    async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
+        self.inner.count(filters).await.map_err(Into::into)
+    }
+
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        self.inner.tag_values(filter, tag).await.map_err(Into::into)
+    }
+
+    async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
+        let events: Vec<Event> = self.inner.query(filters, order).await.map_err(Into::into)?;
+        let mut decrypted: Vec<Event> = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            decrypted.push(self.decrypt(event)?);
+        }
+        Ok(decrypted)
+    }
+
+    async fn event_ids_by_filters(
+        &self,
+        filters: Vec<Filter>,
+        order: Order,
+    ) -> Result<Vec<EventId>, Self::Err> {
+        self.inner
+            .event_ids_by_filters(filters, order)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn negentropy_items(
+        &self,
+        filter: Filter,
+    ) -> Result<Vec<(EventId, Timestamp)>, Self::Err> {
+        self.inner
+            .negentropy_items(filter)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn delete(&self, filter: Filter) -> Result<(), Self::Err> {
+        self.inner.delete(filter).await.map_err(Into::into)
+    }
+
+    async fn wipe(&self) -> Result<(), Self::Err> {
+        self.inner.wipe().await.map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Tag};
+
+    use super::*;
+    use crate::{MemoryDatabase, MemoryDatabaseOptions};
+
+    #[tokio::test]
+    async fn test_encrypted_database() {
+        let keys = Keys::generate();
+        let inner = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        });
+        let key = EncryptionKey::from_secret_key(keys.secret_key().unwrap());
+        let database = EncryptedDatabase::new(inner, key);
+
+        let dm = EventBuilder::new(
+            Kind::EncryptedDirectMessage,
+            "Sensitive",
+            [Tag::public_key(keys.public_key())],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let note = EventBuilder::text_note("Public", [])
+            .to_event(&keys)
+            .unwrap();
+        database.save_event(&dm).await.unwrap();
+        database.save_event(&note).await.unwrap();
+
+        // Stored encrypted
+        let stored: Event = database.inner().event_by_id(dm.id).await.unwrap();
+        assert!(stored.content().starts_with(PREFIX));
+        assert!(!stored.content().contains("Sensitive"));
+        let stored: Event = database.inner().event_by_id(note.id).await.unwrap();
+        assert_eq!(stored, note);
+
+        // Transparent decryption
+        let event: Event = database.event_by_id(dm.id).await.unwrap();
+        assert_eq!(event.content(), "Sensitive");
+        event.verify().unwrap();
+        let events = database
+            .query(
+                vec![Filter::new().kind(Kind::EncryptedDirectMessage)],
+                Order::Desc,
+            )
+            .await
+            .unwrap();
+        assert_eq!(events, vec![dm]);
+    }
+}
@@ -5,9 +5,10 @@
 //! Nostr Database Indexes
 
 use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
 use std::collections::{BTreeSet, HashMap, HashSet};
-use std::iter;
 use std::sync::Arc;
+use std::{iter, mem};
 
 use nostr::event::id;
 use nostr::nips::nip01::Coordinate;
@@ -17,9 +18,10 @@ use nostr::{
 use thiserror::Error;
 use tokio::sync::RwLock;
 
-use crate::tag_indexes::{hash, TagIndexValues, TagIndexes, TAG_INDEX_VALUE_SIZE};
+use crate::tag_indexes::{self, hash, TagIndexValues, TagIndexes, TAG_INDEX_VALUE_SIZE};
 #[cfg(feature = "flatbuf")]
 use crate::temp::TempEvent;
+use crate::views::{Trigger, TriggerEvent, Views};
 use crate::Order;
 
 /// Public Key Prefix Size
@@ -33,6 +35,9 @@ enum Error {
 
 type ArcEventIndex = Arc<EventIndex>;
 
+/// Kind, author and hashed `d` tag (empty for replaceable events)
+type ReplaceableKey = (Kind, PublicKeyPrefix, [u8; TAG_INDEX_VALUE_SIZE]);
+
 /// Event Index
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct EventIndex {
@@ -46,6 +51,8 @@ struct EventIndex {
     kind: Kind,
     /// Tag indexes
     tags: TagIndexes,
+    /// Has a NIP36 content warning
+    sensitive: bool,
 }
 
 impl PartialOrd for EventIndex {
@@ -72,6 +79,7 @@ impl From<&Event> for EventIndex {
             pubkey: PublicKeyPrefix::from(e.author_ref()),
             kind: e.kind(),
             tags: TagIndexes::from(e.iter_tags()),
+            sensitive: e.is_sensitive(),
         }
     }
 }
@@ -109,6 +117,7 @@ struct FilterIndex {
     since: Option<Timestamp>,
     until: Option<Timestamp>,
     generic_tags: HashMap<SingleLetterTag, HashSet<GenericTagValue>>,
+    sensitive: Option<bool>,
 }
 
 impl FilterIndex {
@@ -150,11 +159,13 @@ impl FilterIndex {
             && self.kind_match(&event.kind)
             && self.authors_match(event)
             && self.tag_match(event)
+            && self.sensitive.map_or(true, |s| event.sensitive == s)
     }
 }
 
 impl From<Filter> for FilterIndex {
     fn from(value: Filter) -> Self {
+        let sensitive: Option<bool> = value.get_sensitive();
         Self {
             ids: value.ids.unwrap_or_default(),
             authors: value
@@ -167,6 +178,7 @@ impl From<Filter> for FilterIndex {
             since: value.since,
             until: value.until,
             generic_tags: value.generic_tags,
+            sensitive,
         }
     }
 }
@@ -271,6 +283,44 @@ impl<'a> EventOrTempEvent<'a> {
         }
     }
 
+    fn is_sensitive(&self) -> bool {
+        match self {
+            Self::Event(e) => e.is_sensitive(),
+            Self::EventOwned(e) => e.is_sensitive(),
+            #[cfg(feature = "flatbuf")]
+            Self::Temp(r) => r.sensitive,
+        }
+    }
+
+    fn author(&self) -> Option<PublicKey> {
+        match self {
+            Self::Event(e) => Some(e.author()),
+            Self::EventOwned(e) => Some(e.author()),
+            #[cfg(feature = "flatbuf")]
+            Self::Temp(r) => PublicKey::from_slice(&r.pubkey).ok(),
+        }
+    }
+
+    fn tag_values(&self) -> Vec<([u8; TAG_INDEX_VALUE_SIZE], String)> {
+        match self {
+            Self::Event(e) => tag_indexes::tag_values(e.iter_tags()),
+            Self::EventOwned(e) => tag_indexes::tag_values(e.iter_tags()),
+            #[cfg(feature = "flatbuf")]
+            Self::Temp(r) => r.tag_values.clone(),
+        }
+    }
+
+    fn trigger_event(&self, id: EventId) -> Option<TriggerEvent> {
+        Some(TriggerEvent {
+            id,
+            author: self.author()?,
+            kind: self.kind(),
+            created_at: self.created_at(),
+            event_ids: self.event_ids().copied().collect(),
+            coordinates: self.coordinates().cloned().collect(),
+        })
+    }
+
     fn is_expired(&self, now: &Timestamp) -> bool {
         match self {
             Self::Event(e) => e.is_expired_at(now),
@@ -326,7 +376,7 @@ impl QueryByParamReplaceable {
 enum QueryPattern {
     KindAuthor(QueryByKindAndAuthorParams),
     ParamReplaceable(QueryByParamReplaceable),
-    Generic(Filter),
+    Generic(Box<Filter>),
 }
 
 impl From<Filter> for QueryPattern {
@@ -343,6 +393,11 @@ impl From<Filter> for QueryPattern {
             .unwrap_or_default();
         let ids_len: usize = filter.ids.as_ref().map(|set| set.len()).unwrap_or_default();
         let generic_tags_len: usize = filter.generic_tags.len();
+        // The fast paths don't check the content warning
+        if filter.get_sensitive().is_some() {
+            return Self::Generic(Box::new(filter));
+        }
+
         let identifier = filter
             .generic_tags
             .get(&SingleLetterTag::lowercase(Alphabet::D))
@@ -376,11 +431,34 @@ impl From<Filter> for QueryPattern {
                     until: filter.until,
                 })
             }
-            _ => Self::Generic(filter),
+            _ => Self::Generic(Box::new(filter)),
         }
     }
 }
 
+fn replaceable_key(coordinate: &Coordinate) -> ReplaceableKey {
+    (
+        coordinate.kind,
+        PublicKeyPrefix::from(coordinate.public_key),
+        hash(&coordinate.identifier),
+    )
+}
+
+/// Original value of a hashed tag index
+#[derive(Debug, Clone)]
+struct TagValue {
+    value: String,
+    /// Number of indexed events using the value
+    events: usize,
+}
+
+/// Distinct hashed values of the tag indexes
+fn tag_hashes(tags: &TagIndexes) -> HashSet<[u8; TAG_INDEX_VALUE_SIZE]> {
+    tags.values()
+        .flat_map(|values| values.iter().copied())
+        .collect()
+}
+
 /// Event Index Result
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct EventIndexResult {
@@ -404,7 +482,21 @@ struct InternalDatabaseIndexes {
     kind_author_tags_index:
         HashMap<(Kind, PublicKeyPrefix, [u8; TAG_INDEX_VALUE_SIZE]), ArcEventIndex>,
     deleted_ids: HashSet<EventId>,
-    deleted_coordinates: HashMap<Coordinate, Timestamp>,
+    /// Deletions of events not received yet
+    pending_deletions: HashMap<(EventId, PublicKeyPrefix), Timestamp>,
+    deleted_coordinates: HashMap<ReplaceableKey, Timestamp>,
+    /// Original values of the hashed tag indexes
+    tag_values: HashMap<[u8; TAG_INDEX_VALUE_SIZE], TagValue>,
+    views: Views,
+}
+
+/// Check if a replaceable event replaces the `existing` one
+///
+/// The newest event wins. On equal timestamps, the lowest ID wins (NIP01), so the result
+/// doesn't depend on the order in which the events are received.
+fn replaces(existing: &EventIndex, created_at: Timestamp, event_id: EventId) -> bool {
+    created_at > existing.created_at
+        || (created_at == existing.created_at && event_id < existing.event_id)
 }
 
 impl InternalDatabaseIndexes {
@@ -473,29 +565,60 @@ impl InternalDatabaseIndexes {
         let created_at: Timestamp = event.created_at();
         let kind: Kind = event.kind();
 
+        // Check if was deleted before being received
+        let deleted_at: Option<Timestamp> = if kind.is_replaceable() {
+            self.deleted_coordinates
+                .get(&(kind, pubkey_prefix, hash("")))
+                .copied()
+        } else if kind.is_parameterized_replaceable() {
+            event
+                .identifier()
+                .and_then(|identifier| {
+                    self.deleted_coordinates
+                        .get(&(kind, pubkey_prefix, identifier))
+                })
+                .copied()
+        } else {
+            None
+        };
+        let pending_deleted_at: Option<Timestamp> =
+            self.pending_deletions.remove(&(event_id, pubkey_prefix));
+        if deleted_at
+            .into_iter()
+            .chain(pending_deleted_at)
+            .any(|t| created_at <= t)
+        {
+            self.deleted_ids.insert(event_id);
+            let mut to_discard: HashSet<EventId> = HashSet::with_capacity(1);
+            to_discard.insert(event_id);
+            return Ok(EventIndexResult {
+                to_store: false,
+                to_discard,
+            });
+        }
+
         let mut should_insert: bool = true;
 
         if kind.is_replaceable() {
             let params: QueryByKindAndAuthorParams =
                 QueryByKindAndAuthorParams::new(kind, pubkey_prefix);
             for ev in self.internal_query_by_kind_and_author(params) {
-                if ev.created_at > created_at || ev.event_id == event_id {
-                    should_insert = false;
-                } else {
+                if replaces(ev, created_at, event_id) {
                     to_discard.insert(ev.event_id);
+                } else {
+                    should_insert = false;
                 }
             }
         } else if kind.is_parameterized_replaceable() {
             match event.identifier() {
                 Some(identifier) => {
-                    // TODO: check if coordinate was deleted
                     let params: QueryByParamReplaceable =
                         QueryByParamReplaceable::new(kind, pubkey_prefix, identifier);
                     if let Some(ev) = self.internal_query_param_replaceable(params) {
-                        if ev.created_at > created_at || ev.event_id == event_id {
-                            should_insert = false;
-                        } else {
+                        if replaces(ev, created_at, event_id) {
                             to_discard.insert(ev.event_id);
+                        } else {
+                            should_insert = false;
                         }
                     }
                 }
@@ -504,9 +627,21 @@ impl InternalDatabaseIndexes {
         } else if kind == Kind::EventDeletion {
             // Check `e` tags
             for id in event.event_ids() {
-                if let Some(ev) = self.ids_index.get(id) {
-                    if ev.pubkey == pubkey_prefix && ev.created_at <= created_at {
-                        to_discard.insert(ev.event_id);
+                match self.ids_index.get(id) {
+                    Some(ev) => {
+                        if ev.pubkey == pubkey_prefix && ev.created_at <= created_at {
+                            to_discard.insert(ev.event_id);
+                        }
+                    }
+                    None => {
+                        // Not received yet: check it when received
+                        if !self.deleted_ids.contains(id) {
+                            let deleted_at = self
+                                .pending_deletions
+                                .entry((*id, pubkey_prefix))
+                                .or_insert(created_at);
+                            *deleted_at = (*deleted_at).max(created_at);
+                        }
                     }
                 }
             }
@@ -515,13 +650,24 @@ impl InternalDatabaseIndexes {
             for coordinate in event.coordinates() {
                 let coordinate_pubkey_prefix: PublicKeyPrefix =
                     PublicKeyPrefix::from(coordinate.public_key);
-                if coordinate_pubkey_prefix == pubkey_prefix {
-                    // Save deleted coordinate at certain timestamp
-                    self.deleted_coordinates
-                        .insert(coordinate.clone(), created_at);
-
-                    let filter: Filter = coordinate.into();
-                    let filter: Filter = filter.until(created_at);
+                if coordinate_pubkey_prefix == pubkey_prefix
+                    && (coordinate.kind.is_replaceable()
+                        || coordinate.kind.is_parameterized_replaceable())
+                {
+                    // Save deleted coordinate at certain timestamp (keep the most recent deletion)
+                    let deleted_at = self
+                        .deleted_coordinates
+                        .entry(replaceable_key(coordinate))
+                        .or_insert(created_at);
+                    *deleted_at = (*deleted_at).max(created_at);
+
+                    let mut filter: Filter = Filter::new()
+                        .kind(coordinate.kind)
+                        .author(coordinate.public_key)
+                        .until(created_at);
+                    if coordinate.kind.is_parameterized_replaceable() {
+                        filter = filter.identifier(coordinate.identifier.clone());
+                    }
                     // Not check if ev.pubkey match the pubkey_prefix because assume that query
                     // returned only the events owned by pubkey_prefix
                     to_discard.extend(self.internal_generic_query(filter).map(|e| e.event_id));
@@ -534,14 +680,25 @@ impl InternalDatabaseIndexes {
 
         // Insert event
         if should_insert {
+            // Run triggers
+            if !self.views.is_empty() {
+                if let Some(trigger_event) = event.trigger_event(event_id) {
+                    self.views.insert(&trigger_event);
+                }
+            }
+
+            let tag_values: Vec<([u8; TAG_INDEX_VALUE_SIZE], String)> = event.tag_values();
             let e: ArcEventIndex = Arc::new(EventIndex {
                 created_at,
                 event_id,
                 pubkey: pubkey_prefix,
                 kind,
+                sensitive: event.is_sensitive(),
                 tags: event.tags(),
             });
 
+            self.reference_tag_values(&e.tags, tag_values);
+
             self.index.insert(e.clone());
             self.ids_index.insert(event_id, e.clone());
 
@@ -586,12 +743,49 @@ impl InternalDatabaseIndexes {
                     if let Some(set) = self.kind_author_index.get_mut(&(ev.kind, ev.pubkey)) {
                         set.remove(&ev);
                     }
+
+                    self.release_tag_values(&ev.tags);
                 }
+                self.views.remove(id);
                 self.deleted_ids.insert(*id);
             }
         }
     }
 
+    /// Reference the original values of the tag indexes of an indexed event
+    fn reference_tag_values(
+        &mut self,
+        tags: &TagIndexes,
+        values: Vec<([u8; TAG_INDEX_VALUE_SIZE], String)>,
+    ) {
+        let mut values: HashMap<[u8; TAG_INDEX_VALUE_SIZE], String> = values.into_iter().collect();
+        for hash in tag_hashes(tags).into_iter() {
+            match self.tag_values.entry(hash) {
+                Entry::Occupied(mut entry) => entry.get_mut().events += 1,
+                Entry::Vacant(entry) => {
+                    if let Some(value) = values.remove(&hash) {
+                        entry.insert(TagValue { value, events: 1 });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Release the original values of the tag indexes of a removed event
+    ///
+    /// Values not used anymore by any indexed event are dropped.
+    fn release_tag_values(&mut self, tags: &TagIndexes) {
+        for hash in tag_hashes(tags).into_iter() {
+            if let Entry::Occupied(mut entry) = self.tag_values.entry(hash) {
+                let tag_value: &mut TagValue = entry.get_mut();
+                tag_value.events = tag_value.events.saturating_sub(1);
+                if tag_value.events == 0 {
+                    entry.remove();
+                }
+            }
+        }
+    }
+
     /// Index [`Event`]
     ///
     /// **This method assume that [`Event`] was already verified**
@@ -719,7 +913,7 @@ impl InternalDatabaseIndexes {
                         None => Box::new(iter::empty()),
                     }
                 }
-                QueryPattern::Generic(filter) => Box::new(self.internal_generic_query(filter)),
+                QueryPattern::Generic(filter) => Box::new(self.internal_generic_query(*filter)),
             };
 
             if let Some(limit) = limit {
@@ -777,6 +971,29 @@ impl InternalDatabaseIndexes {
         }
     }
 
+    /// Distinct values of `tag` across the events matching the filter, with the number of events using each
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub fn tag_values(&self, filter: Filter, tag: SingleLetterTag) -> HashMap<String, usize> {
+        let mut counts: HashMap<[u8; TAG_INDEX_VALUE_SIZE], usize> = HashMap::new();
+        let mut count = |ev: &ArcEventIndex| {
+            if let Some(values) = ev.tags.get(&tag) {
+                for value in values.iter() {
+                    *counts.entry(*value).or_default() += 1;
+                }
+            }
+        };
+
+        match self.internal_query([filter]) {
+            InternalQueryResult::All => self.index.iter().for_each(&mut count),
+            InternalQueryResult::Set(set) => set.into_iter().for_each(&mut count),
+        }
+
+        counts
+            .into_iter()
+            .filter_map(|(hash, count)| Some((self.tag_values.get(&hash)?.value.clone(), count)))
+            .collect()
+    }
+
     /// Check if an event with [`EventId`] has been deleted
     pub fn has_event_id_been_deleted(&self, event_id: &EventId) -> bool {
         self.deleted_ids.contains(event_id)
@@ -788,7 +1005,11 @@ impl InternalDatabaseIndexes {
         coordinate: &Coordinate,
         timestamp: Timestamp,
     ) -> bool {
-        if let Some(t) = self.deleted_coordinates.get(coordinate).copied() {
+        if let Some(t) = self
+            .deleted_coordinates
+            .get(&replaceable_key(coordinate))
+            .copied()
+        {
             t >= timestamp
         } else {
             false
@@ -809,9 +1030,41 @@ impl InternalDatabaseIndexes {
         }
     }
 
-    /// Clear indexes
+    /// Clear indexes (the triggers are kept)
     pub fn clear(&mut self) {
-        *self = Self::default();
+        let mut views: Views = mem::take(&mut self.views);
+        views.reset();
+        *self = Self {
+            views,
+            ..Self::default()
+        };
+    }
+
+    pub fn register_trigger(&mut self, trigger: Trigger) {
+        self.views.register(trigger);
+    }
+
+    pub fn unregister_trigger(&mut self, name: &str) {
+        self.views.unregister(name);
+    }
+
+    /// Recompute the views from scratch
+    ///
+    /// Only the events still indexed are counted.
+    pub fn rebuild_views<I>(&mut self, events: I)
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        self.views.reset();
+        for event in events.into_iter() {
+            let event_id: EventId = event.id();
+            if self.ids_index.contains_key(&event_id) {
+                if let Some(trigger_event) = EventOrTempEvent::from(&event).trigger_event(event_id)
+                {
+                    self.views.insert(&trigger_event);
+                }
+            }
+        }
     }
 }
 
@@ -881,6 +1134,15 @@ impl DatabaseIndexes {
         inner.count(filters)
     }
 
+    /// Get the distinct values of `tag` across the events matching the filter, with the number of events using each
+    ///
+    /// Computed from the tag indexes, without loading the events.
+    #[tracing::instrument(skip_all, level = "trace")]
+    pub async fn tag_values(&self, filter: Filter, tag: SingleLetterTag) -> HashMap<String, usize> {
+        let inner = self.inner.read().await;
+        inner.tag_values(filter, tag)
+    }
+
     /// Get negentropy items
     #[tracing::instrument(skip_all, level = "trace")]
     pub async fn negentropy_items(&self, filter: Filter) -> Vec<(EventId, Timestamp)> {
@@ -913,10 +1175,52 @@ impl DatabaseIndexes {
     }
 
     /// Clear indexes
+    ///
+    /// The registered triggers are kept, with empty views.
     pub async fn clear(&self) {
         let mut inner = self.inner.write().await;
         inner.clear();
     }
+
+    /// Register a [`Trigger`], replacing the one with the same name
+    ///
+    /// The view is maintained on every index mutation. Already indexed events are counted only
+    /// after [`DatabaseIndexes::rebuild_views`].
+    pub async fn register_trigger(&self, trigger: Trigger) {
+        let mut inner = self.inner.write().await;
+        inner.register_trigger(trigger);
+    }
+
+    /// Remove a trigger and its view
+    pub async fn unregister_trigger(&self, name: &str) {
+        let mut inner = self.inner.write().await;
+        inner.unregister_trigger(name);
+    }
+
+    /// Recompute all the views from the stored events
+    ///
+    /// Events not indexed (i.e. deleted or replaced) are ignored, so passing all the stored events is safe.
+    pub async fn rebuild_views<I>(&self, events: I)
+    where
+        I: IntoIterator<Item = Event>,
+    {
+        let mut inner = self.inner.write().await;
+        inner.rebuild_views(events);
+    }
+
+    /// Get the count of a group of the view
+    pub async fn view(&self, name: &str, group: &str) -> u64 {
+        let inner = self.inner.read().await;
+        inner.views.get(name, group)
+    }
+
+    /// Get all the counts of the view
+    ///
+    /// Return `None` if no trigger is registered with this name.
+    pub async fn view_all(&self, name: &str) -> Option<HashMap<String, u64>> {
+        let inner = self.inner.read().await;
+        inner.views.all(name)
+    }
 }
 
 #[cfg(test)]
@@ -924,7 +1228,7 @@ mod tests {
     use std::str::FromStr;
 
     use nostr::secp256k1::schnorr::Signature;
-    use nostr::{FromBech32, JsonUtil, Keys, SecretKey, Tag};
+    use nostr::{EventBuilder, FromBech32, JsonUtil, Keys, SecretKey, Tag};
 
     use super::*;
 
@@ -1155,6 +1459,144 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_query_sensitive() {
+        let keys = Keys::generate();
+        let indexes = DatabaseIndexes::new();
+
+        let sensitive = EventBuilder::text_note("Spoiler", [])
+            .content_warning(None::<String>)
+            .to_event(&keys)
+            .unwrap();
+        let event = EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap();
+        indexes.index_event(&sensitive).await;
+        indexes.index_event(&event).await;
+
+        // Kind and author: the fast path must not be used
+        let filter = Filter::new().kind(Kind::TextNote).author(keys.public_key());
+        assert_eq!(
+            indexes
+                .query([filter.clone().sensitive(false)], Order::Desc)
+                .await,
+            vec![event.id()]
+        );
+        assert_eq!(
+            indexes.query([filter.sensitive(true)], Order::Desc).await,
+            vec![sensitive.id()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tag_values() {
+        let keys = Keys::generate();
+        let indexes = DatabaseIndexes::new();
+
+        let t = SingleLetterTag::lowercase(Alphabet::T);
+        for hashtags in [vec!["nostr", "rust"], vec!["nostr"], vec!["bitcoin"]] {
+            let tags: Vec<Tag> = hashtags
+                .into_iter()
+                .map(|t| Tag::Hashtag(t.to_string()))
+                .collect();
+            let event = EventBuilder::text_note("Hello", tags)
+                .to_event(&keys)
+                .unwrap();
+            indexes.index_event(&event).await;
+        }
+        let other = EventBuilder::text_note("Other", [Tag::Hashtag(String::from("nostr"))])
+            .to_event(&Keys::generate())
+            .unwrap();
+        indexes.index_event(&other).await;
+
+        let values = indexes.tag_values(Filter::new(), t).await;
+        assert_eq!(values.len(), 3);
+        assert_eq!(values.get("nostr"), Some(&3));
+        assert_eq!(values.get("rust"), Some(&1));
+        assert_eq!(values.get("bitcoin"), Some(&1));
+
+        let values = indexes
+            .tag_values(Filter::new().author(other.author()), t)
+            .await;
+        assert_eq!(values, HashMap::from([(String::from("nostr"), 1)]));
+
+        // No events with this tag
+        let e = SingleLetterTag::lowercase(Alphabet::E);
+        assert!(indexes.tag_values(Filter::new(), e).await.is_empty());
+
+        // Values are dropped with the last event using them
+        indexes
+            .delete(Filter::new().author(keys.public_key()))
+            .await
+            .unwrap();
+        assert_eq!(indexes.inner.read().await.tag_values.len(), 1);
+        let values = indexes.tag_values(Filter::new(), t).await;
+        assert_eq!(values, HashMap::from([(String::from("nostr"), 1)]));
+
+        // Replaced events
+        let d = SingleLetterTag::lowercase(Alphabet::D);
+        for name in ["first", "second"] {
+            let event = EventBuilder::new(
+                Kind::ParameterizedReplaceable(30000),
+                "",
+                [
+                    Tag::Identifier(String::from("list")),
+                    Tag::Hashtag(name.to_string()),
+                ],
+            )
+            .to_event(&keys)
+            .unwrap();
+            indexes.index_event(&event).await;
+        }
+        let values = indexes.tag_values(Filter::new(), t).await;
+        assert_eq!(values.len(), 2);
+        assert!(values.contains_key("nostr"));
+        assert!(indexes
+            .tag_values(Filter::new(), d)
+            .await
+            .contains_key("list"));
+        assert_eq!(indexes.inner.read().await.tag_values.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_triggers() {
+        let keys = Keys::generate();
+        let indexes = DatabaseIndexes::new();
+
+        let root = EventBuilder::text_note("Root", []).to_event(&keys).unwrap();
+        indexes.index_event(&root).await;
+
+        indexes
+            .register_trigger(Trigger::author_count("posts", Kind::TextNote))
+            .await;
+        indexes
+            .register_trigger(Trigger::reply_count("replies"))
+            .await;
+
+        // Not counted until rebuild
+        let author: String = keys.public_key().to_string();
+        assert_eq!(indexes.view("posts", &author).await, 0);
+        indexes.rebuild_views([root.clone()]).await;
+        assert_eq!(indexes.view("posts", &author).await, 1);
+
+        let reply = EventBuilder::text_note("Reply", [Tag::event(root.id())])
+            .to_event(&keys)
+            .unwrap();
+        indexes.index_event(&reply).await;
+        assert_eq!(indexes.view("posts", &author).await, 2);
+        assert_eq!(indexes.view("replies", &root.id().to_hex()).await, 1);
+
+        // Deletion
+        let deletion = EventBuilder::delete([reply.id()]).to_event(&keys).unwrap();
+        indexes.index_event(&deletion).await;
+        assert_eq!(indexes.view("posts", &author).await, 1);
+        assert_eq!(indexes.view("replies", &root.id().to_hex()).await, 0);
+
+        // Clear keeps the triggers
+        indexes.clear().await;
+        assert_eq!(indexes.view_all("posts").await, Some(HashMap::new()));
+    }
+
     #[test]
     fn test_match_event() {
         let event_id =
@@ -1272,4 +1714,195 @@ mod tests {
         assert!(!filter.match_event(&event));
         assert!(!filter.match_event(&event_with_empty_tags));
     }
+
+    mod semantics {
+        use std::cmp::Reverse;
+
+        use nostr::util::EventIdOrCoordinate;
+        use proptest::prelude::*;
+
+        use super::*;
+
+        const BASE_TIMESTAMP: u64 = 1_700_000_000;
+        const PARAM_KIND: Kind = Kind::ParameterizedReplaceable(32122);
+        const IDENTIFIERS: [&str; 2] = ["a", "b"];
+
+        #[derive(Debug, Clone)]
+        enum Spec {
+            Note,
+            Replaceable,
+            ParamReplaceable(usize),
+        }
+
+        #[derive(Debug, Clone)]
+        enum Deletion {
+            /// Delete events by index
+            Ids(Vec<usize>),
+            /// Delete coordinate (target author, param identifier)
+            Coordinate(bool, Option<usize>),
+        }
+
+        fn spec() -> impl Strategy<Value = (bool, u64, Spec)> {
+            let spec = prop_oneof![
+                Just(Spec::Note),
+                Just(Spec::Replaceable),
+                (0..IDENTIFIERS.len()).prop_map(Spec::ParamReplaceable),
+            ];
+            (any::<bool>(), 0..4u64, spec)
+        }
+
+        fn deletion() -> impl Strategy<Value = (bool, u64, Deletion)> {
+            let deletion = prop_oneof![
+                prop::collection::vec(0..16usize, 1..3).prop_map(Deletion::Ids),
+                (any::<bool>(), prop::option::of(0..IDENTIFIERS.len()))
+                    .prop_map(|(author, ident)| Deletion::Coordinate(author, ident)),
+            ];
+            (any::<bool>(), 0..4u64, deletion)
+        }
+
+        /// Events and a random insertion order
+        fn scenario() -> impl Strategy<Value = (Vec<Event>, usize, Vec<usize>)> {
+            (
+                prop::collection::vec(spec(), 1..10),
+                prop::collection::vec(deletion(), 0..5),
+            )
+                .prop_flat_map(|(specs, deletions)| {
+                    let keys_a = Keys::new(SecretKey::from_bech32(SECRET_KEY_A).unwrap());
+                    let keys_b = Keys::new(SecretKey::from_bech32(SECRET_KEY_B).unwrap());
+                    let keys = |a: bool| if a { &keys_a } else { &keys_b };
+
+                    let mut events: Vec<Event> = specs
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, (author, offset, spec))| {
+                            let (kind, tags) = match spec {
+                                Spec::Note => (Kind::TextNote, Vec::new()),
+                                Spec::Replaceable => (Kind::Metadata, Vec::new()),
+                                Spec::ParamReplaceable(ident) => (
+                                    PARAM_KIND,
+                                    vec![Tag::Identifier(IDENTIFIERS[ident].to_string())],
+                                ),
+                            };
+                            EventBuilder::new(kind, format!("event {i}"), tags)
+                                .custom_created_at(Timestamp::from(BASE_TIMESTAMP + offset))
+                                .to_event(keys(author))
+                                .unwrap()
+                        })
+                        .collect();
+                    let len: usize = events.len();
+
+                    for (i, (author, offset, deletion)) in deletions.into_iter().enumerate() {
+                        let targets: Vec<EventIdOrCoordinate> = match deletion {
+                            Deletion::Ids(indexes) => indexes
+                                .into_iter()
+                                .map(|index| events[index % len].id().into())
+                                .collect(),
+                            Deletion::Coordinate(target, ident) => {
+                                let public_key = keys(target).public_key();
+                                let coordinate = match ident {
+                                    Some(ident) => Coordinate::new(PARAM_KIND, public_key)
+                                        .identifier(IDENTIFIERS[ident]),
+                                    None => Coordinate::new(Kind::Metadata, public_key),
+                                };
+                                vec![coordinate.into()]
+                            }
+                        };
+                        let event = EventBuilder::new(
+                            Kind::EventDeletion,
+                            format!("deletion {i}"),
+                            targets.into_iter().map(Tag::from),
+                        )
+                        .custom_created_at(Timestamp::from(BASE_TIMESTAMP + offset))
+                        .to_event(keys(author))
+                        .unwrap();
+                        events.push(event);
+                    }
+
+                    let order: Vec<usize> = (0..events.len()).collect();
+                    (Just(events), Just(len), Just(order).prop_shuffle())
+                })
+        }
+
+        fn group(event: &Event) -> Option<(Kind, PublicKey, Option<String>)> {
+            if event.kind().is_replaceable() || event.kind().is_parameterized_replaceable() {
+                Some((
+                    event.kind(),
+                    event.author(),
+                    event.identifier().map(|i| i.to_string()),
+                ))
+            } else {
+                None
+            }
+        }
+
+        /// Check if the event is not deleted by any of the deletion events
+        fn is_alive(event: &Event, deletions: &[Event]) -> bool {
+            !deletions.iter().any(|deletion| {
+                if deletion.author() != event.author() || deletion.created_at() < event.created_at()
+                {
+                    return false;
+                }
+                deletion.event_ids().any(|id| *id == event.id())
+                    || deletion.coordinates().any(|c| {
+                        c.public_key == event.author()
+                            && c.kind == event.kind()
+                            && (c.kind.is_replaceable()
+                                || event.identifier() == Some(c.identifier.as_str()))
+                    })
+            })
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn test_replaceable_and_deletion_semantics((events, len, order) in scenario()) {
+                let mut indexes = InternalDatabaseIndexes::default();
+                for index in order.into_iter() {
+                    indexes.index_event(&events[index]);
+                }
+
+                let stored: HashSet<EventId> = indexes
+                    .query([Filter::new()], Order::Desc)
+                    .into_iter()
+                    .collect();
+                let (events, deletions) = events.split_at(len);
+
+                // Deletion events are never deleted
+                for deletion in deletions.iter() {
+                    prop_assert!(stored.contains(&deletion.id()));
+                }
+
+                // Deletions never resurrect and coordinate deletions respect timestamps
+                for event in events.iter() {
+                    if stored.contains(&event.id()) {
+                        prop_assert!(is_alive(event, deletions), "{} resurrected", event.id());
+                    } else if group(event).is_none() {
+                        prop_assert!(!is_alive(event, deletions), "{} missing", event.id());
+                    }
+                }
+
+                // Newest wins (lowest ID on equal timestamps)
+                let mut groups: HashMap<(Kind, PublicKey, Option<String>), Vec<&Event>> =
+                    HashMap::new();
+                for event in events.iter() {
+                    if let Some(group) = group(event) {
+                        groups.entry(group).or_default().push(event);
+                    }
+                }
+                for group in groups.into_values() {
+                    let count: usize = group.iter().filter(|e| stored.contains(&e.id())).count();
+                    prop_assert!(count <= 1);
+
+                    let best: &Event = group
+                        .into_iter()
+                        .min_by_key(|e| (Reverse(e.created_at()), e.id()))
+                        .unwrap();
+                    if is_alive(best, deletions) {
+                        prop_assert!(stored.contains(&best.id()), "{} not stored", best.id());
+                    }
+                }
+            }
+        }
+    }
 }
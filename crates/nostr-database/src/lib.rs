@@ -8,32 +8,52 @@
 #![warn(rustdoc::bare_urls)]
 
 use core::fmt;
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::Arc;
 
 pub use async_trait::async_trait;
 pub use nostr;
 use nostr::nips::nip01::Coordinate;
-use nostr::{Event, EventId, Filter, JsonUtil, Kind, Metadata, PublicKey, Timestamp, Url};
+use nostr::{
+    Event, EventId, Filter, JsonUtil, Kind, Metadata, PublicKey, SingleLetterTag, Timestamp, Url,
+};
 
+pub mod delivery;
+#[cfg(feature = "encryption")]
+pub mod encrypted;
 mod error;
 #[cfg(feature = "flatbuf")]
 pub mod flatbuffers;
 pub mod index;
 pub mod memory;
+pub mod namespace;
 pub mod profile;
+pub mod provenance;
+pub mod quarantine;
+pub mod router;
 mod tag_indexes;
 #[cfg(feature = "flatbuf")]
 mod temp;
+pub mod thread;
+pub mod views;
 
+pub use self::delivery::{DeliveryRecord, DeliveryStatus};
+#[cfg(feature = "encryption")]
+pub use self::encrypted::{EncryptedDatabase, EncryptionError, EncryptionKey};
 pub use self::error::DatabaseError;
 #[cfg(feature = "flatbuf")]
 pub use self::flatbuffers::{FlatBufferBuilder, FlatBufferDecode, FlatBufferEncode};
 pub use self::index::{DatabaseIndexes, EventIndexResult};
 pub use self::memory::{MemoryDatabase, MemoryDatabaseOptions};
+pub use self::namespace::NamespacedDatabase;
 pub use self::profile::Profile;
+pub use self::provenance::{EventMetadata, VerificationStatus};
+pub use self::quarantine::QuarantinedDatabase;
+pub use self::router::RoutedDatabase;
 #[cfg(feature = "flatbuf")]
 pub use self::temp::TempEvent;
+pub use self::thread::{ThreadNode, ThreadView};
+pub use self::views::{Trigger, TriggerEvent};
 
 /// Backend
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -150,9 +170,51 @@ pub trait NostrDatabase: AsyncTraitDeps {
         event_id: EventId,
     ) -> Result<Option<HashSet<Url>>, Self::Err>;
 
+    /// Get provenance metadata of [`EventId`]
+    ///
+    /// The default implementation only knows the relays that have seen the event:
+    /// backends that track timestamps and verification status should override it.
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
+        Ok(self
+            .event_seen_on_relays(event_id)
+            .await?
+            .map(EventMetadata::from_relays))
+    }
+
+    /// Set [`VerificationStatus`] of [`EventId`]
+    ///
+    /// The default implementation doesn't store anything.
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        let _ = (event_id, status);
+        Ok(())
+    }
+
     /// Get [`Event`] by [`EventId`]
     async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err>;
 
+    /// Save (or overwrite) a state checkpoint
+    ///
+    /// Used by long-running clients to persist adaptive state (i.e. relay stats, subscription cursors).
+    /// The value must be written atomically.
+    ///
+    /// The default implementation doesn't store anything.
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        let _ = (key, value);
+        Ok(())
+    }
+
+    /// Load a state checkpoint
+    ///
+    /// The default implementation always returns `None`.
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        let _ = key;
+        Ok(None)
+    }
+
     /// Count number of [`Event`] found by filters
     ///
     /// Use `Filter::new()` or `Filter::default()` to count all events.
@@ -161,6 +223,33 @@ pub trait NostrDatabase: AsyncTraitDeps {
     /// Query store with filters
     async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err>;
 
+    /// Get the distinct values of `tag` across the events matching the filter, with the number of events using each
+    ///
+    /// I.e. all the hashtags used in a community or all the `e` targets.
+    ///
+    /// The default implementation queries the events: backends with [`DatabaseIndexes`] should compute it
+    /// from the tag indexes.
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        let events: Vec<Event> = self.query(vec![filter], Order::Desc).await?;
+        let mut values: HashMap<String, usize> = HashMap::new();
+        for event in events.iter() {
+            let distinct: HashSet<String> = event
+                .iter_tags()
+                .filter(|t| t.single_letter_tag() == Some(tag))
+                .filter_map(|t| t.content())
+                .map(|content| content.to_string())
+                .collect();
+            for value in distinct.into_iter() {
+                *values.entry(value).or_default() += 1;
+            }
+        }
+        Ok(values)
+    }
+
     /// Get event IDs by filters
     async fn event_ids_by_filters(
         &self,
@@ -205,6 +294,107 @@ pub trait NostrDatabaseExt: NostrDatabase {
         }
     }
 
+    /// Query store with filters and return events with their provenance metadata
+    ///
+    /// Events without metadata are returned with [`EventMetadata::default`].
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn query_with_metadata(
+        &self,
+        filters: Vec<Filter>,
+        order: Order,
+    ) -> Result<Vec<(Event, EventMetadata)>, Self::Err> {
+        let events: Vec<Event> = self.query(filters, order).await?;
+        let mut list: Vec<(Event, EventMetadata)> = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            let mut metadata: EventMetadata =
+                self.event_metadata(event.id()).await?.unwrap_or_default();
+            metadata.protected = event.is_protected();
+            list.push((event, metadata));
+        }
+        Ok(list)
+    }
+
+    /// Verify [`Event`]s and bulk import them into database
+    ///
+    /// Every event is verified once: the ones with an invalid ID or signature are discarded and returned.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn verify_and_bulk_import(
+        &self,
+        events: BTreeSet<Event>,
+    ) -> Result<Vec<Event>, Self::Err> {
+        let (valid, invalid): (BTreeSet<Event>, BTreeSet<Event>) =
+            events.into_iter().partition(|e| e.verify().is_ok());
+        if !valid.is_empty() {
+            self.bulk_import(valid).await?;
+        }
+        Ok(invalid.into_iter().collect())
+    }
+
+    /// Assemble the thread of `root_id` from the events stored in the database
+    ///
+    /// Replies (kind `1`) are queried transitively, so replies that don't reference the root are included too.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn thread(&self, root_id: EventId) -> Result<ThreadView, Self::Err> {
+        let filter = Filter::new().id(root_id).limit(1);
+        let root: Option<Event> = self
+            .query(vec![filter], Order::Desc)
+            .await?
+            .into_iter()
+            .next();
+
+        let mut seen: HashSet<EventId> = HashSet::new();
+        seen.insert(root_id);
+        let mut frontier: Vec<EventId> = vec![root_id];
+        let mut replies: Vec<Event> = Vec::new();
+        while !frontier.is_empty() {
+            let filter = Filter::new()
+                .kind(Kind::TextNote)
+                .events(frontier.drain(..));
+            for event in self.query(vec![filter], Order::Asc).await?.into_iter() {
+                if seen.insert(event.id()) {
+                    frontier.push(event.id());
+                    replies.push(event);
+                }
+            }
+        }
+
+        Ok(ThreadView::new(root_id, root, replies))
+    }
+
+    /// Save the delivery record of [`EventId`] to a relay
+    ///
+    /// Stored as a checkpoint (see [`NostrDatabase::save_checkpoint`]).
+    /// The record is read, updated and written back: the writes for the same event must not be concurrent.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn save_delivery(
+        &self,
+        event_id: EventId,
+        relay_url: Url,
+        record: DeliveryRecord,
+    ) -> Result<(), Self::Err> {
+        let mut status: DeliveryStatus = self
+            .delivery_status(event_id)
+            .await?
+            .unwrap_or_else(|| DeliveryStatus::new(event_id));
+        status.update(relay_url, record);
+        self.save_checkpoint(&DeliveryStatus::key(&event_id), status.to_bytes())
+            .await
+    }
+
+    /// Get the delivery status of [`EventId`]
+    ///
+    /// Return `None` if no delivery has been recorded.
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn delivery_status(
+        &self,
+        event_id: EventId,
+    ) -> Result<Option<DeliveryStatus>, Self::Err> {
+        Ok(self
+            .load_checkpoint(&DeliveryStatus::key(&event_id))
+            .await?
+            .and_then(|bytes| DeliveryStatus::from_bytes(event_id, &bytes)))
+    }
+
     /// Get contact list public keys
     #[tracing::instrument(skip_all, level = "trace")]
     async fn contacts_public_keys(
@@ -336,10 +526,33 @@ impl<T: NostrDatabase> NostrDatabase for EraseNostrDatabaseError<T> {
             .map_err(Into::into)
     }
 
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
+        self.0.event_metadata(event_id).await.map_err(Into::into)
+    }
+
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        self.0
+            .set_event_verification(event_id, status)
+            .await
+            .map_err(Into::into)
+    }
+
     async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
         self.0.event_by_id(event_id).await.map_err(Into::into)
     }
 
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        self.0.save_checkpoint(key, value).await.map_err(Into::into)
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.0.load_checkpoint(key).await.map_err(Into::into)
+    }
+
     async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
         self.0.count(filters).await.map_err(Into::into)
     }
@@ -348,6 +561,14 @@ impl<T: NostrDatabase> NostrDatabase for EraseNostrDatabaseError<T> {
         self.0.query(filters, order).await.map_err(Into::into)
     }
 
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        self.0.tag_values(filter, tag).await.map_err(Into::into)
+    }
+
     async fn event_ids_by_filters(
         &self,
         filters: Vec<Filter>,
@@ -408,3 +629,37 @@ impl<T> SyncOutsideWasm for T {}
 /// implemented, while other targets will.
 pub trait AsyncTraitDeps: std::fmt::Debug + SendOutsideWasm + SyncOutsideWasm {}
 impl<T: std::fmt::Debug + SendOutsideWasm + SyncOutsideWasm> AsyncTraitDeps for T {}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, JsonUtil, Keys};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_verify_and_bulk_import() {
+        let opts = MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        };
+        let database = MemoryDatabase::with_opts(opts);
+
+        let keys = Keys::generate();
+        let mut events: BTreeSet<Event> = (0..5)
+            .map(|i| {
+                EventBuilder::text_note(format!("Note {i}"), [])
+                    .to_event(&keys)
+                    .unwrap()
+            })
+            .collect();
+        let event = EventBuilder::text_note("Note 5", [])
+            .to_event(&keys)
+            .unwrap();
+        let tampered = Event::from_json(event.as_json().replace("Note 5", "Tampered")).unwrap();
+        events.insert(tampered.clone());
+
+        let invalid = database.verify_and_bulk_import(events).await.unwrap();
+        assert_eq!(invalid, vec![tampered]);
+        assert_eq!(database.count(vec![Filter::new()]).await.unwrap(), 5);
+    }
+}
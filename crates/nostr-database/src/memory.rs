@@ -4,7 +4,7 @@
 
 //! Memory (RAM) Storage backend for Nostr apps
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::hash::Hash;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
@@ -12,10 +12,13 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use lru::LruCache;
 use nostr::nips::nip01::Coordinate;
-use nostr::{Event, EventId, Filter, Timestamp, Url};
+use nostr::{Event, EventId, Filter, SingleLetterTag, Timestamp, Url};
 use tokio::sync::Mutex;
 
-use crate::{Backend, DatabaseError, DatabaseIndexes, EventIndexResult, NostrDatabase, Order};
+use crate::{
+    Backend, DatabaseError, DatabaseIndexes, EventIndexResult, EventMetadata, NostrDatabase, Order,
+    Trigger, VerificationStatus,
+};
 
 /// Database options
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -48,8 +51,9 @@ impl MemoryDatabaseOptions {
 #[derive(Debug)]
 pub struct MemoryDatabase {
     opts: MemoryDatabaseOptions,
-    seen_event_ids: Arc<Mutex<LruCache<EventId, HashSet<Url>>>>,
+    seen_event_ids: Arc<Mutex<LruCache<EventId, EventMetadata>>>,
     events: Arc<Mutex<LruCache<EventId, Event>>>,
+    checkpoints: Arc<Mutex<HashMap<String, Vec<u8>>>>,
     indexes: DatabaseIndexes,
 }
 
@@ -71,24 +75,48 @@ impl MemoryDatabase {
             opts,
             seen_event_ids: Arc::new(Mutex::new(new_lru_cache(opts.max_events))),
             events: Arc::new(Mutex::new(new_lru_cache(opts.max_events))),
+            checkpoints: Arc::new(Mutex::new(HashMap::new())),
             indexes: DatabaseIndexes::new(),
         }
     }
 
+    /// Register a [`Trigger`] and build its view from the stored events
+    ///
+    /// Check [`DatabaseIndexes::register_trigger`] to learn more.
+    pub async fn register_trigger(&self, trigger: Trigger) {
+        self.indexes.register_trigger(trigger).await;
+        let events: Vec<Event> = {
+            let events = self.events.lock().await;
+            events.iter().map(|(_, e)| e.clone()).collect()
+        };
+        self.indexes.rebuild_views(events).await;
+    }
+
+    /// Get the count of a group of the view
+    pub async fn view(&self, name: &str, group: &str) -> u64 {
+        self.indexes.view(name, group).await
+    }
+
+    /// Get all the counts of the view
+    pub async fn view_all(&self, name: &str) -> Option<HashMap<String, u64>> {
+        self.indexes.view_all(name).await
+    }
+
     fn _event_id_seen(
         &self,
-        seen_event_ids: &mut LruCache<EventId, HashSet<Url>>,
+        seen_event_ids: &mut LruCache<EventId, EventMetadata>,
         event_id: EventId,
         relay_url: Url,
     ) {
+        let now: Timestamp = Timestamp::now();
         match seen_event_ids.get_mut(&event_id) {
-            Some(set) => {
-                set.insert(relay_url);
+            Some(metadata) => {
+                metadata.seen(relay_url, now);
             }
             None => {
-                let mut set = HashSet::with_capacity(1);
-                set.insert(relay_url);
-                seen_event_ids.put(event_id, set);
+                let mut metadata = EventMetadata::new();
+                metadata.seen(relay_url, now);
+                seen_event_ids.put(event_id, metadata);
             }
         }
     }
@@ -186,10 +214,36 @@ impl NostrDatabase for MemoryDatabase {
         &self,
         event_id: EventId,
     ) -> Result<Option<HashSet<Url>>, Self::Err> {
+        let mut seen_event_ids = self.seen_event_ids.lock().await;
+        Ok(seen_event_ids.get(&event_id).map(|m| m.relays.clone()))
+    }
+
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
         let mut seen_event_ids = self.seen_event_ids.lock().await;
         Ok(seen_event_ids.get(&event_id).cloned())
     }
 
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        let mut seen_event_ids = self.seen_event_ids.lock().await;
+        match seen_event_ids.get_mut(&event_id) {
+            Some(metadata) => {
+                metadata.verification = status;
+            }
+            None => {
+                let metadata = EventMetadata {
+                    verification: status,
+                    ..Default::default()
+                };
+                seen_event_ids.put(event_id, metadata);
+            }
+        }
+        Ok(())
+    }
+
     async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
         if self.opts.events {
             let mut events = self.events.lock().await;
@@ -202,11 +256,31 @@ impl NostrDatabase for MemoryDatabase {
         }
     }
 
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        let mut checkpoints = self.checkpoints.lock().await;
+        checkpoints.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        let checkpoints = self.checkpoints.lock().await;
+        Ok(checkpoints.get(key).cloned())
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
         Ok(self.indexes.count(filters).await)
     }
 
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        Ok(self.indexes.tag_values(filter, tag).await)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
         if self.opts.events {
@@ -274,6 +348,8 @@ impl NostrDatabase for MemoryDatabase {
         seen_event_ids.clear();
         let mut events = self.events.lock().await;
         events.clear();
+        let mut checkpoints = self.checkpoints.lock().await;
+        checkpoints.clear();
         Ok(())
     }
 }
@@ -0,0 +1,379 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Database namespaces
+//!
+//! Isolate the events of many accounts (or apps) sharing the same store,
+//! without opening a database instance for each of them.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use nostr::nips::nip01::Coordinate;
+use nostr::serde_json::{self, json, Value};
+use nostr::{Event, EventId, Filter, SingleLetterTag, Timestamp, Url};
+use tokio::sync::RwLock;
+
+use crate::{
+    Backend, DatabaseError, DatabaseIndexes, DynNostrDatabase, EventIndexResult, EventMetadata,
+    IntoNostrDatabase, NostrDatabase, Order, VerificationStatus,
+};
+
+const VERSION: u64 = 1;
+const CHECKPOINT_PREFIX: &str = "nostr-database/namespace";
+
+/// Namespace of a shared database
+///
+/// Each namespace has its own indexes, so queries, counts and negentropy items only
+/// include the events saved through it. The events are saved once into the shared store,
+/// even if saved by many namespaces. Checkpoints (i.e. delivery status) are isolated too.
+///
+/// Replaceable events and deletions are applied per namespace. Since the shared store applies
+/// them too, an event replaced or deleted in the store is skipped by the namespace queries.
+///
+/// [`NostrDatabase::delete`] and [`NostrDatabase::wipe`] only remove the events from the namespace:
+/// the shared store is never wiped, since the events may belong to other namespaces.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+///
+/// use nostr_database::{DynNostrDatabase, IntoNostrDatabase, MemoryDatabase, NamespacedDatabase};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let store: Arc<DynNostrDatabase> = MemoryDatabase::new().into_nostr_database();
+///
+/// let alice = NamespacedDatabase::new(store.clone(), "alice");
+/// alice.load().await.unwrap();
+///
+/// let bob = NamespacedDatabase::new(store, "bob");
+/// bob.load().await.unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NamespacedDatabase {
+    db: Arc<DynNostrDatabase>,
+    namespace: String,
+    indexes: DatabaseIndexes,
+    events: Arc<RwLock<HashSet<EventId>>>,
+    seen: Arc<RwLock<HashSet<EventId>>>,
+}
+
+impl NamespacedDatabase {
+    /// New namespace of a shared database
+    ///
+    /// Call [`NamespacedDatabase::load`] to restore the namespace of a persistent store.
+    pub fn new<D, S>(database: D, namespace: S) -> Self
+    where
+        D: IntoNostrDatabase,
+        S: Into<String>,
+    {
+        Self {
+            db: database.into_nostr_database(),
+            namespace: namespace.into(),
+            indexes: DatabaseIndexes::new(),
+            events: Arc::new(RwLock::new(HashSet::new())),
+            seen: Arc::new(RwLock::new(HashSet::new())),
+        }
+    }
+
+    /// Get namespace
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Restore the events of the namespace saved in the store and build the indexes
+    pub async fn load(&self) -> Result<(), DatabaseError> {
+        let ids: Vec<EventId> = match self.db.load_checkpoint(&self.events_key()).await? {
+            Some(bytes) => ids_from_bytes(&bytes),
+            None => return Ok(()),
+        };
+
+        let events: Vec<Event> = if ids.is_empty() {
+            Vec::new()
+        } else {
+            let filter: Filter = Filter::new().ids(ids);
+            self.db.query(vec![filter], Order::Desc).await?
+        };
+
+        let mut members = self.events.write().await;
+        members.extend(events.iter().map(|e| e.id));
+        self.indexes.bulk_index(events.iter().collect()).await;
+        Ok(())
+    }
+
+    fn events_key(&self) -> String {
+        format!("{CHECKPOINT_PREFIX}/{}/events", self.namespace)
+    }
+
+    fn checkpoint_key(&self, key: &str) -> String {
+        format!("{CHECKPOINT_PREFIX}/{}/checkpoints/{key}", self.namespace)
+    }
+
+    async fn persist(&self, members: &HashSet<EventId>) -> Result<(), DatabaseError> {
+        self.db
+            .save_checkpoint(&self.events_key(), ids_to_bytes(members))
+            .await
+    }
+}
+
+fn ids_to_bytes(ids: &HashSet<EventId>) -> Vec<u8> {
+    let ids: Vec<String> = ids.iter().map(|id| id.to_hex()).collect();
+    json!({ "version": VERSION, "events": ids })
+        .to_string()
+        .into_bytes()
+}
+
+fn ids_from_bytes(bytes: &[u8]) -> Vec<EventId> {
+    let parse = || -> Option<Vec<EventId>> {
+        let value: Value = serde_json::from_slice(bytes).ok()?;
+        if value.get("version")?.as_u64()? != VERSION {
+            return None;
+        }
+        value
+            .get("events")?
+            .as_array()?
+            .iter()
+            .map(|id| EventId::from_str(id.as_str()?).ok())
+            .collect()
+    };
+    parse().unwrap_or_default()
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NostrDatabase for NamespacedDatabase {
+    type Err = DatabaseError;
+
+    fn backend(&self) -> Backend {
+        self.db.backend()
+    }
+
+    async fn save_event(&self, event: &Event) -> Result<bool, Self::Err> {
+        let EventIndexResult {
+            to_store,
+            to_discard,
+        } = self.indexes.index_event(event).await;
+
+        let mut members = self.events.write().await;
+        let mut changed: bool = false;
+
+        for event_id in to_discard.iter() {
+            changed |= members.remove(event_id);
+        }
+
+        if to_store {
+            // The event may have already been saved by another namespace
+            if self.db.save_event(event).await?
+                || self.db.has_event_already_been_saved(&event.id).await?
+            {
+                members.insert(event.id);
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.persist(&members).await?;
+        }
+
+        Ok(to_store)
+    }
+
+    async fn bulk_import(&self, events: BTreeSet<Event>) -> Result<(), Self::Err> {
+        let events: BTreeSet<Event> = self.indexes.bulk_import(events).await;
+        let ids: Vec<EventId> = events.iter().map(|e| e.id).collect();
+        self.db.bulk_import(events).await?;
+
+        let mut members = self.events.write().await;
+        members.extend(ids);
+        self.persist(&members).await
+    }
+
+    async fn has_event_already_been_saved(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        if self.indexes.has_event_id_been_deleted(event_id).await {
+            return Ok(true);
+        }
+        Ok(self.events.read().await.contains(event_id))
+    }
+
+    async fn has_event_already_been_seen(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        Ok(self.seen.read().await.contains(event_id))
+    }
+
+    async fn has_event_id_been_deleted(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        Ok(self.indexes.has_event_id_been_deleted(event_id).await)
+    }
+
+    async fn has_coordinate_been_deleted(
+        &self,
+        coordinate: &Coordinate,
+        timestamp: Timestamp,
+    ) -> Result<bool, Self::Err> {
+        Ok(self
+            .indexes
+            .has_coordinate_been_deleted(coordinate, timestamp)
+            .await)
+    }
+
+    async fn event_id_seen(&self, event_id: EventId, relay_url: Url) -> Result<(), Self::Err> {
+        self.seen.write().await.insert(event_id);
+        self.db.event_id_seen(event_id, relay_url).await
+    }
+
+    async fn event_seen_on_relays(
+        &self,
+        event_id: EventId,
+    ) -> Result<Option<HashSet<Url>>, Self::Err> {
+        self.db.event_seen_on_relays(event_id).await
+    }
+
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
+        self.db.event_metadata(event_id).await
+    }
+
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        self.db.set_event_verification(event_id, status).await
+    }
+
+    async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
+        if !self.events.read().await.contains(&event_id) {
+            return Err(DatabaseError::NotFound);
+        }
+        self.db.event_by_id(event_id).await
+    }
+
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        self.db
+            .save_checkpoint(&self.checkpoint_key(key), value)
+            .await
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.db.load_checkpoint(&self.checkpoint_key(key)).await
+    }
+
+    async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
+        Ok(self.indexes.count(filters).await)
+    }
+
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        Ok(self.indexes.tag_values(filter, tag).await)
+    }
+
+    async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
+        let ids: Vec<EventId> = self.indexes.query(filters, order).await;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let filter: Filter = Filter::new().ids(ids);
+        self.db.query(vec![filter], order).await
+    }
+
+    async fn event_ids_by_filters(
+        &self,
+        filters: Vec<Filter>,
+        order: Order,
+    ) -> Result<Vec<EventId>, Self::Err> {
+        Ok(self.indexes.query(filters, order).await)
+    }
+
+    async fn negentropy_items(
+        &self,
+        filter: Filter,
+    ) -> Result<Vec<(EventId, Timestamp)>, Self::Err> {
+        Ok(self.indexes.negentropy_items(filter).await)
+    }
+
+    async fn delete(&self, filter: Filter) -> Result<(), Self::Err> {
+        let mut members = self.events.write().await;
+        match self.indexes.delete(filter).await {
+            Some(ids) => members.retain(|id| !ids.contains(id)),
+            None => members.clear(),
+        }
+        self.persist(&members).await
+    }
+
+    async fn wipe(&self) -> Result<(), Self::Err> {
+        self.indexes.clear().await;
+        self.seen.write().await.clear();
+        let mut members = self.events.write().await;
+        members.clear();
+        self.persist(&members).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+    use crate::{MemoryDatabase, MemoryDatabaseOptions};
+
+    #[tokio::test]
+    async fn test_namespaced_database() {
+        let store: Arc<DynNostrDatabase> = MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        })
+        .into_nostr_database();
+        let alice = NamespacedDatabase::new(store.clone(), "alice");
+        let bob = NamespacedDatabase::new(store.clone(), "bob");
+
+        let keys = Keys::generate();
+        let shared = EventBuilder::text_note("Shared", [])
+            .to_event(&keys)
+            .unwrap();
+        let private = EventBuilder::text_note("Alice", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(alice.save_event(&shared).await.unwrap());
+        assert!(alice.save_event(&private).await.unwrap());
+        assert!(bob.save_event(&shared).await.unwrap());
+
+        // Isolated queries
+        let filter = Filter::new().author(keys.public_key());
+        assert_eq!(alice.count(vec![filter.clone()]).await.unwrap(), 2);
+        assert_eq!(
+            bob.query(vec![filter.clone()], Order::Desc).await.unwrap(),
+            vec![shared.clone()]
+        );
+        assert!(!bob.has_event_already_been_saved(&private.id).await.unwrap());
+        assert!(matches!(
+            bob.event_by_id(private.id).await,
+            Err(DatabaseError::NotFound)
+        ));
+
+        // Shared store
+        assert_eq!(store.count(vec![filter.clone()]).await.unwrap(), 2);
+
+        // Isolated checkpoints
+        alice.save_checkpoint("key", vec![1]).await.unwrap();
+        assert_eq!(bob.load_checkpoint("key").await.unwrap(), None);
+
+        // Wipe only the namespace
+        bob.wipe().await.unwrap();
+        assert_eq!(bob.count(vec![filter.clone()]).await.unwrap(), 0);
+        assert_eq!(alice.count(vec![filter.clone()]).await.unwrap(), 2);
+
+        // Restore
+        let restored = NamespacedDatabase::new(store, "alice");
+        restored.load().await.unwrap();
+        assert_eq!(
+            restored.query(vec![filter], Order::Desc).await.unwrap(),
+            alice.query(vec![Filter::new()], Order::Desc).await.unwrap()
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event provenance metadata
+
+use std::collections::HashSet;
+
+use nostr::{Timestamp, Url};
+
+/// Event verification status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VerificationStatus {
+    /// Unknown (default)
+    #[default]
+    Unknown,
+    /// Not verified
+    Unverified,
+    /// Only the event ID has been verified
+    IdVerified,
+    /// Event ID and signature have been verified
+    Verified,
+}
+
+/// Event provenance metadata
+///
+/// Where and when an event has been seen and how it was verified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventMetadata {
+    /// Relays that have seen the event
+    pub relays: HashSet<Url>,
+    /// When the event was seen for the first time
+    pub first_seen: Option<Timestamp>,
+    /// When the event was seen for the last time
+    pub last_seen: Option<Timestamp>,
+    /// Verification status
+    pub verification: VerificationStatus,
+    /// The event is protected (NIP70): it must not be rebroadcasted by other users
+    ///
+    /// Set by [`NostrDatabaseExt::query_with_metadata`](crate::NostrDatabaseExt::query_with_metadata).
+    pub protected: bool,
+}
+
+impl EventMetadata {
+    /// New empty event metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compose metadata from relays that have seen the event
+    pub fn from_relays(relays: HashSet<Url>) -> Self {
+        Self {
+            relays,
+            ..Default::default()
+        }
+    }
+
+    /// Mark the event as seen by relay at [`Timestamp`]
+    pub fn seen(&mut self, relay_url: Url, timestamp: Timestamp) {
+        self.relays.insert(relay_url);
+        if self.first_seen.map_or(true, |t| timestamp < t) {
+            self.first_seen = Some(timestamp);
+        }
+        if self.last_seen.map_or(true, |t| timestamp > t) {
+            self.last_seen = Some(timestamp);
+        }
+    }
+
+    /// Number of relays that have seen the event
+    ///
+    /// Useful to sort events by reliability.
+    pub fn relays_count(&self) -> usize {
+        self.relays.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seen() {
+        let relay_a = Url::parse("wss://relay.damus.io").unwrap();
+        let relay_b = Url::parse("wss://nos.lol").unwrap();
+
+        let mut metadata = EventMetadata::new();
+        metadata.seen(relay_a.clone(), Timestamp::from(20));
+        metadata.seen(relay_b, Timestamp::from(10));
+        metadata.seen(relay_a, Timestamp::from(30));
+
+        assert_eq!(metadata.relays_count(), 2);
+        assert_eq!(metadata.first_seen, Some(Timestamp::from(10)));
+        assert_eq!(metadata.last_seen, Some(Timestamp::from(30)));
+        assert_eq!(metadata.verification, VerificationStatus::Unknown);
+    }
+}
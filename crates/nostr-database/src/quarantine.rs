@@ -0,0 +1,405 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Key compromise quarantine
+//!
+//! When a key is reported as compromised, the events it signed after the compromise
+//! can't be trusted anymore: flag them and hide them from queries, without deleting them.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use nostr::nips::nip01::Coordinate;
+use nostr::serde_json::{self, json, Value};
+use nostr::{Event, EventId, Filter, PublicKey, Timestamp, Url};
+use tokio::sync::RwLock;
+
+use crate::{
+    Backend, DatabaseError, DynNostrDatabase, EventMetadata, IntoNostrDatabase, NostrDatabase,
+    Order, VerificationStatus,
+};
+
+const VERSION: u64 = 1;
+const CHECKPOINT_KEY: &str = "nostr-database/quarantine";
+
+/// Database that hides the events signed by compromised keys
+///
+/// Events of a compromised key with `created_at` equal or greater than the compromise
+/// timestamp are flagged: they are kept in the store but excluded from [`NostrDatabase::query`],
+/// [`NostrDatabase::count`], [`NostrDatabase::event_ids_by_filters`] and [`NostrDatabase::event_by_id`].
+/// Use [`QuarantinedDatabase::query_with_flagged`] to explicitly include them.
+///
+/// Flagged events are still reported as saved, so they aren't downloaded again.
+///
+/// Note: flagged events are excluded after the `limit` of filters has been applied,
+/// so queries may return less events than the limit.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+///
+/// use nostr::{PublicKey, Timestamp};
+/// use nostr_database::{MemoryDatabase, QuarantinedDatabase};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// # let public_key = PublicKey::from_slice(&[2; 32]).unwrap();
+/// let database = QuarantinedDatabase::new(MemoryDatabase::new());
+/// database.load().await.unwrap();
+///
+/// // Key compromised 1 day ago
+/// let compromised_at = Timestamp::now() - Duration::from_secs(86400);
+/// let flagged = database
+///     .report_compromise(public_key, compromised_at)
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuarantinedDatabase {
+    db: Arc<DynNostrDatabase>,
+    reports: Arc<RwLock<HashMap<PublicKey, Timestamp>>>,
+}
+
+impl QuarantinedDatabase {
+    /// New quarantined database
+    ///
+    /// Call [`QuarantinedDatabase::load`] to restore the reports of a persistent store.
+    pub fn new<D>(database: D) -> Self
+    where
+        D: IntoNostrDatabase,
+    {
+        Self {
+            db: database.into_nostr_database(),
+            reports: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Restore the compromise reports saved in the store
+    pub async fn load(&self) -> Result<(), DatabaseError> {
+        if let Some(bytes) = self.db.load_checkpoint(CHECKPOINT_KEY).await? {
+            let mut reports = self.reports.write().await;
+            for (public_key, compromised_at) in reports_from_bytes(&bytes).into_iter() {
+                insert_report(&mut reports, public_key, compromised_at);
+            }
+        }
+        Ok(())
+    }
+
+    /// Report a compromised key
+    ///
+    /// Flag all the stored events of `public_key` with `created_at` equal or greater than
+    /// `compromised_at` and return their IDs. Events received later are flagged too.
+    ///
+    /// If the key was already reported, the earliest timestamp is kept.
+    pub async fn report_compromise(
+        &self,
+        public_key: PublicKey,
+        compromised_at: Timestamp,
+    ) -> Result<Vec<EventId>, DatabaseError> {
+        let compromised_at: Timestamp = {
+            let mut reports = self.reports.write().await;
+            insert_report(&mut reports, public_key, compromised_at);
+            self.db
+                .save_checkpoint(CHECKPOINT_KEY, reports_to_bytes(&reports))
+                .await?;
+            reports[&public_key]
+        };
+
+        let filter: Filter = Filter::new().author(public_key).since(compromised_at);
+        self.db
+            .event_ids_by_filters(vec![filter], Order::Desc)
+            .await
+    }
+
+    /// Withdraw the report of a compromised key (i.e. false report)
+    ///
+    /// Return `false` if the key wasn't reported.
+    pub async fn withdraw_report(&self, public_key: &PublicKey) -> Result<bool, DatabaseError> {
+        let mut reports = self.reports.write().await;
+        if reports.remove(public_key).is_none() {
+            return Ok(false);
+        }
+        self.db
+            .save_checkpoint(CHECKPOINT_KEY, reports_to_bytes(&reports))
+            .await?;
+        Ok(true)
+    }
+
+    /// Get compromised keys and their compromise timestamps
+    pub async fn reports(&self) -> HashMap<PublicKey, Timestamp> {
+        self.reports.read().await.clone()
+    }
+
+    /// Check if the event is flagged
+    pub async fn is_flagged(&self, event: &Event) -> bool {
+        let reports = self.reports.read().await;
+        is_flagged(&reports, event)
+    }
+
+    /// Query store with filters, including flagged events
+    pub async fn query_with_flagged(
+        &self,
+        filters: Vec<Filter>,
+        order: Order,
+    ) -> Result<Vec<Event>, DatabaseError> {
+        self.db.query(filters, order).await
+    }
+}
+
+fn is_flagged(reports: &HashMap<PublicKey, Timestamp>, event: &Event) -> bool {
+    match reports.get(&event.pubkey) {
+        Some(compromised_at) => event.created_at >= *compromised_at,
+        None => false,
+    }
+}
+
+fn insert_report(
+    reports: &mut HashMap<PublicKey, Timestamp>,
+    public_key: PublicKey,
+    compromised_at: Timestamp,
+) {
+    reports
+        .entry(public_key)
+        .and_modify(|t| *t = (*t).min(compromised_at))
+        .or_insert(compromised_at);
+}
+
+fn reports_to_bytes(reports: &HashMap<PublicKey, Timestamp>) -> Vec<u8> {
+    let reports: Vec<Value> = reports
+        .iter()
+        .map(|(public_key, compromised_at)| {
+            json!({
+                "public_key": public_key.to_string(),
+                "compromised_at": compromised_at.as_u64(),
+            })
+        })
+        .collect();
+    json!({ "version": VERSION, "reports": reports })
+        .to_string()
+        .into_bytes()
+}
+
+fn reports_from_bytes(bytes: &[u8]) -> Vec<(PublicKey, Timestamp)> {
+    let parse = || -> Option<Vec<(PublicKey, Timestamp)>> {
+        let value: Value = serde_json::from_slice(bytes).ok()?;
+        if value.get("version")?.as_u64()? != VERSION {
+            return None;
+        }
+        value
+            .get("reports")?
+            .as_array()?
+            .iter()
+            .map(|report| {
+                Some((
+                    PublicKey::from_str(report.get("public_key")?.as_str()?).ok()?,
+                    Timestamp::from(report.get("compromised_at")?.as_u64()?),
+                ))
+            })
+            .collect()
+    };
+    parse().unwrap_or_default()
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NostrDatabase for QuarantinedDatabase {
+    type Err = DatabaseError;
+
+    fn backend(&self) -> Backend {
+        self.db.backend()
+    }
+
+    async fn save_event(&self, event: &Event) -> Result<bool, Self::Err> {
+        self.db.save_event(event).await
+    }
+
+    async fn bulk_import(&self, events: BTreeSet<Event>) -> Result<(), Self::Err> {
+        self.db.bulk_import(events).await
+    }
+
+    async fn has_event_already_been_saved(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.db.has_event_already_been_saved(event_id).await
+    }
+
+    async fn has_event_already_been_seen(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.db.has_event_already_been_seen(event_id).await
+    }
+
+    async fn has_event_id_been_deleted(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.db.has_event_id_been_deleted(event_id).await
+    }
+
+    async fn has_coordinate_been_deleted(
+        &self,
+        coordinate: &Coordinate,
+        timestamp: Timestamp,
+    ) -> Result<bool, Self::Err> {
+        self.db
+            .has_coordinate_been_deleted(coordinate, timestamp)
+            .await
+    }
+
+    async fn event_id_seen(&self, event_id: EventId, relay_url: Url) -> Result<(), Self::Err> {
+        self.db.event_id_seen(event_id, relay_url).await
+    }
+
+    async fn event_seen_on_relays(
+        &self,
+        event_id: EventId,
+    ) -> Result<Option<HashSet<Url>>, Self::Err> {
+        self.db.event_seen_on_relays(event_id).await
+    }
+
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
+        self.db.event_metadata(event_id).await
+    }
+
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        self.db.set_event_verification(event_id, status).await
+    }
+
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        self.db.save_checkpoint(key, value).await
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.db.load_checkpoint(key).await
+    }
+
+    async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
+        let event: Event = self.db.event_by_id(event_id).await?;
+        if self.is_flagged(&event).await {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(event)
+    }
+
+    async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
+        if self.reports.read().await.is_empty() {
+            return self.db.count(filters).await;
+        }
+        Ok(self.query(filters, Order::Desc).await?.len())
+    }
+
+    async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
+        let mut events: Vec<Event> = self.db.query(filters, order).await?;
+        let reports = self.reports.read().await;
+        if !reports.is_empty() {
+            events.retain(|event| !is_flagged(&reports, event));
+        }
+        Ok(events)
+    }
+
+    async fn event_ids_by_filters(
+        &self,
+        filters: Vec<Filter>,
+        order: Order,
+    ) -> Result<Vec<EventId>, Self::Err> {
+        if self.reports.read().await.is_empty() {
+            return self.db.event_ids_by_filters(filters, order).await;
+        }
+        Ok(self
+            .query(filters, order)
+            .await?
+            .into_iter()
+            .map(|e| e.id)
+            .collect())
+    }
+
+    async fn negentropy_items(
+        &self,
+        filter: Filter,
+    ) -> Result<Vec<(EventId, Timestamp)>, Self::Err> {
+        // Flagged events are still stored: don't fetch them again
+        self.db.negentropy_items(filter).await
+    }
+
+    async fn delete(&self, filter: Filter) -> Result<(), Self::Err> {
+        self.db.delete(filter).await
+    }
+
+    async fn wipe(&self) -> Result<(), Self::Err> {
+        self.reports.write().await.clear();
+        self.db.wipe().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+    use crate::{MemoryDatabase, MemoryDatabaseOptions};
+
+    fn memory() -> MemoryDatabase {
+        MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_quarantined_database() {
+        let database = QuarantinedDatabase::new(memory());
+
+        let keys = Keys::generate();
+        let old = EventBuilder::text_note("Old", [])
+            .custom_created_at(Timestamp::from(100))
+            .to_event(&keys)
+            .unwrap();
+        let new = EventBuilder::text_note("New", [])
+            .custom_created_at(Timestamp::from(200))
+            .to_event(&keys)
+            .unwrap();
+        database.save_event(&old).await.unwrap();
+        database.save_event(&new).await.unwrap();
+
+        let flagged = database
+            .report_compromise(keys.public_key(), Timestamp::from(150))
+            .await
+            .unwrap();
+        assert_eq!(flagged, vec![new.id]);
+
+        // Excluded by default
+        let filter = Filter::new().author(keys.public_key());
+        let events = database
+            .query(vec![filter.clone()], Order::Desc)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![old.clone()]);
+        assert_eq!(database.count(vec![filter.clone()]).await.unwrap(), 1);
+        assert!(matches!(
+            database.event_by_id(new.id).await,
+            Err(DatabaseError::NotFound)
+        ));
+        assert!(database
+            .has_event_already_been_saved(&new.id)
+            .await
+            .unwrap());
+
+        // Explicit override
+        let events = database
+            .query_with_flagged(vec![filter.clone()], Order::Desc)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+
+        // Reports are persisted
+        let restored = QuarantinedDatabase::new(database.db.clone());
+        restored.load().await.unwrap();
+        assert_eq!(restored.reports().await, database.reports().await);
+
+        // Withdraw
+        assert!(database.withdraw_report(&keys.public_key()).await.unwrap());
+        assert_eq!(database.count(vec![filter]).await.unwrap(), 2);
+    }
+}
@@ -0,0 +1,404 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Database router
+//!
+//! Route events to different databases by [`Kind`] (i.e. profiles cache, events archive, DMs store, ...),
+//! so each store can have its own retention and encryption policies.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use nostr::nips::nip01::Coordinate;
+use nostr::{Event, EventId, Filter, Kind, Timestamp, Url};
+
+use crate::{
+    Backend, DatabaseError, DynNostrDatabase, EventMetadata, IntoNostrDatabase, NostrDatabase,
+    Order, VerificationStatus,
+};
+
+/// Database that routes events to different stores by [`Kind`]
+///
+/// Events with a kind without a route are saved into the default store,
+/// that is also used to track the relays where events have been seen.
+/// Deletion events are saved into every store.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use nostr::Kind;
+/// use nostr_database::{MemoryDatabase, MemoryDatabaseOptions, RoutedDatabase};
+///
+/// let opts = MemoryDatabaseOptions {
+///     events: true,
+///     ..Default::default()
+/// };
+/// let archive = MemoryDatabase::with_opts(opts);
+/// let profiles = MemoryDatabase::with_opts(opts);
+/// let dms = MemoryDatabase::with_opts(opts);
+///
+/// let database = RoutedDatabase::new(archive)
+///     .route([Kind::Metadata, Kind::ContactList], profiles)
+///     .route([Kind::EncryptedDirectMessage, Kind::GiftWrap], dms);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RoutedDatabase {
+    stores: Vec<Arc<DynNostrDatabase>>,
+    routes: HashMap<Kind, usize>,
+}
+
+impl RoutedDatabase {
+    /// New routed database with the default store
+    pub fn new<D>(default: D) -> Self
+    where
+        D: IntoNostrDatabase,
+    {
+        Self {
+            stores: vec![default.into_nostr_database()],
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Route events of the specified [`Kind`]s to a store
+    ///
+    /// If a kind was already routed, the previous route is overwritten.
+    pub fn route<I, D>(mut self, kinds: I, database: D) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+        D: IntoNostrDatabase,
+    {
+        let index: usize = self.stores.len();
+        self.stores.push(database.into_nostr_database());
+        for kind in kinds.into_iter() {
+            self.routes.insert(kind, index);
+        }
+        self
+    }
+
+    /// Get the store where the events of a [`Kind`] are saved
+    pub fn store(&self, kind: &Kind) -> Arc<DynNostrDatabase> {
+        self.stores[self.store_index(kind)].clone()
+    }
+
+    fn default_store(&self) -> &Arc<DynNostrDatabase> {
+        &self.stores[0]
+    }
+
+    fn store_index(&self, kind: &Kind) -> usize {
+        self.routes.get(kind).copied().unwrap_or_default()
+    }
+
+    /// Get the stores that may contain events matching the filter
+    fn stores_for_filter(&self, filter: &Filter) -> BTreeSet<usize> {
+        match &filter.kinds {
+            Some(kinds) if !kinds.is_empty() => kinds.iter().map(|k| self.store_index(k)).collect(),
+            _ => (0..self.stores.len()).collect(),
+        }
+    }
+
+    /// Group filters by store
+    fn group_filters(&self, filters: Vec<Filter>) -> HashMap<usize, Vec<Filter>> {
+        let mut groups: HashMap<usize, Vec<Filter>> = HashMap::new();
+        for filter in filters.into_iter() {
+            for index in self.stores_for_filter(&filter).into_iter() {
+                groups.entry(index).or_default().push(filter.clone());
+            }
+        }
+        groups
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl NostrDatabase for RoutedDatabase {
+    type Err = DatabaseError;
+
+    fn backend(&self) -> Backend {
+        Backend::Custom(String::from("routed"))
+    }
+
+    async fn save_event(&self, event: &Event) -> Result<bool, Self::Err> {
+        if event.kind == Kind::EventDeletion {
+            // Deleted events may be in any store
+            let mut saved: bool = false;
+            for store in self.stores.iter() {
+                saved |= store.save_event(event).await?;
+            }
+            return Ok(saved);
+        }
+
+        self.stores[self.store_index(&event.kind)]
+            .save_event(event)
+            .await
+    }
+
+    async fn bulk_import(&self, events: BTreeSet<Event>) -> Result<(), Self::Err> {
+        let mut groups: HashMap<usize, BTreeSet<Event>> = HashMap::new();
+        for event in events.into_iter() {
+            if event.kind == Kind::EventDeletion {
+                for index in 0..self.stores.len() {
+                    groups.entry(index).or_default().insert(event.clone());
+                }
+            } else {
+                groups
+                    .entry(self.store_index(&event.kind))
+                    .or_default()
+                    .insert(event);
+            }
+        }
+
+        for (index, events) in groups.into_iter() {
+            self.stores[index].bulk_import(events).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn has_event_already_been_saved(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        for store in self.stores.iter() {
+            if store.has_event_already_been_saved(event_id).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn has_event_already_been_seen(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        self.default_store()
+            .has_event_already_been_seen(event_id)
+            .await
+    }
+
+    async fn has_event_id_been_deleted(&self, event_id: &EventId) -> Result<bool, Self::Err> {
+        for store in self.stores.iter() {
+            if store.has_event_id_been_deleted(event_id).await? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    async fn has_coordinate_been_deleted(
+        &self,
+        coordinate: &Coordinate,
+        timestamp: Timestamp,
+    ) -> Result<bool, Self::Err> {
+        self.stores[self.store_index(&coordinate.kind)]
+            .has_coordinate_been_deleted(coordinate, timestamp)
+            .await
+    }
+
+    async fn event_id_seen(&self, event_id: EventId, relay_url: Url) -> Result<(), Self::Err> {
+        self.default_store()
+            .event_id_seen(event_id, relay_url)
+            .await
+    }
+
+    async fn event_seen_on_relays(
+        &self,
+        event_id: EventId,
+    ) -> Result<Option<HashSet<Url>>, Self::Err> {
+        self.default_store().event_seen_on_relays(event_id).await
+    }
+
+    async fn event_metadata(&self, event_id: EventId) -> Result<Option<EventMetadata>, Self::Err> {
+        self.default_store().event_metadata(event_id).await
+    }
+
+    async fn set_event_verification(
+        &self,
+        event_id: EventId,
+        status: VerificationStatus,
+    ) -> Result<(), Self::Err> {
+        self.default_store()
+            .set_event_verification(event_id, status)
+            .await
+    }
+
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        self.default_store().save_checkpoint(key, value).await
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.default_store().load_checkpoint(key).await
+    }
+
+    async fn event_by_id(&self, event_id: EventId) -> Result<Event, Self::Err> {
+        for store in self.stores.iter() {
+            match store.event_by_id(event_id).await {
+                Ok(event) => return Ok(event),
+                Err(DatabaseError::NotFound) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(DatabaseError::NotFound)
+    }
+
+    async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
+        // Deletion events are saved into every store: count unique IDs
+        let ids: Vec<EventId> = self.event_ids_by_filters(filters, Order::Desc).await?;
+        Ok(ids.len())
+    }
+
+    /// Query the stores and merge the results
+    ///
+    /// Note: when a filter spans more stores, its `limit` is applied to every store.
+    async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
+        let mut groups = self.group_filters(filters);
+
+        // Single store: no need to merge
+        if groups.len() == 1 {
+            if let Some((index, filters)) = groups.drain().next() {
+                return self.stores[index].query(filters, order).await;
+            }
+        }
+
+        let mut ids: HashSet<EventId> = HashSet::new();
+        let mut events: Vec<Event> = Vec::new();
+        for (index, filters) in groups.into_iter() {
+            for event in self.stores[index].query(filters, order).await? {
+                if ids.insert(event.id) {
+                    events.push(event);
+                }
+            }
+        }
+
+        match order {
+            Order::Asc => events.sort_by_key(|e| e.created_at),
+            Order::Desc => events.sort_by_key(|e| Reverse(e.created_at)),
+        }
+
+        Ok(events)
+    }
+
+    async fn event_ids_by_filters(
+        &self,
+        filters: Vec<Filter>,
+        order: Order,
+    ) -> Result<Vec<EventId>, Self::Err> {
+        Ok(self
+            .query(filters, order)
+            .await?
+            .into_iter()
+            .map(|e| e.id)
+            .collect())
+    }
+
+    async fn negentropy_items(
+        &self,
+        filter: Filter,
+    ) -> Result<Vec<(EventId, Timestamp)>, Self::Err> {
+        let mut ids: HashSet<EventId> = HashSet::new();
+        let mut items: Vec<(EventId, Timestamp)> = Vec::new();
+        for index in self.stores_for_filter(&filter).into_iter() {
+            for (id, timestamp) in self.stores[index]
+                .negentropy_items(filter.clone())
+                .await?
+                .into_iter()
+            {
+                if ids.insert(id) {
+                    items.push((id, timestamp));
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    async fn delete(&self, filter: Filter) -> Result<(), Self::Err> {
+        for index in self.stores_for_filter(&filter).into_iter() {
+            self.stores[index].delete(filter.clone()).await?;
+        }
+        Ok(())
+    }
+
+    async fn wipe(&self) -> Result<(), Self::Err> {
+        for store in self.stores.iter() {
+            store.wipe().await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Metadata};
+
+    use super::*;
+    use crate::{MemoryDatabase, MemoryDatabaseOptions};
+
+    fn memory() -> Arc<MemoryDatabase> {
+        Arc::new(MemoryDatabase::with_opts(MemoryDatabaseOptions {
+            events: true,
+            ..Default::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_routed_database() {
+        let archive = memory();
+        let profiles = memory();
+        let database =
+            RoutedDatabase::new(archive.clone()).route([Kind::Metadata], profiles.clone());
+
+        let keys = Keys::generate();
+        let metadata = EventBuilder::metadata(&Metadata::new().name("Test"))
+            .to_event(&keys)
+            .unwrap();
+        let note = EventBuilder::text_note("Test", []).to_event(&keys).unwrap();
+
+        assert!(database.save_event(&metadata).await.unwrap());
+        assert!(database.save_event(&note).await.unwrap());
+
+        // Check routing
+        assert!(profiles
+            .has_event_already_been_saved(&metadata.id)
+            .await
+            .unwrap());
+        assert!(!archive
+            .has_event_already_been_saved(&metadata.id)
+            .await
+            .unwrap());
+        assert!(archive
+            .has_event_already_been_saved(&note.id)
+            .await
+            .unwrap());
+        assert!(!profiles
+            .has_event_already_been_saved(&note.id)
+            .await
+            .unwrap());
+
+        // Query
+        let events = database
+            .query(vec![Filter::new().author(keys.public_key())], Order::Desc)
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        let events = database
+            .query(vec![Filter::new().kind(Kind::Metadata)], Order::Desc)
+            .await
+            .unwrap();
+        assert_eq!(events, vec![metadata.clone()]);
+        assert_eq!(database.event_by_id(note.id).await.unwrap(), note);
+
+        // Deletion reaches every store
+        let deletion = EventBuilder::delete([metadata.id, note.id])
+            .to_event(&keys)
+            .unwrap();
+        database.save_event(&deletion).await.unwrap();
+        assert!(database
+            .has_event_id_been_deleted(&metadata.id)
+            .await
+            .unwrap());
+        assert!(database.has_event_id_been_deleted(&note.id).await.unwrap());
+        assert_eq!(
+            database
+                .count(vec![Filter::new().kind(Kind::EventDeletion)])
+                .await
+                .unwrap(),
+            1
+        );
+    }
+}
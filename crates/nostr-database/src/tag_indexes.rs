@@ -85,6 +85,20 @@ where
     }
 }
 
+/// Values of the indexed tags, with their hash
+pub(crate) fn tag_values<'a, I>(iter: I) -> Vec<([u8; TAG_INDEX_VALUE_SIZE], String)>
+where
+    I: Iterator<Item = &'a Tag>,
+{
+    iter.filter(|t| t.single_letter_tag().is_some())
+        .filter_map(|t| t.content())
+        .map(|content| {
+            let value: String = content.to_string();
+            (hash(&value), value)
+        })
+        .collect()
+}
+
 #[cfg(feature = "flatbuf")]
 #[inline]
 fn single_char_tagname(tagname: &str) -> Option<SingleLetterTag> {
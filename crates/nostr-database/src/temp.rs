@@ -27,6 +27,9 @@ pub struct TempEvent {
     pub identifier: Option<[u8; TAG_INDEX_VALUE_SIZE]>,
     pub event_ids: Vec<EventId>,
     pub coordinates: Vec<Coordinate>,
+    pub sensitive: bool,
+    /// Tag values, with their hash
+    pub tag_values: Vec<([u8; TAG_INDEX_VALUE_SIZE], String)>,
 }
 
 impl PartialOrd for TempEvent {
@@ -62,6 +65,8 @@ impl TempEvent {
             identifier: extract_identifier(&tags),
             event_ids: extract_event_ids(&tags),
             coordinates: extract_coordinates(&tags),
+            sensitive: is_sensitive(&tags),
+            tag_values: extract_tag_values(&tags),
             tags: TagIndexes::from_flatb(tags),
         }
     }
@@ -99,6 +104,31 @@ fn extract_identifier<'a>(
     })
 }
 
+fn is_sensitive<'a>(tags: &Vector<'a, ForwardsUOffset<StringVector<'a>>>) -> bool {
+    tags.iter().any(|tag| {
+        tag.data().map_or(false, |tag| {
+            !tag.is_empty() && tag.get(0) == "content-warning"
+        })
+    })
+}
+
+fn extract_tag_values<'a>(
+    tags: &Vector<'a, ForwardsUOffset<StringVector<'a>>>,
+) -> Vec<([u8; TAG_INDEX_VALUE_SIZE], String)> {
+    tags.iter()
+        .filter_map(|tag| {
+            tag.data().and_then(|tag| {
+                if tag.len() >= 2 {
+                    let value = tag.get(1);
+                    Some((hash(value), value.to_string()))
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
 fn extract_event_ids<'a>(tags: &Vector<'a, ForwardsUOffset<StringVector<'a>>>) -> Vec<EventId> {
     tags.iter()
         .filter_map(|tag| {
@@ -147,6 +177,8 @@ mod tests {
             identifier: None,
             event_ids: Vec::new(),
             coordinates: Vec::new(),
+            sensitive: false,
+            tag_values: Vec::new(),
         };
         let now = Timestamp::now();
         assert!(raw.is_expired(&now));
@@ -167,6 +199,8 @@ mod tests {
             identifier: None,
             event_ids: Vec::new(),
             coordinates: Vec::new(),
+            sensitive: false,
+            tag_values: Vec::new(),
         };
 
         assert!(!raw.is_expired(&now));
@@ -0,0 +1,162 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Thread view
+
+use std::collections::{HashMap, HashSet};
+
+use nostr::nips::nip10;
+use nostr::{Event, EventId};
+
+/// Thread node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadNode {
+    /// Event
+    pub event: Event,
+    /// Parent not found in the database (the node is attached to the root)
+    pub missing_parent: Option<EventId>,
+    /// Replies, sorted by `created_at`
+    pub children: Vec<ThreadNode>,
+}
+
+/// Thread assembled from the local database
+///
+/// Built with NIP10 markers (or positional `e` tags).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadView {
+    /// Root event ID
+    pub root_id: EventId,
+    /// Root event (`None` if not found in the database)
+    pub root: Option<Event>,
+    /// Direct replies to the root and replies with a missing parent, sorted by `created_at`
+    pub replies: Vec<ThreadNode>,
+}
+
+impl ThreadView {
+    /// Build the thread tree
+    pub fn new(root_id: EventId, root: Option<Event>, replies: Vec<Event>) -> Self {
+        let ids: HashSet<EventId> = replies.iter().map(|e| e.id()).collect();
+
+        // Group events by parent
+        let mut children: HashMap<EventId, Vec<Event>> = HashMap::new();
+        let mut orphans: Vec<(Event, EventId)> = Vec::new();
+        for event in replies.into_iter() {
+            if event.id() == root_id {
+                continue;
+            }
+
+            let parent: EventId = nip10::extract_thread(&event)
+                .parent
+                .map(|p| p.event_id)
+                .unwrap_or(root_id);
+
+            if parent == root_id || ids.contains(&parent) {
+                children.entry(parent).or_default().push(event);
+            } else {
+                orphans.push((event, parent));
+            }
+        }
+
+        let mut replies: Vec<ThreadNode> = build_nodes(root_id, &mut children);
+        replies.extend(orphans.into_iter().map(|(event, parent)| {
+            let event_id: EventId = event.id();
+            ThreadNode {
+                event,
+                missing_parent: Some(parent),
+                children: build_nodes(event_id, &mut children),
+            }
+        }));
+        replies.sort_by_key(|reply| reply.event.created_at());
+
+        Self {
+            root_id,
+            root,
+            replies,
+        }
+    }
+
+    /// IDs of the events referenced in the thread but not found in the database
+    ///
+    /// A UI can use them to lazily fetch the gaps from relays.
+    pub fn missing(&self) -> Vec<EventId> {
+        let mut missing: Vec<EventId> = Vec::new();
+        if self.root.is_none() {
+            missing.push(self.root_id);
+        }
+        let mut stack: Vec<&ThreadNode> = self.replies.iter().collect();
+        while let Some(node) = stack.pop() {
+            if let Some(parent) = node.missing_parent {
+                if !missing.contains(&parent) {
+                    missing.push(parent);
+                }
+            }
+            stack.extend(node.children.iter());
+        }
+        missing
+    }
+}
+
+fn build_nodes(parent: EventId, children: &mut HashMap<EventId, Vec<Event>>) -> Vec<ThreadNode> {
+    let mut events: Vec<Event> = children.remove(&parent).unwrap_or_default();
+    events.sort_by_key(|e| e.created_at());
+    events
+        .into_iter()
+        .map(|event| {
+            let event_id: EventId = event.id();
+            ThreadNode {
+                event,
+                missing_parent: None,
+                children: build_nodes(event_id, children),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Tag, Timestamp};
+
+    use super::*;
+
+    #[test]
+    fn test_thread_view() {
+        let keys = Keys::generate();
+        let root = EventBuilder::text_note("Root", []).to_event(&keys).unwrap();
+        let reply_1 = EventBuilder::text_note_reply("Reply 1", &root, None, None)
+            .custom_created_at(Timestamp::from(2))
+            .to_event(&keys)
+            .unwrap();
+        let reply_2 = EventBuilder::text_note_reply("Reply 2", &root, None, None)
+            .custom_created_at(Timestamp::from(1))
+            .to_event(&keys)
+            .unwrap();
+        let nested = EventBuilder::text_note_reply("Nested", &reply_1, Some(&root), None)
+            .to_event(&keys)
+            .unwrap();
+
+        // Reply to an event not in the database
+        let missing = EventId::all_zeros();
+        let orphan =
+            EventBuilder::text_note("Orphan", [Tag::event(root.id()), Tag::event(missing)])
+                .to_event(&keys)
+                .unwrap();
+
+        let thread = ThreadView::new(
+            root.id(),
+            Some(root.clone()),
+            vec![
+                nested.clone(),
+                reply_1.clone(),
+                orphan.clone(),
+                reply_2.clone(),
+            ],
+        );
+
+        let ids: Vec<EventId> = thread.replies.iter().map(|n| n.event.id()).collect();
+        assert_eq!(ids, vec![reply_2.id(), reply_1.id(), orphan.id()]);
+        assert_eq!(thread.replies[1].children[0].event, nested);
+        assert_eq!(thread.replies[2].missing_parent, Some(missing));
+        assert_eq!(thread.missing(), vec![missing]);
+    }
+}
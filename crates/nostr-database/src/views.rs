@@ -0,0 +1,194 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Materialized views
+//!
+//! Triggers run on every index mutation (insert, replace, deletion) and maintain per-group counters,
+//! to avoid repeated `count` queries for values shown in the UI (i.e. posts per author, replies per thread).
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use nostr::nips::nip01::Coordinate;
+use nostr::{EventId, Kind, PublicKey, Timestamp};
+
+/// Indexed event, as seen by the triggers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerEvent {
+    /// Event ID
+    pub id: EventId,
+    /// Author
+    pub author: PublicKey,
+    /// Kind
+    pub kind: Kind,
+    /// Timestamp
+    pub created_at: Timestamp,
+    /// Referenced event IDs (`e` tags)
+    pub event_ids: Vec<EventId>,
+    /// Referenced coordinates (`a` tags)
+    pub coordinates: Vec<Coordinate>,
+}
+
+type GroupsFn = dyn Fn(&TriggerEvent) -> Vec<String> + Send + Sync;
+
+/// Trigger that maintains a materialized view
+///
+/// The view counts, for each group returned by the trigger, the indexed events that belong to it.
+#[derive(Clone)]
+pub struct Trigger {
+    name: String,
+    groups: Arc<GroupsFn>,
+}
+
+impl fmt::Debug for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trigger").field("name", &self.name).finish()
+    }
+}
+
+impl Trigger {
+    /// New trigger
+    ///
+    /// `groups` returns the groups (i.e. the author) that count the event. Return an empty list to skip it.
+    pub fn new<S, F>(name: S, groups: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&TriggerEvent) -> Vec<String> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            groups: Arc::new(groups),
+        }
+    }
+
+    /// Count the events of `kind` for each author (hex public key)
+    pub fn author_count<S>(name: S, kind: Kind) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(name, move |event| {
+            if event.kind == kind {
+                vec![event.author.to_string()]
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    /// Count the text notes that reference each event (hex event ID)
+    ///
+    /// All the `e` tags are counted, so a reply is counted both for the root and for the parent.
+    pub fn reply_count<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(name, |event| {
+            if event.kind == Kind::TextNote {
+                event.event_ids.iter().map(|id| id.to_hex()).collect()
+            } else {
+                Vec::new()
+            }
+        })
+    }
+
+    /// Trigger name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Materialized views of the indexes
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Views {
+    triggers: Vec<Trigger>,
+    /// View name -> group -> count
+    counts: HashMap<String, HashMap<String, u64>>,
+    /// Groups counted for each event, to undo them on removal
+    contributions: HashMap<EventId, Vec<(String, String)>>,
+}
+
+impl Views {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.triggers.is_empty()
+    }
+
+    /// Register a trigger, replacing the one with the same name
+    ///
+    /// The view starts empty: already indexed events are counted only after a rebuild.
+    pub fn register(&mut self, trigger: Trigger) {
+        self.unregister(trigger.name());
+        self.counts.insert(trigger.name.clone(), HashMap::new());
+        self.triggers.push(trigger);
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.triggers.retain(|t| t.name != name);
+        self.counts.remove(name);
+        for list in self.contributions.values_mut() {
+            list.retain(|(view, _)| view != name);
+        }
+        self.contributions.retain(|_, list| !list.is_empty());
+    }
+
+    /// Count an inserted event
+    ///
+    /// Already counted events are ignored.
+    pub fn insert(&mut self, event: &TriggerEvent) {
+        if self.contributions.contains_key(&event.id) {
+            return;
+        }
+
+        let mut list: Vec<(String, String)> = Vec::new();
+        for trigger in self.triggers.iter() {
+            let groups: BTreeSet<String> = (trigger.groups)(event).into_iter().collect();
+            let counts = self.counts.entry(trigger.name.clone()).or_default();
+            for group in groups.into_iter() {
+                *counts.entry(group.clone()).or_default() += 1;
+                list.push((trigger.name.clone(), group));
+            }
+        }
+
+        if !list.is_empty() {
+            self.contributions.insert(event.id, list);
+        }
+    }
+
+    /// Undo the counts of a removed event
+    pub fn remove(&mut self, id: &EventId) {
+        if let Some(list) = self.contributions.remove(id) {
+            for (view, group) in list.into_iter() {
+                if let Some(counts) = self.counts.get_mut(&view) {
+                    if let Some(count) = counts.get_mut(&group) {
+                        *count = count.saturating_sub(1);
+                        if *count == 0 {
+                            counts.remove(&group);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reset all the counts, keeping the triggers
+    pub fn reset(&mut self) {
+        for counts in self.counts.values_mut() {
+            counts.clear();
+        }
+        self.contributions.clear();
+    }
+
+    pub fn get(&self, view: &str, group: &str) -> u64 {
+        self.counts
+            .get(view)
+            .and_then(|counts| counts.get(group))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn all(&self, view: &str) -> Option<HashMap<String, u64>> {
+        self.counts.get(view).cloned()
+    }
+}
@@ -25,7 +25,7 @@ use indexed_db_futures::web_sys::IdbTransactionMode;
 use indexed_db_futures::{IdbDatabase, IdbQuerySource, IdbVersionChangeEvent};
 use nostr::nips::nip01::Coordinate;
 use nostr::util::hex;
-use nostr::{Event, EventId, Filter, Timestamp, Url};
+use nostr::{Event, EventId, Filter, SingleLetterTag, Timestamp, Url};
 #[cfg(target_arch = "wasm32")]
 use nostr_database::NostrDatabase;
 use nostr_database::{
@@ -418,6 +418,14 @@ impl_nostr_database!({
         Ok(self.indexes.count(filters).await)
     }
 
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, IndexedDBError> {
+        Ok(self.indexes.tag_values(filter, tag).await)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn query(
         &self,
@@ -13,14 +13,27 @@
 pub mod pool;
 pub mod prelude;
 pub mod relay;
+pub mod runtime;
 
+pub use self::pool::dedup::DedupOptions;
+pub use self::pool::filtering::FilteringPolicy;
+pub use self::pool::kinds::NotificationKinds;
 pub use self::pool::options::RelayPoolOptions;
-pub use self::pool::{RelayPool, RelayPoolNotification};
+pub use self::pool::output::{Output, PowEscalation};
+pub use self::pool::verifier::{EventVerifier, VerificationPolicy, VerifyMode};
+pub use self::pool::{
+    CompletenessReport, PoolCheckpoint, RelayCheckpoint, RelayPool, RelayPoolNotification,
+};
 pub use self::relay::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
 pub use self::relay::limits::RelayLimits;
 pub use self::relay::options::{
-    FilterOptions, NegentropyDirection, NegentropyOptions, RelayOptions, RelaySendOptions,
-    SubscribeAutoCloseOptions, SubscribeOptions,
+    ConnectionMode, FilterOptions, NegentropyDirection, NegentropyOptions, RelayOptions,
+    RelaySendOptions, SubscribeAutoCloseOptions, SubscribeOptions,
+};
+pub use self::relay::rate::{FloodAction, FloodProtection, RateLimit};
+pub use self::relay::stats::{RelayConnectionStats, RelayStatsSnapshot};
+pub use self::relay::supervisor::RestartPolicy;
+pub use self::relay::{
+    Relay, RelayNotification, RelayStatus, ReqMapping, SubscriptionCloseReason, SubscriptionCost,
+    SubscriptionLifecycle, SubscriptionUpdate, MESSAGE_LOG_TARGET,
 };
-pub use self::relay::stats::RelayConnectionStats;
-pub use self::relay::{Relay, RelayNotification, RelayStatus};
@@ -0,0 +1,147 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay Pool state checkpoint
+
+use std::collections::HashMap;
+
+use nostr::serde_json::{self, json, Map, Value};
+use nostr::{SubscriptionId, Timestamp, Url};
+
+use crate::relay::RelayStatsSnapshot;
+
+/// Database key of the [`PoolCheckpoint`]
+pub const CHECKPOINT_KEY: &str = "nostr-relay-pool/checkpoint";
+
+const CHECKPOINT_VERSION: u64 = 1;
+
+/// Adaptive state of a relay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayCheckpoint {
+    /// Cumulative connection stats
+    pub stats: RelayStatsSnapshot,
+    /// `created_at` of the most recent event received for each subscription
+    pub cursors: HashMap<SubscriptionId, Timestamp>,
+}
+
+/// Adaptive state of the relay pool
+///
+/// Persisted to the database to survive restarts and crashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolCheckpoint {
+    /// When the checkpoint has been taken
+    pub created_at: Timestamp,
+    /// Relays state
+    pub relays: HashMap<Url, RelayCheckpoint>,
+}
+
+impl PoolCheckpoint {
+    /// Serialize checkpoint
+    pub fn encode(&self) -> Vec<u8> {
+        let relays: Map<String, Value> = self
+            .relays
+            .iter()
+            .map(|(url, relay)| {
+                let cursors: Map<String, Value> = relay
+                    .cursors
+                    .iter()
+                    .map(|(id, timestamp)| (id.to_string(), json!(timestamp.as_u64())))
+                    .collect();
+                let value: Value = json!({
+                    "stats": {
+                        "attempts": relay.stats.attempts,
+                        "success": relay.stats.success,
+                        "bytes_sent": relay.stats.bytes_sent,
+                        "bytes_received": relay.stats.bytes_received,
+                        "first_connection_timestamp": relay.stats.first_connection_timestamp.as_u64(),
+                    },
+                    "cursors": cursors,
+                });
+                (url.to_string(), value)
+            })
+            .collect();
+        json!({
+            "version": CHECKPOINT_VERSION,
+            "created_at": self.created_at.as_u64(),
+            "relays": relays,
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Deserialize checkpoint
+    ///
+    /// Return `None` if the checkpoint is corrupted or has an unknown version.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let value: Value = serde_json::from_slice(bytes).ok()?;
+        if value.get("version")?.as_u64()? != CHECKPOINT_VERSION {
+            return None;
+        }
+
+        let created_at = Timestamp::from(value.get("created_at")?.as_u64()?);
+
+        let mut relays: HashMap<Url, RelayCheckpoint> = HashMap::new();
+        for (url, relay) in value.get("relays")?.as_object()?.iter() {
+            let url: Url = Url::parse(url).ok()?;
+
+            let stats: &Value = relay.get("stats")?;
+            let field = |name: &str| -> Option<u64> { stats.get(name)?.as_u64() };
+            let stats = RelayStatsSnapshot {
+                attempts: field("attempts")? as usize,
+                success: field("success")? as usize,
+                bytes_sent: field("bytes_sent")? as usize,
+                bytes_received: field("bytes_received")? as usize,
+                first_connection_timestamp: Timestamp::from(field("first_connection_timestamp")?),
+            };
+
+            let mut cursors: HashMap<SubscriptionId, Timestamp> = HashMap::new();
+            for (id, timestamp) in relay.get("cursors")?.as_object()?.iter() {
+                cursors.insert(
+                    SubscriptionId::new(id),
+                    Timestamp::from(timestamp.as_u64()?),
+                );
+            }
+
+            relays.insert(url, RelayCheckpoint { stats, cursors });
+        }
+
+        Some(Self { created_at, relays })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_encode_decode() {
+        let mut cursors = HashMap::new();
+        cursors.insert(SubscriptionId::new("feed"), Timestamp::from(1_700_000_000));
+
+        let mut relays = HashMap::new();
+        relays.insert(
+            Url::parse("wss://relay.damus.io").unwrap(),
+            RelayCheckpoint {
+                stats: RelayStatsSnapshot {
+                    attempts: 10,
+                    success: 8,
+                    bytes_sent: 1024,
+                    bytes_received: 4096,
+                    first_connection_timestamp: Timestamp::from(1_600_000_000),
+                },
+                cursors,
+            },
+        );
+
+        let checkpoint = PoolCheckpoint {
+            created_at: Timestamp::from(1_700_000_100),
+            relays,
+        };
+        let bytes = checkpoint.encode();
+        assert_eq!(PoolCheckpoint::decode(&bytes), Some(checkpoint));
+
+        // Corrupted (i.e. partially written) checkpoint
+        assert_eq!(PoolCheckpoint::decode(&bytes[..bytes.len() / 2]), None);
+    }
+}
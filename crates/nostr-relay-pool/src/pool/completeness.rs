@@ -0,0 +1,184 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Query completeness estimation
+
+use std::collections::{BTreeMap, HashSet};
+
+use nostr::{EventId, Url};
+
+/// Comparison of the result sets returned by each relay for the same query
+///
+/// Only relays that sent `EOSE` are compared: the others are reported as incomplete.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompletenessReport {
+    sets: BTreeMap<Url, HashSet<EventId>>,
+    incomplete: HashSet<Url>,
+    union: HashSet<EventId>,
+}
+
+impl CompletenessReport {
+    pub(crate) fn new(sets: BTreeMap<Url, HashSet<EventId>>, incomplete: HashSet<Url>) -> Self {
+        let union: HashSet<EventId> = sets.values().flatten().copied().collect();
+        Self {
+            sets,
+            incomplete,
+            union,
+        }
+    }
+
+    /// Relays that sent `EOSE` (sorted)
+    pub fn relays(&self) -> impl Iterator<Item = &Url> {
+        self.sets.keys()
+    }
+
+    /// Relays that didn't send `EOSE` before the timeout or failed
+    pub fn incomplete_relays(&self) -> &HashSet<Url> {
+        &self.incomplete
+    }
+
+    /// Number of distinct events received from all the relays
+    pub fn total(&self) -> usize {
+        self.union.len()
+    }
+
+    /// Number of events received from a relay
+    pub fn count(&self, url: &Url) -> Option<usize> {
+        self.sets.get(url).map(|set| set.len())
+    }
+
+    /// Fraction of the distinct events returned by a relay (from `0.0` to `1.0`)
+    pub fn relay_coverage(&self, url: &Url) -> Option<f64> {
+        let set = self.sets.get(url)?;
+        Some(ratio(set.len(), self.union.len()))
+    }
+
+    /// Overlap between the result sets of two relays (Jaccard index, from `0.0` to `1.0`)
+    pub fn overlap(&self, a: &Url, b: &Url) -> Option<f64> {
+        let a = self.sets.get(a)?;
+        let b = self.sets.get(b)?;
+        Some(jaccard(a, b))
+    }
+
+    /// Overlap matrix
+    ///
+    /// Rows and columns follow the order of [`CompletenessReport::relays`].
+    pub fn overlap_matrix(&self) -> Vec<Vec<f64>> {
+        self.sets
+            .values()
+            .map(|a| self.sets.values().map(|b| jaccard(a, b)).collect())
+            .collect()
+    }
+
+    /// Consistency score: mean overlap between every pair of relays (from `0.0` to `1.0`)
+    ///
+    /// `1.0` means that all the relays returned the same events.
+    pub fn consistency(&self) -> f64 {
+        let sets: Vec<&HashSet<EventId>> = self.sets.values().collect();
+        let mut sum: f64 = 0.0;
+        let mut pairs: usize = 0;
+        for (i, a) in sets.iter().enumerate() {
+            for b in sets.iter().skip(i + 1) {
+                sum += jaccard(a, b);
+                pairs += 1;
+            }
+        }
+
+        if pairs == 0 {
+            1.0
+        } else {
+            sum / pairs as f64
+        }
+    }
+
+    /// Estimated completeness of the merged result (from `0.0` to `1.0`)
+    ///
+    /// The number of existing events is estimated with the capture-recapture method,
+    /// averaging the estimate of every pair of overlapping relays.
+    /// A low value suggests to query more relays for this kind of data.
+    ///
+    /// Return `None` if there aren't at least two overlapping relays.
+    pub fn estimated_completeness(&self) -> Option<f64> {
+        let sets: Vec<&HashSet<EventId>> = self.sets.values().collect();
+        let mut sum: f64 = 0.0;
+        let mut estimates: usize = 0;
+        for (i, a) in sets.iter().enumerate() {
+            for b in sets.iter().skip(i + 1) {
+                let common: usize = a.intersection(b).count();
+                if common > 0 {
+                    sum += (a.len() * b.len()) as f64 / common as f64;
+                    estimates += 1;
+                }
+            }
+        }
+
+        if estimates == 0 {
+            return None;
+        }
+
+        let estimated_total: f64 = sum / estimates as f64;
+        Some((self.union.len() as f64 / estimated_total).min(1.0))
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+fn jaccard(a: &HashSet<EventId>, b: &HashSet<EventId>) -> f64 {
+    let common: usize = a.intersection(b).count();
+    ratio(common, a.len() + b.len() - common)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u8) -> EventId {
+        EventId::from_slice(&[n; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_completeness_report() {
+        let a = Url::parse("wss://relay.damus.io").unwrap();
+        let b = Url::parse("wss://nos.lol").unwrap();
+        let c = Url::parse("wss://relay.nostr.band").unwrap();
+
+        let mut sets = BTreeMap::new();
+        sets.insert(a.clone(), (0..8).map(id).collect());
+        sets.insert(b.clone(), (4..12).map(id).collect());
+        let mut incomplete = HashSet::new();
+        incomplete.insert(c.clone());
+        let report = CompletenessReport::new(sets, incomplete);
+
+        assert_eq!(report.total(), 12);
+        assert_eq!(report.count(&a), Some(8));
+        assert_eq!(report.count(&c), None);
+        assert!(report.incomplete_relays().contains(&c));
+        assert_eq!(report.overlap(&a, &b), Some(4.0 / 12.0));
+        assert_eq!(report.overlap_matrix()[0][0], 1.0);
+        assert_eq!(report.consistency(), 4.0 / 12.0);
+
+        // Estimated total: 8 * 8 / 4 = 16
+        assert_eq!(report.estimated_completeness(), Some(12.0 / 16.0));
+    }
+
+    #[test]
+    fn test_completeness_report_without_overlap() {
+        let a = Url::parse("wss://relay.damus.io").unwrap();
+        let b = Url::parse("wss://nos.lol").unwrap();
+
+        let mut sets = BTreeMap::new();
+        sets.insert(a, (0..4).map(id).collect());
+        sets.insert(b, (4..8).map(id).collect());
+        let report = CompletenessReport::new(sets, HashSet::new());
+
+        assert_eq!(report.consistency(), 0.0);
+        assert_eq!(report.estimated_completeness(), None);
+    }
+}
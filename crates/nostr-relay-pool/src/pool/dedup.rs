@@ -0,0 +1,383 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event deduplication
+//!
+//! Bounded in-memory layer, shared by all the relays of the pool, that suppresses duplicated events
+//! without querying the database: a ring buffer of the most recent IDs (exact)
+//! and an optional bloom filter for the older ones (probabilistic).
+//!
+//! Optionally, identical content repeatedly posted by the same author (common spam pattern)
+//! is collapsed to the first occurrence.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f64::consts::LN_2;
+use std::mem;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::Hash;
+use nostr::{Event, EventId, Kind, PublicKey, Timestamp};
+use tokio::sync::Mutex;
+
+/// Deduplication options
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DedupOptions {
+    /// Number of recent IDs remembered exactly
+    pub recent: usize,
+    /// Bloom filter `(expected items, false positive rate)` for the IDs evicted from the recent ones
+    pub bloom_filter: Option<(usize, f64)>,
+    /// Window in which identical content of the same author and kind is suppressed
+    pub content_window: Option<Duration>,
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        Self {
+            recent: 10_000,
+            bloom_filter: None,
+            content_window: None,
+        }
+    }
+}
+
+impl DedupOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of recent IDs remembered exactly (default: 10_000)
+    pub fn recent(mut self, capacity: usize) -> Self {
+        self.recent = capacity;
+        self
+    }
+
+    /// Remember also the older IDs in a bloom filter (default: disabled)
+    ///
+    /// Memory stays bounded (two rotating filters sized for `expected_items`), but a false positive
+    /// suppresses a new event: choose a low `false_positive_rate` (i.e. `0.000001`).
+    pub fn bloom_filter(mut self, expected_items: usize, false_positive_rate: f64) -> Self {
+        self.bloom_filter = Some((expected_items, false_positive_rate));
+        self
+    }
+
+    /// Suppress non-replaceable events with the same author, kind and content of an event
+    /// created less than `window` apart (default: disabled)
+    ///
+    /// The first occurrence is kept. The last `recent` contents are remembered.
+    pub fn content_window(mut self, window: Duration) -> Self {
+        self.content_window = Some(window);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct BloomFilter {
+    bits: Vec<u64>,
+    size: u64,
+    hashes: u64,
+    len: usize,
+}
+
+impl BloomFilter {
+    fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n: f64 = expected_items.max(1) as f64;
+        let p: f64 = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let size: u64 = ((-n * p.ln()) / (LN_2 * LN_2)).ceil().max(64.0) as u64;
+        let hashes: u64 = ((size as f64 / n) * LN_2).round().clamp(1.0, 32.0) as u64;
+        Self {
+            bits: vec![0; ((size + 63) / 64) as usize],
+            size,
+            hashes,
+            len: 0,
+        }
+    }
+
+    /// Event IDs are already uniformly distributed: use their bytes as hashes (double hashing)
+    fn indexes(&self, id: &EventId) -> impl Iterator<Item = u64> {
+        let bytes: &[u8] = id.as_bytes();
+        let mut h1: [u8; 8] = [0; 8];
+        let mut h2: [u8; 8] = [0; 8];
+        h1.copy_from_slice(&bytes[..8]);
+        h2.copy_from_slice(&bytes[8..16]);
+        let h1: u64 = u64::from_le_bytes(h1);
+        let h2: u64 = u64::from_le_bytes(h2) | 1;
+        let size: u64 = self.size;
+        (0..self.hashes).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % size)
+    }
+
+    fn insert(&mut self, id: &EventId) {
+        let indexes: Vec<u64> = self.indexes(id).collect();
+        for i in indexes.into_iter() {
+            self.bits[(i / 64) as usize] |= 1 << (i % 64);
+        }
+        self.len += 1;
+    }
+
+    fn contains(&self, id: &EventId) -> bool {
+        self.indexes(id)
+            .all(|i| self.bits[(i / 64) as usize] & (1 << (i % 64)) != 0)
+    }
+}
+
+#[derive(Debug)]
+struct Bloom {
+    current: BloomFilter,
+    previous: BloomFilter,
+    expected_items: usize,
+    false_positive_rate: f64,
+}
+
+impl Bloom {
+    fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        Self {
+            current: BloomFilter::new(expected_items, false_positive_rate),
+            previous: BloomFilter::new(expected_items, false_positive_rate),
+            expected_items,
+            false_positive_rate,
+        }
+    }
+
+    fn insert(&mut self, id: &EventId) {
+        // Full: forget the oldest IDs, to keep the false positive rate
+        if self.current.len >= self.expected_items {
+            let new = BloomFilter::new(self.expected_items, self.false_positive_rate);
+            self.previous = mem::replace(&mut self.current, new);
+        }
+        self.current.insert(id);
+    }
+
+    fn contains(&self, id: &EventId) -> bool {
+        self.current.contains(id) || self.previous.contains(id)
+    }
+}
+
+type ContentKey = (PublicKey, Kind, Sha256Hash);
+
+#[derive(Debug)]
+struct ContentDedup {
+    window: u64,
+    queue: VecDeque<ContentKey>,
+    /// First occurrence of each content
+    first: HashMap<ContentKey, (EventId, Timestamp)>,
+    capacity: usize,
+}
+
+impl ContentDedup {
+    fn new(window: Duration, capacity: usize) -> Self {
+        let capacity: usize = capacity.max(1);
+        Self {
+            window: window.as_secs(),
+            queue: VecDeque::with_capacity(capacity),
+            first: HashMap::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn check_and_insert(&mut self, event: &Event) -> bool {
+        let key: ContentKey = (
+            event.author(),
+            event.kind(),
+            Sha256Hash::hash(event.content().as_bytes()),
+        );
+
+        if let Some((id, created_at)) = self.first.get(&key) {
+            if *id == event.id() {
+                return false;
+            }
+
+            let distance: u64 = created_at.as_u64().abs_diff(event.created_at().as_u64());
+            if distance <= self.window {
+                return true;
+            }
+        }
+
+        // First occurrence (or outside of the window of the previous one)
+        if self
+            .first
+            .insert(key, (event.id(), event.created_at()))
+            .is_none()
+        {
+            self.queue.push_back(key);
+            if self.queue.len() > self.capacity {
+                if let Some(old) = self.queue.pop_front() {
+                    self.first.remove(&old);
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[derive(Debug)]
+struct InnerDedup {
+    queue: VecDeque<EventId>,
+    ids: HashSet<EventId>,
+    capacity: usize,
+    bloom: Option<Bloom>,
+    content: Option<ContentDedup>,
+}
+
+impl InnerDedup {
+    fn new(opts: DedupOptions) -> Self {
+        Self {
+            queue: VecDeque::with_capacity(opts.recent),
+            ids: HashSet::with_capacity(opts.recent),
+            capacity: opts.recent,
+            bloom: opts.bloom_filter.map(|(n, p)| Bloom::new(n, p)),
+            content: opts
+                .content_window
+                .map(|window| ContentDedup::new(window, opts.recent)),
+        }
+    }
+
+    fn is_duplicated_content(&mut self, event: &Event) -> bool {
+        // Replaceable events legitimately republish the same content
+        let kind: Kind = event.kind();
+        if kind.is_replaceable() || kind.is_parameterized_replaceable() {
+            return false;
+        }
+
+        self.content
+            .as_mut()
+            .map_or(false, |content| content.check_and_insert(event))
+    }
+
+    fn check_and_insert(&mut self, id: EventId) -> bool {
+        if self.ids.contains(&id) {
+            return true;
+        }
+
+        let seen: bool = self.bloom.as_ref().map_or(false, |b| b.contains(&id));
+
+        if self.capacity > 0 {
+            self.ids.insert(id);
+            self.queue.push_back(id);
+            if self.queue.len() > self.capacity {
+                if let Some(old) = self.queue.pop_front() {
+                    self.ids.remove(&old);
+                }
+            }
+        }
+
+        if !seen {
+            if let Some(bloom) = self.bloom.as_mut() {
+                bloom.insert(&id);
+            }
+        }
+
+        seen
+    }
+}
+
+/// Event deduplication layer
+#[derive(Debug, Clone)]
+pub(crate) struct EventDedup {
+    inner: Arc<Mutex<InnerDedup>>,
+}
+
+impl EventDedup {
+    pub fn new(opts: DedupOptions) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(InnerDedup::new(opts))),
+        }
+    }
+
+    /// Mark the ID as seen
+    ///
+    /// Return `true` if it was already seen.
+    pub async fn check_and_insert(&self, id: EventId) -> bool {
+        let mut inner = self.inner.lock().await;
+        inner.check_and_insert(id)
+    }
+
+    /// Check if the event repeats the content recently posted by the same author
+    ///
+    /// Return `true` if it must be suppressed. Replaceable events aren't checked.
+    pub async fn is_duplicated_content(&self, event: &Event) -> bool {
+        let mut inner = self.inner.lock().await;
+        inner.is_duplicated_content(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Timestamp;
+
+    use super::*;
+
+    fn id(i: u64) -> EventId {
+        EventId::new(
+            &nostr::Keys::generate().public_key(),
+            Timestamp::from(i),
+            &nostr::Kind::TextNote,
+            &[],
+            "",
+        )
+    }
+
+    #[test]
+    fn test_recent_ids() {
+        let mut dedup = InnerDedup::new(DedupOptions::new().recent(2));
+        let (a, b, c) = (id(1), id(2), id(3));
+        assert!(!dedup.check_and_insert(a));
+        assert!(dedup.check_and_insert(a));
+        assert!(!dedup.check_and_insert(b));
+        assert!(!dedup.check_and_insert(c));
+
+        // Evicted
+        assert!(!dedup.check_and_insert(a));
+    }
+
+    #[test]
+    fn test_content_window() {
+        let keys = nostr::Keys::generate();
+        let note = |content: &str, created_at: u64| {
+            nostr::EventBuilder::text_note(content, [])
+                .custom_created_at(Timestamp::from(created_at))
+                .to_event(&keys)
+                .unwrap()
+        };
+
+        let mut dedup =
+            InnerDedup::new(DedupOptions::new().content_window(Duration::from_secs(60)));
+        let first = note("Buy now!", 1000);
+        assert!(!dedup.is_duplicated_content(&first));
+        assert!(!dedup.is_duplicated_content(&first));
+        assert!(dedup.is_duplicated_content(&note("Buy now!", 1030)));
+        assert!(!dedup.is_duplicated_content(&note("Hello", 1030)));
+
+        // Other author
+        let other = nostr::EventBuilder::text_note("Buy now!", [])
+            .custom_created_at(Timestamp::from(1030))
+            .to_event(&nostr::Keys::generate())
+            .unwrap();
+        assert!(!dedup.is_duplicated_content(&other));
+
+        // Outside of the window
+        assert!(!dedup.is_duplicated_content(&note("Buy now!", 2000)));
+
+        // Disabled
+        let mut dedup = InnerDedup::new(DedupOptions::new());
+        assert!(!dedup.is_duplicated_content(&first));
+        assert!(!dedup.is_duplicated_content(&note("Buy now!", 1030)));
+    }
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut dedup = InnerDedup::new(DedupOptions::new().recent(1).bloom_filter(100, 0.000001));
+        let ids: Vec<EventId> = (0..50).map(id).collect();
+        for id in ids.iter() {
+            assert!(!dedup.check_and_insert(*id));
+        }
+
+        // Evicted from the recent IDs but still in the bloom filter
+        for id in ids.iter() {
+            assert!(dedup.check_and_insert(*id));
+        }
+    }
+}
@@ -2,13 +2,13 @@
 // Copyright (c) 2023-2024 Rust Nostr Developers
 // Distributed under the MIT software license
 
-use async_utility::thread;
 use nostr::message::MessageHandleError;
 use nostr::types::url;
 use nostr_database::DatabaseError;
 use thiserror::Error;
 
 use crate::relay;
+use crate::runtime::thread;
 
 /// [`RelayPool`](super::RelayPool) error
 #[derive(Debug, Error)]
@@ -0,0 +1,185 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Filtering policy
+//!
+//! Blacklist of public keys and event IDs, checked on received events before verification,
+//! and optional allowlist of the relays where messages can be sent.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use nostr::{EventId, PublicKey, Url};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Default)]
+struct InnerFilteringPolicy {
+    public_keys: HashSet<PublicKey>,
+    ids: HashSet<EventId>,
+    /// `None` means that all relays are allowed
+    relays: Option<HashSet<Url>>,
+}
+
+impl InnerFilteringPolicy {
+    fn is_blocked(&self, id: &EventId, public_key: &PublicKey) -> bool {
+        self.ids.contains(id) || self.public_keys.contains(public_key)
+    }
+
+    fn is_relay_allowed(&self, url: &Url) -> bool {
+        self.relays
+            .as_ref()
+            .map_or(true, |relays| relays.contains(url))
+    }
+}
+
+/// Filtering policy, shared by all the relays of the pool
+///
+/// Changes are applied at runtime to all the relays.
+#[derive(Debug, Clone, Default)]
+pub struct FilteringPolicy {
+    inner: Arc<RwLock<InnerFilteringPolicy>>,
+}
+
+impl FilteringPolicy {
+    /// New empty policy (nothing blocked, all relays allowed)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the events authored by these public keys
+    pub async fn add_public_keys<I>(&self, public_keys: I)
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let mut inner = self.inner.write().await;
+        inner.public_keys.extend(public_keys);
+    }
+
+    /// Remove public keys from the blacklist
+    pub async fn remove_public_keys<'a, I>(&self, public_keys: I)
+    where
+        I: IntoIterator<Item = &'a PublicKey>,
+    {
+        let mut inner = self.inner.write().await;
+        for public_key in public_keys.into_iter() {
+            inner.public_keys.remove(public_key);
+        }
+    }
+
+    /// Drop the events with these IDs
+    pub async fn add_ids<I>(&self, ids: I)
+    where
+        I: IntoIterator<Item = EventId>,
+    {
+        let mut inner = self.inner.write().await;
+        inner.ids.extend(ids);
+    }
+
+    /// Remove event IDs from the blacklist
+    pub async fn remove_ids<'a, I>(&self, ids: I)
+    where
+        I: IntoIterator<Item = &'a EventId>,
+    {
+        let mut inner = self.inner.write().await;
+        for id in ids.into_iter() {
+            inner.ids.remove(id);
+        }
+    }
+
+    /// Add relays to the allowlist
+    ///
+    /// The first call enables the allowlist: messages to the relays not in the list are refused
+    /// (except `CLOSE`, to not leave subscriptions open).
+    pub async fn add_allowed_relays<I>(&self, urls: I)
+    where
+        I: IntoIterator<Item = Url>,
+    {
+        let mut inner = self.inner.write().await;
+        inner.relays.get_or_insert_with(HashSet::new).extend(urls);
+    }
+
+    /// Remove relays from the allowlist
+    ///
+    /// The allowlist stays enabled also if it becomes empty: use [`FilteringPolicy::disable_relay_allowlist`]
+    /// to allow all the relays again.
+    pub async fn remove_allowed_relays<'a, I>(&self, urls: I)
+    where
+        I: IntoIterator<Item = &'a Url>,
+    {
+        let mut inner = self.inner.write().await;
+        if let Some(relays) = inner.relays.as_mut() {
+            for url in urls.into_iter() {
+                relays.remove(url);
+            }
+        }
+    }
+
+    /// Allow all the relays
+    pub async fn disable_relay_allowlist(&self) {
+        let mut inner = self.inner.write().await;
+        inner.relays = None;
+    }
+
+    /// Check if the event must be dropped
+    pub async fn is_blocked(&self, id: &EventId, public_key: &PublicKey) -> bool {
+        let inner = self.inner.read().await;
+        inner.is_blocked(id, public_key)
+    }
+
+    /// Check if messages can be sent to the relay
+    pub async fn is_relay_allowed(&self, url: &Url) -> bool {
+        let inner = self.inner.read().await;
+        inner.is_relay_allowed(url)
+    }
+
+    /// Remove all the public keys and IDs from the blacklist and allow all the relays
+    pub async fn clear(&self) {
+        let mut inner = self.inner.write().await;
+        *inner = InnerFilteringPolicy::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{Keys, Kind, Timestamp};
+
+    use super::*;
+
+    #[test]
+    fn test_blacklist() {
+        let keys = Keys::generate();
+        let id = EventId::new(
+            &keys.public_key(),
+            Timestamp::from(0),
+            &Kind::TextNote,
+            &[],
+            "",
+        );
+        let other = Keys::generate().public_key();
+
+        let mut policy = InnerFilteringPolicy::default();
+        assert!(!policy.is_blocked(&id, &keys.public_key()));
+
+        policy.public_keys.insert(keys.public_key());
+        assert!(policy.is_blocked(&id, &keys.public_key()));
+
+        policy.public_keys.clear();
+        policy.ids.insert(id);
+        assert!(policy.is_blocked(&id, &other));
+    }
+
+    #[test]
+    fn test_relay_allowlist() {
+        let url = Url::parse("wss://relay.damus.io").unwrap();
+
+        let mut policy = InnerFilteringPolicy::default();
+        assert!(policy.is_relay_allowed(&url));
+
+        policy.relays = Some(HashSet::new());
+        assert!(!policy.is_relay_allowed(&url));
+
+        policy.relays = Some(HashSet::from([url.clone()]));
+        assert!(policy.is_relay_allowed(&url));
+    }
+}
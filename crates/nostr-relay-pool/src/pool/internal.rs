@@ -4,23 +4,32 @@
 
 //! Relay Pool
 
-use std::collections::btree_set::IntoIter;
-use std::collections::{BTreeSet, HashMap, HashSet};
-use std::iter::Rev;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::mem;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
-use async_utility::{thread, time};
 use atomic_destructor::AtomicDestroyer;
 use nostr::{ClientMessage, Event, EventId, Filter, SubscriptionId, Timestamp, TryIntoUrl, Url};
 use nostr_database::{DynNostrDatabase, IntoNostrDatabase, Order};
 use tokio::sync::{broadcast, Mutex, RwLock};
 
+use super::completeness::CompletenessReport;
+use super::dedup::EventDedup;
+use super::filtering::FilteringPolicy;
+use super::kinds::NotificationKinds;
+#[cfg(all(feature = "network-monitor", not(target_arch = "wasm32")))]
+use super::network::NetworkMonitor;
 use super::options::RelayPoolOptions;
+use super::output::Output;
+use super::quota::{Slot, SubscriptionQuota};
+use super::reconcile::reconcile;
+use super::verifier::EventVerifier;
 use super::{Error, RelayPoolNotification};
 use crate::relay::options::{FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions};
-use crate::relay::Relay;
+use crate::relay::{self, Relay, RelayStatus};
+use crate::runtime::{thread, time};
 use crate::SubscribeOptions;
 
 #[derive(Debug, Clone)]
@@ -29,6 +38,13 @@ pub struct InternalRelayPool {
     relays: Arc<RwLock<HashMap<Url, Relay>>>,
     notification_sender: broadcast::Sender<RelayPoolNotification>,
     subscriptions: Arc<RwLock<HashMap<SubscriptionId, Vec<Filter>>>>,
+    verifier: EventVerifier,
+    dedup: EventDedup,
+    filtering: FilteringPolicy,
+    notification_kinds: Arc<RwLock<NotificationKinds>>,
+    quota: Arc<Mutex<SubscriptionQuota>>,
+    #[cfg(all(feature = "network-monitor", not(target_arch = "wasm32")))]
+    network_monitor: Arc<AtomicBool>,
     // opts: RelayPoolOptions,
 }
 
@@ -59,6 +75,17 @@ impl InternalRelayPool {
             relays: Arc::new(RwLock::new(HashMap::new())),
             notification_sender,
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            verifier: EventVerifier::with_policy(
+                opts.verify_events,
+                opts.verification_policy,
+                opts.verification_threads,
+            ),
+            dedup: EventDedup::new(opts.dedup),
+            filtering: FilteringPolicy::new(),
+            notification_kinds: Arc::new(RwLock::new(NotificationKinds::default())),
+            quota: Arc::new(Mutex::new(SubscriptionQuota::default())),
+            #[cfg(all(feature = "network-monitor", not(target_arch = "wasm32")))]
+            network_monitor: Arc::new(AtomicBool::new(false)),
             //opts,
         }
     }
@@ -99,6 +126,22 @@ impl InternalRelayPool {
         self.database.clone()
     }
 
+    pub fn filtering(&self) -> FilteringPolicy {
+        self.filtering.clone()
+    }
+
+    pub async fn notification_kinds(&self) -> NotificationKinds {
+        self.notification_kinds.read().await.clone()
+    }
+
+    pub async fn set_notification_kinds(&self, kinds: NotificationKinds) {
+        let relays = self.relays.read().await;
+        *self.notification_kinds.write().await = kinds.clone();
+        for relay in relays.values() {
+            relay.set_notification_kinds(kinds.clone()).await;
+        }
+    }
+
     pub async fn relays(&self) -> HashMap<Url, Relay> {
         let relays = self.relays.read().await;
         relays.clone()
@@ -150,16 +193,48 @@ impl InternalRelayPool {
     {
         let url: Url = url.try_into_url()?;
         let mut relays = self.relays.write().await;
-        if !relays.contains_key(&url) {
-            let relay = Relay::custom(url, self.database.clone(), opts);
-            relay
-                .set_notification_sender(Some(self.notification_sender.clone()))
-                .await;
-            relays.insert(relay.url(), relay);
-            Ok(true)
-        } else {
-            Ok(false)
+
+        let previous_status: RelayStatus = match relays.get(&url) {
+            Some(relay) => {
+                let current: RelayOptions = relay.opts();
+
+                // Update options of the live relay
+                if current.has_same_connection_opts(&opts) {
+                    return Ok(current.merge(&opts));
+                }
+
+                // Proxy, limits or send defaults changed: replace the relay
+                let status: RelayStatus = relay.status().await;
+                relay.terminate().await?;
+                status
+            }
+            None => RelayStatus::Initialized,
+        };
+
+        let relay = Relay::custom(url, self.database.clone(), opts);
+        relay
+            .set_notification_sender(Some(self.notification_sender.clone()))
+            .await;
+        relay.set_event_verifier(Some(self.verifier.clone())).await;
+        relay.set_event_dedup(Some(self.dedup.clone())).await;
+        relay
+            .set_filtering_policy(Some(self.filtering.clone()))
+            .await;
+        relay
+            .set_notification_kinds(self.notification_kinds.read().await.clone())
+            .await;
+        relays.insert(relay.url(), relay.clone());
+        drop(relays);
+
+        // Reconnect if the replaced relay was in use
+        if !matches!(
+            previous_status,
+            RelayStatus::Initialized | RelayStatus::Stopped | RelayStatus::Terminated
+        ) {
+            self.connect_relay(&relay, None).await;
         }
+
+        Ok(true)
     }
 
     pub async fn remove_relay<U>(&self, url: U) -> Result<(), Error>
@@ -251,7 +326,9 @@ impl InternalRelayPool {
         if urls.len() == 1 {
             let url: Url = urls.into_iter().next().ok_or(Error::RelayNotFound)?;
             let relay: &Relay = relays.get(&url).ok_or(Error::RelayNotFound)?;
-            relay.batch_msg(msgs, opts).await?;
+            relay
+                .batch_msg(msgs, relay.opts().get_send_opts(opts))
+                .await?;
         } else {
             // Check if urls set contains ONLY already added relays
             if !urls.iter().all(|url| relays.contains_key(url)) {
@@ -264,6 +341,7 @@ impl InternalRelayPool {
             for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
                 let msgs = msgs.clone();
                 let sent = sent_to_at_least_one_relay.clone();
+                let opts: RelaySendOptions = relay.opts().get_send_opts(opts);
                 let handle = thread::spawn(async move {
                     match relay.batch_msg(msgs, opts).await {
                         Ok(_) => {
@@ -287,7 +365,7 @@ impl InternalRelayPool {
         Ok(())
     }
 
-    pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<EventId, Error> {
+    pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<Output, Error> {
         let relays: HashMap<Url, Relay> = self.relays().await;
         self.send_event_to(relays.into_keys(), event, opts).await
     }
@@ -306,15 +384,18 @@ impl InternalRelayPool {
         urls: I,
         event: Event,
         opts: RelaySendOptions,
-    ) -> Result<EventId, Error>
+    ) -> Result<Output, Error>
     where
         I: IntoIterator<Item = U>,
         U: TryIntoUrl,
         Error: From<<U as TryIntoUrl>::Err>,
     {
-        let event_id: EventId = event.id;
-        self.batch_event_to(urls, vec![event], opts).await?;
-        Ok(event_id)
+        let event_id: EventId = event.id();
+        let mut output: Output = Output::default();
+        for (url, res) in self.batch_event_to_each(urls, vec![event], opts).await? {
+            output.insert(url, res.map(|()| event_id));
+        }
+        Ok(output)
     }
 
     pub async fn batch_event_to<I, U>(
@@ -323,6 +404,43 @@ impl InternalRelayPool {
         events: Vec<Event>,
         opts: RelaySendOptions,
     ) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let results = self.batch_event_to_each(urls, events, opts).await?;
+
+        // If passed only 1 url, return the error of the relay
+        if results.len() == 1 {
+            for (.., res) in results.into_iter() {
+                res?;
+            }
+            return Ok(());
+        }
+
+        let mut sent_to_at_least_one_relay: bool = false;
+        for (url, res) in results.into_iter() {
+            match res {
+                Ok(()) => sent_to_at_least_one_relay = true,
+                Err(e) => tracing::error!("Impossible to send event to {url}: {e}"),
+            }
+        }
+
+        if !sent_to_at_least_one_relay {
+            return Err(Error::EventNotPublished);
+        }
+
+        Ok(())
+    }
+
+    /// Send events to each relay, collecting the result of each relay
+    async fn batch_event_to_each<I, U>(
+        &self,
+        urls: I,
+        events: Vec<Event>,
+        opts: RelaySendOptions,
+    ) -> Result<Vec<(Url, Result<(), relay::Error>)>, Error>
     where
         I: IntoIterator<Item = U>,
         U: TryIntoUrl,
@@ -351,44 +469,38 @@ impl InternalRelayPool {
             return Err(Error::NoRelays);
         }
 
+        // Check if urls set contains ONLY already added relays
+        if !urls.iter().all(|url| relays.contains_key(url)) {
+            return Err(Error::RelayNotFound);
+        }
+
         // If passed only 1 url, not use threads
         if urls.len() == 1 {
             let url: Url = urls.into_iter().next().ok_or(Error::RelayNotFound)?;
             let relay: &Relay = relays.get(&url).ok_or(Error::RelayNotFound)?;
-            relay.batch_event(events, opts).await?;
-        } else {
-            // Check if urls set contains ONLY already added relays
-            if !urls.iter().all(|url| relays.contains_key(url)) {
-                return Err(Error::RelayNotFound);
-            }
-
-            let sent_to_at_least_one_relay: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
-            let mut handles = Vec::with_capacity(urls.len());
-
-            for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
-                let events = events.clone();
-                let sent = sent_to_at_least_one_relay.clone();
-                let handle = thread::spawn(async move {
-                    match relay.batch_event(events, opts).await {
-                        Ok(_) => {
-                            sent.store(true, Ordering::SeqCst);
-                        }
-                        Err(e) => tracing::error!("Impossible to send event to {url}: {e}"),
-                    }
-                })?;
-                handles.push(handle);
-            }
+            let res = relay
+                .batch_event(events, relay.opts().get_send_opts(opts))
+                .await;
+            return Ok(vec![(url, res)]);
+        }
 
-            for handle in handles.into_iter() {
-                handle.join().await?;
-            }
+        let mut handles = Vec::with_capacity(urls.len());
 
-            if !sent_to_at_least_one_relay.load(Ordering::SeqCst) {
-                return Err(Error::EventNotPublished);
-            }
+        for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
+            let events: Vec<Event> = events.clone();
+            let opts: RelaySendOptions = relay.opts().get_send_opts(opts);
+            let handle = thread::spawn(async move {
+                let res = relay.batch_event(events, opts).await;
+                (url, res)
+            })?;
+            handles.push(handle);
         }
 
-        Ok(())
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles.into_iter() {
+            results.push(handle.join().await?);
+        }
+        Ok(results)
     }
 
     pub async fn subscribe(&self, filters: Vec<Filter>, opts: SubscribeOptions) -> SubscriptionId {
@@ -407,13 +519,22 @@ impl InternalRelayPool {
         let relays = self.relays().await;
 
         // Check if isn't auto-closing subscription
-        if !opts.is_auto_closing() {
+        let auto_closing: bool = opts.is_auto_closing();
+        if !auto_closing {
             // Update pool subscriptions
             self.update_subscription(id.clone(), filters.clone()).await;
+            self.quota
+                .lock()
+                .await
+                .register(id.clone(), opts.get_priority());
         }
 
         // Subscribe
         for relay in relays.values() {
+            if !auto_closing && !self.acquire_slot(relay, &id, opts.get_priority()).await {
+                continue;
+            }
+
             if let Err(e) = relay
                 .subscribe_with_id(id.clone(), filters.clone(), opts)
                 .await
@@ -423,26 +544,201 @@ impl InternalRelayPool {
         }
     }
 
+    pub async fn update_subscription_filters(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+        opts: RelaySendOptions,
+    ) {
+        let relays = self.relays().await;
+        self.update_subscription(id.clone(), filters.clone()).await;
+        for relay in relays.values() {
+            if let Err(e) = relay
+                .update_subscription_filters(
+                    id.clone(),
+                    filters.clone(),
+                    relay.opts().get_send_opts(opts),
+                )
+                .await
+            {
+                tracing::error!("{e}");
+            }
+        }
+    }
+
     pub async fn unsubscribe(&self, id: SubscriptionId, opts: RelaySendOptions) {
         let relays = self.relays().await;
         self.remove_subscription(&id).await;
+        self.quota.lock().await.remove(&id);
         for relay in relays.values() {
-            if let Err(e) = relay.unsubscribe(id.clone(), opts).await {
+            if let Err(e) = relay
+                .unsubscribe(id.clone(), relay.opts().get_send_opts(opts))
+                .await
+            {
                 tracing::error!("{e}");
             }
+
+            // Give the slot to the subscriptions waiting for it
+            self.fill_slots(relay).await;
         }
     }
 
     pub async fn unsubscribe_all(&self, opts: RelaySendOptions) {
         let relays = self.relays().await;
         self.remove_all_subscriptions().await;
+        self.quota.lock().await.clear();
         for relay in relays.values() {
-            if let Err(e) = relay.unsubscribe_all(opts).await {
+            if let Err(e) = relay
+                .unsubscribe_all(relay.opts().get_send_opts(opts))
+                .await
+            {
                 tracing::error!("{e}");
             }
         }
     }
 
+    /// Request a slot for a pool subscription on the relay, preempting lower-priority subscriptions if needed
+    ///
+    /// Return `false` if the relay is full.
+    async fn acquire_slot(&self, relay: &Relay, id: &SubscriptionId, priority: u8) -> bool {
+        let limit: Option<usize> = relay.max_subscriptions().await;
+        let active: HashMap<SubscriptionId, Vec<Filter>> = relay.subscriptions().await;
+        let slot: Slot = self
+            .quota
+            .lock()
+            .await
+            .slot(id, priority, active.keys(), limit);
+
+        match slot {
+            Slot::Free => {
+                self.set_served(id, relay.url()).await;
+                true
+            }
+            Slot::Preempt(victim) => {
+                tracing::debug!(
+                    "{} reached max subscriptions: {id} preempts {victim}",
+                    relay.url()
+                );
+                if let Err(e) = relay
+                    .unsubscribe(
+                        victim.clone(),
+                        relay.opts().get_send_opts(RelaySendOptions::default()),
+                    )
+                    .await
+                {
+                    tracing::error!("{e}");
+                }
+                self.set_missing(&victim, relay.url()).await;
+                self.set_served(id, relay.url()).await;
+                true
+            }
+            Slot::Full => {
+                tracing::debug!("{} reached max subscriptions: {id} not sent", relay.url());
+                self.set_missing(id, relay.url()).await;
+                false
+            }
+        }
+    }
+
+    /// Subscribe the highest-priority subscriptions not served by the relay, while slots are available
+    async fn fill_slots(&self, relay: &Relay) {
+        let url: Url = relay.url();
+        loop {
+            let (id, priority) = {
+                let quota = self.quota.lock().await;
+                match quota.next_for(&url) {
+                    Some(id) => {
+                        let priority: u8 = quota.priority(&id);
+                        (id, priority)
+                    }
+                    None => break,
+                }
+            };
+
+            let filters: Vec<Filter> = match self.subscription(&id).await {
+                Some(filters) => filters,
+                None => break,
+            };
+
+            if !self.acquire_slot(relay, &id, priority).await {
+                break;
+            }
+
+            let opts: SubscribeOptions = SubscribeOptions::default()
+                .send_opts(relay.opts().get_send_opts(RelaySendOptions::default()))
+                .priority(priority);
+            if let Err(e) = relay.subscribe_with_id(id, filters, opts).await {
+                tracing::error!("{e}");
+                break;
+            }
+        }
+    }
+
+    async fn set_missing(&self, id: &SubscriptionId, url: Url) {
+        let missing: Option<HashSet<Url>> = self.quota.lock().await.set_missing(id, url);
+        if let Some(missing) = missing {
+            self.notify_degraded(id, missing);
+        }
+    }
+
+    async fn set_served(&self, id: &SubscriptionId, url: Url) {
+        let missing: Option<HashSet<Url>> = self.quota.lock().await.set_served(id, &url);
+        if let Some(missing) = missing {
+            self.notify_degraded(id, missing);
+        }
+    }
+
+    fn notify_degraded(&self, id: &SubscriptionId, missing: HashSet<Url>) {
+        let _ = self
+            .notification_sender
+            .send(RelayPoolNotification::SubscriptionDegraded {
+                subscription_id: id.clone(),
+                missing,
+            });
+    }
+
+    pub async fn count_events(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Duration,
+    ) -> Result<usize, Error> {
+        let relays: HashMap<Url, Relay> = self.relays().await;
+
+        if relays.is_empty() {
+            return Err(Error::NoRelays);
+        }
+
+        let mut handles = Vec::with_capacity(relays.len());
+        for (url, relay) in relays.into_iter() {
+            let filters: Vec<Filter> = filters.clone();
+            let handle = thread::spawn(async move {
+                let res = relay.count_events(filters, timeout).await;
+                (url, res)
+            })?;
+            handles.push(handle);
+        }
+
+        let mut count: Option<usize> = None;
+        let mut fallback: bool = false;
+        for handle in handles.into_iter() {
+            let (url, res) = handle.join().await?;
+            match res {
+                Ok(c) => count = Some(count.map_or(c, |n| n.max(c))),
+                Err(e) => {
+                    tracing::warn!("Impossible to count events on {url}: {e}");
+                    fallback = true;
+                }
+            }
+        }
+
+        if fallback {
+            let local: usize = self.database.count(filters).await?;
+            count = Some(count.map_or(local, |n| n.max(local)));
+        }
+
+        Ok(count.unwrap_or_default())
+    }
+
     pub async fn get_events_from<I, U>(
         &self,
         urls: I,
@@ -468,72 +764,92 @@ impl InternalRelayPool {
         if urls.len() == 1 {
             let url: Url = urls.into_iter().next().ok_or(Error::RelayNotFound)?;
             let relay: Relay = self.internal_relay(&url).await?;
-            Ok(relay.get_events_of(filters, timeout, opts).await?)
+            let events: Vec<Event> = relay.get_events_of(filters.clone(), timeout, opts).await?;
+            Ok(reconcile(events, &filters))
         } else {
-            let relays: HashMap<Url, Relay> = self.relays().await;
-
-            // Check if urls set contains ONLY already added relays
-            if !urls.iter().all(|url| relays.contains_key(url)) {
-                return Err(Error::RelayNotFound);
-            }
-
-            let stored_events: Vec<Event> = self
-                .database
-                .query(filters.clone(), Order::Desc)
+            self.get_events_with_report(urls, filters, timeout, opts)
                 .await
-                .unwrap_or_default();
-
-            // Compose IDs and Events collections
-            let ids: Arc<Mutex<HashSet<EventId>>> =
-                Arc::new(Mutex::new(stored_events.iter().map(|e| e.id()).collect()));
-            let events: Arc<Mutex<BTreeSet<Event>>> =
-                Arc::new(Mutex::new(stored_events.into_iter().collect()));
+                .map(|(events, ..)| events)
+        }
+    }
 
-            // Filter relays and start query
-            let mut handles = Vec::with_capacity(urls.len());
-            for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
-                let filters = filters.clone();
-                let ids = ids.clone();
-                let events = events.clone();
-                let handle = thread::spawn(async move {
-                    if let Err(e) = relay
-                        .get_events_of_with_callback(filters, timeout, opts, |event| async {
-                            let mut ids = ids.lock().await;
-                            if !ids.contains(&event.id()) {
-                                let mut events = events.lock().await;
-                                ids.insert(event.id());
-                                events.insert(event);
-                            }
-                        })
-                        .await
-                    {
-                        tracing::error!("Failed to get events from {url}: {e}");
-                    }
-                })?;
-                handles.push(handle);
-            }
+    pub async fn get_events_with_report(
+        &self,
+        urls: HashSet<Url>,
+        filters: Vec<Filter>,
+        timeout: Duration,
+        opts: FilterOptions,
+    ) -> Result<(Vec<Event>, CompletenessReport), Error> {
+        // Check if urls set is empty
+        if urls.is_empty() {
+            return Err(Error::NoRelaysSpecified);
+        }
 
-            // Join threads
-            for handle in handles.into_iter() {
-                handle.join().await?;
-            }
+        let relays: HashMap<Url, Relay> = self.relays().await;
 
-            // Lock events, iterate set and revert order (events are sorted in ascending order in the BTreeSet)
-            let events: BTreeSet<Event> = events.lock().await.clone();
-            let iter: Rev<IntoIter<Event>> = events.into_iter().rev();
+        // Check if urls set contains ONLY already added relays
+        if !urls.iter().all(|url| relays.contains_key(url)) {
+            return Err(Error::RelayNotFound);
+        }
 
-            // Check how many filters are passed and return the limit
-            let limit: Option<usize> = match (filters.len(), filters.first()) {
-                (1, Some(filter)) => filter.limit,
-                _ => None,
-            };
+        let stored_events: Vec<Event> = self
+            .database
+            .query(filters.clone(), Order::Desc)
+            .await
+            .unwrap_or_default();
+        let stored_events: Vec<Event> = self.verifier.verify_stored(stored_events).await;
+
+        // Compose IDs and Events collections
+        let ids: Arc<Mutex<HashSet<EventId>>> =
+            Arc::new(Mutex::new(stored_events.iter().map(|e| e.id()).collect()));
+        let events: Arc<Mutex<Vec<Event>>> = Arc::new(Mutex::new(stored_events));
+
+        // Filter relays and start query
+        let mut handles = Vec::with_capacity(urls.len());
+        for (url, relay) in relays.into_iter().filter(|(url, ..)| urls.contains(url)) {
+            let filters = filters.clone();
+            let ids = ids.clone();
+            let events = events.clone();
+            let handle = thread::spawn(async move {
+                // IDs received from this relay
+                let relay_ids: Arc<Mutex<HashSet<EventId>>> = Arc::new(Mutex::new(HashSet::new()));
+                let res = relay
+                    .get_events_of_with_callback(filters, timeout, opts, |event| async {
+                        relay_ids.lock().await.insert(event.id());
+                        let mut ids = ids.lock().await;
+                        if !ids.contains(&event.id()) {
+                            let mut events = events.lock().await;
+                            ids.insert(event.id());
+                            events.push(event);
+                        }
+                    })
+                    .await;
+                if let Err(e) = &res {
+                    tracing::error!("Failed to get events from {url}: {e}");
+                }
+                let relay_ids: HashSet<EventId> = relay_ids.lock().await.clone();
+                (url, relay_ids, res.is_ok())
+            })?;
+            handles.push(handle);
+        }
 
-            // Check limit
-            match limit {
-                Some(limit) => Ok(iter.take(limit).collect()),
-                None => Ok(iter.collect()),
+        // Join threads
+        let mut sets: BTreeMap<Url, HashSet<EventId>> = BTreeMap::new();
+        let mut incomplete: HashSet<Url> = HashSet::new();
+        for handle in handles.into_iter() {
+            let (url, relay_ids, completed) = handle.join().await?;
+            if completed {
+                sets.insert(url, relay_ids);
+            } else {
+                incomplete.insert(url);
             }
         }
+
+        // Merge the results of the relays
+        let events: Vec<Event> = mem::take(&mut *events.lock().await);
+        let events: Vec<Event> = reconcile(events, &filters);
+
+        Ok((events, CompletenessReport::new(sets, incomplete)))
     }
 
     pub async fn connect(&self, connection_timeout: Option<Duration>) {
@@ -560,6 +876,43 @@ impl InternalRelayPool {
                 self.connect_relay(relay, None).await;
             }
         }
+
+        #[cfg(all(feature = "network-monitor", not(target_arch = "wasm32")))]
+        self.spawn_network_monitor();
+    }
+
+    pub async fn retry_disconnected(&self) {
+        let relays: HashMap<Url, Relay> = self.relays().await;
+        for relay in relays.values() {
+            relay.retry_now().await;
+        }
+    }
+
+    #[cfg(all(feature = "network-monitor", not(target_arch = "wasm32")))]
+    fn spawn_network_monitor(&self) {
+        // Check if already running
+        if self.network_monitor.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let mut monitor = match NetworkMonitor::new() {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                tracing::error!("Impossible to monitor network changes: {e}");
+                self.network_monitor.store(false, Ordering::SeqCst);
+                return;
+            }
+        };
+
+        let pool = self.clone();
+        let _ = thread::spawn(async move {
+            tracing::debug!("Network monitor started");
+            while monitor.connectivity_restored().await {
+                pool.retry_disconnected().await;
+            }
+            tracing::debug!("Network monitor exited");
+            pool.network_monitor.store(false, Ordering::SeqCst);
+        });
     }
 
     pub async fn disconnect(&self) -> Result<(), Error> {
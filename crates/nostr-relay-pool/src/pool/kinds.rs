@@ -0,0 +1,85 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Notification filtering by kind
+
+use std::collections::HashSet;
+
+use nostr::{Kind, RelayMessage};
+
+use super::RelayPoolNotification;
+
+/// Kinds of the events delivered to the pool notification channel
+///
+/// Independent of subscriptions: filtered events are still saved into the database
+/// and delivered to internal consumers (i.e. `get_events_of`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum NotificationKinds {
+    /// Deliver all kinds
+    #[default]
+    All,
+    /// Deliver only these kinds
+    Allow(HashSet<Kind>),
+    /// Deliver all kinds except these
+    Deny(HashSet<Kind>),
+}
+
+impl NotificationKinds {
+    /// Deliver only these kinds
+    pub fn allow<I>(kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        Self::Allow(kinds.into_iter().collect())
+    }
+
+    /// Deliver all kinds except these
+    pub fn deny<I>(kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        Self::Deny(kinds.into_iter().collect())
+    }
+
+    /// Check if the kind is delivered
+    pub fn is_allowed(&self, kind: &Kind) -> bool {
+        match self {
+            Self::All => true,
+            Self::Allow(kinds) => kinds.contains(kind),
+            Self::Deny(kinds) => !kinds.contains(kind),
+        }
+    }
+
+    /// Check if the notification is delivered
+    ///
+    /// Only event notifications and event messages are filtered.
+    pub(crate) fn is_delivered(&self, notification: &RelayPoolNotification) -> bool {
+        match notification {
+            RelayPoolNotification::Event { event, .. } => self.is_allowed(&event.kind()),
+            RelayPoolNotification::Message {
+                message: RelayMessage::Event { event, .. },
+                ..
+            } => self.is_allowed(&event.kind()),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_kinds() {
+        assert!(NotificationKinds::All.is_allowed(&Kind::Metadata));
+
+        let allow = NotificationKinds::allow([Kind::TextNote, Kind::Reaction]);
+        assert!(allow.is_allowed(&Kind::TextNote));
+        assert!(!allow.is_allowed(&Kind::Metadata));
+
+        let deny = NotificationKinds::deny([Kind::Metadata, Kind::ContactList]);
+        assert!(deny.is_allowed(&Kind::TextNote));
+        assert!(!deny.is_allowed(&Kind::ContactList));
+    }
+}
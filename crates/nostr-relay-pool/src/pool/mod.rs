@@ -4,7 +4,7 @@
 
 //! Relay Pool
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
@@ -17,23 +17,44 @@ use nostr::{
 use nostr_database::{DynNostrDatabase, IntoNostrDatabase, MemoryDatabase};
 use tokio::sync::broadcast;
 
+pub mod checkpoint;
+pub mod completeness;
+pub mod dedup;
 mod error;
+pub mod filtering;
 mod internal;
+pub mod kinds;
+#[cfg(all(feature = "network-monitor", not(target_arch = "wasm32")))]
+mod network;
 pub mod options;
-
+pub mod output;
+mod quota;
+mod reconcile;
+pub mod verifier;
+
+pub use self::checkpoint::{PoolCheckpoint, RelayCheckpoint};
+pub use self::completeness::CompletenessReport;
+pub use self::dedup::DedupOptions;
 pub use self::error::Error;
+pub use self::filtering::FilteringPolicy;
 use self::internal::InternalRelayPool;
+pub use self::kinds::NotificationKinds;
 pub use self::options::RelayPoolOptions;
+pub use self::output::{Output, PowEscalation};
+pub use self::verifier::{EventVerifier, VerificationPolicy, VerifyMode};
 use crate::relay::options::{FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions};
-use crate::relay::{Relay, RelayStatus};
+use crate::relay::{Relay, RelayStatus, SubscriptionLifecycle};
 use crate::SubscribeOptions;
 
 /// Relay Pool Notification
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RelayPoolNotification {
     /// Received an [`Event`]. Does not include events sent by this client.
+    ///
+    /// Delivered once per event: the other relays that sent it are tracked in the database
+    /// (see [`NostrDatabase::event_seen_on_relays`](nostr_database::NostrDatabase::event_seen_on_relays)).
     Event {
-        /// Relay url
+        /// Source relay url (the first that sent the event)
         relay_url: Url,
         /// Subscription ID
         subscription_id: SubscriptionId,
@@ -54,6 +75,24 @@ pub enum RelayPoolNotification {
         /// Relay Status
         status: RelayStatus,
     },
+    /// Subscription lifecycle milestone (i.e. `REQ` sent, `EOSE` received or subscription closed)
+    Subscription {
+        /// Relay url
+        relay_url: Url,
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Lifecycle
+        lifecycle: SubscriptionLifecycle,
+    },
+    /// Subscription not served by some relays, since they reached their max number of subscriptions
+    ///
+    /// Emitted also when a relay gives a slot back (`missing` is empty when all relays serve it again).
+    SubscriptionDegraded {
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Relays not serving the subscription
+        missing: HashSet<Url>,
+    },
     /// Stop
     Stop,
     /// Shutdown
@@ -110,6 +149,26 @@ impl RelayPool {
         self.inner.database()
     }
 
+    /// Get the filtering policy
+    ///
+    /// Use it to block public keys and event IDs or to restrict the relays where messages are sent, at runtime.
+    pub fn filtering(&self) -> FilteringPolicy {
+        self.inner.filtering()
+    }
+
+    /// Get the kinds of the events delivered to the notification channel
+    pub async fn notification_kinds(&self) -> NotificationKinds {
+        self.inner.notification_kinds().await
+    }
+
+    /// Set the kinds of the events delivered to the notification channel (default: all)
+    ///
+    /// Independent of subscriptions: useful to not flood the notification consumers with kinds
+    /// requested only to fill the database.
+    pub async fn set_notification_kinds(&self, kinds: NotificationKinds) {
+        self.inner.set_notification_kinds(kinds).await
+    }
+
     /// Get relays
     pub async fn relays(&self) -> HashMap<Url, Relay> {
         self.inner.relays().await
@@ -125,6 +184,11 @@ impl RelayPool {
     }
 
     /// Add new relay
+    ///
+    /// If the relay already exists, its options are updated: flags, POW difficulty and reconnection
+    /// options are changed in place, while a change of proxy, limits or send defaults replaces the relay.
+    ///
+    /// Return `true` if the relay has been added or its options changed.
     pub async fn add_relay<U>(&self, url: U, opts: RelayOptions) -> Result<bool, Error>
     where
         U: TryIntoUrl,
@@ -152,6 +216,14 @@ impl RelayPool {
         self.inner.connect(connection_timeout).await
     }
 
+    /// Try to reconnect the disconnected relays immediately, skipping the remaining retry interval
+    ///
+    /// Call it when the network connectivity is restored (i.e. resume from sleep).
+    /// With the `network-monitor` feature, it's called automatically on network changes.
+    pub async fn retry_disconnected(&self) {
+        self.inner.retry_disconnected().await
+    }
+
     /// Disconnect from all relays
     pub async fn disconnect(&self) -> Result<(), Error> {
         self.inner.disconnect().await
@@ -231,7 +303,9 @@ impl RelayPool {
     }
 
     /// Send event to **all connected relays** and wait for `OK` message
-    pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<EventId, Error> {
+    ///
+    /// Return the outcome of each relay: the event may be rejected by all of them.
+    pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<Output, Error> {
         self.inner.send_event(event, opts).await
     }
 
@@ -245,12 +319,14 @@ impl RelayPool {
     }
 
     /// Send event to **specific relays** and wait for `OK` message
+    ///
+    /// Return the outcome of each relay: the event may be rejected by all of them.
     pub async fn send_event_to<I, U>(
         &self,
         urls: I,
         event: Event,
         opts: RelaySendOptions,
-    ) -> Result<EventId, Error>
+    ) -> Result<Output, Error>
     where
         I: IntoIterator<Item = U>,
         U: TryIntoUrl,
@@ -301,6 +377,20 @@ impl RelayPool {
         self.inner.subscribe_with_id(id, filters, opts).await
     }
 
+    /// Update the filters of a subscription, minimizing the events resent by relays
+    ///
+    /// Check [`Relay::update_subscription_filters`] for details.
+    pub async fn update_subscription_filters(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+        opts: RelaySendOptions,
+    ) {
+        self.inner
+            .update_subscription_filters(id, filters, opts)
+            .await
+    }
+
     /// Unsubscribe from subscription
     pub async fn unsubscribe(&self, id: SubscriptionId, opts: RelaySendOptions) {
         self.inner.unsubscribe(id, opts).await
@@ -325,9 +415,24 @@ impl RelayPool {
             .await
     }
 
+    /// Count events of filters (NIP45)
+    ///
+    /// Relays count independently: the highest count is returned.
+    /// For relays that don't support NIP45, the count of the **local database** is used.
+    pub async fn count_events(
+        &self,
+        filters: Vec<Filter>,
+        timeout: Duration,
+    ) -> Result<usize, Error> {
+        self.inner.count_events(filters, timeout).await
+    }
+
     /// Get events of filters from **specific relays**
     ///
     /// Get events both from **local database** and **relays**
+    ///
+    /// The results are merged: events are deduplicated, only the latest version of the replaceable events is kept
+    /// and the list is sorted by `created_at` (newest first), respecting the `limit` of each filter.
     pub async fn get_events_from<I, U>(
         &self,
         urls: I,
@@ -345,6 +450,93 @@ impl RelayPool {
             .await
     }
 
+    /// Get events of filters from **specific relays** and compare the result sets of each relay
+    ///
+    /// The [`CompletenessReport`] estimates how complete and consistent the results are,
+    /// helping to decide if more relays should be queried for this kind of data.
+    ///
+    /// Get events both from **local database** and **relays**
+    pub async fn get_events_with_report<I, U>(
+        &self,
+        urls: I,
+        filters: Vec<Filter>,
+        timeout: Duration,
+        opts: FilterOptions,
+    ) -> Result<(Vec<Event>, CompletenessReport), Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let urls: HashSet<Url> = urls
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()?;
+        self.inner
+            .get_events_with_report(urls, filters, timeout, opts)
+            .await
+    }
+
+    /// Take a checkpoint of the adaptive state (relay stats and subscription cursors)
+    pub async fn checkpoint(&self) -> PoolCheckpoint {
+        let mut checkpoint = PoolCheckpoint {
+            created_at: Timestamp::now(),
+            relays: HashMap::new(),
+        };
+        for (url, relay) in self.relays().await.into_iter() {
+            let relay_checkpoint = RelayCheckpoint {
+                stats: relay.stats().snapshot(),
+                cursors: relay.subscription_cursors().await,
+            };
+            checkpoint.relays.insert(url, relay_checkpoint);
+        }
+        checkpoint
+    }
+
+    /// Restore the adaptive state from a [`PoolCheckpoint`]
+    ///
+    /// Relays not in the pool are skipped: add relays before restoring.
+    pub async fn restore(&self, checkpoint: PoolCheckpoint) {
+        let relays = self.relays().await;
+        for (url, relay_checkpoint) in checkpoint.relays.into_iter() {
+            if let Some(relay) = relays.get(&url) {
+                relay.inner.stats().restore(relay_checkpoint.stats);
+                relay.inner.restore_cursors(relay_checkpoint.cursors).await;
+            }
+        }
+    }
+
+    /// Save a [`PoolCheckpoint`] into the database
+    pub async fn save_checkpoint(&self) -> Result<(), Error> {
+        let checkpoint: PoolCheckpoint = self.checkpoint().await;
+        self.database()
+            .save_checkpoint(checkpoint::CHECKPOINT_KEY, checkpoint.encode())
+            .await?;
+        Ok(())
+    }
+
+    /// Restore the last [`PoolCheckpoint`] saved into the database
+    ///
+    /// A corrupted checkpoint is discarded.
+    /// Return `true` if a checkpoint has been restored.
+    pub async fn restore_checkpoint(&self) -> Result<bool, Error> {
+        let bytes: Option<Vec<u8>> = self
+            .database()
+            .load_checkpoint(checkpoint::CHECKPOINT_KEY)
+            .await?;
+        match bytes.map(|bytes| PoolCheckpoint::decode(&bytes)) {
+            Some(Some(checkpoint)) => {
+                self.restore(checkpoint).await;
+                Ok(true)
+            }
+            Some(None) => {
+                tracing::warn!("Discarding corrupted relay pool checkpoint");
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+
     /// Negentropy reconciliation
     pub async fn reconcile(&self, filter: Filter, opts: NegentropyOptions) -> Result<(), Error> {
         self.inner.reconcile(filter, opts).await
@@ -0,0 +1,80 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Network reachability
+//!
+//! Watch the network interfaces of the OS (Linux, macOS and Windows), to reconnect the relays
+//! as soon as the connectivity is restored instead of waiting out the retry interval.
+//!
+//! With the `async-std` or `smol` features, the interfaces are watched with the `smol` backend of `if-watch`.
+
+use std::io;
+
+use async_utility::futures_util::StreamExt;
+#[cfg(any(feature = "async-std", feature = "smol"))]
+use if_watch::smol::IfWatcher;
+#[cfg(not(any(feature = "async-std", feature = "smol")))]
+use if_watch::tokio::IfWatcher;
+use if_watch::IfEvent;
+
+pub(super) struct NetworkMonitor {
+    watcher: IfWatcher,
+}
+
+impl NetworkMonitor {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            watcher: IfWatcher::new()?,
+        })
+    }
+
+    /// Wait for a non-loopback address to come up
+    ///
+    /// Return `false` if the watcher is terminated.
+    pub async fn connectivity_restored(&mut self) -> bool {
+        while let Some(event) = self.watcher.next().await {
+            match event {
+                Ok(event) => {
+                    if restores_connectivity(&event) {
+                        tracing::debug!("Network change: {event:?}");
+                        return true;
+                    }
+                }
+                Err(e) => tracing::warn!("Network monitor error: {e}"),
+            }
+        }
+        false
+    }
+}
+
+/// Check if the relays must be reconnected after a network interface change
+///
+/// Only a non-loopback address coming up can restore the connectivity.
+fn restores_connectivity(event: &IfEvent) -> bool {
+    match event {
+        IfEvent::Up(net) => !net.addr().is_loopback(),
+        IfEvent::Down(..) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use if_watch::IpNet;
+
+    use super::*;
+
+    #[test]
+    fn test_restores_connectivity() {
+        let lan: IpNet = "192.168.1.10/24".parse().unwrap();
+        let ipv6: IpNet = "2001:db8::1/64".parse().unwrap();
+        let loopback: IpNet = "127.0.0.1/8".parse().unwrap();
+        let loopback_v6: IpNet = "::1/128".parse().unwrap();
+
+        assert!(restores_connectivity(&IfEvent::Up(lan)));
+        assert!(restores_connectivity(&IfEvent::Up(ipv6)));
+        assert!(!restores_connectivity(&IfEvent::Up(loopback)));
+        assert!(!restores_connectivity(&IfEvent::Up(loopback_v6)));
+        assert!(!restores_connectivity(&IfEvent::Down(lan)));
+    }
+}
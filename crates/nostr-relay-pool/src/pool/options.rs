@@ -4,16 +4,27 @@
 
 //! Pool options
 
+use super::dedup::DedupOptions;
+use super::verifier::{VerificationPolicy, VerifyMode};
+
 /// Relay Pool Options
 #[derive(Debug, Clone, Copy)]
 pub struct RelayPoolOptions {
     pub(super) notification_channel_size: usize,
+    pub(super) verify_events: VerifyMode,
+    pub(super) verification_policy: VerificationPolicy,
+    pub(super) verification_threads: Option<usize>,
+    pub(super) dedup: DedupOptions,
 }
 
 impl Default for RelayPoolOptions {
     fn default() -> Self {
         Self {
             notification_channel_size: 4096,
+            verify_events: VerifyMode::default(),
+            verification_policy: VerificationPolicy::default(),
+            verification_threads: None,
+            dedup: DedupOptions::default(),
         }
     }
 }
@@ -29,4 +40,35 @@ impl RelayPoolOptions {
         self.notification_channel_size = size;
         self
     }
+
+    /// Verification of received events (default: [`VerifyMode::Full`])
+    ///
+    /// Events are verified by a bounded pool of dedicated threads, off the async runtime.
+    pub fn verify_events(mut self, mode: VerifyMode) -> Self {
+        self.verify_events = mode;
+        self
+    }
+
+    /// Where events are verified (default: [`VerificationPolicy::VerifyFromRelaysOnly`])
+    ///
+    /// Events read from the database (i.e. in `get_events_of`) are verified only with
+    /// [`VerificationPolicy::AlwaysVerify`].
+    pub fn verification_policy(mut self, policy: VerificationPolicy) -> Self {
+        self.verification_policy = policy;
+        self
+    }
+
+    /// Number of event verification threads (default: available parallelism, max 4)
+    pub fn verification_threads(mut self, threads: usize) -> Self {
+        self.verification_threads = Some(threads);
+        self
+    }
+
+    /// Deduplication of the events received from many relays (default: 10_000 recent IDs, no bloom filter)
+    ///
+    /// IDs not remembered by the deduplication layer are checked in the database.
+    pub fn dedup(mut self, opts: DedupOptions) -> Self {
+        self.dedup = opts;
+        self
+    }
 }
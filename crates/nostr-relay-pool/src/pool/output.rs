@@ -0,0 +1,122 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Publish output
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use nostr::{EventId, Url};
+
+use crate::relay;
+
+/// POW escalation performed for a relay that rejected an event with `pow:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowEscalation {
+    /// ID of the re-mined event
+    pub event_id: EventId,
+    /// Difficulty required by the relay
+    pub difficulty: u8,
+    /// Extra time spent to re-mine and resend the event
+    pub elapsed: Duration,
+    /// Whether the relay accepted the re-mined event
+    pub published: bool,
+}
+
+/// Publish output, built from the `OK` messages (and the timeouts) of each relay
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Output {
+    /// Relays that accepted the event, with the ID of the accepted event
+    pub success: HashMap<Url, EventId>,
+    /// Relays that rejected the event or failed, with the reason
+    ///
+    /// For rejections, the reason is the message sent by the relay (i.e. `pow: difficulty 25>=24`).
+    pub failed: HashMap<Url, String>,
+    /// POW escalations, per relay
+    ///
+    /// Filled only when the event is re-mined after a `pow:` rejection:
+    /// the ID in [`Output::success`] of these relays is the one of the re-mined event.
+    pub pow_escalations: HashMap<Url, PowEscalation>,
+}
+
+impl Output {
+    /// Check if at least one relay accepted the event
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        !self.success.is_empty()
+    }
+
+    /// Relays that rejected the event or failed
+    ///
+    /// Useful to retry only where the event wasn't published.
+    pub fn failed_relays(&self) -> impl Iterator<Item = &Url> {
+        self.failed.keys()
+    }
+
+    /// Get the ID of the published event
+    ///
+    /// If a relay accepted the original event, its ID is returned,
+    /// otherwise the ID of the first accepted re-mined event (see [`Output::pow_escalations`]).
+    pub fn id(&self) -> Option<EventId> {
+        self.success
+            .iter()
+            .find(|(url, ..)| !self.pow_escalations.contains_key(*url))
+            .or_else(|| self.success.iter().next())
+            .map(|(.., id)| *id)
+    }
+
+    pub(crate) fn insert(&mut self, url: Url, res: Result<EventId, relay::Error>) {
+        match res {
+            Ok(id) => {
+                self.success.insert(url, id);
+            }
+            Err(relay::Error::EventNotPublished(message)) => {
+                self.failed.insert(url, message);
+            }
+            Err(e) => {
+                self.failed.insert(url, e.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+
+    #[test]
+    fn test_id() {
+        let keys = Keys::generate();
+        let original = EventBuilder::text_note("original", [])
+            .to_event(&keys)
+            .unwrap();
+        let mined = EventBuilder::text_note("mined", [])
+            .to_event(&keys)
+            .unwrap();
+        let relay_a = Url::parse("wss://relay.a.com").unwrap();
+        let relay_b = Url::parse("wss://relay.b.com").unwrap();
+
+        let mut output = Output::default();
+        assert_eq!(output.id(), None);
+
+        // Only the re-mined event was accepted
+        output.success.insert(relay_a.clone(), mined.id());
+        output.pow_escalations.insert(
+            relay_a,
+            PowEscalation {
+                event_id: mined.id(),
+                difficulty: 20,
+                elapsed: Duration::from_secs(1),
+                published: true,
+            },
+        );
+        assert_eq!(output.id(), Some(mined.id()));
+
+        // The original event is preferred
+        output.success.insert(relay_b, original.id());
+        assert_eq!(output.id(), Some(original.id()));
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription quota
+//!
+//! Relays limit the number of active subscriptions per connection (i.e. NIP11 `max_subscriptions`).
+//! When a relay is full, the subscriptions with the highest priority get its slots:
+//! lower-priority ones are preempted, otherwise the new subscription isn't sent to that relay.
+
+use std::collections::{HashMap, HashSet};
+
+use nostr::{SubscriptionId, Url};
+
+/// Result of a slot request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Slot {
+    /// Slot available
+    Free,
+    /// Relay full: close the subscription to free a slot
+    Preempt(SubscriptionId),
+    /// Relay full of subscriptions with equal or higher priority
+    Full,
+}
+
+/// Priorities of the pool subscriptions and relays not serving them
+#[derive(Debug, Default)]
+pub(crate) struct SubscriptionQuota {
+    priorities: HashMap<SubscriptionId, u8>,
+    missing: HashMap<SubscriptionId, HashSet<Url>>,
+}
+
+impl SubscriptionQuota {
+    pub fn register(&mut self, id: SubscriptionId, priority: u8) {
+        self.priorities.insert(id, priority);
+    }
+
+    pub fn priority(&self, id: &SubscriptionId) -> u8 {
+        self.priorities.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn remove(&mut self, id: &SubscriptionId) {
+        self.priorities.remove(id);
+        self.missing.remove(id);
+    }
+
+    pub fn clear(&mut self) {
+        self.priorities.clear();
+        self.missing.clear();
+    }
+
+    /// Request a slot on a relay with `active` subscriptions
+    pub fn slot<'a, I>(
+        &self,
+        id: &SubscriptionId,
+        priority: u8,
+        active: I,
+        limit: Option<usize>,
+    ) -> Slot
+    where
+        I: IntoIterator<Item = &'a SubscriptionId>,
+    {
+        let limit: usize = match limit {
+            Some(limit) => limit,
+            None => return Slot::Free,
+        };
+
+        let active: Vec<&SubscriptionId> = active.into_iter().filter(|a| *a != id).collect();
+        if active.len() < limit {
+            return Slot::Free;
+        }
+
+        // Only pool subscriptions can be preempted
+        active
+            .into_iter()
+            .filter_map(|a| self.priorities.get(a).map(|p| (*p, a)))
+            .filter(|(p, ..)| *p < priority)
+            .min_by_key(|(p, ..)| *p)
+            .map_or(Slot::Full, |(.., a)| Slot::Preempt(a.clone()))
+    }
+
+    /// Mark the subscription as not served by the relay
+    ///
+    /// Return the relays not serving the subscription, if changed.
+    pub fn set_missing(&mut self, id: &SubscriptionId, url: Url) -> Option<HashSet<Url>> {
+        if !self.priorities.contains_key(id) {
+            return None;
+        }
+        let missing: &mut HashSet<Url> = self.missing.entry(id.clone()).or_default();
+        missing.insert(url).then(|| missing.clone())
+    }
+
+    /// Mark the subscription as served by the relay
+    ///
+    /// Return the relays not serving the subscription, if changed.
+    pub fn set_served(&mut self, id: &SubscriptionId, url: &Url) -> Option<HashSet<Url>> {
+        let missing: &mut HashSet<Url> = self.missing.get_mut(id)?;
+        if !missing.remove(url) {
+            return None;
+        }
+        let missing: HashSet<Url> = missing.clone();
+        if missing.is_empty() {
+            self.missing.remove(id);
+        }
+        Some(missing)
+    }
+
+    /// Get the highest-priority subscription not served by the relay
+    pub fn next_for(&self, url: &Url) -> Option<SubscriptionId> {
+        self.missing
+            .iter()
+            .filter(|(.., missing)| missing.contains(url))
+            .max_by_key(|(id, ..)| self.priority(id))
+            .map(|(id, ..)| id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_quota() {
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        let low = SubscriptionId::new("low");
+        let high = SubscriptionId::new("high");
+        let new = SubscriptionId::new("new");
+        let other = SubscriptionId::new("other");
+
+        let mut quota = SubscriptionQuota::default();
+        quota.register(low.clone(), 1);
+        quota.register(high.clone(), 10);
+
+        let active = [low.clone(), high.clone()];
+        assert_eq!(quota.slot(&new, 5, &active, None), Slot::Free);
+        assert_eq!(quota.slot(&new, 5, &active, Some(3)), Slot::Free);
+        assert_eq!(
+            quota.slot(&new, 5, &active, Some(2)),
+            Slot::Preempt(low.clone())
+        );
+        assert_eq!(quota.slot(&new, 1, &active, Some(2)), Slot::Full);
+        // Already active: not count itself
+        assert_eq!(quota.slot(&low, 1, &active, Some(2)), Slot::Free);
+
+        // Missing relays
+        quota.register(new.clone(), 5);
+        assert_eq!(
+            quota.set_missing(&low, url.clone()),
+            Some(HashSet::from([url.clone()]))
+        );
+        assert_eq!(quota.set_missing(&low, url.clone()), None);
+        assert!(quota.set_missing(&new, url.clone()).is_some());
+        assert_eq!(quota.set_missing(&other, url.clone()), None);
+        assert_eq!(quota.next_for(&url), Some(new.clone()));
+
+        assert_eq!(quota.set_served(&new, &url), Some(HashSet::new()));
+        assert_eq!(quota.set_served(&new, &url), None);
+        assert_eq!(quota.next_for(&url), Some(low));
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Reconciliation of the events received from many relays for the same query
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use nostr::{Event, EventId, Filter, Kind, PublicKey};
+
+/// Replaceable (`kind`, `author`) or parameterized replaceable (`kind`, `author`, `d`) event key
+type ReplaceableKey = (Kind, PublicKey, Option<String>);
+
+fn replaceable_key(event: &Event) -> Option<ReplaceableKey> {
+    if event.is_replaceable() {
+        Some((event.kind(), event.author(), None))
+    } else if event.is_parameterized_replaceable() {
+        let identifier: String = event.identifier().unwrap_or_default().to_string();
+        Some((event.kind(), event.author(), Some(identifier)))
+    } else {
+        None
+    }
+}
+
+/// Check if `event` replaces `other`
+///
+/// The newest wins. With the same timestamp, the event with the lowest ID is kept.
+fn replaces(event: &Event, other: &Event) -> bool {
+    match event.created_at().cmp(&other.created_at()) {
+        Ordering::Greater => true,
+        Ordering::Less => false,
+        Ordering::Equal => event.id() < other.id(),
+    }
+}
+
+/// Merge the events of the relays
+///
+/// Events are deduplicated by ID, only the latest version of the replaceable events is kept
+/// and the result is sorted by `created_at` (newest first).
+///
+/// The limit of each filter is respected: an event is returned if it's one of the `limit` newest events
+/// matching at least one of the filters. Events not matching any filter are discarded.
+pub(crate) fn reconcile<I>(events: I, filters: &[Filter]) -> Vec<Event>
+where
+    I: IntoIterator<Item = Event>,
+{
+    let mut ids: HashSet<EventId> = HashSet::new();
+    let mut regular: Vec<Event> = Vec::new();
+    let mut replaceable: HashMap<ReplaceableKey, Event> = HashMap::new();
+
+    for event in events.into_iter() {
+        if !ids.insert(event.id()) {
+            continue;
+        }
+
+        match replaceable_key(&event) {
+            Some(key) => match replaceable.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    if replaces(&event, entry.get()) {
+                        entry.insert(event);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(event);
+                }
+            },
+            None => regular.push(event),
+        }
+    }
+
+    let mut events: Vec<Event> = regular;
+    events.extend(replaceable.into_values());
+
+    // Newest first
+    events.sort_by(|a, b| b.cmp(a));
+
+    if filters.is_empty() {
+        return events;
+    }
+
+    // Number of events taken for each filter
+    let mut taken: Vec<usize> = vec![0; filters.len()];
+    events
+        .into_iter()
+        .filter(|event| {
+            let mut keep: bool = false;
+            for (filter, taken) in filters.iter().zip(taken.iter_mut()) {
+                if !filter.match_event(event) {
+                    continue;
+                }
+
+                match filter.limit {
+                    Some(limit) if *taken >= limit => {}
+                    _ => {
+                        *taken += 1;
+                        keep = true;
+                    }
+                }
+            }
+            keep
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys, Metadata, Tag, Timestamp};
+
+    use super::*;
+
+    fn note(keys: &Keys, content: &str, created_at: u64) -> Event {
+        EventBuilder::text_note(content, [])
+            .custom_created_at(Timestamp::from(created_at))
+            .to_event(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dedup_and_order() {
+        let keys = Keys::generate();
+        let a = note(&keys, "a", 1);
+        let b = note(&keys, "b", 3);
+        let c = note(&keys, "c", 2);
+
+        let events = reconcile(
+            vec![a.clone(), b.clone(), a.clone(), c.clone(), b.clone()],
+            &[Filter::new()],
+        );
+        assert_eq!(events, vec![b, c, a]);
+    }
+
+    #[test]
+    fn test_replaceable() {
+        let keys = Keys::generate();
+        let old = EventBuilder::metadata(&Metadata::new().name("old"))
+            .custom_created_at(Timestamp::from(1))
+            .to_event(&keys)
+            .unwrap();
+        let new = EventBuilder::metadata(&Metadata::new().name("new"))
+            .custom_created_at(Timestamp::from(2))
+            .to_event(&keys)
+            .unwrap();
+
+        let events = reconcile(vec![new.clone(), old.clone()], &[Filter::new()]);
+        assert_eq!(events, vec![new.clone()]);
+        let events = reconcile(vec![old, new.clone()], &[Filter::new()]);
+        assert_eq!(events, vec![new]);
+
+        // Different identifiers are different events
+        let kind = Kind::ParameterizedReplaceable(30000);
+        let first = EventBuilder::new(kind, "", [Tag::Identifier(String::from("a"))])
+            .to_event(&keys)
+            .unwrap();
+        let second = EventBuilder::new(kind, "", [Tag::Identifier(String::from("b"))])
+            .to_event(&keys)
+            .unwrap();
+        let events = reconcile(vec![first, second], &[Filter::new()]);
+        assert_eq!(events.len(), 2);
+    }
+
+    #[test]
+    fn test_limit() {
+        let keys = Keys::generate();
+        let other = Keys::generate();
+        let events: Vec<Event> = vec![
+            note(&keys, "1", 1),
+            note(&keys, "2", 2),
+            note(&keys, "3", 3),
+            note(&other, "4", 4),
+            note(&other, "5", 5),
+        ];
+
+        let filter = Filter::new().limit(2);
+        let res = reconcile(events.clone(), &[filter]);
+        assert_eq!(res, vec![events[4].clone(), events[3].clone()]);
+
+        // The limit is applied to each filter
+        let filters = vec![
+            Filter::new().author(keys.public_key()).limit(1),
+            Filter::new().author(other.public_key()).limit(1),
+        ];
+        let res = reconcile(events.clone(), &filters);
+        assert_eq!(res, vec![events[4].clone(), events[2].clone()]);
+
+        // Not matching
+        let filter = Filter::new().kind(Kind::Reaction);
+        assert!(reconcile(events, &[filter]).is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event verification worker pool
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+
+use nostr::event::Error as EventError;
+use nostr::Event;
+use nostr_database::VerificationStatus;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::{mpsc, oneshot};
+
+use crate::relay::Error;
+
+#[cfg(not(target_arch = "wasm32"))]
+const QUEUE_SIZE: usize = 1024;
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_DEFAULT_THREADS: usize = 4;
+
+/// Verification of received events
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VerifyMode {
+    /// Don't verify events
+    ///
+    /// Use it only with trusted relays!
+    None,
+    /// Verify only the event ID
+    Fast,
+    /// Verify event ID and signature
+    #[default]
+    Full,
+}
+
+impl VerifyMode {
+    /// Verify event according to the mode
+    pub fn verify(&self, event: &Event) -> Result<(), EventError> {
+        match self {
+            Self::None => Ok(()),
+            Self::Fast => event.verify_id(),
+            Self::Full => event.verify(),
+        }
+    }
+}
+
+impl From<VerifyMode> for VerificationStatus {
+    fn from(mode: VerifyMode) -> Self {
+        match mode {
+            VerifyMode::None => Self::Unverified,
+            VerifyMode::Fast => Self::IdVerified,
+            VerifyMode::Full => Self::Verified,
+        }
+    }
+}
+
+/// Where events are verified
+///
+/// How they are verified is defined by [`VerifyMode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum VerificationPolicy {
+    /// Verify events received from relays and events read from the database
+    AlwaysVerify,
+    /// Verify only events received from relays
+    ///
+    /// Events read from the database have already been verified at ingest.
+    #[default]
+    VerifyFromRelaysOnly,
+    /// Never verify events
+    ///
+    /// Use it only with trusted relays!
+    Never,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Job = (Event, oneshot::Sender<Result<Event, EventError>>);
+
+/// Bounded pool of dedicated threads that verify events off the async runtime
+///
+/// Workers exit when the last clone of the verifier is dropped.
+/// On `wasm32` the events are verified inline.
+#[derive(Debug, Clone)]
+pub struct EventVerifier {
+    mode: VerifyMode,
+    policy: VerificationPolicy,
+    #[cfg(not(target_arch = "wasm32"))]
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+impl EventVerifier {
+    /// New event verifier
+    ///
+    /// If `threads` is `None`, the available parallelism is used (max 4 threads).
+    pub fn new(mode: VerifyMode, threads: Option<usize>) -> Self {
+        Self::with_policy(mode, VerificationPolicy::default(), threads)
+    }
+
+    /// New event verifier with [`VerificationPolicy`]
+    ///
+    /// If the policy is [`VerificationPolicy::Never`], the mode is ignored.
+    pub fn with_policy(
+        mode: VerifyMode,
+        policy: VerificationPolicy,
+        threads: Option<usize>,
+    ) -> Self {
+        let mode: VerifyMode = match policy {
+            VerificationPolicy::Never => VerifyMode::None,
+            _ => mode,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let sender = match mode {
+                VerifyMode::None => None,
+                mode => Some(spawn_workers(mode, threads.unwrap_or_else(default_threads))),
+            };
+            Self {
+                mode,
+                policy,
+                sender,
+            }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = threads;
+            Self { mode, policy }
+        }
+    }
+
+    /// Verification mode
+    pub fn mode(&self) -> VerifyMode {
+        self.mode
+    }
+
+    /// Verification policy
+    pub fn policy(&self) -> VerificationPolicy {
+        self.policy
+    }
+
+    /// Verify event
+    ///
+    /// Return back the event if valid.
+    pub async fn verify(&self, event: Event) -> Result<Event, Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(sender) = &self.sender {
+            let (tx, rx) = oneshot::channel();
+            match sender.send((event, tx)).await {
+                Ok(()) => return Ok(rx.await.map_err(|_| Error::OneShotRecvError)??),
+                // Workers are gone: fallback to inline verification
+                Err(mpsc::error::SendError((event, ..))) => {
+                    self.mode.verify(&event)?;
+                    return Ok(event);
+                }
+            }
+        }
+
+        self.mode.verify(&event)?;
+        Ok(event)
+    }
+
+    /// Verify events read from the database, according to the [`VerificationPolicy`]
+    ///
+    /// Invalid events are discarded.
+    pub async fn verify_stored(&self, events: Vec<Event>) -> Vec<Event> {
+        if self.policy != VerificationPolicy::AlwaysVerify {
+            return events;
+        }
+
+        let mut verified: Vec<Event> = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            let id = event.id();
+            match self.verify(event).await {
+                Ok(event) => verified.push(event),
+                Err(e) => tracing::warn!("Discarding invalid stored event {id}: {e}"),
+            }
+        }
+        verified
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_threads() -> usize {
+    thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_DEFAULT_THREADS)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_workers(mode: VerifyMode, threads: usize) -> mpsc::Sender<Job> {
+    let (sender, receiver) = mpsc::channel::<Job>(QUEUE_SIZE);
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for index in 0..threads.max(1) {
+        let receiver = receiver.clone();
+        let res = thread::Builder::new()
+            .name(format!("nostr-verifier-{index}"))
+            .spawn(move || loop {
+                let job: Option<Job> = match receiver.lock() {
+                    Ok(mut receiver) => receiver.blocking_recv(),
+                    Err(_) => None,
+                };
+
+                match job {
+                    Some((event, tx)) => {
+                        let res = mode.verify(&event).map(|_| event);
+                        let _ = tx.send(res);
+                    }
+                    None => break,
+                }
+            });
+
+        if let Err(e) = res {
+            tracing::error!("Impossible to spawn event verification thread: {e}");
+        }
+    }
+
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, JsonUtil, Keys};
+
+    use super::*;
+
+    #[test]
+    fn test_verify_mode() {
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("Test", []).to_event(&keys).unwrap();
+        assert!(VerifyMode::Full.verify(&event).is_ok());
+
+        // Tampered content
+        let tampered = Event::from_json(event.as_json().replace("Test", "Tampered")).unwrap();
+        assert!(VerifyMode::None.verify(&tampered).is_ok());
+        assert!(VerifyMode::Fast.verify(&tampered).is_err());
+        assert!(VerifyMode::Full.verify(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_verification_policy() {
+        let verifier =
+            EventVerifier::with_policy(VerifyMode::Full, VerificationPolicy::Never, Some(1));
+        assert_eq!(verifier.mode(), VerifyMode::None);
+
+        let verifier =
+            EventVerifier::with_policy(VerifyMode::Fast, VerificationPolicy::AlwaysVerify, Some(1));
+        assert_eq!(verifier.mode(), VerifyMode::Fast);
+        assert_eq!(verifier.policy(), VerificationPolicy::AlwaysVerify);
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription cost
+//!
+//! Observed throughput of each subscription and detection of pathologically broad filters.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use nostr::types::time::Instant;
+use nostr::{Filter, SubscriptionId, Timestamp};
+
+/// Observed cost of a subscription
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubscriptionCost {
+    /// Events received
+    pub events: u64,
+    /// Bytes received (uncompressed size of the `EVENT` messages)
+    pub bytes: u64,
+    /// Time elapsed since the `REQ`
+    pub elapsed: Duration,
+}
+
+impl SubscriptionCost {
+    /// Average events per second
+    pub fn events_per_sec(&self) -> f64 {
+        per_sec(self.events, self.elapsed)
+    }
+
+    /// Average bytes per second
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.bytes, self.elapsed)
+    }
+}
+
+fn per_sec(value: u64, elapsed: Duration) -> f64 {
+    let secs: f64 = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        value as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Check if the filter is pathologically broad
+///
+/// A filter without `ids`, `authors`, `kinds`, tags and `since` matches the whole relay database
+/// and all the new events. A `since` not after [`Timestamp::GENESIS`] is treated as missing.
+pub fn is_expensive(filter: &Filter) -> bool {
+    filter.ids.as_ref().map_or(true, |v| v.is_empty())
+        && filter.authors.as_ref().map_or(true, |v| v.is_empty())
+        && filter.kinds.as_ref().map_or(true, |v| v.is_empty())
+        && filter.generic_tags.is_empty()
+        && filter.search.is_none()
+        && filter.since.map_or(true, |since| since <= Timestamp::GENESIS)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Meter {
+    started_at: Instant,
+    events: u64,
+    bytes: u64,
+}
+
+/// Cost meters of the active subscriptions
+#[derive(Debug, Default)]
+pub(crate) struct CostMeters {
+    meters: HashMap<SubscriptionId, Meter>,
+}
+
+impl CostMeters {
+    /// Start (or restart) metering the subscription
+    pub fn start(&mut self, id: SubscriptionId) {
+        self.meters.insert(
+            id,
+            Meter {
+                started_at: Instant::now(),
+                events: 0,
+                bytes: 0,
+            },
+        );
+    }
+
+    /// Record an event received for the subscription
+    ///
+    /// Events of unknown subscriptions are ignored.
+    pub fn record(&mut self, id: &SubscriptionId, bytes: usize) {
+        if let Some(meter) = self.meters.get_mut(id) {
+            meter.events = meter.events.saturating_add(1);
+            meter.bytes = meter.bytes.saturating_add(bytes as u64);
+        }
+    }
+
+    pub fn remove(&mut self, id: &SubscriptionId) {
+        self.meters.remove(id);
+    }
+
+    pub fn get(&self, id: &SubscriptionId) -> Option<SubscriptionCost> {
+        self.meters.get(id).map(cost)
+    }
+
+    pub fn all(&self) -> HashMap<SubscriptionId, SubscriptionCost> {
+        self.meters
+            .iter()
+            .map(|(id, meter)| (id.clone(), cost(meter)))
+            .collect()
+    }
+}
+
+fn cost(meter: &Meter) -> SubscriptionCost {
+    SubscriptionCost {
+        events: meter.events,
+        bytes: meter.bytes,
+        elapsed: meter.started_at.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Kind;
+
+    use super::*;
+
+    #[test]
+    fn test_is_expensive() {
+        assert!(is_expensive(&Filter::new()));
+        assert!(is_expensive(&Filter::new().limit(10)));
+        assert!(!is_expensive(&Filter::new().kind(Kind::TextNote)));
+        assert!(!is_expensive(&Filter::new().since(Timestamp::now())));
+        assert!(is_expensive(&Filter::new().since(Timestamp::GENESIS)));
+        assert!(!is_expensive(&Filter::new().hashtag("nostr")));
+    }
+
+    #[test]
+    fn test_cost_meters() {
+        let id = SubscriptionId::new("test");
+        let mut meters = CostMeters::default();
+        meters.record(&id, 100);
+        assert!(meters.get(&id).is_none());
+
+        meters.start(id.clone());
+        meters.record(&id, 100);
+        meters.record(&id, 50);
+        let cost = meters.get(&id).unwrap();
+        assert_eq!(cost.events, 2);
+        assert_eq!(cost.bytes, 150);
+
+        meters.remove(&id);
+        assert!(meters.all().is_empty());
+    }
+}
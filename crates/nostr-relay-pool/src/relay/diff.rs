@@ -0,0 +1,195 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription filters diffing
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use nostr::Filter;
+
+/// How a subscription has been updated with new filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionUpdate {
+    /// Same filters: nothing sent
+    Unchanged,
+    /// Filters only extended (i.e. new authors or new filters): an additional subscription
+    /// has been opened with the incremental filters, so that the relay doesn't resend
+    /// the events already received.
+    Incremental(Vec<Filter>),
+    /// Filters narrowed or changed: the subscription has been amended with a new `REQ` with the same ID
+    Amend,
+}
+
+/// Part of a new filter not covered by the old filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Delta {
+    /// Old filter extended (or equal) to the new one
+    pub base: Option<usize>,
+    /// Incremental filter
+    pub increment: Option<Filter>,
+}
+
+/// Compute the delta of each new filter against the old ones
+///
+/// Return `None` if the new filters aren't a pure extension of the old ones
+/// (i.e. an old filter has been removed or narrowed).
+pub(crate) fn diff_filters(old: &[Filter], new: &[Filter]) -> Option<Vec<Delta>> {
+    let mut covered: Vec<bool> = vec![false; old.len()];
+    let mut deltas: Vec<Delta> = Vec::with_capacity(new.len());
+
+    for filter in new.iter() {
+        if let Some(index) = old.iter().position(|o| o == filter) {
+            covered[index] = true;
+            deltas.push(Delta {
+                base: Some(index),
+                increment: None,
+            });
+            continue;
+        }
+
+        let extended = old
+            .iter()
+            .enumerate()
+            .filter(|(index, ..)| !covered[*index])
+            .find_map(|(index, o)| Some((index, increment(o, filter)?)));
+        match extended {
+            Some((index, increment)) => {
+                covered[index] = true;
+                deltas.push(Delta {
+                    base: Some(index),
+                    increment: Some(increment),
+                });
+            }
+            None => deltas.push(Delta {
+                base: None,
+                increment: Some(filter.clone()),
+            }),
+        }
+    }
+
+    if covered.into_iter().all(|c| c) {
+        Some(deltas)
+    } else {
+        None
+    }
+}
+
+/// Compose the filter matching what `new` adds to `old`
+///
+/// Only a single list (`ids`, `authors`, `kinds` or generic tag values) can be extended:
+/// extending two of them also adds their combinations, that can't be expressed with a single filter.
+fn increment(old: &Filter, new: &Filter) -> Option<Filter> {
+    if old.search != new.search
+        || old.since != new.since
+        || old.until != new.until
+        || old.limit != new.limit
+        || old.get_sensitive() != new.get_sensitive()
+    {
+        return None;
+    }
+
+    let mut increment: Filter = new.clone();
+    let mut changes: usize = 0;
+
+    if old.ids != new.ids {
+        increment.ids = Some(added(old.ids.as_ref()?, new.ids.as_ref()?)?);
+        changes += 1;
+    }
+
+    if old.authors != new.authors {
+        increment.authors = Some(added(old.authors.as_ref()?, new.authors.as_ref()?)?);
+        changes += 1;
+    }
+
+    if old.kinds != new.kinds {
+        increment.kinds = Some(added(old.kinds.as_ref()?, new.kinds.as_ref()?)?);
+        changes += 1;
+    }
+
+    if old.generic_tags != new.generic_tags {
+        if old.generic_tags.len() != new.generic_tags.len() {
+            return None;
+        }
+        for (tag, values) in new.generic_tags.iter() {
+            let old_values = old.generic_tags.get(tag)?;
+            if old_values != values {
+                increment
+                    .generic_tags
+                    .insert(*tag, added(old_values, values)?);
+                changes += 1;
+            }
+        }
+    }
+
+    if changes == 1 {
+        Some(increment)
+    } else {
+        None
+    }
+}
+
+/// Values of `new` not in `old`
+///
+/// Return `None` if some values of `old` have been removed.
+fn added<T>(old: &HashSet<T>, new: &HashSet<T>) -> Option<HashSet<T>>
+where
+    T: Clone + Eq + Hash,
+{
+    if !old.is_subset(new) {
+        return None;
+    }
+    Some(new.difference(old).cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{Keys, Kind, Timestamp};
+
+    use super::*;
+
+    #[test]
+    fn test_diff_filters() {
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+        let old = vec![Filter::new().author(alice).kind(Kind::TextNote)];
+
+        // Same filters
+        let deltas = diff_filters(&old, &old).unwrap();
+        assert!(deltas.iter().all(|d| d.increment.is_none()));
+
+        // Added author
+        let new = vec![Filter::new().authors([alice, bob]).kind(Kind::TextNote)];
+        let deltas = diff_filters(&old, &new).unwrap();
+        assert_eq!(deltas[0].base, Some(0));
+        assert_eq!(
+            deltas[0].increment,
+            Some(Filter::new().author(bob).kind(Kind::TextNote))
+        );
+
+        // Added filter
+        let mut new = old.clone();
+        new.push(Filter::new().kind(Kind::Metadata));
+        let deltas = diff_filters(&old, &new).unwrap();
+        assert_eq!(deltas[1].base, None);
+        assert_eq!(
+            deltas[1].increment,
+            Some(Filter::new().kind(Kind::Metadata))
+        );
+
+        // Added author and kind
+        let new = vec![Filter::new()
+            .authors([alice, bob])
+            .kinds([Kind::TextNote, Kind::Repost])];
+        assert!(diff_filters(&old, &new).is_none());
+
+        // Removed author
+        let new = vec![Filter::new().author(bob).kind(Kind::TextNote)];
+        assert!(diff_filters(&old, &new).is_none());
+
+        // Changed since
+        let new = vec![old[0].clone().since(Timestamp::from(1_000))];
+        assert!(diff_filters(&old, &new).is_none());
+    }
+}
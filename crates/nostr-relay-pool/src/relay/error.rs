@@ -4,13 +4,14 @@
 
 use std::collections::HashMap;
 
-use async_utility::thread;
 use nostr::message::relay::NegentropyErrorCode;
 use nostr::message::MessageHandleError;
 use nostr::{event, negentropy, EventId};
 use nostr_database::DatabaseError;
 use thiserror::Error;
 
+use crate::runtime::thread;
+
 /// [`Relay`](super::Relay) error
 #[derive(Debug, Error)]
 pub enum Error {
@@ -76,6 +77,12 @@ pub enum Error {
     /// Filters empty
     #[error("filters empty")]
     FiltersEmpty,
+    /// Relay not in the allowlist of the filtering policy
+    #[error("relay not allowed by the filtering policy")]
+    RelayNotAllowed,
+    /// Pathologically broad filter refused
+    #[error("expensive filter refused: {0}")]
+    ExpensiveFilter(String),
     /// Reconciliation error
     #[error("negentropy reconciliation error: {0}")]
     NegentropyReconciliation(NegentropyErrorCode),
@@ -85,6 +92,12 @@ pub enum Error {
     /// Unknown negentropy error
     #[error("unknown negentropy error")]
     UnknownNegentropyError,
+    /// COUNT not supported (NIP45)
+    #[error("COUNT not supported")]
+    CountNotSupported,
+    /// `COUNT` closed by the relay (i.e. `auth-required: ...`)
+    #[error("COUNT closed by relay: {0}")]
+    CountClosed(String),
     /// Relay message too large
     #[error("Received message too large: size={size}, max_size={max_size}")]
     RelayMessageTooLarge {
@@ -122,3 +135,17 @@ pub enum Error {
     #[error("notification handler error: {0}")]
     Handler(String),
 }
+
+impl Error {
+    /// Check if the error is transient (i.e. timeout or connection issues), so the action can be retried
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RecvTimeout
+                | Self::Timeout
+                | Self::MessageNotSent
+                | Self::NotConnected
+                | Self::NotConnectedStatusChanged
+        )
+    }
+}
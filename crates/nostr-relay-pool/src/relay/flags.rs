@@ -101,6 +101,14 @@ impl AtomicRelayServiceFlags {
         }
     }
 
+    pub(crate) fn load(&self) -> RelayServiceFlags {
+        RelayServiceFlags(self.flags.load(Ordering::SeqCst))
+    }
+
+    pub(crate) fn store(&self, flags: RelayServiceFlags) {
+        self.flags.store(flags.to_u64(), Ordering::SeqCst);
+    }
+
     /// Add [RelayServiceFlags] together.
     pub fn add(&self, other: RelayServiceFlags) {
         let _ = self
@@ -4,19 +4,19 @@
 
 //! Internal Relay
 
-use std::cmp;
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
+use std::{cmp, mem};
 
-use async_utility::futures_util::stream::AbortHandle;
-use async_utility::{futures_util, thread, time};
+use async_utility::futures_util;
+use async_utility::futures_util::future::{self, Either};
+use async_utility::futures_util::stream::{AbortHandle, FuturesOrdered};
 use async_wsocket::futures_util::{Future, SinkExt, StreamExt};
-use async_wsocket::{Sink, Stream, WsMessage};
+use async_wsocket::WsMessage;
 use atomic_destructor::AtomicDestroyer;
-#[cfg(not(target_arch = "wasm32"))]
 use nostr::message::MessageHandleError;
 use nostr::negentropy::{Bytes, Negentropy};
 use nostr::nips::nip01::Coordinate;
@@ -24,29 +24,52 @@ use nostr::nips::nip01::Coordinate;
 use nostr::nips::nip11::RelayInformationDocument;
 use nostr::secp256k1::rand::{self, Rng};
 use nostr::{
-    ClientMessage, Event, EventId, Filter, JsonUtil, Keys, MissingPartialEvent, PartialEvent,
-    RawRelayMessage, RelayMessage, SubscriptionId, Timestamp, Url,
+    ClientMessage, Event, EventId, Filter, JsonUtil, Keys, MachineReadablePrefix,
+    MissingPartialEvent, PartialEvent, RawRelayMessage, RelayMessage, SubscriptionId, Timestamp,
+    Url,
 };
-use nostr_database::{DynNostrDatabase, Order};
+use nostr_database::{DynNostrDatabase, Order, VerificationStatus};
 use tokio::sync::mpsc::{self, Receiver, Sender};
-use tokio::sync::{broadcast, oneshot, Mutex, RwLock};
+use tokio::sync::{broadcast, oneshot, Mutex, Notify, RwLock};
 
+use super::cost::{self, CostMeters, SubscriptionCost};
+use super::diff::{self, SubscriptionUpdate};
 use super::flags::AtomicRelayServiceFlags;
 use super::options::{
-    FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions, SubscribeAutoCloseOptions,
-    SubscribeOptions, MAX_ADJ_RETRY_SEC, MIN_RETRY_SEC, NEGENTROPY_BATCH_SIZE_DOWN,
-    NEGENTROPY_HIGH_WATER_UP, NEGENTROPY_LOW_WATER_UP,
+    ConnectionMode, FilterOptions, NegentropyOptions, RelayOptions, RelaySendOptions,
+    SubscribeAutoCloseOptions, SubscribeOptions, MAX_ADJ_RETRY_SEC, MIN_RETRY_SEC,
+    NEGENTROPY_BATCH_SIZE_DOWN, NEGENTROPY_HIGH_WATER_UP, NEGENTROPY_LOW_WATER_UP,
 };
+use super::rate::{FloodAction, TokenBucket};
+use super::shrink::{self, ReqMapping, ReqMappings};
 use super::stats::RelayConnectionStats;
+use super::subscription::{SubscriptionCloseReason, SubscriptionLifecycle};
+use super::supervisor::{Actor, Supervisor};
+use super::transport::{self, Sink, Stream};
 use super::{Error, RelayNotification, RelayStatus};
+use crate::pool::dedup::EventDedup;
+use crate::pool::filtering::FilteringPolicy;
+use crate::pool::kinds::NotificationKinds;
+use crate::pool::verifier::EventVerifier;
 use crate::pool::RelayPoolNotification;
+use crate::runtime::{thread, time};
 
 type Message = (RelayEvent, Option<oneshot::Sender<bool>>);
 
+#[cfg(not(target_arch = "wasm32"))]
+type BoxedFuture<'a> = futures_util::future::BoxFuture<'a, ()>;
+#[cfg(target_arch = "wasm32")]
+type BoxedFuture<'a> = futures_util::future::LocalBoxFuture<'a, ()>;
+
 const MIN_ATTEMPTS: usize = 1;
 const MIN_UPTIME: f64 = 0.90;
-#[cfg(not(target_arch = "wasm32"))]
-const PING_INTERVAL: u64 = 55;
+/// Max number of received messages waiting for verification, for each relay
+const MAX_PENDING_MESSAGES: usize = 64;
+
+/// Tracing target of the raw messages exchanged with the relays
+///
+/// Messages are logged only if enabled with [`Relay::set_message_logging`](super::Relay::set_message_logging).
+pub const MESSAGE_LOG_TARGET: &str = "nostr_relay_pool::relay::message";
 
 /// Relay event
 #[derive(Debug)]
@@ -67,6 +90,19 @@ enum RelayEvent {
     Terminate,
 }
 
+/// Received message, ready to be handled
+#[derive(Debug)]
+enum ReceivedMessage {
+    /// Verified event
+    Event {
+        subscription_id: String,
+        event: Event,
+        status: VerificationStatus,
+    },
+    /// Other relay message
+    Message(RelayMessage),
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct InternalRelay {
     pub(super) url: Url,
@@ -78,11 +114,33 @@ pub(crate) struct InternalRelay {
     database: Arc<DynNostrDatabase>,
     scheduled_for_stop: Arc<AtomicBool>,
     scheduled_for_termination: Arc<AtomicBool>,
+    retry_now: Arc<Notify>,
     relay_sender: Sender<Message>,
     relay_receiver: Arc<Mutex<Receiver<Message>>>,
     pub(super) internal_notification_sender: broadcast::Sender<RelayNotification>,
     external_notification_sender: Arc<RwLock<Option<broadcast::Sender<RelayPoolNotification>>>>,
     subscriptions: Arc<RwLock<HashMap<SubscriptionId, Vec<Filter>>>>,
+    req_mappings: Arc<RwLock<ReqMappings>>,
+    cursors: Arc<RwLock<HashMap<SubscriptionId, Timestamp>>>,
+    costs: Arc<Mutex<CostMeters>>,
+    /// Events being retried and if the `OK` has been already delivered to the application
+    retrying: Arc<Mutex<HashMap<EventId, bool>>>,
+    verifier: Arc<RwLock<Option<EventVerifier>>>,
+    dedup: Arc<RwLock<Option<EventDedup>>>,
+    filtering: Arc<RwLock<Option<FilteringPolicy>>>,
+    notification_kinds: Arc<RwLock<NotificationKinds>>,
+    message_logging: Arc<AtomicBool>,
+    /// Connected on first use and not disconnected for idleness yet
+    lazy_connected: Arc<AtomicBool>,
+    /// Last sent message (UNIX timestamp)
+    last_activity: Arc<AtomicU64>,
+    /// Subscriptions closed with `auth-required`, waiting for the authentication
+    auth_required: Arc<Mutex<HashMap<SubscriptionId, Vec<Filter>>>>,
+    /// `AUTH` events waiting for the `OK`
+    auth_events: Arc<Mutex<HashSet<EventId>>>,
+    outgoing_limiter: Option<Arc<Mutex<TokenBucket>>>,
+    incoming_limiter: Option<Arc<Mutex<TokenBucket>>>,
+    supervisor: Supervisor,
 }
 
 impl AtomicDestroyer for InternalRelay {
@@ -102,8 +160,19 @@ impl AtomicDestroyer for InternalRelay {
 
 impl InternalRelay {
     pub fn new(url: Url, database: Arc<DynNostrDatabase>, opts: RelayOptions) -> Self {
-        let (relay_sender, relay_receiver) = mpsc::channel::<Message>(1024);
+        let (relay_sender, relay_receiver) = mpsc::channel::<Message>(opts.mailbox_capacity);
         let (relay_notification_sender, ..) = broadcast::channel::<RelayNotification>(2048);
+        let stats = RelayConnectionStats::new();
+        let supervisor = Supervisor::new(url.clone(), opts.restart_policy, stats.clone());
+        let outgoing_limiter = opts
+            .rate_limit
+            .map(|l| Arc::new(Mutex::new(TokenBucket::new(l.per_sec, l.burst))));
+        let incoming_limiter = opts.flood_protection.map(|p| {
+            Arc::new(Mutex::new(TokenBucket::new(
+                p.max_events_per_sec,
+                p.max_events_per_sec,
+            )))
+        });
 
         Self {
             url,
@@ -111,15 +180,32 @@ impl InternalRelay {
             #[cfg(feature = "nip11")]
             document: Arc::new(RwLock::new(RelayInformationDocument::new())),
             opts,
-            stats: RelayConnectionStats::new(),
+            stats,
             database,
             scheduled_for_stop: Arc::new(AtomicBool::new(false)),
             scheduled_for_termination: Arc::new(AtomicBool::new(false)),
+            retry_now: Arc::new(Notify::new()),
             relay_sender,
             relay_receiver: Arc::new(Mutex::new(relay_receiver)),
             internal_notification_sender: relay_notification_sender,
             external_notification_sender: Arc::new(RwLock::new(None)),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            req_mappings: Arc::new(RwLock::new(ReqMappings::default())),
+            cursors: Arc::new(RwLock::new(HashMap::new())),
+            costs: Arc::new(Mutex::new(CostMeters::default())),
+            retrying: Arc::new(Mutex::new(HashMap::new())),
+            verifier: Arc::new(RwLock::new(None)),
+            dedup: Arc::new(RwLock::new(None)),
+            filtering: Arc::new(RwLock::new(None)),
+            notification_kinds: Arc::new(RwLock::new(NotificationKinds::default())),
+            message_logging: Arc::new(AtomicBool::new(false)),
+            lazy_connected: Arc::new(AtomicBool::new(false)),
+            last_activity: Arc::new(AtomicU64::new(0)),
+            auth_required: Arc::new(Mutex::new(HashMap::new())),
+            auth_events: Arc::new(Mutex::new(HashSet::new())),
+            outgoing_limiter,
+            incoming_limiter,
+            supervisor,
         }
     }
 
@@ -194,6 +280,108 @@ impl InternalRelay {
     pub(crate) async fn remove_subscription(&self, id: &SubscriptionId) {
         let mut subscriptions = self.subscriptions.write().await;
         subscriptions.remove(id);
+        let mut cursors = self.cursors.write().await;
+        cursors.remove(id);
+        let mut costs = self.costs.lock().await;
+        costs.remove(id);
+    }
+
+    pub async fn subscription_cost(&self, id: &SubscriptionId) -> Option<SubscriptionCost> {
+        let costs = self.costs.lock().await;
+        costs.get(id)
+    }
+
+    pub async fn subscription_costs(&self) -> HashMap<SubscriptionId, SubscriptionCost> {
+        let costs = self.costs.lock().await;
+        costs.all()
+    }
+
+    /// `created_at` of the most recent event received for each long-lived subscription
+    pub async fn cursors(&self) -> HashMap<SubscriptionId, Timestamp> {
+        self.cursors.read().await.clone()
+    }
+
+    /// Restore subscription cursors, keeping the most recent ones
+    pub(crate) async fn restore_cursors(&self, restored: HashMap<SubscriptionId, Timestamp>) {
+        let mut cursors = self.cursors.write().await;
+        for (id, timestamp) in restored.into_iter() {
+            let current: &mut Timestamp = cursors.entry(id).or_insert(timestamp);
+            if timestamp > *current {
+                *current = timestamp;
+            }
+        }
+    }
+
+    async fn update_cursor(&self, subscription_id: &str, created_at: Timestamp) {
+        let id: SubscriptionId = SubscriptionId::new(subscription_id);
+
+        // Track only long-lived subscriptions
+        if !self.subscriptions.read().await.contains_key(&id) {
+            return;
+        }
+
+        // Never move the cursor in the future
+        let created_at: Timestamp = created_at.min(Timestamp::now());
+
+        let mut cursors = self.cursors.write().await;
+        let current: &mut Timestamp = cursors.entry(id).or_insert(created_at);
+        if created_at > *current {
+            *current = created_at;
+        }
+    }
+
+    pub async fn req_mapping(&self, id: &SubscriptionId) -> Option<ReqMapping> {
+        let req_mappings = self.req_mappings.read().await;
+        req_mappings.get(id).cloned()
+    }
+
+    /// Get the ID of the subscription requested by the user
+    ///
+    /// The additional subscriptions of a split `REQ` (`<id>:<n>`) are mapped back to the original ID.
+    async fn original_subscription_id(&self, id: SubscriptionId) -> SubscriptionId {
+        let req_mappings = self.req_mappings.read().await;
+        req_mappings.original_id(&id).clone()
+    }
+
+    /// Get the max `REQ` size
+    ///
+    /// The lowest between the `max_req_size` limit and the NIP11 `max_message_length` is used.
+    async fn max_req_size(&self) -> Option<usize> {
+        let limit: Option<usize> = self.opts.limits.messages.max_req_size.map(|s| s as usize);
+
+        #[cfg(feature = "nip11")]
+        {
+            let document = self.document().await;
+            let max_message_length: Option<usize> = document
+                .limitation
+                .and_then(|l| l.max_message_length)
+                .and_then(|len| usize::try_from(len).ok())
+                .filter(|len| *len > 0);
+            if let Some(len) = max_message_length {
+                return Some(limit.map_or(len, |limit| limit.min(len)));
+            }
+        }
+
+        limit
+    }
+
+    pub async fn max_subscriptions(&self) -> Option<usize> {
+        let limit: Option<usize> = self.opts.max_subscriptions;
+
+        #[cfg(feature = "nip11")]
+        {
+            let document = self.document().await;
+            let max_subscriptions: Option<usize> = document
+                .limitation
+                .and_then(|l| l.max_subscriptions)
+                .and_then(|max| usize::try_from(max).ok())
+                .filter(|max| *max > 0);
+            if let Some(max) = max_subscriptions {
+                return Some(limit.map_or(max, |limit| limit.min(max)));
+            }
+        }
+
+        limit
     }
 
     #[inline]
@@ -240,6 +428,77 @@ impl InternalRelay {
         *external_notification_sender = notification_sender;
     }
 
+    pub async fn set_event_verifier(&self, verifier: Option<EventVerifier>) {
+        let mut current = self.verifier.write().await;
+        *current = verifier;
+    }
+
+    pub async fn set_event_dedup(&self, dedup: Option<EventDedup>) {
+        let mut current = self.dedup.write().await;
+        *current = dedup;
+    }
+
+    pub async fn set_filtering_policy(&self, filtering: Option<FilteringPolicy>) {
+        let mut current = self.filtering.write().await;
+        *current = filtering;
+    }
+
+    pub async fn set_notification_kinds(&self, kinds: NotificationKinds) {
+        let mut current = self.notification_kinds.write().await;
+        *current = kinds;
+    }
+
+    pub fn message_logging(&self) -> bool {
+        self.message_logging.load(Ordering::SeqCst)
+    }
+
+    pub fn set_message_logging(&self, enable: bool) {
+        self.message_logging.store(enable, Ordering::SeqCst);
+    }
+
+    /// Wait until the rate limit allows to send `n` messages
+    async fn throttle(&self, n: usize) {
+        if let Some(limiter) = &self.outgoing_limiter {
+            let wait: Option<Duration> = limiter.lock().await.acquire(n);
+            if let Some(wait) = wait {
+                self.stats.add_throttled_messages(n);
+                tracing::debug!("Rate limit reached for {}: waiting {wait:?}", self.url);
+                thread::sleep(wait).await;
+            }
+        }
+    }
+
+    /// Apply the flood protection to a received event
+    ///
+    /// Return `false` if the event must be dropped.
+    async fn check_flood(&self) -> bool {
+        let (limiter, action) = match (&self.incoming_limiter, self.opts.flood_protection) {
+            (Some(limiter), Some(protection)) => (limiter, protection.action),
+            _ => return true,
+        };
+
+        match action {
+            FloodAction::Drop => {
+                if limiter.lock().await.try_acquire() {
+                    true
+                } else {
+                    self.stats.new_dropped_event();
+                    tracing::trace!("Flood from {}: event dropped", self.url);
+                    false
+                }
+            }
+            FloodAction::Buffer => {
+                let wait: Option<Duration> = limiter.lock().await.acquire(1);
+                if let Some(wait) = wait {
+                    self.stats.new_buffered_event();
+                    tracing::trace!("Flood from {}: waiting {wait:?}", self.url);
+                    thread::sleep(wait).await;
+                }
+                true
+            }
+        }
+    }
+
     async fn send_notification(&self, notification: RelayNotification) {
         // Send internal notification
         let _ = self.internal_notification_sender.send(notification.clone());
@@ -247,6 +506,25 @@ impl InternalRelay {
         // Send external notification
         let external_notification_sender = self.external_notification_sender.read().await;
         if let Some(external_notification_sender) = external_notification_sender.as_ref() {
+            // Deliver a single `OK` for retried events
+            if let RelayNotification::Message {
+                message:
+                    RelayMessage::Ok {
+                        event_id,
+                        status,
+                        message,
+                    },
+            } = &notification
+            {
+                let mut retrying = self.retrying.lock().await;
+                if let Some(delivered) = retrying.get_mut(event_id) {
+                    if *delivered || (!*status && is_transient_rejection(message)) {
+                        return;
+                    }
+                    *delivered = true;
+                }
+            }
+
             // Convert relay to notification to pool notification
             let notification: RelayPoolNotification = match notification {
                 RelayNotification::Event {
@@ -254,7 +532,7 @@ impl InternalRelay {
                     event,
                 } => RelayPoolNotification::Event {
                     relay_url: self.url(),
-                    subscription_id,
+                    subscription_id: self.original_subscription_id(subscription_id).await,
                     event,
                 },
                 RelayNotification::Message { message } => RelayPoolNotification::Message {
@@ -265,16 +543,126 @@ impl InternalRelay {
                     relay_url: self.url(),
                     status,
                 },
+                RelayNotification::Subscription {
+                    subscription_id,
+                    lifecycle,
+                } => RelayPoolNotification::Subscription {
+                    relay_url: self.url(),
+                    subscription_id,
+                    lifecycle,
+                },
                 RelayNotification::Shutdown => RelayPoolNotification::Shutdown,
                 RelayNotification::Stop => RelayPoolNotification::Stop,
             };
 
+            // Filter by kind
+            if !self
+                .notification_kinds
+                .read()
+                .await
+                .is_delivered(&notification)
+            {
+                return;
+            }
+
             // Send notification
             let _ = external_notification_sender.send(notification);
         }
     }
 
+    async fn send_subscription_notification(
+        &self,
+        subscription_id: SubscriptionId,
+        lifecycle: SubscriptionLifecycle,
+    ) {
+        if lifecycle.is_closed() {
+            let mut costs = self.costs.lock().await;
+            costs.remove(&subscription_id);
+        }
+
+        self.send_notification(RelayNotification::Subscription {
+            subscription_id,
+            lifecycle,
+        })
+        .await;
+    }
+
     pub async fn connect(&self, connection_timeout: Option<Duration>) {
+        // Lazy relays are connected on first use
+        if let ConnectionMode::Lazy { .. } = self.opts.connection_mode {
+            self.lazy_connected.store(false, Ordering::SeqCst);
+            tracing::debug!(
+                "Lazy connection for {}: waiting for the first use",
+                self.url
+            );
+            return;
+        }
+
+        self.establish(connection_timeout).await
+    }
+
+    /// Connect lazy relays on first use and spawn the idle watcher
+    ///
+    /// Boxed to break the cycle of the `Send` check: the tasks spawned by `establish` can get back here.
+    fn connect_on_use(
+        &self,
+        connection_timeout: Duration,
+        idle_timeout: Duration,
+    ) -> BoxedFuture<'_> {
+        Box::pin(async move {
+            self.last_activity
+                .store(Timestamp::now().as_u64(), Ordering::SeqCst);
+
+            if let RelayStatus::Initialized | RelayStatus::Stopped = self.status().await {
+                if !self.lazy_connected.swap(true, Ordering::SeqCst) {
+                    tracing::debug!("Lazy connecting to {}", self.url);
+                    self.establish(Some(connection_timeout)).await;
+                    self.spawn_idle_watcher(idle_timeout);
+                }
+            }
+        })
+    }
+
+    fn spawn_idle_watcher(&self, idle_timeout: Duration) {
+        let relay = self.clone();
+        let _ = thread::spawn(async move {
+            let interval: Duration =
+                idle_timeout.clamp(Duration::from_secs(1), Duration::from_secs(60));
+            loop {
+                thread::sleep(interval).await;
+
+                // Stopped or terminated by the user
+                if let RelayStatus::Stopped | RelayStatus::Terminated = relay.status().await {
+                    break;
+                }
+
+                if !relay.subscriptions.read().await.is_empty() {
+                    continue;
+                }
+
+                let last_activity: u64 = relay.last_activity.load(Ordering::SeqCst);
+                if Timestamp::now().as_u64().saturating_sub(last_activity) >= idle_timeout.as_secs()
+                {
+                    tracing::debug!("Disconnecting idle relay {}", relay.url);
+
+                    // Not use `stop`: the `Stop` notification would end the notification handlers
+                    relay.schedule_for_stop(true);
+                    if !relay.status().await.is_disconnected() {
+                        if let Err(e) = relay.send_relay_event(RelayEvent::Stop, None) {
+                            tracing::error!(
+                                "Impossible to disconnect idle relay {}: {e}",
+                                relay.url
+                            );
+                        }
+                    }
+                    relay.lazy_connected.store(false, Ordering::SeqCst);
+                    break;
+                }
+            }
+        });
+    }
+
+    async fn establish(&self, connection_timeout: Option<Duration>) {
         self.schedule_for_stop(false);
         self.schedule_for_termination(false);
 
@@ -294,68 +682,17 @@ impl InternalRelay {
 
                 let relay = self.clone();
                 let _ = thread::spawn(async move {
-                    loop {
-                        let queue = relay.queue();
-                        if queue > 0 {
-                            tracing::info!(
-                                "{} messages queued for {} (capacity: {})",
-                                queue,
-                                relay.url(),
-                                relay.relay_sender.capacity()
-                            );
-                        }
-
-                        // Schedule relay for termination
-                        // Needed to terminate the auto reconnect loop, also if the relay is not connected yet.
-                        if relay.is_scheduled_for_stop() {
-                            relay.set_status(RelayStatus::Stopped).await;
-                            relay.schedule_for_stop(false);
-                            tracing::debug!(
-                                "Auto connect loop terminated for {} [stop - schedule]",
-                                relay.url
-                            );
-                            break;
-                        } else if relay.is_scheduled_for_termination() {
-                            relay.set_status(RelayStatus::Terminated).await;
-                            relay.schedule_for_termination(false);
-                            tracing::debug!(
-                                "Auto connect loop terminated for {} [schedule]",
-                                relay.url
-                            );
-                            break;
-                        }
-
-                        // Check status
-                        match relay.status().await {
-                            RelayStatus::Initialized
-                            | RelayStatus::Pending
-                            | RelayStatus::Disconnected => {
-                                relay.try_connect(connection_timeout).await
-                            }
-                            RelayStatus::Stopped | RelayStatus::Terminated => {
-                                tracing::debug!("Auto connect loop terminated for {}", relay.url);
-                                break;
-                            }
-                            _ => (),
-                        };
-
-                        let retry_sec: u64 = if relay.opts.get_adjust_retry_sec() {
-                            let var: u64 =
-                                relay.stats.attempts().saturating_sub(relay.stats.success()) as u64;
-                            if var >= 3 {
-                                let retry_interval: i64 =
-                                    cmp::min(MIN_RETRY_SEC * (1 + var), MAX_ADJ_RETRY_SEC) as i64;
-                                let jitter: i64 = rand::thread_rng().gen_range(-1..=1);
-                                retry_interval.saturating_add(jitter) as u64
-                            } else {
-                                relay.opts().get_retry_sec()
-                            }
-                        } else {
-                            relay.opts().get_retry_sec()
-                        };
+                    let restarted: bool = relay
+                        .supervisor
+                        .supervise(Actor::Connection, || relay.auto_connect(connection_timeout))
+                        .await;
 
-                        tracing::trace!("{} retry time set to {retry_sec} secs", relay.url);
-                        thread::sleep(Duration::from_secs(retry_sec)).await;
+                    if !restarted {
+                        relay.set_status(RelayStatus::Stopped).await;
+                        tracing::error!(
+                            "Auto connect loop terminated for {} [too many crashes]",
+                            relay.url
+                        );
                     }
                 });
             } else if connection_timeout.is_some() {
@@ -367,6 +704,73 @@ impl InternalRelay {
         }
     }
 
+    #[tracing::instrument(name = "relay", skip_all, fields(url = %self.url))]
+    async fn auto_connect(&self, connection_timeout: Option<Duration>) {
+        loop {
+            let queue = self.queue();
+            if queue > 0 {
+                tracing::info!(
+                    "{} messages queued for {} (capacity: {})",
+                    queue,
+                    self.url(),
+                    self.relay_sender.capacity()
+                );
+            }
+
+            // Schedule relay for termination
+            // Needed to terminate the auto reconnect loop, also if the relay is not connected yet.
+            if self.is_scheduled_for_stop() {
+                self.set_status(RelayStatus::Stopped).await;
+                self.schedule_for_stop(false);
+                tracing::debug!(
+                    "Auto connect loop terminated for {} [stop - schedule]",
+                    self.url
+                );
+                break;
+            } else if self.is_scheduled_for_termination() {
+                self.set_status(RelayStatus::Terminated).await;
+                self.schedule_for_termination(false);
+                tracing::debug!("Auto connect loop terminated for {} [schedule]", self.url);
+                break;
+            }
+
+            // Check status
+            match self.status().await {
+                RelayStatus::Initialized | RelayStatus::Pending | RelayStatus::Disconnected => {
+                    self.try_connect(connection_timeout).await
+                }
+                RelayStatus::Stopped | RelayStatus::Terminated => {
+                    tracing::debug!("Auto connect loop terminated for {}", self.url);
+                    break;
+                }
+                _ => (),
+            };
+
+            let retry_sec: u64 = if self.opts.get_adjust_retry_sec() {
+                let var: u64 = self.stats.attempts().saturating_sub(self.stats.success()) as u64;
+                if var >= 3 {
+                    let retry_interval: i64 =
+                        cmp::min(MIN_RETRY_SEC * (1 + var), MAX_ADJ_RETRY_SEC) as i64;
+                    let jitter: i64 = rand::thread_rng().gen_range(-1..=1);
+                    retry_interval.saturating_add(jitter) as u64
+                } else {
+                    self.opts().get_retry_sec()
+                }
+            } else {
+                self.opts().get_retry_sec()
+            };
+
+            tracing::trace!("{} retry time set to {retry_sec} secs", self.url);
+
+            // Wait the retry interval or a wake up (i.e. network connectivity restored)
+            let _ = time::timeout(
+                Some(Duration::from_secs(retry_sec)),
+                self.retry_now.notified(),
+            )
+            .await;
+        }
+    }
+
     #[cfg(feature = "nip11")]
     fn request_nip11_document(&self) {
         let relay = self.clone();
@@ -383,302 +787,492 @@ impl InternalRelay {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    fn spawn_pinger(&self) -> Option<AbortHandle> {
+    fn spawn_pinger(&self, receiver_abort_handle: Option<AbortHandle>) -> Option<AbortHandle> {
         let relay = self.clone();
         thread::abortable(async move {
-            if relay.opts.flags.has_ping() {
-                tracing::debug!("Relay Ping Thread Started");
+            if relay
+                .supervisor
+                .run(Actor::Pinger, relay.pinger(receiver_abort_handle))
+                .await
+            {
+                relay.handle_crash(Actor::Pinger).await;
+            }
+        })
+        .ok()
+    }
 
-                loop {
-                    if relay.stats.ping.last_nonce() != 0 && !relay.stats.ping.replied() {
-                        tracing::warn!("{} not replied to ping", relay.url);
-                        relay.stats.ping.reset();
-                        break;
+    #[cfg(not(target_arch = "wasm32"))]
+    #[tracing::instrument(name = "relay", skip_all, fields(url = %self.url))]
+    async fn pinger(&self, receiver_abort_handle: Option<AbortHandle>) {
+        if self.opts.flags.has_ping() {
+            tracing::debug!("Relay Ping Thread Started");
+
+            let interval: Duration = self.opts.ping_interval;
+            let pong_timeout: Duration = cmp::min(self.opts.pong_timeout, interval);
+
+            loop {
+                let nonce: u64 = rand::random();
+                self.stats.ping.set_last_nonce(nonce);
+                self.stats.ping.set_replied(false);
+
+                if let Err(e) = self.send_relay_event(RelayEvent::Ping { nonce }, None) {
+                    tracing::error!("Impossible to ping {}: {e}", self.url);
+                    break;
+                };
+
+                thread::sleep(pong_timeout).await;
+
+                if !self.stats.ping.replied() {
+                    tracing::warn!(
+                        "{} not replied to ping within {pong_timeout:?}: connection is dead",
+                        self.url
+                    );
+                    self.stats.ping.reset();
+
+                    // The receiver may wait forever on a zombie connection: stop it
+                    if let Some(handle) = receiver_abort_handle {
+                        handle.abort();
                     }
 
-                    let nonce: u64 = rand::random();
-                    relay.stats.ping.set_last_nonce(nonce);
-                    relay.stats.ping.set_replied(false);
+                    if let Err(e) = self.send_relay_event(RelayEvent::Close, None) {
+                        tracing::error!("Impossible to close connection with {}: {e}", self.url);
+                    }
 
-                    if let Err(e) = relay.send_relay_event(RelayEvent::Ping { nonce }, None) {
-                        tracing::error!("Impossible to ping {}: {e}", relay.url);
-                        break;
-                    };
+                    // Mark as disconnected immediately (also if the sender is stuck),
+                    // so the reconnect loop can take over
+                    self.set_status(RelayStatus::Disconnected).await;
 
-                    thread::sleep(Duration::from_secs(PING_INTERVAL)).await;
+                    return;
                 }
 
-                tracing::debug!("Exited from Ping Thread of {}", relay.url);
+                thread::sleep(interval.saturating_sub(pong_timeout)).await;
+            }
 
-                if let Err(err) = relay.disconnect().await {
-                    tracing::error!("Impossible to disconnect {}: {}", relay.url, err);
-                }
+            tracing::debug!("Exited from Ping Thread of {}", self.url);
+
+            if let Err(err) = self.disconnect().await {
+                tracing::error!("Impossible to disconnect {}: {}", self.url, err);
             }
-        })
-        .ok()
+        }
     }
 
     #[inline]
     #[cfg(target_arch = "wasm32")]
-    fn spawn_pinger(&self) -> Option<AbortHandle> {
+    fn spawn_pinger(&self, _receiver_abort_handle: Option<AbortHandle>) -> Option<AbortHandle> {
         None
     }
 
-    fn spawn_message_sender(&self, mut ws_tx: Sink, _ping_abort_handle: Option<AbortHandle>) {
+    fn spawn_message_sender(
+        &self,
+        ws_tx: Sink,
+        _ping_abort_handle: Option<AbortHandle>,
+        receiver_abort_handle: Option<AbortHandle>,
+    ) {
         let relay = self.clone();
         let _ = thread::spawn(async move {
-            tracing::debug!("Relay Event Thread Started");
-            let mut rx = relay.relay_receiver.lock().await;
-            while let Some((relay_event, oneshot_sender)) = rx.recv().await {
-                match relay_event {
-                    RelayEvent::Batch(msgs) => {
-                        let msgs: Vec<String> = msgs.into_iter().map(|msg| msg.as_json()).collect();
-                        let size: usize = msgs.iter().map(|msg| msg.as_bytes().len()).sum();
-                        let len = msgs.len();
-
-                        if len == 1 {
-                            if let Some(json) = msgs.first() {
-                                tracing::debug!(
-                                    "Sending {json} to {} (size: {size} bytes)",
-                                    relay.url
-                                );
+            let crashed: bool = relay
+                .supervisor
+                .run(Actor::Sender, relay.message_sender(ws_tx))
+                .await;
+
+            tracing::debug!("Exited from Relay Event Thread");
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(handle) = _ping_abort_handle {
+                handle.abort();
+            }
+
+            if crashed {
+                // The websocket sink is gone: stop also the receiver
+                if let Some(handle) = receiver_abort_handle {
+                    handle.abort();
+                }
+                relay.handle_crash(Actor::Sender).await;
+            }
+        });
+    }
+
+    #[tracing::instrument(name = "relay", skip_all, fields(url = %self.url))]
+    async fn message_sender(&self, mut ws_tx: Sink) {
+        tracing::debug!("Relay Event Thread Started");
+        let mut rx = self.relay_receiver.lock().await;
+        while let Some((relay_event, oneshot_sender)) = rx.recv().await {
+            match relay_event {
+                RelayEvent::Batch(msgs) => {
+                    let msgs: Vec<String> = msgs.into_iter().map(|msg| msg.as_json()).collect();
+                    let size: usize = msgs.iter().map(|msg| msg.as_bytes().len()).sum();
+                    let len = msgs.len();
+
+                    if self.message_logging() {
+                        for json in msgs.iter() {
+                            tracing::info!(target: MESSAGE_LOG_TARGET, url = %self.url, "Sent {json}");
+                        }
+                    }
+
+                    if len == 1 {
+                        if let Some(json) = msgs.first() {
+                            tracing::debug!("Sending {json} to {} (size: {size} bytes)", self.url);
+                        }
+                    } else {
+                        tracing::debug!(
+                            "Sending {len} messages to {} (size: {size} bytes)",
+                            self.url
+                        );
+                    }
+
+                    self.throttle(len).await;
+
+                    let msgs = msgs.into_iter().map(|msg| Ok(WsMessage::Text(msg)));
+                    let mut stream = futures_util::stream::iter(msgs);
+                    match ws_tx.send_all(&mut stream).await {
+                        Ok(_) => {
+                            self.stats.add_bytes_sent(size);
+                            if let Some(sender) = oneshot_sender {
+                                if let Err(e) = sender.send(true) {
+                                    tracing::error!("Impossible to send oneshot msg: {}", e);
+                                }
                             }
-                        } else {
-                            tracing::debug!(
-                                "Sending {len} messages to {} (size: {size} bytes)",
-                                relay.url
+                        }
+                        Err(e) => {
+                            tracing::error!(
+                                "Impossible to send {len} messages to {}: {}",
+                                self.url(),
+                                e.to_string()
                             );
+                            if let Some(sender) = oneshot_sender {
+                                if let Err(e) = sender.send(false) {
+                                    tracing::error!("Impossible to send oneshot msg: {}", e);
+                                }
+                            }
+                            break;
                         }
-
-                        let msgs = msgs.into_iter().map(|msg| Ok(WsMessage::Text(msg)));
-                        let mut stream = futures_util::stream::iter(msgs);
-                        match ws_tx.send_all(&mut stream).await {
+                    }
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                RelayEvent::Ping { nonce } => {
+                    if self.opts.flags.has_ping() {
+                        match ws_tx
+                            .send(WsMessage::Ping(nonce.to_string().as_bytes().to_vec()))
+                            .await
+                        {
                             Ok(_) => {
-                                relay.stats.add_bytes_sent(size);
-                                if let Some(sender) = oneshot_sender {
-                                    if let Err(e) = sender.send(true) {
-                                        tracing::error!("Impossible to send oneshot msg: {}", e);
-                                    }
-                                }
+                                self.stats.ping.just_sent().await;
+                                tracing::debug!("Ping {} (nonce {})", self.url, nonce);
                             }
                             Err(e) => {
                                 tracing::error!(
-                                    "Impossible to send {len} messages to {}: {}",
-                                    relay.url(),
+                                    "Impossible to ping {}: {}",
+                                    self.url(),
                                     e.to_string()
                                 );
-                                if let Some(sender) = oneshot_sender {
-                                    if let Err(e) = sender.send(false) {
-                                        tracing::error!("Impossible to send oneshot msg: {}", e);
-                                    }
-                                }
-                                break;
                             }
                         }
                     }
-                    #[cfg(not(target_arch = "wasm32"))]
-                    RelayEvent::Ping { nonce } => {
-                        if relay.opts.flags.has_ping() {
-                            match ws_tx
-                                .send(WsMessage::Ping(nonce.to_string().as_bytes().to_vec()))
-                                .await
-                            {
-                                Ok(_) => {
-                                    relay.stats.ping.just_sent().await;
-                                    tracing::debug!("Ping {} (nonce {})", relay.url, nonce);
-                                }
-                                Err(e) => {
-                                    tracing::error!(
-                                        "Impossible to ping {}: {}",
-                                        relay.url(),
-                                        e.to_string()
-                                    );
-                                }
-                            }
-                        }
-                    }
-                    RelayEvent::Close => {
+                }
+                RelayEvent::Close => {
+                    let _ = ws_tx.close().await;
+                    self.set_status(RelayStatus::Disconnected).await;
+                    tracing::info!("Disconnected from {}", self.url);
+                    break;
+                }
+                RelayEvent::Stop => {
+                    if self.is_scheduled_for_stop() {
                         let _ = ws_tx.close().await;
-                        relay.set_status(RelayStatus::Disconnected).await;
-                        tracing::info!("Disconnected from {}", relay.url);
+                        self.set_status(RelayStatus::Stopped).await;
+                        self.schedule_for_stop(false);
+                        tracing::info!("Stopped {}", self.url);
                         break;
                     }
-                    RelayEvent::Stop => {
-                        if relay.is_scheduled_for_stop() {
-                            let _ = ws_tx.close().await;
-                            relay.set_status(RelayStatus::Stopped).await;
-                            relay.schedule_for_stop(false);
-                            tracing::info!("Stopped {}", relay.url);
-                            break;
-                        }
-                    }
-                    RelayEvent::Terminate => {
-                        if relay.is_scheduled_for_termination() {
-                            let _ = ws_tx.close().await;
-                            relay.set_status(RelayStatus::Terminated).await;
-                            relay.schedule_for_termination(false);
-                            tracing::info!("Completely disconnected from {}", relay.url);
-                            break;
-                        }
+                }
+                RelayEvent::Terminate => {
+                    if self.is_scheduled_for_termination() {
+                        let _ = ws_tx.close().await;
+                        self.set_status(RelayStatus::Terminated).await;
+                        self.schedule_for_termination(false);
+                        tracing::info!("Completely disconnected from {}", self.url);
+                        break;
                     }
                 }
             }
+        }
+    }
 
-            tracing::debug!("Exited from Relay Event Thread");
-
-            #[cfg(not(target_arch = "wasm32"))]
-            if let Some(handle) = _ping_abort_handle {
-                handle.abort();
-            }
-        });
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_message_receiver(&self, ws_rx: Stream) -> Option<AbortHandle> {
+        let relay = self.clone();
+        thread::abortable(async move { relay.run_message_receiver(ws_rx).await }).ok()
     }
 
-    fn spawn_message_receiver(&self, mut ws_rx: Stream) {
+    #[cfg(target_arch = "wasm32")]
+    fn spawn_message_receiver(&self, ws_rx: Stream) -> Option<AbortHandle> {
         let relay = self.clone();
-        let _ = thread::spawn(async move {
-            tracing::debug!("Relay Message Thread Started");
+        let _ = thread::spawn(async move { relay.run_message_receiver(ws_rx).await });
+        None
+    }
 
-            async fn func(relay: &InternalRelay, data: Vec<u8>) -> Result<bool, Error> {
-                let size: usize = data.len();
-                relay.stats.add_bytes_received(size);
+    async fn run_message_receiver(&self, ws_rx: Stream) {
+        if self
+            .supervisor
+            .run(Actor::Receiver, self.message_receiver(ws_rx))
+            .await
+        {
+            self.handle_crash(Actor::Receiver).await;
+            return;
+        }
 
-                if let Some(max_size) = relay.opts.limits.messages.max_size {
-                    let max_size: usize = max_size as usize;
-                    if size > max_size {
-                        return Err(Error::RelayMessageTooLarge { size, max_size });
-                    }
-                }
+        tracing::debug!("Exited from Message Thread of {}", self.url);
 
-                let msg = RawRelayMessage::from_json(&data)?;
-                tracing::trace!("Received message from {}: {:?}", relay.url, msg);
+        if let Err(err) = self.disconnect().await {
+            tracing::error!("Impossible to disconnect {}: {}", self.url, err);
+        }
+    }
 
-                if let RawRelayMessage::Event { event, .. } = &msg {
-                    // Check event size
-                    if let Some(max_size) = relay.opts.limits.events.max_size {
-                        let size: usize = event.as_json().as_bytes().len();
-                        let max_size: usize = max_size as usize;
-                        if size > max_size {
-                            return Err(Error::EventTooLarge { size, max_size });
-                        }
-                    }
+    #[tracing::instrument(name = "relay", skip_all, fields(url = %self.url))]
+    async fn message_receiver(&self, mut ws_rx: Stream) {
+        tracing::debug!("Relay Message Thread Started");
+
+        // Events are verified concurrently, but messages are handled in the order they are received
+        let mut pending = FuturesOrdered::new();
 
-                    // Check tags limit
-                    if let Some(max_num_tags) = relay.opts.limits.events.max_num_tags {
-                        let size: usize = event.tags.len();
-                        let max_num_tags: usize = max_num_tags as usize;
-                        if size > max_num_tags {
-                            return Err(Error::TooManyTags {
-                                size,
-                                max_size: max_num_tags,
-                            });
+        loop {
+            // Wait for the next message, unless too many are waiting for verification
+            let next = if pending.len() >= MAX_PENDING_MESSAGES {
+                Either::Right(pending.next().await)
+            } else if pending.is_empty() {
+                Either::Left(ws_rx.next().await)
+            } else {
+                match future::select(ws_rx.next(), pending.next()).await {
+                    Either::Left((msg, _)) => Either::Left(msg),
+                    Either::Right((res, _)) => Either::Right(res),
+                }
+            };
+
+            match next {
+                Either::Left(Some(msg)) => {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Ok(msg) = msg {
+                        match msg {
+                            WsMessage::Pong(bytes) => self.handle_pong(bytes).await,
+                            msg => pending.push_back(self.receive_message(msg.into_data())),
                         }
                     }
+
+                    #[cfg(target_arch = "wasm32")]
+                    pending.push_back(self.receive_message(msg.as_ref().to_vec()));
                 }
+                Either::Left(None) => break,
+                Either::Right(Some(res)) => self.handle_received_message(res).await,
+                Either::Right(None) => (),
+            }
+        }
 
-                match relay.handle_relay_message(msg).await {
-                    Ok(Some(msg)) => {
-                        // Send notification
-                        relay
-                            .send_notification(RelayNotification::Message {
-                                message: msg.clone(),
-                            })
-                            .await;
+        // Handle the messages received before the stream closed
+        while let Some(res) = pending.next().await {
+            self.handle_received_message(res).await;
+        }
+    }
 
-                        match msg {
-                            RelayMessage::Notice { message } => {
-                                tracing::warn!("Notice from {}: {message}", relay.url)
-                            }
-                            RelayMessage::Ok {
-                                event_id,
-                                status,
-                                message,
-                            } => {
-                                tracing::debug!("Received OK from {} for event {event_id}: status={status}, message={message}", relay.url);
-                            }
-                            _ => (),
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn handle_pong(&self, bytes: Vec<u8>) {
+        if self.opts.flags.has_ping() {
+            match String::from_utf8(bytes) {
+                Ok(nonce) => match nonce.parse::<u64>() {
+                    Ok(nonce) => {
+                        if self.stats.ping.last_nonce() == nonce {
+                            tracing::debug!("Pong from {} match nonce: {}", self.url, nonce);
+                            self.stats.ping.set_replied(true);
+                            let sent_at = self.stats.ping.sent_at().await;
+                            self.stats.save_latency(sent_at.elapsed()).await;
+                        } else {
+                            tracing::error!(
+                                "Pong nonce not match: received={nonce}, expected={}",
+                                self.stats.ping.last_nonce()
+                            );
                         }
                     }
-                    Ok(None) => (),
-                    Err(e) => tracing::error!(
-                        "Impossible to handle relay message from {}: {e}",
-                        relay.url
-                    ),
+                    Err(e) => tracing::error!("{e}"),
+                },
+                Err(e) => tracing::error!("{e}"),
+            }
+        }
+    }
+
+    /// Check and verify a received message
+    ///
+    /// Run concurrently for the pending messages.
+    async fn receive_message(&self, data: Vec<u8>) -> Result<Option<ReceivedMessage>, Error> {
+        let size: usize = data.len();
+        self.stats.add_bytes_received(size);
+
+        if self.message_logging() {
+            tracing::info!(
+                target: MESSAGE_LOG_TARGET,
+                url = %self.url,
+                "Received {}",
+                String::from_utf8_lossy(&data)
+            );
+        }
+
+        if let Some(max_size) = self.opts.limits.messages.max_size {
+            let max_size: usize = max_size as usize;
+            if size > max_size {
+                return Err(Error::RelayMessageTooLarge { size, max_size });
+            }
+        }
+
+        let msg = RawRelayMessage::from_json(&data)?;
+        tracing::trace!("Received message from {}: {:?}", self.url, msg);
+
+        if let RawRelayMessage::Event {
+            subscription_id,
+            event,
+        } = &msg
+        {
+            // Update subscription cost
+            let id = SubscriptionId::new(subscription_id);
+            self.costs.lock().await.record(&id, size);
+
+            // Check event size
+            if let Some(max_size) = self.opts.limits.events.max_size {
+                let size: usize = event.as_json().as_bytes().len();
+                let max_size: usize = max_size as usize;
+                if size > max_size {
+                    return Err(Error::EventTooLarge { size, max_size });
                 }
+            }
 
-                Ok(false)
+            // Check tags limit
+            if let Some(max_num_tags) = self.opts.limits.events.max_num_tags {
+                let size: usize = event.tags.len();
+                let max_num_tags: usize = max_num_tags as usize;
+                if size > max_num_tags {
+                    return Err(Error::TooManyTags {
+                        size,
+                        max_size: max_num_tags,
+                    });
+                }
             }
 
-            #[cfg(not(target_arch = "wasm32"))]
-            while let Some(msg_res) = ws_rx.next().await {
-                if let Ok(msg) = msg_res {
-                    match msg {
-                        WsMessage::Pong(bytes) => {
-                            if relay.opts.flags.has_ping() {
-                                match String::from_utf8(bytes) {
-                                    Ok(nonce) => match nonce.parse::<u64>() {
-                                        Ok(nonce) => {
-                                            if relay.stats.ping.last_nonce() == nonce {
-                                                tracing::debug!(
-                                                    "Pong from {} match nonce: {}",
-                                                    relay.url,
-                                                    nonce
-                                                );
-                                                relay.stats.ping.set_replied(true);
-                                                let sent_at = relay.stats.ping.sent_at().await;
-                                                relay.stats.save_latency(sent_at.elapsed()).await;
-                                            } else {
-                                                tracing::error!("Pong nonce not match: received={nonce}, expected={}", relay.stats.ping.last_nonce());
-                                            }
-                                        }
-                                        Err(e) => tracing::error!("{e}"),
-                                    },
-                                    Err(e) => tracing::error!("{e}"),
-                                }
-                            }
-                        }
-                        _ => {
-                            let data: Vec<u8> = msg.into_data();
-                            match func(&relay, data).await {
-                                Ok(exit) => {
-                                    if exit {
-                                        break;
-                                    }
-                                }
-                                Err(Error::MessageHandle(MessageHandleError::EmptyMsg)) => {}
-                                Err(e) => tracing::error!(
-                                    "Impossible to handle relay message from {}: {e}",
-                                    relay.url
-                                ),
-                            }
-                        }
+            // Check flood
+            if !self.check_flood().await {
+                return Ok(None);
+            }
+        }
+
+        self.verify_relay_message(msg).await
+    }
+
+    /// Handle a received message, in the order of receipt
+    async fn handle_received_message(&self, res: Result<Option<ReceivedMessage>, Error>) {
+        let res: Result<Option<RelayMessage>, Error> = match res {
+            Ok(Some(msg)) => self.handle_verified_message(msg).await,
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        let msg: RelayMessage = match res {
+            Ok(Some(msg)) => msg,
+            Ok(None) | Err(Error::MessageHandle(MessageHandleError::EmptyMsg)) => return,
+            Err(e) => {
+                tracing::error!("Impossible to handle relay message from {}: {e}", self.url);
+                return;
+            }
+        };
+
+        // Send notification
+        self.send_notification(RelayNotification::Message {
+            message: msg.clone(),
+        })
+        .await;
+
+        match msg {
+            RelayMessage::Notice { message } => {
+                tracing::warn!("Notice from {}: {message}", self.url)
+            }
+            RelayMessage::EndOfStoredEvents(subscription_id) => {
+                self.send_subscription_notification(
+                    subscription_id,
+                    SubscriptionLifecycle::EndOfStoredEvents,
+                )
+                .await;
+            }
+            RelayMessage::Closed {
+                subscription_id,
+                message,
+            } => {
+                tracing::debug!(
+                    "Subscription {subscription_id} closed by {}: {message}",
+                    self.url
+                );
+
+                let reason = SubscriptionCloseReason::Relay {
+                    prefix: MachineReadablePrefix::parse(&message),
+                    message,
+                };
+
+                // Keep the filters to send them again after the authentication
+                if reason.is_auth_required() && self.opts.resubscribe_after_auth {
+                    if let Some(filters) = self.subscription(&subscription_id).await {
+                        let mut auth_required = self.auth_required.lock().await;
+                        auth_required.insert(subscription_id.clone(), filters);
                     }
                 }
-            }
 
-            #[cfg(target_arch = "wasm32")]
-            while let Some(msg) = ws_rx.next().await {
-                let data: Vec<u8> = msg.as_ref().to_vec();
-                match func(&relay, data).await {
-                    Ok(exit) => {
-                        if exit {
-                            break;
+                // Relay closed the subscription: don't re-subscribe on reconnection
+                self.remove_subscription(&subscription_id).await;
+
+                self.send_subscription_notification(
+                    subscription_id,
+                    SubscriptionLifecycle::Closed { reason },
+                )
+                .await;
+            }
+            RelayMessage::Ok {
+                event_id,
+                status,
+                message,
+            } => {
+                tracing::debug!(
+                    "Received OK from {} for event {event_id}: status={status}, message={message}",
+                    self.url
+                );
+
+                // Authenticated: send again the subscriptions closed with `auth-required`
+                if self.auth_events.lock().await.remove(&event_id) && status {
+                    let relay = self.clone();
+                    let _ = thread::spawn(async move {
+                        if let Err(e) = relay.resubscribe_after_auth().await {
+                            tracing::error!(
+                                "Impossible to resubscribe to {} after authentication: {e}",
+                                relay.url
+                            );
                         }
-                    }
-                    Err(e) => tracing::error!(
-                        "Impossible to handle relay message from {}: {e}",
-                        relay.url
-                    ),
+                    });
                 }
             }
+            _ => (),
+        }
+    }
 
-            tracing::debug!("Exited from Message Thread of {}", relay.url);
+    /// Tear down the connection after an actor crash
+    ///
+    /// The connection actors are respawned on reconnection by the auto connect loop,
+    /// unless the restart policy is exhausted: in that case the relay is stopped.
+    async fn handle_crash(&self, actor: Actor) {
+        if !self.supervisor.can_restart() {
+            tracing::error!("Too many crashes for {}: stopping the relay", self.url);
+            self.schedule_for_stop(true);
+        }
 
-            if let Err(err) = relay.disconnect().await {
-                tracing::error!("Impossible to disconnect {}: {}", relay.url, err);
-            }
-        });
+        self.set_status(RelayStatus::Disconnected).await;
+
+        // Ask the sender to close the websocket, if it's still alive
+        if actor != Actor::Sender {
+            let _ = self.send_relay_event(RelayEvent::Close, None);
+        }
     }
 
+    #[tracing::instrument(name = "relay", skip_all, fields(url = %self.url))]
     async fn try_connect(&self, connection_timeout: Option<Duration>) {
         self.stats.new_attempt();
 
@@ -690,7 +1284,9 @@ impl InternalRelay {
 
         // Request `RelayInformationDocument`
         #[cfg(feature = "nip11")]
-        self.request_nip11_document();
+        if !transport::is_unix(&self.url) {
+            self.request_nip11_document();
+        }
 
         // Compose timeout
         let timeout: Option<Duration> = if self.stats.attempts() > 1 {
@@ -702,24 +1298,25 @@ impl InternalRelay {
         };
 
         // Connect
-        match async_wsocket::connect(&self.url, self.proxy(), timeout).await {
+        match transport::connect(&self.url, self.proxy(), timeout).await {
             Ok((ws_tx, ws_rx)) => {
                 self.set_status(RelayStatus::Connected).await;
                 tracing::info!("Connected to {url}");
 
                 self.stats.new_success();
 
+                // Spawn message receiver
+                let receiver_abort_handle: Option<AbortHandle> = self.spawn_message_receiver(ws_rx);
+
                 // Spawn pinger
-                let ping_abort_handle: Option<AbortHandle> = self.spawn_pinger();
+                let ping_abort_handle: Option<AbortHandle> =
+                    self.spawn_pinger(receiver_abort_handle.clone());
 
                 // Spawn message sender
-                self.spawn_message_sender(ws_tx, ping_abort_handle);
-
-                // Spawn message receiver
-                self.spawn_message_receiver(ws_rx);
+                self.spawn_message_sender(ws_tx, ping_abort_handle, receiver_abort_handle);
 
-                // Subscribe to relay
-                if self.opts.flags.has_read() {
+                // Replay subscriptions
+                if self.opts.flags.has_read() && self.opts.get_resubscribe() {
                     if let Err(e) = self
                         .resubscribe_all(RelaySendOptions::default().skip_send_confirmation(true))
                         .await
@@ -739,11 +1336,12 @@ impl InternalRelay {
         };
     }
 
+    /// Check and verify the event, before marking it as seen
     #[tracing::instrument(skip(self), level = "trace")]
-    async fn handle_relay_message(
+    async fn verify_relay_message(
         &self,
         msg: RawRelayMessage,
-    ) -> Result<Option<RelayMessage>, Error> {
+    ) -> Result<Option<ReceivedMessage>, Error> {
         match msg {
             RawRelayMessage::Event {
                 subscription_id,
@@ -758,6 +1356,22 @@ impl InternalRelay {
                     return Err(Error::PowDifficultyTooLow { min: difficulty });
                 }
 
+                // Check filtering policy
+                let filtering: Option<FilteringPolicy> = self.filtering.read().await.clone();
+                if let Some(filtering) = filtering {
+                    if filtering
+                        .is_blocked(&partial_event.id, &partial_event.pubkey)
+                        .await
+                    {
+                        tracing::trace!(
+                            "Received event {} blocked by filtering policy: relay_url={}",
+                            partial_event.id,
+                            self.url
+                        );
+                        return Ok(None);
+                    }
+                }
+
                 // Check if event has been deleted
                 if self
                     .database
@@ -795,24 +1409,6 @@ impl InternalRelay {
                     }
                 }
 
-                // Check if event id was already seen
-                let seen: bool = self
-                    .database
-                    .has_event_already_been_seen(&partial_event.id)
-                    .await?;
-
-                // Set event as seen by relay
-                if let Err(e) = self
-                    .database
-                    .event_id_seen(partial_event.id, self.url())
-                    .await
-                {
-                    tracing::error!(
-                        "Impossible to set event {} as seen by relay: {e}",
-                        partial_event.id
-                    );
-                }
-
                 // Check if event was already saved
                 if self
                     .database
@@ -820,6 +1416,10 @@ impl InternalRelay {
                     .await?
                 {
                     tracing::trace!("Event {} already saved into database", partial_event.id);
+                    // The saved event has already been verified: set it as seen by relay
+                    self.event_id_seen(partial_event.id).await;
+                    self.update_cursor(&subscription_id, missing.created_at)
+                        .await;
                     return Ok(None);
                 }
 
@@ -832,11 +1432,86 @@ impl InternalRelay {
                 }
 
                 // Verify event
-                event.verify()?;
+                let verifier: Option<EventVerifier> = self.verifier.read().await.clone();
+                let (event, status) = match verifier {
+                    Some(verifier) => (
+                        verifier.verify(event).await?,
+                        VerificationStatus::from(verifier.mode()),
+                    ),
+                    None => {
+                        event.verify()?;
+                        (event, VerificationStatus::Verified)
+                    }
+                };
+
+                Ok(Some(ReceivedMessage::Event {
+                    subscription_id,
+                    event,
+                    status,
+                }))
+            }
+            m => Ok(Some(ReceivedMessage::Message(RelayMessage::try_from(m)?))),
+        }
+    }
+
+    /// Mark the verified event as seen and save it
+    async fn handle_verified_message(
+        &self,
+        msg: ReceivedMessage,
+    ) -> Result<Option<RelayMessage>, Error> {
+        match msg {
+            ReceivedMessage::Event {
+                subscription_id,
+                event,
+                status,
+            } => {
+                // Check if event id was already seen (deduplication layer first, then database)
+                let dedup: Option<EventDedup> = self.dedup.read().await.clone();
+                let seen: bool = match &dedup {
+                    Some(dedup) if dedup.check_and_insert(event.id()).await => true,
+                    _ => {
+                        self.database
+                            .has_event_already_been_seen(&event.id())
+                            .await?
+                    }
+                };
+
+                // Set event as seen by relay
+                self.event_id_seen(event.id()).await;
+
+                // Suppress content repeated by the same author
+                // (after verification, to not let forged events hide the real ones)
+                if let Some(dedup) = &dedup {
+                    if dedup.is_duplicated_content(&event).await {
+                        tracing::trace!(
+                            "Received event {} with duplicated content: relay_url={}",
+                            event.id(),
+                            self.url
+                        );
+                        self.stats.new_suppressed_duplicate();
+                        return Ok(None);
+                    }
+                }
+
+                // Set verification status
+                if let Err(e) = self
+                    .database
+                    .set_event_verification(event.id(), status)
+                    .await
+                {
+                    tracing::error!(
+                        "Impossible to set verification status of event {}: {e}",
+                        event.id()
+                    );
+                }
 
                 // Save event
                 self.database.save_event(&event).await?;
 
+                // Update subscription cursor
+                self.update_cursor(&subscription_id, event.created_at())
+                    .await;
+
                 // Box event
                 let event: Box<Event> = Box::new(event);
 
@@ -855,7 +1530,13 @@ impl InternalRelay {
                     event,
                 }))
             }
-            m => Ok(Some(RelayMessage::try_from(m)?)),
+            ReceivedMessage::Message(msg) => Ok(Some(msg)),
+        }
+    }
+
+    async fn event_id_seen(&self, event_id: EventId) {
+        if let Err(e) = self.database.event_id_seen(event_id, self.url()).await {
+            tracing::error!("Impossible to set event {event_id} as seen by relay: {e}");
         }
     }
 
@@ -869,6 +1550,17 @@ impl InternalRelay {
             .map_err(|_| Error::MessageNotSent)
     }
 
+    /// Skip the remaining retry interval of the auto connect loop, if the relay is disconnected
+    pub async fn retry_now(&self) {
+        if matches!(
+            self.status().await,
+            RelayStatus::Pending | RelayStatus::Disconnected
+        ) {
+            tracing::debug!("Retrying to connect to {} now", self.url);
+            self.retry_now.notify_one();
+        }
+    }
+
     async fn disconnect(&self) -> Result<(), Error> {
         let status = self.status().await;
         if !status.is_disconnected() {
@@ -915,6 +1607,21 @@ impl InternalRelay {
             return Err(Error::ReadDisabled);
         }
 
+        if let ConnectionMode::Lazy { idle_timeout } = self.opts.connection_mode {
+            if msgs.iter().any(|msg| !msg.is_close()) {
+                self.connect_on_use(opts.timeout, idle_timeout).await;
+            }
+        }
+
+        if msgs.iter().any(|msg| !msg.is_close()) {
+            let filtering: Option<FilteringPolicy> = self.filtering.read().await.clone();
+            if let Some(filtering) = filtering {
+                if !filtering.is_relay_allowed(&self.url).await {
+                    return Err(Error::RelayNotAllowed);
+                }
+            }
+        }
+
         if opts.skip_disconnected
             && !self.is_connected().await
             && self.stats.attempts() > MIN_ATTEMPTS
@@ -923,6 +1630,16 @@ impl InternalRelay {
             return Err(Error::NotConnected);
         }
 
+        // Track the authentications, to resubscribe when accepted
+        if self.opts.resubscribe_after_auth {
+            let mut auth_events = self.auth_events.lock().await;
+            for msg in msgs.iter() {
+                if let ClientMessage::Auth(event) = msg {
+                    auth_events.insert(event.id());
+                }
+            }
+        }
+
         if opts.skip_send_confirmation {
             self.send_relay_event(RelayEvent::Batch(msgs), None)
         } else {
@@ -942,24 +1659,139 @@ impl InternalRelay {
                 _ => Err(Error::RecvTimeout),
             }
         }
-    }
+    }
+
+    #[inline]
+    pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<EventId, Error> {
+        let id: EventId = event.id();
+        self.batch_event(vec![event], opts).await?;
+        Ok(id)
+    }
+
+    pub async fn batch_event(
+        &self,
+        events: Vec<Event>,
+        opts: RelaySendOptions,
+    ) -> Result<(), Error> {
+        if events.is_empty() {
+            return Err(Error::BatchEventEmpty);
+        }
+
+        if opts.retries == 0 {
+            return self.try_batch_event(events, opts).await;
+        }
+
+        let ids: Vec<EventId> = events.iter().map(|e| e.id()).collect();
+
+        {
+            let mut retrying = self.retrying.lock().await;
+            for id in ids.iter() {
+                retrying.entry(*id).or_insert(false);
+            }
+        }
 
-    #[inline]
-    pub async fn send_event(&self, event: Event, opts: RelaySendOptions) -> Result<EventId, Error> {
-        let id: EventId = event.id();
-        self.batch_event(vec![event], opts).await?;
-        Ok(id)
+        let res = self.batch_event_with_retries(events, opts).await;
+
+        let mut retrying = self.retrying.lock().await;
+        for id in ids.iter() {
+            retrying.remove(id);
+        }
+
+        res
     }
 
-    pub async fn batch_event(
+    async fn batch_event_with_retries(
         &self,
         events: Vec<Event>,
         opts: RelaySendOptions,
     ) -> Result<(), Error> {
-        if events.is_empty() {
-            return Err(Error::BatchEventEmpty);
+        let events_len: usize = events.len();
+        let mut pending: Vec<Event> = events;
+        let mut published: Vec<EventId> = Vec::with_capacity(events_len);
+        let mut not_published: HashMap<EventId, String> = HashMap::new();
+        let mut attempt: u8 = 0;
+
+        loop {
+            let ids: Vec<EventId> = pending.iter().map(|e| e.id()).collect();
+            let (ok, failed, transient): (Vec<EventId>, HashMap<EventId, String>, bool) =
+                match self.try_batch_event(pending.clone(), opts).await {
+                    Ok(()) => (ids, HashMap::new(), false),
+                    Err(Error::EventNotPublished(message)) => (
+                        Vec::new(),
+                        ids.into_iter().map(|id| (id, message.clone())).collect(),
+                        false,
+                    ),
+                    Err(Error::EventsNotPublished(failed)) => (Vec::new(), failed, false),
+                    Err(Error::PartialPublish {
+                        published,
+                        not_published,
+                    }) => (published, not_published, false),
+                    Err(e) => {
+                        let transient: bool = e.is_transient() && attempt < opts.retries;
+
+                        // Nothing to retry or to merge: keep the original error
+                        if !transient && published.is_empty() && not_published.is_empty() {
+                            return Err(e);
+                        }
+
+                        let message: String = e.to_string();
+                        (
+                            Vec::new(),
+                            ids.into_iter().map(|id| (id, message.clone())).collect(),
+                            transient,
+                        )
+                    }
+                };
+
+            published.extend(ok);
+
+            let retry: HashSet<EventId> = if attempt < opts.retries {
+                failed
+                    .iter()
+                    .filter(|(.., message)| transient || is_transient_rejection(message))
+                    .map(|(id, ..)| *id)
+                    .collect()
+            } else {
+                HashSet::new()
+            };
+
+            not_published.extend(failed.into_iter().filter(|(id, ..)| !retry.contains(id)));
+            pending.retain(|e| retry.contains(&e.id()));
+
+            if pending.is_empty() {
+                break;
+            }
+
+            let backoff: Duration = opts.backoff(attempt);
+            attempt += 1;
+            tracing::debug!(
+                "Retrying to send {} events to {} in {backoff:?} (attempt {attempt})",
+                pending.len(),
+                self.url
+            );
+            thread::sleep(backoff).await;
+        }
+
+        if not_published.is_empty() {
+            Ok(())
+        } else if events_len == 1 {
+            let message: String = not_published.into_values().next().unwrap_or_default();
+            Err(Error::EventNotPublished(message))
+        } else if published.is_empty() {
+            Err(Error::EventsNotPublished(not_published))
+        } else {
+            Err(Error::PartialPublish {
+                published,
+                not_published,
+            })
         }
+    }
 
+    async fn try_batch_event(
+        &self,
+        events: Vec<Event>,
+        opts: RelaySendOptions,
+    ) -> Result<(), Error> {
         let events_len: usize = events.len();
         let mut msgs: Vec<ClientMessage> = Vec::with_capacity(events_len);
         let mut missing: HashSet<EventId> = HashSet::with_capacity(events_len);
@@ -1006,9 +1838,9 @@ impl InternalRelay {
                     }
                     RelayNotification::RelayStatus { status } => {
                         if opts.skip_disconnected && status.is_disconnected() {
-                            return Err(Error::EventNotPublished(String::from(
-                                "relay not connected (status changed)",
-                            )));
+                            return Err(Error::EventNotPublished(
+                                Error::NotConnectedStatusChanged.to_string(),
+                            ));
                         }
                     }
                     _ => (),
@@ -1040,12 +1872,69 @@ impl InternalRelay {
         }
 
         let subscriptions = self.subscriptions().await;
+        let cursors = if self.opts.get_adaptive_since() {
+            self.cursors().await
+        } else {
+            HashMap::new()
+        };
+
+        let mut ids: Vec<SubscriptionId> = Vec::with_capacity(subscriptions.len());
+        let mut msgs: Vec<ClientMessage> = Vec::with_capacity(subscriptions.len());
         for (id, filters) in subscriptions.into_iter() {
             if !filters.is_empty() {
-                self.send_msg(ClientMessage::req(id, filters), opts).await?;
+                let filters: Vec<Filter> = match cursors.get(&id) {
+                    Some(cursor) => resume_filters(filters, *cursor),
+                    None => filters,
+                };
+                ids.push(id.clone());
+                msgs.push(ClientMessage::req(id, filters));
             }
         }
 
+        if msgs.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!("Replaying {} subscriptions to {}", msgs.len(), self.url);
+        self.batch_msg(msgs, opts).await?;
+
+        for id in ids.into_iter() {
+            self.send_subscription_notification(id, SubscriptionLifecycle::Resubscribed)
+                .await;
+        }
+
+        Ok(())
+    }
+
+    /// Send again the subscriptions closed with `auth-required`
+    async fn resubscribe_after_auth(&self) -> Result<(), Error> {
+        let subscriptions: HashMap<SubscriptionId, Vec<Filter>> = {
+            let mut auth_required = self.auth_required.lock().await;
+            mem::take(&mut *auth_required)
+        };
+
+        if subscriptions.is_empty() {
+            return Ok(());
+        }
+
+        let msgs: Vec<ClientMessage> = subscriptions
+            .iter()
+            .map(|(id, filters)| ClientMessage::req(id.clone(), filters.clone()))
+            .collect();
+
+        tracing::debug!(
+            "Resubscribing {} subscriptions to {} after authentication",
+            msgs.len(),
+            self.url
+        );
+        self.batch_msg(msgs, RelaySendOptions::default()).await?;
+
+        for (id, filters) in subscriptions.into_iter() {
+            self.update_subscription(id.clone(), filters).await;
+            self.send_subscription_notification(id, SubscriptionLifecycle::Resubscribed)
+                .await;
+        }
+
         Ok(())
     }
 
@@ -1065,7 +1954,7 @@ impl InternalRelay {
         id: SubscriptionId,
         filters: Vec<Filter>,
         opts: SubscribeOptions,
-    ) -> Result<(), Error> {
+    ) -> Result<ReqMapping, Error> {
         // Check if relay has READ flags disabled
         if !self.opts.flags.has_read() {
             return Err(Error::ReadDisabled);
@@ -1076,117 +1965,357 @@ impl InternalRelay {
             return Err(Error::FiltersEmpty);
         }
 
-        // Compose and send message
-        let msg: ClientMessage = ClientMessage::req(id.clone(), filters.clone());
-        self.send_msg(msg, opts.send_opts).await?;
+        // Check if filters are too broad
+        if !opts.allow_expensive {
+            if let Some(filter) = filters.iter().find(|f| cost::is_expensive(f)) {
+                if self.opts.refuse_expensive_filters {
+                    return Err(Error::ExpensiveFilter(filter.as_json()));
+                }
+
+                tracing::warn!(
+                    "Expensive filter in REQ {id} for {}: {}",
+                    self.url,
+                    filter.as_json()
+                );
+            }
+        }
+
+        // Shrink REQ, if needed
+        let mapping: ReqMapping = match self.max_req_size().await {
+            Some(max_size) => shrink::shrink_req(id.clone(), filters, max_size),
+            None => ReqMapping::single(id.clone(), filters),
+        };
+
+        if mapping.is_split() {
+            tracing::debug!(
+                "REQ {id} too large for {}: split into {} subscriptions",
+                self.url,
+                mapping.requests.len()
+            );
+        }
+
+        // Start metering cost
+        {
+            let mut costs = self.costs.lock().await;
+            for (id, ..) in mapping.requests.iter() {
+                costs.start(id.clone());
+            }
+        }
+
+        // Compose and send messages
+        let msgs: Vec<ClientMessage> = mapping
+            .requests
+            .iter()
+            .map(|(id, filters)| ClientMessage::req(id.clone(), filters.clone()))
+            .collect();
+        if let Err(e) = self.batch_msg(msgs, opts.send_opts).await {
+            let mut costs = self.costs.lock().await;
+            for (id, ..) in mapping.requests.iter() {
+                costs.remove(id);
+            }
+            return Err(e);
+        }
+
+        for (id, ..) in mapping.requests.iter() {
+            self.send_subscription_notification(id.clone(), SubscriptionLifecycle::Created)
+                .await;
+        }
 
         // Check if auto-close condition is set
-        if let Some(opts) = opts.auto_close {
-            let this = self.clone();
-            thread::spawn(async move {
-                let sub_id = id.clone();
-                let relay = this.clone();
-                let res = time::timeout(opts.timeout, async move {
-                    let mut counter = 0;
-                    let mut received_eose: bool = false;
-
-                    let mut notifications = relay.internal_notification_sender.subscribe();
-                    while let Ok(notification) = notifications.recv().await {
-                        match notification {
-                            RelayNotification::Message { message, .. } => match message {
-                                RelayMessage::Event {
-                                    subscription_id, ..
-                                } => {
-                                    if subscription_id.eq(&id) {
-                                        if let FilterOptions::WaitForEventsAfterEOSE(num) =
-                                            opts.filter
-                                        {
-                                            if received_eose {
-                                                counter += 1;
-                                                if counter >= num {
-                                                    break;
-                                                }
+        match opts.auto_close {
+            Some(opts) => {
+                for (id, ..) in mapping.requests.iter() {
+                    self.spawn_auto_closing_handler(id.clone(), opts)?;
+                }
+            }
+            None => {
+                // No auto-close subscription: update subscription filters
+                for (id, filters) in mapping.requests.iter() {
+                    self.update_subscription(id.clone(), filters.clone()).await;
+                }
+
+                let mut req_mappings = self.req_mappings.write().await;
+                if mapping.is_split() {
+                    req_mappings.insert(id, mapping.clone());
+                } else {
+                    req_mappings.remove(&id);
+                }
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    pub async fn update_subscription_filters(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+        opts: RelaySendOptions,
+    ) -> Result<SubscriptionUpdate, Error> {
+        if !self.opts.flags.has_read() {
+            return Err(Error::ReadDisabled);
+        }
+
+        if filters.is_empty() {
+            return Err(Error::FiltersEmpty);
+        }
+
+        // Get the current filters (the original ones, if the REQ was split)
+        let mapping: ReqMapping = match self.req_mapping(&id).await {
+            Some(mapping) => mapping,
+            None => match self.subscription(&id).await {
+                Some(current) => ReqMapping::single(id.clone(), current),
+                None => {
+                    // Not subscribed yet
+                    self.subscribe_with_id(
+                        id,
+                        filters,
+                        SubscribeOptions::default().send_opts(opts),
+                    )
+                    .await?;
+                    return Ok(SubscriptionUpdate::Amend);
+                }
+            },
+        };
+        let current: Vec<Filter> = mapping.filters.iter().map(|(f, ..)| f.clone()).collect();
+
+        if current == filters {
+            return Ok(SubscriptionUpdate::Unchanged);
+        }
+
+        match diff::diff_filters(&current, &filters) {
+            Some(deltas) => {
+                let increments: Vec<Filter> =
+                    deltas.iter().filter_map(|d| d.increment.clone()).collect();
+                if increments.is_empty() {
+                    // Only removed duplicated filters
+                    return Ok(SubscriptionUpdate::Unchanged);
+                }
+
+                // Open an additional subscription for the increments
+                let increment_id = SubscriptionId::new(format!("{id}:{}", mapping.requests.len()));
+                self.send_msg(
+                    ClientMessage::req(increment_id.clone(), increments.clone()),
+                    opts,
+                )
+                .await?;
+                self.send_subscription_notification(
+                    increment_id.clone(),
+                    SubscriptionLifecycle::Created,
+                )
+                .await;
+                self.update_subscription(increment_id.clone(), increments.clone())
+                    .await;
+
+                // Update mapping
+                let mut requests = mapping.requests;
+                requests.push((increment_id.clone(), increments.clone()));
+                let filters: Vec<(Filter, Vec<SubscriptionId>)> = filters
+                    .into_iter()
+                    .zip(deltas.into_iter())
+                    .map(|(filter, delta)| {
+                        let mut ids: Vec<SubscriptionId> = match delta.base {
+                            Some(index) => mapping.filters[index].1.clone(),
+                            None => Vec::new(),
+                        };
+                        if delta.increment.is_some() {
+                            ids.push(increment_id.clone());
+                        }
+                        (filter, ids)
+                    })
+                    .collect();
+                let mut req_mappings = self.req_mappings.write().await;
+                req_mappings.insert(id, ReqMapping { requests, filters });
+
+                Ok(SubscriptionUpdate::Incremental(increments))
+            }
+            None => {
+                // Close the additional subscriptions
+                let additional: Vec<SubscriptionId> =
+                    mapping.ids().filter(|i| *i != &id).cloned().collect();
+                self.req_mappings.write().await.remove(&id);
+                for i in additional.iter() {
+                    self.remove_subscription(i).await;
+                }
+                if !additional.is_empty() {
+                    let msgs: Vec<ClientMessage> = additional
+                        .iter()
+                        .cloned()
+                        .map(ClientMessage::close)
+                        .collect();
+                    self.batch_msg(msgs, opts).await?;
+                    for i in additional.into_iter() {
+                        self.send_subscription_notification(
+                            i,
+                            SubscriptionLifecycle::Closed {
+                                reason: SubscriptionCloseReason::Unsubscribed,
+                            },
+                        )
+                        .await;
+                    }
+                }
+
+                // Amend with a new REQ with the same ID (the cursor isn't valid anymore)
+                self.remove_subscription(&id).await;
+                self.subscribe_with_id(id, filters, SubscribeOptions::default().send_opts(opts))
+                    .await?;
+
+                Ok(SubscriptionUpdate::Amend)
+            }
+        }
+    }
+
+    fn spawn_auto_closing_handler(
+        &self,
+        id: SubscriptionId,
+        opts: SubscribeAutoCloseOptions,
+    ) -> Result<(), Error> {
+        let this = self.clone();
+        thread::spawn(async move {
+            let sub_id = id.clone();
+            let relay = this.clone();
+            let res = time::timeout(opts.timeout, async move {
+                let mut counter = 0;
+                let mut total: u16 = 0;
+                let mut received_eose: bool = false;
+
+                let mut notifications = relay.internal_notification_sender.subscribe();
+                while let Ok(notification) = notifications.recv().await {
+                    match notification {
+                        RelayNotification::Message { message, .. } => match message {
+                            RelayMessage::Event {
+                                subscription_id, ..
+                            } => {
+                                if subscription_id.eq(&id) {
+                                    total = total.saturating_add(1);
+                                    if opts.max_events.map_or(false, |max| total >= max) {
+                                        break;
+                                    }
+
+                                    if let Some(FilterOptions::WaitForEventsAfterEOSE(num)) =
+                                        opts.filter
+                                    {
+                                        if received_eose {
+                                            counter += 1;
+                                            if counter >= num {
+                                                break;
                                             }
                                         }
                                     }
                                 }
-                                RelayMessage::EndOfStoredEvents(subscription_id) => {
-                                    if subscription_id.eq(&id) {
-                                        tracing::debug!(
-                                            "Received EOSE for subscription {id} from {}",
-                                            relay.url
-                                        );
-                                        received_eose = true;
-                                        if let FilterOptions::ExitOnEOSE
-                                        | FilterOptions::WaitDurationAfterEOSE(_) = opts.filter
-                                        {
-                                            break;
-                                        }
+                            }
+                            RelayMessage::EndOfStoredEvents(subscription_id) => {
+                                if subscription_id.eq(&id) {
+                                    tracing::debug!(
+                                        "Received EOSE for subscription {id} from {}",
+                                        relay.url
+                                    );
+                                    received_eose = true;
+                                    if let Some(
+                                        FilterOptions::ExitOnEOSE
+                                        | FilterOptions::WaitDurationAfterEOSE(_),
+                                    ) = opts.filter
+                                    {
+                                        break;
                                     }
                                 }
-                                _ => (),
-                            },
-                            RelayNotification::RelayStatus { status } => {
-                                if status.is_disconnected() {
-                                    return false; // No need to send CLOSE msg
+                            }
+                            RelayMessage::Closed {
+                                subscription_id, ..
+                            } => {
+                                if subscription_id.eq(&id) {
+                                    return false; // Already closed by relay
                                 }
                             }
-                            RelayNotification::Stop | RelayNotification::Shutdown => {
+                            _ => (),
+                        },
+                        RelayNotification::RelayStatus { status } => {
+                            if status.is_disconnected() {
                                 return false; // No need to send CLOSE msg
                             }
-                            _ => (),
                         }
+                        RelayNotification::Stop | RelayNotification::Shutdown => {
+                            return false; // No need to send CLOSE msg
+                        }
+                        _ => (),
                     }
+                }
 
-                    if let FilterOptions::WaitDurationAfterEOSE(duration) = opts.filter {
-                        time::timeout(Some(duration), async {
-                            while let Ok(notification) = notifications.recv().await {
-                                match notification {
-                                    RelayNotification::RelayStatus { status } => {
-                                        if status.is_disconnected() {
-                                            return Ok(()); // No need to send CLOSE msg
+                // Skip the wait if the max number of events has been already reached
+                let reached_max_events: bool = opts.max_events.map_or(false, |max| total >= max);
+                let wait_after_eose: Option<Duration> = match opts.filter {
+                    Some(FilterOptions::WaitDurationAfterEOSE(duration)) if !reached_max_events => {
+                        Some(duration)
+                    }
+                    _ => None,
+                };
+                if let Some(duration) = wait_after_eose {
+                    time::timeout(Some(duration), async {
+                        while let Ok(notification) = notifications.recv().await {
+                            match notification {
+                                RelayNotification::Message {
+                                    message:
+                                        RelayMessage::Event {
+                                            subscription_id, ..
+                                        },
+                                    ..
+                                } => {
+                                    if subscription_id.eq(&id) {
+                                        total = total.saturating_add(1);
+                                        if opts.max_events.map_or(false, |max| total >= max) {
+                                            break;
                                         }
                                     }
-                                    RelayNotification::Stop | RelayNotification::Shutdown => {
+                                }
+                                RelayNotification::RelayStatus { status } => {
+                                    if status.is_disconnected() {
                                         return Ok(()); // No need to send CLOSE msg
                                     }
-                                    _ => (),
                                 }
+                                RelayNotification::Stop | RelayNotification::Shutdown => {
+                                    return Ok(()); // No need to send CLOSE msg
+                                }
+                                _ => (),
                             }
+                        }
 
-                            Ok::<(), Error>(())
-                        })
-                        .await;
-                    }
+                        Ok::<(), Error>(())
+                    })
+                    .await;
+                }
 
-                    true // Need to send CLOSE msg
-                })
-                .await;
+                true // Need to send CLOSE msg
+            })
+            .await;
 
-                // Check if CLOSE needed
-                let to_close: bool = res.unwrap_or_else(|| {
+            // Check if CLOSE needed
+            let (to_close, reason): (bool, SubscriptionCloseReason) = match res {
+                Some(to_close) => (to_close, SubscriptionCloseReason::AutoClosed),
+                None => {
                     tracing::warn!("Timeout reached for REQ {sub_id}, auto-closing.");
-                    true
-                });
+                    (true, SubscriptionCloseReason::Timeout)
+                }
+            };
 
-                if to_close {
-                    // Unsubscribe
-                    this.send_msg(
-                        ClientMessage::close(sub_id.clone()),
-                        RelaySendOptions::default(),
-                    )
-                    .await?;
+            if to_close {
+                // Unsubscribe
+                this.send_msg(
+                    ClientMessage::close(sub_id.clone()),
+                    RelaySendOptions::default(),
+                )
+                .await?;
 
-                    tracing::debug!("Subscription {sub_id} auto-closed");
-                }
+                tracing::debug!("Subscription {sub_id} auto-closed");
 
-                Ok::<(), Error>(())
-            })?;
-        } else {
-            // No auto-close subscription: update subscription filters
-            self.update_subscription(id.clone(), filters).await;
-        }
+                this.send_subscription_notification(
+                    sub_id,
+                    SubscriptionLifecycle::Closed { reason },
+                )
+                .await;
+            }
 
+            Ok::<(), Error>(())
+        })?;
         Ok(())
     }
 
@@ -1199,12 +2328,35 @@ impl InternalRelay {
             return Err(Error::ReadDisabled);
         }
 
-        // Remove subscription
-        self.remove_subscription(&id).await;
+        // Get subscriptions emitted for this ID (more than one if the REQ was split)
+        let ids: Vec<SubscriptionId> = {
+            let mut req_mappings = self.req_mappings.write().await;
+            match req_mappings.remove(&id) {
+                Some(mapping) => mapping.requests.into_iter().map(|(id, ..)| id).collect(),
+                None => vec![id],
+            }
+        };
+
+        // Remove subscriptions
+        for id in ids.iter() {
+            self.remove_subscription(id).await;
+        }
+
+        // Send CLOSE messages
+        let msgs: Vec<ClientMessage> = ids.iter().cloned().map(ClientMessage::close).collect();
+        self.batch_msg(msgs, opts).await?;
+
+        for id in ids.into_iter() {
+            self.send_subscription_notification(
+                id,
+                SubscriptionLifecycle::Closed {
+                    reason: SubscriptionCloseReason::Unsubscribed,
+                },
+            )
+            .await;
+        }
 
-        // Send CLOSE message
-        let msg: ClientMessage = ClientMessage::close(id);
-        self.send_msg(msg, opts).await
+        Ok(())
     }
 
     pub async fn unsubscribe_all(&self, opts: RelaySendOptions) -> Result<(), Error> {
@@ -1214,13 +2366,26 @@ impl InternalRelay {
 
         let subscriptions = self.subscriptions().await;
 
+        // Clear REQ mappings
+        let mut req_mappings = self.req_mappings.write().await;
+        req_mappings.clear();
+        drop(req_mappings);
+
         for id in subscriptions.into_keys() {
             // Remove subscription
             self.remove_subscription(&id).await;
 
             // Send CLOSE message
-            let msg: ClientMessage = ClientMessage::close(id);
+            let msg: ClientMessage = ClientMessage::close(id.clone());
             self.send_msg(msg, opts).await?;
+
+            self.send_subscription_notification(
+                id,
+                SubscriptionLifecycle::Closed {
+                    reason: SubscriptionCloseReason::Unsubscribed,
+                },
+            )
+            .await;
         }
 
         Ok(())
@@ -1255,7 +2420,14 @@ impl InternalRelay {
         let mut notifications = self.internal_notification_sender.subscribe();
 
         // Subscribe with auto-close
-        let id: SubscriptionId = self.subscribe(filters, subscribe_opts).await?;
+        let id: SubscriptionId = SubscriptionId::generate();
+        let mapping: ReqMapping = self
+            .subscribe_with_id(id.clone(), filters, subscribe_opts)
+            .await?;
+
+        // The REQ may have been split: wait for EOSE of every emitted subscription
+        let ids: HashSet<SubscriptionId> = mapping.ids().cloned().collect();
+        let mut missing_eose: HashSet<SubscriptionId> = ids.clone();
 
         let mut counter: u16 = 0;
         let mut received_eose: bool = false;
@@ -1268,7 +2440,7 @@ impl InternalRelay {
                             subscription_id,
                             event,
                         } => {
-                            if subscription_id.eq(&id) {
+                            if ids.contains(&subscription_id) {
                                 callback(*event).await;
                                 if let FilterOptions::WaitForEventsAfterEOSE(num) = opts {
                                     if received_eose {
@@ -1280,8 +2452,19 @@ impl InternalRelay {
                                 }
                             }
                         }
-                        RelayMessage::EndOfStoredEvents(subscription_id) => {
-                            if subscription_id.eq(&id) {
+                        // A subscription closed by the relay will never send EOSE
+                        RelayMessage::EndOfStoredEvents(subscription_id)
+                        | RelayMessage::Closed {
+                            subscription_id, ..
+                        } => {
+                            if missing_eose.remove(&subscription_id) {
+                                tracing::debug!(
+                                    "Received EOSE for subscription {subscription_id} from {}",
+                                    self.url
+                                );
+                            }
+
+                            if missing_eose.is_empty() && !received_eose {
                                 tracing::debug!(
                                     "Received EOSE for subscription {id} from {}",
                                     self.url
@@ -1324,7 +2507,7 @@ impl InternalRelay {
                                 },
                             ..
                         } => {
-                            if subscription_id.eq(&id) {
+                            if ids.contains(&subscription_id) {
                                 callback(*event).await;
                             }
                         }
@@ -1357,6 +2540,11 @@ impl InternalRelay {
             .query(filters.clone(), Order::Desc)
             .await
             .unwrap_or_default();
+        let verifier: Option<EventVerifier> = self.verifier.read().await.clone();
+        let stored_events: Vec<Event> = match verifier {
+            Some(verifier) => verifier.verify_stored(stored_events).await,
+            None => stored_events,
+        };
         let events: Mutex<BTreeSet<Event>> = Mutex::new(stored_events.into_iter().collect());
         self.get_events_of_with_callback(filters, timeout, opts, |event| async {
             let mut events = events.lock().await;
@@ -1366,43 +2554,59 @@ impl InternalRelay {
         Ok(events.into_inner().into_iter().rev().collect())
     }
 
-    pub async fn count_events_of(
+    pub async fn count_events(
         &self,
         filters: Vec<Filter>,
         timeout: Duration,
     ) -> Result<usize, Error> {
+        // Check if the relay declared to not support NIP45
+        #[cfg(feature = "nip11")]
+        {
+            let document = self.document.read().await;
+            if let Some(nips) = &document.supported_nips {
+                if !nips.contains(&45) {
+                    return Err(Error::CountNotSupported);
+                }
+            }
+        }
+
         let id = SubscriptionId::generate();
         let send_opts = RelaySendOptions::default().skip_send_confirmation(true);
+        let mut notifications = self.internal_notification_sender.subscribe();
         self.send_msg(ClientMessage::count(id.clone(), filters), send_opts)
             .await?;
 
-        let mut count = 0;
-
-        let mut notifications = self.internal_notification_sender.subscribe();
-        time::timeout(Some(timeout), async {
+        let res: Option<Result<usize, Error>> = time::timeout(Some(timeout), async {
             while let Ok(notification) = notifications.recv().await {
-                if let RelayNotification::Message {
-                    message:
+                if let RelayNotification::Message { message } = notification {
+                    match message {
                         RelayMessage::Count {
                             subscription_id,
-                            count: c,
-                        },
-                } = notification
-                {
-                    if subscription_id == id {
-                        count = c;
-                        break;
+                            count,
+                        } if subscription_id == id => return Ok(count),
+                        RelayMessage::Closed {
+                            subscription_id,
+                            message,
+                        } if subscription_id == id => {
+                            return match MachineReadablePrefix::parse(&message) {
+                                Some(MachineReadablePrefix::Unsupported) => {
+                                    Err(Error::CountNotSupported)
+                                }
+                                _ => Err(Error::CountClosed(message)),
+                            };
+                        }
+                        _ => (),
                     }
                 }
             }
+            Err(Error::RecvTimeout)
         })
-        .await
-        .ok_or(Error::Timeout)?;
+        .await;
 
-        // Unsubscribe
+        // Unsubscribe, also if timed out or closed by the relay
         self.send_msg(ClientMessage::close(id), send_opts).await?;
 
-        Ok(count)
+        res.ok_or(Error::Timeout)?
     }
 
     pub async fn reconcile(&self, filter: Filter, opts: NegentropyOptions) -> Result<(), Error> {
@@ -1682,3 +2886,59 @@ impl InternalRelay {
         }
     }
 }
+
+/// Move forward the `since` of filters without `until` to resume a subscription from `cursor`
+fn resume_filters(filters: Vec<Filter>, cursor: Timestamp) -> Vec<Filter> {
+    filters
+        .into_iter()
+        .map(|filter| {
+            if filter.until.is_none() && filter.since.map_or(true, |since| since < cursor) {
+                filter.since(cursor)
+            } else {
+                filter
+            }
+        })
+        .collect()
+}
+
+/// Check if the `OK` message is a transient rejection, worth to retry
+fn is_transient_rejection(message: &str) -> bool {
+    message.starts_with("rate-limited:") || message == Error::NotConnectedStatusChanged.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::Kind;
+
+    use super::*;
+
+    #[test]
+    fn test_resume_filters() {
+        let cursor = Timestamp::from(1_000);
+        let filters = vec![
+            Filter::new().kind(Kind::TextNote),
+            Filter::new()
+                .kind(Kind::TextNote)
+                .since(Timestamp::from(2_000)),
+            Filter::new()
+                .kind(Kind::TextNote)
+                .until(Timestamp::from(500)),
+        ];
+        let resumed = resume_filters(filters, cursor);
+        assert_eq!(resumed[0].since, Some(cursor));
+        assert_eq!(resumed[1].since, Some(Timestamp::from(2_000)));
+        assert_eq!(resumed[2].since, None);
+    }
+
+    #[test]
+    fn test_transient_rejection() {
+        assert!(is_transient_rejection("rate-limited: slow down"));
+        assert!(is_transient_rejection(
+            &Error::NotConnectedStatusChanged.to_string()
+        ));
+        assert!(!is_transient_rejection("blocked: not allowed"));
+        assert!(!is_transient_rejection(
+            "duplicate: already have this event"
+        ));
+    }
+}
@@ -29,12 +29,18 @@ impl RelayLimits {
 pub struct RelayMessageLimits {
     /// Maximum size of normalised JSON, in bytes (default: 5_250_000)
     pub max_size: Option<u32>,
+    /// Maximum size of outbound `REQ` messages, in bytes (default: None)
+    ///
+    /// Oversized `REQ`s are shrunk and split into more subscriptions.
+    /// If the relay publish a `max_message_length` in its NIP11 document, the lowest value is used.
+    pub max_req_size: Option<u32>,
 }
 
 impl Default for RelayMessageLimits {
     fn default() -> Self {
         Self {
             max_size: Some(5_250_000),
+            max_req_size: None,
         }
     }
 }
@@ -43,7 +49,10 @@ impl RelayMessageLimits {
     /// Disable all limits
     #[inline]
     pub fn disable() -> Self {
-        Self { max_size: None }
+        Self {
+            max_size: None,
+            max_req_size: None,
+        }
     }
 }
 
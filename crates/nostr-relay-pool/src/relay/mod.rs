@@ -3,6 +3,15 @@
 // Distributed under the MIT software license
 
 //! Relay
+//!
+//! The connection tasks of each relay run in a `relay` span with the `url` field,
+//! so logs can be enabled for a single relay with an `EnvFilter` directive:
+//!
+//! ```text
+//! RUST_LOG="info,nostr_relay_pool::relay[relay{url=wss://relay.damus.io/}]=debug"
+//! ```
+//!
+//! The raw messages are logged only if enabled at runtime (check [`Relay::set_message_logging`]).
 
 use std::cmp;
 use std::collections::HashMap;
@@ -21,24 +30,42 @@ use nostr::{
 use nostr_database::{DynNostrDatabase, MemoryDatabase};
 use tokio::sync::broadcast;
 
+pub mod cost;
+mod diff;
 mod error;
 pub mod flags;
 mod internal;
 pub mod limits;
 pub mod options;
+pub mod rate;
+mod shrink;
 pub mod stats;
 mod status;
+mod subscription;
+pub mod supervisor;
+pub mod transport;
 
+pub use self::cost::SubscriptionCost;
+pub use self::diff::SubscriptionUpdate;
 pub use self::error::Error;
 pub use self::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
 use self::internal::InternalRelay;
+pub use self::internal::MESSAGE_LOG_TARGET;
 pub use self::limits::RelayLimits;
 pub use self::options::{
-    FilterOptions, NegentropyDirection, NegentropyOptions, RelayOptions, RelaySendOptions,
-    SubscribeAutoCloseOptions, SubscribeOptions,
+    ConnectionMode, FilterOptions, NegentropyDirection, NegentropyOptions, RelayOptions,
+    RelaySendOptions, SubscribeAutoCloseOptions, SubscribeOptions,
 };
-pub use self::stats::RelayConnectionStats;
+pub use self::rate::{FloodAction, FloodProtection, RateLimit};
+pub use self::shrink::{estimate_req_size, shrink_req, ReqMapping};
+pub use self::stats::{RelayConnectionStats, RelayStatsSnapshot};
 pub use self::status::RelayStatus;
+pub use self::subscription::{SubscriptionCloseReason, SubscriptionLifecycle};
+pub use self::supervisor::RestartPolicy;
+use crate::pool::dedup::EventDedup;
+use crate::pool::filtering::FilteringPolicy;
+use crate::pool::kinds::NotificationKinds;
+use crate::pool::verifier::EventVerifier;
 use crate::pool::RelayPoolNotification;
 
 /// Relay Notification
@@ -61,6 +88,13 @@ pub enum RelayNotification {
         /// Relay Status
         status: RelayStatus,
     },
+    /// Subscription lifecycle milestone
+    Subscription {
+        /// Subscription ID
+        subscription_id: SubscriptionId,
+        /// Lifecycle
+        lifecycle: SubscriptionLifecycle,
+    },
     /// Stop
     Stop,
     /// Shutdown
@@ -156,12 +190,40 @@ impl Relay {
         self.inner.subscriptions().await
     }
 
+    /// Get the observed cost of a subscription (events and bytes received since the `REQ`)
+    #[inline]
+    pub async fn subscription_cost(&self, id: &SubscriptionId) -> Option<SubscriptionCost> {
+        self.inner.subscription_cost(id).await
+    }
+
+    /// Get the observed cost of all the active subscriptions
+    #[inline]
+    pub async fn subscription_costs(&self) -> HashMap<SubscriptionId, SubscriptionCost> {
+        self.inner.subscription_costs().await
+    }
+
+    /// Get max number of active subscriptions
+    ///
+    /// Lowest value between [`RelayOptions::max_subscriptions`] and the NIP11 `max_subscriptions`, if any.
+    #[inline]
+    pub async fn max_subscriptions(&self) -> Option<usize> {
+        self.inner.max_subscriptions().await
+    }
+
     /// Get filters by [SubscriptionId]
     #[inline]
     pub async fn subscription(&self, id: &SubscriptionId) -> Option<Vec<Filter>> {
         self.inner.subscription(id).await
     }
 
+    /// Get the `created_at` of the most recent event received for each subscription
+    ///
+    /// Used to resume subscriptions (see [`RelayOptions::adaptive_since`]).
+    #[inline]
+    pub async fn subscription_cursors(&self) -> HashMap<SubscriptionId, Timestamp> {
+        self.inner.cursors().await
+    }
+
     /// Get [`RelayOptions`]
     #[inline]
     pub fn opts(&self) -> RelayOptions {
@@ -197,12 +259,62 @@ impl Relay {
             .await
     }
 
+    /// Set event verifier
+    ///
+    /// If `None`, received events are fully verified inline.
+    #[inline]
+    pub async fn set_event_verifier(&self, verifier: Option<EventVerifier>) {
+        self.inner.set_event_verifier(verifier).await
+    }
+
+    /// Set the deduplication layer shared with the other relays of the pool
+    #[inline]
+    pub(crate) async fn set_event_dedup(&self, dedup: Option<EventDedup>) {
+        self.inner.set_event_dedup(dedup).await
+    }
+
+    /// Set the filtering policy (blacklist of public keys and IDs, allowlist of relays)
+    #[inline]
+    pub async fn set_filtering_policy(&self, filtering: Option<FilteringPolicy>) {
+        self.inner.set_filtering_policy(filtering).await
+    }
+
+    /// Set the kinds of the events delivered to the external notification channel
+    #[inline]
+    pub async fn set_notification_kinds(&self, kinds: NotificationKinds) {
+        self.inner.set_notification_kinds(kinds).await
+    }
+
+    /// Check if the raw messages exchanged with the relay are logged
+    #[inline]
+    pub fn message_logging(&self) -> bool {
+        self.inner.message_logging()
+    }
+
+    /// Enable or disable the logging of the raw messages exchanged with the relay (default: disabled)
+    ///
+    /// Messages are logged at `INFO` level with the [`MESSAGE_LOG_TARGET`] target.
+    #[inline]
+    pub fn set_message_logging(&self, enable: bool) {
+        self.inner.set_message_logging(enable)
+    }
+
     /// Connect to relay and keep alive connection
+    ///
+    /// With [`ConnectionMode::Lazy`], the connection is postponed to the first use.
     #[inline]
     pub async fn connect(&self, connection_timeout: Option<Duration>) {
         self.inner.connect(connection_timeout).await
     }
 
+    /// Try to reconnect immediately, skipping the remaining retry interval
+    ///
+    /// No effect if the relay is connected or the auto connect loop isn't running.
+    #[inline]
+    pub async fn retry_now(&self) {
+        self.inner.retry_now().await
+    }
+
     /// Disconnect from relay and set status to 'Stopped'
     #[inline]
     pub async fn stop(&self) -> Result<(), Error> {
@@ -272,6 +384,11 @@ impl Relay {
     /// It's possible to automatically close a subscription by configuring the [SubscribeOptions].
     ///
     /// Note: auto-closing subscriptions aren't saved in subscriptions map!
+    ///
+    /// ### Oversized REQ
+    ///
+    /// If the `REQ` exceeds the max message size of the relay (see [`RelayLimits`] and NIP11 document),
+    /// the filters are shrunk and sent in more subscriptions. Use [`Relay::subscribe_with_mapping`] to get the [`ReqMapping`].
     #[inline]
     pub async fn subscribe_with_id(
         &self,
@@ -279,9 +396,50 @@ impl Relay {
         filters: Vec<Filter>,
         opts: SubscribeOptions,
     ) -> Result<(), Error> {
+        self.inner.subscribe_with_id(id, filters, opts).await?;
+        Ok(())
+    }
+
+    /// Subscribe with custom [SubscriptionId] and get the emitted `REQ`s
+    ///
+    /// Same as [`Relay::subscribe_with_id`] but return the [`ReqMapping`] of the subscription.
+    #[inline]
+    pub async fn subscribe_with_mapping(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+        opts: SubscribeOptions,
+    ) -> Result<ReqMapping, Error> {
         self.inner.subscribe_with_id(id, filters, opts).await
     }
 
+    /// Update the filters of a subscription, minimizing the events resent by the relay
+    ///
+    /// If the new filters only extend the current ones (i.e. adding an author to a filter or adding a filter),
+    /// an additional subscription is opened with the incremental filters only (see [`ReqMapping`]).
+    /// Otherwise, the subscription is amended with a new `REQ` with the same [`SubscriptionId`].
+    ///
+    /// If not subscribed yet, a new subscription is created.
+    #[inline]
+    pub async fn update_subscription_filters(
+        &self,
+        id: SubscriptionId,
+        filters: Vec<Filter>,
+        opts: RelaySendOptions,
+    ) -> Result<SubscriptionUpdate, Error> {
+        self.inner
+            .update_subscription_filters(id, filters, opts)
+            .await
+    }
+
+    /// Get the [`ReqMapping`] of a subscription split into more `REQ`s
+    ///
+    /// Return `None` if the subscription wasn't split (oversized `REQ` or incremental update).
+    #[inline]
+    pub async fn req_mapping(&self, id: &SubscriptionId) -> Option<ReqMapping> {
+        self.inner.req_mapping(id).await
+    }
+
     /// Unsubscribe
     #[inline]
     pub async fn unsubscribe(
@@ -328,14 +486,19 @@ impl Relay {
         self.inner.get_events_of(filters, timeout, opts).await
     }
 
-    /// Count events of filters
+    /// Count events of filters (NIP45)
+    ///
+    /// Return [`Error::CountNotSupported`] if the relay declared to not support NIP45 or closed the `COUNT` as `unsupported`,
+    /// [`Error::CountClosed`] if closed for another reason (i.e. `auth-required` or `rate-limited`).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/45.md>
     #[inline]
-    pub async fn count_events_of(
+    pub async fn count_events(
         &self,
         filters: Vec<Filter>,
         timeout: Duration,
     ) -> Result<usize, Error> {
-        self.inner.count_events_of(filters, timeout).await
+        self.inner.count_events(filters, timeout).await
     }
 
     /// Negentropy reconciliation
@@ -11,10 +11,17 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use super::flags::{AtomicRelayServiceFlags, RelayServiceFlags};
+use super::rate::{FloodProtection, RateLimit};
+use super::supervisor::RestartPolicy;
 use crate::RelayLimits;
 
 /// Default send timeout
 pub const DEFAULT_SEND_TIMEOUT: Duration = Duration::from_secs(20);
+pub(super) const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+pub(super) const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+pub(super) const DEFAULT_PING_INTERVAL: Duration = Duration::from_secs(55);
+pub(super) const DEFAULT_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+pub(super) const DEFAULT_MAILBOX_CAPACITY: usize = 1024;
 pub(super) const DEFAULT_RETRY_SEC: u64 = 10;
 pub(super) const MIN_RETRY_SEC: u64 = 5;
 pub(super) const MAX_ADJ_RETRY_SEC: u64 = 60;
@@ -22,6 +29,22 @@ pub(super) const NEGENTROPY_HIGH_WATER_UP: usize = 100;
 pub(super) const NEGENTROPY_LOW_WATER_UP: usize = 50;
 pub(super) const NEGENTROPY_BATCH_SIZE_DOWN: usize = 50;
 
+/// Relay connection mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectionMode {
+    /// Connect when the pool connects (default)
+    #[default]
+    Eager,
+    /// Stay disconnected until the first message (subscription, publish, ...) targets the relay
+    ///
+    /// The relay is disconnected after `idle_timeout` without sent messages and active subscriptions,
+    /// and connected again on the next use.
+    Lazy {
+        /// Idle time before disconnecting
+        idle_timeout: Duration,
+    },
+}
+
 /// [`Relay`](super::Relay) options
 #[derive(Debug, Clone)]
 pub struct RelayOptions {
@@ -32,7 +55,20 @@ pub struct RelayOptions {
     reconnect: Arc<AtomicBool>,
     retry_sec: Arc<AtomicU64>,
     adjust_retry_sec: Arc<AtomicBool>,
+    adaptive_since: Arc<AtomicBool>,
+    resubscribe: Arc<AtomicBool>,
     pub(super) limits: RelayLimits,
+    send_defaults: Option<RelaySendOptions>,
+    pub(super) restart_policy: RestartPolicy,
+    pub(super) mailbox_capacity: usize,
+    pub(super) rate_limit: Option<RateLimit>,
+    pub(super) flood_protection: Option<FloodProtection>,
+    pub(super) max_subscriptions: Option<usize>,
+    pub(super) refuse_expensive_filters: bool,
+    pub(super) ping_interval: Duration,
+    pub(super) pong_timeout: Duration,
+    pub(super) connection_mode: ConnectionMode,
+    pub(super) resubscribe_after_auth: bool,
 }
 
 impl Default for RelayOptions {
@@ -45,7 +81,20 @@ impl Default for RelayOptions {
             reconnect: Arc::new(AtomicBool::new(true)),
             retry_sec: Arc::new(AtomicU64::new(DEFAULT_RETRY_SEC)),
             adjust_retry_sec: Arc::new(AtomicBool::new(true)),
+            adaptive_since: Arc::new(AtomicBool::new(false)),
+            resubscribe: Arc::new(AtomicBool::new(true)),
             limits: RelayLimits::default(),
+            send_defaults: None,
+            restart_policy: RestartPolicy::default(),
+            mailbox_capacity: DEFAULT_MAILBOX_CAPACITY,
+            rate_limit: None,
+            flood_protection: None,
+            max_subscriptions: None,
+            refuse_expensive_filters: false,
+            ping_interval: DEFAULT_PING_INTERVAL,
+            pong_timeout: DEFAULT_PONG_TIMEOUT,
+            connection_mode: ConnectionMode::Eager,
+            resubscribe_after_auth: false,
         }
     }
 }
@@ -177,19 +226,229 @@ impl RelayOptions {
             .store(adjust_retry_sec, Ordering::SeqCst);
     }
 
+    /// Resume subscriptions from the last received event on reconnection (default: false)
+    ///
+    /// When enabled, the `since` of the filters re-sent after a reconnection is moved forward
+    /// to the `created_at` of the most recent event received for that subscription.
+    pub fn adaptive_since(self, adaptive_since: bool) -> Self {
+        Self {
+            adaptive_since: Arc::new(AtomicBool::new(adaptive_since)),
+            ..self
+        }
+    }
+
+    pub(crate) fn get_adaptive_since(&self) -> bool {
+        self.adaptive_since.load(Ordering::SeqCst)
+    }
+
+    /// Set adaptive_since option
+    pub fn update_adaptive_since(&self, adaptive_since: bool) {
+        self.adaptive_since.store(adaptive_since, Ordering::SeqCst);
+    }
+
+    /// Automatically replay the active subscriptions on reconnection (default: true)
+    ///
+    /// Combine with [`RelayOptions::adaptive_since`] to not receive again the events
+    /// received before the disconnection.
+    pub fn resubscribe(self, resubscribe: bool) -> Self {
+        Self {
+            resubscribe: Arc::new(AtomicBool::new(resubscribe)),
+            ..self
+        }
+    }
+
+    pub(crate) fn get_resubscribe(&self) -> bool {
+        self.resubscribe.load(Ordering::SeqCst)
+    }
+
+    /// Set resubscribe option
+    pub fn update_resubscribe(&self, resubscribe: bool) {
+        self.resubscribe.store(resubscribe, Ordering::SeqCst);
+    }
+
     /// Set custom limits
     pub fn limits(mut self, limits: RelayLimits) -> Self {
         self.limits = limits;
         self
     }
+
+    /// Set relay-specific [`RelaySendOptions`]
+    ///
+    /// When set, replace the send options passed to the pool-level send methods for this relay
+    /// (i.e. a longer timeout for slow archive relays or skip the `OK` wait for throwaway relays).
+    pub fn send_defaults(mut self, opts: RelaySendOptions) -> Self {
+        self.send_defaults = Some(opts);
+        self
+    }
+
+    /// Set the restart policy of the relay actors (default: max 5 restarts within 60 secs)
+    pub fn restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Max number of messages queued for sending (default: 1024)
+    ///
+    /// When the queue is full, new messages are rejected with [`Error::MessageNotSent`](super::Error::MessageNotSent).
+    pub fn mailbox_capacity(mut self, capacity: usize) -> Self {
+        self.mailbox_capacity = capacity.max(1);
+        self
+    }
+
+    /// Limit the rate of the outgoing messages (default: None)
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Protect from relays flooding events (default: None)
+    ///
+    /// Check [`FloodAction`](super::rate::FloodAction) to learn more.
+    pub fn flood_protection(mut self, protection: FloodProtection) -> Self {
+        self.flood_protection = Some(protection);
+        self
+    }
+
+    /// Max number of active subscriptions (default: None)
+    ///
+    /// If the relay publish a `max_subscriptions` in its NIP11 document, the lowest value is used.
+    /// When the relay is full, the pool gives the slots to the subscriptions with the highest priority
+    /// (see [`SubscribeOptions::priority`]).
+    pub fn max_subscriptions(mut self, max: usize) -> Self {
+        self.max_subscriptions = Some(max);
+        self
+    }
+
+    /// Refuse pathologically broad filters (default: false)
+    ///
+    /// By default, subscribing to a filter without `ids`, `authors`, `kinds`, tags and `since`
+    /// only logs a warning. If enabled, it fails with [`Error::ExpensiveFilter`](super::Error::ExpensiveFilter),
+    /// unless [`SubscribeOptions::allow_expensive`] is set.
+    pub fn refuse_expensive_filters(mut self, refuse: bool) -> Self {
+        self.refuse_expensive_filters = refuse;
+        self
+    }
+
+    /// Interval between pings (default: 55 secs)
+    ///
+    /// Pings are sent only if the `PING` flag is enabled.
+    pub fn ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Max time to wait for a pong (default: 10 secs)
+    ///
+    /// If the pong isn't received in time, the connection is considered dead:
+    /// the relay is marked as disconnected and, if enabled, reconnected.
+    /// Can't be greater than the [`RelayOptions::ping_interval`].
+    pub fn pong_timeout(mut self, timeout: Duration) -> Self {
+        self.pong_timeout = timeout;
+        self
+    }
+
+    /// Connection mode (default: eager)
+    ///
+    /// Use [`ConnectionMode::Lazy`] for relays used only occasionally (i.e. hint relays of the gossip model),
+    /// to not keep hundreds of sockets open.
+    pub fn connection_mode(mut self, mode: ConnectionMode) -> Self {
+        self.connection_mode = mode;
+        self
+    }
+
+    /// Send again the subscriptions closed with `auth-required` after a successful NIP42 authentication (default: false)
+    ///
+    /// The authentication is considered successful when the relay accepts the `AUTH` event with an `OK`.
+    pub fn resubscribe_after_auth(mut self, enable: bool) -> Self {
+        self.resubscribe_after_auth = enable;
+        self
+    }
+
+    /// Get the send options to use for this relay, falling back to `opts` if no relay-specific ones are set
+    pub(crate) fn get_send_opts(&self, opts: RelaySendOptions) -> RelaySendOptions {
+        self.send_defaults.unwrap_or(opts)
+    }
+
+    /// Check if the options that can't be changed on a live relay (proxy, limits, send defaults, actors supervision and rate limits) are the same
+    pub(crate) fn has_same_connection_opts(&self, other: &Self) -> bool {
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.proxy != other.proxy {
+            return false;
+        }
+
+        self.limits == other.limits
+            && self.send_defaults == other.send_defaults
+            && self.restart_policy == other.restart_policy
+            && self.mailbox_capacity == other.mailbox_capacity
+            && self.rate_limit == other.rate_limit
+            && self.flood_protection == other.flood_protection
+            && self.max_subscriptions == other.max_subscriptions
+            && self.ping_interval == other.ping_interval
+            && self.pong_timeout == other.pong_timeout
+            && self.connection_mode == other.connection_mode
+    }
+
+    /// Update flags, POW difficulty, reconnection and adaptive since options with the values of `other`
+    ///
+    /// The options are shared with the relay, so changes take effect immediately.
+    /// Return `true` if anything changed.
+    pub(crate) fn merge(&self, other: &Self) -> bool {
+        let mut changed: bool = false;
+
+        let flags: RelayServiceFlags = other.flags.load();
+        if self.flags.load() != flags {
+            self.flags.store(flags);
+            changed = true;
+        }
+
+        let pow: u8 = other.get_pow_difficulty();
+        if self.get_pow_difficulty() != pow {
+            self.update_pow_difficulty(pow);
+            changed = true;
+        }
+
+        let reconnect: bool = other.get_reconnect();
+        if self.get_reconnect() != reconnect {
+            self.update_reconnect(reconnect);
+            changed = true;
+        }
+
+        let retry_sec: u64 = other.get_retry_sec();
+        if self.get_retry_sec() != retry_sec {
+            self.update_retry_sec(retry_sec);
+            changed = true;
+        }
+
+        let adjust_retry_sec: bool = other.get_adjust_retry_sec();
+        if self.get_adjust_retry_sec() != adjust_retry_sec {
+            self.update_adjust_retry_sec(adjust_retry_sec);
+            changed = true;
+        }
+
+        let adaptive_since: bool = other.get_adaptive_since();
+        if self.get_adaptive_since() != adaptive_since {
+            self.update_adaptive_since(adaptive_since);
+            changed = true;
+        }
+
+        let resubscribe: bool = other.get_resubscribe();
+        if self.get_resubscribe() != resubscribe {
+            self.update_resubscribe(resubscribe);
+            changed = true;
+        }
+
+        changed
+    }
 }
 
 /// [`Relay`](super::Relay) send options
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RelaySendOptions {
     pub(super) skip_disconnected: bool,
     pub(super) skip_send_confirmation: bool,
     pub(super) timeout: Duration,
+    pub(super) retries: u8,
+    pub(super) retry_backoff: Duration,
 }
 
 impl Default for RelaySendOptions {
@@ -198,6 +457,8 @@ impl Default for RelaySendOptions {
             skip_disconnected: true,
             skip_send_confirmation: false,
             timeout: DEFAULT_SEND_TIMEOUT,
+            retries: 0,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
         }
     }
 }
@@ -227,19 +488,61 @@ impl RelaySendOptions {
         self.timeout = timeout.unwrap_or(DEFAULT_SEND_TIMEOUT);
         self
     }
+
+    /// Max number of retries for events rejected with `rate-limited:` or not sent due to transient errors (default: 0)
+    ///
+    /// Retries are deduplicated by event ID: the application is notified only of the final `OK` of each event.
+    pub fn retries(mut self, retries: u8) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Initial delay between retries, doubled at every attempt (default: 1 sec, max: 60 secs)
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    pub(crate) fn backoff(&self, attempt: u8) -> Duration {
+        self.retry_backoff
+            .saturating_mul(2u32.saturating_pow(attempt as u32))
+            .min(MAX_RETRY_BACKOFF)
+    }
 }
 
 /// Auto-closing subscribe options
-#[derive(Debug, Clone, Copy, Default)]
+///
+/// The subscription is closed as soon as one of the conditions is satisfied.
+#[derive(Debug, Clone, Copy)]
 pub struct SubscribeAutoCloseOptions {
-    pub(super) filter: FilterOptions,
+    /// `None` means that EOSE is ignored
+    pub(super) filter: Option<FilterOptions>,
     pub(super) timeout: Option<Duration>,
+    pub(super) max_events: Option<u16>,
+}
+
+impl Default for SubscribeAutoCloseOptions {
+    fn default() -> Self {
+        Self {
+            filter: Some(FilterOptions::ExitOnEOSE),
+            timeout: None,
+            max_events: None,
+        }
+    }
 }
 
 impl SubscribeAutoCloseOptions {
-    /// Close subscription when [FilterOptions] is satisfied
+    /// Close subscription when [FilterOptions] is satisfied (default: [FilterOptions::ExitOnEOSE])
     pub fn filter(mut self, filter: FilterOptions) -> Self {
-        self.filter = filter;
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Don't close subscription on EOSE
+    ///
+    /// Useful for long-lived subscriptions that must be closed only after a [`Duration`] or N events.
+    pub fn ignore_eose(mut self) -> Self {
+        self.filter = None;
         self
     }
 
@@ -248,6 +551,12 @@ impl SubscribeAutoCloseOptions {
         self.timeout = timeout;
         self
     }
+
+    /// Automatically close subscription after receiving N events, both stored and new ones
+    pub fn max_events(mut self, max: Option<u16>) -> Self {
+        self.max_events = max;
+        self
+    }
 }
 
 /// Subscribe options
@@ -255,6 +564,8 @@ impl SubscribeAutoCloseOptions {
 pub struct SubscribeOptions {
     pub(super) auto_close: Option<SubscribeAutoCloseOptions>,
     pub(super) send_opts: RelaySendOptions,
+    pub(super) priority: u8,
+    pub(super) allow_expensive: bool,
 }
 
 impl SubscribeOptions {
@@ -270,9 +581,30 @@ impl SubscribeOptions {
         self
     }
 
+    /// Set priority (default: 0)
+    ///
+    /// When a relay reaches its max number of subscriptions, subscriptions with higher priority
+    /// preempt the ones with lower priority. Auto-closing subscriptions are never limited.
+    pub fn priority(mut self, priority: u8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Allow pathologically broad filters (default: false)
+    ///
+    /// See [`RelayOptions::refuse_expensive_filters`].
+    pub fn allow_expensive(mut self, allow: bool) -> Self {
+        self.allow_expensive = allow;
+        self
+    }
+
     pub(crate) fn is_auto_closing(&self) -> bool {
         self.auto_close.is_some()
     }
+
+    pub(crate) fn get_priority(&self) -> u8 {
+        self.priority
+    }
 }
 
 /// Filter options
@@ -344,3 +676,50 @@ impl NegentropyOptions {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_send_defaults() {
+        let pool_opts = RelaySendOptions::new().timeout(Some(Duration::from_secs(5)));
+
+        let opts = RelayOptions::new();
+        assert_eq!(
+            opts.get_send_opts(pool_opts).timeout,
+            Duration::from_secs(5)
+        );
+
+        let opts = RelayOptions::new().send_defaults(
+            RelaySendOptions::new()
+                .skip_send_confirmation(true)
+                .timeout(Some(Duration::from_secs(60))),
+        );
+        let send_opts = opts.get_send_opts(pool_opts);
+        assert!(send_opts.skip_send_confirmation);
+        assert_eq!(send_opts.timeout, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_retry_backoff() {
+        let opts = RelaySendOptions::new()
+            .retries(10)
+            .retry_backoff(Duration::from_millis(500));
+        assert_eq!(opts.backoff(0), Duration::from_millis(500));
+        assert_eq!(opts.backoff(1), Duration::from_secs(1));
+        assert_eq!(opts.backoff(3), Duration::from_secs(4));
+        assert_eq!(opts.backoff(9), MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn test_auto_close_defaults() {
+        let opts = SubscribeAutoCloseOptions::default();
+        assert!(matches!(opts.filter, Some(FilterOptions::ExitOnEOSE)));
+        assert!(opts.max_events.is_none());
+
+        let opts = opts.ignore_eose().max_events(Some(10));
+        assert!(opts.filter.is_none());
+        assert_eq!(opts.max_events, Some(10));
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay rate limits
+
+use std::time::Duration;
+
+use nostr::types::time::Instant;
+
+/// Outgoing messages rate limit
+///
+/// Messages exceeding the limit are delayed (not dropped), so the relay doesn't ban the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Max messages per second
+    pub per_sec: u32,
+    /// Max messages sent at once, before applying the rate
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// New rate limit
+    pub fn new(per_sec: u32, burst: u32) -> Self {
+        Self { per_sec, burst }
+    }
+}
+
+/// What to do with the events received above the flood limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloodAction {
+    /// Drop the events
+    #[default]
+    Drop,
+    /// Stop reading from the relay until the rate is back under the limit
+    ///
+    /// No event is lost: the messages are buffered by the connection (backpressure).
+    Buffer,
+}
+
+/// Incoming events flood protection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloodProtection {
+    /// Max events per second
+    pub max_events_per_sec: u32,
+    /// Action
+    pub action: FloodAction,
+}
+
+impl FloodProtection {
+    /// New flood protection
+    pub fn new(max_events_per_sec: u32, action: FloodAction) -> Self {
+        Self {
+            max_events_per_sec,
+            action,
+        }
+    }
+}
+
+/// Token bucket
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(per_sec: u32, burst: u32) -> Self {
+        let capacity: f64 = burst.max(1) as f64;
+        Self {
+            rate: per_sec.max(1) as f64,
+            capacity,
+            tokens: capacity,
+            last: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now: Instant = Instant::now();
+        let elapsed: f64 = now.duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last = now;
+    }
+
+    /// Take a token, if available
+    pub fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reserve `n` tokens and return how long to wait before using them
+    pub fn acquire(&mut self, n: usize) -> Option<Duration> {
+        self.refill();
+        self.tokens -= n as f64;
+        if self.tokens >= 0.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(-self.tokens / self.rate))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(10, 3);
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+
+        // Reserve 5 tokens: ~0.5 secs
+        let wait: Duration = bucket.acquire(5).unwrap();
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+
+        let mut bucket = TokenBucket::new(10, 3);
+        assert_eq!(bucket.acquire(3), None);
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! REQ size estimation and shrinking
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use nostr::{ClientMessage, Filter, JsonUtil, SubscriptionId};
+
+/// Estimate the size, in bytes, of the serialized `REQ` message
+pub fn estimate_req_size(id: &SubscriptionId, filters: &[Filter]) -> usize {
+    ClientMessage::req(id.clone(), filters.to_vec())
+        .as_json()
+        .len()
+}
+
+/// `REQ` messages emitted after shrinking
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReqMapping {
+    /// Emitted subscriptions
+    ///
+    /// The first one always uses the original [`SubscriptionId`].
+    pub requests: Vec<(SubscriptionId, Vec<Filter>)>,
+    /// Original filter -> emitted subscriptions containing (part of) it
+    pub filters: Vec<(Filter, Vec<SubscriptionId>)>,
+}
+
+impl ReqMapping {
+    /// Mapping of a `REQ` sent as is
+    pub fn single(id: SubscriptionId, filters: Vec<Filter>) -> Self {
+        Self {
+            filters: filters
+                .iter()
+                .cloned()
+                .map(|f| (f, vec![id.clone()]))
+                .collect(),
+            requests: vec![(id, filters)],
+        }
+    }
+
+    /// Check if the original `REQ` has been split into more subscriptions
+    pub fn is_split(&self) -> bool {
+        self.requests.len() > 1
+    }
+
+    /// Emitted subscription IDs
+    pub fn ids(&self) -> impl Iterator<Item = &SubscriptionId> {
+        self.requests.iter().map(|(id, ..)| id)
+    }
+}
+
+/// [`ReqMapping`]s of the split subscriptions, indexed by the emitted IDs too
+#[derive(Debug, Default)]
+pub(super) struct ReqMappings {
+    mappings: HashMap<SubscriptionId, ReqMapping>,
+    /// Emitted subscription ID -> original subscription ID
+    original_ids: HashMap<SubscriptionId, SubscriptionId>,
+}
+
+impl ReqMappings {
+    pub fn get(&self, id: &SubscriptionId) -> Option<&ReqMapping> {
+        self.mappings.get(id)
+    }
+
+    /// Get the ID of the subscription requested by the user
+    pub fn original_id<'a>(&'a self, id: &'a SubscriptionId) -> &'a SubscriptionId {
+        self.original_ids.get(id).unwrap_or(id)
+    }
+
+    pub fn insert(&mut self, id: SubscriptionId, mapping: ReqMapping) {
+        self.remove(&id);
+        for emitted in mapping.ids() {
+            self.original_ids.insert(emitted.clone(), id.clone());
+        }
+        self.mappings.insert(id, mapping);
+    }
+
+    pub fn remove(&mut self, id: &SubscriptionId) -> Option<ReqMapping> {
+        let mapping: ReqMapping = self.mappings.remove(id)?;
+        for emitted in mapping.ids() {
+            self.original_ids.remove(emitted);
+        }
+        Some(mapping)
+    }
+
+    pub fn clear(&mut self) {
+        self.mappings.clear();
+        self.original_ids.clear();
+    }
+}
+
+/// Shrink a `REQ` so that every emitted message fits in `max_size` bytes
+///
+/// Oversized filters are split by halving the largest list (`ids`, `authors` or generic tag values)
+/// until they fit, and the pieces are packed in as few `REQ`s as possible.
+/// The additional subscriptions use the `<id>:<n>` format.
+///
+/// Note: the `limit` of a split filter is applied to every piece.
+pub fn shrink_req(id: SubscriptionId, filters: Vec<Filter>, max_size: usize) -> ReqMapping {
+    if estimate_req_size(&id, &filters) <= max_size {
+        return ReqMapping::single(id, filters);
+    }
+
+    // Split every filter in pieces that fit alone in a REQ
+    let pieces: Vec<(usize, Filter)> = filters
+        .iter()
+        .enumerate()
+        .flat_map(|(index, filter)| {
+            split_filter(&id, filter.clone(), max_size)
+                .into_iter()
+                .map(move |f| (index, f))
+        })
+        .collect();
+
+    // Pack pieces into REQs
+    let mut requests: Vec<(SubscriptionId, Vec<Filter>)> = Vec::new();
+    let mut mapping: Vec<(Filter, Vec<SubscriptionId>)> =
+        filters.into_iter().map(|f| (f, Vec::new())).collect();
+    let mut current: Vec<Filter> = Vec::new();
+    let mut current_indexes: Vec<usize> = Vec::new();
+
+    for (index, piece) in pieces.into_iter() {
+        current.push(piece);
+
+        let current_id: SubscriptionId = derived_id(&id, requests.len());
+        if current.len() > 1 && estimate_req_size(&current_id, &current) > max_size {
+            // Doesn't fit: flush the previous pieces
+            let piece: Option<Filter> = current.pop();
+            for i in current_indexes.drain(..) {
+                push_unique(&mut mapping[i].1, current_id.clone());
+            }
+            requests.push((current_id, std::mem::take(&mut current)));
+            current.extend(piece);
+        }
+
+        current_indexes.push(index);
+    }
+
+    if !current.is_empty() {
+        let current_id: SubscriptionId = derived_id(&id, requests.len());
+        for i in current_indexes.into_iter() {
+            push_unique(&mut mapping[i].1, current_id.clone());
+        }
+        requests.push((current_id, current));
+    }
+
+    ReqMapping {
+        requests,
+        filters: mapping,
+    }
+}
+
+fn derived_id(id: &SubscriptionId, index: usize) -> SubscriptionId {
+    if index == 0 {
+        id.clone()
+    } else {
+        SubscriptionId::new(format!("{id}:{index}"))
+    }
+}
+
+fn push_unique(ids: &mut Vec<SubscriptionId>, id: SubscriptionId) {
+    if !ids.contains(&id) {
+        ids.push(id);
+    }
+}
+
+/// Recursively split filter until every piece fits in a `REQ` of `max_size` bytes
+fn split_filter(id: &SubscriptionId, filter: Filter, max_size: usize) -> Vec<Filter> {
+    if estimate_req_size(id, std::slice::from_ref(&filter)) <= max_size {
+        return vec![filter];
+    }
+
+    match halve_filter(&filter) {
+        Some((a, b)) => {
+            let mut pieces = split_filter(id, a, max_size);
+            pieces.extend(split_filter(id, b, max_size));
+            pieces
+        }
+        None => {
+            tracing::warn!("Filter can't be shrunk to fit in {max_size} bytes");
+            vec![filter]
+        }
+    }
+}
+
+/// Split the largest list of the filter in two halves
+///
+/// Return `None` if there isn't a list with at least 2 items.
+fn halve_filter(filter: &Filter) -> Option<(Filter, Filter)> {
+    let ids_len: usize = filter.ids.as_ref().map_or(0, |s| s.len());
+    let authors_len: usize = filter.authors.as_ref().map_or(0, |s| s.len());
+    let largest_tag = filter
+        .generic_tags
+        .iter()
+        .map(|(tag, values)| (*tag, values.len()))
+        .max_by_key(|(.., len)| *len);
+    let tag_len: usize = largest_tag.map_or(0, |(.., len)| len);
+
+    let max: usize = ids_len.max(authors_len).max(tag_len);
+    if max < 2 {
+        return None;
+    }
+
+    let mut a: Filter = filter.clone();
+    let mut b: Filter = filter.clone();
+
+    if max == ids_len {
+        let (x, y) = halve_set(a.ids.take().unwrap_or_default());
+        a.ids = Some(x);
+        b.ids = Some(y);
+    } else if max == authors_len {
+        let (x, y) = halve_set(a.authors.take().unwrap_or_default());
+        a.authors = Some(x);
+        b.authors = Some(y);
+    } else if let Some((tag, ..)) = largest_tag {
+        let (x, y) = halve_set(a.generic_tags.remove(&tag).unwrap_or_default());
+        a.generic_tags.insert(tag, x);
+        b.generic_tags.insert(tag, y);
+    }
+
+    Some((a, b))
+}
+
+fn halve_set<T>(set: HashSet<T>) -> (HashSet<T>, HashSet<T>)
+where
+    T: Eq + Hash,
+{
+    let half: usize = set.len() / 2;
+    let mut first: HashSet<T> = HashSet::with_capacity(half);
+    let mut second: HashSet<T> = HashSet::with_capacity(set.len() - half);
+    for (index, item) in set.into_iter().enumerate() {
+        if index < half {
+            first.insert(item);
+        } else {
+            second.insert(item);
+        }
+    }
+    (first, second)
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{Keys, Kind};
+
+    use super::*;
+
+    #[test]
+    fn test_shrink_req_not_needed() {
+        let id = SubscriptionId::new("test");
+        let filters = vec![Filter::new().kind(Kind::TextNote).limit(10)];
+        let mapping = shrink_req(id.clone(), filters.clone(), 1024);
+        assert!(!mapping.is_split());
+        assert_eq!(mapping.requests, vec![(id.clone(), filters.clone())]);
+        assert_eq!(mapping.filters, vec![(filters[0].clone(), vec![id])]);
+    }
+
+    #[test]
+    fn test_shrink_req_authors() {
+        let id = SubscriptionId::new("test");
+        let authors: Vec<_> = (0..100).map(|_| Keys::generate().public_key()).collect();
+        let filter = Filter::new().authors(authors.clone()).kind(Kind::TextNote);
+        let max_size: usize = 2_000;
+
+        let mapping = shrink_req(id.clone(), vec![filter.clone()], max_size);
+        assert!(mapping.is_split());
+
+        // Every REQ must fit
+        for (id, filters) in mapping.requests.iter() {
+            assert!(estimate_req_size(id, filters) <= max_size);
+        }
+
+        // No author must be lost
+        let mut shrunk_authors: HashSet<_> = HashSet::new();
+        for (.., filters) in mapping.requests.iter() {
+            for f in filters.iter() {
+                shrunk_authors.extend(f.authors.clone().unwrap_or_default());
+            }
+        }
+        assert_eq!(shrunk_authors, authors.into_iter().collect());
+
+        // Mapping
+        assert_eq!(mapping.filters.len(), 1);
+        assert_eq!(mapping.filters[0].0, filter);
+        assert_eq!(
+            mapping.filters[0].1,
+            mapping.ids().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(mapping.requests[0].0, id);
+    }
+
+    #[test]
+    fn test_req_mappings() {
+        let id = SubscriptionId::new("test");
+        let authors: Vec<_> = (0..100).map(|_| Keys::generate().public_key()).collect();
+        let filter = Filter::new().authors(authors).kind(Kind::TextNote);
+        let mapping = shrink_req(id.clone(), vec![filter], 2_000);
+        let emitted: Vec<SubscriptionId> = mapping.ids().cloned().collect();
+
+        let mut mappings = ReqMappings::default();
+        mappings.insert(id.clone(), mapping);
+        for i in emitted.iter() {
+            assert_eq!(mappings.original_id(i), &id);
+        }
+
+        let other = SubscriptionId::new("other");
+        assert_eq!(mappings.original_id(&other), &other);
+
+        // Replace with a single REQ: the previous emitted IDs are removed
+        mappings.insert(id.clone(), ReqMapping::single(id.clone(), Vec::new()));
+        assert_eq!(mappings.original_id(&emitted[1]), &emitted[1]);
+
+        assert!(mappings.remove(&id).is_some());
+        assert!(mappings.get(&id).is_none());
+        assert!(mappings.original_ids.is_empty());
+    }
+}
@@ -78,6 +78,21 @@ impl PingStats {
     }
 }
 
+/// Snapshot of the cumulative [`RelayConnectionStats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelayStatsSnapshot {
+    /// The number of times a connection has been attempted
+    pub attempts: usize,
+    /// The number of times a connection has been successfully established
+    pub success: usize,
+    /// Bytes sent
+    pub bytes_sent: usize,
+    /// Bytes received
+    pub bytes_received: usize,
+    /// UNIX timestamp of the first connection
+    pub first_connection_timestamp: Timestamp,
+}
+
 /// Relay connection stats
 #[derive(Debug, Clone)]
 pub struct RelayConnectionStats {
@@ -85,6 +100,11 @@ pub struct RelayConnectionStats {
     success: Arc<AtomicUsize>,
     bytes_sent: Arc<AtomicUsize>,
     bytes_received: Arc<AtomicUsize>,
+    crashes: Arc<AtomicUsize>,
+    throttled_messages: Arc<AtomicUsize>,
+    dropped_events: Arc<AtomicUsize>,
+    buffered_events: Arc<AtomicUsize>,
+    suppressed_duplicates: Arc<AtomicUsize>,
     connected_at: Arc<AtomicU64>,
     first_connection_timestamp: Arc<AtomicU64>,
     #[cfg(not(target_arch = "wasm32"))]
@@ -107,6 +127,11 @@ impl RelayConnectionStats {
             success: Arc::new(AtomicUsize::new(0)),
             bytes_sent: Arc::new(AtomicUsize::new(0)),
             bytes_received: Arc::new(AtomicUsize::new(0)),
+            crashes: Arc::new(AtomicUsize::new(0)),
+            throttled_messages: Arc::new(AtomicUsize::new(0)),
+            dropped_events: Arc::new(AtomicUsize::new(0)),
+            buffered_events: Arc::new(AtomicUsize::new(0)),
+            suppressed_duplicates: Arc::new(AtomicUsize::new(0)),
             connected_at: Arc::new(AtomicU64::new(0)),
             first_connection_timestamp: Arc::new(AtomicU64::new(0)),
             #[cfg(not(target_arch = "wasm32"))]
@@ -147,6 +172,33 @@ impl RelayConnectionStats {
         self.bytes_received.load(Ordering::SeqCst)
     }
 
+    /// The number of times a relay actor panicked
+    pub fn crashes(&self) -> usize {
+        self.crashes.load(Ordering::SeqCst)
+    }
+
+    /// The number of outgoing messages delayed by the rate limit
+    pub fn throttled_messages(&self) -> usize {
+        self.throttled_messages.load(Ordering::SeqCst)
+    }
+
+    /// The number of incoming events dropped by the flood protection
+    pub fn dropped_events(&self) -> usize {
+        self.dropped_events.load(Ordering::SeqCst)
+    }
+
+    /// The number of incoming events delayed by the flood protection
+    pub fn buffered_events(&self) -> usize {
+        self.buffered_events.load(Ordering::SeqCst)
+    }
+
+    /// The number of incoming events suppressed because repeating the content of the same author
+    ///
+    /// See [`DedupOptions::content_window`](crate::pool::dedup::DedupOptions::content_window).
+    pub fn suppressed_duplicates(&self) -> usize {
+        self.suppressed_duplicates.load(Ordering::SeqCst)
+    }
+
     /// Get UNIX timestamp of the last connection
     pub fn connected_at(&self) -> Timestamp {
         Timestamp::from(self.connected_at.load(Ordering::SeqCst))
@@ -165,6 +217,38 @@ impl RelayConnectionStats {
         sum.checked_div(latencies.len() as u32)
     }
 
+    /// Take a snapshot of the cumulative stats
+    pub fn snapshot(&self) -> RelayStatsSnapshot {
+        RelayStatsSnapshot {
+            attempts: self.attempts(),
+            success: self.success(),
+            bytes_sent: self.bytes_sent(),
+            bytes_received: self.bytes_received(),
+            first_connection_timestamp: self.first_connection_timestamp(),
+        }
+    }
+
+    /// Restore stats from a [`RelayStatsSnapshot`]
+    ///
+    /// Counters are added to the current ones, so stats collected after the snapshot are not lost.
+    pub(crate) fn restore(&self, snapshot: RelayStatsSnapshot) {
+        self.attempts.fetch_add(snapshot.attempts, Ordering::SeqCst);
+        self.success.fetch_add(snapshot.success, Ordering::SeqCst);
+        self.bytes_sent
+            .fetch_add(snapshot.bytes_sent, Ordering::SeqCst);
+        self.bytes_received
+            .fetch_add(snapshot.bytes_received, Ordering::SeqCst);
+
+        let first: u64 = snapshot.first_connection_timestamp.as_u64();
+        if first > 0 {
+            let current: u64 = self.first_connection_timestamp.load(Ordering::SeqCst);
+            if current == 0 || first < current {
+                self.first_connection_timestamp
+                    .store(first, Ordering::SeqCst);
+            }
+        }
+    }
+
     pub(crate) fn new_attempt(&self) {
         self.attempts.fetch_add(1, Ordering::SeqCst);
     }
@@ -181,6 +265,26 @@ impl RelayConnectionStats {
         }
     }
 
+    pub(crate) fn new_crash(&self) {
+        self.crashes.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn add_throttled_messages(&self, n: usize) {
+        self.throttled_messages.fetch_add(n, Ordering::SeqCst);
+    }
+
+    pub(crate) fn new_dropped_event(&self) {
+        self.dropped_events.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn new_buffered_event(&self) {
+        self.buffered_events.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn new_suppressed_duplicate(&self) {
+        self.suppressed_duplicates.fetch_add(1, Ordering::SeqCst);
+    }
+
     pub(crate) fn add_bytes_sent(&self, size: usize) {
         self.bytes_sent.fetch_add(size, Ordering::SeqCst);
     }
@@ -0,0 +1,95 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Subscription lifecycle
+
+use core::fmt;
+
+use nostr::MachineReadablePrefix;
+
+/// Why a subscription has been closed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SubscriptionCloseReason {
+    /// Closed by us with `unsubscribe` or `unsubscribe_all`
+    Unsubscribed,
+    /// Closed by us since the auto-close condition has been satisfied
+    AutoClosed,
+    /// Closed by us since the auto-close timeout has been reached
+    Timeout,
+    /// Closed by the relay with a `CLOSED` message
+    Relay {
+        /// Machine-readable prefix of the message (i.e. `auth-required`), if any
+        prefix: Option<MachineReadablePrefix>,
+        /// Message
+        message: String,
+    },
+}
+
+impl fmt::Display for SubscriptionCloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsubscribed => write!(f, "unsubscribed"),
+            Self::AutoClosed => write!(f, "auto-closed"),
+            Self::Timeout => write!(f, "timeout"),
+            Self::Relay { message, .. } => write!(f, "closed by relay: {message}"),
+        }
+    }
+}
+
+impl SubscriptionCloseReason {
+    /// Check if the subscription has been closed by us
+    pub fn is_by_us(&self) -> bool {
+        !self.is_by_relay()
+    }
+
+    /// Check if the subscription has been closed by the relay
+    pub fn is_by_relay(&self) -> bool {
+        matches!(self, Self::Relay { .. })
+    }
+
+    /// Check if the relay closed the subscription since NIP42 authentication is required
+    pub fn is_auth_required(&self) -> bool {
+        matches!(
+            self,
+            Self::Relay {
+                prefix: Some(MachineReadablePrefix::AuthRequired),
+                ..
+            }
+        )
+    }
+
+    /// Check if the relay closed the subscription since rate limited
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(
+            self,
+            Self::Relay {
+                prefix: Some(MachineReadablePrefix::RateLimited),
+                ..
+            }
+        )
+    }
+}
+
+/// Subscription lifecycle milestone
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SubscriptionLifecycle {
+    /// `REQ` sent to the relay
+    Created,
+    /// `REQ` sent again to the relay after a reconnection
+    Resubscribed,
+    /// `EOSE` received from the relay
+    EndOfStoredEvents,
+    /// Subscription closed
+    Closed {
+        /// Reason
+        reason: SubscriptionCloseReason,
+    },
+}
+
+impl SubscriptionLifecycle {
+    /// Check if the subscription has been closed
+    pub fn is_closed(&self) -> bool {
+        matches!(self, Self::Closed { .. })
+    }
+}
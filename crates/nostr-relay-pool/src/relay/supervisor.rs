@@ -0,0 +1,268 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay actors supervision
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_utility::futures_util::FutureExt;
+use async_wsocket::futures_util::Future;
+use nostr::types::time::Instant;
+use nostr::Url;
+
+use super::stats::RelayConnectionStats;
+
+/// Restart policy of the relay actors
+///
+/// Each relay runs a connection actor (auto reconnection loop) and, while connected,
+/// the message sender, message receiver and pinger actors.
+/// When an actor panics, the connection is torn down and the actors are respawned on reconnection.
+///
+/// Panics are caught by unwinding, so the policy is effective only with `panic = "unwind"` (the default).
+/// With `panic = "abort"` (i.e. the release profile of the bindings), a panicking actor aborts the whole process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Never restart: the relay is stopped at the first crash
+    Never,
+    /// Restart at most `max_restarts` times within `period`, otherwise stop the relay
+    Limited {
+        /// Max number of restarts
+        max_restarts: usize,
+        /// Sliding window
+        period: Duration,
+    },
+    /// Always restart
+    Always,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self::Limited {
+            max_restarts: 5,
+            period: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Relay actor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Actor {
+    /// Auto reconnection loop
+    Connection,
+    /// Consume the mailbox and write to the websocket
+    Sender,
+    /// Read from the websocket and handle relay messages
+    Receiver,
+    /// Ping the relay
+    #[cfg(not(target_arch = "wasm32"))]
+    Pinger,
+}
+
+impl fmt::Display for Actor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connection => write!(f, "connection"),
+            Self::Sender => write!(f, "sender"),
+            Self::Receiver => write!(f, "receiver"),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Pinger => write!(f, "pinger"),
+        }
+    }
+}
+
+/// Catch the panics of the relay actors and decide if they can be restarted
+#[derive(Debug, Clone)]
+pub(crate) struct Supervisor {
+    url: Url,
+    policy: RestartPolicy,
+    stats: RelayConnectionStats,
+    restarts: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl Supervisor {
+    pub fn new(url: Url, policy: RestartPolicy, stats: RelayConnectionStats) -> Self {
+        Self {
+            url,
+            policy,
+            stats,
+            restarts: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Run the actor until completion
+    ///
+    /// Return `true` if the actor panicked. Requires `panic = "unwind"`: check [`RestartPolicy`].
+    pub async fn run<Fut>(&self, actor: Actor, future: Fut) -> bool
+    where
+        Fut: Future<Output = ()>,
+    {
+        match AssertUnwindSafe(future).catch_unwind().await {
+            Ok(()) => false,
+            Err(payload) => {
+                self.stats.new_crash();
+                tracing::error!(
+                    "The {actor} actor of {} panicked: {}",
+                    self.url,
+                    panic_message(payload.as_ref())
+                );
+                true
+            }
+        }
+    }
+
+    /// Run the actor, restarting it after a panic according to the [`RestartPolicy`]
+    ///
+    /// Return `false` if the actor panicked and has not been restarted.
+    pub async fn supervise<F, Fut>(&self, actor: Actor, mut spawn: F) -> bool
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        loop {
+            if !self.run(actor, spawn()).await {
+                return true;
+            }
+
+            if !self.can_restart() {
+                return false;
+            }
+
+            tracing::warn!("Restarting the {actor} actor of {}", self.url);
+        }
+    }
+
+    /// Register a restart, if allowed by the [`RestartPolicy`]
+    pub fn can_restart(&self) -> bool {
+        match self.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::Always => true,
+            RestartPolicy::Limited {
+                max_restarts,
+                period,
+            } => {
+                let mut restarts = match self.restarts.lock() {
+                    Ok(restarts) => restarts,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                let now: Instant = Instant::now();
+                while let Some(restart) = restarts.front() {
+                    if now.duration_since(*restart) >= period {
+                        restarts.pop_front();
+                    } else {
+                        break;
+                    }
+                }
+
+                if restarts.len() >= max_restarts {
+                    return false;
+                }
+
+                restarts.push_back(now);
+                true
+            }
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.as_str()
+    } else {
+        "unknown"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn supervisor(policy: RestartPolicy) -> Supervisor {
+        let url = Url::parse("wss://relay.damus.io").unwrap();
+        Supervisor::new(url, policy, RelayConnectionStats::new())
+    }
+
+    #[test]
+    fn test_restart_policy() {
+        let never = supervisor(RestartPolicy::Never);
+        assert!(!never.can_restart());
+
+        let always = supervisor(RestartPolicy::Always);
+        assert!((0..100).all(|_| always.can_restart()));
+
+        let limited = supervisor(RestartPolicy::Limited {
+            max_restarts: 2,
+            period: Duration::from_secs(60),
+        });
+        assert!(limited.can_restart());
+        assert!(limited.can_restart());
+        assert!(!limited.can_restart());
+
+        let expired = supervisor(RestartPolicy::Limited {
+            max_restarts: 1,
+            period: Duration::ZERO,
+        });
+        assert!(expired.can_restart());
+        assert!(expired.can_restart());
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload: Box<dyn Any + Send> = Box::new("boom");
+        assert_eq!(panic_message(payload.as_ref()), "boom");
+
+        let payload: Box<dyn Any + Send> = Box::new(String::from("boom"));
+        assert_eq!(panic_message(payload.as_ref()), "boom");
+
+        let payload: Box<dyn Any + Send> = Box::new(42);
+        assert_eq!(panic_message(payload.as_ref()), "unknown");
+    }
+
+    #[tokio::test]
+    async fn test_supervise_panicking_actor() {
+        // Panic twice, then complete
+        let always = supervisor(RestartPolicy::Always);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let completed = always
+            .supervise(Actor::Receiver, || {
+                let runs = runs.clone();
+                async move {
+                    if runs.fetch_add(1, Ordering::SeqCst) < 2 {
+                        panic!("boom");
+                    }
+                }
+            })
+            .await;
+        assert!(completed);
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(always.stats.crashes(), 2);
+
+        // Always panic: stop after the max restarts
+        let limited = supervisor(RestartPolicy::Limited {
+            max_restarts: 2,
+            period: Duration::from_secs(60),
+        });
+        let runs = Arc::new(AtomicUsize::new(0));
+        let completed = limited
+            .supervise(Actor::Sender, || {
+                let runs = runs.clone();
+                async move {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    panic!("boom");
+                }
+            })
+            .await;
+        assert!(!completed);
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(limited.stats.crashes(), 3);
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Relay transport
+//!
+//! Relays are reached with WebSocket over TCP (`ws://` and `wss://`) or, for co-located relays
+//! (i.e. embedded relay or strfry on the same host), over a unix domain socket (`ws+unix:///path/to/socket`).
+
+use std::net::SocketAddr;
+#[cfg(not(target_arch = "wasm32"))]
+use std::pin::Pin;
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+use async_wsocket::futures_util;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+use async_wsocket::futures_util::StreamExt;
+#[cfg(not(target_arch = "wasm32"))]
+use async_wsocket::WsMessage;
+#[cfg(target_arch = "wasm32")]
+pub use async_wsocket::{Sink, Stream};
+use nostr::Url;
+use thiserror::Error;
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+use tokio::net::UnixStream;
+
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+use crate::runtime::time;
+
+/// Unix domain socket URL scheme
+pub const UNIX_SCHEME: &str = "ws+unix";
+
+#[cfg(not(target_arch = "wasm32"))]
+type WsError = tokio_tungstenite::tungstenite::Error;
+
+/// WebSocket sink
+#[cfg(not(target_arch = "wasm32"))]
+pub type Sink = Pin<Box<dyn futures_util::Sink<WsMessage, Error = WsError> + Send>>;
+
+/// WebSocket stream
+#[cfg(not(target_arch = "wasm32"))]
+pub type Stream = Pin<Box<dyn futures_util::Stream<Item = Result<WsMessage, WsError>> + Send>>;
+
+/// Transport error
+#[derive(Debug, Error)]
+pub enum Error {
+    /// WebSocket error
+    #[error(transparent)]
+    WebSocket(#[from] async_wsocket::Error),
+    /// I/O error
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// WebSocket handshake error
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error(transparent)]
+    Handshake(#[from] WsError),
+    /// Connection timeout
+    #[error("timeout")]
+    Timeout,
+    /// Unix domain sockets not supported on this platform
+    #[error("unix domain sockets not supported on this platform")]
+    UnixSocketNotSupported,
+}
+
+/// Check if the URL is a unix domain socket URL
+#[inline]
+pub fn is_unix(url: &Url) -> bool {
+    url.scheme() == UNIX_SCHEME
+}
+
+/// Connect to relay
+///
+/// The proxy is ignored for unix domain sockets.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn connect(
+    url: &Url,
+    proxy: Option<SocketAddr>,
+    timeout: Option<Duration>,
+) -> Result<(Sink, Stream), Error> {
+    if is_unix(url) {
+        return connect_unix(url, timeout).await;
+    }
+
+    let (tx, rx) = async_wsocket::connect(url, proxy, timeout).await?;
+    Ok((Box::pin(tx), Box::pin(rx)))
+}
+
+/// Connect to relay
+#[cfg(target_arch = "wasm32")]
+pub async fn connect(
+    url: &Url,
+    proxy: Option<SocketAddr>,
+    timeout: Option<Duration>,
+) -> Result<(Sink, Stream), Error> {
+    if is_unix(url) {
+        return Err(Error::UnixSocketNotSupported);
+    }
+
+    Ok(async_wsocket::connect(url, proxy, timeout).await?)
+}
+
+#[cfg(all(unix, not(target_arch = "wasm32")))]
+async fn connect_unix(url: &Url, timeout: Option<Duration>) -> Result<(Sink, Stream), Error> {
+    let (socket, ..) = time::timeout(timeout, async {
+        let stream: UnixStream = UnixStream::connect(url.path()).await?;
+        // The handshake requires a host, meaningless over a unix domain socket
+        Ok::<_, Error>(tokio_tungstenite::client_async("ws://localhost/", stream).await?)
+    })
+    .await
+    .ok_or(Error::Timeout)??;
+
+    let (tx, rx) = socket.split();
+    Ok((Box::pin(tx), Box::pin(rx)))
+}
+
+#[cfg(all(not(unix), not(target_arch = "wasm32")))]
+async fn connect_unix(_url: &Url, _timeout: Option<Duration>) -> Result<(Sink, Stream), Error> {
+    Err(Error::UnixSocketNotSupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_url() {
+        let url = Url::parse("ws+unix:///var/run/strfry.sock").unwrap();
+        assert!(is_unix(&url));
+        assert_eq!(url.path(), "/var/run/strfry.sock");
+
+        let url = Url::parse("wss://relay.damus.io").unwrap();
+        assert!(!is_unix(&url));
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Async runtime
+//!
+//! Tasks are spawned and timers are created through this module, so the pool can run on
+//! `tokio` (default), `async-std` (`async-std` feature) or `smol` (`smol` feature).
+//! On WASM, tasks are always spawned with `wasm-bindgen-futures`.
+//!
+//! Channels and locks come from `tokio::sync`, that doesn't depend on the tokio runtime.
+//! The WebSocket transport needs the tokio reactor: with `async-std` its `tokio1` compatibility layer is enabled,
+//! while with `smol` the spawned tasks are wrapped with `async-compat`.
+
+#[cfg(all(feature = "async-std", feature = "smol"))]
+compile_error!("features `async-std` and `smol` are mutually exclusive");
+
+#[cfg(any(
+    target_arch = "wasm32",
+    not(any(feature = "async-std", feature = "smol"))
+))]
+pub use async_utility::{thread, time};
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "async-std", feature = "smol")
+))]
+pub mod thread;
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "async-std", feature = "smol")
+))]
+pub mod time;
@@ -0,0 +1,97 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Tasks on `async-std` or `smol`
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "smol")]
+use async_compat::Compat;
+use async_utility::futures_util::future::{AbortHandle, Abortable};
+
+/// Thread error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Impossible to join the task
+    JoinError,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::JoinError => write!(f, "impossible to join thread"),
+        }
+    }
+}
+
+/// Handle of a spawned task
+///
+/// Dropping the handle detaches the task.
+pub struct JoinHandle<T> {
+    #[cfg(feature = "async-std")]
+    inner: async_std::task::JoinHandle<T>,
+    #[cfg(feature = "smol")]
+    inner: Option<smol::Task<T>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Wait for the task to finish
+    #[cfg(feature = "async-std")]
+    pub async fn join(self) -> Result<T, Error> {
+        Ok(self.inner.await)
+    }
+
+    /// Wait for the task to finish
+    #[cfg(feature = "smol")]
+    pub async fn join(mut self) -> Result<T, Error> {
+        let task: smol::Task<T> = self.inner.take().ok_or(Error::JoinError)?;
+        Ok(task.await)
+    }
+}
+
+/// `smol` cancels the tasks on drop
+#[cfg(feature = "smol")]
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(task) = self.inner.take() {
+            task.detach();
+        }
+    }
+}
+
+/// Spawn a new task
+pub fn spawn<T>(future: T) -> Result<JoinHandle<T::Output>, Error>
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    #[cfg(feature = "async-std")]
+    let inner = async_std::task::spawn(future);
+    #[cfg(feature = "smol")]
+    let inner = Some(smol::spawn(Compat::new(future)));
+    Ok(JoinHandle { inner })
+}
+
+/// Spawn an abortable task
+pub fn abortable<T>(future: T) -> Result<AbortHandle, Error>
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    let (abort_handle, abort_registration) = AbortHandle::new_pair();
+    spawn(Abortable::new(future, abort_registration))?;
+    Ok(abort_handle)
+}
+
+/// Sleep
+pub async fn sleep(duration: Duration) {
+    #[cfg(feature = "async-std")]
+    async_std::task::sleep(duration).await;
+    #[cfg(feature = "smol")]
+    smol::Timer::after(duration).await;
+}
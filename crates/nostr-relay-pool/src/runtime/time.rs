@@ -0,0 +1,34 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Timers on `async-std` or `smol`
+
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(feature = "smol")]
+use smol::future::FutureExt;
+
+/// Run the future until completion or until the timeout expires
+///
+/// Return `None` if the timeout expires. If timeout is set to `None`, wait forever.
+pub async fn timeout<F>(timeout: Option<Duration>, future: F) -> Option<F::Output>
+where
+    F: Future,
+{
+    match timeout {
+        #[cfg(feature = "async-std")]
+        Some(duration) => async_std::future::timeout(duration, future).await.ok(),
+        #[cfg(feature = "smol")]
+        Some(duration) => {
+            async { Some(future.await) }
+                .or(async {
+                    smol::Timer::after(duration).await;
+                    None
+                })
+                .await
+        }
+        None => Some(future.await),
+    }
+}
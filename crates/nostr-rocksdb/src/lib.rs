@@ -9,7 +9,7 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::bare_urls)]
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -18,7 +18,7 @@ pub extern crate nostr_database as database;
 
 use async_trait::async_trait;
 use nostr::nips::nip01::Coordinate;
-use nostr::{Event, EventId, Filter, Timestamp, Url};
+use nostr::{Event, EventId, Filter, SingleLetterTag, Timestamp, Url};
 use nostr_database::{
     Backend, DatabaseError, DatabaseIndexes, EventIndexResult, FlatBufferBuilder, FlatBufferDecode,
     FlatBufferEncode, NostrDatabase, Order, TempEvent,
@@ -33,6 +33,7 @@ mod ops;
 
 const EVENTS_CF: &str = "events";
 const EVENTS_SEEN_BY_RELAYS_CF: &str = "event-seen-by-relays";
+const CHECKPOINTS_CF: &str = "checkpoints";
 
 /// RocksDB Nostr Database
 #[derive(Debug, Clone)]
@@ -66,6 +67,7 @@ fn column_families() -> Vec<ColumnFamilyDescriptor> {
     vec![
         ColumnFamilyDescriptor::new(EVENTS_CF, default_opts()),
         ColumnFamilyDescriptor::new(EVENTS_SEEN_BY_RELAYS_CF, relay_urls_opts),
+        ColumnFamilyDescriptor::new(CHECKPOINTS_CF, default_opts()),
     ]
 }
 
@@ -297,11 +299,32 @@ impl NostrDatabase for RocksDatabase {
         .map_err(DatabaseError::backend)?
     }
 
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        let cf = self.cf_handle(CHECKPOINTS_CF)?;
+        self.db
+            .put_cf(&cf, key, value)
+            .map_err(DatabaseError::backend)
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        let cf = self.cf_handle(CHECKPOINTS_CF)?;
+        self.db.get_cf(&cf, key).map_err(DatabaseError::backend)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
         Ok(self.indexes.count(filters).await)
     }
 
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        Ok(self.indexes.tag_values(filter, tag).await)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
         let ids: Vec<EventId> = self.indexes.query(filters, order).await;
@@ -0,0 +1,428 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Multi-device app state sync
+//!
+//! Each key is stored as a NIP44 encrypted (to self) application-specific data event (kind `30078`).
+//! The `d` tag is the hash of namespace and key, so keys aren't leaked to relays.
+//! Every write is tagged with a vector clock (one `clock` tag per device), used to detect concurrent writes.
+//! Concurrent writes are resolved with last-writer-wins (`created_at`, then device ID).
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::Hash;
+use nostr::prelude::*;
+use nostr::serde_json::{self, json, Value};
+use nostr_relay_pool::runtime::thread;
+use nostr_relay_pool::RelayPoolNotification;
+use nostr_signer::NostrSigner;
+use tokio::sync::{broadcast, RwLock};
+
+use super::{published_id, Client, Error};
+
+const CLOCK_TAG: &str = "clock";
+
+/// Vector clock: number of writes seen from each device
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VectorClock(BTreeMap<String, u64>);
+
+/// Causal relation between two [`VectorClock`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Causality {
+    /// Same clock
+    Equal,
+    /// Happened before
+    Before,
+    /// Happened after
+    After,
+    /// Concurrent writes: conflict
+    Concurrent,
+}
+
+impl VectorClock {
+    /// Writes seen from a device
+    pub fn get(&self, device: &str) -> u64 {
+        self.0.get(device).copied().unwrap_or_default()
+    }
+
+    /// Increment the counter of a device
+    pub fn increment(&mut self, device: &str) {
+        *self.0.entry(device.to_string()).or_default() += 1;
+    }
+
+    /// Merge with another clock, taking the max of each counter
+    pub fn merge(&mut self, other: &Self) {
+        for (device, counter) in other.0.iter() {
+            let current = self.0.entry(device.clone()).or_default();
+            *current = (*current).max(*counter);
+        }
+    }
+
+    /// Compare with another clock
+    pub fn compare(&self, other: &Self) -> Causality {
+        let mut less: bool = false;
+        let mut greater: bool = false;
+        for device in self.0.keys().chain(other.0.keys()) {
+            match self.get(device).cmp(&other.get(device)) {
+                Ordering::Less => less = true,
+                Ordering::Greater => greater = true,
+                Ordering::Equal => (),
+            }
+        }
+
+        match (less, greater) {
+            (false, false) => Causality::Equal,
+            (true, false) => Causality::Before,
+            (false, true) => Causality::After,
+            (true, true) => Causality::Concurrent,
+        }
+    }
+
+    fn to_tags(&self) -> impl Iterator<Item = Tag> + '_ {
+        self.0.iter().map(|(device, counter)| {
+            Tag::custom(
+                TagKind::Custom(CLOCK_TAG.to_string()),
+                [device.clone(), counter.to_string()],
+            )
+        })
+    }
+
+    fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: Iterator<Item = &'a Tag>,
+    {
+        let mut clock = Self::default();
+        for tag in tags.map(|t| t.as_vec()) {
+            if let [kind, device, counter] = tag.as_slice() {
+                if kind == CLOCK_TAG {
+                    if let Ok(counter) = counter.parse::<u64>() {
+                        clock.0.insert(device.clone(), counter);
+                    }
+                }
+            }
+        }
+        clock
+    }
+}
+
+/// App state update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppSyncUpdate {
+    /// Key
+    pub key: String,
+    /// New value
+    pub value: String,
+    /// Device that wrote the value
+    pub device: String,
+    /// Whether the value has been selected among concurrent writes
+    pub conflict: bool,
+}
+
+/// Observer of the updates of a key
+#[derive(Debug)]
+pub struct AppSyncObserver {
+    key: String,
+    receiver: broadcast::Receiver<AppSyncUpdate>,
+}
+
+impl AppSyncObserver {
+    /// Wait for the next update
+    ///
+    /// Return `None` if the [`AppSync`] has been dropped.
+    pub async fn recv(&mut self) -> Option<AppSyncUpdate> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(update) if update.key == self.key => return Some(update),
+                Ok(..) | Err(broadcast::error::RecvError::Lagged(..)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    value: String,
+    clock: VectorClock,
+    device: String,
+    created_at: Timestamp,
+}
+
+impl Entry {
+    /// Check if `self` wins against a concurrent write
+    fn wins_against(&self, other: &Self) -> bool {
+        (self.created_at, &self.device) > (other.created_at, &other.device)
+    }
+}
+
+/// Sync app state across devices through relays
+#[derive(Debug, Clone)]
+pub struct AppSync {
+    client: Client,
+    namespace: String,
+    device: String,
+    entries: Arc<RwLock<HashMap<String, Entry>>>,
+    updates: broadcast::Sender<AppSyncUpdate>,
+}
+
+impl AppSync {
+    /// New app state sync
+    ///
+    /// `namespace` identifies the app, `device` must be unique for each device of the user.
+    pub fn new<N, D>(client: &Client, namespace: N, device: D) -> Self
+    where
+        N: Into<String>,
+        D: Into<String>,
+    {
+        let (updates, ..) = broadcast::channel(1024);
+        Self {
+            client: client.clone(),
+            namespace: namespace.into(),
+            device: device.into(),
+            entries: Arc::new(RwLock::new(HashMap::new())),
+            updates,
+        }
+    }
+
+    /// Get the current value of a key
+    pub async fn get(&self, key: &str) -> Option<String> {
+        let entries = self.entries.read().await;
+        entries.get(key).map(|e| e.value.clone())
+    }
+
+    /// Set the value of a key and publish it
+    pub async fn set<K, V>(&self, key: K, value: V) -> Result<EventId, Error>
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let key: String = key.into();
+        let value: String = value.into();
+
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let mut entries = self.entries.write().await;
+        let mut clock: VectorClock = entries
+            .get(&key)
+            .map(|e| e.clock.clone())
+            .unwrap_or_default();
+        clock.increment(&self.device);
+
+        let payload: String = json!({
+            "namespace": self.namespace,
+            "key": key,
+            "value": value,
+            "device": self.device,
+        })
+        .to_string();
+        let content: String = signer.nip44_encrypt(public_key, payload).await?;
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(self.identifier(&key))];
+        tags.extend(clock.to_tags());
+        let builder = EventBuilder::new(Kind::ApplicationSpecificData, content, tags);
+        let event: Event = self.client.sign_event_builder(builder).await?;
+        let event_id: EventId = published_id(self.client.send_event(event.clone()).await?)?;
+
+        entries.insert(
+            key.clone(),
+            Entry {
+                value: value.clone(),
+                clock,
+                device: self.device.clone(),
+                created_at: event.created_at(),
+            },
+        );
+        drop(entries);
+
+        let _ = self.updates.send(AppSyncUpdate {
+            key,
+            value,
+            device: self.device.clone(),
+            conflict: false,
+        });
+
+        Ok(event_id)
+    }
+
+    /// Observe the updates of a key, both local and from other devices
+    pub fn observe<K>(&self, key: K) -> AppSyncObserver
+    where
+        K: Into<String>,
+    {
+        AppSyncObserver {
+            key: key.into(),
+            receiver: self.updates.subscribe(),
+        }
+    }
+
+    /// Fetch the app state stored on relays
+    pub async fn pull(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .kind(Kind::ApplicationSpecificData)
+            .author(public_key);
+        let events: Vec<Event> = self.client.get_events_of(vec![filter], timeout).await?;
+        for event in events.iter() {
+            self.merge(&signer, public_key, event).await;
+        }
+        Ok(())
+    }
+
+    /// Subscribe to the updates from other devices
+    ///
+    /// Updates are merged in background until the client is shut down.
+    pub async fn start(&self) -> Result<SubscriptionId, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .kind(Kind::ApplicationSpecificData)
+            .author(public_key)
+            .since(Timestamp::now());
+
+        let mut notifications = self.client.notifications();
+        let id: SubscriptionId = self.client.subscribe(vec![filter], None).await;
+
+        let sync = self.clone();
+        let _ = thread::spawn(async move {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Event { event, .. } => {
+                        if event.kind() == Kind::ApplicationSpecificData
+                            && event.author() == public_key
+                        {
+                            sync.merge(&signer, public_key, &event).await;
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+
+        Ok(id)
+    }
+
+    fn identifier(&self, key: &str) -> String {
+        let data: String = format!("{}:{}", self.namespace, key);
+        Sha256Hash::hash(data.as_bytes()).to_string()
+    }
+
+    /// Merge a remote write
+    async fn merge(&self, signer: &NostrSigner, public_key: PublicKey, event: &Event) {
+        let payload: String = match signer.nip44_decrypt(public_key, event.content()).await {
+            Ok(payload) => payload,
+            // Not written by this sync engine
+            Err(_) => return,
+        };
+        let payload: Value = match serde_json::from_str(&payload) {
+            Ok(payload) => payload,
+            Err(_) => return,
+        };
+
+        let field = |name: &str| payload.get(name).and_then(Value::as_str);
+        let (key, value, device) = match (field("key"), field("value"), field("device")) {
+            (Some(key), Some(value), Some(device))
+                if field("namespace") == Some(self.namespace.as_str()) =>
+            {
+                (key.to_string(), value.to_string(), device.to_string())
+            }
+            _ => return,
+        };
+
+        // Check that the identifier matches the key
+        if event.identifier() != Some(self.identifier(&key).as_str()) {
+            tracing::warn!("App state sync: identifier mismatch for {}", event.id());
+            return;
+        }
+
+        let remote = Entry {
+            value,
+            clock: VectorClock::from_tags(event.iter_tags()),
+            device,
+            created_at: event.created_at(),
+        };
+
+        let mut entries = self.entries.write().await;
+        let (update, conflict) = match entries.get_mut(&key) {
+            Some(local) => match remote.clock.compare(&local.clock) {
+                Causality::Equal | Causality::Before => return,
+                Causality::After => {
+                    *local = remote.clone();
+                    (true, false)
+                }
+                Causality::Concurrent => {
+                    tracing::debug!("App state sync: concurrent writes for '{key}'");
+                    let wins: bool = remote.wins_against(local);
+                    let mut clock: VectorClock = local.clock.clone();
+                    clock.merge(&remote.clock);
+                    if wins {
+                        *local = remote.clone();
+                    }
+                    local.clock = clock;
+                    (wins, true)
+                }
+            },
+            None => {
+                entries.insert(key.clone(), remote.clone());
+                (true, false)
+            }
+        };
+        drop(entries);
+
+        if update {
+            let _ = self.updates.send(AppSyncUpdate {
+                key,
+                value: remote.value,
+                device: remote.device,
+                conflict,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(counters: &[(&str, u64)]) -> VectorClock {
+        let mut clock = VectorClock::default();
+        for (device, counter) in counters.iter() {
+            for _ in 0..*counter {
+                clock.increment(device);
+            }
+        }
+        clock
+    }
+
+    #[test]
+    fn test_vector_clock_compare() {
+        let a = clock(&[("phone", 2), ("laptop", 1)]);
+        let b = clock(&[("phone", 2), ("laptop", 2)]);
+        let c = clock(&[("phone", 3), ("laptop", 1)]);
+
+        assert_eq!(a.compare(&a), Causality::Equal);
+        assert_eq!(a.compare(&b), Causality::Before);
+        assert_eq!(b.compare(&a), Causality::After);
+        assert_eq!(b.compare(&c), Causality::Concurrent);
+
+        let mut merged = b.clone();
+        merged.merge(&c);
+        assert_eq!(merged, clock(&[("phone", 3), ("laptop", 2)]));
+        assert_eq!(merged.compare(&b), Causality::After);
+        assert_eq!(merged.compare(&c), Causality::After);
+    }
+
+    #[test]
+    fn test_vector_clock_tags() {
+        let clock = clock(&[("phone", 2), ("laptop", 1)]);
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(String::from("id"))];
+        tags.extend(clock.to_tags());
+        assert_eq!(VectorClock::from_tags(tags.iter()), clock);
+    }
+}
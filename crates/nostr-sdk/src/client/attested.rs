@@ -0,0 +1,120 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Attested time ordering
+//!
+//! Order query results by the time attested by OpenTimestamps (NIP03, kind `1040`), for
+//! applications where the `created_at` claimed by the authors isn't trustworthy.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::nips::nip03::OpenTimestamps;
+use nostr::{Event, EventId, Filter, Kind, Timestamp};
+use nostr_database::Order;
+use nostr_relay_pool::{FilterOptions, RelayPool};
+
+type BlockTime = Arc<dyn Fn(u64) -> Option<Timestamp> + Send + Sync>;
+
+/// Order events by attested time
+///
+/// Proofs only commit to a block height: the block time is resolved with the provided function
+/// (i.e. from a local Bitcoin node or a block explorer cache).
+#[derive(Clone)]
+pub struct AttestedOrder {
+    block_time: BlockTime,
+}
+
+impl fmt::Debug for AttestedOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttestedOrder").finish()
+    }
+}
+
+impl AttestedOrder {
+    /// New attested time ordering
+    ///
+    /// `block_time` must return the time of the Bitcoin block at the given height, if known.
+    pub fn new<F>(block_time: F) -> Self
+    where
+        F: Fn(u64) -> Option<Timestamp> + Send + Sync + 'static,
+    {
+        Self {
+            block_time: Arc::new(block_time),
+        }
+    }
+
+    /// Sort the events, newest first, by attested time
+    ///
+    /// The attestations are requested to the relays and then looked up in the database.
+    /// Events without a verified attestation (missing, pending, invalid or unknown block time)
+    /// fall back to `created_at`.
+    pub(crate) async fn sort(&self, pool: &RelayPool, events: &mut [Event], timeout: Duration) {
+        if events.is_empty() {
+            return;
+        }
+
+        let filter: Filter = Filter::new()
+            .kind(Kind::OpenTimestamps)
+            .events(events.iter().map(|e| e.id()));
+
+        // Attestations are stored in the database when received: errors are ignored
+        if let Err(e) = pool
+            .get_events_of(vec![filter.clone()], timeout, FilterOptions::ExitOnEOSE)
+            .await
+        {
+            tracing::warn!("Impossible to get attestations: {e}");
+        }
+
+        let attestations: Vec<Event> = match pool.database().query(vec![filter], Order::Asc).await {
+            Ok(events) => events,
+            Err(e) => {
+                tracing::error!("Impossible to query attestations: {e}");
+                Vec::new()
+            }
+        };
+
+        let times: HashMap<EventId, Timestamp> = self.attested_times(&attestations);
+        events.sort_by(|a, b| {
+            let a_time: Timestamp = times.get(&a.id()).copied().unwrap_or(a.created_at());
+            let b_time: Timestamp = times.get(&b.id()).copied().unwrap_or(b.created_at());
+            b_time
+                .cmp(&a_time)
+                .then_with(|| b.created_at().cmp(&a.created_at()))
+        });
+    }
+
+    /// Earliest attested time of each event
+    fn attested_times(&self, attestations: &[Event]) -> HashMap<EventId, Timestamp> {
+        let mut times: HashMap<EventId, Timestamp> = HashMap::new();
+        for event in attestations.iter() {
+            let ots: OpenTimestamps = match OpenTimestamps::try_from(event) {
+                Ok(ots) => ots,
+                Err(e) => {
+                    tracing::debug!("Invalid attestation {}: {e}", event.id());
+                    continue;
+                }
+            };
+
+            let time: Option<Timestamp> = match ots.block_height() {
+                Ok(Some(height)) => (self.block_time)(height),
+                Ok(None) => None,
+                Err(e) => {
+                    tracing::debug!("Invalid attestation {}: {e}", event.id());
+                    None
+                }
+            };
+
+            if let Some(time) = time {
+                times
+                    .entry(ots.event_id)
+                    .and_modify(|t| *t = (*t).min(time))
+                    .or_insert(time);
+            }
+        }
+        times
+    }
+}
@@ -0,0 +1,146 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Rate-limited backfill of profiles and relay lists
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::{Filter, Kind, PublicKey};
+use nostr_database::{DynNostrDatabase, Order};
+use nostr_relay_pool::runtime::thread;
+use nostr_relay_pool::{FilterOptions, RelayPool};
+use tokio::sync::Mutex;
+
+/// Max number of public keys remembered to deduplicate requests
+const MAX_REQUESTED: usize = 10_000;
+const KINDS: [Kind; 2] = [Kind::Metadata, Kind::RelayList];
+
+#[derive(Debug, Default)]
+struct Queue {
+    pending: VecDeque<PublicKey>,
+    requested: HashSet<PublicKey>,
+}
+
+/// Queue of authors whose profile (kind `0`) and relay list (kind `10002`) must be backfilled
+///
+/// Queued authors are fetched in background, `batch_size` authors per `REQ`,
+/// with at most one `REQ` every `interval` for the whole client.
+#[derive(Debug, Clone)]
+pub(crate) struct MetadataBackfill {
+    queue: Arc<Mutex<Queue>>,
+    running: Arc<AtomicBool>,
+    batch_size: usize,
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl MetadataBackfill {
+    pub fn new(batch_size: usize, interval: Duration, timeout: Duration) -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(Queue::default())),
+            running: Arc::new(AtomicBool::new(false)),
+            batch_size: batch_size.max(1),
+            interval,
+            timeout,
+        }
+    }
+
+    /// Queue the public keys not already requested
+    pub async fn queue<I>(&self, pool: &RelayPool, public_keys: I)
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let mut queue = self.queue.lock().await;
+        for public_key in public_keys.into_iter() {
+            if queue.requested.len() >= MAX_REQUESTED {
+                queue.requested.clear();
+            }
+            if queue.requested.insert(public_key) {
+                queue.pending.push_back(public_key);
+            }
+        }
+        let empty: bool = queue.pending.is_empty();
+        drop(queue);
+
+        if !empty {
+            self.spawn_worker(pool);
+        }
+    }
+
+    fn spawn_worker(&self, pool: &RelayPool) {
+        // Check if already running
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let backfill: Self = self.clone();
+        let pool: RelayPool = pool.clone();
+        let _ = thread::spawn(async move {
+            loop {
+                let batch: Vec<PublicKey> = backfill.next_batch().await;
+
+                if batch.is_empty() {
+                    backfill.running.store(false, Ordering::SeqCst);
+
+                    // Keys queued while stopping: restart, unless another worker already did
+                    if backfill.queue.lock().await.pending.is_empty()
+                        || backfill.running.swap(true, Ordering::SeqCst)
+                    {
+                        break;
+                    }
+                    continue;
+                }
+
+                let database: Arc<DynNostrDatabase> = pool.database();
+                let missing: Vec<PublicKey> = missing(&database, batch).await;
+                if missing.is_empty() {
+                    continue;
+                }
+
+                tracing::debug!("Backfilling metadata of {} authors", missing.len());
+
+                // Received events are saved into the database by the pool
+                let filter: Filter = Filter::new().authors(missing).kinds(KINDS);
+                if let Err(e) = pool
+                    .get_events_of(vec![filter], backfill.timeout, FilterOptions::ExitOnEOSE)
+                    .await
+                {
+                    tracing::warn!("Impossible to backfill metadata: {e}");
+                }
+
+                // Global rate limit
+                thread::sleep(backfill.interval).await;
+            }
+        });
+    }
+
+    async fn next_batch(&self) -> Vec<PublicKey> {
+        let mut queue = self.queue.lock().await;
+        let len: usize = queue.pending.len().min(self.batch_size);
+        queue.pending.drain(..len).collect()
+    }
+}
+
+/// Keep only the public keys without profile or relay list in the database
+async fn missing(database: &Arc<DynNostrDatabase>, public_keys: Vec<PublicKey>) -> Vec<PublicKey> {
+    let filter: Filter = Filter::new()
+        .authors(public_keys.iter().copied())
+        .kinds(KINDS);
+    let mut stored: HashSet<(PublicKey, Kind)> = HashSet::new();
+    for event in database
+        .query(vec![filter], Order::Desc)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+    {
+        stored.insert((event.author(), event.kind()));
+    }
+    public_keys
+        .into_iter()
+        .filter(|p| KINDS.iter().any(|k| !stored.contains(&(*p, *k))))
+        .collect()
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Periodic checkpointing of the adaptive state
+
+use std::time::Duration;
+
+use nostr::types::time::Instant;
+use nostr_relay_pool::runtime::{thread, time};
+use nostr_relay_pool::RelayPoolNotification;
+use tokio::sync::broadcast;
+
+use super::{prefetch, Client, Error, TaskGuard};
+
+impl Client {
+    /// Save the adaptive state (relay stats, subscription cursors and prefetch dedup caches) into the database
+    ///
+    /// Called periodically if [`Options::checkpoint_interval`](super::Options::checkpoint_interval) is set.
+    pub async fn checkpoint(&self) -> Result<(), Error> {
+        self.pool.save_checkpoint().await?;
+        if let Some(prefetcher) = &self.prefetcher {
+            self.database()
+                .save_checkpoint(prefetch::CHECKPOINT_KEY, prefetcher.checkpoint().await)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Restore the adaptive state saved by [`Client::checkpoint`]
+    ///
+    /// Call it at startup, after adding the relays and before subscribing.
+    /// Corrupted checkpoints (i.e. after a crash during a write) are discarded.
+    ///
+    /// Return `true` if the relay pool state has been restored.
+    pub async fn restore_checkpoint(&self) -> Result<bool, Error> {
+        let restored: bool = self.pool.restore_checkpoint().await?;
+        if let Some(prefetcher) = &self.prefetcher {
+            if let Some(bytes) = self
+                .database()
+                .load_checkpoint(prefetch::CHECKPOINT_KEY)
+                .await?
+            {
+                if !prefetcher.restore(&bytes).await {
+                    tracing::warn!("Discarding corrupted prefetch checkpoint");
+                }
+            }
+        }
+        Ok(restored)
+    }
+
+    pub(super) fn spawn_checkpointing(&self, interval: Duration) {
+        // Check if already running
+        let guard: TaskGuard = match TaskGuard::acquire(&self.checkpointing) {
+            Some(guard) => guard,
+            None => return,
+        };
+
+        let client: Client = self.clone();
+        let _ = thread::spawn(async move {
+            let _guard: TaskGuard = guard;
+            let mut notifications = client.notifications();
+            let mut last_checkpoint: Instant = Instant::now();
+            loop {
+                let shutdown: bool = matches!(
+                    time::timeout(Some(interval), notifications.recv()).await,
+                    Some(Ok(RelayPoolNotification::Shutdown))
+                        | Some(Err(broadcast::error::RecvError::Closed))
+                );
+
+                if shutdown || last_checkpoint.elapsed() >= interval {
+                    if let Err(e) = client.checkpoint().await {
+                        tracing::error!("Impossible to save checkpoint: {e}");
+                    }
+                    last_checkpoint = Instant::now();
+                }
+
+                if shutdown {
+                    break;
+                }
+            }
+        });
+    }
+}
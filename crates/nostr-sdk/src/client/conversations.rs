@@ -0,0 +1,354 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Conversations
+//!
+//! Group the direct messages (NIP04 and NIP17) stored in the database by conversation.
+//! The conversation is identified by the set of participants (see [`ConversationId`]), so group messages (NIP17)
+//! are kept apart from the one-to-one messages with each participant.
+
+use std::collections::HashMap;
+#[cfg(feature = "nip59")]
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_database::Order;
+use nostr_relay_pool::RelayPoolNotification;
+use nostr_signer::NostrSigner;
+use tokio::sync::broadcast::Receiver;
+
+#[cfg(feature = "nip59")]
+use super::dms::unwrap_gift_wrap;
+use super::{Client, Error};
+
+/// Direct message protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirectMessageProtocol {
+    /// Encrypted direct message (kind `4`)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
+    Nip04,
+    /// Gift wrapped sealed direct message (kind `14` in kind `1059`)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    Nip17,
+}
+
+/// Decrypted direct message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectMessage {
+    /// Event ID (the gift wrap ID for NIP17)
+    pub id: EventId,
+    /// Conversation ID
+    pub conversation_id: ConversationId,
+    /// Counterparty: the sender if incoming, the first receiver if outgoing
+    pub counterparty: PublicKey,
+    /// Participants, excluding the user
+    pub participants: Vec<PublicKey>,
+    /// `true` if sent by the user
+    pub outgoing: bool,
+    /// Decrypted content
+    pub content: String,
+    /// Creation time (the rumor timestamp for NIP17)
+    pub created_at: Timestamp,
+    /// Protocol
+    pub protocol: DirectMessageProtocol,
+}
+
+/// Conversation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversation {
+    /// Conversation ID
+    pub id: ConversationId,
+    /// Participants, excluding the user
+    pub participants: Vec<PublicKey>,
+    /// Most recent message
+    pub last_message: DirectMessage,
+    /// Number of messages
+    pub messages: usize,
+}
+
+/// Stream of the new messages of a conversation
+#[derive(Debug)]
+pub struct ConversationStream {
+    signer: NostrSigner,
+    public_key: PublicKey,
+    conversation_id: ConversationId,
+    since: Timestamp,
+    id: SubscriptionId,
+    notifications: Receiver<RelayPoolNotification>,
+}
+
+impl ConversationStream {
+    /// Subscription ID
+    pub fn id(&self) -> &SubscriptionId {
+        &self.id
+    }
+
+    /// Wait for the next message
+    ///
+    /// Return `None` when the client is shut down.
+    pub async fn recv(&mut self) -> Option<DirectMessage> {
+        while let Ok(notification) = self.notifications.recv().await {
+            match notification {
+                RelayPoolNotification::Event {
+                    subscription_id,
+                    event,
+                    ..
+                } if subscription_id == self.id => {
+                    if let Some(message) = decrypt(&self.signer, self.public_key, &event).await {
+                        if message.conversation_id == self.conversation_id
+                            && message.created_at >= self.since
+                        {
+                            return Some(message);
+                        }
+                    }
+                }
+                RelayPoolNotification::Shutdown => break,
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+/// Direct messages grouped by conversation
+#[derive(Debug, Clone)]
+pub struct Conversations {
+    client: Client,
+}
+
+impl Conversations {
+    /// New conversations
+    pub fn new(client: &Client) -> Self {
+        Self {
+            client: client.clone(),
+        }
+    }
+
+    /// List the conversations stored in the database, sorted by last activity (most recent first)
+    pub async fn list(&self) -> Result<Vec<Conversation>, Error> {
+        let mut conversations: HashMap<ConversationId, Conversation> = HashMap::new();
+        for message in self.load(None).await? {
+            match conversations.get_mut(&message.conversation_id) {
+                Some(conversation) => {
+                    conversation.messages += 1;
+                    if message.created_at > conversation.last_message.created_at {
+                        conversation.last_message = message;
+                    }
+                }
+                None => {
+                    conversations.insert(
+                        message.conversation_id,
+                        Conversation {
+                            id: message.conversation_id,
+                            participants: message.participants.clone(),
+                            last_message: message,
+                            messages: 1,
+                        },
+                    );
+                }
+            }
+        }
+
+        let mut conversations: Vec<Conversation> = conversations.into_values().collect();
+        conversations.sort_by(|a, b| b.last_message.created_at.cmp(&a.last_message.created_at));
+        Ok(conversations)
+    }
+
+    /// Get a page of the messages exchanged with a counterparty (group messages excluded), sorted from the most recent
+    ///
+    /// Only the messages created before `until` (excluded) are returned: pass the timestamp
+    /// of the oldest message of the previous page to get the next one.
+    pub async fn messages(
+        &self,
+        counterparty: PublicKey,
+        until: Option<Timestamp>,
+        limit: usize,
+    ) -> Result<Vec<DirectMessage>, Error> {
+        let messages: Vec<DirectMessage> = self.load(Some(counterparty)).await?;
+        Ok(page(messages, until, limit))
+    }
+
+    /// Get a page of the messages of a conversation (i.e. a group), sorted from the most recent
+    ///
+    /// See [`Conversations::messages`] for pagination.
+    pub async fn conversation_messages(
+        &self,
+        id: ConversationId,
+        until: Option<Timestamp>,
+        limit: usize,
+    ) -> Result<Vec<DirectMessage>, Error> {
+        let messages: Vec<DirectMessage> = self
+            .load(None)
+            .await?
+            .into_iter()
+            .filter(|m| m.conversation_id == id)
+            .collect();
+        Ok(page(messages, until, limit))
+    }
+
+    /// Subscribe to the new messages exchanged with a counterparty
+    pub async fn stream(&self, counterparty: PublicKey) -> Result<ConversationStream, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let now: Timestamp = Timestamp::now();
+
+        let notifications = self.client.notifications();
+        let id: SubscriptionId = self
+            .client
+            .subscribe(filters(public_key, Some(counterparty), Some(now)), None)
+            .await;
+
+        Ok(ConversationStream {
+            signer,
+            public_key,
+            conversation_id: ConversationId::new([public_key, counterparty]),
+            since: now,
+            id,
+            notifications,
+        })
+    }
+
+    /// Load and decrypt the messages stored in the database
+    async fn load(&self, counterparty: Option<PublicKey>) -> Result<Vec<DirectMessage>, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(filters(public_key, counterparty, None), Order::Desc)
+            .await?;
+
+        let mut messages: Vec<DirectMessage> = Vec::with_capacity(events.len());
+        for event in events.iter() {
+            if let Some(message) = decrypt(&signer, public_key, event).await {
+                if counterparty.map_or(true, |c| {
+                    message.conversation_id == ConversationId::new([public_key, c])
+                }) {
+                    messages.push(message);
+                }
+            }
+        }
+        Ok(messages)
+    }
+}
+
+fn page(
+    mut messages: Vec<DirectMessage>,
+    until: Option<Timestamp>,
+    limit: usize,
+) -> Vec<DirectMessage> {
+    messages.retain(|m| until.map_or(true, |until| m.created_at < until));
+    messages.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    messages.truncate(limit);
+    messages
+}
+
+/// Filters of the direct messages exchanged by the user
+#[allow(unused_mut, unused_variables)]
+fn filters(
+    public_key: PublicKey,
+    counterparty: Option<PublicKey>,
+    since: Option<Timestamp>,
+) -> Vec<Filter> {
+    let mut filters: Vec<Filter> = Vec::new();
+
+    #[cfg(feature = "nip04")]
+    {
+        let mut incoming: Filter = Filter::new()
+            .kind(Kind::EncryptedDirectMessage)
+            .pubkey(public_key);
+        let mut outgoing: Filter = Filter::new()
+            .kind(Kind::EncryptedDirectMessage)
+            .author(public_key);
+        if let Some(counterparty) = counterparty {
+            incoming = incoming.author(counterparty);
+            outgoing = outgoing.pubkey(counterparty);
+        }
+        if let Some(since) = since {
+            incoming = incoming.since(since);
+            outgoing = outgoing.since(since);
+        }
+        filters.push(incoming);
+        filters.push(outgoing);
+    }
+
+    #[cfg(feature = "nip59")]
+    {
+        // The counterparty is hidden in the gift wrap and its timestamp is tweaked up to 2 days in the past
+        let mut gift_wraps: Filter = Filter::new().kind(Kind::GiftWrap).pubkey(public_key);
+        if let Some(since) = since {
+            gift_wraps = gift_wraps.since(since - Duration::from_secs(2 * 24 * 60 * 60));
+        }
+        filters.push(gift_wraps);
+    }
+
+    filters
+}
+
+/// Decrypt a direct message
+#[allow(unused_variables)]
+async fn decrypt(
+    signer: &NostrSigner,
+    public_key: PublicKey,
+    event: &Event,
+) -> Option<DirectMessage> {
+    match event.kind() {
+        #[cfg(feature = "nip04")]
+        Kind::EncryptedDirectMessage => {
+            let receiver: PublicKey = *event.public_keys().next()?;
+            let (counterparty, outgoing) = if event.author() == public_key {
+                (receiver, true)
+            } else if receiver == public_key {
+                (event.author(), false)
+            } else {
+                return None;
+            };
+            let content: String = signer
+                .nip04_decrypt(counterparty, event.content())
+                .await
+                .ok()?;
+            Some(DirectMessage {
+                id: event.id(),
+                conversation_id: ConversationId::new([public_key, counterparty]),
+                counterparty,
+                participants: vec![counterparty],
+                outgoing,
+                content,
+                created_at: event.created_at(),
+                protocol: DirectMessageProtocol::Nip04,
+            })
+        }
+        #[cfg(feature = "nip59")]
+        Kind::GiftWrap => {
+            let UnwrappedGift { sender, rumor } = unwrap_gift_wrap(signer, event).await?;
+            if rumor.kind != Kind::SealedDirect {
+                return None;
+            }
+            let mut participants: Vec<PublicKey> = Vec::new();
+            for p in std::iter::once(&sender).chain(rumor.tags.iter().filter_map(|t| match t {
+                Tag::PublicKey { public_key, .. } => Some(public_key),
+                _ => None,
+            })) {
+                if p != &public_key && !participants.contains(p) {
+                    participants.push(*p);
+                }
+            }
+            let outgoing: bool = sender == public_key;
+            let counterparty: PublicKey = *participants.first()?;
+            Some(DirectMessage {
+                id: event.id(),
+                conversation_id: ConversationId::from_rumor(&rumor),
+                counterparty,
+                participants,
+                outgoing,
+                content: rumor.content,
+                created_at: rumor.created_at,
+                protocol: DirectMessageProtocol::Nip17,
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Deep links
+//!
+//! Resolve `nostr:` URIs (NIP21) to the action to perform, fetching the referenced profile or event
+//! from the local database or, if missing, from the relay hints embedded in the URI.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_database::Order;
+
+use super::{parse_relay_hints, Client, Error};
+
+type ProfileHandler = Arc<dyn Fn(PublicKey, Option<Metadata>) + Send + Sync>;
+type EventHandler = Arc<dyn Fn(Event) + Send + Sync>;
+type AddressHandler = Arc<dyn Fn(Coordinate, Event) + Send + Sync>;
+
+/// Deep link action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeepLinkAction {
+    /// Open a profile (`npub`, `nprofile`)
+    OpenProfile {
+        /// Public key
+        public_key: PublicKey,
+        /// Profile metadata, if found
+        metadata: Option<Metadata>,
+    },
+    /// Open an event (`note`, `nevent`)
+    OpenEvent(Event),
+    /// Open a replaceable or parameterized replaceable event (`naddr`)
+    OpenAddress {
+        /// Coordinate
+        coordinate: Coordinate,
+        /// Latest event of the coordinate
+        event: Event,
+    },
+}
+
+/// Deep link dispatcher
+///
+/// Register a handler for each action, then pass any `nostr:` URI to [`DeepLinks::dispatch`].
+#[derive(Clone)]
+pub struct DeepLinks {
+    client: Client,
+    timeout: Option<Duration>,
+    profile: Option<ProfileHandler>,
+    event: Option<EventHandler>,
+    address: Option<AddressHandler>,
+}
+
+impl fmt::Debug for DeepLinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeepLinks")
+            .field("timeout", &self.timeout)
+            .field("profile", &self.profile.is_some())
+            .field("event", &self.event.is_some())
+            .field("address", &self.address.is_some())
+            .finish()
+    }
+}
+
+impl DeepLinks {
+    /// New deep link dispatcher without handlers
+    pub fn new(client: &Client) -> Self {
+        Self {
+            client: client.clone(),
+            timeout: None,
+            profile: None,
+            event: None,
+            address: None,
+        }
+    }
+
+    /// Timeout for fetching from relays
+    ///
+    /// If `None`, the default from [`Options`](super::Options) will be used.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the handler for `npub` and `nprofile` URIs
+    pub fn on_profile<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(PublicKey, Option<Metadata>) + Send + Sync + 'static,
+    {
+        self.profile = Some(Arc::new(handler));
+        self
+    }
+
+    /// Set the handler for `note` and `nevent` URIs
+    pub fn on_event<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(Event) + Send + Sync + 'static,
+    {
+        self.event = Some(Arc::new(handler));
+        self
+    }
+
+    /// Set the handler for `naddr` URIs
+    pub fn on_address<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(Coordinate, Event) + Send + Sync + 'static,
+    {
+        self.address = Some(Arc::new(handler));
+        self
+    }
+
+    /// Resolve the URI and call the registered handler
+    ///
+    /// Return `false` if there isn't a handler for the URI type.
+    /// In this case, nothing is fetched.
+    pub async fn dispatch<S>(&self, uri: S) -> Result<bool, Error>
+    where
+        S: AsRef<str>,
+    {
+        let nip21: Nip21 = Nip21::parse(uri)?;
+
+        let handled: bool = match nip21 {
+            Nip21::Pubkey(..) | Nip21::Profile(..) => self.profile.is_some(),
+            Nip21::EventId(..) | Nip21::Event(..) => self.event.is_some(),
+            Nip21::Coordinate(..) => self.address.is_some(),
+        };
+
+        if !handled {
+            return Ok(false);
+        }
+
+        match self.resolve_nip21(nip21).await? {
+            DeepLinkAction::OpenProfile {
+                public_key,
+                metadata,
+            } => {
+                if let Some(handler) = &self.profile {
+                    handler(public_key, metadata);
+                }
+            }
+            DeepLinkAction::OpenEvent(event) => {
+                if let Some(handler) = &self.event {
+                    handler(event);
+                }
+            }
+            DeepLinkAction::OpenAddress { coordinate, event } => {
+                if let Some(handler) = &self.address {
+                    handler(coordinate, event);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Resolve the URI to a [`DeepLinkAction`]
+    pub async fn resolve<S>(&self, uri: S) -> Result<DeepLinkAction, Error>
+    where
+        S: AsRef<str>,
+    {
+        let nip21: Nip21 = Nip21::parse(uri)?;
+        self.resolve_nip21(nip21).await
+    }
+
+    async fn resolve_nip21(&self, nip21: Nip21) -> Result<DeepLinkAction, Error> {
+        match nip21 {
+            Nip21::Pubkey(public_key) => self.profile(public_key, Vec::new()).await,
+            Nip21::Profile(profile) => self.profile(profile.public_key, profile.relays).await,
+            Nip21::EventId(event_id) => {
+                let nip19_event = Nip19Event::new(event_id, Vec::<String>::new());
+                let event: Event = self.client.fetch(nip19_event, self.timeout).await?;
+                Ok(DeepLinkAction::OpenEvent(event))
+            }
+            Nip21::Event(nip19_event) => {
+                let event: Event = self.client.fetch(nip19_event, self.timeout).await?;
+                Ok(DeepLinkAction::OpenEvent(event))
+            }
+            Nip21::Coordinate(coordinate) => self.address(coordinate).await,
+        }
+    }
+
+    async fn profile(
+        &self,
+        public_key: PublicKey,
+        relays: Vec<Url>,
+    ) -> Result<DeepLinkAction, Error> {
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::Metadata)
+            .limit(1);
+        let event: Option<Event> = self.latest(filter, relays).await?;
+
+        // Invalid metadata: open the profile anyway
+        let metadata: Option<Metadata> = event.and_then(|e| Metadata::from_json(e.content()).ok());
+
+        Ok(DeepLinkAction::OpenProfile {
+            public_key,
+            metadata,
+        })
+    }
+
+    async fn address(&self, coordinate: Coordinate) -> Result<DeepLinkAction, Error> {
+        let filter: Filter = Filter::from(&coordinate).limit(1);
+        let relays: Vec<Url> = parse_relay_hints(&coordinate.relays);
+        match self.latest(filter, relays).await? {
+            Some(event) => Ok(DeepLinkAction::OpenAddress { coordinate, event }),
+            None => Err(Error::CoordinateNotFound(Box::new(coordinate))),
+        }
+    }
+
+    /// Get the latest event from the database or, if missing, from the relays
+    async fn latest(&self, filter: Filter, relays: Vec<Url>) -> Result<Option<Event>, Error> {
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![filter.clone()], Order::Desc)
+            .await?;
+        if let Some(event) = events.into_iter().next() {
+            return Ok(Some(event));
+        }
+
+        let events: Vec<Event> = self
+            .client
+            .get_events_from_hints(relays, vec![filter], self.timeout)
+            .await?;
+        Ok(events.into_iter().max_by_key(|e| e.created_at()))
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Delivery records of the sent events
+
+use nostr::prelude::*;
+use nostr_database::{DeliveryRecord, DeliveryStatus, NostrDatabaseExt};
+use nostr_relay_pool::runtime::thread;
+use nostr_relay_pool::RelayPoolNotification;
+use tokio::sync::broadcast;
+
+use super::{Client, Error, TaskGuard};
+
+impl Client {
+    /// Get the delivery status of a sent event: which relays accepted or rejected it, and when
+    ///
+    /// Require [`Options::delivery_records`](super::Options::delivery_records).
+    pub async fn delivery_status(
+        &self,
+        event_id: EventId,
+    ) -> Result<Option<DeliveryStatus>, Error> {
+        Ok(self.database().delivery_status(event_id).await?)
+    }
+
+    pub(super) fn spawn_delivery_recording(&self) {
+        // Check if already running
+        let guard: TaskGuard = match TaskGuard::acquire(&self.recording_deliveries) {
+            Some(guard) => guard,
+            None => return,
+        };
+
+        let client: Client = self.clone();
+        let _ = thread::spawn(async move {
+            let _guard: TaskGuard = guard;
+            let database = client.database();
+            let mut notifications = client.notifications();
+
+            // Records are written sequentially, so concurrent `OK`s for the same event aren't lost
+            loop {
+                let notification = match notifications.recv().await {
+                    Ok(notification) => notification,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "Missed {skipped} notifications: delivery records may be incomplete"
+                        );
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                match notification {
+                    RelayPoolNotification::Message {
+                        relay_url,
+                        message:
+                            RelayMessage::Ok {
+                                event_id,
+                                status,
+                                message,
+                            },
+                    } => {
+                        let record = DeliveryRecord {
+                            accepted: status,
+                            message,
+                            timestamp: Timestamp::now(),
+                        };
+                        if let Err(e) = database.save_delivery(event_id, relay_url, record).await {
+                            tracing::error!("Impossible to save delivery record: {e}");
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event dispatch filtering
+//!
+//! Events not relevant for the application are dropped before reaching the handler of
+//! [`Client::handle_notifications`](super::Client::handle_notifications), so slow consumers
+//! aren't flooded by the traffic of subscriptions shared with other components.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use nostr::{Event, Kind, PublicKey};
+use tokio::sync::RwLock;
+
+type Predicate = dyn Fn(&Event) -> Pin<Box<dyn Future<Output = bool> + Send>> + Send + Sync;
+
+#[derive(Default)]
+struct InnerDispatchFilter {
+    kinds: Option<HashSet<Kind>>,
+    authors: Option<HashSet<PublicKey>>,
+    predicates: Vec<Arc<Predicate>>,
+}
+
+impl fmt::Debug for InnerDispatchFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InnerDispatchFilter")
+            .field("kinds", &self.kinds)
+            .field("authors", &self.authors)
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
+impl InnerDispatchFilter {
+    fn is_allowed(&self, event: &Event) -> bool {
+        self.kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(&event.kind()))
+            && self
+                .authors
+                .as_ref()
+                .map_or(true, |authors| authors.contains(event.author_ref()))
+    }
+}
+
+/// Event dispatch filter
+///
+/// An event is dispatched only if its kind and author are allowed and all the predicates
+/// return `true`. By default, all the events are dispatched.
+#[derive(Debug, Clone, Default)]
+pub struct DispatchFilter {
+    inner: Arc<RwLock<InnerDispatchFilter>>,
+}
+
+impl DispatchFilter {
+    /// Dispatch only events of these kinds
+    ///
+    /// Calling it again extends the whitelist.
+    pub async fn allow_kinds<I>(&self, kinds: I)
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        let mut inner = self.inner.write().await;
+        inner.kinds.get_or_insert_with(HashSet::new).extend(kinds);
+    }
+
+    /// Dispatch only events of these authors
+    ///
+    /// Calling it again extends the whitelist.
+    pub async fn allow_authors<I>(&self, authors: I)
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let mut inner = self.inner.write().await;
+        inner
+            .authors
+            .get_or_insert_with(HashSet::new)
+            .extend(authors);
+    }
+
+    /// Add an async predicate
+    ///
+    /// Predicates run only for the events allowed by the kind and author whitelists.
+    pub async fn add_predicate<F, Fut>(&self, predicate: F)
+    where
+        F: Fn(Event) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        let predicate: Arc<Predicate> = Arc::new(move |event: &Event| {
+            Box::pin(predicate(event.clone())) as Pin<Box<dyn Future<Output = bool> + Send>>
+        });
+        let mut inner = self.inner.write().await;
+        inner.predicates.push(predicate);
+    }
+
+    /// Remove whitelists and predicates: dispatch all the events
+    pub async fn clear(&self) {
+        let mut inner = self.inner.write().await;
+        *inner = InnerDispatchFilter::default();
+    }
+
+    /// Check if the event must be dispatched
+    pub async fn is_dispatched(&self, event: &Event) -> bool {
+        let predicates: Vec<Arc<Predicate>> = {
+            let inner = self.inner.read().await;
+            if !inner.is_allowed(event) {
+                return false;
+            }
+            inner.predicates.clone()
+        };
+
+        // The lock isn't held while the predicates run
+        for predicate in predicates.into_iter() {
+            if !predicate(event).await {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::{EventBuilder, Keys};
+
+    use super::*;
+
+    #[test]
+    fn test_whitelists() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap();
+
+        let mut filter = InnerDispatchFilter::default();
+        assert!(filter.is_allowed(&note));
+
+        filter.kinds = Some(HashSet::from([Kind::Reaction]));
+        assert!(!filter.is_allowed(&note));
+
+        filter.kinds = Some(HashSet::from([Kind::TextNote]));
+        filter.authors = Some(HashSet::from([Keys::generate().public_key()]));
+        assert!(!filter.is_allowed(&note));
+
+        filter.authors = Some(HashSet::from([keys.public_key()]));
+        assert!(filter.is_allowed(&note));
+    }
+}
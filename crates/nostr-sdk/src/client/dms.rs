@@ -0,0 +1,285 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Direct messages read markers
+//!
+//! The read marker of each conversation is stored as a NIP44 encrypted (to self) application-specific data event (kind `30078`).
+//! The `d` tag is the hash of the counterparty public key, so conversations aren't leaked to relays.
+//! Markers only move forward: the most recent one wins when they are merged from other devices.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::hashes::sha256::Hash as Sha256Hash;
+use nostr::hashes::Hash;
+use nostr::prelude::*;
+use nostr::serde_json::{self, json, Value};
+use nostr_database::Order;
+use nostr_signer::NostrSigner;
+use tokio::sync::RwLock;
+
+use super::{Client, Error};
+
+const READ_MARKER_NAMESPACE: &str = "nostr-sdk:dms:read";
+
+/// Direct messages read markers and unread counts
+#[derive(Debug, Clone)]
+pub struct Dms {
+    client: Client,
+    markers: Arc<RwLock<HashMap<PublicKey, Timestamp>>>,
+}
+
+impl Dms {
+    /// New direct messages read markers
+    pub fn new(client: &Client) -> Self {
+        Self {
+            client: client.clone(),
+            markers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Mark the messages of a conversation as read, up to `until` (included)
+    ///
+    /// The marker never moves backward: if a more recent marker is already known, it's published again.
+    pub async fn mark_read(
+        &self,
+        conversation: PublicKey,
+        until: Timestamp,
+    ) -> Result<EventId, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let until: Timestamp = match self.last_read(conversation).await? {
+            Some(last_read) if last_read > until => last_read,
+            _ => until,
+        };
+
+        let payload: String = json!({
+            "conversation": conversation.to_hex(),
+            "until": until.as_u64(),
+        })
+        .to_string();
+        let content: String = signer.nip44_encrypt(public_key, payload).await?;
+        let builder = EventBuilder::new(
+            Kind::ApplicationSpecificData,
+            content,
+            [Tag::Identifier(identifier(&conversation))],
+        );
+        let event_id: EventId = self.client.send_event_builder(builder).await?;
+
+        let mut markers = self.markers.write().await;
+        markers.insert(conversation, until);
+
+        Ok(event_id)
+    }
+
+    /// Get the read marker of a conversation
+    ///
+    /// Return `None` if the conversation has never been marked as read.
+    pub async fn last_read(&self, conversation: PublicKey) -> Result<Option<Timestamp>, Error> {
+        if let Some(until) = self.markers.read().await.get(&conversation) {
+            return Ok(Some(*until));
+        }
+
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .kind(Kind::ApplicationSpecificData)
+            .author(public_key)
+            .identifier(identifier(&conversation));
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?;
+        for event in events.iter() {
+            self.merge(&signer, public_key, event).await;
+        }
+
+        Ok(self.markers.read().await.get(&conversation).copied())
+    }
+
+    /// Fetch the read markers stored on relays (i.e. set by other devices)
+    pub async fn pull(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .kind(Kind::ApplicationSpecificData)
+            .author(public_key);
+        let events: Vec<Event> = self.client.get_events_of(vec![filter], timeout).await?;
+        for event in events.iter() {
+            self.merge(&signer, public_key, event).await;
+        }
+        Ok(())
+    }
+
+    /// Count the unread messages of a conversation, using the events stored in the database
+    pub async fn unread_count(&self, conversation: PublicKey) -> Result<usize, Error> {
+        let last_read: Option<Timestamp> = self.last_read(conversation).await?;
+        let messages = self.incoming(Some(conversation), last_read).await?;
+        Ok(messages.len())
+    }
+
+    /// Count the unread messages of all the conversations, using the events stored in the database
+    ///
+    /// Conversations without unread messages are not included.
+    pub async fn unread_counts(&self) -> Result<HashMap<PublicKey, usize>, Error> {
+        let mut counts: HashMap<PublicKey, usize> = HashMap::new();
+        for (sender, created_at) in self.incoming(None, None).await? {
+            match self.last_read(sender).await? {
+                Some(last_read) if created_at <= last_read => (),
+                _ => *counts.entry(sender).or_default() += 1,
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Get sender and timestamp of the received messages (NIP04 and NIP17) stored in the database
+    async fn incoming(
+        &self,
+        conversation: Option<PublicKey>,
+        since: Option<Timestamp>,
+    ) -> Result<Vec<(PublicKey, Timestamp)>, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let is_unread = |sender: &PublicKey, created_at: Timestamp| {
+            sender != &public_key
+                && conversation.map_or(true, |c| &c == sender)
+                && since.map_or(true, |since| created_at > since)
+        };
+
+        let mut messages: Vec<(PublicKey, Timestamp)> = Vec::new();
+
+        // NIP04
+        let mut filter: Filter = Filter::new()
+            .kind(Kind::EncryptedDirectMessage)
+            .pubkey(public_key);
+        if let Some(conversation) = conversation {
+            filter = filter.author(conversation);
+        }
+        if let Some(since) = since {
+            filter = filter.since(since + 1_u64);
+        }
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?;
+        messages.extend(
+            events
+                .into_iter()
+                .filter(|e| is_unread(&e.author(), e.created_at()))
+                .map(|e| (e.author(), e.created_at())),
+        );
+
+        // NIP17
+        #[cfg(feature = "nip59")]
+        {
+            // The gift wrap timestamp is tweaked up to 2 days in the past
+            let mut filter: Filter = Filter::new().kind(Kind::GiftWrap).pubkey(public_key);
+            if let Some(since) = since {
+                filter = filter.since(since - Duration::from_secs(2 * 24 * 60 * 60));
+            }
+            let events: Vec<Event> = self
+                .client
+                .database()
+                .query(vec![filter], Order::Desc)
+                .await?;
+            for event in events.iter() {
+                if let Some(UnwrappedGift { sender, rumor }) =
+                    unwrap_gift_wrap(&signer, event).await
+                {
+                    if rumor.kind == Kind::SealedDirect && is_unread(&sender, rumor.created_at) {
+                        messages.push((sender, rumor.created_at));
+                    }
+                }
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Merge a read marker
+    async fn merge(&self, signer: &NostrSigner, public_key: PublicKey, event: &Event) {
+        let payload: String = match signer.nip44_decrypt(public_key, event.content()).await {
+            Ok(payload) => payload,
+            // Not a read marker
+            Err(_) => return,
+        };
+        let payload: Value = match serde_json::from_str(&payload) {
+            Ok(payload) => payload,
+            Err(_) => return,
+        };
+
+        let conversation: Option<PublicKey> = payload
+            .get("conversation")
+            .and_then(Value::as_str)
+            .and_then(|c| PublicKey::from_hex(c).ok());
+        let until: Option<u64> = payload.get("until").and_then(Value::as_u64);
+        let (conversation, until) = match (conversation, until) {
+            (Some(conversation), Some(until)) => (conversation, Timestamp::from(until)),
+            _ => return,
+        };
+
+        // Check that the identifier matches the conversation
+        if event.identifier() != Some(identifier(&conversation).as_str()) {
+            return;
+        }
+
+        let mut markers = self.markers.write().await;
+        let current = markers.entry(conversation).or_insert(until);
+        if until > *current {
+            *current = until;
+        }
+    }
+}
+
+fn identifier(conversation: &PublicKey) -> String {
+    let data: String = format!("{READ_MARKER_NAMESPACE}:{conversation}");
+    Sha256Hash::hash(data.as_bytes()).to_string()
+}
+
+/// Decrypt a gift wrap with the signer, checking that the rumor is authored by the seal signer
+#[cfg(feature = "nip59")]
+pub(crate) async fn unwrap_gift_wrap(
+    signer: &NostrSigner,
+    gift_wrap: &Event,
+) -> Option<UnwrappedGift> {
+    let seal: String = signer
+        .nip44_decrypt(gift_wrap.author(), gift_wrap.content())
+        .await
+        .ok()?;
+    let seal: Event = Event::from_json(seal).ok()?;
+    seal.verify().ok()?;
+
+    let rumor: String = signer
+        .nip44_decrypt(seal.author(), seal.content())
+        .await
+        .ok()?;
+    let rumor: UnsignedEvent = UnsignedEvent::from_json(rumor).ok()?;
+    if rumor.pubkey != seal.author() {
+        return None;
+    }
+
+    Some(UnwrappedGift {
+        sender: seal.author(),
+        rumor,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_marker_identifier() {
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+
+        assert_eq!(identifier(&alice), identifier(&alice));
+        assert_ne!(identifier(&alice), identifier(&bob));
+        assert!(!identifier(&alice).contains(&alice.to_hex()));
+    }
+}
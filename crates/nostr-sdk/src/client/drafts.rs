@@ -0,0 +1,234 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Event drafts
+//!
+//! Drafts are stored in the database as NIP44 encrypted (to self) draft events (kind `31234`).
+//! They are sent to relays only if sync is enabled, to share them across devices.
+
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_database::Order;
+use nostr_relay_pool::runtime::thread;
+use nostr_signer::NostrSigner;
+use tokio::sync::Mutex;
+
+use super::{published_id, Client, Error};
+
+/// Store, edit and publish event drafts
+#[derive(Debug, Clone)]
+pub struct Drafts {
+    client: Client,
+    sync: bool,
+}
+
+impl Drafts {
+    /// New drafts store (local only)
+    pub fn new(client: &Client) -> Self {
+        Self {
+            client: client.clone(),
+            sync: false,
+        }
+    }
+
+    /// Also send drafts and deletions to relays (default: false)
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Save a draft
+    ///
+    /// Replace the previous version with the same identifier.
+    pub async fn save(&self, draft: &Draft) -> Result<EventId, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let payload: String = signer.nip44_encrypt(public_key, draft.payload()).await?;
+        self.store(public_key, EventBuilder::event_draft(draft, payload))
+            .await
+    }
+
+    /// Get a draft
+    pub async fn get(&self, identifier: &str) -> Result<Option<Draft>, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let filter: Filter = self.filter(public_key).identifier(identifier);
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![filter], Order::Desc)
+            .await?;
+        match events.first() {
+            Some(event) if !event.content().is_empty() => {
+                Ok(Some(decrypt(&signer, public_key, event).await?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// List drafts, most recently saved first
+    ///
+    /// Drafts that can't be decrypted are skipped.
+    pub async fn list(&self) -> Result<Vec<Draft>, Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let events: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![self.filter(public_key)], Order::Desc)
+            .await?;
+
+        let mut drafts: Vec<Draft> = Vec::with_capacity(events.len());
+        for event in events.into_iter().filter(|e| !e.content().is_empty()) {
+            match decrypt(&signer, public_key, &event).await {
+                Ok(draft) => drafts.push(draft),
+                Err(e) => tracing::warn!("Impossible to decrypt draft {}: {e}", event.id()),
+            }
+        }
+        Ok(drafts)
+    }
+
+    /// Delete a draft
+    ///
+    /// If sync is enabled, a blank draft is sent to relays to replace it.
+    pub async fn delete(&self, identifier: &str) -> Result<(), Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        let database = self.client.database();
+
+        let filter: Filter = self.filter(public_key).identifier(identifier);
+        let events: Vec<Event> = database.query(vec![filter.clone()], Order::Desc).await?;
+        database.delete(filter).await?;
+
+        if self.sync {
+            if let Some(event) = events.first() {
+                let builder = EventBuilder::new(Kind::Draft, "", event.tags().to_vec());
+                let event: Event = self.client.sign_event_builder(builder).await?;
+                published_id(self.client.send_event(event).await?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Publish a draft and delete it
+    pub async fn publish(&self, identifier: &str) -> Result<EventId, Error> {
+        let draft: Draft = self
+            .get(identifier)
+            .await?
+            .ok_or_else(|| Error::DraftNotFound(identifier.to_string()))?;
+        let event_id: EventId = self
+            .client
+            .send_event_builder(draft.to_event_builder())
+            .await?;
+        self.delete(identifier).await?;
+        Ok(event_id)
+    }
+
+    /// Fetch the drafts stored on relays into the database
+    pub async fn pull(&self, timeout: Option<Duration>) -> Result<(), Error> {
+        let signer: NostrSigner = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+        self.client
+            .get_events_of(vec![self.filter(public_key)], timeout)
+            .await?;
+        Ok(())
+    }
+
+    /// Autosave hook for editors
+    ///
+    /// The last draft passed to [`DraftAutosave::update`] is saved every `interval`,
+    /// until the [`DraftAutosave`] is dropped.
+    pub fn autosave(&self, interval: Duration) -> DraftAutosave {
+        let pending: Arc<Mutex<Option<Draft>>> = Arc::new(Mutex::new(None));
+
+        let drafts: Self = self.clone();
+        let weak: Weak<Mutex<Option<Draft>>> = Arc::downgrade(&pending);
+        let _ = thread::spawn(async move {
+            loop {
+                thread::sleep(interval).await;
+
+                // Stop when the hook is dropped
+                let pending = match weak.upgrade() {
+                    Some(pending) => pending,
+                    None => break,
+                };
+
+                let draft: Option<Draft> = pending.lock().await.take();
+                if let Some(draft) = draft {
+                    if let Err(e) = drafts.save(&draft).await {
+                        tracing::warn!("Impossible to autosave draft '{}': {e}", draft.identifier);
+                    }
+                }
+            }
+        });
+
+        DraftAutosave {
+            drafts: self.clone(),
+            pending,
+        }
+    }
+
+    fn filter(&self, public_key: PublicKey) -> Filter {
+        Filter::new().kind(Kind::Draft).author(public_key)
+    }
+
+    async fn store(&self, public_key: PublicKey, builder: EventBuilder) -> Result<EventId, Error> {
+        let event: Event = self.client.sign_event_builder(builder).await?;
+        let event_id: EventId = event.id();
+
+        // Two saves within the same second have the same `created_at`:
+        // remove the previous version instead of relying on replaceable event ordering
+        let database = self.client.database();
+        if let Some(identifier) = event.identifier() {
+            database
+                .delete(self.filter(public_key).identifier(identifier))
+                .await?;
+        }
+        database.save_event(&event).await?;
+
+        if self.sync {
+            published_id(self.client.send_event(event).await?)?;
+        }
+
+        Ok(event_id)
+    }
+}
+
+/// Autosave hook (see [`Drafts::autosave`])
+#[derive(Debug)]
+pub struct DraftAutosave {
+    drafts: Drafts,
+    pending: Arc<Mutex<Option<Draft>>>,
+}
+
+impl DraftAutosave {
+    /// Update the draft to save at the next tick
+    pub async fn update(&self, draft: Draft) {
+        *self.pending.lock().await = Some(draft);
+    }
+
+    /// Save the pending draft now
+    ///
+    /// Return `None` if there wasn't any pending draft.
+    pub async fn flush(&self) -> Result<Option<EventId>, Error> {
+        let draft: Option<Draft> = self.pending.lock().await.take();
+        match draft {
+            Some(draft) => Ok(Some(self.drafts.save(&draft).await?)),
+            None => Ok(None),
+        }
+    }
+}
+
+async fn decrypt(
+    signer: &NostrSigner,
+    public_key: PublicKey,
+    event: &Event,
+) -> Result<Draft, Error> {
+    let identifier: &str = Draft::identifier_of(event)?;
+    let payload: String = signer.nip44_decrypt(public_key, event.content()).await?;
+    Ok(Draft::from_payload(identifier, payload)?)
+}
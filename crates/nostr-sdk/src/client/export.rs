@@ -0,0 +1,302 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Export of events to strfry-compatible JSONL
+//!
+//! Events are exported newest first, one per line, in pages of 500 events.
+//! After each page, a checkpoint (`<path>.checkpoint`) is written next to the export,
+//! so that an interrupted export is resumed from the last completed page.
+//! The checkpoint is removed when the export completes.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use nostr::prelude::*;
+use nostr::serde_json::{self, json, Value};
+
+use super::{Client, Error};
+
+/// Number of events requested per page
+const PAGE_SIZE: usize = 500;
+const CHECKPOINT_VERSION: u64 = 1;
+
+#[derive(Debug, Default)]
+struct Checkpoint {
+    /// Size of the export when the checkpoint was written
+    offset: u64,
+    /// Number of exported events
+    exported: usize,
+    /// `created_at` of the oldest exported events
+    until: Option<Timestamp>,
+    /// IDs of the exported events created at `until`
+    ids: HashSet<EventId>,
+}
+
+impl Checkpoint {
+    fn load(path: &Path) -> Option<Self> {
+        let bytes: Vec<u8> = fs::read(path).ok()?;
+        let value: Value = serde_json::from_slice(&bytes).ok()?;
+        if value.get("version")?.as_u64()? != CHECKPOINT_VERSION {
+            return None;
+        }
+        Some(Self {
+            offset: value.get("offset")?.as_u64()?,
+            exported: value.get("exported")?.as_u64()? as usize,
+            until: value
+                .get("until")
+                .and_then(|v| v.as_u64())
+                .map(Timestamp::from),
+            ids: value
+                .get("ids")?
+                .as_array()?
+                .iter()
+                .filter_map(|v| EventId::from_hex(v.as_str()?).ok())
+                .collect(),
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<(), Error> {
+        let value: Value = json!({
+            "version": CHECKPOINT_VERSION,
+            "offset": self.offset,
+            "exported": self.exported,
+            "until": self.until.map(|t| t.as_u64()),
+            "ids": self.ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>(),
+        });
+
+        // Write to a temp file and rename, to never leave a truncated checkpoint
+        let tmp: PathBuf = path.with_extension("checkpoint.tmp");
+        fs::write(&tmp, serde_json::to_vec(&value)?)?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+}
+
+impl Client {
+    /// Export the events matching the filter to a strfry-compatible JSONL file (one event per line)
+    ///
+    /// Events are both queried from the **local database** and backfilled from **relays**,
+    /// newest first. The `limit` of the filter, if set, is the max number of exported events.
+    ///
+    /// If a previous export to the same `path` was interrupted, it's resumed from the last checkpoint.
+    /// Otherwise, the file must not exist: [`Error::FileAlreadyExists`] is returned instead of overwriting it.
+    ///
+    /// Return the total number of exported events.
+    pub async fn export_to_file<P>(&self, filter: Filter, path: P) -> Result<usize, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let checkpoint_path: PathBuf = checkpoint_path(&path);
+
+        let (mut file, mut checkpoint) = {
+            let path: PathBuf = path.clone();
+            let checkpoint_path: PathBuf = checkpoint_path.clone();
+            let until: Option<Timestamp> = filter.until;
+            blocking(move || open(&path, &checkpoint_path, until)).await?
+        };
+
+        let max: Option<usize> = filter.limit;
+
+        loop {
+            let remaining: usize = match max {
+                Some(max) if checkpoint.exported >= max => break,
+                Some(max) => (max - checkpoint.exported).min(PAGE_SIZE),
+                None => PAGE_SIZE,
+            };
+
+            let mut page: Filter = filter.clone().limit(remaining + checkpoint.ids.len());
+            if let Some(until) = checkpoint.until {
+                page = page.until(until);
+            }
+
+            let mut events: Vec<Event> = self.get_events_of(vec![page], None).await?;
+            if events.is_empty() {
+                break;
+            }
+
+            events.sort_by_key(|e| Reverse(e.created_at()));
+
+            let mut seen: HashSet<EventId> = HashSet::new();
+            let mut lines: Vec<u8> = Vec::new();
+            let mut written: usize = 0;
+            for event in events.iter() {
+                if written >= remaining {
+                    break;
+                }
+
+                if checkpoint.ids.contains(&event.id()) || !seen.insert(event.id()) {
+                    continue;
+                }
+
+                lines.extend_from_slice(event.as_json().as_bytes());
+                lines.push(b'\n');
+
+                if checkpoint.until != Some(event.created_at()) {
+                    checkpoint.until = Some(event.created_at());
+                    checkpoint.ids.clear();
+                }
+                checkpoint.ids.insert(event.id());
+                written += 1;
+            }
+
+            if written == 0 {
+                // Only already exported events: move to the previous second
+                match checkpoint.until {
+                    Some(until) if until.as_u64() > 0 => {
+                        checkpoint.until = Some(until - 1_u64);
+                        checkpoint.ids.clear();
+                    }
+                    _ => break,
+                }
+            }
+
+            checkpoint.exported += written;
+
+            // Write the page and the checkpoint
+            (file, checkpoint) = {
+                let checkpoint_path: PathBuf = checkpoint_path.clone();
+                blocking(move || {
+                    file.write_all(&lines)?;
+                    file.flush()?;
+                    file.sync_data()?;
+                    checkpoint.offset = file.metadata()?.len();
+                    checkpoint.save(&checkpoint_path)?;
+                    Ok((file, checkpoint))
+                })
+                .await?
+            };
+
+            tracing::debug!(
+                "Exported {} events to {} ({} total)",
+                written,
+                path.display(),
+                checkpoint.exported
+            );
+        }
+
+        blocking(move || {
+            if checkpoint_path.exists() {
+                fs::remove_file(&checkpoint_path)?;
+            }
+            Ok(())
+        })
+        .await?;
+
+        Ok(checkpoint.exported)
+    }
+}
+
+/// Open the export, resuming from the checkpoint if any
+fn open(
+    path: &Path,
+    checkpoint_path: &Path,
+    until: Option<Timestamp>,
+) -> Result<(File, Checkpoint), Error> {
+    match Checkpoint::load(checkpoint_path) {
+        Some(checkpoint) if path.exists() => {
+            // Discard the events written after the last checkpoint
+            let file: File = OpenOptions::new().append(true).open(path)?;
+            file.set_len(checkpoint.offset)?;
+            Ok((file, checkpoint))
+        }
+        _ => {
+            // New export: never overwrite an existing file
+            let file: File = OpenOptions::new()
+                .append(true)
+                .create_new(true)
+                .open(path)
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::AlreadyExists => Error::FileAlreadyExists(path.to_path_buf()),
+                    _ => Error::Io(e),
+                })?;
+            let checkpoint = Checkpoint {
+                until,
+                ..Default::default()
+            };
+            Ok((file, checkpoint))
+        }
+    }
+}
+
+/// Run the file operations in the blocking thread pool
+async fn blocking<F, T>(f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Error::Io(io::Error::new(io::ErrorKind::Other, e)))?
+}
+
+fn checkpoint_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".checkpoint");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let dir =
+            std::env::temp_dir().join(format!("nostr-sdk-export-{}", Timestamp::now().as_u64()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = checkpoint_path(&dir.join("events.jsonl"));
+        assert!(path.ends_with("events.jsonl.checkpoint"));
+
+        let mut checkpoint = Checkpoint {
+            offset: 1024,
+            exported: 3,
+            until: Some(Timestamp::from(1_700_000_000)),
+            ids: HashSet::new(),
+        };
+        checkpoint.ids.insert(EventId::all_zeros());
+        checkpoint.save(&path).unwrap();
+
+        let loaded = Checkpoint::load(&path).unwrap();
+        assert_eq!(loaded.offset, checkpoint.offset);
+        assert_eq!(loaded.exported, checkpoint.exported);
+        assert_eq!(loaded.until, checkpoint.until);
+        assert_eq!(loaded.ids, checkpoint.ids);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_open() {
+        let dir = std::env::temp_dir().join(format!(
+            "nostr-sdk-export-open-{}",
+            Timestamp::now().as_u64()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.jsonl");
+        let checkpoint_path = checkpoint_path(&path);
+        fs::write(&path, b"line1\nline2\n").unwrap();
+
+        // No checkpoint: the existing file is not overwritten
+        let res = open(&path, &checkpoint_path, None);
+        assert!(matches!(res, Err(Error::FileAlreadyExists(..))));
+        assert_eq!(fs::read(&path).unwrap(), b"line1\nline2\n");
+
+        // Checkpoint: resume from its offset
+        let checkpoint = Checkpoint {
+            offset: 6,
+            exported: 1,
+            ..Default::default()
+        };
+        checkpoint.save(&checkpoint_path).unwrap();
+        let (_, checkpoint) = open(&path, &checkpoint_path, None).unwrap();
+        assert_eq!(checkpoint.exported, 1);
+        assert_eq!(fs::read(&path).unwrap(), b"line1\n");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
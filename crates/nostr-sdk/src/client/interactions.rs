@@ -0,0 +1,275 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Interaction queue
+//!
+//! Reactions, reposts and zap requests are signed and stored in the database immediately,
+//! so the UI can show them as pending, and queued for publishing. The queue is persisted in the database
+//! and flushed in batches when at least one relay is connected, to survive flaky mobile networks.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr::serde_json::{self, json, Value};
+use nostr_relay_pool::runtime::{thread, time};
+use nostr_relay_pool::{RelayPoolNotification, RelaySendOptions, RelayStatus};
+use tokio::sync::Mutex;
+
+use super::{Client, Error};
+
+/// Database key of the persisted queue
+const CHECKPOINT_KEY: &str = "nostr-sdk/interactions";
+const CHECKPOINT_VERSION: u64 = 1;
+
+/// Queue of interactions to publish
+#[derive(Debug, Clone)]
+pub struct Interactions {
+    client: Client,
+    queue: Arc<Mutex<VecDeque<Event>>>,
+    flushing: Arc<Mutex<()>>,
+    batch_size: usize,
+}
+
+impl Interactions {
+    /// New empty interaction queue
+    ///
+    /// Call [`Interactions::restore`] to load the interactions left unsent in a previous session.
+    pub fn new(client: &Client) -> Self {
+        Self {
+            client: client.clone(),
+            queue: Arc::new(Mutex::new(VecDeque::new())),
+            flushing: Arc::new(Mutex::new(())),
+            batch_size: 20,
+        }
+    }
+
+    /// Max interactions sent for each batch (default: 20)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// React to an [`Event`]
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/25.md>
+    pub async fn react<S>(&self, event: &Event, reaction: S) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        self.enqueue(EventBuilder::reaction(event, reaction)).await
+    }
+
+    /// Repost an [`Event`]
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/18.md>
+    pub async fn repost(
+        &self,
+        event: &Event,
+        relay_url: Option<UncheckedUrl>,
+    ) -> Result<EventId, Error> {
+        self.enqueue(EventBuilder::repost(event, relay_url)).await
+    }
+
+    /// Public zap request
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/57.md>
+    #[cfg(feature = "nip57")]
+    pub async fn zap_request(&self, data: ZapRequestData) -> Result<EventId, Error> {
+        self.enqueue(EventBuilder::public_zap_request(data)).await
+    }
+
+    /// Sign the [`EventBuilder`] and queue the event
+    ///
+    /// The event is saved into the database and a flush is attempted in background.
+    pub async fn enqueue(&self, builder: EventBuilder) -> Result<EventId, Error> {
+        let event: Event = self.client.sign_event_builder(builder).await?;
+        let event_id: EventId = event.id();
+
+        self.client.database().save_event(&event).await?;
+        self.queue.lock().await.push_back(event);
+        self.persist().await?;
+
+        let interactions: Self = self.clone();
+        let _ = thread::spawn(async move {
+            if let Err(e) = interactions.flush().await {
+                tracing::warn!("Impossible to flush interactions: {e}");
+            }
+        });
+
+        Ok(event_id)
+    }
+
+    /// Interactions not published yet, oldest first
+    pub async fn pending(&self) -> Vec<Event> {
+        self.queue.lock().await.iter().cloned().collect()
+    }
+
+    /// Check if the interaction hasn't been published yet
+    pub async fn is_pending(&self, event_id: &EventId) -> bool {
+        self.queue.lock().await.iter().any(|e| &e.id() == event_id)
+    }
+
+    /// Publish the pending interactions, in batches
+    ///
+    /// Nothing is sent if no relay is connected. Batches not published stay in the queue.
+    /// Return the number of published interactions.
+    pub async fn flush(&self) -> Result<usize, Error> {
+        let _guard = self.flushing.lock().await;
+
+        let mut published: usize = 0;
+        loop {
+            if !self.is_online().await {
+                break;
+            }
+
+            let batch: Vec<Event> = self
+                .queue
+                .lock()
+                .await
+                .iter()
+                .take(self.batch_size)
+                .cloned()
+                .collect();
+            if batch.is_empty() {
+                break;
+            }
+
+            let sent: HashSet<EventId> = batch.iter().map(|e| e.id()).collect();
+            let opts: RelaySendOptions = self.client.opts.get_wait_for_send();
+            if let Err(e) = self.client.batch_event(batch, opts).await {
+                // No progress: retry at next flush
+                tracing::warn!("Impossible to publish interactions: {e}");
+                break;
+            }
+
+            published += sent.len();
+            self.queue.lock().await.retain(|e| !sent.contains(&e.id()));
+            self.persist().await?;
+        }
+
+        Ok(published)
+    }
+
+    /// Flush the queue every `interval` and as soon as a relay connects
+    ///
+    /// Stop when all the clones of this [`Interactions`] are dropped.
+    pub fn auto_flush(&self, interval: Duration) {
+        let weak: Weak<Mutex<VecDeque<Event>>> = Arc::downgrade(&self.queue);
+        let client: Client = self.client.clone();
+        let flushing: Arc<Mutex<()>> = self.flushing.clone();
+        let batch_size: usize = self.batch_size;
+        let _ = thread::spawn(async move {
+            let mut notifications = client.notifications();
+            loop {
+                // Wait for a relay to connect or for the interval
+                let _ = time::timeout(Some(interval), async {
+                    while let Ok(notification) = notifications.recv().await {
+                        if let RelayPoolNotification::RelayStatus { status, .. } = notification {
+                            if status == RelayStatus::Connected {
+                                break;
+                            }
+                        }
+                    }
+                })
+                .await;
+
+                // Stop when the queue is dropped
+                let queue = match weak.upgrade() {
+                    Some(queue) => queue,
+                    None => break,
+                };
+
+                let interactions: Self = Self {
+                    client: client.clone(),
+                    queue,
+                    flushing: flushing.clone(),
+                    batch_size,
+                };
+                if let Err(e) = interactions.flush().await {
+                    tracing::warn!("Impossible to flush interactions: {e}");
+                }
+            }
+        });
+    }
+
+    /// Load the interactions left unsent in a previous session
+    ///
+    /// Return the number of restored interactions. Corrupted entries are discarded.
+    pub async fn restore(&self) -> Result<usize, Error> {
+        let bytes: Vec<u8> = match self
+            .client
+            .database()
+            .load_checkpoint(CHECKPOINT_KEY)
+            .await?
+        {
+            Some(bytes) => bytes,
+            None => return Ok(0),
+        };
+
+        let value: Value = match serde_json::from_slice(&bytes) {
+            Ok(value) => value,
+            Err(_) => {
+                tracing::warn!("Discarding corrupted interaction queue");
+                return Ok(0);
+            }
+        };
+        if value.get("version").and_then(Value::as_u64) != Some(CHECKPOINT_VERSION) {
+            return Ok(0);
+        }
+
+        let events: Vec<Event> = value
+            .get("events")
+            .and_then(Value::as_array)
+            .map(|list| {
+                list.iter()
+                    .filter_map(Value::as_str)
+                    .filter_map(|json| Event::from_json(json).ok())
+                    .filter(|event| event.verify().is_ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut queue = self.queue.lock().await;
+        let mut restored: usize = 0;
+        for event in events.into_iter() {
+            if !queue.iter().any(|e| e.id() == event.id()) {
+                queue.push_back(event);
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+
+    async fn persist(&self) -> Result<(), Error> {
+        let events: Vec<String> = self
+            .queue
+            .lock()
+            .await
+            .iter()
+            .map(|e| e.as_json())
+            .collect();
+        let value: Vec<u8> = json!({
+            "version": CHECKPOINT_VERSION,
+            "events": events,
+        })
+        .to_string()
+        .into_bytes();
+        self.client
+            .database()
+            .save_checkpoint(CHECKPOINT_KEY, value)
+            .await?;
+        Ok(())
+    }
+
+    async fn is_online(&self) -> bool {
+        for relay in self.client.relays().await.values() {
+            if relay.is_connected().await {
+                return true;
+            }
+        }
+        false
+    }
+}
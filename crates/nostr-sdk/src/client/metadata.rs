@@ -0,0 +1,126 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Metadata fetching, with the local database as cache
+
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_database::{EventMetadata, Order};
+use nostr_relay_pool::runtime::thread;
+
+use super::{Client, Error};
+
+impl Client {
+    /// Fetch public key metadata, using the local database as cache
+    ///
+    /// If the metadata is already stored in the database, it's returned immediately and, if it was
+    /// last received from relays more than [`Options::metadata_cache_ttl`](super::Options::metadata_cache_ttl) ago, refreshed from relays in background.
+    /// If it's not stored, it's fetched from relays.
+    ///
+    /// If timeout is set to `None`, the default from [`Options`](super::Options) will be used.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+    pub async fn fetch_metadata(
+        &self,
+        public_key: PublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Metadata, Error> {
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::Metadata)
+            .limit(1);
+        let events: Vec<Event> = self.database().query(vec![filter], Order::Desc).await?;
+        match events.first() {
+            Some(event) => {
+                if self.is_metadata_stale(event).await
+                    && self.mark_metadata_as_refreshing(public_key).await
+                {
+                    let client: Self = self.clone();
+                    let res = thread::spawn(async move {
+                        if let Err(e) = client.refresh_metadata(public_key, timeout).await {
+                            tracing::warn!("Impossible to refresh metadata of {public_key}: {e}");
+                        }
+                        client.metadata_refreshing.lock().await.remove(&public_key);
+                    });
+                    if res.is_err() {
+                        self.metadata_refreshing.lock().await.remove(&public_key);
+                    }
+                }
+                Ok(Metadata::from_json(event.content())?)
+            }
+            None => self.refresh_metadata(public_key, timeout).await,
+        }
+    }
+
+    /// Fetch metadata from relays (the received event is stored into the database)
+    pub(super) async fn refresh_metadata(
+        &self,
+        public_key: PublicKey,
+        timeout: Option<Duration>,
+    ) -> Result<Metadata, Error> {
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::Metadata)
+            .limit(1);
+        let events: Vec<Event> = self.get_events_of(vec![filter], timeout).await?;
+        match events.into_iter().max_by_key(|e| e.created_at()) {
+            Some(event) => Ok(Metadata::from_json(event.content())?),
+            None => Err(Error::MetadataNotFound),
+        }
+    }
+
+    /// Check if the stored metadata event must be refreshed
+    ///
+    /// The age is computed from when the event was last received from a relay (see [`EventMetadata`])
+    /// or, if unknown, from its `created_at`.
+    async fn is_metadata_stale(&self, event: &Event) -> bool {
+        let received_at: Timestamp = match self.database().event_metadata(event.id()).await {
+            Ok(Some(EventMetadata {
+                last_seen: Some(last_seen),
+                ..
+            })) => last_seen,
+            _ => event.created_at(),
+        };
+        received_at + self.opts.metadata_cache_ttl <= Timestamp::now()
+    }
+
+    /// Return `true` if the metadata isn't already being refreshed, to avoid concurrent refreshes
+    async fn mark_metadata_as_refreshing(&self, public_key: PublicKey) -> bool {
+        let mut refreshing = self.metadata_refreshing.lock().await;
+        refreshing.insert(public_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_is_metadata_stale() {
+        let keys = Keys::generate();
+        let client = Client::new(&keys);
+        let metadata = Metadata::new().name("test");
+
+        // Just created
+        let event = EventBuilder::metadata(&metadata).to_event(&keys).unwrap();
+        assert!(!client.is_metadata_stale(&event).await);
+
+        // Created 2 hours ago and never received from relays
+        let event = EventBuilder::metadata(&metadata)
+            .custom_created_at(Timestamp::now() - Duration::from_secs(2 * 60 * 60))
+            .to_event(&keys)
+            .unwrap();
+        assert!(client.is_metadata_stale(&event).await);
+
+        // Received again now
+        let url = Url::parse("wss://relay.example.com").unwrap();
+        client
+            .database()
+            .event_id_seen(event.id(), url)
+            .await
+            .unwrap();
+        assert!(!client.is_metadata_stale(&event).await);
+    }
+}
@@ -4,35 +4,86 @@
 
 //! Client
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use nostr::event::builder::Error as EventBuilderError;
 use nostr::prelude::*;
 use nostr::types::metadata::Error as MetadataError;
-use nostr_database::DynNostrDatabase;
+use nostr::types::time::Instant;
+use nostr_database::{DatabaseError, DynNostrDatabase, Order};
 use nostr_relay_pool::pool::{self, Error as RelayPoolError, RelayPool};
 use nostr_relay_pool::relay::Error as RelayError;
+use nostr_relay_pool::runtime::time;
 use nostr_relay_pool::{
-    FilterOptions, NegentropyOptions, Relay, RelayOptions, RelayPoolNotification, RelaySendOptions,
+    CompletenessReport, FilterOptions, FilteringPolicy, NegentropyOptions, NotificationKinds,
+    Output, PowEscalation, Relay, RelayOptions, RelayPoolNotification, RelaySendOptions,
     SubscribeAutoCloseOptions, SubscribeOptions,
 };
 use nostr_signer::prelude::*;
 #[cfg(feature = "nip57")]
 use nostr_zapper::{DynNostrZapper, IntoNostrZapper, ZapperError};
 use thiserror::Error;
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, Mutex, RwLock};
 
+#[cfg(feature = "nip44")]
+mod app_sync;
+#[cfg(feature = "nip03")]
+mod attested;
+mod backfill;
 pub mod builder;
+mod checkpoint;
+#[cfg(feature = "nip44")]
+mod conversations;
+mod deep_link;
+mod delivery;
+mod dispatch;
+#[cfg(feature = "nip44")]
+mod dms;
+#[cfg(feature = "nip44")]
+mod drafts;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
+mod interactions;
+mod metadata;
 pub mod options;
-#[cfg(feature = "nip57")]
+mod output;
+mod prefetch;
+mod presence;
+#[cfg(feature = "nip44")]
+mod rpc;
+mod watcher;
+#[cfg(feature = "lnurl")]
 mod zapper;
 
+#[cfg(feature = "nip44")]
+pub use self::app_sync::{AppSync, AppSyncObserver, AppSyncUpdate, Causality, VectorClock};
+#[cfg(feature = "nip03")]
+pub use self::attested::AttestedOrder;
+use self::backfill::MetadataBackfill;
 pub use self::builder::ClientBuilder;
+#[cfg(feature = "nip44")]
+pub use self::conversations::{
+    Conversation, ConversationStream, Conversations, DirectMessage, DirectMessageProtocol,
+};
+pub use self::deep_link::{DeepLinkAction, DeepLinks};
+pub use self::dispatch::DispatchFilter;
+#[cfg(feature = "nip44")]
+pub use self::dms::Dms;
+#[cfg(feature = "nip44")]
+pub use self::drafts::{DraftAutosave, Drafts};
+pub use self::interactions::Interactions;
 pub use self::options::Options;
-#[cfg(feature = "nip57")]
+pub use self::output::SendPrivateMsgOutput;
+use self::prefetch::Prefetcher;
+pub use self::presence::{Presence, PresenceNotification, PresenceOptions, PresenceStatus};
+#[cfg(feature = "nip44")]
+pub use self::rpc::{ClientRpcExt, RpcOptions, RpcRequest};
+pub use self::watcher::{ContactChange, ContactNotification, ContactWatcher};
+#[cfg(feature = "lnurl")]
 pub use self::zapper::{ZapDetails, ZapEntity};
 
 /// [`Client`] error
@@ -44,6 +95,9 @@ pub enum Error {
     /// [`RelayPool`] error
     #[error("relay pool error: {0}")]
     RelayPool(#[from] RelayPoolError),
+    /// Database error
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
     /// Signer error
     #[error(transparent)]
     Signer(#[from] nostr_signer::Error),
@@ -69,18 +123,78 @@ pub enum Error {
     #[error(transparent)]
     NIP57(#[from] nip57::Error),
     /// LNURL Pay
-    #[cfg(feature = "nip57")]
+    #[cfg(feature = "lnurl")]
     #[error(transparent)]
     LnUrlPay(#[from] lnurl_pay::Error),
     /// Event not found
     #[error("event not found: {0}")]
     EventNotFound(EventId),
+    /// Event not found for the coordinate
+    #[error("event not found: {0}")]
+    CoordinateNotFound(Box<Coordinate>),
     /// Impossible to zap
     #[error("impossible to send zap: {0}")]
     ImpossibleToZap(String),
+    /// NIP37 error
+    #[error(transparent)]
+    NIP37(#[from] nip37::Error),
+    /// NIP51 error
+    #[error(transparent)]
+    NIP51(#[from] nip51::Error),
     /// Metadata not found
     #[error("metadata not found")]
     MetadataNotFound,
+    /// Impossible to fetch the current list from relays
+    #[error("impossible to fetch the current list of kind {0} from relays")]
+    ListNotFetched(Kind),
+    /// Draft not found
+    #[error("draft not found: {0}")]
+    DraftNotFound(String),
+    /// Protected event of another author
+    #[error("protected event of another author: {0}")]
+    ProtectedEvent(EventId),
+    /// NIP05 error
+    #[cfg(feature = "nip05")]
+    #[error(transparent)]
+    NIP05(#[from] nip05::Error),
+    /// NIP53 error
+    #[error(transparent)]
+    NIP53(#[from] nip53::Error),
+    /// NIP21 error
+    #[error(transparent)]
+    NIP21(#[from] nip21::Error),
+    /// NIP58 error
+    #[error(transparent)]
+    NIP58(#[from] nip58::Error),
+    /// NIP90 error
+    #[error(transparent)]
+    NIP90(#[from] nip90::Error),
+    /// Job failed
+    #[error("job failed: {0}")]
+    JobFailed(String),
+    /// Job result not received
+    #[error("job result not received")]
+    JobResultNotFound,
+    /// Timeout
+    #[error("timeout")]
+    Timeout,
+    /// JSON error
+    #[error(transparent)]
+    Json(#[from] nostr::serde_json::Error),
+    /// Error replied by the RPC receiver
+    #[error("rpc error: {0}")]
+    Rpc(String),
+    /// Unknown presence status
+    #[error("unknown presence status: {0}")]
+    UnknownPresenceStatus(String),
+    /// I/O error
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Export file already exists
+    #[cfg(not(target_arch = "wasm32"))]
+    #[error("file already exists: {}", .0.display())]
+    FileAlreadyExists(std::path::PathBuf),
 }
 
 /// Nostr client
@@ -90,6 +204,15 @@ pub struct Client {
     signer: Arc<RwLock<Option<NostrSigner>>>,
     #[cfg(feature = "nip57")]
     zapper: Arc<RwLock<Option<Arc<DynNostrZapper>>>>,
+    /// Public keys whose metadata is being refreshed in background
+    metadata_refreshing: Arc<Mutex<HashSet<PublicKey>>>,
+    prefetcher: Option<Prefetcher>,
+    metadata_backfill: MetadataBackfill,
+    #[cfg(feature = "nip05")]
+    nip05_resolver: Nip05Resolver,
+    checkpointing: Arc<AtomicBool>,
+    recording_deliveries: Arc<AtomicBool>,
+    dispatch: DispatchFilter,
     opts: Options,
 }
 
@@ -144,6 +267,25 @@ impl Client {
             signer: Arc::new(RwLock::new(builder.signer)),
             #[cfg(feature = "nip57")]
             zapper: Arc::new(RwLock::new(builder.zapper)),
+            metadata_refreshing: Arc::new(Mutex::new(HashSet::new())),
+            prefetcher: if builder.opts.get_prefetch() {
+                Some(Prefetcher::new(
+                    builder.opts.get_prefetch_concurrency(),
+                    builder.opts.timeout,
+                ))
+            } else {
+                None
+            },
+            metadata_backfill: MetadataBackfill::new(
+                builder.opts.get_metadata_backfill_batch_size(),
+                builder.opts.get_metadata_backfill_interval(),
+                builder.opts.timeout,
+            ),
+            #[cfg(feature = "nip05")]
+            nip05_resolver: new_nip05_resolver(&builder.opts),
+            checkpointing: Arc::new(AtomicBool::new(false)),
+            recording_deliveries: Arc::new(AtomicBool::new(false)),
+            dispatch: DispatchFilter::default(),
             opts: builder.opts,
         }
     }
@@ -210,6 +352,22 @@ impl Client {
         self.pool.database()
     }
 
+    /// Get the filtering policy
+    ///
+    /// Check [`RelayPool::filtering`] to learn more.
+    #[inline]
+    pub fn filtering(&self) -> FilteringPolicy {
+        self.pool.filtering()
+    }
+
+    /// Set the kinds of the events delivered to the notification channel (default: all)
+    ///
+    /// Check [`RelayPool::set_notification_kinds`] to learn more.
+    #[inline]
+    pub async fn set_notification_kinds(&self, kinds: NotificationKinds) {
+        self.pool.set_notification_kinds(kinds).await
+    }
+
     /// Start a previously stopped client
     pub async fn start(&self) {
         self.connect().await;
@@ -246,9 +404,23 @@ impl Client {
         Ok(self.pool.relay(url).await?)
     }
 
+    /// Enable or disable the logging of the raw messages exchanged with a relay
+    ///
+    /// Check [`Relay::set_message_logging`] to learn more.
+    pub async fn set_relay_message_logging<U>(&self, url: U, enable: bool) -> Result<(), Error>
+    where
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let relay: Relay = self.relay(url).await?;
+        relay.set_message_logging(enable);
+        Ok(())
+    }
+
     /// Add new relay
     ///
-    /// Return `false` if the relay already exists.
+    /// If the relay already exists, its options are updated (check [`RelayPool::add_relay`]).
+    /// Return `false` if the relay already exists with the same options.
     ///
     /// This method use perviously set or default [Options] to configure the [Relay] (ex. set proxy, set min POW, set relay limits, ...).
     /// To use custom [RelayOptions], check `Client::add_relay_with_opts`.
@@ -291,7 +463,8 @@ impl Client {
 
     /// Add new relay with custom [`RelayOptions`]
     ///
-    /// Return `false` if the relay already exists.
+    /// If the relay already exists, its options are updated (check [`RelayPool::add_relay`]).
+    /// Return `false` if the relay already exists with the same options.
     ///
     /// Connection is **NOT** automatically started with relay, remember to call `client.connect()`!
     ///
@@ -338,6 +511,45 @@ impl Client {
         Ok(())
     }
 
+    /// Set relays
+    ///
+    /// Relays not in the list are disconnected and removed, the missing ones are added.
+    /// Relays already added are kept as they are.
+    ///
+    /// Connection is **NOT** automatically started with new relays, remember to call `client.connect()`!
+    pub async fn set_relays<I, U>(&self, relays: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let urls: HashSet<Url> = relays
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()
+            .map_err(pool::Error::from)?;
+        self.set_relay_urls(urls).await
+    }
+
+    /// Not generic: the `TryIntoUrl` bound of `set_relays` would be selected for the `Url`s too
+    async fn set_relay_urls(&self, urls: HashSet<Url>) -> Result<(), Error> {
+        let current: HashMap<Url, Relay> = self.pool.relays().await;
+
+        // Remove relays not in the list
+        for url in current.keys().filter(|url| !urls.contains(url)) {
+            self.pool.remove_relay(url).await?;
+        }
+
+        // Add missing relays
+        for url in urls.into_iter() {
+            if !current.contains_key(&url) {
+                self.add_relay(url).await?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Disconnect and remove relay
     ///
     /// # Example
@@ -434,6 +646,25 @@ impl Client {
     /// ```
     pub async fn connect(&self) {
         self.pool.connect(self.opts.connection_timeout).await;
+
+        if let Some(interval) = self.opts.get_checkpoint_interval() {
+            self.spawn_checkpointing(interval);
+        }
+
+        if self.opts.get_delivery_records() {
+            self.spawn_delivery_recording();
+        }
+    }
+
+    /// Get the relays the event was received from, sorted
+    ///
+    /// Useful for relay hints (i.e. `nevent`) and to debug the event propagation.
+    /// Return an empty list if the event has never been received.
+    pub async fn seen_on(&self, event_id: EventId) -> Result<Vec<Url>, Error> {
+        let relays: Option<HashSet<Url>> = self.database().event_seen_on_relays(event_id).await?;
+        let mut relays: Vec<Url> = relays.unwrap_or_default().into_iter().collect();
+        relays.sort();
+        Ok(relays)
     }
 
     /// Disconnect from all relays
@@ -508,6 +739,7 @@ impl Client {
         let opts: SubscribeOptions = SubscribeOptions::default()
             .close_on(opts)
             .send_opts(send_opts);
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
         self.pool.subscribe(filters, opts).await
     }
 
@@ -554,9 +786,23 @@ impl Client {
         let opts: SubscribeOptions = SubscribeOptions::default()
             .close_on(opts)
             .send_opts(send_opts);
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
         self.pool.subscribe_with_id(id, filters, opts).await
     }
 
+    /// Update the filters of a subscription, minimizing the events resent by relays
+    ///
+    /// If the new filters only extend the current ones (i.e. adding an author), an additional subscription
+    /// is opened with the incremental filters only. Otherwise, the subscription is amended with a new `REQ`
+    /// with the same [`SubscriptionId`].
+    pub async fn update_subscription_filters(&self, id: SubscriptionId, filters: Vec<Filter>) {
+        let opts: RelaySendOptions = self.opts.get_wait_for_subscription();
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
+        self.pool
+            .update_subscription_filters(id, filters, opts)
+            .await
+    }
+
     /// Unsubscribe
     pub async fn unsubscribe(&self, id: SubscriptionId) {
         let opts: RelaySendOptions = self.opts.get_wait_for_subscription();
@@ -603,6 +849,15 @@ impl Client {
             .await
     }
 
+    /// Apply [`Options::query_floor`] to the filters
+    fn apply_query_floor(&self, filters: Vec<Filter>) -> Vec<Filter> {
+        let floor: Option<Timestamp> = self.opts.get_query_floor();
+        filters
+            .into_iter()
+            .map(|filter| apply_floor(filter, floor))
+            .collect()
+    }
+
     /// Get events of filters with [`FilterOptions`]
     ///
     /// If timeout is set to `None`, the default from [`Options`] will be used.
@@ -616,12 +871,43 @@ impl Client {
             Some(t) => t,
             None => self.opts.timeout,
         };
-        Ok(self.pool.get_events_of(filters, timeout, opts).await?)
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
+        #[allow(unused_mut)]
+        let mut events: Vec<Event> = self.pool.get_events_of(filters, timeout, opts).await?;
+        #[cfg(feature = "nip03")]
+        if let Some(order) = self.opts.get_attested_order() {
+            order.sort(&self.pool, &mut events, timeout).await;
+        }
+        if let Some(prefetcher) = &self.prefetcher {
+            prefetcher.schedule(&self.pool, events.iter()).await;
+        }
+        if self.opts.get_metadata_backfill() {
+            self.backfill_metadata(events.iter().map(|e| e.author()))
+                .await;
+        }
+        Ok(events)
+    }
+
+    /// Count events of filters (NIP45)
+    ///
+    /// Relays count independently: the highest count is returned.
+    /// For relays that don't support NIP45, the count of the **local database** is used.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/45.md>
+    pub async fn count(&self, filters: Vec<Filter>) -> Result<usize, Error> {
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
+        Ok(self.pool.count_events(filters, self.opts.timeout).await?)
     }
 
     /// Get events of filters from specific relays
     ///
     /// Get events both from **local database** and **relays**
+    ///
+    /// The results of the relays are merged: events are deduplicated by ID, only the latest version
+    /// of the replaceable events is kept and the list is sorted by `created_at` (newest first),
+    /// respecting the `limit` of each filter.
+    ///
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
     pub async fn get_events_from<I, U>(
         &self,
         urls: I,
@@ -634,12 +920,102 @@ impl Client {
         pool::Error: From<<U as TryIntoUrl>::Err>,
     {
         let timeout: Duration = timeout.unwrap_or(self.opts.timeout);
-        Ok(self
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
+        #[allow(unused_mut)]
+        let mut events: Vec<Event> = self
             .pool
             .get_events_from(urls, filters, timeout, FilterOptions::ExitOnEOSE)
+            .await?;
+        #[cfg(feature = "nip03")]
+        if let Some(order) = self.opts.get_attested_order() {
+            order.sort(&self.pool, &mut events, timeout).await;
+        }
+        Ok(events)
+    }
+
+    /// Get events of filters from specific relays and estimate how complete the result is
+    ///
+    /// After `EOSE`, the result sets of the relays are compared: check
+    /// [`CompletenessReport::estimated_completeness`] and [`CompletenessReport::consistency`]
+    /// to decide if more relays should be queried for this kind of data.
+    ///
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
+    pub async fn get_events_with_report<I, U>(
+        &self,
+        urls: I,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<(Vec<Event>, CompletenessReport), Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let timeout: Duration = timeout.unwrap_or(self.opts.timeout);
+        let filters: Vec<Filter> = self.apply_query_floor(filters);
+        Ok(self
+            .pool
+            .get_events_with_report(urls, filters, timeout, FilterOptions::ExitOnEOSE)
             .await?)
     }
 
+    /// Fetch the event referenced by a [`Nip19Event`] (`nevent`)
+    ///
+    /// The event is first looked up in the **local database**. If it's not found, it's requested
+    /// to the relay hints embedded in the [`Nip19Event`] (added to the pool if missing)
+    /// or, if there are no hints, to **all relays**.
+    ///
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/19.md>
+    pub async fn fetch(
+        &self,
+        nip19_event: Nip19Event,
+        timeout: Option<Duration>,
+    ) -> Result<Event, Error> {
+        let event_id: EventId = nip19_event.event_id;
+
+        if let Ok(event) = self.database().event_by_id(event_id).await {
+            return Ok(event);
+        }
+
+        let mut filter: Filter = Filter::new().id(event_id).limit(1);
+        if let Some(author) = nip19_event.author {
+            filter = filter.author(author);
+        }
+        if let Some(kind) = nip19_event.kind {
+            filter = filter.kind(kind);
+        }
+
+        let relays: Vec<Url> = parse_relay_hints(&nip19_event.relays);
+        let events: Vec<Event> = self
+            .get_events_from_hints(relays, vec![filter], timeout)
+            .await?;
+
+        events
+            .into_iter()
+            .find(|e| e.id() == event_id)
+            .ok_or(Error::EventNotFound(event_id))
+    }
+
+    /// Get events from the relay hints (added to the pool if missing) or, if there are no hints, from **all relays**
+    pub(crate) async fn get_events_from_hints(
+        &self,
+        relays: Vec<Url>,
+        filters: Vec<Filter>,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        if relays.is_empty() {
+            self.get_events_of(filters, timeout).await
+        } else {
+            for url in relays.iter() {
+                self.add_relay(url.clone()).await?;
+                self.connect_relay(url.clone()).await?;
+            }
+            self.get_events_from(relays, filters, timeout).await
+        }
+    }
+
     /// Send client message to **all relays**
     pub async fn send_msg(&self, msg: ClientMessage) -> Result<(), Error> {
         let opts: RelaySendOptions = self.opts.get_wait_for_send();
@@ -685,7 +1061,12 @@ impl Client {
     ///
     /// This method will wait for the `OK` message from the relay.
     /// If you not want to wait for the `OK` message, use `send_msg` method instead.
-    pub async fn send_event(&self, event: Event) -> Result<EventId, Error> {
+    ///
+    /// Return the outcome of each relay: check [`Output::is_success`] and retry on [`Output::failed`] if needed.
+    ///
+    /// Protected events (NIP70) of other authors are refused.
+    pub async fn send_event(&self, event: Event) -> Result<Output, Error> {
+        self.check_protected([&event]).await?;
         let opts: RelaySendOptions = self.opts.get_wait_for_send();
         Ok(self.pool.send_event(event, opts).await?)
     }
@@ -696,6 +1077,7 @@ impl Client {
         events: Vec<Event>,
         opts: RelaySendOptions,
     ) -> Result<(), Error> {
+        self.check_protected(&events).await?;
         Ok(self.pool.batch_event(events, opts).await?)
     }
 
@@ -703,12 +1085,15 @@ impl Client {
     ///
     /// This method will wait for the `OK` message from the relay.
     /// If you not want to wait for the `OK` message, use `send_msg` method instead.
-    pub async fn send_event_to<I, U>(&self, urls: I, event: Event) -> Result<EventId, Error>
+    ///
+    /// Return the outcome of each relay: check [`Output::is_success`] and retry on [`Output::failed`] if needed.
+    pub async fn send_event_to<I, U>(&self, urls: I, event: Event) -> Result<Output, Error>
     where
         I: IntoIterator<Item = U>,
         U: TryIntoUrl,
         pool::Error: From<<U as TryIntoUrl>::Err>,
     {
+        self.check_protected([&event]).await?;
         let opts: RelaySendOptions = self.opts.get_wait_for_send();
         Ok(self.pool.send_event_to(urls, event, opts).await?)
     }
@@ -725,16 +1110,70 @@ impl Client {
         U: TryIntoUrl,
         pool::Error: From<<U as TryIntoUrl>::Err>,
     {
+        self.check_protected(&events).await?;
         Ok(self.pool.batch_event_to(urls, events, opts).await?)
     }
 
+    /// Refuse to broadcast protected events (NIP70) not authored by the signer
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/70.md>
+    async fn check_protected<'a, I>(&self, events: I) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let mut public_key: Option<Option<PublicKey>> = None;
+        for event in events.into_iter().filter(|e| e.is_protected()) {
+            // Get the signer public key only if needed
+            if public_key.is_none() {
+                public_key = Some(match self.signer().await {
+                    Ok(signer) => Some(signer.public_key().await?),
+                    Err(_) => None,
+                });
+            }
+
+            if public_key.flatten() != Some(event.author()) {
+                tracing::warn!(
+                    "Refusing to broadcast protected event {} of {}",
+                    event.id(),
+                    event.author()
+                );
+                return Err(Error::ProtectedEvent(event.id()));
+            }
+        }
+        Ok(())
+    }
+
     /// Signs the [`EventBuilder`] into an [`Event`] using the [`NostrSigner`]
+    ///
+    /// Rise an error if the builder exceeds the [`Options::event_limits`].
     pub async fn sign_event_builder(&self, builder: EventBuilder) -> Result<Event, Error> {
+        let difficulty: u8 = self.opts.get_difficulty_for_kind(&builder.kind());
+        self.sign_event_builder_with_difficulty(builder, difficulty)
+            .await
+    }
+
+    async fn sign_event_builder_with_difficulty(
+        &self,
+        builder: EventBuilder,
+        difficulty: u8,
+    ) -> Result<Event, Error> {
+        builder.check_limits(self.opts.get_event_limits())?;
+
         let signer = self.signer().await?;
 
         let public_key = signer.public_key().await?;
-        let difficulty: u8 = self.opts.get_difficulty();
         let unsigned = if difficulty > 0 {
+            #[cfg(all(not(target_arch = "wasm32"), feature = "pow-multi-thread"))]
+            {
+                builder.to_unsigned_pow_event_with(
+                    public_key,
+                    difficulty,
+                    self.opts.get_pow_threads(),
+                    &PowToken::new(),
+                )?
+            }
+
+            #[cfg(any(target_arch = "wasm32", not(feature = "pow-multi-thread")))]
             builder.to_unsigned_pow_event(public_key, difficulty)
         } else {
             builder.to_unsigned_event(public_key)
@@ -747,8 +1186,16 @@ impl Client {
     ///
     /// Rise an error if the [`NostrSigner`] is not set.
     pub async fn send_event_builder(&self, builder: EventBuilder) -> Result<EventId, Error> {
+        if self.opts.get_max_pow_escalation() > 0 {
+            let relays = self.pool.relays().await;
+            let output = self
+                .send_event_builder_with_output(relays.into_keys(), builder)
+                .await?;
+            return published_id(output);
+        }
+
         let event: Event = self.sign_event_builder(builder).await?;
-        self.send_event(event).await
+        published_id(self.send_event(event).await?)
     }
 
     /// Take an [`EventBuilder`], sign it by using the [`NostrSigner`] and broadcast to **specific relays**.
@@ -764,22 +1211,148 @@ impl Client {
         U: TryIntoUrl,
         pool::Error: From<<U as TryIntoUrl>::Err>,
     {
+        if self.opts.get_max_pow_escalation() > 0 {
+            let output = self.send_event_builder_with_output(urls, builder).await?;
+            return published_id(output);
+        }
+
         let event: Event = self.sign_event_builder(builder).await?;
-        self.send_event_to(urls, event).await
+        published_id(self.send_event_to(urls, event).await?)
+    }
+
+    /// Take an [`EventBuilder`], sign it by using the [`NostrSigner`] and send to **specific relays**,
+    /// returning the outcome for each relay.
+    ///
+    /// If [`Options::pow_escalation`] is set, the event is re-mined at the required difficulty for
+    /// the relays that reject it with `pow:` and sent again only to them.
+    ///
+    /// Rise an error if the [`NostrSigner`] is not set.
+    pub async fn send_event_builder_with_output<I, U>(
+        &self,
+        urls: I,
+        builder: EventBuilder,
+    ) -> Result<Output, Error>
+    where
+        I: IntoIterator<Item = U>,
+        U: TryIntoUrl,
+        pool::Error: From<<U as TryIntoUrl>::Err>,
+    {
+        let urls: Vec<Url> = urls
+            .into_iter()
+            .map(|u| u.try_into_url())
+            .collect::<Result<_, _>>()
+            .map_err(pool::Error::from)?;
+        self.send_event_builder_with_output_to_urls(urls, builder)
+            .await
+    }
+
+    /// Not generic: the `TryIntoUrl` bound of `send_event_builder_with_output` would be selected for the `Url`s too
+    async fn send_event_builder_with_output_to_urls(
+        &self,
+        urls: Vec<Url>,
+        builder: EventBuilder,
+    ) -> Result<Output, Error> {
+        if urls.is_empty() {
+            return Err(Error::RelayPool(RelayPoolError::NoRelaysSpecified));
+        }
+
+        let difficulty: u8 = self.opts.get_difficulty_for_kind(&builder.kind());
+        let max_difficulty: u8 = self.opts.get_max_pow_escalation();
+        let event: Event = self
+            .sign_event_builder_with_difficulty(builder.clone(), difficulty)
+            .await?;
+        let opts: RelaySendOptions = self.opts.get_wait_for_send();
+        let mut output: Output = self.pool.send_event_to(urls, event, opts).await?;
+
+        let mut rejected: Vec<(Url, u8)> = Vec::new();
+        for (url, message) in output.failed.iter() {
+            if let Some(required) = nip13::required_difficulty(message) {
+                if required > difficulty && required <= max_difficulty {
+                    rejected.push((url.clone(), required));
+                }
+            }
+        }
+
+        // Re-mine and resend to relays that required more POW
+        let mut mined: HashMap<u8, Event> = HashMap::new();
+        for (url, required) in rejected.into_iter() {
+            let start = Instant::now();
+
+            let event: Event = match mined.get(&required) {
+                Some(event) => event.clone(),
+                None => {
+                    let event: Event = self
+                        .sign_event_builder_with_difficulty(builder.clone(), required)
+                        .await?;
+                    mined.insert(required, event.clone());
+                    event
+                }
+            };
+
+            let event_id: EventId = event.id();
+            match self.pool.send_event_to([url.clone()], event, opts).await {
+                Ok(sent) => {
+                    output.failed.remove(&url);
+                    output.success.extend(sent.success);
+                    output.failed.extend(sent.failed);
+                }
+                Err(e) => {
+                    output.failed.insert(url.clone(), e.to_string());
+                }
+            }
+            let published: bool = output.success.contains_key(&url);
+
+            output.pow_escalations.insert(
+                url,
+                PowEscalation {
+                    event_id,
+                    difficulty: required,
+                    elapsed: start.elapsed(),
+                    published,
+                },
+            );
+        }
+
+        Ok(output)
     }
 
     /// Get public key metadata
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
     pub async fn metadata(&self, public_key: PublicKey) -> Result<Metadata, Error> {
-        let filter: Filter = Filter::new()
-            .author(public_key)
-            .kind(Kind::Metadata)
-            .limit(1);
-        let events: Vec<Event> = self.get_events_of(vec![filter], None).await?;
-        match events.first() {
-            Some(event) => Ok(Metadata::from_json(event.content())?),
-            None => Err(Error::MetadataNotFound),
+        self.refresh_metadata(public_key, None).await
+    }
+
+    /// Queue the backfill of profiles (kind `0`) and relay lists (kind `10002`) missing in the database
+    ///
+    /// Authors are fetched in background, batched in a single `REQ` of up to
+    /// [`Options::metadata_backfill_batch_size`] authors, with at most one `REQ` every
+    /// [`Options::metadata_backfill_interval`]. Authors already queued are skipped.
+    ///
+    /// Called automatically for the authors of the events received in `get_events_of` and
+    /// `handle_notifications` if [`Options::metadata_backfill`] is enabled.
+    pub async fn backfill_metadata<I>(&self, public_keys: I)
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        self.metadata_backfill.queue(&self.pool, public_keys).await;
+    }
+
+    /// Verify the NIP05 identifier of [`PublicKey`]
+    ///
+    /// Return `false` if the identifier doesn't match the public key.
+    /// Resolved identifiers are cached (see [`Nip05Resolver`]).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/05.md>
+    #[cfg(feature = "nip05")]
+    pub async fn verify_nip05<S>(&self, public_key: PublicKey, nip05: S) -> Result<bool, Error>
+    where
+        S: AsRef<str>,
+    {
+        match self.nip05_resolver.verify(public_key, nip05).await {
+            Ok(()) => Ok(true),
+            Err(nip05::Error::ImpossibleToVerify) => Ok(false),
+            Err(e) => Err(e.into()),
         }
     }
 
@@ -807,7 +1380,12 @@ impl Client {
     /// ```
     pub async fn set_metadata(&self, metadata: &Metadata) -> Result<EventId, Error> {
         let builder = EventBuilder::metadata(metadata);
-        self.send_event_builder(builder).await
+        let event: Event = self.sign_event_builder(builder).await?;
+
+        // Store into the local database, so `fetch_metadata` will return the updated metadata
+        self.database().save_event(&event).await?;
+
+        published_id(self.send_event(event).await?)
     }
 
     /// Set relay list (NIP65)
@@ -821,6 +1399,37 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Publish the relay list (NIP65) derived from the relays of the pool
+    ///
+    /// Relays with both the `READ` and `WRITE` flags are advertised without marker, the others as `read` or `write`.
+    /// Relays without both flags and not `ws://` or `wss://` relays (i.e. unix domain sockets) are excluded.
+    /// Fail with [`RelayPoolError::NoRelays`] if no relay can be advertised, to not publish an empty list.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/65.md>
+    pub async fn publish_relay_list_from_pool(&self) -> Result<EventId, Error> {
+        let relays: HashMap<Url, Relay> = self.pool.relays().await;
+        let list: Vec<(UncheckedUrl, Option<RelayMetadata>)> = relays
+            .into_iter()
+            .filter(|(url, ..)| matches!(url.scheme(), "ws" | "wss"))
+            .filter_map(|(url, relay)| {
+                let flags = relay.flags();
+                let metadata: Option<RelayMetadata> = match (flags.has_read(), flags.has_write()) {
+                    (true, true) => None,
+                    (true, false) => Some(RelayMetadata::Read),
+                    (false, true) => Some(RelayMetadata::Write),
+                    (false, false) => return None,
+                };
+                Some((UncheckedUrl::from(url), metadata))
+            })
+            .collect();
+
+        if list.is_empty() {
+            return Err(Error::RelayPool(RelayPoolError::NoRelays));
+        }
+
+        self.set_relay_list(list).await
+    }
+
     /// Publish text note
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/01.md>
@@ -959,53 +1568,451 @@ impl Client {
         Ok(contacts)
     }
 
-    /// Send encrypted direct message
+    /// Find applications able to handle events of `kind`
     ///
-    /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
+    /// Handler information events are queried from both database and relays and sorted
+    /// by number of recommendations (most recommended first).
+    /// Use [`HandlerUrl::url`] to build the link to an event from the returned URL templates.
     ///
-    /// # Example
-    /// ```rust,no_run
-    /// use nostr_sdk::prelude::*;
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
     ///
-    /// # #[tokio::main]
-    /// # async fn main() {
-    /// #   let my_keys = Keys::generate();
-    /// #   let client = Client::new(&my_keys);
-    /// let alice_pubkey =
-    ///     PublicKey::from_bech32("npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy")
-    ///         .unwrap();
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub async fn find_handler(
+        &self,
+        kind: Kind,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<HandlerInformation>, Error> {
+        let k: SingleLetterTag = SingleLetterTag::lowercase(Alphabet::K);
+        let filters: Vec<Filter> = vec![
+            Filter::new()
+                .kind(Kind::HandlerInformation)
+                .custom_tag(k, [kind.to_string()]),
+            Filter::new()
+                .kind(Kind::HandlerRecommendation)
+                .identifier(kind.to_string()),
+        ];
+
+        let mut events: Vec<Event> = self.database().query(filters.clone(), Order::Desc).await?;
+        events.extend(self.get_events_of(filters, timeout).await?);
+
+        // Keep the latest handler information of each coordinate
+        let mut handlers: HashMap<Coordinate, (Timestamp, HandlerInformation)> = HashMap::new();
+        let mut recommendations: HashMap<Coordinate, usize> = HashMap::new();
+        let mut recommenders: HashSet<(PublicKey, Coordinate)> = HashSet::new();
+        for event in events.iter() {
+            match event.kind() {
+                Kind::HandlerInformation => {
+                    let info = match HandlerInformation::try_from(event) {
+                        Ok(info) if info.supports(&kind) => info,
+                        _ => continue,
+                    };
+                    let coordinate: Coordinate =
+                        Coordinate::new(Kind::HandlerInformation, event.author())
+                            .identifier(info.id.clone());
+                    match handlers.get(&coordinate) {
+                        Some((created_at, ..)) if *created_at >= event.created_at() => (),
+                        _ => {
+                            handlers.insert(coordinate, (event.created_at(), info));
+                        }
+                    }
+                }
+                Kind::HandlerRecommendation => {
+                    if let Ok(recommendation) = HandlerRecommendation::try_from(event) {
+                        for handler in recommendation.handlers.into_iter() {
+                            let coordinate: Coordinate = Coordinate::new(
+                                handler.coordinate.kind,
+                                handler.coordinate.public_key,
+                            )
+                            .identifier(handler.coordinate.identifier);
+                            // Count each recommender once
+                            if recommenders.insert((event.author(), coordinate.clone())) {
+                                *recommendations.entry(coordinate).or_default() += 1;
+                            }
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mut handlers: Vec<(usize, HandlerInformation)> = handlers
+            .into_iter()
+            .map(|(coordinate, (.., info))| {
+                (
+                    recommendations
+                        .get(&coordinate)
+                        .copied()
+                        .unwrap_or_default(),
+                    info,
+                )
+            })
+            .collect();
+        handlers.sort_by(|(a, ..), (b, ..)| b.cmp(a));
+
+        Ok(handlers.into_iter().map(|(.., info)| info).collect())
+    }
+
+    /// Get application-specific data
     ///
-    /// client
-    ///     .send_direct_msg(alice_pubkey, "My first DM fro Nostr SDK!", None)
-    ///     .await
-    ///     .unwrap();
-    /// # }
-    /// ```
-    #[cfg(feature = "nip04")]
-    pub async fn send_direct_msg<S>(
+    /// The latest event is looked up both in database and on relays.
+    /// NIP44 encrypted content (see [`Client::set_encrypted_app_data`]) is decrypted.
+    /// Return `None` if nothing is stored for `identifier`.
+    ///
+    /// If timeout is set to `None`, the default from [`Options`] will be used.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    pub async fn get_app_data<S>(
         &self,
-        receiver: PublicKey,
-        msg: S,
-        reply_to: Option<EventId>,
-    ) -> Result<EventId, Error>
+        identifier: S,
+        timeout: Option<Duration>,
+    ) -> Result<Option<String>, Error>
     where
         S: Into<String>,
     {
         let signer = self.signer().await?;
-        let content: String = signer.nip04_encrypt(receiver, msg.into()).await?;
+        let public_key = signer.public_key().await?;
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::ApplicationSpecificData)
+            .identifier(identifier)
+            .limit(1);
 
-        let mut tags: Vec<Tag> = Vec::with_capacity(1 + usize::from(reply_to.is_some()));
-        tags.push(Tag::public_key(receiver));
-        if let Some(id) = reply_to {
-            tags.push(Tag::event(id));
-        }
+        let mut events: Vec<Event> = self
+            .database()
+            .query(vec![filter.clone()], Order::Desc)
+            .await?;
+        events.extend(self.get_events_of(vec![filter], timeout).await?);
+        let event: Event = match events.into_iter().max_by_key(|e| e.created_at()) {
+            Some(event) => event,
+            None => return Ok(None),
+        };
 
-        let builder: EventBuilder = EventBuilder::new(Kind::EncryptedDirectMessage, content, tags);
+        // Not encrypted content fails to decrypt: return it as is
+        #[cfg(feature = "nip44")]
+        if let Ok(content) = signer.nip44_decrypt(public_key, event.content()).await {
+            return Ok(Some(content));
+        }
 
-        self.send_event_builder(builder).await
+        Ok(Some(event.content().to_string()))
     }
 
-    /// Repost
+    /// Set application-specific data
+    ///
+    /// The content is public: use [`Client::set_encrypted_app_data`] for private data (i.e. settings).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    pub async fn set_app_data<S, C>(&self, identifier: S, content: C) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        let builder = EventBuilder::new(
+            Kind::ApplicationSpecificData,
+            content,
+            [Tag::Identifier(identifier.into())],
+        );
+        self.send_event_builder(builder).await
+    }
+
+    /// Set NIP44 encrypted (to self) application-specific data
+    ///
+    /// Useful to sync settings across devices through relays.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/78.md>
+    #[cfg(feature = "nip44")]
+    pub async fn set_encrypted_app_data<S, C>(
+        &self,
+        identifier: S,
+        content: C,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+        C: Into<String>,
+    {
+        let signer = self.signer().await?;
+        let public_key = signer.public_key().await?;
+        let content: String = signer.nip44_encrypt(public_key, content.into()).await?;
+        self.set_app_data(identifier, content).await
+    }
+
+    /// Get the latest event of `kind` authored by the [`NostrSigner`]
+    async fn get_own_latest_event(
+        &self,
+        kind: Kind,
+        timeout: Option<Duration>,
+    ) -> Result<Option<Event>, Error> {
+        let signer = self.signer().await?;
+        let public_key = signer.public_key().await?;
+        let filter: Filter = Filter::new().author(public_key).kind(kind).limit(1);
+        let events: Vec<Event> = self.get_events_of(vec![filter], timeout).await?;
+        Ok(events.into_iter().max_by_key(|e| e.created_at()))
+    }
+
+    /// Get the events of `kind` authored by the [`NostrSigner`]
+    async fn get_own_events(
+        &self,
+        kind: Kind,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<Event>, Error> {
+        let signer = self.signer().await?;
+        let public_key = signer.public_key().await?;
+        let filter: Filter = Filter::new().author(public_key).kind(kind);
+        self.get_events_of(vec![filter], timeout).await
+    }
+
+    /// Get the current version of a list of the [`NostrSigner`], before replacing it
+    ///
+    /// Fail if no relay is connected: publishing a new list would overwrite the one stored on relays.
+    async fn get_own_list_for_update(&self, kind: Kind) -> Result<Option<Event>, Error> {
+        let mut connected: bool = false;
+        for relay in self.relays().await.values() {
+            if relay.is_connected().await {
+                connected = true;
+                break;
+            }
+        }
+
+        if !connected {
+            return Err(Error::ListNotFetched(kind));
+        }
+
+        self.get_own_latest_event(kind, None).await
+    }
+
+    /// Decrypt the private items of a NIP51 list
+    ///
+    /// Both NIP44 and legacy NIP04 encrypted content are supported.
+    #[cfg(feature = "nip44")]
+    async fn decrypt_private_list_items(&self, event: &Event) -> Result<Vec<Tag>, Error> {
+        if event.content().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let signer = self.signer().await?;
+        let public_key = signer.public_key().await?;
+
+        #[cfg(feature = "nip04")]
+        if event.content().contains("?iv=") {
+            let json: String = signer.nip04_decrypt(public_key, event.content()).await?;
+            return Ok(nip51::private_items_from_json(json)?);
+        }
+
+        let json: String = signer.nip44_decrypt(public_key, event.content()).await?;
+        Ok(nip51::private_items_from_json(json)?)
+    }
+
+    #[cfg(not(feature = "nip44"))]
+    async fn decrypt_private_list_items(&self, _event: &Event) -> Result<Vec<Tag>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Get mute list
+    ///
+    /// Private items, encrypted in the event content, are merged with the public ones.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_mute_list(&self, timeout: Option<Duration>) -> Result<MuteList, Error> {
+        match self.get_own_latest_event(Kind::MuteList, timeout).await? {
+            Some(event) => {
+                let mut list: MuteList = MuteList::from_tags(event.iter_tags());
+                let private: Vec<Tag> = self.decrypt_private_list_items(&event).await?;
+                list.merge(MuteList::from_tags(private.iter()));
+                Ok(list)
+            }
+            None => Ok(MuteList::default()),
+        }
+    }
+
+    /// Add public key to the mute list
+    ///
+    /// The other public items of the current mute list are preserved and the encrypted content,
+    /// with the private items, is kept as it is. If the public key is already muted privately, it's not made public.
+    ///
+    /// Fail if the current mute list can't be fetched from relays, to not overwrite it.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn mute(&self, public_key: PublicKey) -> Result<EventId, Error> {
+        let (content, mut tags, private) =
+            match self.get_own_list_for_update(Kind::MuteList).await? {
+                Some(event) => {
+                    let private: Vec<Tag> = self.decrypt_private_list_items(&event).await?;
+                    (event.content().to_string(), event.tags().to_vec(), private)
+                }
+                None => (String::new(), Vec::new(), Vec::new()),
+            };
+
+        let muted: bool = tags
+            .iter()
+            .chain(private.iter())
+            .any(|t| is_public_key_tag(t, &public_key));
+        if !muted {
+            tags.push(Tag::public_key(public_key));
+        }
+
+        let builder = EventBuilder::new(Kind::MuteList, content, tags);
+        self.send_event_builder(builder).await
+    }
+
+    /// Remove public key from the mute list
+    ///
+    /// Only public items are removed: the encrypted content, with the private items, is kept as it is.
+    ///
+    /// Fail if the current mute list can't be fetched from relays, to not overwrite it.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn unmute(&self, public_key: PublicKey) -> Result<EventId, Error> {
+        let (content, mut tags) = match self.get_own_list_for_update(Kind::MuteList).await? {
+            Some(event) => (event.content().to_string(), event.tags().to_vec()),
+            None => (String::new(), Vec::new()),
+        };
+
+        tags.retain(|t| !is_public_key_tag(t, &public_key));
+
+        let builder = EventBuilder::new(Kind::MuteList, content, tags);
+        self.send_event_builder(builder).await
+    }
+
+    /// Get bookmarks
+    ///
+    /// Private items, encrypted in the event content, are merged with the public ones.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_bookmarks(&self, timeout: Option<Duration>) -> Result<Bookmarks, Error> {
+        match self.get_own_latest_event(Kind::Bookmarks, timeout).await? {
+            Some(event) => {
+                let mut tags: Vec<Tag> = event.tags().to_vec();
+                tags.extend(self.decrypt_private_list_items(&event).await?);
+                Ok(Bookmarks::from_tags(tags.iter()))
+            }
+            None => Ok(Bookmarks::default()),
+        }
+    }
+
+    /// Get pinned notes
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_pinned_notes(&self, timeout: Option<Duration>) -> Result<PinList, Error> {
+        match self.get_own_latest_event(Kind::PinList, timeout).await? {
+            Some(event) => Ok(PinList::from_tags(event.iter_tags())),
+            None => Ok(PinList::default()),
+        }
+    }
+
+    /// Get follow sets
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_follow_sets(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<FollowSet>, Error> {
+        let events: Vec<Event> = self.get_own_events(Kind::FollowSets, timeout).await?;
+        Ok(events
+            .iter()
+            .map(|e| FollowSet::from_tags(e.iter_tags()))
+            .collect())
+    }
+
+    /// Get bookmark sets
+    ///
+    /// Private items, encrypted in the event content, are merged with the public ones.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_bookmark_sets(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<BookmarkSet>, Error> {
+        let events: Vec<Event> = self.get_own_events(Kind::BookmarkSets, timeout).await?;
+        let mut sets: Vec<BookmarkSet> = Vec::with_capacity(events.len());
+        for event in events.into_iter() {
+            let mut tags: Vec<Tag> = event.tags().to_vec();
+            tags.extend(self.decrypt_private_list_items(&event).await?);
+            sets.push(BookmarkSet::from_tags(tags.iter()));
+        }
+        Ok(sets)
+    }
+
+    /// Get interest sets
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_interest_sets(
+        &self,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<InterestSet>, Error> {
+        let events: Vec<Event> = self.get_own_events(Kind::InterestSets, timeout).await?;
+        Ok(events
+            .iter()
+            .map(|e| InterestSet::from_tags(e.iter_tags()))
+            .collect())
+    }
+
+    /// Get emoji sets
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_emoji_sets(&self, timeout: Option<Duration>) -> Result<Vec<EmojiSet>, Error> {
+        let events: Vec<Event> = self.get_own_events(Kind::EmojiSets, timeout).await?;
+        Ok(events
+            .iter()
+            .map(|e| EmojiSet::from_tags(e.iter_tags()))
+            .collect())
+    }
+
+    /// Get interests
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
+    pub async fn get_interests(&self, timeout: Option<Duration>) -> Result<Interests, Error> {
+        match self.get_own_latest_event(Kind::Interests, timeout).await? {
+            Some(event) => Ok(Interests::from_tags(event.iter_tags())),
+            None => Ok(Interests::default()),
+        }
+    }
+
+    /// Send encrypted direct message
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use nostr_sdk::prelude::*;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// #   let my_keys = Keys::generate();
+    /// #   let client = Client::new(&my_keys);
+    /// let alice_pubkey =
+    ///     PublicKey::from_bech32("npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy")
+    ///         .unwrap();
+    ///
+    /// client
+    ///     .send_direct_msg(alice_pubkey, "My first DM fro Nostr SDK!", None)
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "nip04")]
+    pub async fn send_direct_msg<S>(
+        &self,
+        receiver: PublicKey,
+        msg: S,
+        reply_to: Option<EventId>,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let signer = self.signer().await?;
+        let content: String = signer.nip04_encrypt(receiver, msg.into()).await?;
+
+        let mut tags: Vec<Tag> = Vec::with_capacity(1 + usize::from(reply_to.is_some()));
+        tags.push(Tag::public_key(receiver));
+        if let Some(id) = reply_to {
+            tags.push(Tag::event(id));
+        }
+
+        let builder: EventBuilder = EventBuilder::new(Kind::EncryptedDirectMessage, content, tags);
+
+        self.send_event_builder(builder).await
+    }
+
+    /// Repost
     pub async fn repost(
         &self,
         event: &Event,
@@ -1018,12 +2025,58 @@ impl Client {
     /// Delete event
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/09.md>
-    pub async fn delete_event<T>(&self, id: T) -> Result<EventId, Error>
+    pub async fn delete_event(&self, id: EventId) -> Result<EventId, Error> {
+        self.delete_with_reason([id], "").await
+    }
+
+    /// Delete replaceable or parameterized replaceable event by coordinate
+    ///
+    /// All the versions of the event published up to now are deleted.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/09.md>
+    pub async fn delete_coordinate(&self, coordinate: Coordinate) -> Result<EventId, Error> {
+        self.delete_with_reason([coordinate], "").await
+    }
+
+    /// Delete events and/or coordinates with reason
+    ///
+    /// A `k` tag is added for the kind of each deleted coordinate and of each deleted event found in the database.
+    /// The deletion is applied to the database before being sent, so the deleted events
+    /// are immediately removed from local queries.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/09.md>
+    pub async fn delete_with_reason<I, T, S>(&self, targets: I, reason: S) -> Result<EventId, Error>
     where
+        I: IntoIterator<Item = T>,
         T: Into<EventIdOrCoordinate>,
+        S: Into<String>,
     {
-        let builder = EventBuilder::delete([id]);
-        self.send_event_builder(builder).await
+        let database = self.database();
+        let mut tags: Vec<Tag> = Vec::new();
+        let mut kinds: BTreeSet<Kind> = BTreeSet::new();
+        for target in targets.into_iter() {
+            match target.into() {
+                EventIdOrCoordinate::Id(id) => {
+                    if let Ok(event) = database.event_by_id(id).await {
+                        kinds.insert(event.kind());
+                    }
+                    tags.push(Tag::event(id));
+                }
+                EventIdOrCoordinate::Coordinate(coordinate) => {
+                    kinds.insert(coordinate.kind);
+                    tags.push(coordinate.into());
+                }
+            }
+        }
+        tags.extend(kinds.into_iter().map(Tag::Kind));
+
+        let builder = EventBuilder::new(Kind::EventDeletion, reason, tags);
+        let event: Event = self.sign_event_builder(builder).await?;
+
+        // Apply deletion to local indexes without waiting for relays
+        database.save_event(&event).await?;
+
+        published_id(self.send_event(event).await?)
     }
 
     /// Like event
@@ -1105,6 +2158,43 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// React to an [`Event`] with a [`ReactionContent`] (i.e. a NIP30 custom emoji)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/25.md>
+    pub async fn react(&self, event: &Event, reaction: ReactionContent) -> Result<EventId, Error> {
+        let builder = EventBuilder::reaction_extended(
+            event.id(),
+            event.author(),
+            Some(event.kind()),
+            reaction,
+        );
+        self.send_event_builder(builder).await
+    }
+
+    /// Report a public key or, if `event_id` is set, one of its events
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/56.md>
+    pub async fn report<S>(
+        &self,
+        public_key: PublicKey,
+        event_id: Option<EventId>,
+        report: Report,
+        reason: S,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let tags: Vec<Tag> = match event_id {
+            Some(event_id) => vec![
+                Tag::EventReport(event_id, report),
+                Tag::public_key(public_key),
+            ],
+            None => vec![Tag::PubKeyReport(public_key, report)],
+        };
+        let builder = EventBuilder::report(tags, reason);
+        self.send_event_builder(builder).await
+    }
+
     /// Create new channel
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -1142,6 +2232,42 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Reply to a channel message
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub async fn reply_channel_msg<S>(
+        &self,
+        channel_id: EventId,
+        relay_url: Url,
+        reply_to: &Event,
+        msg: S,
+    ) -> Result<EventId, Error>
+    where
+        S: Into<String>,
+    {
+        let builder = EventBuilder::channel_msg_reply(channel_id, relay_url, reply_to, msg);
+        self.send_event_builder(builder).await
+    }
+
+    /// Subscribe to the creation, metadata updates and messages of a channel
+    ///
+    /// Parse the received events with [`nip28`] types (i.e. [`ChannelMessage`]).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub async fn subscribe_to_channel(
+        &self,
+        channel_id: EventId,
+        opts: Option<SubscribeAutoCloseOptions>,
+    ) -> SubscriptionId {
+        let filters: Vec<Filter> = vec![
+            Filter::new()
+                .kinds([Kind::ChannelMetadata, Kind::ChannelMessage])
+                .event(channel_id),
+            Filter::new().kind(Kind::ChannelCreation).id(channel_id),
+        ];
+        self.subscribe(filters, opts).await
+    }
+
     /// Hide channel message
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -1203,7 +2329,7 @@ impl Client {
     /// Send a Zap!
     ///
     /// This method automatically create a split zap to support Rust Nostr development.
-    #[cfg(feature = "nip57")]
+    #[cfg(feature = "lnurl")]
     pub async fn zap<T>(
         &self,
         to: T,
@@ -1231,18 +2357,34 @@ impl Client {
         let public_key: PublicKey = signer.public_key().await?;
         let rumor = rumor.to_unsigned_event(public_key);
 
+        // Compose gift wrap
+        let gift_wrap: Event = self
+            .seal_and_gift_wrap(&signer, receiver, &rumor, expiration)
+            .await?;
+
+        // Send event
+        published_id(self.send_event(gift_wrap).await?)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "nip59")]
+    async fn seal_and_gift_wrap(
+        &self,
+        signer: &NostrSigner,
+        receiver: PublicKey,
+        rumor: &UnsignedEvent,
+        expiration: Option<Timestamp>,
+    ) -> Result<Event, Error> {
         // Compose seal
         let content: String = signer.nip44_encrypt(receiver, rumor.as_json()).await?;
         let seal: EventBuilder = EventBuilder::new(Kind::Seal, content, []);
         let seal: Event = self.sign_event_builder(seal).await?;
 
         // Compose gift wrap
-        let gift_wrap: Event = EventBuilder::gift_wrap_from_seal(&receiver, &seal, expiration)?;
-
-        // Send event
-        self.send_event(gift_wrap).await?;
-
-        Ok(())
+        Ok(EventBuilder::gift_wrap_from_seal(
+            &receiver, &seal, expiration,
+        )?)
     }
 
     /// Send GiftWrapper Sealed Direct message
@@ -1260,6 +2402,50 @@ impl Client {
         self.gift_wrap(receiver, rumor, expiration).await
     }
 
+    /// Send GiftWrapped Sealed Direct message to multiple receivers
+    ///
+    /// The same rumor is gift wrapped for each receiver and for the sender (to sync the other devices),
+    /// and sent to all relays. The delivery status is reported for each receiver and relay.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    #[cfg(feature = "nip59")]
+    pub async fn send_sealed_msg_group<I, S>(
+        &self,
+        receivers: I,
+        message: S,
+        expiration: Option<Timestamp>,
+    ) -> Result<SendPrivateMsgOutput, Error>
+    where
+        I: IntoIterator<Item = PublicKey>,
+        S: Into<String>,
+    {
+        let signer: NostrSigner = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        // Compose rumor
+        let receivers: BTreeSet<PublicKey> =
+            receivers.into_iter().filter(|p| p != &public_key).collect();
+        let rumor: UnsignedEvent =
+            EventBuilder::sealed_direct_group(receivers.iter().copied(), message)
+                .to_unsigned_event(public_key);
+
+        let mut output = SendPrivateMsgOutput::new(ConversationId::from_rumor(&rumor));
+        let urls: Vec<Url> = self.relays().await.into_keys().collect();
+        if urls.is_empty() {
+            return Err(Error::RelayPool(RelayPoolError::NoRelays));
+        }
+
+        for receiver in receivers.into_iter().chain(std::iter::once(public_key)) {
+            let gift_wrap: Event = self
+                .seal_and_gift_wrap(&signer, receiver, &rumor, expiration)
+                .await?;
+            let sent: Output = self.send_event_to(urls.clone(), gift_wrap).await?;
+            output.receivers.insert(receiver, sent);
+        }
+
+        Ok(output)
+    }
+
     /// File metadata
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/94.md>
@@ -1275,10 +2461,160 @@ impl Client {
         self.send_event_builder(builder).await
     }
 
+    /// Subscribe to the chat of a live event
+    ///
+    /// `live_event` is the coordinate of the live event (i.e. parsed from an `naddr`).
+    /// Both the chat messages (kind `1311`) and the updates of the live event itself are received.
+    /// Use [`LiveEventMessage::try_from`] to parse the messages.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/53.md>
+    pub async fn live_chat(
+        &self,
+        live_event: &Coordinate,
+        limit: Option<usize>,
+    ) -> Result<SubscriptionId, Error> {
+        if live_event.kind != Kind::LiveEvent {
+            return Err(Error::NIP53(nip53::Error::WrongKind));
+        }
+
+        let mut chat: Filter = Filter::new().kind(Kind::LiveEventMessage).custom_tag(
+            SingleLetterTag::lowercase(Alphabet::A),
+            [live_event.to_string()],
+        );
+        if let Some(limit) = limit {
+            chat = chat.limit(limit);
+        }
+
+        Ok(self
+            .subscribe(vec![chat, Filter::from(live_event.clone())], None)
+            .await)
+    }
+
+    /// Get the badges displayed on a profile, using the events stored in the database
+    ///
+    /// The badge awards and definitions referenced by the latest profile badges event are
+    /// validated with [`nip58::resolve_profile_badges`]. Return an empty list if the
+    /// profile badges event is not stored.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/58.md>
+    pub async fn profile_badges(&self, public_key: PublicKey) -> Result<Vec<ProfileBadge>, Error> {
+        let filter: Filter = Filter::new()
+            .author(public_key)
+            .kind(Kind::ProfileBadges)
+            .identifier(nip58::PROFILE_BADGES_IDENTIFIER)
+            .limit(1);
+        let events: Vec<Event> = self.database().query(vec![filter], Order::Desc).await?;
+        let profile_badges: &Event = match events.first() {
+            Some(event) => event,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut award_ids: Vec<EventId> = Vec::new();
+        let mut issuers: Vec<PublicKey> = Vec::new();
+        let mut identifiers: Vec<String> = Vec::new();
+        for tag in profile_badges.iter_tags() {
+            match tag {
+                Tag::A { coordinate, .. } if coordinate.kind == Kind::BadgeDefinition => {
+                    issuers.push(coordinate.public_key);
+                    identifiers.push(coordinate.identifier.clone());
+                }
+                Tag::Event { event_id, .. } => award_ids.push(*event_id),
+                _ => (),
+            }
+        }
+
+        if award_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let awards: Vec<Event> = self
+            .database()
+            .query(
+                vec![Filter::new().ids(award_ids).kind(Kind::BadgeAward)],
+                Order::Desc,
+            )
+            .await?;
+        let definitions: Vec<Event> = self
+            .database()
+            .query(
+                vec![Filter::new()
+                    .kind(Kind::BadgeDefinition)
+                    .authors(issuers)
+                    .identifiers(identifiers)],
+                Order::Desc,
+            )
+            .await?;
+
+        Ok(nip58::resolve_profile_badges(
+            profile_badges,
+            &definitions,
+            &awards,
+        )?)
+    }
+
+    /// Data Vending Machine - Send a job request and wait for the result
+    ///
+    /// Job feedback events are ignored, unless the service provider reports an `error` status.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/90.md>
+    pub async fn request_job(
+        &self,
+        request: JobRequest,
+        timeout: Duration,
+    ) -> Result<JobResult, Error> {
+        let result_kind: Kind = request.result_kind();
+        let builder = EventBuilder::job_request(request.kind, Vec::<Tag>::from(request))?;
+        let event: Event = self.sign_event_builder(builder).await?;
+        let request_id: EventId = event.id();
+
+        // Subscribe before sending the request to not miss fast responses
+        let mut notifications = self.notifications();
+        let id = SubscriptionId::generate();
+        let filter: Filter = Filter::new()
+            .kinds([result_kind, Kind::JobFeedback])
+            .event(request_id);
+        self.subscribe_with_id(id.clone(), vec![filter], None).await;
+
+        let res = time::timeout(Some(timeout), async {
+            published_id(self.send_event(event).await?)?;
+
+            while let Ok(notification) = notifications.recv().await {
+                if let RelayPoolNotification::Event { event, .. } = notification {
+                    if !event.event_ids().any(|id| id == &request_id) {
+                        continue;
+                    }
+
+                    if event.kind() == result_kind {
+                        return Ok(JobResult::try_from(event.as_ref())?);
+                    }
+
+                    if event.kind() == Kind::JobFeedback {
+                        if let Ok(feedback) = JobFeedback::try_from(event.as_ref()) {
+                            if feedback.status == DataVendingMachineStatus::Error {
+                                return Err(Error::JobFailed(
+                                    feedback.extra_info.unwrap_or_default(),
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+
+            Err(Error::JobResultNotFound)
+        })
+        .await
+        .ok_or(Error::Timeout);
+
+        self.unsubscribe(id).await;
+
+        res?
+    }
+
     /// Negentropy reconciliation
     ///
     /// <https://github.com/hoytech/negentropy>
     pub async fn reconcile(&self, filter: Filter, opts: NegentropyOptions) -> Result<(), Error> {
+        let filter: Filter = apply_floor(filter, self.opts.get_query_floor());
         Ok(self.pool.reconcile(filter, opts).await?)
     }
 
@@ -1289,15 +2625,230 @@ impl Client {
         items: Vec<(EventId, Timestamp)>,
         opts: NegentropyOptions,
     ) -> Result<(), Error> {
+        let filter: Filter = apply_floor(filter, self.opts.get_query_floor());
         Ok(self.pool.reconcile_with_items(filter, items, opts).await?)
     }
 
+    /// Get the dispatch filter of [`Client::handle_notifications`]
+    ///
+    /// Changes are applied at runtime.
+    #[inline]
+    pub fn dispatch_filter(&self) -> DispatchFilter {
+        self.dispatch.clone()
+    }
+
     /// Handle notifications
+    ///
+    /// Event notifications not allowed by the [`DispatchFilter`] are skipped.
     pub async fn handle_notifications<F, Fut>(&self, func: F) -> Result<(), Error>
     where
         F: Fn(RelayPoolNotification) -> Fut,
         Fut: Future<Output = Result<bool>>,
     {
-        Ok(self.pool.handle_notifications(func).await?)
+        let func = &func;
+        Ok(self
+            .pool
+            .handle_notifications(|notification| async move {
+                if let RelayPoolNotification::Event { event, .. } = &notification {
+                    if !self.dispatch.is_dispatched(event).await {
+                        return Ok(false);
+                    }
+                    if let Some(prefetcher) = &self.prefetcher {
+                        prefetcher.schedule(&self.pool, [event.as_ref()]).await;
+                    }
+                    if self.opts.get_metadata_backfill() {
+                        self.backfill_metadata([event.author()]).await;
+                    }
+                }
+                func(notification).await
+            })
+            .await?)
+    }
+}
+
+#[cfg(feature = "nip05")]
+fn new_nip05_resolver(opts: &Options) -> Nip05Resolver {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(proxy) = opts.proxy {
+        match Nip05Resolver::with_proxy(proxy) {
+            Ok(resolver) => return resolver,
+            Err(e) => tracing::error!("Impossible to build NIP05 resolver with proxy: {e}"),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    let _ = opts;
+
+    Nip05Resolver::new()
+}
+
+/// Parse relay hints, skipping the invalid ones
+pub(crate) fn parse_relay_hints(relays: &[String]) -> Vec<Url> {
+    let mut urls: Vec<Url> = Vec::with_capacity(relays.len());
+    for relay in relays.iter() {
+        match Url::parse(relay) {
+            Ok(url) => urls.push(url),
+            Err(e) => tracing::warn!("Invalid relay hint '{relay}': {e}"),
+        }
+    }
+    urls
+}
+
+fn is_public_key_tag(tag: &Tag, public_key: &PublicKey) -> bool {
+    matches!(
+        tag,
+        Tag::PublicKey {
+            public_key: p,
+            uppercase: false,
+            ..
+        } if p == public_key
+    )
+}
+
+/// Get the ID of the published event, or an error if no relay accepted it
+/// Flag of a background task, cleared when the task exits (even if it panics or is cancelled)
+struct TaskGuard(Arc<AtomicBool>);
+
+impl TaskGuard {
+    /// Set the flag, returning `None` if the task is already running
+    fn acquire(running: &Arc<AtomicBool>) -> Option<Self> {
+        if running.swap(true, Ordering::SeqCst) {
+            None
+        } else {
+            Some(Self(running.clone()))
+        }
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+pub(crate) fn published_id(output: Output) -> Result<EventId, Error> {
+    output
+        .id()
+        .ok_or(Error::RelayPool(RelayPoolError::EventNotPublished))
+}
+
+/// Set `since` of the unbounded filter to the floor timestamp
+///
+/// Filters with `ids`, `since` or `limit` and lookups of replaceable events (i.e. coordinates) are returned unchanged.
+fn apply_floor(filter: Filter, floor: Option<Timestamp>) -> Filter {
+    let floor: Timestamp = match floor {
+        Some(floor) => floor,
+        None => return filter,
+    };
+
+    if filter.ids.is_some() || filter.since.is_some() || filter.limit.is_some() {
+        return filter;
+    }
+
+    let replaceable: bool = filter.kinds.as_ref().map_or(false, |kinds| {
+        !kinds.is_empty()
+            && kinds
+                .iter()
+                .all(|k| k.is_replaceable() || k.is_parameterized_replaceable())
+    });
+    if replaceable {
+        return filter;
+    }
+
+    filter.since(floor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_protected() {
+        let keys = Keys::generate();
+        let client = Client::new(&keys);
+
+        let own = EventBuilder::text_note("Mine", [])
+            .protected()
+            .to_event(&keys)
+            .unwrap();
+        assert!(client.check_protected([&own]).await.is_ok());
+
+        // Not protected events of other authors can be broadcast
+        let unprotected = EventBuilder::text_note("Not protected", [])
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert!(client.check_protected([&unprotected]).await.is_ok());
+
+        // Protected event of another author
+        let other = EventBuilder::text_note("Not mine", [])
+            .protected()
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert!(matches!(
+            client.check_protected([&own, &other]).await,
+            Err(Error::ProtectedEvent(id)) if id == other.id()
+        ));
+        assert!(matches!(
+            client.send_event(other.clone()).await,
+            Err(Error::ProtectedEvent(id)) if id == other.id()
+        ));
+
+        // Without signer, every protected event is refused
+        let client = Client::default();
+        assert!(matches!(
+            client.check_protected([&own]).await,
+            Err(Error::ProtectedEvent(id)) if id == own.id()
+        ));
+    }
+
+    #[test]
+    fn test_task_guard() {
+        let running = Arc::new(AtomicBool::new(false));
+
+        let guard = TaskGuard::acquire(&running).unwrap();
+        assert!(TaskGuard::acquire(&running).is_none());
+
+        drop(guard);
+        assert!(!running.load(Ordering::SeqCst));
+        assert!(TaskGuard::acquire(&running).is_some());
+    }
+
+    #[test]
+    fn test_apply_floor() {
+        let floor = Timestamp::GENESIS;
+        let keys = Keys::generate();
+
+        // Disabled by default
+        let filter = Filter::new().kind(Kind::TextNote);
+        assert_eq!(Options::default().get_query_floor(), None);
+        assert_eq!(apply_floor(filter.clone(), None), filter);
+
+        // Unbounded
+        assert_eq!(
+            apply_floor(filter.clone(), Some(floor)),
+            filter.since(floor)
+        );
+
+        // Id lookups
+        let id = EventId::all_zeros();
+        let filter = Filter::new().id(id);
+        assert_eq!(apply_floor(filter.clone(), Some(floor)), filter);
+
+        // Coordinate lookups
+        let filter = Filter::new()
+            .kind(Kind::ParameterizedReplaceable(30000))
+            .author(keys.public_key())
+            .identifier("list");
+        assert_eq!(apply_floor(filter.clone(), Some(floor)), filter);
+        let filter = Filter::new().kind(Kind::Metadata).author(keys.public_key());
+        assert_eq!(apply_floor(filter.clone(), Some(floor)), filter);
+
+        // Bounded
+        let filter = Filter::new()
+            .kind(Kind::TextNote)
+            .since(Timestamp::from(10));
+        assert_eq!(apply_floor(filter.clone(), Some(floor)), filter);
+        let filter = Filter::new().kind(Kind::TextNote).limit(10);
+        assert_eq!(apply_floor(filter.clone(), Some(floor)), filter);
     }
 }
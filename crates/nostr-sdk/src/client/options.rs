@@ -4,14 +4,21 @@
 
 //! Client Options
 
+use std::collections::HashMap;
 #[cfg(not(target_arch = "wasm32"))]
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use nostr::{EventLimits, Kind, Timestamp};
 use nostr_relay_pool::relay::options::DEFAULT_SEND_TIMEOUT;
-use nostr_relay_pool::{RelayLimits, RelayPoolOptions, RelaySendOptions};
+use nostr_relay_pool::{
+    RelayLimits, RelayPoolOptions, RelaySendOptions, VerificationPolicy, VerifyMode,
+};
+
+#[cfg(feature = "nip03")]
+use super::AttestedOrder;
 
 /// Options
 #[derive(Debug, Clone)]
@@ -21,6 +28,11 @@ pub struct Options {
     /// Wait for the subscription msg to be sent (default: false)
     wait_for_subscription: Arc<AtomicBool>,
     new_events_difficulty: Arc<AtomicU8>,
+    difficulty_per_kind: HashMap<Kind, u8>,
+    /// Max POW difficulty to re-mine events rejected with `pow:` (default: 0, disabled)
+    max_pow_escalation: u8,
+    #[cfg(feature = "pow-multi-thread")]
+    pow_threads: usize,
     min_pow_difficulty: Arc<AtomicU8>,
     /// REQ filters chunk size (default: 10)
     req_filters_chunk_size: Arc<AtomicU8>,
@@ -38,11 +50,36 @@ pub struct Options {
     pub connection_timeout: Option<Duration>,
     /// Send timeout (default: 20 secs)
     pub send_timeout: Option<Duration>,
+    /// Metadata cache TTL (default: 1 hour)
+    ///
+    /// Used in `fetch_metadata` to decide when the metadata stored in the database must be refreshed.
+    pub metadata_cache_ttl: Duration,
+    /// Prefetch referenced events and profiles (default: false)
+    prefetch: bool,
+    /// Max concurrent prefetch requests (default: 4)
+    prefetch_concurrency: usize,
+    /// Backfill metadata of feed authors (default: false)
+    metadata_backfill: bool,
+    /// Max authors per metadata backfill request (default: 100)
+    metadata_backfill_batch_size: usize,
+    /// Min interval between metadata backfill requests (default: 1 sec)
+    metadata_backfill_interval: Duration,
+    /// State checkpoint interval (default: None)
+    checkpoint_interval: Option<Duration>,
+    /// Persist delivery records (default: false)
+    delivery_records: bool,
+    /// Min timestamp of the unbounded queries (default: None)
+    query_floor: Option<Timestamp>,
+    /// Order the query results by attested time (default: None)
+    #[cfg(feature = "nip03")]
+    attested_order: Option<AttestedOrder>,
     /// Proxy
     #[cfg(not(target_arch = "wasm32"))]
     pub proxy: Option<SocketAddr>,
     /// Default limits for new added relays
     pub relay_limits: RelayLimits,
+    /// Limits of the events built by the client
+    event_limits: EventLimits,
     /// Pool Options
     pub pool: RelayPoolOptions,
 }
@@ -53,15 +90,31 @@ impl Default for Options {
             wait_for_send: Arc::new(AtomicBool::new(true)),
             wait_for_subscription: Arc::new(AtomicBool::new(false)),
             new_events_difficulty: Arc::new(AtomicU8::new(0)),
+            difficulty_per_kind: HashMap::new(),
+            max_pow_escalation: 0,
+            #[cfg(feature = "pow-multi-thread")]
+            pow_threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
             min_pow_difficulty: Arc::new(AtomicU8::new(0)),
             req_filters_chunk_size: Arc::new(AtomicU8::new(10)),
             skip_disconnected_relays: Arc::new(AtomicBool::new(true)),
             timeout: Duration::from_secs(60),
             connection_timeout: None,
             send_timeout: Some(DEFAULT_SEND_TIMEOUT),
+            metadata_cache_ttl: Duration::from_secs(60 * 60),
+            prefetch: false,
+            prefetch_concurrency: 4,
+            metadata_backfill: false,
+            metadata_backfill_batch_size: 100,
+            metadata_backfill_interval: Duration::from_secs(1),
+            checkpoint_interval: None,
+            delivery_records: false,
+            query_floor: None,
+            #[cfg(feature = "nip03")]
+            attested_order: None,
             #[cfg(not(target_arch = "wasm32"))]
             proxy: None,
             relay_limits: RelayLimits::default(),
+            event_limits: EventLimits::default(),
             pool: RelayPoolOptions::default(),
         }
     }
@@ -124,6 +177,46 @@ impl Options {
             .store(difficulty, Ordering::SeqCst);
     }
 
+    /// Set POW difficulty for events of a specific [`Kind`]
+    ///
+    /// Override the default difficulty set with [`Options::difficulty`].
+    pub fn difficulty_for_kind(mut self, kind: Kind, difficulty: u8) -> Self {
+        self.difficulty_per_kind.insert(kind, difficulty);
+        self
+    }
+
+    pub(crate) fn get_difficulty_for_kind(&self, kind: &Kind) -> u8 {
+        match self.difficulty_per_kind.get(kind) {
+            Some(difficulty) => *difficulty,
+            None => self.get_difficulty(),
+        }
+    }
+
+    /// Re-mine events rejected by a relay with `pow:` (default: 0, disabled)
+    ///
+    /// When a relay rejects an event with not enough POW, the event is re-mined at the difficulty
+    /// required by the relay, if not greater than `max_difficulty`, and sent again only to that relay.
+    pub fn pow_escalation(mut self, max_difficulty: u8) -> Self {
+        self.max_pow_escalation = max_difficulty;
+        self
+    }
+
+    pub(crate) fn get_max_pow_escalation(&self) -> u8 {
+        self.max_pow_escalation
+    }
+
+    /// Number of threads used to mine POW events (default: available parallelism)
+    #[cfg(feature = "pow-multi-thread")]
+    pub fn pow_threads(mut self, threads: usize) -> Self {
+        self.pow_threads = threads;
+        self
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "pow-multi-thread"))]
+    pub(crate) fn get_pow_threads(&self) -> usize {
+        self.pow_threads
+    }
+
     /// Minimum POW difficulty for received events
     pub fn min_pow(self, difficulty: u8) -> Self {
         Self {
@@ -188,6 +281,124 @@ impl Options {
         }
     }
 
+    /// Set metadata cache TTL
+    pub fn metadata_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.metadata_cache_ttl = ttl;
+        self
+    }
+
+    /// Prefetch referenced events and profiles (default: false)
+    ///
+    /// If enabled, the events and profiles referenced by the events received in `get_events_of`
+    /// and `handle_notifications`, and missing in the database, are fetched in background.
+    pub fn prefetch(mut self, enable: bool) -> Self {
+        self.prefetch = enable;
+        self
+    }
+
+    pub(crate) fn get_prefetch(&self) -> bool {
+        self.prefetch
+    }
+
+    /// Max concurrent prefetch requests (default: 4)
+    pub fn prefetch_concurrency(mut self, concurrency: usize) -> Self {
+        self.prefetch_concurrency = concurrency;
+        self
+    }
+
+    pub(crate) fn get_prefetch_concurrency(&self) -> usize {
+        self.prefetch_concurrency
+    }
+
+    /// Backfill profiles and relay lists of feed authors (default: false)
+    ///
+    /// If enabled, the authors of the events received in `get_events_of` and `handle_notifications`
+    /// are queued for [`Client::backfill_metadata`](super::Client::backfill_metadata).
+    pub fn metadata_backfill(mut self, enable: bool) -> Self {
+        self.metadata_backfill = enable;
+        self
+    }
+
+    pub(crate) fn get_metadata_backfill(&self) -> bool {
+        self.metadata_backfill
+    }
+
+    /// Max authors per metadata backfill request (default: 100)
+    pub fn metadata_backfill_batch_size(mut self, size: usize) -> Self {
+        self.metadata_backfill_batch_size = size;
+        self
+    }
+
+    pub(crate) fn get_metadata_backfill_batch_size(&self) -> usize {
+        self.metadata_backfill_batch_size
+    }
+
+    /// Min interval between metadata backfill requests, for the whole client (default: 1 sec)
+    pub fn metadata_backfill_interval(mut self, interval: Duration) -> Self {
+        self.metadata_backfill_interval = interval;
+        self
+    }
+
+    pub(crate) fn get_metadata_backfill_interval(&self) -> Duration {
+        self.metadata_backfill_interval
+    }
+
+    /// Periodically checkpoint the adaptive state into the database (default: disabled)
+    ///
+    /// Relay stats, subscription cursors and prefetch dedup caches are saved every `interval`
+    /// after [`Client::connect`](super::Client::connect).
+    /// Call [`Client::restore_checkpoint`](super::Client::restore_checkpoint) at startup to recover them.
+    pub fn checkpoint_interval(mut self, interval: Duration) -> Self {
+        self.checkpoint_interval = Some(interval);
+        self
+    }
+
+    pub(crate) fn get_checkpoint_interval(&self) -> Option<Duration> {
+        self.checkpoint_interval
+    }
+
+    /// Persist the delivery records (`OK` messages) of the sent events into the database (default: false)
+    ///
+    /// Recording starts at [`Client::connect`](super::Client::connect).
+    /// Use [`Client::delivery_status`](super::Client::delivery_status) to audit them.
+    pub fn delivery_records(mut self, enable: bool) -> Self {
+        self.delivery_records = enable;
+        self
+    }
+
+    pub(crate) fn get_delivery_records(&self) -> bool {
+        self.delivery_records
+    }
+
+    /// Min timestamp of the unbounded queries of `get_events_of`, `subscribe` and similar methods (default: None)
+    ///
+    /// Set as `since` of the filters without `ids`, `since` and `limit`, to bound the backfill scans (i.e. [`Timestamp::GENESIS`]).
+    /// Lookups of replaceable events are left unchanged, since their latest version can be older than the floor.
+    /// To query older events with a floor set, use an explicit `since` in the filter.
+    pub fn query_floor(mut self, floor: Option<Timestamp>) -> Self {
+        self.query_floor = floor;
+        self
+    }
+
+    pub(crate) fn get_query_floor(&self) -> Option<Timestamp> {
+        self.query_floor
+    }
+
+    /// Order the results of `get_events_of` and `get_events_from` by attested time (NIP03) when available (default: None)
+    ///
+    /// Events without a verified OpenTimestamps attestation fall back to `created_at`.
+    /// Costs an additional request for the attestations (kind `1040`).
+    #[cfg(feature = "nip03")]
+    pub fn attested_order(mut self, order: Option<AttestedOrder>) -> Self {
+        self.attested_order = order;
+        self
+    }
+
+    #[cfg(feature = "nip03")]
+    pub(crate) fn get_attested_order(&self) -> Option<&AttestedOrder> {
+        self.attested_order.as_ref()
+    }
+
     /// Proxy
     #[cfg(not(target_arch = "wasm32"))]
     pub fn proxy(mut self, proxy: Option<SocketAddr>) -> Self {
@@ -201,6 +412,34 @@ impl Options {
         self
     }
 
+    /// Set the size and shape limits of the events built by the client (default: [`EventLimits::default`])
+    ///
+    /// Checked before signing an [`EventBuilder`](nostr::EventBuilder).
+    pub fn event_limits(mut self, limits: EventLimits) -> Self {
+        self.event_limits = limits;
+        self
+    }
+
+    pub(crate) fn get_event_limits(&self) -> &EventLimits {
+        &self.event_limits
+    }
+
+    /// Verification of received events (default: [`VerifyMode::Full`])
+    ///
+    /// Shortcut for [`RelayPoolOptions::verify_events`].
+    pub fn verify_events(mut self, mode: VerifyMode) -> Self {
+        self.pool = self.pool.verify_events(mode);
+        self
+    }
+
+    /// Where events are verified (default: [`VerificationPolicy::VerifyFromRelaysOnly`])
+    ///
+    /// Shortcut for [`RelayPoolOptions::verification_policy`].
+    pub fn verification_policy(mut self, policy: VerificationPolicy) -> Self {
+        self.pool = self.pool.verification_policy(policy);
+        self
+    }
+
     /// Set pool options
     pub fn pool(self, opts: RelayPoolOptions) -> Self {
         Self { pool: opts, ..self }
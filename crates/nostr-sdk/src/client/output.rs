@@ -0,0 +1,30 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Send output
+
+use std::collections::HashMap;
+
+use nostr::nips::nip17::ConversationId;
+use nostr::PublicKey;
+use nostr_relay_pool::Output;
+
+/// Send private message output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendPrivateMsgOutput {
+    /// Conversation ID
+    pub conversation_id: ConversationId,
+    /// Gift wrap delivery status, per receiver (the sender included)
+    pub receivers: HashMap<PublicKey, Output>,
+}
+
+impl SendPrivateMsgOutput {
+    #[cfg(feature = "nip59")]
+    pub(crate) fn new(conversation_id: ConversationId) -> Self {
+        Self {
+            conversation_id,
+            receivers: HashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Prefetch of referenced events and profiles
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::serde_json::{self, json, Value};
+use nostr::{Event, EventId, Filter, Kind, PublicKey};
+use nostr_database::{DynNostrDatabase, Order};
+use nostr_relay_pool::runtime::thread;
+use nostr_relay_pool::{FilterOptions, RelayPool};
+use tokio::sync::{Mutex, Semaphore};
+
+/// Max number of referents remembered to deduplicate requests
+const MAX_REQUESTED: usize = 10_000;
+
+/// Database key of the prefetch dedup caches checkpoint
+pub(crate) const CHECKPOINT_KEY: &str = "nostr-sdk/prefetch";
+const CHECKPOINT_VERSION: u64 = 1;
+
+/// Schedule background fetch of events and profiles referenced by other events
+#[derive(Debug, Clone)]
+pub(crate) struct Prefetcher {
+    semaphore: Arc<Semaphore>,
+    requested_ids: Arc<Mutex<HashSet<EventId>>>,
+    requested_public_keys: Arc<Mutex<HashSet<PublicKey>>>,
+    timeout: Duration,
+}
+
+impl Prefetcher {
+    pub fn new(concurrency: usize, timeout: Duration) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(concurrency.max(1))),
+            requested_ids: Arc::new(Mutex::new(HashSet::new())),
+            requested_public_keys: Arc::new(Mutex::new(HashSet::new())),
+            timeout,
+        }
+    }
+
+    /// Schedule the fetch of the events (`e` tags) and profiles (authors and `p` tags)
+    /// referenced by `events` and missing in the database
+    ///
+    /// Referents already requested are skipped.
+    pub async fn schedule<'a, I>(&self, pool: &RelayPool, events: I)
+    where
+        I: IntoIterator<Item = &'a Event>,
+    {
+        let mut ids: HashSet<EventId> = HashSet::new();
+        let mut public_keys: HashSet<PublicKey> = HashSet::new();
+        for event in events.into_iter() {
+            ids.extend(event.event_ids().copied());
+            public_keys.insert(event.author());
+            public_keys.extend(event.public_keys().copied());
+        }
+
+        // Dedup
+        let ids: Vec<EventId> = dedup(&self.requested_ids, ids).await;
+        let public_keys: Vec<PublicKey> = dedup(&self.requested_public_keys, public_keys).await;
+
+        if ids.is_empty() && public_keys.is_empty() {
+            return;
+        }
+
+        let pool: RelayPool = pool.clone();
+        let semaphore: Arc<Semaphore> = self.semaphore.clone();
+        let timeout: Duration = self.timeout;
+        let _ = thread::spawn(async move {
+            let _permit = match semaphore.acquire_owned().await {
+                Ok(permit) => permit,
+                Err(_) => return,
+            };
+
+            let database: Arc<DynNostrDatabase> = pool.database();
+            let filters: Vec<Filter> = missing_filters(&database, ids, public_keys).await;
+
+            if filters.is_empty() {
+                return;
+            }
+
+            // Received events are saved into the database by the pool
+            if let Err(e) = pool
+                .get_events_of(filters, timeout, FilterOptions::ExitOnEOSE)
+                .await
+            {
+                tracing::warn!("Impossible to prefetch referenced events: {e}");
+            }
+        });
+    }
+
+    /// Serialize the dedup caches
+    pub async fn checkpoint(&self) -> Vec<u8> {
+        let ids: Vec<String> = self
+            .requested_ids
+            .lock()
+            .await
+            .iter()
+            .map(|id| id.to_hex())
+            .collect();
+        let public_keys: Vec<String> = self
+            .requested_public_keys
+            .lock()
+            .await
+            .iter()
+            .map(|p| p.to_hex())
+            .collect();
+        json!({
+            "version": CHECKPOINT_VERSION,
+            "ids": ids,
+            "public_keys": public_keys,
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Restore the dedup caches
+    ///
+    /// Return `false` if the checkpoint is corrupted.
+    pub async fn restore(&self, bytes: &[u8]) -> bool {
+        let value: Value = match serde_json::from_slice(bytes) {
+            Ok(value) => value,
+            Err(_) => return false,
+        };
+        if value.get("version").and_then(Value::as_u64) != Some(CHECKPOINT_VERSION) {
+            return false;
+        }
+
+        let ids: HashSet<EventId> = parse_list(&value, "ids", |s| EventId::from_hex(s).ok());
+        let public_keys: HashSet<PublicKey> =
+            parse_list(&value, "public_keys", |s| PublicKey::from_hex(s).ok());
+
+        dedup(&self.requested_ids, ids).await;
+        dedup(&self.requested_public_keys, public_keys).await;
+        true
+    }
+}
+
+fn parse_list<T, F>(value: &Value, field: &str, parse: F) -> HashSet<T>
+where
+    T: Eq + std::hash::Hash,
+    F: Fn(&str) -> Option<T>,
+{
+    value
+        .get(field)
+        .and_then(Value::as_array)
+        .map(|list| {
+            list.iter()
+                .filter_map(Value::as_str)
+                .filter_map(&parse)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Keep only the items not already requested and mark them as requested
+async fn dedup<T>(requested: &Mutex<HashSet<T>>, items: HashSet<T>) -> Vec<T>
+where
+    T: Copy + Eq + std::hash::Hash,
+{
+    let mut requested = requested.lock().await;
+    if requested.len() + items.len() > MAX_REQUESTED {
+        requested.clear();
+    }
+    items.into_iter().filter(|i| requested.insert(*i)).collect()
+}
+
+/// Compose filters for referents not stored in the database
+async fn missing_filters(
+    database: &Arc<DynNostrDatabase>,
+    ids: Vec<EventId>,
+    public_keys: Vec<PublicKey>,
+) -> Vec<Filter> {
+    let mut filters: Vec<Filter> = Vec::with_capacity(2);
+
+    let mut missing_ids: Vec<EventId> = Vec::with_capacity(ids.len());
+    for id in ids.into_iter() {
+        let saved: bool = database
+            .has_event_already_been_saved(&id)
+            .await
+            .unwrap_or_default();
+        if !saved {
+            missing_ids.push(id);
+        }
+    }
+    if !missing_ids.is_empty() {
+        filters.push(Filter::new().ids(missing_ids));
+    }
+
+    if !public_keys.is_empty() {
+        let filter: Filter = Filter::new()
+            .authors(public_keys.iter().copied())
+            .kind(Kind::Metadata);
+        let stored: HashSet<PublicKey> = database
+            .query(vec![filter], Order::Desc)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| e.author())
+            .collect();
+        let missing: Vec<PublicKey> = public_keys
+            .into_iter()
+            .filter(|p| !stored.contains(p))
+            .collect();
+        if !missing.is_empty() {
+            filters.push(Filter::new().authors(missing).kind(Kind::Metadata));
+        }
+    }
+
+    filters
+}
@@ -0,0 +1,435 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Presence and typing indicators
+//!
+//! Published as ephemeral events (not stored by relays), with an `expiration` tag (NIP40):
+//! * presence: content is the status (`online`, `away` or `offline`);
+//! * typing: content is `typing` or `stopped`, tagged with the counterparty (`p` tag).
+//!
+//! Outgoing events are throttled, incoming ones expire if not refreshed before the TTL.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr_relay_pool::runtime::thread;
+use nostr_relay_pool::RelayPoolNotification;
+use tokio::sync::{broadcast, RwLock};
+
+use super::{Client, Error};
+
+const TYPING: &str = "typing";
+const STOPPED: &str = "stopped";
+
+/// Presence status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresenceStatus {
+    /// Online
+    Online,
+    /// Away
+    Away,
+    /// Offline
+    Offline,
+}
+
+impl fmt::Display for PresenceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Online => write!(f, "online"),
+            Self::Away => write!(f, "away"),
+            Self::Offline => write!(f, "offline"),
+        }
+    }
+}
+
+impl FromStr for PresenceStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "online" => Ok(Self::Online),
+            "away" => Ok(Self::Away),
+            "offline" => Ok(Self::Offline),
+            s => Err(Error::UnknownPresenceStatus(s.to_string())),
+        }
+    }
+}
+
+/// Presence options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresenceOptions {
+    presence_kind: Kind,
+    typing_kind: Kind,
+    throttle: Duration,
+    typing_ttl: Duration,
+    presence_ttl: Duration,
+}
+
+impl Default for PresenceOptions {
+    fn default() -> Self {
+        Self {
+            presence_kind: Kind::Ephemeral(20_400),
+            typing_kind: Kind::Ephemeral(20_401),
+            throttle: Duration::from_secs(3),
+            typing_ttl: Duration::from_secs(10),
+            presence_ttl: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+impl PresenceOptions {
+    /// New default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ephemeral kind of the presence events (default: `20400`)
+    pub fn presence_kind(self, kind: Kind) -> Self {
+        Self {
+            presence_kind: kind,
+            ..self
+        }
+    }
+
+    /// Ephemeral kind of the typing events (default: `20401`)
+    pub fn typing_kind(self, kind: Kind) -> Self {
+        Self {
+            typing_kind: kind,
+            ..self
+        }
+    }
+
+    /// Min interval between two outgoing events of the same type and target (default: 3 secs)
+    pub fn throttle(self, throttle: Duration) -> Self {
+        Self { throttle, ..self }
+    }
+
+    /// Time after which a typing indicator expires if not refreshed (default: 10 secs)
+    pub fn typing_ttl(self, ttl: Duration) -> Self {
+        Self {
+            typing_ttl: ttl,
+            ..self
+        }
+    }
+
+    /// Time after which a presence status expires if not refreshed (default: 5 min)
+    pub fn presence_ttl(self, ttl: Duration) -> Self {
+        Self {
+            presence_ttl: ttl,
+            ..self
+        }
+    }
+}
+
+/// Presence notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresenceNotification {
+    /// Presence status changed
+    ///
+    /// Expired statuses are notified as [`PresenceStatus::Offline`].
+    Status {
+        /// Public key
+        public_key: PublicKey,
+        /// Status
+        status: PresenceStatus,
+    },
+    /// Typing indicator changed
+    Typing {
+        /// Public key of the user typing
+        public_key: PublicKey,
+        /// `false` if stopped or expired
+        typing: bool,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Throttle {
+    Status,
+    Typing(PublicKey),
+}
+
+#[derive(Debug, Default)]
+struct State {
+    sent: HashMap<Throttle, (Timestamp, String)>,
+    statuses: HashMap<PublicKey, (PresenceStatus, Timestamp)>,
+    typing: HashMap<PublicKey, Timestamp>,
+}
+
+/// Presence and typing indicators
+#[derive(Debug, Clone)]
+pub struct Presence {
+    client: Client,
+    opts: PresenceOptions,
+    state: Arc<RwLock<State>>,
+    notifications: broadcast::Sender<PresenceNotification>,
+}
+
+impl Presence {
+    /// New presence and typing indicators
+    pub fn new(client: &Client, opts: PresenceOptions) -> Self {
+        let (notifications, ..) = broadcast::channel(1024);
+        Self {
+            client: client.clone(),
+            opts,
+            state: Arc::new(RwLock::new(State::default())),
+            notifications,
+        }
+    }
+
+    /// Presence event builder
+    pub fn status_event(&self, status: PresenceStatus) -> EventBuilder {
+        EventBuilder::new(
+            self.opts.presence_kind,
+            status.to_string(),
+            [Tag::Expiration(Timestamp::now() + self.opts.presence_ttl)],
+        )
+    }
+
+    /// Typing event builder
+    pub fn typing_event(&self, conversation: PublicKey, typing: bool) -> EventBuilder {
+        EventBuilder::new(
+            self.opts.typing_kind,
+            if typing { TYPING } else { STOPPED },
+            [
+                Tag::public_key(conversation),
+                Tag::Expiration(Timestamp::now() + self.opts.typing_ttl),
+            ],
+        )
+    }
+
+    /// Publish the presence status
+    ///
+    /// Return `None` if throttled (same status sent less than [`PresenceOptions::throttle`] ago).
+    pub async fn set_status(&self, status: PresenceStatus) -> Result<Option<EventId>, Error> {
+        let builder: EventBuilder = self.status_event(status);
+        self.send_throttled(Throttle::Status, status.to_string(), builder)
+            .await
+    }
+
+    /// Publish the typing indicator for a conversation
+    ///
+    /// Call on each keystroke: the event is sent at most once per [`PresenceOptions::throttle`],
+    /// so the counterparty sees a steady indicator. Stopping is never throttled.
+    pub async fn set_typing(
+        &self,
+        conversation: PublicKey,
+        typing: bool,
+    ) -> Result<Option<EventId>, Error> {
+        let builder: EventBuilder = self.typing_event(conversation, typing);
+        if typing {
+            self.send_throttled(Throttle::Typing(conversation), TYPING.to_string(), builder)
+                .await
+        } else {
+            let mut state = self.state.write().await;
+            state.sent.remove(&Throttle::Typing(conversation));
+            drop(state);
+            Ok(Some(self.client.send_event_builder(builder).await?))
+        }
+    }
+
+    /// Get the presence status of a public key
+    ///
+    /// Return `None` if unknown or expired.
+    pub async fn status(&self, public_key: &PublicKey) -> Option<PresenceStatus> {
+        let state = self.state.read().await;
+        let (status, expiration) = state.statuses.get(public_key)?;
+        if *expiration > Timestamp::now() {
+            Some(*status)
+        } else {
+            None
+        }
+    }
+
+    /// Check if a public key is typing to the user
+    pub async fn is_typing(&self, public_key: &PublicKey) -> bool {
+        let state = self.state.read().await;
+        state
+            .typing
+            .get(public_key)
+            .map_or(false, |expiration| *expiration > Timestamp::now())
+    }
+
+    /// Get presence notifications
+    ///
+    /// Require [`Presence::start`].
+    pub fn notifications(&self) -> broadcast::Receiver<PresenceNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Subscribe to the presence status of `contacts` and to the typing indicators addressed to the user
+    ///
+    /// Events are handled in background until the client is shut down.
+    pub async fn start<I>(&self, contacts: I) -> Result<SubscriptionId, Error>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let signer = self.client.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let filters: Vec<Filter> = vec![
+            Filter::new()
+                .kind(self.opts.presence_kind)
+                .authors(contacts),
+            Filter::new().kind(self.opts.typing_kind).pubkey(public_key),
+        ];
+
+        let mut notifications = self.client.notifications();
+        let id: SubscriptionId = self.client.subscribe(filters, None).await;
+
+        let presence = self.clone();
+        let _ = thread::spawn(async move {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Event { event, .. } => {
+                        if event.author() != public_key {
+                            presence.handle_event(&event).await;
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+
+        Ok(id)
+    }
+
+    async fn send_throttled(
+        &self,
+        key: Throttle,
+        content: String,
+        builder: EventBuilder,
+    ) -> Result<Option<EventId>, Error> {
+        let now: Timestamp = Timestamp::now();
+
+        let mut state = self.state.write().await;
+        if let Some((last, last_content)) = state.sent.get(&key) {
+            if last_content == &content && *last + self.opts.throttle > now {
+                return Ok(None);
+            }
+        }
+        state.sent.insert(key, (now, content));
+        drop(state);
+
+        Ok(Some(self.client.send_event_builder(builder).await?))
+    }
+
+    async fn handle_event(&self, event: &Event) {
+        let expiration: Timestamp = match event.expiration() {
+            Some(expiration) if !event.is_expired() => *expiration,
+            _ => return,
+        };
+        let public_key: PublicKey = event.author();
+
+        if event.kind() == self.opts.presence_kind {
+            let status: PresenceStatus = match PresenceStatus::from_str(event.content()) {
+                Ok(status) => status,
+                Err(_) => return,
+            };
+
+            let mut state = self.state.write().await;
+            let changed: bool = state.statuses.get(&public_key).map(|(s, _)| *s) != Some(status);
+            state.statuses.insert(public_key, (status, expiration));
+            drop(state);
+
+            if changed {
+                let _ = self
+                    .notifications
+                    .send(PresenceNotification::Status { public_key, status });
+            }
+
+            if status != PresenceStatus::Offline {
+                self.schedule_expiry(public_key, expiration, false);
+            }
+        } else if event.kind() == self.opts.typing_kind {
+            let typing: bool = match event.content() {
+                TYPING => true,
+                STOPPED => false,
+                _ => return,
+            };
+
+            let mut state = self.state.write().await;
+            let was_typing: bool = state.typing.contains_key(&public_key);
+            if typing {
+                state.typing.insert(public_key, expiration);
+            } else {
+                state.typing.remove(&public_key);
+            }
+            drop(state);
+
+            if was_typing != typing {
+                let _ = self
+                    .notifications
+                    .send(PresenceNotification::Typing { public_key, typing });
+            }
+
+            if typing {
+                self.schedule_expiry(public_key, expiration, true);
+            }
+        }
+    }
+
+    /// Notify the expiration, if not refreshed in the meantime
+    fn schedule_expiry(&self, public_key: PublicKey, expiration: Timestamp, typing: bool) {
+        let presence = self.clone();
+        let _ = thread::spawn(async move {
+            let delay: u64 = expiration
+                .as_u64()
+                .saturating_sub(Timestamp::now().as_u64());
+            thread::sleep(Duration::from_secs(delay)).await;
+
+            let mut state = presence.state.write().await;
+            let notification = if typing {
+                match state.typing.get(&public_key) {
+                    Some(current) if *current <= expiration => {
+                        state.typing.remove(&public_key);
+                        PresenceNotification::Typing {
+                            public_key,
+                            typing: false,
+                        }
+                    }
+                    _ => return,
+                }
+            } else {
+                match state.statuses.get(&public_key) {
+                    Some((_, current)) if *current <= expiration => {
+                        state.statuses.remove(&public_key);
+                        PresenceNotification::Status {
+                            public_key,
+                            status: PresenceStatus::Offline,
+                        }
+                    }
+                    _ => return,
+                }
+            };
+            drop(state);
+
+            let _ = presence.notifications.send(notification);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presence_status() {
+        for status in [
+            PresenceStatus::Online,
+            PresenceStatus::Away,
+            PresenceStatus::Offline,
+        ] {
+            assert_eq!(
+                PresenceStatus::from_str(&status.to_string()).unwrap(),
+                status
+            );
+        }
+        assert!(PresenceStatus::from_str("busy").is_err());
+    }
+}
@@ -0,0 +1,485 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Request/response over ephemeral or parameterized replaceable events
+//!
+//! Requests and responses are NIP44 encrypted and sent with the same app-defined kind:
+//! * request: `{"id": "<correlation-id>", "method": "<method>", "params": <value>}`, tagged with the receiver (`p` tag)
+//! * response: `{"id": "<correlation-id>", "result": <value>}` or `{"id": "<correlation-id>", "error": "<msg>"}`,
+//!   tagged with the requester (`p` tag) and the request (`e` tag)
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::time::Duration;
+
+use nostr::prelude::*;
+use nostr::secp256k1::rand;
+use nostr::serde_json::{self, json, Value};
+use nostr_database::async_trait;
+use nostr_relay_pool::runtime::time;
+use nostr_relay_pool::RelayPoolNotification;
+use nostr_signer::NostrSigner;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+use tokio::sync::Mutex;
+
+use super::{published_id, Client, Error};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Max number of responses kept to answer the retries
+const MAX_RESPONSES: usize = 1000;
+
+/// RPC options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcOptions {
+    kind: Kind,
+    timeout: Duration,
+    retries: usize,
+}
+
+impl RpcOptions {
+    /// New RPC options
+    ///
+    /// `kind` should be an ephemeral (`20000-29999`) or parameterized replaceable (`30000-39999`) kind.
+    pub fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            timeout: DEFAULT_TIMEOUT,
+            retries: 0,
+        }
+    }
+
+    /// Timeout of each attempt (default: 10 secs)
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Number of times the request is re-sent if no response is received (default: 0)
+    pub fn retries(self, retries: usize) -> Self {
+        Self { retries, ..self }
+    }
+}
+
+/// RPC request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpcRequest {
+    /// Correlation ID
+    pub id: String,
+    /// Requester
+    pub sender: PublicKey,
+    /// Method
+    pub method: String,
+    /// Params
+    pub params: Value,
+}
+
+/// Request/response framework for app-defined protocols
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait ClientRpcExt {
+    /// Send a request to `receiver` and wait for the response
+    ///
+    /// If no response is received within the timeout, the request is signed again and re-sent with the same correlation ID.
+    /// Return [`Error::Rpc`] if the receiver replied with an error.
+    async fn rpc_request<S>(
+        &self,
+        receiver: PublicKey,
+        method: S,
+        params: Value,
+        opts: RpcOptions,
+    ) -> Result<Value, Error>
+    where
+        S: Into<String> + Send;
+
+    /// Serve the requests addressed to the signer public key
+    ///
+    /// The `handler` result is sent back to the requester. Retries of an already handled request
+    /// (same requester and correlation ID) get the same response, without calling the `handler` again.
+    /// Run until the client is shut down.
+    async fn rpc_serve<F, Fut>(&self, opts: RpcOptions, handler: F) -> Result<(), Error>
+    where
+        F: Fn(RpcRequest) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<Value, String>> + Send;
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl ClientRpcExt for Client {
+    async fn rpc_request<S>(
+        &self,
+        receiver: PublicKey,
+        method: S,
+        params: Value,
+        opts: RpcOptions,
+    ) -> Result<Value, Error>
+    where
+        S: Into<String> + Send,
+    {
+        let signer: NostrSigner = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        // Compose request
+        let id: String = format!("{:016x}", rand::random::<u64>());
+        let payload: String = json!({
+            "id": id,
+            "method": method.into(),
+            "params": params,
+        })
+        .to_string();
+
+        // Subscribe to responses before sending the request
+        let mut notifications = self.notifications();
+        let sub_id = SubscriptionId::generate();
+        let filter: Filter = Filter::new()
+            .kind(opts.kind)
+            .author(receiver)
+            .pubkey(public_key)
+            .since(Timestamp::now());
+        self.subscribe_with_id(sub_id.clone(), vec![filter], None)
+            .await;
+
+        let res = async {
+            for attempt in 0..=opts.retries {
+                // Sign a new event at every attempt: relays and pools drop the already seen IDs
+                let request: Event = self
+                    .rpc_request_event(&signer, receiver, &id, &payload, opts)
+                    .await?;
+                published_id(self.send_event(request).await?)?;
+
+                let response = time::timeout(
+                    Some(opts.timeout),
+                    wait_for_response(&signer, &mut notifications, receiver, &id),
+                )
+                .await;
+                if let Some(response) = response {
+                    return response;
+                }
+
+                tracing::debug!(
+                    "No response for RPC request {id} (attempt {}/{})",
+                    attempt + 1,
+                    opts.retries + 1
+                );
+            }
+
+            Err(Error::Timeout)
+        }
+        .await;
+
+        self.unsubscribe(sub_id).await;
+
+        res
+    }
+
+    async fn rpc_serve<F, Fut>(&self, opts: RpcOptions, handler: F) -> Result<(), Error>
+    where
+        F: Fn(RpcRequest) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<Value, String>> + Send,
+    {
+        let signer: NostrSigner = self.signer().await?;
+        let public_key: PublicKey = signer.public_key().await?;
+
+        let filter: Filter = Filter::new()
+            .kind(opts.kind)
+            .pubkey(public_key)
+            .since(Timestamp::now());
+        self.subscribe(vec![filter], None).await;
+
+        let responses: Mutex<Responses> = Mutex::new(Responses::default());
+        let handler = &handler;
+        let signer = &signer;
+        let responses = &responses;
+        self.handle_notifications(|notification| async move {
+            if let RelayPoolNotification::Event { event, .. } = notification {
+                if event.kind() == opts.kind && event.public_keys().any(|p| p == &public_key) {
+                    if let Err(e) = self
+                        .rpc_reply(signer, &event, opts, handler, responses)
+                        .await
+                    {
+                        tracing::warn!("Impossible to reply to RPC request {}: {e}", event.id());
+                    }
+                }
+            }
+            Ok(false)
+        })
+        .await
+    }
+}
+
+/// Responses of the handled requests, by requester and correlation ID
+#[derive(Debug, Default)]
+struct Responses {
+    order: VecDeque<(PublicKey, String)>,
+    responses: HashMap<(PublicKey, String), Value>,
+}
+
+impl Responses {
+    fn get(&self, sender: PublicKey, id: &str) -> Option<Value> {
+        self.responses.get(&(sender, id.to_string())).cloned()
+    }
+
+    /// Keep the response, dropping the oldest one if full
+    fn insert(&mut self, sender: PublicKey, id: String, response: Value) {
+        let key = (sender, id);
+        if self.responses.insert(key.clone(), response).is_none() {
+            self.order.push_back(key);
+        }
+
+        while self.order.len() > MAX_RESPONSES {
+            if let Some(key) = self.order.pop_front() {
+                self.responses.remove(&key);
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Compose and sign a request event
+    ///
+    /// The content is encrypted again at every call, so each event has a different ID.
+    async fn rpc_request_event(
+        &self,
+        signer: &NostrSigner,
+        receiver: PublicKey,
+        id: &str,
+        payload: &str,
+        opts: RpcOptions,
+    ) -> Result<Event, Error> {
+        let content: String = signer.nip44_encrypt(receiver, payload).await?;
+        let mut tags: Vec<Tag> = vec![Tag::public_key(receiver)];
+        if opts.kind.is_parameterized_replaceable() {
+            tags.push(Tag::Identifier(id.to_string()));
+        }
+        let builder = EventBuilder::new(opts.kind, content, tags);
+        self.sign_event_builder(builder).await
+    }
+
+    async fn rpc_reply<F, Fut>(
+        &self,
+        signer: &NostrSigner,
+        event: &Event,
+        opts: RpcOptions,
+        handler: &F,
+        responses: &Mutex<Responses>,
+    ) -> Result<(), Error>
+    where
+        F: Fn(RpcRequest) -> Fut,
+        Fut: Future<Output = Result<Value, String>>,
+    {
+        let sender: PublicKey = event.author();
+        let payload: String = signer.nip44_decrypt(sender, event.content()).await?;
+        let payload: Value = serde_json::from_str(&payload)?;
+
+        // Responses are sent with the same kind: skip them
+        let (id, method) = match (
+            payload.get("id").and_then(Value::as_str),
+            payload.get("method").and_then(Value::as_str),
+        ) {
+            (Some(id), Some(method)) => (id.to_string(), method.to_string()),
+            _ => return Ok(()),
+        };
+
+        // Retry of a handled request: send the same response
+        let cached: Option<Value> = responses.lock().await.get(sender, &id);
+        let response: Value = match cached {
+            Some(response) => {
+                tracing::debug!("Retry of RPC request {id}: sending the same response");
+                response
+            }
+            None => {
+                let request = RpcRequest {
+                    id: id.clone(),
+                    sender,
+                    method,
+                    params: payload.get("params").cloned().unwrap_or(Value::Null),
+                };
+                let response: Value = match handler(request).await {
+                    Ok(result) => json!({ "id": id, "result": result }),
+                    Err(error) => json!({ "id": id, "error": error }),
+                };
+                let mut responses = responses.lock().await;
+                responses.insert(sender, id.clone(), response.clone());
+                response
+            }
+        };
+
+        let content: String = signer.nip44_encrypt(sender, response.to_string()).await?;
+        let mut tags: Vec<Tag> = vec![Tag::public_key(sender), Tag::event(event.id())];
+        if opts.kind.is_parameterized_replaceable() {
+            tags.push(Tag::Identifier(format!("{id}:response")));
+        }
+        let builder = EventBuilder::new(opts.kind, content, tags);
+        self.send_event_builder(builder).await?;
+
+        Ok(())
+    }
+}
+
+async fn wait_for_response(
+    signer: &NostrSigner,
+    notifications: &mut Receiver<RelayPoolNotification>,
+    receiver: PublicKey,
+    id: &str,
+) -> Result<Value, Error> {
+    loop {
+        let notification: RelayPoolNotification = match notifications.recv().await {
+            Ok(notification) => notification,
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::debug!("Skipped {skipped} notifications while waiting RPC response");
+                continue;
+            }
+            // The response can't be received anymore
+            Err(RecvError::Closed) => return Err(Error::Timeout),
+        };
+
+        if let RelayPoolNotification::Event { event, .. } = notification {
+            if event.author() != receiver {
+                continue;
+            }
+
+            let payload: String = match signer.nip44_decrypt(receiver, event.content()).await {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::debug!("Impossible to decrypt RPC response: {e}");
+                    continue;
+                }
+            };
+            let mut payload: Value = match serde_json::from_str(&payload) {
+                Ok(payload) => payload,
+                Err(_) => continue,
+            };
+
+            if payload.get("id").and_then(Value::as_str) != Some(id) {
+                continue;
+            }
+
+            if let Some(error) = payload.get("error") {
+                let error: String = error
+                    .as_str()
+                    .map(|e| e.to_string())
+                    .unwrap_or_else(|| error.to_string());
+                return Err(Error::Rpc(error));
+            }
+
+            return Ok(payload
+                .get_mut("result")
+                .map(Value::take)
+                .unwrap_or(Value::Null));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nostr::nips::nip44::{self, Version};
+    use tokio::sync::broadcast;
+
+    use super::*;
+
+    const KIND: Kind = Kind::Custom(21234);
+
+    fn notification(event: Event) -> RelayPoolNotification {
+        RelayPoolNotification::Event {
+            relay_url: Url::parse("wss://relay.example.com").unwrap(),
+            subscription_id: SubscriptionId::generate(),
+            event: Box::new(event),
+        }
+    }
+
+    fn response(receiver: &Keys, requester: &Keys, id: &str) -> Event {
+        let payload: String = json!({ "id": id, "result": "pong" }).to_string();
+        let content: String = nip44::encrypt(
+            receiver.secret_key().unwrap(),
+            &requester.public_key(),
+            payload,
+            Version::default(),
+        )
+        .unwrap();
+        EventBuilder::new(KIND, content, [Tag::public_key(requester.public_key())])
+            .to_event(receiver)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_response_lagged() {
+        let requester = Keys::generate();
+        let receiver = Keys::generate();
+        let signer = NostrSigner::from(requester.clone());
+
+        let (tx, mut rx) = broadcast::channel(2);
+        for i in 0..3 {
+            let other = EventBuilder::text_note(format!("Other {i}"), [])
+                .to_event(&Keys::generate())
+                .unwrap();
+            tx.send(notification(other)).unwrap();
+        }
+        tx.send(notification(response(&receiver, &requester, "abc")))
+            .unwrap();
+
+        // The receiver lagged: keep waiting
+        let result = wait_for_response(&signer, &mut rx, receiver.public_key(), "abc").await;
+        assert_eq!(result.unwrap(), Value::String(String::from("pong")));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_response_closed() {
+        let requester = Keys::generate();
+        let receiver = Keys::generate();
+        let signer = NostrSigner::from(requester);
+
+        let (tx, mut rx) = broadcast::channel::<RelayPoolNotification>(2);
+        drop(tx);
+
+        let result = wait_for_response(&signer, &mut rx, receiver.public_key(), "abc").await;
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_rpc_request_event_resigned() {
+        let requester = Keys::generate();
+        let receiver = Keys::generate();
+        let client = Client::new(&requester);
+        let signer = NostrSigner::from(requester.clone());
+        let opts = RpcOptions::new(KIND);
+        let payload: String = json!({ "id": "abc", "method": "ping" }).to_string();
+
+        let first = client
+            .rpc_request_event(&signer, receiver.public_key(), "abc", &payload, opts)
+            .await
+            .unwrap();
+        let second = client
+            .rpc_request_event(&signer, receiver.public_key(), "abc", &payload, opts)
+            .await
+            .unwrap();
+        assert_ne!(first.id(), second.id());
+
+        // Same correlation ID
+        for event in [first, second] {
+            let decrypted = nip44::decrypt(
+                receiver.secret_key().unwrap(),
+                &requester.public_key(),
+                event.content(),
+            )
+            .unwrap();
+            assert_eq!(decrypted, payload);
+        }
+    }
+
+    #[test]
+    fn test_responses() {
+        let sender = Keys::generate().public_key();
+        let mut responses = Responses::default();
+
+        responses.insert(sender, String::from("abc"), json!({ "id": "abc" }));
+        assert_eq!(responses.get(sender, "abc"), Some(json!({ "id": "abc" })));
+        assert_eq!(responses.get(Keys::generate().public_key(), "abc"), None);
+
+        for i in 0..MAX_RESPONSES {
+            responses.insert(sender, i.to_string(), Value::Null);
+        }
+        assert_eq!(responses.order.len(), MAX_RESPONSES);
+        assert_eq!(responses.responses.len(), MAX_RESPONSES);
+        assert_eq!(responses.get(sender, "abc"), None);
+    }
+}
@@ -0,0 +1,277 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Watch-only monitoring of profiles, contact lists and relay lists
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use nostr::nips::nip65;
+use nostr::prelude::*;
+use nostr::serde_json::{self, Map, Value};
+use nostr_database::Order;
+use nostr_relay_pool::runtime::thread;
+use nostr_relay_pool::RelayPoolNotification;
+use tokio::sync::{broadcast, RwLock};
+
+use super::{Client, Error};
+
+const KINDS: [Kind; 3] = [Kind::Metadata, Kind::ContactList, Kind::RelayList];
+
+/// Change of a watched public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContactChange {
+    /// Profile field changed (kind `0`)
+    ///
+    /// `None` if the field was added (`old`) or removed (`new`).
+    ProfileField {
+        /// Field name
+        field: String,
+        /// Old value
+        old: Option<Value>,
+        /// New value
+        new: Option<Value>,
+    },
+    /// Followed a public key (kind `3`)
+    Followed(PublicKey),
+    /// Unfollowed a public key (kind `3`)
+    Unfollowed(PublicKey),
+    /// Relay list changed (kind `10002`)
+    ///
+    /// A relay with changed read/write flag is both in `removed` and `added`.
+    RelayList {
+        /// Added relays
+        added: Vec<(UncheckedUrl, Option<RelayMetadata>)>,
+        /// Removed relays
+        removed: Vec<(UncheckedUrl, Option<RelayMetadata>)>,
+    },
+}
+
+/// Contact watcher notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactNotification {
+    /// Watched public key
+    pub public_key: PublicKey,
+    /// ID of the event that introduced the change
+    pub event_id: EventId,
+    /// Change
+    pub change: ContactChange,
+}
+
+/// Compute the changes between two versions of a profile, contact list or relay list
+///
+/// Return an empty list if the events have a different author or kind.
+fn diff(old: &Event, new: &Event) -> Vec<ContactChange> {
+    if old.author() != new.author() || old.kind() != new.kind() {
+        return Vec::new();
+    }
+
+    match new.kind() {
+        Kind::Metadata => {
+            let old: Map<String, Value> = parse_profile(old);
+            let new: Map<String, Value> = parse_profile(new);
+            let fields: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            fields
+                .into_iter()
+                .filter_map(|field| {
+                    let (o, n) = (old.get(field), new.get(field));
+                    if o == n {
+                        None
+                    } else {
+                        Some(ContactChange::ProfileField {
+                            field: field.clone(),
+                            old: o.cloned(),
+                            new: n.cloned(),
+                        })
+                    }
+                })
+                .collect()
+        }
+        Kind::ContactList => {
+            let old: BTreeSet<PublicKey> = old.public_keys().copied().collect();
+            let new: BTreeSet<PublicKey> = new.public_keys().copied().collect();
+            let followed = new.difference(&old).map(|p| ContactChange::Followed(*p));
+            let unfollowed = old.difference(&new).map(|p| ContactChange::Unfollowed(*p));
+            followed.chain(unfollowed).collect()
+        }
+        Kind::RelayList => {
+            let old = nip65::extract_relay_list(old);
+            let new = nip65::extract_relay_list(new);
+            let added: Vec<_> = new.iter().filter(|r| !old.contains(r)).cloned().collect();
+            let removed: Vec<_> = old.iter().filter(|r| !new.contains(r)).cloned().collect();
+            if added.is_empty() && removed.is_empty() {
+                Vec::new()
+            } else {
+                vec![ContactChange::RelayList { added, removed }]
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn parse_profile(event: &Event) -> Map<String, Value> {
+    match serde_json::from_str(event.content()) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Watch-only monitoring of profiles (kind `0`), contact lists (kind `3`) and relay lists (kind `10002`)
+///
+/// The first version seen of each event is used as a baseline (no change is notified).
+/// Then, each newer version is compared to the previous one. Old versions and duplicates
+/// (i.e. the same event received from many relays) are ignored.
+#[derive(Debug, Clone)]
+pub struct ContactWatcher {
+    client: Client,
+    latest: Arc<RwLock<HashMap<(PublicKey, Kind), Event>>>,
+    notifications: broadcast::Sender<ContactNotification>,
+}
+
+impl ContactWatcher {
+    /// New contact watcher
+    pub fn new(client: &Client) -> Self {
+        let (notifications, ..) = broadcast::channel(1024);
+        Self {
+            client: client.clone(),
+            latest: Arc::new(RwLock::new(HashMap::new())),
+            notifications,
+        }
+    }
+
+    /// Get change notifications
+    pub fn notifications(&self) -> broadcast::Receiver<ContactNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Watch the public keys
+    ///
+    /// The baselines are loaded from the database, then events are handled in background
+    /// until the client is shut down.
+    pub async fn start<I>(&self, public_keys: I) -> Result<SubscriptionId, Error>
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let public_keys: HashSet<PublicKey> = public_keys.into_iter().collect();
+
+        // Load baselines
+        let filter: Filter = Filter::new()
+            .authors(public_keys.iter().copied())
+            .kinds(KINDS);
+        let stored: Vec<Event> = self
+            .client
+            .database()
+            .query(vec![filter.clone()], Order::Desc)
+            .await?;
+        let mut latest = self.latest.write().await;
+        for event in stored.into_iter() {
+            latest
+                .entry((event.author(), event.kind()))
+                .or_insert(event);
+        }
+        drop(latest);
+
+        let mut notifications = self.client.notifications();
+        let id: SubscriptionId = self.client.subscribe(vec![filter], None).await;
+
+        let watcher = self.clone();
+        let _ = thread::spawn(async move {
+            while let Ok(notification) = notifications.recv().await {
+                match notification {
+                    RelayPoolNotification::Event { event, .. } => {
+                        if public_keys.contains(&event.author()) && KINDS.contains(&event.kind()) {
+                            watcher.handle_event(*event).await;
+                        }
+                    }
+                    RelayPoolNotification::Shutdown => break,
+                    _ => (),
+                }
+            }
+        });
+
+        Ok(id)
+    }
+
+    async fn handle_event(&self, event: Event) {
+        let public_key: PublicKey = event.author();
+        let event_id: EventId = event.id();
+
+        let mut latest = self.latest.write().await;
+        let changes: Vec<ContactChange> = match latest.get(&(public_key, event.kind())) {
+            Some(previous) if previous.created_at() >= event.created_at() => return,
+            Some(previous) => diff(previous, &event),
+            None => Vec::new(),
+        };
+        latest.insert((public_key, event.kind()), event);
+        drop(latest);
+
+        for change in changes.into_iter() {
+            let _ = self.notifications.send(ContactNotification {
+                public_key,
+                event_id,
+                change,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let keys = Keys::generate();
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+
+        let old = EventBuilder::new(Kind::Metadata, r#"{"name":"a","about":"x"}"#, [])
+            .to_event(&keys)
+            .unwrap();
+        let new = EventBuilder::new(Kind::Metadata, r#"{"name":"b","picture":"p"}"#, [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                ContactChange::ProfileField {
+                    field: String::from("about"),
+                    old: Some(Value::from("x")),
+                    new: None,
+                },
+                ContactChange::ProfileField {
+                    field: String::from("name"),
+                    old: Some(Value::from("a")),
+                    new: Some(Value::from("b")),
+                },
+                ContactChange::ProfileField {
+                    field: String::from("picture"),
+                    old: None,
+                    new: Some(Value::from("p")),
+                },
+            ]
+        );
+
+        let old = EventBuilder::new(Kind::ContactList, "", [Tag::public_key(alice)])
+            .to_event(&keys)
+            .unwrap();
+        let new = EventBuilder::new(Kind::ContactList, "", [Tag::public_key(bob)])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                ContactChange::Followed(bob),
+                ContactChange::Unfollowed(alice)
+            ]
+        );
+
+        // Different kind
+        assert!(diff(
+            &old,
+            &EventBuilder::text_note("", []).to_event(&keys).unwrap()
+        )
+        .is_empty());
+    }
+}
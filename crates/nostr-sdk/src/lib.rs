@@ -11,7 +11,7 @@
 //! High level Nostr client library.
 
 #![cfg_attr(
-    feature = "all-nips",
+    all(feature = "all-nips", feature = "lnurl", feature = "nwc"),
     doc = include_str!("../README.md")
 )]
 
@@ -24,9 +24,12 @@ pub use nostr_database::{self as database, NostrDatabase, NostrDatabaseExt, Prof
 #[cfg(all(target_arch = "wasm32", feature = "indexeddb"))]
 pub use nostr_indexeddb::{IndexedDBError, WebDatabase};
 pub use nostr_relay_pool::{
-    self as pool, AtomicRelayServiceFlags, FilterOptions, NegentropyDirection, NegentropyOptions,
-    Relay, RelayConnectionStats, RelayOptions, RelayPool, RelayPoolNotification, RelayPoolOptions,
-    RelaySendOptions, RelayServiceFlags, RelayStatus, SubscribeAutoCloseOptions, SubscribeOptions,
+    self as pool, AtomicRelayServiceFlags, CompletenessReport, ConnectionMode, FilterOptions,
+    FilteringPolicy, NegentropyDirection, NegentropyOptions, NotificationKinds, Output,
+    PowEscalation, Relay, RelayConnectionStats, RelayOptions, RelayPool, RelayPoolNotification,
+    RelayPoolOptions, RelaySendOptions, RelayServiceFlags, RelayStatus, SubscribeAutoCloseOptions,
+    SubscribeOptions, SubscriptionCloseReason, SubscriptionLifecycle, SubscriptionUpdate,
+    VerificationPolicy, VerifyMode,
 };
 #[cfg(feature = "rocksdb")]
 pub use nostr_rocksdb::RocksDatabase;
@@ -37,7 +40,7 @@ pub use nostr_sqlite::{Error as SQLiteError, SQLiteDatabase};
 pub use nostr_webln::WebLNZapper;
 #[cfg(feature = "nip57")]
 pub use nostr_zapper::{self as zapper, NostrZapper, ZapperBackend, ZapperError};
-#[cfg(feature = "nip47")]
+#[cfg(feature = "nwc")]
 pub use nwc::{self, NostrWalletConnectOptions, NWC};
 #[cfg(feature = "blocking")]
 use once_cell::sync::Lazy;
@@ -0,0 +1,243 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Integration tests against a real relay
+//!
+//! The tests are ignored by default. Start the relay and run them with `just integration` or:
+//!
+//! ```text
+//! docker compose -f contrib/integration/docker-compose.yml up -d
+//! cargo test -p nostr-sdk --test relay -- --ignored
+//! ```
+//!
+//! Set `NOSTR_TEST_RELAY` to test another relay (default: `ws://127.0.0.1:7777`).
+
+use std::time::Duration;
+
+use nostr_sdk::async_utility::time;
+use nostr_sdk::prelude::*;
+use tokio::sync::broadcast::Receiver;
+
+const DEFAULT_RELAY: &str = "ws://127.0.0.1:7777";
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn relay_url() -> Url {
+    let url: String = std::env::var("NOSTR_TEST_RELAY").unwrap_or_else(|_| DEFAULT_RELAY.into());
+    Url::parse(&url).unwrap()
+}
+
+/// New client, with an empty database, connected to the test relay
+async fn client(keys: &Keys) -> Client {
+    let opts = Options::new().connection_timeout(Some(TIMEOUT));
+    let client = Client::with_opts(keys, opts);
+    client.add_relay(relay_url()).await.unwrap();
+    client.connect().await;
+    client
+}
+
+/// Fetch from the relay with a fresh client, so the local database can't hide regressions
+async fn fetch(filter: Filter) -> Vec<Event> {
+    let reader = client(&Keys::generate()).await;
+    let events = reader
+        .get_events_of(vec![filter], Some(TIMEOUT))
+        .await
+        .unwrap();
+    reader.shutdown().await.unwrap();
+    events
+}
+
+/// Wait for a notification matching `f`
+async fn wait_for<T, F>(notifications: &mut Receiver<RelayPoolNotification>, f: F) -> Option<T>
+where
+    F: Fn(RelayPoolNotification) -> Option<T>,
+{
+    time::timeout(Some(TIMEOUT), async {
+        while let Ok(notification) = notifications.recv().await {
+            if let Some(value) = f(notification) {
+                return Some(value);
+            }
+        }
+        None
+    })
+    .await
+    .flatten()
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_publish_and_fetch() {
+    let keys = Keys::generate();
+    let client = client(&keys).await;
+
+    let event = EventBuilder::text_note("Integration test", [])
+        .to_event(&keys)
+        .unwrap();
+    let output = client.send_event(event.clone()).await.unwrap();
+    assert!(output.is_success(), "{:?}", output.failed);
+
+    let events = fetch(Filter::new().id(event.id())).await;
+    assert_eq!(events, vec![event]);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_subscribe() {
+    let keys = Keys::generate();
+    let writer = client(&keys).await;
+
+    let reader = client(&Keys::generate()).await;
+    let mut notifications = reader.notifications();
+    let filter = Filter::new()
+        .author(keys.public_key())
+        .kind(Kind::TextNote)
+        .since(Timestamp::now());
+    reader.subscribe(vec![filter], None).await;
+
+    let id = writer.publish_text_note("Live", []).await.unwrap();
+
+    let received = wait_for(&mut notifications, |notification| match notification {
+        RelayPoolNotification::Event { event, .. } if event.id() == id => Some(event),
+        _ => None,
+    })
+    .await;
+    assert!(received.is_some(), "event not received");
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_seen_on() {
+    let keys = Keys::generate();
+    let writer = client(&keys).await;
+    let id = writer.publish_text_note("Seen on", []).await.unwrap();
+
+    let reader = client(&Keys::generate()).await;
+    assert!(reader.seen_on(id).await.unwrap().is_empty());
+
+    let events = reader
+        .get_events_of(vec![Filter::new().id(id)], Some(TIMEOUT))
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+
+    let stored = reader
+        .database()
+        .event_seen_on_relays(id)
+        .await
+        .unwrap()
+        .expect("relays not stored");
+    assert!(stored.contains(&relay_url()));
+    assert_eq!(reader.seen_on(id).await.unwrap(), vec![relay_url()]);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_delete() {
+    let keys = Keys::generate();
+    let client = client(&keys).await;
+
+    let id = client.publish_text_note("To delete", []).await.unwrap();
+    assert_eq!(fetch(Filter::new().id(id)).await.len(), 1);
+
+    client.delete_event(id).await.unwrap();
+    assert!(fetch(Filter::new().id(id)).await.is_empty());
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_replaceable() {
+    let keys = Keys::generate();
+    let client = client(&keys).await;
+
+    let now = Timestamp::now();
+    let old = EventBuilder::metadata(&Metadata::new().name("old"))
+        .custom_created_at(now - Duration::from_secs(60))
+        .to_event(&keys)
+        .unwrap();
+    let new = EventBuilder::metadata(&Metadata::new().name("new"))
+        .custom_created_at(now)
+        .to_event(&keys)
+        .unwrap();
+
+    // Publish the newest first: the older one must not replace it
+    assert!(client.send_event(new.clone()).await.unwrap().is_success());
+    client.send_event(old).await.unwrap();
+
+    let events = fetch(Filter::new().author(keys.public_key()).kind(Kind::Metadata)).await;
+    assert_eq!(events, vec![new]);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_interactions() {
+    let keys = Keys::generate();
+    let opts = Options::new().connection_timeout(Some(TIMEOUT));
+    let client = Client::with_opts(&keys, opts);
+    client.add_relay(relay_url()).await.unwrap();
+
+    // Offline: the reaction is queued and persisted
+    let note = EventBuilder::text_note("React to me", [])
+        .to_event(&keys)
+        .unwrap();
+    let interactions = Interactions::new(&client);
+    let id = interactions.react(&note, "+").await.unwrap();
+    assert!(interactions.is_pending(&id).await);
+
+    // Restore the queue in a new session
+    let restored = Interactions::new(&client);
+    assert_eq!(restored.restore().await.unwrap(), 1);
+    assert!(restored.is_pending(&id).await);
+
+    // Flush once connected
+    client.connect().await;
+    assert_eq!(restored.flush().await.unwrap(), 1);
+    assert!(restored.pending().await.is_empty());
+    assert_eq!(fetch(Filter::new().id(id)).await.len(), 1);
+
+    // Nothing left to restore
+    assert_eq!(Interactions::new(&client).restore().await.unwrap(), 0);
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_auth() {
+    let keys = Keys::generate();
+    let opts = Options::new().connection_timeout(Some(TIMEOUT));
+    let client = Client::with_opts(&keys, opts);
+    client.add_relay(relay_url()).await.unwrap();
+
+    // Listen before connecting: the challenge is sent on connection
+    let mut notifications = client.notifications();
+    client.connect().await;
+
+    let challenge = wait_for(&mut notifications, |notification| match notification {
+        RelayPoolNotification::Message {
+            message: RelayMessage::Auth { challenge },
+            ..
+        } => Some(challenge),
+        _ => None,
+    })
+    .await
+    .expect("AUTH challenge not received");
+
+    let event = EventBuilder::auth(challenge, relay_url())
+        .to_event(&keys)
+        .unwrap();
+    let id = event.id();
+    client
+        .send_msg_to([relay_url()], ClientMessage::auth(event))
+        .await
+        .unwrap();
+
+    let status = wait_for(&mut notifications, |notification| match notification {
+        RelayPoolNotification::Message {
+            message: RelayMessage::Ok {
+                event_id, status, ..
+            },
+            ..
+        } if event_id == id => Some(status),
+        _ => None,
+    })
+    .await;
+    assert_eq!(status, Some(true));
+}
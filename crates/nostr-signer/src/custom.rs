@@ -0,0 +1,82 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Custom signer
+//!
+//! Plug signers that are not natively supported (i.e. hardware-backed keys or app-specific
+//! remote signers) without exposing the secret key to the [`Client`](https://docs.rs/nostr-sdk).
+
+use std::fmt;
+
+pub use async_trait::async_trait;
+use nostr::prelude::*;
+
+use crate::Error;
+
+/// Custom Nostr signer
+///
+/// Methods mirror the `window.nostr` (NIP07) capabilities.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait CustomNostrSigner: AsyncTraitDeps {
+    /// Get signer public key
+    async fn get_public_key(&self) -> Result<PublicKey, Error>;
+
+    /// Sign an [UnsignedEvent]
+    async fn sign_event(&self, unsigned: UnsignedEvent) -> Result<Event, Error>;
+
+    /// NIP04 encrypt
+    #[cfg(feature = "nip04")]
+    async fn nip04_encrypt(&self, public_key: PublicKey, content: &[u8]) -> Result<String, Error>;
+
+    /// NIP04 decrypt
+    #[cfg(feature = "nip04")]
+    async fn nip04_decrypt(
+        &self,
+        public_key: PublicKey,
+        encrypted_content: &str,
+    ) -> Result<String, Error>;
+
+    /// NIP44 encrypt
+    #[cfg(feature = "nip44")]
+    async fn nip44_encrypt(&self, public_key: PublicKey, content: &[u8]) -> Result<String, Error>;
+
+    /// NIP44 decrypt
+    #[cfg(feature = "nip44")]
+    async fn nip44_decrypt(&self, public_key: PublicKey, payload: &[u8]) -> Result<String, Error>;
+}
+
+/// Alias for `Send` on non-wasm, empty trait (implemented by everything) on
+/// wasm.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait SendOutsideWasm: Send {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Send> SendOutsideWasm for T {}
+
+/// Alias for `Send` on non-wasm, empty trait (implemented by everything) on
+/// wasm.
+#[cfg(target_arch = "wasm32")]
+pub trait SendOutsideWasm {}
+#[cfg(target_arch = "wasm32")]
+impl<T> SendOutsideWasm for T {}
+
+/// Alias for `Sync` on non-wasm, empty trait (implemented by everything) on
+/// wasm.
+#[cfg(not(target_arch = "wasm32"))]
+pub trait SyncOutsideWasm: Sync {}
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Sync> SyncOutsideWasm for T {}
+
+/// Alias for `Sync` on non-wasm, empty trait (implemented by everything) on
+/// wasm.
+#[cfg(target_arch = "wasm32")]
+pub trait SyncOutsideWasm {}
+#[cfg(target_arch = "wasm32")]
+impl<T> SyncOutsideWasm for T {}
+
+/// Super trait that is used for our signer traits, this trait will differ if
+/// it's used on WASM. WASM targets will not require `Send` and `Sync` to have
+/// implemented, while other targets will.
+pub trait AsyncTraitDeps: fmt::Debug + SendOutsideWasm + SyncOutsideWasm {}
+impl<T: fmt::Debug + SendOutsideWasm + SyncOutsideWasm> AsyncTraitDeps for T {}
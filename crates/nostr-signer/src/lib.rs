@@ -9,15 +9,18 @@
 #![warn(rustdoc::bare_urls)]
 
 use std::fmt;
+use std::sync::Arc;
 
 use nostr::key;
 use nostr::prelude::*;
 use thiserror::Error;
 
+pub mod custom;
 #[cfg(feature = "nip46")]
 pub mod nip46;
 pub mod prelude;
 
+pub use self::custom::CustomNostrSigner;
 #[cfg(feature = "nip46")]
 pub use self::nip46::Nip46Signer;
 
@@ -46,6 +49,22 @@ pub enum Error {
     #[cfg(feature = "nip46")]
     #[error(transparent)]
     NIP46(#[from] nip46::Error),
+    /// An error happened in a custom signer
+    #[error("custom signer: {0}")]
+    Custom(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// Create a new `Custom` error.
+    ///
+    /// Shorthand for `Error::Custom(Box::new(error))`.
+    #[inline]
+    pub fn custom<E>(error: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::Custom(Box::new(error))
+    }
 }
 
 /// Nostr Signer Type
@@ -59,6 +78,8 @@ pub enum NostrSignerType {
     /// NIP46
     #[cfg(feature = "nip46")]
     NIP46,
+    /// Custom
+    Custom,
 }
 
 // TODO: better display
@@ -70,6 +91,7 @@ impl fmt::Display for NostrSignerType {
             Self::NIP07 => write!(f, "Nostr Browser Extension"),
             #[cfg(feature = "nip46")]
             Self::NIP46 => write!(f, "Nostr Connect"),
+            Self::Custom => write!(f, "Custom"),
         }
     }
 }
@@ -85,6 +107,8 @@ pub enum NostrSigner {
     /// NIP46 signer
     #[cfg(feature = "nip46")]
     NIP46(Box<Nip46Signer>),
+    /// Custom signer
+    Custom(Arc<dyn CustomNostrSigner>),
 }
 
 impl NostrSigner {
@@ -101,6 +125,14 @@ impl NostrSigner {
         Self::NIP46(Box::new(signer))
     }
 
+    /// Compose [NostrSigner] with a [CustomNostrSigner]
+    pub fn custom<T>(signer: T) -> Self
+    where
+        T: CustomNostrSigner + 'static,
+    {
+        Self::Custom(Arc::new(signer))
+    }
+
     /// Get Nostr Signer Type
     pub fn r#type(&self) -> NostrSignerType {
         match self {
@@ -109,6 +141,7 @@ impl NostrSigner {
             Self::NIP07(..) => NostrSignerType::NIP07,
             #[cfg(feature = "nip46")]
             Self::NIP46(..) => NostrSignerType::NIP46,
+            Self::Custom(..) => NostrSignerType::Custom,
         }
     }
 
@@ -120,6 +153,7 @@ impl NostrSigner {
             Self::NIP07(s) => Ok(s.get_public_key().await?),
             #[cfg(feature = "nip46")]
             Self::NIP46(s) => Ok(s.signer_public_key()),
+            Self::Custom(s) => s.get_public_key().await,
         }
     }
 
@@ -138,6 +172,7 @@ impl NostrSigner {
             Self::NIP07(nip07) => Ok(nip07.sign_event(unsigned).await?),
             #[cfg(feature = "nip46")]
             Self::NIP46(nip46) => Ok(nip46.sign_event(unsigned).await?),
+            Self::Custom(signer) => signer.sign_event(unsigned).await,
         }
     }
 
@@ -154,6 +189,7 @@ impl NostrSigner {
             Self::NIP07(signer) => Ok(signer.nip04_encrypt(public_key, content).await?),
             #[cfg(feature = "nip46")]
             Self::NIP46(signer) => Ok(signer.nip04_encrypt(public_key, content).await?),
+            Self::Custom(signer) => signer.nip04_encrypt(public_key, content).await,
         }
     }
 
@@ -178,6 +214,7 @@ impl NostrSigner {
             Self::NIP07(signer) => Ok(signer.nip04_decrypt(public_key, encrypted_content).await?),
             #[cfg(feature = "nip46")]
             Self::NIP46(signer) => Ok(signer.nip04_decrypt(public_key, encrypted_content).await?),
+            Self::Custom(signer) => signer.nip04_decrypt(public_key, encrypted_content).await,
         }
     }
 
@@ -199,6 +236,7 @@ impl NostrSigner {
             Self::NIP07(signer) => Ok(signer.nip44_encrypt(public_key, content).await?),
             #[cfg(feature = "nip46")]
             Self::NIP46(signer) => Ok(signer.nip44_encrypt(public_key, content).await?),
+            Self::Custom(signer) => signer.nip44_encrypt(public_key, content).await,
         }
     }
 
@@ -215,6 +253,7 @@ impl NostrSigner {
             Self::NIP07(signer) => Ok(signer.nip44_decrypt(public_key, payload).await?),
             #[cfg(feature = "nip46")]
             Self::NIP46(signer) => Ok(signer.nip44_decrypt(public_key, payload).await?),
+            Self::Custom(signer) => signer.nip44_decrypt(public_key, payload).await,
         }
     }
 }
@@ -244,3 +283,9 @@ impl From<Nip46Signer> for NostrSigner {
         Self::nip46(nip46)
     }
 }
+
+impl From<Arc<dyn CustomNostrSigner>> for NostrSigner {
+    fn from(signer: Arc<dyn CustomNostrSigner>) -> Self {
+        Self::Custom(signer)
+    }
+}
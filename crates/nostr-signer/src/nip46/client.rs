@@ -11,7 +11,8 @@ use async_utility::time;
 use nostr::nips::nip46::{Message, NostrConnectURI, Request, ResponseResult};
 use nostr::prelude::*;
 use nostr_relay_pool::{
-    RelayOptions, RelayPool, RelayPoolNotification, RelaySendOptions, SubscribeOptions,
+    pool, Output, RelayOptions, RelayPool, RelayPoolNotification, RelaySendOptions,
+    SubscribeOptions,
 };
 use tokio::sync::broadcast::Receiver;
 
@@ -121,7 +122,10 @@ impl Nip46Signer {
         let mut notifications = self.pool.notifications();
 
         // Send request
-        self.pool.send_event(event, RelaySendOptions::new()).await?;
+        let output: Output = self.pool.send_event(event, RelaySendOptions::new()).await?;
+        if !output.is_success() {
+            return Err(Error::Pool(pool::Error::EventNotPublished));
+        }
 
         time::timeout(Some(self.timeout), async {
             while let Ok(notification) = notifications.recv().await {
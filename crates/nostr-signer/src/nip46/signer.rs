@@ -9,7 +9,8 @@ use std::time::Duration;
 use nostr::nips::nip46::{Message, NostrConnectURI, Request, ResponseResult};
 use nostr::prelude::*;
 use nostr_relay_pool::{
-    pool, RelayOptions, RelayPool, RelayPoolNotification, RelaySendOptions, SubscribeOptions,
+    pool, Output, RelayOptions, RelayPool, RelayPoolNotification, RelaySendOptions,
+    SubscribeOptions,
 };
 
 use super::Error;
@@ -96,9 +97,13 @@ impl NostrConnectRemoteSigner {
         });
         let event =
             EventBuilder::nostr_connect(&self.keys, public_key, msg)?.to_event(&self.keys)?;
-        self.pool
+        let output: Output = self
+            .pool
             .send_event(event, RelaySendOptions::default())
             .await?;
+        if !output.is_success() {
+            return Err(Error::Pool(pool::Error::EventNotPublished));
+        }
         Ok(())
     }
 
@@ -243,7 +248,14 @@ impl NostrConnectRemoteSigner {
                                 let event =
                                     EventBuilder::nostr_connect(&self.keys, event.author(), msg)?
                                         .to_event(&self.keys)?;
-                                self.pool.send_event(event, RelaySendOptions::new()).await?;
+                                let output: Output =
+                                    self.pool.send_event(event, RelaySendOptions::new()).await?;
+                                if !output.is_success() {
+                                    tracing::error!(
+                                        "NIP46 response not published: {:?}",
+                                        output.failed
+                                    );
+                                }
                             }
                         } else {
                             eprintln!("Impossible to decrypt NIP46 message");
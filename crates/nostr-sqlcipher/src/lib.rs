@@ -9,7 +9,7 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::bare_urls)]
 
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -19,7 +19,7 @@ pub extern crate nostr_database as database;
 use async_trait::async_trait;
 use deadpool_sqlite::{Config, Object, Pool, Runtime};
 use nostr::nips::nip01::Coordinate;
-use nostr::{Event, EventId, Filter, Timestamp, Url};
+use nostr::{Event, EventId, Filter, SingleLetterTag, Timestamp, Url};
 use nostr_database::{
     Backend, DatabaseIndexes, EventIndexResult, FlatBufferBuilder, FlatBufferDecode,
     FlatBufferEncode, NostrDatabase, Order, TempEvent,
@@ -79,10 +79,12 @@ impl SQLCipherDatabase {
     #[tracing::instrument(skip_all)]
     async fn key(&self, conn: &Object, key: String) -> Result<(), Error> {
         conn.interact(move |conn| {
-            conn.pragma_update(None, "key", key).expect("Could not unlock db");
+            conn.pragma_update(None, "key", key)
+                .expect("Could not unlock db");
 
             Ok::<(), Error>(())
-        }).await??;
+        })
+        .await??;
 
         Ok(())
     }
@@ -90,10 +92,12 @@ impl SQLCipherDatabase {
     #[tracing::instrument(skip_all)]
     async fn rekey(&self, conn: &Object, key: String) -> Result<(), Error> {
         conn.interact(move |conn| {
-            conn.pragma_update(None, "rekey", key).expect("Could not change db key");
+            conn.pragma_update(None, "rekey", key)
+                .expect("Could not change db key");
 
             Ok::<(), Error>(())
-        }).await??;
+        })
+        .await??;
 
         Ok(())
     }
@@ -340,11 +344,46 @@ impl NostrDatabase for SQLCipherDatabase {
         .await?
     }
 
+    async fn save_checkpoint(&self, key: &str, value: Vec<u8>) -> Result<(), Self::Err> {
+        let conn = self.acquire().await?;
+        let key: String = key.to_string();
+        conn.interact(move |conn| {
+            let mut stmt = conn
+                .prepare_cached("INSERT OR REPLACE INTO checkpoints (key, value) VALUES (?, ?);")?;
+            stmt.execute((key, value))?;
+            Ok(())
+        })
+        .await?
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Err> {
+        let conn = self.acquire().await?;
+        let key: String = key.to_string();
+        conn.interact(move |conn| {
+            let mut stmt = conn.prepare_cached("SELECT value FROM checkpoints WHERE key = ?;")?;
+            let mut rows = stmt.query([key])?;
+            match rows.next()? {
+                Some(row) => Ok(Some(row.get(0)?)),
+                None => Ok(None),
+            }
+        })
+        .await?
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn count(&self, filters: Vec<Filter>) -> Result<usize, Self::Err> {
         Ok(self.indexes.count(filters).await)
     }
 
+    #[tracing::instrument(skip_all, level = "trace")]
+    async fn tag_values(
+        &self,
+        filter: Filter,
+        tag: SingleLetterTag,
+    ) -> Result<HashMap<String, usize>, Self::Err> {
+        Ok(self.indexes.tag_values(filter, tag).await)
+    }
+
     #[tracing::instrument(skip_all, level = "trace")]
     async fn query(&self, filters: Vec<Filter>, order: Order) -> Result<Vec<Event>, Self::Err> {
         let conn = self.acquire().await?;
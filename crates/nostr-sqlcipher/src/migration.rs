@@ -12,7 +12,7 @@ use thiserror::Error;
 use super::Error;
 
 /// Latest database version
-pub const DB_VERSION: usize = 1;
+pub const DB_VERSION: usize = 2;
 
 /// Startup DB Pragmas
 pub const STARTUP_SQL: &str = r##"
@@ -56,10 +56,10 @@ pub(crate) async fn run(conn: &Object) -> Result<(), Error> {
 
                 // for initialized but out-of-date schemas, proceed to
                 // upgrade sequentially until we are current.
-                // if curr_version == 1 {
-                // curr_version = mig_1_to_2(conn)?;
-                // }
-                //
+                if curr_version == 1 {
+                    curr_version = mig_1_to_2(conn)?;
+                }
+
                 // if curr_version == 2 {
                 // curr_version = mig_2_to_3(conn)?;
                 // }
@@ -110,8 +110,14 @@ fn mig_init(conn: &mut Connection) -> Result<usize, Error> {
     Ok(1)
 }
 
-// fn mig_1_to_2(conn: &mut Connection) -> Result<usize, Error> {
-// conn.execute_batch(include_str!("../../migrations/002_notifications.sql"))?;
-// tracing::info!("database schema upgraded v1 -> v2");
-// Ok(2)
+fn mig_1_to_2(conn: &mut Connection) -> Result<usize, Error> {
+    conn.execute_batch(include_str!("../migrations/002_checkpoints.sql"))?;
+    tracing::info!("database schema upgraded v1 -> v2");
+    Ok(2)
+}
+
+// fn mig_2_to_3(conn: &mut Connection) -> Result<usize, Error> {
+// conn.execute_batch(include_str!("../../migrations/003_notifications.sql"))?;
+// tracing::info!("database schema upgraded v2 -> v3");
+// Ok(3)
 // }
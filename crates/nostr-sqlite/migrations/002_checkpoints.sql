@@ -0,0 +1,6 @@
+PRAGMA user_version = 2; -- Schema version
+
+CREATE TABLE IF NOT EXISTS checkpoints (
+    key TEXT PRIMARY KEY NOT NULL,
+    value BLOB NOT NULL
+);
@@ -6,9 +6,15 @@
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use core::fmt;
 use core::ops::Range;
+use core::{fmt, iter};
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
 
+#[cfg(feature = "nip03")]
+use base64::engine::{general_purpose, Engine};
 #[cfg(feature = "std")]
 use bitcoin::secp256k1::rand;
 use bitcoin::secp256k1::rand::{CryptoRng, Rng};
@@ -20,11 +26,19 @@ use super::tag::ImageDimensions;
 use super::{Event, EventId, Marker, Tag, TagKind, UnsignedEvent};
 use crate::key::{self, Keys, PublicKey};
 use crate::nips::nip01::Coordinate;
+#[cfg(feature = "nip03")]
+use crate::nips::nip03;
 #[cfg(feature = "nip04")]
 use crate::nips::nip04;
 use crate::nips::nip15::{ProductData, StallData};
+use crate::nips::nip25::ReactionContent;
+use crate::nips::nip26::DelegationTag;
+use crate::nips::nip34::{GitIssue, GitPatch, GitRepositoryAnnouncement, GitStatusUpdate};
+use crate::nips::nip37::Draft;
 #[cfg(all(feature = "std", feature = "nip44"))]
-use crate::nips::nip44::{self, Version};
+use crate::nips::nip44;
+#[cfg(all(feature = "std", feature = "nip59"))]
+use crate::nips::nip44::Version;
 #[cfg(all(feature = "std", feature = "nip46"))]
 use crate::nips::nip46::Message as NostrConnectMessage;
 use crate::nips::nip51::{ArticlesCuration, Bookmarks, Emojis, Interests, MuteList};
@@ -32,10 +46,14 @@ use crate::nips::nip53::LiveEvent;
 #[cfg(feature = "nip57")]
 use crate::nips::nip57::ZapRequestData;
 use crate::nips::nip58::Error as Nip58Error;
+use crate::nips::nip89::{HandlerInformation, HandlerRecommendation};
 use crate::nips::nip90::DataVendingMachineStatus;
+#[cfg(all(feature = "std", feature = "nip04"))]
+use crate::nips::nip90::JobRequest;
 use crate::nips::nip94::FileMetadata;
 use crate::nips::nip98::HttpData;
-use crate::nips::{nip13, nip58};
+use crate::nips::nip99::ClassifiedListing;
+use crate::nips::{nip10, nip13, nip58};
 #[cfg(feature = "std")]
 use crate::types::time::Instant;
 use crate::types::time::TimeSupplier;
@@ -63,6 +81,118 @@ impl fmt::Display for WrongKindError {
     }
 }
 
+/// Invalid tag value error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidTagError {
+    /// Empty hashtag or hashtag containing whitespaces
+    Hashtag,
+    /// Invalid public key (expected hex, bech32 or NIP21 URI)
+    PublicKey,
+    /// Invalid event ID (expected hex, bech32 or NIP21 URI)
+    EventId,
+    /// Invalid relay URL (expected `ws://` or `wss://` URL)
+    RelayUrl,
+    /// Empty subject
+    Subject,
+    /// Invalid geohash (expected 1 to 12 base32 chars)
+    Geohash,
+}
+
+impl fmt::Display for InvalidTagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hashtag => write!(f, "invalid hashtag"),
+            Self::PublicKey => write!(f, "invalid public key"),
+            Self::EventId => write!(f, "invalid event ID"),
+            Self::RelayUrl => write!(f, "invalid relay URL"),
+            Self::Subject => write!(f, "empty subject"),
+            Self::Geohash => write!(f, "invalid geohash"),
+        }
+    }
+}
+
+/// Event limits exceeded error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitError {
+    /// Content too long
+    ContentTooLong {
+        /// Content size, in bytes
+        size: usize,
+        /// Max allowed size, in bytes
+        max: usize,
+    },
+    /// Too many tags
+    TooManyTags {
+        /// Number of tags
+        count: usize,
+        /// Max allowed number of tags
+        max: usize,
+    },
+    /// Tag value too long
+    TagValueTooLong {
+        /// Index of the tag
+        index: usize,
+        /// Value size, in bytes
+        size: usize,
+        /// Max allowed size, in bytes
+        max: usize,
+    },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ContentTooLong { size, max } => {
+                write!(f, "content too long: size={size}, max={max}")
+            }
+            Self::TooManyTags { count, max } => {
+                write!(f, "too many tags: count={count}, max={max}")
+            }
+            Self::TagValueTooLong { index, size, max } => {
+                write!(
+                    f,
+                    "tag value too long: index={index}, size={size}, max={max}"
+                )
+            }
+        }
+    }
+}
+
+/// Event size and shape limits
+///
+/// Used to avoid building events that almost no relay will accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventLimits {
+    /// Max content size, in bytes (default: 65_536)
+    pub max_content_size: Option<usize>,
+    /// Max number of tags (default: 2_000)
+    pub max_num_tags: Option<usize>,
+    /// Max size of a single tag value, in bytes (default: 8_192)
+    pub max_tag_value_size: Option<usize>,
+}
+
+impl Default for EventLimits {
+    fn default() -> Self {
+        Self {
+            max_content_size: Some(65_536),
+            max_num_tags: Some(2_000),
+            max_tag_value_size: Some(8_192),
+        }
+    }
+}
+
+impl EventLimits {
+    /// Disable all limits
+    #[inline]
+    pub fn disable() -> Self {
+        Self {
+            max_content_size: None,
+            max_num_tags: None,
+            max_tag_value_size: None,
+        }
+    }
+}
+
 /// [`EventBuilder`] error
 #[derive(Debug)]
 pub enum Error {
@@ -77,6 +207,9 @@ pub enum Error {
     /// OpenTimestamps error
     #[cfg(feature = "nip03")]
     OpenTimestamps(nostr_ots::Error),
+    /// NIP03 error
+    #[cfg(feature = "nip03")]
+    NIP03(nip03::Error),
     /// NIP04 error
     #[cfg(feature = "nip04")]
     NIP04(nip04::Error),
@@ -85,6 +218,12 @@ pub enum Error {
     NIP44(nip44::Error),
     /// NIP58 error
     NIP58(nip58::Error),
+    /// POW mining cancelled
+    PowCancelled,
+    /// Invalid tag value
+    InvalidTag(InvalidTagError),
+    /// Event limits exceeded
+    Limit(LimitError),
     /// Wrong kind
     WrongKind {
         /// The received wrong kind
@@ -106,11 +245,16 @@ impl fmt::Display for Error {
             Self::Unsigned(e) => write!(f, "Unsigned event: {e}"),
             #[cfg(feature = "nip03")]
             Self::OpenTimestamps(e) => write!(f, "NIP03: {e}"),
+            #[cfg(feature = "nip03")]
+            Self::NIP03(e) => write!(f, "NIP03: {e}"),
             #[cfg(feature = "nip04")]
             Self::NIP04(e) => write!(f, "NIP04: {e}"),
             #[cfg(all(feature = "std", feature = "nip44"))]
             Self::NIP44(e) => write!(f, "NIP44: {e}"),
             Self::NIP58(e) => write!(f, "NIP58: {e}"),
+            Self::PowCancelled => write!(f, "POW mining cancelled"),
+            Self::InvalidTag(e) => write!(f, "Invalid tag: {e}"),
+            Self::Limit(e) => write!(f, "Limit exceeded: {e}"),
             Self::WrongKind { received, expected } => {
                 write!(f, "Wrong kind: received={received}, expected={expected}")
             }
@@ -149,6 +293,13 @@ impl From<nostr_ots::Error> for Error {
     }
 }
 
+#[cfg(feature = "nip03")]
+impl From<nip03::Error> for Error {
+    fn from(e: nip03::Error) -> Self {
+        Self::NIP03(e)
+    }
+}
+
 #[cfg(feature = "nip04")]
 impl From<nip04::Error> for Error {
     fn from(e: nip04::Error) -> Self {
@@ -169,6 +320,18 @@ impl From<nip58::Error> for Error {
     }
 }
 
+impl From<InvalidTagError> for Error {
+    fn from(e: InvalidTagError) -> Self {
+        Self::InvalidTag(e)
+    }
+}
+
+impl From<LimitError> for Error {
+    fn from(e: LimitError) -> Self {
+        Self::Limit(e)
+    }
+}
+
 /// [`Event`] builder
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct EventBuilder {
@@ -200,6 +363,168 @@ impl EventBuilder {
         self
     }
 
+    /// Add hashtag (`t` tag)
+    ///
+    /// The leading `#` is removed and the hashtag is lowercased.
+    pub fn hashtag<S>(mut self, hashtag: S) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        let hashtag: &str = hashtag.as_ref();
+        let hashtag: &str = hashtag.strip_prefix('#').unwrap_or(hashtag);
+        if hashtag.is_empty() || hashtag.contains(char::is_whitespace) {
+            return Err(Error::InvalidTag(InvalidTagError::Hashtag));
+        }
+        self.tags.push(Tag::Hashtag(hashtag.to_lowercase()));
+        Ok(self)
+    }
+
+    /// Add public key mention (`p` tag)
+    ///
+    /// The public key can be hex, bech32 or NIP21 URI.
+    pub fn mention<S>(mut self, public_key: S) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        let public_key: PublicKey = PublicKey::parse(public_key)
+            .map_err(|_| Error::InvalidTag(InvalidTagError::PublicKey))?;
+        self.tags.push(Tag::PublicKey {
+            public_key,
+            relay_url: None,
+            alias: None,
+            uppercase: false,
+        });
+        Ok(self)
+    }
+
+    /// Add event reference (`e` tag)
+    ///
+    /// The event ID can be hex, bech32 or NIP21 URI.
+    pub fn reference_event<S>(
+        mut self,
+        event_id: S,
+        relay_url: Option<S>,
+        marker: Option<Marker>,
+    ) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        let event_id: EventId =
+            EventId::parse(event_id).map_err(|_| Error::InvalidTag(InvalidTagError::EventId))?;
+        let relay_url: Option<UncheckedUrl> = match relay_url {
+            Some(url) => Some(validate_relay_url(url.as_ref())?),
+            None => None,
+        };
+        self.tags.push(Tag::Event {
+            event_id,
+            relay_url,
+            marker,
+        });
+        Ok(self)
+    }
+
+    /// Add subject (`subject` tag)
+    pub fn subject<S>(mut self, subject: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let subject: String = subject.into();
+        if subject.trim().is_empty() {
+            return Err(Error::InvalidTag(InvalidTagError::Subject));
+        }
+        self.tags.push(Tag::Subject(subject));
+        Ok(self)
+    }
+
+    /// Add geohash (`g` tag)
+    pub fn geohash<S>(mut self, geohash: S) -> Result<Self, Error>
+    where
+        S: AsRef<str>,
+    {
+        const GEOHASH_ALPHABET: &str = "0123456789bcdefghjkmnpqrstuvwxyz";
+
+        let geohash: String = geohash.as_ref().to_lowercase();
+        if geohash.is_empty()
+            || geohash.len() > 12
+            || !geohash.chars().all(|c| GEOHASH_ALPHABET.contains(c))
+        {
+            return Err(Error::InvalidTag(InvalidTagError::Geohash));
+        }
+        self.tags.push(Tag::Geohash(geohash));
+        Ok(self)
+    }
+
+    /// Mark the event as protected (`-` tag)
+    ///
+    /// Relays supporting NIP70 accept it only from the authenticated author.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/70.md>
+    #[inline]
+    pub fn protected(mut self) -> Self {
+        self.tags.push(Tag::Protected);
+        self
+    }
+
+    /// Add NIP36 content warning (`content-warning` tag)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/36.md>
+    #[inline]
+    pub fn content_warning<S>(mut self, reason: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        self.tags.push(Tag::ContentWarning {
+            reason: reason.map(|r| r.into()),
+        });
+        self
+    }
+
+    /// Add NIP26 delegation (`delegation` tag)
+    ///
+    /// The event must be signed with the delegatee keys.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/26.md>
+    #[inline]
+    pub fn delegation(mut self, delegation: DelegationTag) -> Self {
+        self.tags.push(delegation.into());
+        self
+    }
+
+    /// Check the content and tags against the [`EventLimits`]
+    ///
+    /// Tag values are all the elements of a tag, except its kind.
+    pub fn check_limits(&self, limits: &EventLimits) -> Result<(), Error> {
+        if let Some(max) = limits.max_content_size {
+            let size: usize = self.content.len();
+            if size > max {
+                return Err(Error::Limit(LimitError::ContentTooLong { size, max }));
+            }
+        }
+
+        if let Some(max) = limits.max_num_tags {
+            let count: usize = self.tags.len();
+            if count > max {
+                return Err(Error::Limit(LimitError::TooManyTags { count, max }));
+            }
+        }
+
+        if let Some(max) = limits.max_tag_value_size {
+            for (index, tag) in self.tags.iter().enumerate() {
+                if let Some(size) = tag.as_vec().iter().skip(1).map(|v| v.len()).max() {
+                    if size > max {
+                        return Err(Error::Limit(LimitError::TagValueTooLong {
+                            index,
+                            size,
+                            max,
+                        }));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Build [`Event`]
     #[inline]
     pub fn to_event_with_ctx<C, R, T>(
@@ -347,6 +672,121 @@ impl EventBuilder {
     pub fn to_unsigned_pow_event(self, pubkey: PublicKey, difficulty: u8) -> UnsignedEvent {
         self.to_unsigned_pow_event_with_supplier(&Instant::now(), pubkey, difficulty)
     }
+
+    /// Build POW [`Event`] searching the nonce on more threads
+    ///
+    /// Use the [`PowToken`](nip13::PowToken) to cancel the mining or check the progress.
+    #[cfg(feature = "std")]
+    pub fn to_pow_event_with(
+        self,
+        keys: &Keys,
+        difficulty: u8,
+        threads: usize,
+        token: &nip13::PowToken,
+    ) -> Result<Event, Error> {
+        let pubkey: PublicKey = keys.public_key();
+        Ok(self
+            .to_unsigned_pow_event_with(pubkey, difficulty, threads, token)?
+            .sign(keys)?)
+    }
+
+    /// Build unsigned POW [`Event`] searching the nonce on more threads
+    ///
+    /// Every thread checks a different set of nonces (`thread index + 1`, stepping by `threads`).
+    /// Return [`Error::PowCancelled`] if the mining is cancelled with the [`PowToken`](nip13::PowToken).
+    #[cfg(feature = "std")]
+    pub fn to_unsigned_pow_event_with(
+        self,
+        pubkey: PublicKey,
+        difficulty: u8,
+        threads: usize,
+        token: &nip13::PowToken,
+    ) -> Result<UnsignedEvent, Error> {
+        const PROGRESS_BATCH: u64 = 1_000;
+
+        let threads: usize = threads.max(1);
+        let found: AtomicBool = AtomicBool::new(false);
+        let result: Mutex<Option<(u128, Timestamp, EventId)>> = Mutex::new(None);
+        let now = Instant::now();
+
+        std::thread::scope(|scope| {
+            for index in 0..threads {
+                let builder: &Self = &self;
+                let found: &AtomicBool = &found;
+                let result = &result;
+                scope.spawn(move || {
+                    let mut tags: Vec<Tag> = builder.tags.clone();
+                    let mut nonce: u128 = index as u128 + 1;
+                    let mut counter: u64 = 0;
+
+                    while !found.load(Ordering::Relaxed) && !token.is_cancelled() {
+                        tags.push(Tag::POW { nonce, difficulty });
+
+                        let created_at: Timestamp =
+                            builder.custom_created_at.unwrap_or_else(Timestamp::now);
+                        let id: EventId = EventId::new(
+                            &pubkey,
+                            created_at,
+                            &builder.kind,
+                            &tags,
+                            &builder.content,
+                        );
+
+                        counter += 1;
+
+                        if nip13::get_leading_zero_bits(id.inner()) >= difficulty {
+                            if !found.swap(true, Ordering::SeqCst) {
+                                if let Ok(mut result) = result.lock() {
+                                    *result = Some((nonce, created_at, id));
+                                }
+                            }
+                            break;
+                        }
+
+                        tags.pop();
+                        nonce += threads as u128;
+
+                        if counter == PROGRESS_BATCH {
+                            token.add_hashes(counter);
+                            counter = 0;
+                        }
+                    }
+
+                    token.add_hashes(counter);
+                });
+            }
+        });
+
+        let result = result.into_inner().ok().flatten();
+        match result {
+            Some((nonce, created_at, id)) => {
+                tracing::debug!(
+                    "{} hashes on {threads} threads in {} ms",
+                    token.hashes(),
+                    now.elapsed().as_millis(),
+                );
+
+                let mut tags: Vec<Tag> = self.tags;
+                tags.push(Tag::POW { nonce, difficulty });
+
+                Ok(UnsignedEvent {
+                    id: Some(id),
+                    pubkey,
+                    created_at,
+                    kind: self.kind,
+                    tags,
+                    content: self.content,
+                })
+            }
+            None => Err(Error::PowCancelled),
+        }
+    }
+
+    /// Get [`Kind`]
+    #[inline]
+    pub fn kind(&self) -> Kind {
+        self.kind
+    }
 }
 
 impl EventBuilder {
@@ -407,7 +847,10 @@ impl EventBuilder {
 
     /// Text note reply
     ///
-    /// If no `root` is passed, the `rely_to` will be used for root `e` tag.
+    /// If no `root` is passed, it's resolved from the thread of `reply_to` (see [`nip10::extract_thread`]).
+    /// If `reply_to` is the root, only the `root` marked `e` tag is added.
+    ///
+    /// `relay_url` is used as relay hint for `reply_to` and for `root`, if passed.
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/10.md>
     pub fn text_note_reply<S>(
@@ -421,61 +864,44 @@ impl EventBuilder {
     {
         let mut tags: Vec<Tag> = Vec::new();
 
-        // Add `e` and `p` tag of **root** event
+        // Add `e` tag of **root** event
+        let root: Option<(EventId, Option<UncheckedUrl>)> = match root {
+            Some(root) => Some((root.id(), relay_url.clone())),
+            None => nip10::extract_thread(reply_to)
+                .root
+                .map(|root| (root.event_id, root.relay_url)),
+        };
         match root {
-            Some(root) => {
-                // ID and author
+            Some((root_id, root_relay_url)) if root_id != reply_to.id() => {
                 tags.push(Tag::Event {
-                    event_id: root.id(),
-                    relay_url: relay_url.clone(),
+                    event_id: root_id,
+                    relay_url: root_relay_url,
                     marker: Some(Marker::Root),
                 });
-                tags.push(Tag::public_key(root.author()));
-
-                // Add others `p` tags
-                tags.extend(
-                    root.iter_tags()
-                        .filter(|t| {
-                            t.kind()
-                                == TagKind::SingleLetter(SingleLetterTag {
-                                    character: Alphabet::P,
-                                    uppercase: false,
-                                })
-                        })
-                        .cloned(),
-                );
+                tags.push(Tag::Event {
+                    event_id: reply_to.id(),
+                    relay_url,
+                    marker: Some(Marker::Reply),
+                });
             }
-            None => {
-                // No root event is passed, use `reply_to` event ID for `root` marker
+            _ => {
+                // Direct reply to the root
                 tags.push(Tag::Event {
                     event_id: reply_to.id(),
-                    relay_url: relay_url.clone(),
+                    relay_url,
                     marker: Some(Marker::Root),
                 });
             }
         }
 
-        // Add `e` and `p` tag of event author
-        tags.push(Tag::Event {
-            event_id: reply_to.id(),
-            relay_url,
-            marker: Some(Marker::Reply),
-        });
-        tags.push(Tag::public_key(reply_to.author()));
-
-        // Add others `p` tags of reply_to event
-        tags.extend(
-            reply_to
-                .iter_tags()
-                .filter(|t| {
-                    t.kind()
-                        == TagKind::SingleLetter(SingleLetterTag {
-                            character: Alphabet::P,
-                            uppercase: false,
-                        })
-                })
-                .cloned(),
-        );
+        // Add `p` tags of the author and of the participants of the replied event (without duplicates)
+        let mut public_keys: Vec<PublicKey> = vec![reply_to.author()];
+        for public_key in reply_to.public_keys() {
+            if !public_keys.contains(public_key) {
+                public_keys.push(*public_key);
+            }
+        }
+        tags.extend(public_keys.into_iter().map(Tag::public_key));
 
         // Compose event
         Self::new(Kind::TextNote, content, tags)
@@ -511,6 +937,20 @@ impl EventBuilder {
         Self::new(Kind::LongFormTextNote, content, tags)
     }
 
+    /// Long-form text note draft
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/23.md>
+    ///
+    /// Tags can be composed with [`LongFormContent`](crate::nips::nip23::LongFormContent).
+    #[inline]
+    pub fn draft<S, I>(content: S, tags: I) -> Self
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = Tag>,
+    {
+        Self::new(Kind::LongFormTextNoteDraft, content, tags)
+    }
+
     /// Contact list
     pub fn contact_list<I>(contacts: I) -> Self
     where
@@ -545,6 +985,114 @@ impl EventBuilder {
         ))
     }
 
+    /// OpenTimestamps attestation from an existing OTS proof
+    ///
+    /// The proof must commit to the event ID (see [`nip03::verify_proof`]).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/03.md>
+    #[cfg(feature = "nip03")]
+    pub fn opentimestamps_attestation(
+        event_id: EventId,
+        relay_url: Option<UncheckedUrl>,
+        proof: &[u8],
+    ) -> Result<Self, Error> {
+        nip03::verify_proof(proof, &event_id)?;
+        Ok(Self::new(
+            Kind::OpenTimestamps,
+            general_purpose::STANDARD.encode(proof),
+            [Tag::Event {
+                event_id,
+                relay_url,
+                marker: None,
+            }],
+        ))
+    }
+
+    /// Draft event
+    ///
+    /// `encrypted_payload` must be the [`Draft::payload`] encrypted to self with NIP44.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/37.md>
+    pub fn event_draft<S>(draft: &Draft, encrypted_payload: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self::new(
+            Kind::Draft,
+            encrypted_payload,
+            [
+                Tag::Identifier(draft.identifier.clone()),
+                Tag::Kind(draft.kind),
+            ],
+        )
+    }
+
+    /// Git repository announcement
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/34.md>
+    #[inline]
+    pub fn git_repository_announcement(repository: GitRepositoryAnnouncement) -> Self {
+        let tags: Vec<Tag> = repository.into();
+        Self::new(Kind::GitRepoAnnouncement, "", tags)
+    }
+
+    /// Git patch
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/34.md>
+    #[inline]
+    pub fn git_patch(patch: &GitPatch) -> Self {
+        Self::new(Kind::GitPatch, &patch.content, patch.to_tags())
+    }
+
+    /// Git issue
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/34.md>
+    #[inline]
+    pub fn git_issue(issue: &GitIssue) -> Self {
+        Self::new(Kind::GitIssue, &issue.content, issue.to_tags())
+    }
+
+    /// Git patch or issue status
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/34.md>
+    #[inline]
+    pub fn git_status(update: &GitStatusUpdate) -> Self {
+        Self::new(update.status.kind(), &update.content, update.to_tags())
+    }
+
+    /// Classified listing
+    ///
+    /// The kind is `30403` if the listing is a draft, `30402` otherwise.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/99.md>
+    pub fn classified_listing(listing: ClassifiedListing) -> Self {
+        let kind: Kind = listing.kind();
+        let content: String = listing.description.clone();
+        let tags: Vec<Tag> = listing.into();
+        Self::new(kind, content, tags)
+    }
+
+    /// Handler information
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub fn handler_information(info: HandlerInformation) -> Self {
+        let content: String = info
+            .metadata
+            .as_ref()
+            .map(|m| m.as_json())
+            .unwrap_or_default();
+        let tags: Vec<Tag> = info.into();
+        Self::new(Kind::HandlerInformation, content, tags)
+    }
+
+    /// Handler recommendation
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md>
+    pub fn handler_recommendation(recommendation: HandlerRecommendation) -> Self {
+        let tags: Vec<Tag> = recommendation.into();
+        Self::new(Kind::HandlerRecommendation, "", tags)
+    }
+
     /// Create encrypted direct msg event
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/04.md>
@@ -570,6 +1118,11 @@ impl EventBuilder {
     }
 
     /// Repost
+    ///
+    /// Text notes are reposted with kind `6`, other events with kind `16` (generic repost).
+    /// The reposted event is embedded in the content: use [`extract_reposted_event`](crate::nips::nip18::extract_reposted_event) to extract it.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/18.md>
     pub fn repost(event: &Event, relay_url: Option<UncheckedUrl>) -> Self {
         if event.kind == Kind::TextNote {
             Self::new(
@@ -585,20 +1138,57 @@ impl EventBuilder {
                 ],
             )
         } else {
-            Self::new(
-                Kind::GenericRepost,
-                event.as_json(),
-                [
-                    Tag::Event {
-                        event_id: event.id(),
+            let mut tags: Vec<Tag> = vec![
+                Tag::Event {
+                    event_id: event.id(),
+                    relay_url: relay_url.clone(),
+                    marker: None,
+                },
+                Tag::public_key(event.author()),
+                Tag::Kind(event.kind()),
+            ];
+
+            // Reference the address of parameterized replaceable events
+            if event.kind().is_parameterized_replaceable() {
+                if let Some(identifier) = event.identifier() {
+                    tags.push(Tag::A {
+                        coordinate: Coordinate::new(event.kind(), event.author())
+                            .identifier(identifier),
                         relay_url,
-                        marker: None,
-                    },
-                    Tag::public_key(event.author()),
-                    Tag::Kind(event.kind()),
-                ],
-            )
+                    });
+                }
+            }
+
+            Self::new(Kind::GenericRepost, event.as_json(), tags)
+        }
+    }
+
+    /// Quote repost
+    ///
+    /// Text note with a `q` tag referencing the quoted event.
+    /// The NIP21 URI of the quoted event (i.e. `nostr:nevent1...`) should be included in `content`.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/18.md>
+    pub fn quote<S>(content: S, quoted: &Event, relay_url: Option<UncheckedUrl>) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut q: Vec<String> = vec![quoted.id().to_hex()];
+        if let Some(relay_url) = relay_url {
+            q.push(relay_url.to_string());
         }
+
+        Self::new(
+            Kind::TextNote,
+            content,
+            [
+                Tag::custom(
+                    TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::Q)),
+                    q,
+                ),
+                Tag::public_key(quoted.author()),
+            ],
+        )
     }
 
     /// Create delete event
@@ -641,6 +1231,28 @@ impl EventBuilder {
         )
     }
 
+    /// Add reaction to an event, with custom emoji and `k` tag support
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/25.md>
+    pub fn reaction_extended(
+        event_id: EventId,
+        public_key: PublicKey,
+        kind: Option<Kind>,
+        reaction: ReactionContent,
+    ) -> Self {
+        let mut tags: Vec<Tag> = vec![Tag::event(event_id), Tag::public_key(public_key)];
+
+        if let Some(kind) = kind {
+            tags.push(Tag::Kind(kind));
+        }
+
+        if let Some(emoji) = reaction.emoji_tag() {
+            tags.push(emoji);
+        }
+
+        Self::new(Kind::Reaction, reaction.content(), tags)
+    }
+
     /// Create new channel
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -686,6 +1298,43 @@ impl EventBuilder {
         )
     }
 
+    /// Channel message replying to another message of the channel
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
+    pub fn channel_msg_reply<S>(
+        channel_id: EventId,
+        relay_url: Url,
+        reply_to: &Event,
+        content: S,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let relay_url: UncheckedUrl = relay_url.into();
+        Self::new(
+            Kind::ChannelMessage,
+            content,
+            [
+                Tag::Event {
+                    event_id: channel_id,
+                    relay_url: Some(relay_url.clone()),
+                    marker: Some(Marker::Root),
+                },
+                Tag::Event {
+                    event_id: reply_to.id(),
+                    relay_url: Some(relay_url.clone()),
+                    marker: Some(Marker::Reply),
+                },
+                Tag::PublicKey {
+                    public_key: reply_to.author(),
+                    relay_url: Some(relay_url),
+                    alias: None,
+                    uppercase: false,
+                },
+            ],
+        )
+    }
+
     /// Hide message
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/28.md>
@@ -1028,7 +1677,7 @@ impl EventBuilder {
         }
 
         // Add identifier `d` tag
-        let id_tag: Tag = Tag::Identifier("profile_badges".to_string());
+        let id_tag: Tag = Tag::Identifier(nip58::PROFILE_BADGES_IDENTIFIER.to_string());
         let mut tags: Vec<Tag> = vec![id_tag];
 
         let badge_definitions_identifiers = badge_definitions.into_iter().filter_map(|event| {
@@ -1088,6 +1737,30 @@ impl EventBuilder {
         }
     }
 
+    /// Data Vending Machine - Job Request with encrypted inputs and params
+    ///
+    /// Inputs and params are NIP04 encrypted to the `service_provider` and moved to the content.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/90.md#encrypted-params>
+    #[cfg(all(feature = "std", feature = "nip04"))]
+    pub fn encrypted_job_request(
+        customer_keys: &Keys,
+        service_provider: PublicKey,
+        mut request: JobRequest,
+    ) -> Result<Self, Error> {
+        request.service_providers = vec![service_provider];
+        let kind: Kind = request.kind;
+        let (mut tags, private) = request.into_tags();
+        let private: Vec<Vec<String>> = private.into_iter().map(|t| t.as_vec()).collect();
+        let content: String = nip04::encrypt(
+            customer_keys.secret_key()?,
+            &service_provider,
+            json!(private).to_string(),
+        )?;
+        tags.push(Tag::Encrypted);
+        Ok(Self::new(kind, content, tags))
+    }
+
     /// Data Vending Machine - Job Result
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/90.md>
@@ -1269,6 +1942,25 @@ impl EventBuilder {
         Self::new(Kind::SealedDirect, message, [Tag::public_key(receiver)])
     }
 
+    /// GiftWrapped Sealed Direct message to multiple receivers
+    ///
+    /// The rumor must be gift wrapped for each receiver (and for the sender, to sync the other devices).
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/17.md>
+    #[inline]
+    #[cfg(feature = "nip59")]
+    pub fn sealed_direct_group<I, S>(receivers: I, message: S) -> Self
+    where
+        I: IntoIterator<Item = PublicKey>,
+        S: Into<String>,
+    {
+        Self::new(
+            Kind::SealedDirect,
+            message,
+            receivers.into_iter().map(Tag::public_key),
+        )
+    }
+
     /// Mute list
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
@@ -1359,63 +2051,97 @@ impl EventBuilder {
     /// Follow sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
-    pub fn follow_sets<I>(publick_key: I) -> Self
+    pub fn follow_sets<S, I>(identifier: S, public_keys: I) -> Self
     where
+        S: Into<String>,
         I: IntoIterator<Item = PublicKey>,
     {
-        let tags = publick_key.into_iter().map(Tag::public_key);
-        Self::new(Kind::FollowSets, "", tags)
+        let tags = public_keys.into_iter().map(Tag::public_key);
+        Self::new(
+            Kind::FollowSets,
+            "",
+            iter::once(Tag::Identifier(identifier.into())).chain(tags),
+        )
     }
 
     /// Relay sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
-    pub fn relay_sets<I>(relay: I) -> Self
+    pub fn relay_sets<S, I>(identifier: S, relays: I) -> Self
     where
+        S: Into<String>,
         I: IntoIterator<Item = UncheckedUrl>,
     {
-        let tags = relay.into_iter().map(Tag::Relay);
-        Self::new(Kind::RelaySets, "", tags)
+        let tags = relays.into_iter().map(Tag::Relay);
+        Self::new(
+            Kind::RelaySets,
+            "",
+            iter::once(Tag::Identifier(identifier.into())).chain(tags),
+        )
     }
 
     /// Bookmark sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
-    pub fn bookmarks_sets(list: Bookmarks) -> Self {
+    pub fn bookmarks_sets<S>(identifier: S, list: Bookmarks) -> Self
+    where
+        S: Into<String>,
+    {
         let tags: Vec<Tag> = list.into();
-        Self::new(Kind::BookmarkSets, "", tags)
+        Self::new(
+            Kind::BookmarkSets,
+            "",
+            iter::once(Tag::Identifier(identifier.into())).chain(tags),
+        )
     }
 
     /// Article Curation sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
-    pub fn articles_curation_sets(list: ArticlesCuration) -> Self {
+    pub fn articles_curation_sets<S>(identifier: S, list: ArticlesCuration) -> Self
+    where
+        S: Into<String>,
+    {
         let tags: Vec<Tag> = list.into();
-        Self::new(Kind::ArticlesCurationSets, "", tags)
+        Self::new(
+            Kind::ArticlesCurationSets,
+            "",
+            iter::once(Tag::Identifier(identifier.into())).chain(tags),
+        )
     }
 
     /// Videos Curation sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
-    pub fn videos_curation_sets<I>(video: I) -> Self
+    pub fn videos_curation_sets<S, I>(identifier: S, videos: I) -> Self
     where
+        S: Into<String>,
         I: IntoIterator<Item = Coordinate>,
     {
-        let tags = video.into_iter().map(Tag::from);
-        Self::new(Kind::VideosCurationSets, "", tags)
+        let tags = videos.into_iter().map(Tag::from);
+        Self::new(
+            Kind::VideosCurationSets,
+            "",
+            iter::once(Tag::Identifier(identifier.into())).chain(tags),
+        )
     }
 
     /// Emoji sets
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/51.md>
-    pub fn emoji_sets<I>(emoji: I) -> Self
+    pub fn emoji_sets<S, I>(identifier: S, emojis: I) -> Self
     where
+        S: Into<String>,
         I: IntoIterator<Item = (String, UncheckedUrl)>,
     {
-        let tags = emoji
+        let tags = emojis
             .into_iter()
             .map(|(s, url)| Tag::Emoji { shortcode: s, url });
-        Self::new(Kind::EmojiSets, "", tags)
+        Self::new(
+            Kind::EmojiSets,
+            "",
+            iter::once(Tag::Identifier(identifier.into())).chain(tags),
+        )
     }
 
     /// Label
@@ -1436,6 +2162,13 @@ impl EventBuilder {
     }
 }
 
+fn validate_relay_url(url: &str) -> Result<UncheckedUrl, Error> {
+    match Url::parse(url) {
+        Ok(url) if url.scheme() == "ws" || url.scheme() == "wss" => Ok(UncheckedUrl::from(url)),
+        _ => Err(Error::InvalidTag(InvalidTagError::RelayUrl)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "std")]
@@ -1463,6 +2196,126 @@ mod tests {
         assert_eq!(event, deserialized);
     }
 
+    #[test]
+    fn test_fluent_tag_helpers() {
+        let builder = EventBuilder::text_note("hello", [])
+            .hashtag("#Nostr")
+            .unwrap()
+            .mention("npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy")
+            .unwrap()
+            .reference_event(
+                "d94a3f4dd87b9a3b0bed183b32e916fa29c8020107845d1752d72697fe5309a5",
+                Some("wss://relay.damus.io"),
+                Some(Marker::Reply),
+            )
+            .unwrap()
+            .subject("Greetings")
+            .unwrap()
+            .geohash("u4pruydqqvj")
+            .unwrap();
+
+        assert_eq!(builder.tags.len(), 5);
+        assert_eq!(builder.tags[0], Tag::Hashtag(String::from("nostr")));
+        assert_eq!(builder.tags[4], Tag::Geohash(String::from("u4pruydqqvj")));
+    }
+
+    #[test]
+    fn test_fluent_tag_helpers_validation() {
+        let builder = EventBuilder::text_note("hello", []);
+
+        assert!(matches!(
+            builder.clone().hashtag("#"),
+            Err(Error::InvalidTag(InvalidTagError::Hashtag))
+        ));
+        assert!(matches!(
+            builder.clone().mention("abcdef"),
+            Err(Error::InvalidTag(InvalidTagError::PublicKey))
+        ));
+        assert!(matches!(
+            builder.clone().reference_event(
+                "d94a3f4dd87b9a3b0bed183b32e916fa29c8020107845d1752d72697fe5309a5",
+                Some("https://example.com"),
+                None
+            ),
+            Err(Error::InvalidTag(InvalidTagError::RelayUrl))
+        ));
+        assert!(matches!(
+            builder.clone().subject("  "),
+            Err(Error::InvalidTag(InvalidTagError::Subject))
+        ));
+        assert!(matches!(
+            builder.geohash("u4pruyaqqvj"),
+            Err(Error::InvalidTag(InvalidTagError::Geohash))
+        ));
+    }
+
+    #[test]
+    fn test_check_limits() {
+        let limits = EventLimits {
+            max_content_size: Some(5),
+            max_num_tags: Some(1),
+            max_tag_value_size: Some(3),
+        };
+
+        let builder = EventBuilder::text_note("hello", [Tag::Hashtag(String::from("abc"))]);
+        assert!(builder.check_limits(&limits).is_ok());
+
+        let builder = EventBuilder::text_note("hello!", []);
+        assert!(matches!(
+            builder.check_limits(&limits),
+            Err(Error::Limit(LimitError::ContentTooLong { size: 6, max: 5 }))
+        ));
+
+        let builder = EventBuilder::text_note(
+            "",
+            [
+                Tag::Hashtag(String::from("a")),
+                Tag::Hashtag(String::from("b")),
+            ],
+        );
+        assert!(matches!(
+            builder.check_limits(&limits),
+            Err(Error::Limit(LimitError::TooManyTags { count: 2, max: 1 }))
+        ));
+
+        let builder = EventBuilder::text_note("", [Tag::Hashtag(String::from("abcd"))]);
+        assert!(matches!(
+            builder.check_limits(&limits),
+            Err(Error::Limit(LimitError::TagValueTooLong {
+                index: 0,
+                size: 4,
+                max: 3
+            }))
+        ));
+
+        let builder = EventBuilder::text_note("hello!", [Tag::Hashtag(String::from("abcd"))]);
+        assert!(builder.check_limits(&EventLimits::disable()).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_pow_event_with_threads() {
+        let keys = Keys::generate();
+        let token = nip13::PowToken::new();
+        let event = EventBuilder::text_note("POW", [])
+            .to_pow_event_with(&keys, 8, 4, &token)
+            .unwrap();
+        event.verify().unwrap();
+        assert!(event.check_pow(8));
+        assert!(token.hashes() > 0);
+
+        // Cancelled
+        let token = nip13::PowToken::new();
+        token.cancel();
+        let res = EventBuilder::text_note("POW", []).to_unsigned_pow_event_with(
+            keys.public_key(),
+            64,
+            2,
+            &token,
+        );
+        assert!(matches!(res, Err(Error::PowCancelled)));
+    }
+
     #[test]
     #[cfg(all(feature = "std", feature = "nip04"))]
     fn test_encrypted_direct_msg() {
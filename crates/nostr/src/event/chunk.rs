@@ -0,0 +1,237 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Chunked content
+//!
+//! Content too large for a single event (i.e. exceeding relay size limits) can be split
+//! across many events of the same kind, linked by a `chunk` tag:
+//! `["chunk", <group>, <index>, <total>]`, where `group` is the SHA256 of the whole content.
+//!
+//! The original tags are added only to the first chunk.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+
+use super::{Event, EventBuilder, Kind, Tag, TagKind};
+
+/// Chunk tag kind
+pub const CHUNK_TAG: &str = "chunk";
+
+/// Chunk error
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// No chunks
+    Empty,
+    /// Event without chunk tag
+    NotAChunk,
+    /// Chunks of different groups, kinds or authors
+    Mismatch,
+    /// Missing chunk
+    MissingChunk(usize),
+    /// Reassembled content not match the group hash
+    HashMismatch,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no chunks"),
+            Self::NotAChunk => write!(f, "not a chunk"),
+            Self::Mismatch => write!(f, "chunks not belonging to the same content"),
+            Self::MissingChunk(index) => write!(f, "missing chunk {index}"),
+            Self::HashMismatch => write!(f, "reassembled content not match the group hash"),
+        }
+    }
+}
+
+/// Position of a chunk
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ChunkInfo {
+    /// Group (SHA256 of the whole content)
+    pub group: Sha256Hash,
+    /// Chunk index, starting from `0`
+    pub index: usize,
+    /// Number of chunks
+    pub total: usize,
+}
+
+impl ChunkInfo {
+    /// Parse the chunk tag of an event
+    ///
+    /// Return `None` if the event is not a chunk.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        event.iter_tags().find_map(|tag| {
+            if tag.kind() != TagKind::Custom(CHUNK_TAG.to_string()) {
+                return None;
+            }
+            match tag.as_vec().as_slice() {
+                [_, group, index, total, ..] => {
+                    let info = Self {
+                        group: group.parse().ok()?,
+                        index: index.parse().ok()?,
+                        total: total.parse().ok()?,
+                    };
+                    (info.index < info.total).then_some(info)
+                }
+                _ => None,
+            }
+        })
+    }
+
+    fn to_tag(&self) -> Tag {
+        Tag::custom(
+            TagKind::Custom(CHUNK_TAG.to_string()),
+            [
+                self.group.to_string(),
+                self.index.to_string(),
+                self.total.to_string(),
+            ],
+        )
+    }
+}
+
+/// Split content in chunks of at most `max_size` bytes
+///
+/// Chunks are split on UTF-8 char boundaries. If the content fits in a single chunk,
+/// a single event without chunk tag is returned.
+pub fn split<S, I>(kind: Kind, content: S, tags: I, max_size: usize) -> Vec<EventBuilder>
+where
+    S: Into<String>,
+    I: IntoIterator<Item = Tag>,
+{
+    let content: String = content.into();
+    let max_size: usize = max_size.max(4);
+
+    if content.len() <= max_size {
+        return vec![EventBuilder::new(kind, content, tags)];
+    }
+
+    let mut parts: Vec<&str> = Vec::new();
+    let mut rest: &str = &content;
+    while !rest.is_empty() {
+        let mut end: usize = max_size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (part, tail) = rest.split_at(end);
+        parts.push(part);
+        rest = tail;
+    }
+
+    let group: Sha256Hash = Sha256Hash::hash(content.as_bytes());
+    let total: usize = parts.len();
+    let mut tags: Option<Vec<Tag>> = Some(tags.into_iter().collect());
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(index, part)| {
+            let mut chunk_tags: Vec<Tag> = tags.take().unwrap_or_default();
+            chunk_tags.push(
+                ChunkInfo {
+                    group,
+                    index,
+                    total,
+                }
+                .to_tag(),
+            );
+            EventBuilder::new(kind, part, chunk_tags)
+        })
+        .collect()
+}
+
+/// Reassemble the content of chunks (in any order, duplicates allowed)
+///
+/// All the chunks must have the same group, kind and author, and the reassembled content
+/// must match the group hash.
+pub fn reassemble<'a, I>(chunks: I) -> Result<String, Error>
+where
+    I: IntoIterator<Item = &'a Event>,
+{
+    let mut first: Option<(ChunkInfo, &Event)> = None;
+    let mut parts: BTreeMap<usize, &str> = BTreeMap::new();
+
+    for event in chunks.into_iter() {
+        let info: ChunkInfo = ChunkInfo::from_event(event).ok_or(Error::NotAChunk)?;
+        match &first {
+            Some((f, e)) => {
+                if f.group != info.group
+                    || f.total != info.total
+                    || e.kind() != event.kind()
+                    || e.author() != event.author()
+                {
+                    return Err(Error::Mismatch);
+                }
+            }
+            None => first = Some((info.clone(), event)),
+        }
+        parts.insert(info.index, event.content());
+    }
+
+    let (info, ..) = first.ok_or(Error::Empty)?;
+
+    let mut content: String = String::new();
+    for index in 0..info.total {
+        let part: &str = parts.get(&index).ok_or(Error::MissingChunk(index))?;
+        content.push_str(part);
+    }
+
+    if Sha256Hash::hash(content.as_bytes()) != info.group {
+        return Err(Error::HashMismatch);
+    }
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keys;
+
+    #[test]
+    fn test_split_reassemble() {
+        let keys = Keys::generate();
+        let content: String = "Lorem ipsum dolor sit amet ⚡ ".repeat(10);
+
+        let builders = split(
+            Kind::LongFormTextNote,
+            content.clone(),
+            [Tag::Identifier(String::from("lorem"))],
+            64,
+        );
+        assert!(builders.len() > 1);
+
+        let mut events: Vec<Event> = builders
+            .into_iter()
+            .map(|b| b.to_event(&keys).unwrap())
+            .collect();
+        assert_eq!(events[0].identifier(), Some("lorem"));
+        assert!(events.iter().all(|e| e.content().len() <= 64));
+
+        events.reverse();
+        assert_eq!(reassemble(&events).unwrap(), content);
+
+        // Missing chunk
+        let last = events.remove(0);
+        assert_eq!(
+            reassemble(&events),
+            Err(Error::MissingChunk(
+                ChunkInfo::from_event(&last).unwrap().index
+            ))
+        );
+
+        // Small content
+        let builders = split(Kind::TextNote, "Hello", [], 64);
+        assert_eq!(builders.len(), 1);
+        let event = builders[0].clone().to_event(&keys).unwrap();
+        assert!(ChunkInfo::from_event(&event).is_none());
+    }
+}
@@ -149,9 +149,22 @@ kind_variants! {
     EmojiSets => 30030, "Emoji Sets <https://github.com/nostr-protocol/nips/blob/master/51.md>",
     ReleaseArtifactSets => 30063, "Release Artifact Sets <https://github.com/nostr-protocol/nips/blob/master/51.md>",
     LongFormTextNote => 30023, "Long-form Text Note (NIP23)",
+    LongFormTextNoteDraft => 30024, "Long-form Text Note Draft (NIP23)",
     FileMetadata => 1063, "File Metadata (NIP94)",
     HttpAuth => 27235, "HTTP Auth (NIP98)",
     ApplicationSpecificData => 30078, "Application-specific Data (NIP78)",
+    Draft => 31234, "Draft (NIP37)",
+    ClassifiedListing => 30402, "Classified Listing (NIP99)",
+    DraftClassifiedListing => 30403, "Draft or inactive Classified Listing (NIP99)",
+    GitRepoAnnouncement => 30617, "Git Repository Announcement (NIP34)",
+    GitPatch => 1617, "Git Patch (NIP34)",
+    GitIssue => 1621, "Git Issue (NIP34)",
+    GitStatusOpen => 1630, "Git Status Open (NIP34)",
+    GitStatusApplied => 1631, "Git Status Applied / Merged / Resolved (NIP34)",
+    GitStatusClosed => 1632, "Git Status Closed (NIP34)",
+    GitStatusDraft => 1633, "Git Status Draft (NIP34)",
+    HandlerRecommendation => 31989, "Handler Recommendation (NIP89)",
+    HandlerInformation => 31990, "Handler Information (NIP89)",
 }
 
 impl PartialEq<Kind> for Kind {
@@ -19,6 +19,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
 pub mod builder;
+pub mod chunk;
 pub mod id;
 pub mod kind;
 pub mod partial;
@@ -26,13 +27,14 @@ pub mod raw;
 pub mod tag;
 pub mod unsigned;
 
-pub use self::builder::EventBuilder;
+pub use self::builder::{EventBuilder, EventLimits};
 pub use self::id::EventId;
 pub use self::kind::Kind;
 pub use self::partial::{MissingPartialEvent, PartialEvent};
 pub use self::tag::{Marker, Tag, TagKind};
 pub use self::unsigned::UnsignedEvent;
 use crate::nips::nip01::Coordinate;
+use crate::nips::nip26::{self, DelegationTag, EventProperties};
 #[cfg(feature = "std")]
 use crate::types::time::Instant;
 use crate::types::time::TimeSupplier;
@@ -282,6 +284,44 @@ impl Event {
             .map_err(|_| Error::InvalidSignature)
     }
 
+    /// Verify NIP26 delegation
+    ///
+    /// Check the delegation signature and the kind/time conditions of the `delegation` tag.
+    /// Return the delegator public key or `None` if the event isn't delegated.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/26.md>
+    #[cfg(feature = "std")]
+    pub fn verify_delegation(&self) -> Result<Option<PublicKey>, nip26::Error> {
+        self.verify_delegation_with_ctx(&SECP256K1)
+    }
+
+    /// Verify NIP26 delegation
+    ///
+    /// Check [`Event::verify_delegation`] to learn more.
+    pub fn verify_delegation_with_ctx<C>(
+        &self,
+        secp: &Secp256k1<C>,
+    ) -> Result<Option<PublicKey>, nip26::Error>
+    where
+        C: Verification,
+    {
+        match self
+            .iter_tags()
+            .find(|t| matches!(t, Tag::Delegation { .. }))
+        {
+            Some(tag) => {
+                let delegation: DelegationTag = DelegationTag::try_from(tag.clone())?;
+                delegation.validate_with_ctx(
+                    secp,
+                    self.author(),
+                    &EventProperties::from_event(self),
+                )?;
+                Ok(Some(delegation.delegator_pubkey()))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Check POW
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/13.md>
@@ -335,6 +375,40 @@ impl Event {
         false
     }
 
+    /// Check if the event has a NIP36 content warning (`content-warning` tag)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/36.md>
+    #[inline]
+    pub fn is_sensitive(&self) -> bool {
+        self.iter_tags()
+            .any(|t| matches!(t, Tag::ContentWarning { .. }))
+    }
+
+    /// Check if the event is protected (`-` tag)
+    ///
+    /// Protected events should be published only by their author.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/70.md>
+    #[inline]
+    pub fn is_protected(&self) -> bool {
+        self.iter_tags().any(|t| matches!(t, Tag::Protected))
+    }
+
+    /// Get the NIP36 content warning reason, if set
+    ///
+    /// Return `None` also if the content warning has no reason: use [`Event::is_sensitive`] to check it.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/36.md>
+    #[inline]
+    pub fn content_warning(&self) -> Option<&str> {
+        self.iter_tags().find_map(|t| match t {
+            Tag::ContentWarning {
+                reason: Some(reason),
+            } => Some(reason.as_str()),
+            _ => None,
+        })
+    }
+
     /// Check if [`Kind`] is a NIP90 job request
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/90.md>
@@ -631,6 +705,59 @@ mod tests {
         let reserialized_json = event.as_json();
         assert_eq!(json, reserialized_json);
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_verify_delegation() {
+        use crate::nips::nip26::{Condition, Conditions, ValidationError};
+
+        let delegator_keys = Keys::generate();
+        let delegatee_keys = Keys::generate();
+
+        let mut conditions = Conditions::new();
+        conditions.add(Condition::Kind(1));
+        let delegation =
+            DelegationTag::new(&delegator_keys, delegatee_keys.public_key(), conditions).unwrap();
+
+        let event = EventBuilder::text_note("Delegated", [])
+            .delegation(delegation.clone())
+            .to_event(&delegatee_keys)
+            .unwrap();
+        assert_eq!(
+            event.verify_delegation(),
+            Ok(Some(delegator_keys.public_key()))
+        );
+
+        // Kind not allowed by conditions
+        let event = EventBuilder::new(Kind::Reaction, "+", [])
+            .delegation(delegation.clone())
+            .to_event(&delegatee_keys)
+            .unwrap();
+        assert_eq!(
+            event.verify_delegation(),
+            Err(nip26::Error::ConditionsValidation(
+                ValidationError::InvalidKind
+            ))
+        );
+
+        // Signed by a key different from the delegatee
+        let event = EventBuilder::text_note("Delegated", [])
+            .delegation(delegation)
+            .to_event(&Keys::generate())
+            .unwrap();
+        assert_eq!(
+            event.verify_delegation(),
+            Err(nip26::Error::ConditionsValidation(
+                ValidationError::InvalidSignature
+            ))
+        );
+
+        // Not delegated
+        let event = EventBuilder::text_note("Not delegated", [])
+            .to_event(&delegator_keys)
+            .unwrap();
+        assert_eq!(event.verify_delegation(), Ok(None));
+    }
 }
 
 #[cfg(bench)]
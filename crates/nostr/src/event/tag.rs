@@ -205,6 +205,8 @@ where
 pub enum Report {
     /// Depictions of nudity, porn, etc
     Nudity,
+    /// Virus, trojan horse, worm, robot, spyware, adware, back door, ransomware, rootkit, kidnapper, etc.
+    Malware,
     /// Profanity, hateful speech, etc.
     Profanity,
     /// Something which may be illegal in some jurisdiction
@@ -223,6 +225,7 @@ impl fmt::Display for Report {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Nudity => write!(f, "nudity"),
+            Self::Malware => write!(f, "malware"),
             Self::Profanity => write!(f, "profanity"),
             Self::Illegal => write!(f, "illegal"),
             Self::Spam => write!(f, "spam"),
@@ -238,6 +241,7 @@ impl FromStr for Report {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "nudity" => Ok(Self::Nudity),
+            "malware" => Ok(Self::Malware),
             "profanity" => Ok(Self::Profanity),
             "illegal" => Ok(Self::Illegal),
             "spam" => Ok(Self::Spam),
@@ -295,6 +299,8 @@ pub enum HttpMethod {
     PUT,
     /// PATCH
     PATCH,
+    /// DELETE
+    DELETE,
 }
 
 impl fmt::Display for HttpMethod {
@@ -304,6 +310,7 @@ impl fmt::Display for HttpMethod {
             Self::POST => write!(f, "POST"),
             Self::PUT => write!(f, "PUT"),
             Self::PATCH => write!(f, "PATCH"),
+            Self::DELETE => write!(f, "DELETE"),
         }
     }
 }
@@ -317,6 +324,7 @@ impl FromStr for HttpMethod {
             "POST" => Ok(Self::POST),
             "PUT" => Ok(Self::PUT),
             "PATCH" => Ok(Self::PATCH),
+            "DELETE" => Ok(Self::DELETE),
             m => Err(Error::InvalidHttpMethod(m.to_string())),
         }
     }
@@ -433,10 +441,20 @@ pub enum TagKind {
     Emoji,
     /// Encrypted
     Encrypted,
+    /// Protected event (NIP70)
+    Protected,
     /// Request (NIP90)
     Request,
     /// Word
     Word,
+    /// Media attachment metadata
+    Imeta,
+    /// Zap split
+    Zap,
+    /// Client
+    Client,
+    /// Human-readable event summary
+    Alt,
     /// Custom tag kind
     Custom(String),
 }
@@ -483,8 +501,13 @@ impl fmt::Display for TagKind {
             Self::Proxy => write!(f, "proxy"),
             Self::Emoji => write!(f, "emoji"),
             Self::Encrypted => write!(f, "encrypted"),
+            Self::Protected => write!(f, "-"),
             Self::Request => write!(f, "request"),
             Self::Word => write!(f, "word"),
+            Self::Imeta => write!(f, "imeta"),
+            Self::Zap => write!(f, "zap"),
+            Self::Client => write!(f, "client"),
+            Self::Alt => write!(f, "alt"),
             Self::Custom(tag) => write!(f, "{tag}"),
         }
     }
@@ -534,8 +557,13 @@ where
             "proxy" => Self::Proxy,
             "emoji" => Self::Emoji,
             "encrypted" => Self::Encrypted,
+            "-" => Self::Protected,
             "request" => Self::Request,
             "word" => Self::Word,
+            "imeta" => Self::Imeta,
+            "zap" => Self::Zap,
+            "client" => Self::Client,
+            "alt" => Self::Alt,
             t => match SingleLetterTag::from_str(t) {
                 Ok(s) => Self::SingleLetter(s),
                 Err(..) => Self::Custom(t.to_owned()),
@@ -645,6 +673,10 @@ pub enum Tag {
         url: UncheckedUrl,
     },
     Encrypted,
+    /// Protected event: relays must accept it only from the authenticated author
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/70.md>
+    Protected,
     Request(Event),
     DataVendingMachineStatus {
         status: DataVendingMachineStatus,
@@ -659,6 +691,30 @@ pub enum Tag {
     ///
     /// <https://github.com/nostr-protocol/nips/blob/master/32.md>
     Label(Vec<String>),
+    /// Media attachment metadata (`"<key> <value>"` entries)
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/92.md>
+    Imeta(Vec<String>),
+    /// Zap split
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/57.md#appendix-g-zap-tag-on-other-events>
+    Zap {
+        public_key: PublicKey,
+        relay_url: UncheckedUrl,
+        weight: Option<u64>,
+    },
+    /// Client that published the event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/89.md#client-tag>
+    Client {
+        name: String,
+        address: Option<Coordinate>,
+        relay_url: Option<UncheckedUrl>,
+    },
+    /// Human-readable summary of the event
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/31.md>
+    Alt(String),
 }
 
 impl Tag {
@@ -687,11 +743,15 @@ impl Tag {
         })) {
             let labels = tag.iter().skip(1).map(|u| u.as_ref().to_string()).collect();
             Ok(Self::Label(labels))
+        } else if tag_kind.eq(&TagKind::Imeta) {
+            let entries = tag.iter().skip(1).map(|u| u.as_ref().to_string()).collect();
+            Ok(Self::Imeta(entries))
         } else if tag_len == 1 {
             match tag_kind {
                 TagKind::ContentWarning => Ok(Self::ContentWarning { reason: None }),
                 TagKind::Anon => Ok(Self::Anon { msg: None }),
                 TagKind::Encrypted => Ok(Self::Encrypted),
+                TagKind::Protected => Ok(Self::Protected),
                 _ => Ok(Self::Generic(tag_kind, Vec::new())),
             }
         } else if tag_len == 2 {
@@ -783,6 +843,7 @@ impl Tag {
                 TagKind::Url => Ok(Self::Url(Url::parse(tag_1)?)),
                 TagKind::Magnet => Ok(Self::Magnet(tag_1.to_owned())),
                 TagKind::Blurhash => Ok(Self::Blurhash(tag_1.to_owned())),
+                TagKind::Dim => Ok(Self::Dim(ImageDimensions::from_str(tag_1)?)),
                 TagKind::Streaming => Ok(Self::Streaming(UncheckedUrl::from(tag_1))),
                 TagKind::Recording => Ok(Self::Recording(UncheckedUrl::from(tag_1))),
                 TagKind::Starts => Ok(Self::Starts(Timestamp::from_str(tag_1)?)),
@@ -803,6 +864,12 @@ impl Tag {
                 }),
                 TagKind::Request => Ok(Self::Request(Event::from_json(tag_1)?)),
                 TagKind::Word => Ok(Self::Word(tag_1.to_string())),
+                TagKind::Client => Ok(Self::Client {
+                    name: tag_1.to_string(),
+                    address: None,
+                    relay_url: None,
+                }),
+                TagKind::Alt => Ok(Self::Alt(tag_1.to_string())),
                 TagKind::SingleLetter(SingleLetterTag {
                     character: Alphabet::L,
                     uppercase: true,
@@ -896,10 +963,15 @@ impl Tag {
                 TagKind::SingleLetter(SingleLetterTag {
                     character: Alphabet::R,
                     uppercase: false,
-                }) => Ok(Self::RelayMetadata(
-                    UncheckedUrl::from(tag_1),
-                    Some(RelayMetadata::from_str(tag_2)?),
-                )),
+                }) => {
+                    // i.e. `["r", <commit-id>, "euc"]` (NIP34)
+                    let metadata = RelayMetadata::from_str(tag_2).ok();
+                    Ok(typed_or_generic(
+                        tag_kind,
+                        tag,
+                        metadata.map(|m| Self::RelayMetadata(UncheckedUrl::from(tag_1), Some(m))),
+                    ))
+                }
                 TagKind::Proxy => Ok(Self::Proxy {
                     id: tag_1.to_owned(),
                     protocol: Protocol::from(tag_2),
@@ -908,6 +980,8 @@ impl Tag {
                     shortcode: tag_1.to_owned(),
                     url: UncheckedUrl::from(tag_2),
                 }),
+                TagKind::Zap => Ok(typed_or_generic(tag_kind, tag, parse_zap(tag))),
+                TagKind::Client => Ok(typed_or_generic(tag_kind, tag, parse_client(tag))),
                 TagKind::Status => match DataVendingMachineStatus::from_str(tag_1) {
                     Ok(status) => Ok(Self::DataVendingMachineStatus {
                         status,
@@ -964,6 +1038,8 @@ impl Tag {
                     conditions: Conditions::from_str(tag_2)?,
                     sig: Signature::from_str(tag_3)?,
                 }),
+                TagKind::Zap => Ok(typed_or_generic(tag_kind, tag, parse_zap(tag))),
+                TagKind::Client => Ok(typed_or_generic(tag_kind, tag, parse_client(tag))),
                 _ => Ok(Self::Generic(
                     tag_kind,
                     tag[1..].iter().map(|s| s.as_ref().to_owned()).collect(),
@@ -1159,6 +1235,7 @@ impl Tag {
             Self::Proxy { .. } => TagKind::Proxy,
             Self::Emoji { .. } => TagKind::Emoji,
             Self::Encrypted => TagKind::Encrypted,
+            Self::Protected => TagKind::Protected,
             Self::Request(..) => TagKind::Request,
             Self::Word(..) => TagKind::Word,
             Self::LabelNamespace(..) => TagKind::SingleLetter(SingleLetterTag {
@@ -1169,6 +1246,10 @@ impl Tag {
                 character: Alphabet::L,
                 uppercase: false,
             }),
+            Self::Imeta(..) => TagKind::Imeta,
+            Self::Zap { .. } => TagKind::Zap,
+            Self::Client { .. } => TagKind::Client,
+            Self::Alt(..) => TagKind::Alt,
         }
     }
 
@@ -1245,10 +1326,15 @@ impl Tag {
             Self::Proxy { id, .. } => Some(id.into_generic_tag_value()),
             Self::Emoji { shortcode, .. } => Some(shortcode.into_generic_tag_value()),
             Self::Encrypted => None,
+            Self::Protected => None,
             Self::Request(val) => Some(val.as_json().into_generic_tag_value()),
             Self::Word(val) => Some(val.into_generic_tag_value()),
             Self::LabelNamespace(val) => Some(val.into_generic_tag_value()),
             Self::Label(l) => l.first().map(|v| v.into_generic_tag_value()),
+            Self::Imeta(l) => l.first().map(|v| v.into_generic_tag_value()),
+            Self::Zap { public_key, .. } => Some((*public_key).into_generic_tag_value()),
+            Self::Client { name, .. } => Some(name.into_generic_tag_value()),
+            Self::Alt(val) => Some(val.into_generic_tag_value()),
         }
     }
 }
@@ -1456,6 +1542,7 @@ impl From<Tag> for Vec<String> {
                 vec![tag_kind.to_string(), shortcode, url.to_string()]
             }
             Tag::Encrypted => vec![tag_kind.to_string()],
+            Tag::Protected => vec![tag_kind.to_string()],
             Tag::Request(event) => vec![tag_kind.to_string(), event.as_json()],
             Tag::DataVendingMachineStatus { status, extra_info } => {
                 let mut tag = vec![tag_kind.to_string(), status.to_string()];
@@ -1485,6 +1572,42 @@ impl From<Tag> for Vec<String> {
                 tag.extend(l);
                 tag
             }
+            Tag::Imeta(entries) => {
+                let mut tag = Vec::with_capacity(1 + entries.len());
+                tag.push(tag_kind.to_string());
+                tag.extend(entries);
+                tag
+            }
+            Tag::Zap {
+                public_key,
+                relay_url,
+                weight,
+            } => {
+                let mut tag = vec![
+                    tag_kind.to_string(),
+                    public_key.to_string(),
+                    relay_url.to_string(),
+                ];
+                if let Some(weight) = weight {
+                    tag.push(weight.to_string());
+                }
+                tag
+            }
+            Tag::Client {
+                name,
+                address,
+                relay_url,
+            } => {
+                let mut tag = vec![tag_kind.to_string(), name];
+                if let Some(address) = address {
+                    tag.push(address.to_string());
+                    if let Some(relay_url) = relay_url {
+                        tag.push(relay_url.to_string());
+                    }
+                }
+                tag
+            }
+            Tag::Alt(summary) => vec![tag_kind.to_string(), summary],
         }
     }
 }
@@ -1624,6 +1747,50 @@ where
     }
 }
 
+/// Parse `["zap", <public-key>, <relay-url>, <weight>?]`
+fn parse_zap<S>(tag: &[S]) -> Option<Tag>
+where
+    S: AsRef<str>,
+{
+    Some(Tag::Zap {
+        public_key: PublicKey::from_str(tag.get(1)?.as_ref()).ok()?,
+        relay_url: UncheckedUrl::from(tag.get(2)?.as_ref()),
+        weight: match tag.get(3) {
+            Some(weight) => Some(weight.as_ref().parse().ok()?),
+            None => None,
+        },
+    })
+}
+
+/// Parse `["client", <name>, <address>, <relay-url>?]`
+fn parse_client<S>(tag: &[S]) -> Option<Tag>
+where
+    S: AsRef<str>,
+{
+    Some(Tag::Client {
+        name: tag.get(1)?.as_ref().to_string(),
+        address: Some(Coordinate::from_str(tag.get(2)?.as_ref()).ok()?),
+        relay_url: tag.get(3).map(|u| UncheckedUrl::from(u.as_ref())),
+    })
+}
+
+/// Keep the typed tag only if it serializes back to the same values, otherwise fallback to [`Tag::Generic`]
+fn typed_or_generic<S>(kind: TagKind, tag: &[S], typed: Option<Tag>) -> Tag
+where
+    S: AsRef<str>,
+{
+    if let Some(typed) = typed {
+        let values: Vec<String> = typed.as_vec();
+        if values.len() == tag.len() && values.iter().zip(tag).all(|(a, b)| a == b.as_ref()) {
+            return typed;
+        }
+    }
+    Tag::Generic(
+        kind,
+        tag[1..].iter().map(|s| s.as_ref().to_owned()).collect(),
+    )
+}
+
 impl From<Kind> for Tag {
     fn from(value: Kind) -> Self {
         Self::Kind(value)
@@ -1639,7 +1806,7 @@ impl From<Sha256Hash> for Tag {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Event, JsonUtil, Timestamp};
+    use crate::{Event, EventBuilder, JsonUtil, Keys, Timestamp};
 
     #[test]
     fn test_tag_is_reply() {
@@ -1748,6 +1915,8 @@ mod tests {
             Tag::ContentWarning { reason: None }.as_vec()
         );
 
+        assert_eq!(vec!["-"], Tag::Protected.as_vec());
+
         assert_eq!(
             vec![
                 "p",
@@ -1796,10 +1965,11 @@ mod tests {
 
         assert_eq!(
             vec!["client", "nostr-sdk"],
-            Tag::Generic(
-                TagKind::Custom("client".to_string()),
-                vec!["nostr-sdk".to_string()]
-            )
+            Tag::Client {
+                name: String::from("nostr-sdk"),
+                address: None,
+                relay_url: None,
+            }
             .as_vec()
         );
 
@@ -1892,6 +2062,22 @@ mod tests {
             .as_vec()
         );
 
+        assert_eq!(
+            vec![
+                "e",
+                "378f145897eea948952674269945e88612420db35791784abf0616b4fed56ef7",
+                "malware"
+            ],
+            Tag::EventReport(
+                EventId::from_hex(
+                    "378f145897eea948952674269945e88612420db35791784abf0616b4fed56ef7"
+                )
+                .unwrap(),
+                Report::Malware,
+            )
+            .as_vec()
+        );
+
         assert_eq!(
             vec!["nonce", "1", "20"],
             Tag::POW {
@@ -2061,6 +2247,8 @@ mod tests {
             Tag::ContentWarning { reason: None }
         );
 
+        assert_eq!(Tag::parse(&["-"]).unwrap(), Tag::Protected);
+
         assert_eq!(
             Tag::parse(&[
                 "p",
@@ -2108,10 +2296,11 @@ mod tests {
 
         assert_eq!(
             Tag::parse(&["client", "nostr-sdk"]).unwrap(),
-            Tag::Generic(
-                TagKind::Custom("client".to_string()),
-                vec!["nostr-sdk".to_string()]
-            )
+            Tag::Client {
+                name: String::from("nostr-sdk"),
+                address: None,
+                relay_url: None,
+            }
         );
 
         assert_eq!(
@@ -2274,6 +2463,17 @@ mod tests {
             )
         );
 
+        assert_eq!(
+            Tag::parse(&["r", "b5dd2a3b8b7ad8e6a0e1a7c5e2b7c4d1e6f3a2b1", "euc"]).unwrap(),
+            Tag::Generic(
+                TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::R)),
+                vec![
+                    String::from("b5dd2a3b8b7ad8e6a0e1a7c5e2b7c4d1e6f3a2b1"),
+                    String::from("euc")
+                ]
+            )
+        );
+
         assert_eq!(
             Tag::parse(&[
                 "p",
@@ -2389,6 +2589,160 @@ mod tests {
             Tag::Label(vec!["IT-MI".to_string(), "ISO-3166-2".to_string()])
         );
     }
+
+    /// Tiny xorshift PRNG, to keep property tests deterministic and dependency-free
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn string(&mut self, max_len: usize) -> String {
+            const CHARS: &[char] = &[
+                'a', 'b', 'z', 'A', 'Z', '0', '9', '-', '_', ':', ' ', '"', '\\', '\n', '\t', '/',
+                'é', '⚡', '🦀', '\u{0}',
+            ];
+            let len: usize = self.below(max_len + 1);
+            (0..len).map(|_| CHARS[self.below(CHARS.len())]).collect()
+        }
+    }
+
+    #[test]
+    fn test_protected_tag() {
+        let tag = Tag::parse(&["-"]).unwrap();
+        assert_eq!(tag, Tag::Protected);
+        assert_eq!(tag.kind(), TagKind::Protected);
+        assert_eq!(tag.as_vec(), vec!["-"]);
+
+        let json: &str = r#"["-"]"#;
+        assert_eq!(serde_json::to_string(&tag).unwrap(), json);
+        assert_eq!(serde_json::from_str::<Tag>(json).unwrap(), Tag::Protected);
+
+        // Protected event round trip
+        let keys = Keys::generate();
+        let event = EventBuilder::text_note("Protected", [])
+            .protected()
+            .to_event(&keys)
+            .unwrap();
+        assert!(event.as_json().contains(r#""tags":[["-"]]"#));
+        let event = Event::from_json(event.as_json()).unwrap();
+        assert!(event.is_protected());
+        assert_eq!(event.tags(), &[Tag::Protected]);
+    }
+
+    #[test]
+    fn test_unknown_tag_round_trip() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        let mut checked: usize = 0;
+
+        for _ in 0..5_000 {
+            let kind: String = rng.string(8);
+            if !matches!(TagKind::from(&kind), TagKind::Custom(..)) {
+                continue;
+            }
+
+            let len: usize = rng.below(6);
+            let mut raw: Vec<String> = vec![kind];
+            raw.extend((0..len).map(|_| rng.string(16)));
+
+            let tag = Tag::parse(&raw).unwrap();
+            assert_eq!(tag.as_vec(), raw);
+
+            let json: String = serde_json::to_string(&raw).unwrap();
+            assert_eq!(serde_json::to_string(&tag).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Tag>(&json).unwrap(), tag);
+
+            checked += 1;
+        }
+
+        assert!(checked > 1_000);
+    }
+
+    #[test]
+    fn test_typed_tag_round_trip() {
+        let public_key: &str = "a695f6b60119d9521934a691347d9f78e8770b56da16bb255ee286ddf9fda919";
+        let coordinate: String = format!("31990:{public_key}:1685968093690");
+        let tags: Vec<Vec<String>> = vec![
+            vec!["alt".into(), "A short note".into()],
+            vec!["client".into(), "nostr-sdk".into()],
+            vec!["client".into(), "nostr-sdk".into(), coordinate.clone()],
+            vec![
+                "client".into(),
+                "nostr-sdk".into(),
+                coordinate,
+                "wss://relay.damus.io".into(),
+            ],
+            vec!["zap".into(), public_key.into(), "wss://nos.lol".into()],
+            vec![
+                "zap".into(),
+                public_key.into(),
+                "wss://nos.lol".into(),
+                "2".into(),
+            ],
+            vec![
+                "imeta".into(),
+                "url https://nostr.build/i/my-image.jpg".into(),
+                "m image/jpeg".into(),
+                "dim 3024x4032".into(),
+            ],
+            vec![
+                "emoji".into(),
+                "soapbox".into(),
+                "https://gleasonator.com/emoji/Gleasonator/soapbox.png".into(),
+            ],
+            vec![
+                "proxy".into(),
+                "https://example.com/note/1".into(),
+                "web".into(),
+            ],
+            vec!["expiration".into(), "1600000000".into()],
+            vec!["subject".into(), "Hello".into()],
+            vec!["title".into(), "Title".into()],
+            vec!["published_at".into(), "1296962229".into()],
+        ];
+
+        for raw in tags.into_iter() {
+            let tag = Tag::parse(&raw).unwrap();
+            assert!(
+                !matches!(tag, Tag::Generic(..)),
+                "{raw:?} parsed as generic"
+            );
+            assert_eq!(tag.as_vec(), raw);
+        }
+
+        // Malformed known tags fallback to generic and keep the original values
+        let malformed: Vec<Vec<String>> = vec![
+            vec![
+                "zap".into(),
+                public_key.to_uppercase(),
+                "wss://nos.lol".into(),
+            ],
+            vec![
+                "zap".into(),
+                public_key.into(),
+                "wss://nos.lol".into(),
+                "1.5".into(),
+            ],
+            vec!["client".into(), "nostr-sdk".into(), "not-an-address".into()],
+        ];
+
+        for raw in malformed.into_iter() {
+            let tag = Tag::parse(&raw).unwrap();
+            assert!(
+                matches!(tag, Tag::Generic(..)),
+                "{raw:?} not parsed as generic"
+            );
+            assert_eq!(tag.as_vec(), raw);
+        }
+    }
 }
 
 #[cfg(bench)]
@@ -32,7 +32,12 @@ extern crate alloc;
 extern crate serde;
 
 #[doc(hidden)]
-#[cfg(any(feature = "nip04", feature = "nip44"))]
+#[cfg(any(
+    feature = "nip03",
+    feature = "nip04",
+    feature = "nip44",
+    feature = "nip98"
+))]
 pub use base64;
 #[doc(hidden)]
 #[cfg(feature = "nip06")]
@@ -55,10 +60,13 @@ pub use self::event::tag::{
     TagKind,
 };
 pub use self::event::{
-    Event, EventBuilder, EventId, Kind, MissingPartialEvent, PartialEvent, UnsignedEvent,
+    Event, EventBuilder, EventId, EventLimits, Kind, MissingPartialEvent, PartialEvent,
+    UnsignedEvent,
 };
 pub use self::key::{Keys, PublicKey, SecretKey};
-pub use self::message::{ClientMessage, RawRelayMessage, RelayMessage, SubscriptionId};
+pub use self::message::{
+    ClientMessage, MachineReadablePrefix, RawRelayMessage, RelayMessage, SubscriptionId,
+};
 pub use self::nips::nip19::{FromBech32, ToBech32};
 pub use self::types::{
     Alphabet, Contact, Filter, GenericTagValue, Metadata, SingleLetterTag, Timestamp, TryIntoUrl,
@@ -82,6 +82,81 @@ impl<'de> Deserialize<'de> for NegentropyErrorCode {
     }
 }
 
+/// Machine-readable prefix of `OK` and `CLOSED` messages
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/01.md>
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MachineReadablePrefix {
+    /// Duplicate
+    Duplicate,
+    /// Proof of work (NIP13)
+    Pow,
+    /// Blocked
+    Blocked,
+    /// Rate limited
+    RateLimited,
+    /// Invalid
+    Invalid,
+    /// Error
+    Error,
+    /// Unsupported
+    Unsupported,
+    /// Authentication required (NIP42)
+    AuthRequired,
+    /// Restricted (authenticated but not allowed)
+    Restricted,
+    /// Other
+    Other(String),
+}
+
+impl fmt::Display for MachineReadablePrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate => write!(f, "duplicate"),
+            Self::Pow => write!(f, "pow"),
+            Self::Blocked => write!(f, "blocked"),
+            Self::RateLimited => write!(f, "rate-limited"),
+            Self::Invalid => write!(f, "invalid"),
+            Self::Error => write!(f, "error"),
+            Self::Unsupported => write!(f, "unsupported"),
+            Self::AuthRequired => write!(f, "auth-required"),
+            Self::Restricted => write!(f, "restricted"),
+            Self::Other(prefix) => write!(f, "{prefix}"),
+        }
+    }
+}
+
+impl MachineReadablePrefix {
+    /// Parse the prefix of a `OK` or `CLOSED` message (i.e. `auth-required: we only serve members`)
+    ///
+    /// Return `None` if the message hasn't a prefix.
+    pub fn parse(message: &str) -> Option<Self> {
+        let (prefix, _) = message.split_once(':')?;
+        match prefix {
+            "duplicate" => Some(Self::Duplicate),
+            "pow" => Some(Self::Pow),
+            "blocked" => Some(Self::Blocked),
+            "rate-limited" => Some(Self::RateLimited),
+            "invalid" => Some(Self::Invalid),
+            "error" => Some(Self::Error),
+            "unsupported" => Some(Self::Unsupported),
+            "auth-required" => Some(Self::AuthRequired),
+            "restricted" => Some(Self::Restricted),
+            _ => {
+                if !prefix.is_empty()
+                    && prefix
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+                {
+                    Some(Self::Other(prefix.to_string()))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
 /// Messages sent by relays, received by clients
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RelayMessage {
@@ -359,6 +434,26 @@ mod tests {
     use super::*;
     use crate::{Kind, PublicKey, Timestamp};
 
+    #[test]
+    fn test_machine_readable_prefix() {
+        assert_eq!(
+            MachineReadablePrefix::parse("auth-required: we only serve members"),
+            Some(MachineReadablePrefix::AuthRequired)
+        );
+        assert_eq!(
+            MachineReadablePrefix::parse("rate-limited: slow down"),
+            Some(MachineReadablePrefix::RateLimited)
+        );
+        assert_eq!(
+            MachineReadablePrefix::parse("payment-required: pay first"),
+            Some(MachineReadablePrefix::Other(String::from(
+                "payment-required"
+            )))
+        );
+        assert_eq!(MachineReadablePrefix::parse("Too many subs"), None);
+        assert_eq!(MachineReadablePrefix::parse("Error: see https://x.y"), None);
+    }
+
     #[test]
     fn test_handle_valid_notice() {
         let valid_notice_msg = r#"["NOTICE","Invalid event format!"]"#;
@@ -7,6 +7,8 @@
 //! See all at <https://github.com/nostr-protocol/nips>
 
 pub mod nip01;
+#[cfg(feature = "nip03")]
+pub mod nip03;
 #[cfg(feature = "nip04")]
 pub mod nip04;
 #[cfg(all(feature = "std", feature = "nip05"))]
@@ -15,13 +17,21 @@ pub mod nip05;
 pub mod nip06;
 #[cfg(all(feature = "nip07", target_arch = "wasm32"))]
 pub mod nip07;
+pub mod nip10;
 #[cfg(all(feature = "std", feature = "nip11"))]
 pub mod nip11;
 pub mod nip13;
 pub mod nip15;
+pub mod nip17;
+pub mod nip18;
 pub mod nip19;
 pub mod nip21;
+pub mod nip23;
+pub mod nip25;
 pub mod nip26;
+pub mod nip28;
+pub mod nip34;
+pub mod nip37;
 #[cfg(feature = "nip44")]
 pub mod nip44;
 #[cfg(all(feature = "std", feature = "nip46"))]
@@ -39,6 +49,10 @@ pub mod nip58;
 #[cfg(feature = "nip59")]
 pub mod nip59;
 pub mod nip65;
+pub mod nip89;
 pub mod nip90;
 pub mod nip94;
+#[cfg(all(feature = "std", feature = "nip96"))]
+pub mod nip96;
 pub mod nip98;
+pub mod nip99;
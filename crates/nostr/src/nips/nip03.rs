@@ -0,0 +1,402 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP03
+//!
+//! OpenTimestamps Attestations for Events
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/03.md>
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use base64::engine::{general_purpose, Engine};
+use bitcoin::hashes::ripemd160::Hash as Ripemd160Hash;
+use bitcoin::hashes::sha1::Hash as Sha1Hash;
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+
+use crate::{Event, EventId, Kind, Tag, UncheckedUrl};
+
+/// Magic bytes of an OTS detached timestamp file
+const HEADER_MAGIC: [u8; 31] = [
+    0x00, 0x4f, 0x70, 0x65, 0x6e, 0x54, 0x69, 0x6d, 0x65, 0x73, 0x74, 0x61, 0x6d, 0x70, 0x73, 0x00,
+    0x00, 0x50, 0x72, 0x6f, 0x6f, 0x66, 0x00, 0xbf, 0x89, 0xe2, 0xe8, 0x84, 0xe8, 0x92, 0x94,
+];
+const MAJOR_VERSION: u64 = 1;
+const BITCOIN_TAG: [u8; 8] = [0x05, 0x88, 0x96, 0x0d, 0x73, 0xd7, 0x19, 0x01];
+const PENDING_TAG: [u8; 8] = [0x83, 0xdf, 0xe3, 0x0d, 0x2e, 0xf9, 0x0c, 0x8e];
+const MAX_DEPTH: usize = 256;
+const MAX_MSG_LEN: usize = 4096;
+
+/// NIP03 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Base64 decoding error
+    Base64(base64::DecodeError),
+    /// Not an OpenTimestamps attestation event
+    WrongKind,
+    /// Missing `e` tag
+    MissingEventTag,
+    /// Not an OTS detached timestamp
+    InvalidProof,
+    /// Unsupported OTS version
+    UnsupportedVersion(u64),
+    /// Unsupported operation (i.e. file hash not SHA256 or keccak256 op)
+    UnsupportedOp(u8),
+    /// The proof doesn't commit to the event ID
+    DigestMismatch,
+    /// No attestation found in the proof
+    NoAttestation,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base64(e) => write!(f, "{e}"),
+            Self::WrongKind => write!(f, "wrong event kind"),
+            Self::MissingEventTag => write!(f, "missing event tag"),
+            Self::InvalidProof => write!(f, "invalid OTS proof"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported OTS version: {v}"),
+            Self::UnsupportedOp(op) => write!(f, "unsupported OTS operation: {op:#04x}"),
+            Self::DigestMismatch => write!(f, "proof not commit to the event ID"),
+            Self::NoAttestation => write!(f, "no attestation found"),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Base64(e)
+    }
+}
+
+/// Timestamp attestation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attestation {
+    /// Bitcoin block header attestation
+    ///
+    /// The proof is complete only if `merkle_root` match the merkle root
+    /// of the block at `height`: the block header is NOT checked here.
+    Bitcoin {
+        /// Block height
+        height: u64,
+        /// Commitment expected as block header merkle root
+        merkle_root: [u8; 32],
+    },
+    /// Pending attestation (proof not upgraded yet)
+    Pending {
+        /// Calendar server URI
+        uri: String,
+    },
+}
+
+/// OpenTimestamps attestation (kind `1040`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenTimestamps {
+    /// Timestamped event ID
+    pub event_id: EventId,
+    /// Relay hint
+    pub relay_url: Option<UncheckedUrl>,
+    /// OTS detached timestamp file
+    pub proof: Vec<u8>,
+}
+
+impl OpenTimestamps {
+    /// Verify that the proof commits to the event ID
+    ///
+    /// Return the attestations found in the proof.
+    #[inline]
+    pub fn verify(&self) -> Result<Vec<Attestation>, Error> {
+        verify_proof(&self.proof, &self.event_id)
+    }
+
+    /// Verify the proof and get the height of the earliest Bitcoin block attestation
+    ///
+    /// Return `None` if the proof is still pending.
+    pub fn block_height(&self) -> Result<Option<u64>, Error> {
+        Ok(self
+            .verify()?
+            .into_iter()
+            .filter_map(|a| match a {
+                Attestation::Bitcoin { height, .. } => Some(height),
+                Attestation::Pending { .. } => None,
+            })
+            .min())
+    }
+}
+
+impl TryFrom<&Event> for OpenTimestamps {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::OpenTimestamps {
+            return Err(Error::WrongKind);
+        }
+
+        let (event_id, relay_url) = event
+            .tags()
+            .iter()
+            .find_map(|tag| match tag {
+                Tag::Event {
+                    event_id,
+                    relay_url,
+                    ..
+                } => Some((*event_id, relay_url.clone())),
+                _ => None,
+            })
+            .ok_or(Error::MissingEventTag)?;
+
+        Ok(Self {
+            event_id,
+            relay_url,
+            proof: general_purpose::STANDARD.decode(event.content())?,
+        })
+    }
+}
+
+/// Verify that an OTS detached timestamp file commits to the event ID
+///
+/// All the operations of the proof are replayed from the event ID: return the attestations
+/// with the commitment reached for each of them. Bitcoin block headers are NOT checked.
+pub fn verify_proof(proof: &[u8], event_id: &EventId) -> Result<Vec<Attestation>, Error> {
+    let mut reader = Reader::new(proof);
+
+    if reader.read_bytes(HEADER_MAGIC.len())? != HEADER_MAGIC {
+        return Err(Error::InvalidProof);
+    }
+
+    let version: u64 = reader.read_varuint()?;
+    if version != MAJOR_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    // File hash op: event IDs are SHA256 digests
+    let op: u8 = reader.read_u8()?;
+    if op != 0x08 {
+        return Err(Error::UnsupportedOp(op));
+    }
+
+    if reader.read_bytes(32)? != event_id.as_bytes() {
+        return Err(Error::DigestMismatch);
+    }
+
+    let mut attestations: Vec<Attestation> = Vec::new();
+    reader.read_timestamp(event_id.as_bytes().to_vec(), &mut attestations, 0)?;
+
+    if !reader.is_empty() {
+        return Err(Error::InvalidProof);
+    }
+
+    if attestations.is_empty() {
+        return Err(Error::NoAttestation);
+    }
+
+    Ok(attestations)
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn read_u8(&mut self) -> Result<u8, Error> {
+        let (byte, rest) = self.buf.split_first().ok_or(Error::InvalidProof)?;
+        self.buf = rest;
+        Ok(*byte)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.buf.len() < len {
+            return Err(Error::InvalidProof);
+        }
+        let (bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(bytes)
+    }
+
+    fn read_varuint(&mut self) -> Result<u64, Error> {
+        let mut value: u64 = 0;
+        let mut shift: u32 = 0;
+        loop {
+            let byte: u8 = self.read_u8()?;
+            if shift > 63 {
+                return Err(Error::InvalidProof);
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn read_varbytes(&mut self) -> Result<&'a [u8], Error> {
+        let len: usize = self.read_varuint()? as usize;
+        if len > MAX_MSG_LEN {
+            return Err(Error::InvalidProof);
+        }
+        self.read_bytes(len)
+    }
+
+    /// Read a timestamp (tree of operations ending with attestations) applied to `msg`
+    fn read_timestamp(
+        &mut self,
+        msg: Vec<u8>,
+        attestations: &mut Vec<Attestation>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidProof);
+        }
+
+        // `0xff` marks a fork: the next branch isn't the last one
+        let mut tag: u8 = self.read_u8()?;
+        while tag == 0xff {
+            let next: u8 = self.read_u8()?;
+            self.read_op_or_attestation(next, &msg, attestations, depth)?;
+            tag = self.read_u8()?;
+        }
+        self.read_op_or_attestation(tag, &msg, attestations, depth)
+    }
+
+    fn read_op_or_attestation(
+        &mut self,
+        tag: u8,
+        msg: &[u8],
+        attestations: &mut Vec<Attestation>,
+        depth: usize,
+    ) -> Result<(), Error> {
+        if tag == 0x00 {
+            let kind: &[u8] = self.read_bytes(8)?;
+            let mut payload = Reader::new(self.read_varbytes()?);
+            if kind == BITCOIN_TAG {
+                let height: u64 = payload.read_varuint()?;
+                let merkle_root: [u8; 32] = msg.try_into().map_err(|_| Error::InvalidProof)?;
+                attestations.push(Attestation::Bitcoin {
+                    height,
+                    merkle_root,
+                });
+            } else if kind == PENDING_TAG {
+                let uri: &[u8] = payload.read_varbytes()?;
+                let uri: String =
+                    String::from_utf8(uri.to_vec()).map_err(|_| Error::InvalidProof)?;
+                attestations.push(Attestation::Pending { uri });
+            }
+            // Unknown attestations are skipped
+            return Ok(());
+        }
+
+        let result: Vec<u8> = match tag {
+            0x02 => Sha1Hash::hash(msg).to_byte_array().to_vec(),
+            0x03 => Ripemd160Hash::hash(msg).to_byte_array().to_vec(),
+            0x08 => Sha256Hash::hash(msg).to_byte_array().to_vec(),
+            0xf0 => {
+                let mut result: Vec<u8> = msg.to_vec();
+                result.extend_from_slice(self.read_varbytes()?);
+                result
+            }
+            0xf1 => {
+                let mut result: Vec<u8> = self.read_varbytes()?.to_vec();
+                result.extend_from_slice(msg);
+                result
+            }
+            0xf2 => msg.iter().rev().copied().collect(),
+            op => return Err(Error::UnsupportedOp(op)),
+        };
+
+        if result.len() > MAX_MSG_LEN {
+            return Err(Error::InvalidProof);
+        }
+
+        self.read_timestamp(result, attestations, depth + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varbytes(bytes: &[u8]) -> Vec<u8> {
+        let mut v = vec![bytes.len() as u8];
+        v.extend_from_slice(bytes);
+        v
+    }
+
+    fn proof(event_id: &EventId) -> Vec<u8> {
+        let mut proof: Vec<u8> = HEADER_MAGIC.to_vec();
+        proof.push(0x01);
+        proof.push(0x08);
+        proof.extend_from_slice(event_id.as_bytes());
+
+        // Fork: pending attestation
+        proof.push(0xff);
+        proof.push(0x00);
+        proof.extend_from_slice(&PENDING_TAG);
+        let uri = varbytes(b"https://alice.btc.calendar.opentimestamps.org");
+        proof.extend_from_slice(&varbytes(&uri));
+
+        // Append, sha256, bitcoin attestation
+        proof.push(0xf0);
+        proof.extend_from_slice(&varbytes(&[0xaa; 4]));
+        proof.push(0x08);
+        proof.push(0x00);
+        proof.extend_from_slice(&BITCOIN_TAG);
+        proof.extend_from_slice(&varbytes(&[0x64]));
+        proof
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let event_id = EventId::from_slice(&[1; 32]).unwrap();
+        let proof = proof(&event_id);
+
+        let mut msg = event_id.as_bytes().to_vec();
+        msg.extend_from_slice(&[0xaa; 4]);
+        let merkle_root = Sha256Hash::hash(&msg).to_byte_array();
+
+        assert_eq!(
+            verify_proof(&proof, &event_id).unwrap(),
+            vec![
+                Attestation::Pending {
+                    uri: String::from("https://alice.btc.calendar.opentimestamps.org")
+                },
+                Attestation::Bitcoin {
+                    height: 100,
+                    merkle_root
+                }
+            ]
+        );
+
+        let other = EventId::from_slice(&[2; 32]).unwrap();
+        assert_eq!(verify_proof(&proof, &other), Err(Error::DigestMismatch));
+
+        assert_eq!(
+            verify_proof(&proof[..proof.len() - 1], &event_id),
+            Err(Error::InvalidProof)
+        );
+    }
+    #[test]
+    fn test_block_height() {
+        let event_id = EventId::from_slice(&[1; 32]).unwrap();
+        let ots = OpenTimestamps {
+            event_id,
+            relay_url: None,
+            proof: proof(&event_id),
+        };
+        assert_eq!(ots.block_height(), Ok(Some(100)));
+    }
+}
@@ -10,7 +10,10 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
 use core::str::FromStr;
+use core::time::Duration;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
 
 #[cfg(not(target_arch = "wasm32"))]
 use reqwest::Proxy;
@@ -18,7 +21,10 @@ use serde_json::Value;
 use url::Url;
 
 use crate::nips::nip19::Nip19Profile;
-use crate::{key, PublicKey};
+use crate::{key, PublicKey, Timestamp};
+
+/// Default [`Nip05Resolver`] cache TTL
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
 
 /// `NIP05` error
 #[derive(Debug)]
@@ -119,34 +125,45 @@ where
     Err(Error::ImpossibleToVerify)
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+fn build_client(proxy: Option<SocketAddr>) -> Result<reqwest::Client, Error> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = proxy {
+        let proxy = format!("socks5h://{proxy}");
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn build_client(_proxy: Option<SocketAddr>) -> Result<reqwest::Client, Error> {
+    Ok(reqwest::Client::new())
+}
+
+async fn fetch_profile(client: &reqwest::Client, nip05: &str) -> Result<Nip19Profile, Error> {
+    let (url, name) = compose_url(nip05)?;
+    let res = client.get(url).send().await?;
+    let json: Value = serde_json::from_str(&res.text().await?)?;
+
+    let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
+    let relays = get_relays_from_json(json, public_key);
+
+    Ok(Nip19Profile { public_key, relays })
+}
+
 /// Verify NIP05
 ///
 /// **Proxy is ignored for WASM targets!**
 pub async fn verify<S>(
     public_key: PublicKey,
     nip05: S,
-    _proxy: Option<SocketAddr>,
+    proxy: Option<SocketAddr>,
 ) -> Result<(), Error>
 where
     S: AsRef<str>,
 {
-    use reqwest::Client;
-
+    let client: reqwest::Client = build_client(proxy)?;
     let (url, name) = compose_url(nip05)?;
-
-    #[cfg(not(target_arch = "wasm32"))]
-    let client: Client = {
-        let mut builder = Client::builder();
-        if let Some(proxy) = _proxy {
-            let proxy = format!("socks5h://{proxy}");
-            builder = builder.proxy(Proxy::all(proxy)?);
-        }
-        builder.build()?
-    };
-
-    #[cfg(target_arch = "wasm32")]
-    let client: Client = Client::new();
-
     let res = client.get(url).send().await?;
     let json: Value = serde_json::from_str(&res.text().await?)?;
     verify_json(public_key, json, name)
@@ -180,34 +197,12 @@ where
 /// Get [Nip19Profile] from NIP05 (public key and list of advertised relays)
 ///
 /// **Proxy is ignored for WASM targets!**
-pub async fn get_profile<S>(nip05: S, _proxy: Option<SocketAddr>) -> Result<Nip19Profile, Error>
+pub async fn get_profile<S>(nip05: S, proxy: Option<SocketAddr>) -> Result<Nip19Profile, Error>
 where
     S: AsRef<str>,
 {
-    use reqwest::Client;
-
-    let (url, name) = compose_url(nip05)?;
-
-    #[cfg(not(target_arch = "wasm32"))]
-    let client: Client = {
-        let mut builder = Client::builder();
-        if let Some(proxy) = _proxy {
-            let proxy = format!("socks5h://{proxy}");
-            builder = builder.proxy(Proxy::all(proxy)?);
-        }
-        builder.build()?
-    };
-
-    #[cfg(target_arch = "wasm32")]
-    let client: Client = Client::new();
-
-    let res = client.get(url).send().await?;
-    let json: Value = serde_json::from_str(&res.text().await?)?;
-
-    let public_key = get_key_from_json(json.clone(), name).ok_or(Error::ImpossibleToVerify)?;
-    let relays = get_relays_from_json(json, public_key);
-
-    Ok(Nip19Profile { public_key, relays })
+    let client: reqwest::Client = build_client(proxy)?;
+    fetch_profile(&client, nip05.as_ref()).await
 }
 
 /// Get [Nip19Profile] from NIP05 (public key and list of advertised relays)
@@ -234,3 +229,96 @@ where
 
     Ok(Nip19Profile { public_key, relays })
 }
+
+/// NIP05 resolver
+///
+/// Resolve NIP05 identifiers with a custom HTTP client, caching the results.
+/// Cloning the resolver is cheap and the cache is shared between the clones.
+#[derive(Debug, Clone)]
+pub struct Nip05Resolver {
+    client: reqwest::Client,
+    cache: Arc<Mutex<HashMap<String, (Nip19Profile, Timestamp)>>>,
+    ttl: Duration,
+}
+
+impl Default for Nip05Resolver {
+    fn default() -> Self {
+        Self::with_client(reqwest::Client::new())
+    }
+}
+
+impl Nip05Resolver {
+    /// New resolver with default HTTP client
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New resolver with default HTTP client and proxy
+    ///
+    /// **Proxy is ignored for WASM targets!**
+    pub fn with_proxy(proxy: SocketAddr) -> Result<Self, Error> {
+        Ok(Self::with_client(build_client(Some(proxy))?))
+    }
+
+    /// New resolver with custom HTTP client
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+            ttl: DEFAULT_CACHE_TTL,
+        }
+    }
+
+    /// Set cache TTL (default: 1 hour)
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Get [Nip19Profile] from NIP05 (public key and list of advertised relays)
+    ///
+    /// Return the cached profile, if not expired.
+    pub async fn profile<S>(&self, nip05: S) -> Result<Nip19Profile, Error>
+    where
+        S: AsRef<str>,
+    {
+        let nip05: String = nip05.as_ref().to_lowercase();
+        let now: Timestamp = Timestamp::now();
+
+        if let Ok(cache) = self.cache.lock() {
+            if let Some((profile, fetched_at)) = cache.get(&nip05) {
+                if *fetched_at + self.ttl > now {
+                    return Ok(profile.clone());
+                }
+            }
+        }
+
+        let profile: Nip19Profile = fetch_profile(&self.client, &nip05).await?;
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.insert(nip05, (profile.clone(), now));
+        }
+
+        Ok(profile)
+    }
+
+    /// Verify NIP05
+    pub async fn verify<S>(&self, public_key: PublicKey, nip05: S) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        let profile: Nip19Profile = self.profile(nip05).await?;
+        if profile.public_key == public_key {
+            Ok(())
+        } else {
+            Err(Error::ImpossibleToVerify)
+        }
+    }
+
+    /// Clear cache
+    pub fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+}
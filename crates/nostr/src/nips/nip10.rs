@@ -0,0 +1,214 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP10
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/10.md>
+
+use alloc::vec::Vec;
+
+use crate::{Event, EventId, Marker, Tag, UncheckedUrl};
+
+/// Event referenced by an `e` tag
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadEvent {
+    /// Event ID
+    pub event_id: EventId,
+    /// Relay hint
+    pub relay_url: Option<UncheckedUrl>,
+}
+
+/// Thread position of an event
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Thread {
+    /// Root of the thread
+    pub root: Option<ThreadEvent>,
+    /// Parent: the event directly replied to (the root, for a direct reply to the root)
+    pub parent: Option<ThreadEvent>,
+    /// Mentioned events
+    pub mentions: Vec<ThreadEvent>,
+}
+
+impl Thread {
+    /// Check if the event is a reply
+    #[inline]
+    pub fn is_reply(&self) -> bool {
+        self.parent.is_some()
+    }
+}
+
+/// Extract the thread position of an event from its `e` tags
+///
+/// Both the marked (`root`, `reply` and `mention` markers) and the deprecated positional schemes are supported:
+/// * marked: the `reply` marker is the parent, or the `root` one if missing (direct reply to the root).
+///   Unmarked `e` tags are considered mentions;
+/// * positional: the first `e` tag is the root, the last one the parent and the others are mentions.
+pub fn extract_thread(event: &Event) -> Thread {
+    let tags: Vec<(ThreadEvent, Option<&Marker>)> = event
+        .iter_tags()
+        .filter_map(|t| match t {
+            Tag::Event {
+                event_id,
+                relay_url,
+                marker,
+            } => Some((
+                ThreadEvent {
+                    event_id: *event_id,
+                    relay_url: relay_url.clone(),
+                },
+                marker.as_ref(),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let marked: bool = tags
+        .iter()
+        .any(|(_, marker)| matches!(marker, Some(Marker::Root) | Some(Marker::Reply)));
+
+    if marked {
+        let mut thread = Thread::default();
+        for (e, marker) in tags.into_iter() {
+            match marker {
+                Some(Marker::Root) => thread.root = Some(e),
+                Some(Marker::Reply) => thread.parent = Some(e),
+                Some(Marker::Mention) | None => thread.mentions.push(e),
+                Some(Marker::Custom(..)) => (),
+            }
+        }
+
+        if thread.parent.is_none() {
+            thread.parent = thread.root.clone();
+        }
+
+        // Some clients only set the `reply` marker for direct replies
+        if thread.root.is_none() {
+            thread.root = thread.parent.clone();
+        }
+
+        return thread;
+    }
+
+    let mut events: Vec<ThreadEvent> = tags.into_iter().map(|(e, _)| e).collect();
+    match events.len() {
+        0 => Thread::default(),
+        1 => {
+            let root: ThreadEvent = events.remove(0);
+            Thread {
+                root: Some(root.clone()),
+                parent: Some(root),
+                mentions: Vec::new(),
+            }
+        }
+        _ => {
+            let root: ThreadEvent = events.remove(0);
+            let parent: Option<ThreadEvent> = events.pop();
+            Thread {
+                root: Some(root),
+                parent,
+                mentions: events,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Kind};
+
+    fn event_id(n: u8) -> EventId {
+        EventId::from_slice(&[n; 32]).unwrap()
+    }
+
+    fn thread_event(n: u8) -> ThreadEvent {
+        ThreadEvent {
+            event_id: event_id(n),
+            relay_url: None,
+        }
+    }
+
+    #[test]
+    fn test_extract_thread_marked() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new(
+            Kind::TextNote,
+            "",
+            [
+                Tag::Event {
+                    event_id: event_id(3),
+                    relay_url: None,
+                    marker: Some(Marker::Mention),
+                },
+                Tag::Event {
+                    event_id: event_id(2),
+                    relay_url: None,
+                    marker: Some(Marker::Reply),
+                },
+                Tag::Event {
+                    event_id: event_id(1),
+                    relay_url: None,
+                    marker: Some(Marker::Root),
+                },
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let thread = extract_thread(&event);
+        assert_eq!(thread.root, Some(thread_event(1)));
+        assert_eq!(thread.parent, Some(thread_event(2)));
+        assert_eq!(thread.mentions, vec![thread_event(3)]);
+    }
+
+    #[test]
+    fn test_extract_thread_positional() {
+        let keys = Keys::generate();
+        let event = EventBuilder::new(
+            Kind::TextNote,
+            "",
+            [
+                Tag::event(event_id(1)),
+                Tag::event(event_id(3)),
+                Tag::event(event_id(2)),
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+        let thread = extract_thread(&event);
+        assert_eq!(thread.root, Some(thread_event(1)));
+        assert_eq!(thread.parent, Some(thread_event(2)));
+        assert_eq!(thread.mentions, vec![thread_event(3)]);
+
+        let event = EventBuilder::new(Kind::TextNote, "", [Tag::event(event_id(1))])
+            .to_event(&keys)
+            .unwrap();
+        let thread = extract_thread(&event);
+        assert_eq!(thread.root, Some(thread_event(1)));
+        assert_eq!(thread.parent, Some(thread_event(1)));
+
+        let event = EventBuilder::text_note("", []).to_event(&keys).unwrap();
+        assert!(!extract_thread(&event).is_reply());
+    }
+
+    #[test]
+    fn test_text_note_reply_thread() {
+        let keys = Keys::generate();
+        let root = EventBuilder::text_note("Root", []).to_event(&keys).unwrap();
+        let reply = EventBuilder::text_note_reply("Reply", &root, None, None)
+            .to_event(&keys)
+            .unwrap();
+        let thread = extract_thread(&reply);
+        assert_eq!(thread.root.as_ref().map(|e| e.event_id), Some(root.id()));
+        assert_eq!(thread.parent.map(|e| e.event_id), Some(root.id()));
+
+        // The root is resolved from the thread of the replied event
+        let nested = EventBuilder::text_note_reply("Nested", &reply, None, None)
+            .to_event(&keys)
+            .unwrap();
+        let thread = extract_thread(&nested);
+        assert_eq!(thread.root.map(|e| e.event_id), Some(root.id()));
+        assert_eq!(thread.parent.map(|e| e.event_id), Some(reply.id()));
+        assert_eq!(nested.public_keys().count(), 1);
+    }
+}
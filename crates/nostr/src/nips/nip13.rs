@@ -9,6 +9,47 @@
 
 use alloc::string::String;
 use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// Mining token
+///
+/// Allow to cancel a running POW mining and to check its progress from another thread.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct PowToken {
+    cancelled: Arc<AtomicBool>,
+    hashes: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "std")]
+impl PowToken {
+    /// New token
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel mining
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Check if mining has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Number of hashes computed so far (updated in batches)
+    pub fn hashes(&self) -> u64 {
+        self.hashes.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn add_hashes(&self, hashes: u64) {
+        self.hashes.fetch_add(hashes, Ordering::SeqCst);
+    }
+}
 
 /// Gets the number of leading zero bits. Result is between 0 and 255.
 #[inline]
@@ -57,6 +98,21 @@ pub fn get_prefixes_for_difficulty(leading_zero_bits: u8) -> Vec<String> {
     r
 }
 
+/// Get the POW difficulty required by a relay from a rejection message
+///
+/// Relays reject events with not enough POW with a message prefixed by `pow:`
+/// (i.e. `pow: difficulty 25 required`). The highest number in the message is returned.
+pub fn required_difficulty<S>(message: S) -> Option<u8>
+where
+    S: AsRef<str>,
+{
+    let reason: &str = message.as_ref().trim_start().strip_prefix("pow:")?;
+    reason
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|n| n.parse::<u8>().ok())
+        .max()
+}
+
 #[cfg(test)]
 pub mod tests {
     use core::str::FromStr;
@@ -435,4 +491,15 @@ pub mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_required_difficulty() {
+        assert_eq!(required_difficulty("pow: difficulty 25 required"), Some(25));
+        assert_eq!(
+            required_difficulty("pow: difficulty 20 is less than 28"),
+            Some(28)
+        );
+        assert_eq!(required_difficulty("pow:"), None);
+        assert_eq!(required_difficulty("blocked: difficulty 25"), None);
+    }
 }
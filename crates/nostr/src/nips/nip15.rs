@@ -4,12 +4,77 @@
 
 //! NIP15
 //!
+//! Nostr Marketplace
+//!
 //! <https:///github.com/nostr-protocol/nips/blob/master/15.md>
 
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt;
+
+use serde::de::Deserializer;
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Event, JsonUtil, Kind, PublicKey, Tag};
+
+/// NIP15 error
+#[derive(Debug)]
+pub enum Error {
+    /// JSON error
+    Json(serde_json::Error),
+    /// Wrong event kind
+    WrongKind,
+    /// Product currency not match the stall currency
+    CurrencyMismatch {
+        /// Stall currency
+        expected: String,
+        /// Product currency
+        found: String,
+    },
+    /// Product not found in the stall
+    UnknownProduct(String),
+    /// Shipping method not found in the stall
+    UnknownShipping(String),
+    /// Ordered quantity exceeds the available one
+    InsufficientQuantity {
+        /// Product ID
+        product_id: String,
+        /// Available quantity
+        available: u64,
+    },
+}
 
-use crate::{JsonUtil, PublicKey, Tag};
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Json: {e}"),
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::CurrencyMismatch { expected, found } => {
+                write!(f, "Currency mismatch: expected={expected}, found={found}")
+            }
+            Self::UnknownProduct(id) => write!(f, "Unknown product: {id}"),
+            Self::UnknownShipping(id) => write!(f, "Unknown shipping method: {id}"),
+            Self::InsufficientQuantity {
+                product_id,
+                available,
+            } => write!(
+                f,
+                "Insufficient quantity for product {product_id}: available={available}"
+            ),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
 
 /// Payload for creating or updating stall
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +116,74 @@ impl StallData {
     pub fn shipping(self, shipping: Vec<ShippingMethod>) -> Self {
         Self { shipping, ..self }
     }
+
+    /// Get shipping method by ID
+    pub fn shipping_method(&self, id: &str) -> Option<&ShippingMethod> {
+        self.shipping.iter().find(|m| m.id == id)
+    }
+
+    /// Get the shipping methods covering a region (zone)
+    pub fn shipping_methods_for<'a>(
+        &'a self,
+        region: &'a str,
+    ) -> impl Iterator<Item = &'a ShippingMethod> {
+        self.shipping
+            .iter()
+            .filter(move |m| m.regions.iter().any(|r| r.eq_ignore_ascii_case(region)))
+    }
+
+    /// Compute the total of an order (products and shipping), in the stall currency
+    ///
+    /// Products must belong to the stall and use its currency.
+    /// The shipping cost is the cost of the stall shipping method plus the extra cost
+    /// of each ordered product for that method.
+    pub fn order_total(
+        &self,
+        products: &[ProductData],
+        order: &CustomerOrder,
+    ) -> Result<f64, Error> {
+        let method: &ShippingMethod = self
+            .shipping_method(&order.shipping_id)
+            .ok_or_else(|| Error::UnknownShipping(order.shipping_id.clone()))?;
+
+        let mut total: f64 = method.cost;
+        for item in order.items.iter() {
+            let product: &ProductData = products
+                .iter()
+                .find(|p| p.id == item.id && p.stall_id == self.id)
+                .ok_or_else(|| Error::UnknownProduct(item.id.clone()))?;
+
+            if product.currency != self.currency {
+                return Err(Error::CurrencyMismatch {
+                    expected: self.currency.clone(),
+                    found: product.currency.clone(),
+                });
+            }
+
+            if item.quantity > product.quantity {
+                return Err(Error::InsufficientQuantity {
+                    product_id: product.id.clone(),
+                    available: product.quantity,
+                });
+            }
+
+            total += product.price * item.quantity as f64;
+            total += product.extra_shipping_cost(&method.id);
+        }
+
+        Ok(total)
+    }
+}
+
+impl TryFrom<&Event> for StallData {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::SetStall {
+            return Err(Error::WrongKind);
+        }
+        Ok(Self::from_json(event.content())?)
+    }
 }
 
 impl From<StallData> for Vec<Tag> {
@@ -161,12 +294,43 @@ impl ProductData {
             ..self
         }
     }
+
+    /// Get the extra cost of a shipping method (`0` if not set)
+    pub fn extra_shipping_cost(&self, shipping_id: &str) -> f64 {
+        self.shipping
+            .iter()
+            .find(|s| s.id == shipping_id)
+            .map(|s| s.cost)
+            .unwrap_or_default()
+    }
+}
+
+impl TryFrom<&Event> for ProductData {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::SetProduct {
+            return Err(Error::WrongKind);
+        }
+        let mut product: Self = Self::from_json(event.content())?;
+        let categories: Vec<String> = event
+            .iter_tags()
+            .filter_map(|tag| match tag {
+                Tag::Hashtag(hashtag) => Some(hashtag.clone()),
+                _ => None,
+            })
+            .collect();
+        if !categories.is_empty() {
+            product.categories = Some(categories);
+        }
+        Ok(product)
+    }
 }
 
 impl From<ProductData> for Vec<Tag> {
     fn from(value: ProductData) -> Self {
         let mut tags = Vec::new();
-        tags.push(Tag::Identifier(value.stall_id));
+        tags.push(Tag::Identifier(value.id));
         value.categories.unwrap_or_default().iter().for_each(|cat| {
             tags.push(Tag::Hashtag(cat.into()));
         });
@@ -350,6 +514,69 @@ impl JsonUtil for PaymentOption {
     type Err = serde_json::Error;
 }
 
+/// Checkout message, exchanged between customer and merchant via encrypted direct messages
+#[derive(Debug, Clone)]
+pub enum CheckoutMessage {
+    /// Customer order (type `0`)
+    Order(Box<CustomerOrder>),
+    /// Merchant payment request (type `1`)
+    PaymentRequest(MerchantPaymentRequest),
+    /// Merchant order status update (type `2`)
+    OrderStatus(MerchantVerifyPayment),
+}
+
+impl CheckoutMessage {
+    /// Order ID
+    pub fn order_id(&self) -> &str {
+        match self {
+            Self::Order(order) => &order.id,
+            Self::PaymentRequest(request) => &request.id,
+            Self::OrderStatus(status) => &status.id,
+        }
+    }
+}
+
+impl Serialize for CheckoutMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Order(order) => order.serialize(serializer),
+            Self::PaymentRequest(request) => request.serialize(serializer),
+            Self::OrderStatus(status) => status.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CheckoutMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: Value = Value::deserialize(deserializer)?;
+        let r#type: u64 = value
+            .get("type")
+            .and_then(|t| t.as_u64())
+            .ok_or_else(|| serde::de::Error::missing_field("type"))?;
+        match r#type {
+            0 => serde_json::from_value(value).map(Self::Order),
+            1 => serde_json::from_value(value).map(Self::PaymentRequest),
+            2 => serde_json::from_value(value).map(Self::OrderStatus),
+            t => {
+                return Err(serde::de::Error::custom(format!(
+                    "unknown message type: {t}"
+                )))
+            }
+        }
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonUtil for CheckoutMessage {
+    type Err = serde_json::Error;
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::string::String;
@@ -393,8 +620,8 @@ mod tests {
         assert_eq!(tags.len(), 3);
         assert_eq!(
             tags[0],
-            Tag::Identifier("456".into()),
-            "tags contains stall id"
+            Tag::Identifier("123".into()),
+            "tags contains product id"
         );
         assert_eq!(
             tags[1],
@@ -413,4 +640,46 @@ mod tests {
             r#"{"id":"123","stall_id":"456","name":"Test Product","images":["https://example.com/image.png"],"currency":"USD","price":10.0,"quantity":10,"specs":[["Size","M"]],"shipping":[{"id":"123","cost":5.0}]}"#
         );
     }
+
+    #[test]
+    fn test_order_total() {
+        let stall = StallData::new("stall", "Test Stall", "USD").shipping(vec![
+            ShippingMethod::new("eu", 5.0).regions(vec!["Europe".into()]),
+            ShippingMethod::new("world", 20.0).regions(vec!["Worldwide".into()]),
+        ]);
+        assert_eq!(stall.shipping_methods_for("europe").count(), 1);
+
+        let products = vec![
+            ProductData::new("shirt", "stall", "Shirt", "USD")
+                .price(10.0)
+                .quantity(5)
+                .shipping(vec![ShippingCost {
+                    id: "eu".into(),
+                    cost: 1.0,
+                }]),
+            ProductData::new("mug", "stall", "Mug", "EUR").price(8.0),
+        ];
+
+        let json = r#"{"id":"order","type":0,"name":null,"address":null,"message":null,"contact":{"nostr":null,"phone":null,"email":null},"items":[{"id":"shirt","quantity":2}],"shipping_id":"eu"}"#;
+        let order = match CheckoutMessage::from_json(json).unwrap() {
+            CheckoutMessage::Order(order) => order,
+            _ => panic!("Expected order"),
+        };
+        assert_eq!(stall.order_total(&products, &order).unwrap(), 26.0);
+
+        let mut mug_order = order.clone();
+        mug_order.items = vec![CustomerOrderItem {
+            id: "mug".into(),
+            quantity: 1,
+        }];
+        assert!(matches!(
+            stall.order_total(&products, &mug_order),
+            Err(Error::CurrencyMismatch { .. })
+        ));
+
+        let status = r#"{"id":"order","type":2,"paid":true,"shipped":false}"#;
+        let message = CheckoutMessage::from_json(status).unwrap();
+        assert_eq!(message.order_id(), "order");
+        assert_eq!(message.as_json(), status);
+    }
 }
@@ -0,0 +1,90 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP17
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/17.md>
+
+use alloc::collections::BTreeSet;
+use core::fmt;
+
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::{Hash, HashEngine};
+
+use crate::{PublicKey, Tag, UnsignedEvent};
+
+/// Conversation ID
+///
+/// Derived from the set of participants (sender and receivers), so every participant
+/// computes the same ID, regardless of who sent the message and of the order of the `p` tags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConversationId(Sha256Hash);
+
+impl ConversationId {
+    /// Compute the conversation ID from the participants
+    pub fn new<I>(participants: I) -> Self
+    where
+        I: IntoIterator<Item = PublicKey>,
+    {
+        let participants: BTreeSet<PublicKey> = participants.into_iter().collect();
+        let mut engine = Sha256Hash::engine();
+        for public_key in participants.into_iter() {
+            engine.input(&public_key.to_bytes());
+        }
+        Self(Sha256Hash::from_engine(engine))
+    }
+
+    /// Compute the conversation ID of a rumor (kind `14`): author and `p` tags
+    pub fn from_rumor(rumor: &UnsignedEvent) -> Self {
+        let receivers = rumor.tags.iter().filter_map(|t| match t {
+            Tag::PublicKey { public_key, .. } => Some(*public_key),
+            _ => None,
+        });
+        Self::new(core::iter::once(rumor.pubkey).chain(receivers))
+    }
+
+    /// Get as bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_byte_array()
+    }
+}
+
+impl fmt::Display for ConversationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Kind};
+
+    #[test]
+    fn test_conversation_id() {
+        let alice = Keys::generate().public_key();
+        let bob = Keys::generate().public_key();
+        let carol = Keys::generate().public_key();
+
+        assert_eq!(
+            ConversationId::new([alice, bob, carol]),
+            ConversationId::new([carol, alice, bob, alice])
+        );
+        assert_ne!(
+            ConversationId::new([alice, bob]),
+            ConversationId::new([alice, bob, carol])
+        );
+
+        let rumor = EventBuilder::new(
+            Kind::SealedDirect,
+            "Hello",
+            [Tag::public_key(carol), Tag::public_key(bob)],
+        )
+        .to_unsigned_event(alice);
+        assert_eq!(
+            ConversationId::from_rumor(&rumor),
+            ConversationId::new([bob, carol, alice])
+        );
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP18
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/18.md>
+
+use core::fmt;
+
+use bitcoin::secp256k1::{Secp256k1, Verification};
+
+use crate::event::{self, Event};
+#[cfg(feature = "std")]
+use crate::SECP256K1;
+use crate::{JsonUtil, Kind, Tag};
+
+/// NIP18 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Event error
+    Event(event::Error),
+    /// Wrong event kind
+    WrongKind,
+    /// The reposted event isn't embedded
+    EmptyContent,
+    /// The embedded event doesn't match the `e` tag
+    Mismatch,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Event(e) => write!(f, "Event: {e}"),
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::EmptyContent => write!(f, "Reposted event not embedded"),
+            Self::Mismatch => write!(f, "Reposted event doesn't match the `e` tag"),
+        }
+    }
+}
+
+impl From<event::Error> for Error {
+    fn from(e: event::Error) -> Self {
+        Self::Event(e)
+    }
+}
+
+/// Extract the reposted event embedded in a repost (kind `6` or `16`)
+///
+/// The embedded event is verified (ID and signature) and must match the `e` tag.
+#[inline]
+#[cfg(feature = "std")]
+pub fn extract_reposted_event(repost: &Event) -> Result<Event, Error> {
+    extract_reposted_event_with_ctx(&SECP256K1, repost)
+}
+
+/// Extract the reposted event embedded in a repost (kind `6` or `16`)
+///
+/// The embedded event is verified (ID and signature) and must match the `e` tag.
+pub fn extract_reposted_event_with_ctx<C>(
+    secp: &Secp256k1<C>,
+    repost: &Event,
+) -> Result<Event, Error>
+where
+    C: Verification,
+{
+    if repost.kind() != Kind::Repost && repost.kind() != Kind::GenericRepost {
+        return Err(Error::WrongKind);
+    }
+
+    if repost.content().is_empty() {
+        return Err(Error::EmptyContent);
+    }
+
+    let event: Event = Event::from_json(repost.content())?;
+    event.verify_with_ctx(secp)?;
+
+    // Check that the embedded event is the one referenced by the `e` tag
+    let referenced: bool = repost.iter_tags().any(|t| match t {
+        Tag::Event { event_id, .. } => event_id == &event.id(),
+        _ => false,
+    });
+    if !referenced {
+        return Err(Error::Mismatch);
+    }
+
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_extract_reposted_event() {
+        let keys = Keys::generate();
+        let note = EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap();
+        let repost = EventBuilder::repost(&note, None).to_event(&keys).unwrap();
+        assert_eq!(repost.kind(), Kind::Repost);
+        assert_eq!(extract_reposted_event(&repost).unwrap(), note);
+
+        let other = EventBuilder::text_note("Other", [])
+            .to_event(&keys)
+            .unwrap();
+        let forged = EventBuilder::new(
+            Kind::Repost,
+            other.as_json(),
+            [Tag::event(note.id()), Tag::public_key(note.author())],
+        )
+        .to_event(&keys)
+        .unwrap();
+        assert_eq!(extract_reposted_event(&forged), Err(Error::Mismatch));
+
+        assert_eq!(extract_reposted_event(&note), Err(Error::WrongKind));
+    }
+}
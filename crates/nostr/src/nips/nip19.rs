@@ -362,6 +362,7 @@ impl ToBech32 for EventId {
 pub struct Nip19Event {
     pub event_id: EventId,
     pub author: Option<PublicKey>,
+    pub kind: Option<Kind>,
     pub relays: Vec<String>,
 }
 
@@ -374,13 +375,27 @@ impl Nip19Event {
         Self {
             event_id,
             author: None,
+            kind: None,
             relays: relays.into_iter().map(|u| u.into()).collect(),
         }
     }
 
+    /// Set author
+    pub fn author(mut self, author: PublicKey) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    /// Set kind
+    pub fn kind(mut self, kind: Kind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
     fn from_bech32_data(mut data: Vec<u8>) -> Result<Self, Error> {
         let mut event_id: Option<EventId> = None;
         let mut author: Option<PublicKey> = None;
+        let mut kind: Option<Kind> = None;
         let mut relays: Vec<String> = Vec::new();
 
         while !data.is_empty() {
@@ -403,6 +418,11 @@ impl Nip19Event {
                         author = Some(PublicKey::from_slice(bytes)?);
                     }
                 }
+                KIND => {
+                    if kind.is_none() {
+                        kind = Some(parse_kind(bytes)?);
+                    }
+                }
                 RELAY => {
                     relays.push(String::from_utf8(bytes.to_vec())?);
                 }
@@ -415,6 +435,7 @@ impl Nip19Event {
         Ok(Self {
             event_id: event_id.ok_or_else(|| Error::FieldMissing("event id".to_string()))?,
             author,
+            kind,
             relays,
         })
     }
@@ -449,6 +470,18 @@ impl ToBech32 for Nip19Event {
             bytes.extend(relay.as_bytes());
         }
 
+        // Author
+        if let Some(author) = &self.author {
+            bytes.extend([AUTHOR, 32]);
+            bytes.extend(author.serialize());
+        }
+
+        // Kind
+        if let Some(kind) = &self.kind {
+            bytes.extend([KIND, 4]);
+            bytes.extend(kind.as_u32().to_be_bytes());
+        }
+
         Ok(bech32::encode::<Bech32>(HRP_EVENT, &bytes)?)
     }
 }
@@ -573,10 +606,7 @@ impl Coordinate {
                 }
                 KIND => {
                     if kind.is_none() {
-                        let k: u64 =
-                            u32::from_be_bytes(bytes.try_into().map_err(|_| Error::TryFromSlice)?)
-                                as u64;
-                        kind = Some(Kind::from(k));
+                        kind = Some(parse_kind(bytes)?);
                     }
                 }
                 _ => (),
@@ -638,6 +668,12 @@ impl ToBech32 for Coordinate {
     }
 }
 
+/// Parse kind TLV value (32-bit unsigned integer, big-endian)
+fn parse_kind(bytes: &[u8]) -> Result<Kind, Error> {
+    let kind: u32 = u32::from_be_bytes(bytes.try_into().map_err(|_| Error::TryFromSlice)?);
+    Ok(Kind::from(kind as u64))
+}
+
 #[cfg(test)]
 mod tests {
     use core::str::FromStr;
@@ -716,4 +752,28 @@ mod tests {
         let event = Nip19Event::from_bech32(nevent).unwrap();
         assert_eq!(event.author, Some(expected_pubkey));
     }
+
+    #[test]
+    fn nip19_event_tlv_round_trip() {
+        let event_id =
+            EventId::from_hex("d94a3f4dd87b9a3b0bed183b32e916fa29c8020107845d1752d72697fe5309a5")
+                .unwrap();
+        let author =
+            PublicKey::from_str("32e1827635450ebb3c5a7d12c1f8e7b2b514439ac10a67eef3d9fd9c5c68e245")
+                .unwrap();
+        let event = Nip19Event::new(event_id, ["wss://relay.damus.io"])
+            .author(author)
+            .kind(Kind::TextNote);
+
+        let nevent: String = event.to_bech32().unwrap();
+        let decoded = Nip19Event::from_bech32(&nevent).unwrap();
+        assert_eq!(decoded, event);
+        assert_eq!(decoded.kind, Some(Kind::TextNote));
+        assert_eq!(decoded.author, Some(author));
+
+        // Without optional TLVs
+        let event = Nip19Event::new(event_id, Vec::<String>::new());
+        let decoded = Nip19Event::from_bech32(event.to_bech32().unwrap()).unwrap();
+        assert_eq!(decoded, event);
+    }
 }
@@ -7,11 +7,12 @@
 //! <https://github.com/nostr-protocol/nips/blob/master/21.md>
 
 use alloc::string::String;
+use alloc::vec::Vec;
 use core::fmt;
 
 use super::nip01::Coordinate;
 use super::nip19::{self, FromBech32, Nip19, Nip19Event, Nip19Profile, ToBech32};
-use crate::{EventId, PublicKey};
+use crate::{EventId, PublicKey, Url};
 
 /// URI scheme
 pub const SCHEME: &str = "nostr";
@@ -181,6 +182,145 @@ impl Nip21 {
     }
 }
 
+/// Bech32 prefixes of entities that can be referenced in content
+const BECH32_PREFIXES: [&str; 5] = ["npub1", "nprofile1", "note1", "nevent1", "naddr1"];
+
+/// Content token
+///
+/// See [`parse_content`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// Profile mention (`npub` or `nprofile`)
+    Mention(Nip19Profile),
+    /// Event reference (`note` or `nevent`)
+    EventRef(Nip19Event),
+    /// Address reference (`naddr`)
+    Address(Coordinate),
+    /// Hashtag (without `#`)
+    Hashtag(&'a str),
+    /// URL
+    Url(&'a str),
+    /// Plain text
+    Text(&'a str),
+}
+
+impl From<Nip21> for Token<'_> {
+    fn from(value: Nip21) -> Self {
+        match value {
+            Nip21::Pubkey(public_key) => Self::Mention(Nip19Profile {
+                public_key,
+                relays: Vec::new(),
+            }),
+            Nip21::Profile(profile) => Self::Mention(profile),
+            Nip21::EventId(event_id) => {
+                Self::EventRef(Nip19Event::new(event_id, Vec::<String>::new()))
+            }
+            Nip21::Event(event) => Self::EventRef(event),
+            Nip21::Coordinate(coordinate) => Self::Address(coordinate),
+        }
+    }
+}
+
+/// Parse event content into a list of [`Token`]s
+///
+/// Extract `nostr:` URIs, bare bech32 entities (`npub`, `nprofile`, `note`, `nevent` and `naddr`),
+/// hashtags and URLs. Everything else (whitespaces included) is returned as [`Token::Text`],
+/// so the original content can be rebuilt by concatenating the tokens.
+pub fn parse_content(content: &str) -> Vec<Token<'_>> {
+    let mut tokens: Vec<Token<'_>> = Vec::new();
+    let mut text_start: usize = 0;
+    let mut pos: usize = 0;
+
+    while pos < content.len() {
+        let rest: &str = &content[pos..];
+        let word_len: usize = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+        // Skip whitespace
+        if word_len == 0 {
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+            continue;
+        }
+
+        if let Some((offset, len, token)) = parse_word(&rest[..word_len]) {
+            let start: usize = pos + offset;
+            if start > text_start {
+                tokens.push(Token::Text(&content[text_start..start]));
+            }
+            tokens.push(token);
+            text_start = start + len;
+        }
+
+        pos += word_len;
+    }
+
+    if text_start < content.len() {
+        tokens.push(Token::Text(&content[text_start..]));
+    }
+
+    tokens
+}
+
+/// Parse a single word (without whitespaces)
+///
+/// Return the offset and the length of the entity in the word and the parsed token.
+fn parse_word(word: &str) -> Option<(usize, usize, Token<'_>)> {
+    // Skip leading punctuation (i.e. parenthesis or quotes)
+    let trimmed: &str = word.trim_start_matches(['(', '[', '{', '<', '"', '\'']);
+    let offset: usize = word.len() - trimmed.len();
+
+    // URL
+    if trimmed.starts_with("https://") || trimmed.starts_with("http://") {
+        let url: &str =
+            trimmed.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}', '>', '"', '\'']);
+        return match Url::parse(url) {
+            Ok(..) => Some((offset, url.len(), Token::Url(url))),
+            Err(..) => None,
+        };
+    }
+
+    // NIP21 URI
+    if let Some(data) = trimmed
+        .strip_prefix(SCHEME)
+        .and_then(|d| d.strip_prefix(':'))
+    {
+        let len: usize = SCHEME.len() + 1 + bech32_len(data);
+        return match Nip21::parse(&trimmed[..len]) {
+            Ok(nip21) => Some((offset, len, Token::from(nip21))),
+            Err(..) => None,
+        };
+    }
+
+    // Bare bech32 entity
+    if BECH32_PREFIXES.iter().any(|p| trimmed.starts_with(p)) {
+        let len: usize = bech32_len(trimmed);
+        return match Nip19::from_bech32(&trimmed[..len]) {
+            Ok(nip19) => match Nip21::try_from(nip19) {
+                Ok(nip21) => Some((offset, len, Token::from(nip21))),
+                Err(..) => None,
+            },
+            Err(..) => None,
+        };
+    }
+
+    // Hashtag
+    if let Some(tag) = trimmed.strip_prefix('#') {
+        let len: usize = tag
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(tag.len());
+        if len > 0 {
+            return Some((offset, len + 1, Token::Hashtag(&tag[..len])));
+        }
+    }
+
+    None
+}
+
+/// Length of the leading bech32 data
+fn bech32_len(data: &str) -> usize {
+    data.find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(data.len())
+}
+
 #[cfg(test)]
 mod tests {
     use core::str::FromStr;
@@ -237,4 +377,48 @@ mod tests {
             Error::UnsupportedBech32Type(UnsupportedBech32Type::SecretKey)
         );
     }
+
+    #[test]
+    fn test_parse_content() {
+        let content = "Hello nostr:npub14f8usejl26twx0dhuxjh9cas7keav9vr0v8nvtwtrjqx3vycc76qqh9nsy, check #nostr at https://example.com/path. (note1m99r7nwc0wdrkzldrqan96gklg5usqspq7z9696j6unf0ljnpxjspqfw99)";
+        let tokens = parse_content(content);
+
+        let public_key =
+            PublicKey::from_str("aa4fc8665f5696e33db7e1a572e3b0f5b3d615837b0f362dcb1c8068b098c7b4")
+                .unwrap();
+        let event_id =
+            EventId::from_hex("d94a3f4dd87b9a3b0bed183b32e916fa29c8020107845d1752d72697fe5309a5")
+                .unwrap();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("Hello "),
+                Token::Mention(Nip19Profile {
+                    public_key,
+                    relays: Vec::new()
+                }),
+                Token::Text(", check "),
+                Token::Hashtag("nostr"),
+                Token::Text(" at "),
+                Token::Url("https://example.com/path"),
+                Token::Text(". ("),
+                Token::EventRef(Nip19Event::new(event_id, Vec::<String>::new())),
+                Token::Text(")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_content_invalid_entities() {
+        let content = "nostr:npub1invalid #  #_ plain";
+        assert_eq!(
+            parse_content(content),
+            vec![
+                Token::Text("nostr:npub1invalid #  "),
+                Token::Hashtag("_"),
+                Token::Text(" plain"),
+            ]
+        );
+    }
 }
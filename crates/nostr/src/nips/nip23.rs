@@ -0,0 +1,225 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP23
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/23.md>
+
+use alloc::string::String;
+use alloc::vec::{self, Vec};
+use core::fmt;
+
+use crate::{Event, ImageDimensions, Kind, Tag, Timestamp, UncheckedUrl};
+
+/// NIP23 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `d` tag identifier
+    MissingIdentifier,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingIdentifier => write!(f, "Missing identifier"),
+        }
+    }
+}
+
+/// Long-form content metadata
+///
+/// Use with [`EventBuilder::long_form_text_note`](crate::EventBuilder::long_form_text_note)
+/// or [`EventBuilder::draft`](crate::EventBuilder::draft).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongFormContent {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Title
+    pub title: Option<String>,
+    /// Summary
+    pub summary: Option<String>,
+    /// Image
+    pub image: Option<(UncheckedUrl, Option<ImageDimensions>)>,
+    /// First publication time
+    pub published_at: Option<Timestamp>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+}
+
+impl LongFormContent {
+    /// New long-form content metadata
+    pub fn new<S>(identifier: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            identifier: identifier.into(),
+            title: None,
+            summary: None,
+            image: None,
+            published_at: None,
+            hashtags: Vec::new(),
+        }
+    }
+
+    /// Set title
+    pub fn title<S>(self, title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    /// Set summary
+    pub fn summary<S>(self, summary: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            summary: Some(summary.into()),
+            ..self
+        }
+    }
+
+    /// Set image
+    pub fn image(self, url: UncheckedUrl, dimensions: Option<ImageDimensions>) -> Self {
+        Self {
+            image: Some((url, dimensions)),
+            ..self
+        }
+    }
+
+    /// Set first publication time
+    ///
+    /// Keep the original value when editing an already published article.
+    pub fn published_at(self, published_at: Timestamp) -> Self {
+        Self {
+            published_at: Some(published_at),
+            ..self
+        }
+    }
+
+    /// Add hashtag
+    pub fn hashtag<S>(mut self, hashtag: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.hashtags.push(hashtag.into());
+        self
+    }
+}
+
+impl From<LongFormContent> for Vec<Tag> {
+    fn from(content: LongFormContent) -> Self {
+        let LongFormContent {
+            identifier,
+            title,
+            summary,
+            image,
+            published_at,
+            hashtags,
+        } = content;
+
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(identifier)];
+
+        if let Some(title) = title {
+            tags.push(Tag::Title(title));
+        }
+
+        if let Some(summary) = summary {
+            tags.push(Tag::Summary(summary));
+        }
+
+        if let Some((url, dimensions)) = image {
+            tags.push(Tag::Image(url, dimensions));
+        }
+
+        if let Some(published_at) = published_at {
+            tags.push(Tag::PublishedAt(published_at));
+        }
+
+        tags.extend(hashtags.into_iter().map(Tag::Hashtag));
+
+        tags
+    }
+}
+
+impl IntoIterator for LongFormContent {
+    type Item = Tag;
+    type IntoIter = vec::IntoIter<Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Vec::<Tag>::from(self).into_iter()
+    }
+}
+
+impl TryFrom<&Event> for LongFormContent {
+    type Error = Error;
+
+    /// Extract the [`LongFormContent`] from a long-form text note (kind `30023`) or draft (kind `30024`)
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::LongFormTextNote && event.kind() != Kind::LongFormTextNoteDraft {
+            return Err(Error::WrongKind);
+        }
+
+        let identifier: &str = event.identifier().ok_or(Error::MissingIdentifier)?;
+        let mut content = Self::new(identifier);
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Title(title) => content.title = Some(title.clone()),
+                Tag::Summary(summary) => content.summary = Some(summary.clone()),
+                Tag::Image(url, dimensions) => content.image = Some((url.clone(), *dimensions)),
+                Tag::PublishedAt(published_at) => content.published_at = Some(*published_at),
+                Tag::Hashtag(hashtag) => content.hashtags.push(hashtag.clone()),
+                _ => (),
+            }
+        }
+
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_long_form_content() {
+        let keys = Keys::generate();
+        let metadata = LongFormContent::new("lorem-ipsum")
+            .title("Lorem Ipsum")
+            .summary("Dolor sit amet")
+            .image(UncheckedUrl::from("https://example.com/cover.jpg"), None)
+            .published_at(Timestamp::from(1296962229))
+            .hashtag("placeholder");
+
+        let event = EventBuilder::long_form_text_note("Lorem ipsum...", metadata.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::LongFormTextNote);
+        assert_eq!(LongFormContent::try_from(&event).unwrap(), metadata);
+
+        let draft = EventBuilder::draft("Lorem ipsum...", metadata.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(draft.kind(), Kind::LongFormTextNoteDraft);
+        assert_eq!(LongFormContent::try_from(&draft).unwrap(), metadata);
+
+        let note = EventBuilder::text_note("Lorem ipsum...", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(LongFormContent::try_from(&note), Err(Error::WrongKind));
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP25
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/25.md>
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use super::nip01::Coordinate;
+use crate::{Event, EventId, Kind, PublicKey, Tag, UncheckedUrl};
+
+/// NIP25 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `e` tag
+    MissingEventTag,
+    /// Missing `p` tag
+    MissingPublicKeyTag,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingEventTag => write!(f, "Missing `e` tag"),
+            Self::MissingPublicKeyTag => write!(f, "Missing `p` tag"),
+        }
+    }
+}
+
+/// Reaction content
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReactionContent {
+    /// Like or upvote (`+` or empty content)
+    Like,
+    /// Dislike or downvote (`-`)
+    Dislike,
+    /// Emoji or any other text
+    Emoji(String),
+    /// Custom emoji
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/30.md>
+    CustomEmoji {
+        /// Shortcode (without colons)
+        shortcode: String,
+        /// Image URL
+        url: UncheckedUrl,
+    },
+}
+
+impl ReactionContent {
+    /// Parse the reaction from the content and the tags of a reaction event
+    ///
+    /// A `:shortcode:` content is a custom emoji only if a matching `emoji` tag is found.
+    pub fn parse<'a, I>(content: &str, tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        match content {
+            "" | "+" => Self::Like,
+            "-" => Self::Dislike,
+            content => {
+                if let Some(code) = content.strip_prefix(':').and_then(|c| c.strip_suffix(':')) {
+                    let url = tags.into_iter().find_map(|t| match t {
+                        Tag::Emoji { shortcode, url } if shortcode == code => Some(url.clone()),
+                        _ => None,
+                    });
+                    if let Some(url) = url {
+                        return Self::CustomEmoji {
+                            shortcode: code.to_string(),
+                            url,
+                        };
+                    }
+                }
+                Self::Emoji(content.to_string())
+            }
+        }
+    }
+
+    /// Get the event content
+    pub fn content(&self) -> String {
+        match self {
+            Self::Like => String::from("+"),
+            Self::Dislike => String::from("-"),
+            Self::Emoji(emoji) => emoji.clone(),
+            Self::CustomEmoji { shortcode, .. } => format!(":{shortcode}:"),
+        }
+    }
+
+    /// Get the `emoji` tag, if it's a custom emoji
+    pub fn emoji_tag(&self) -> Option<Tag> {
+        match self {
+            Self::CustomEmoji { shortcode, url } => Some(Tag::Emoji {
+                shortcode: shortcode.clone(),
+                url: url.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Parsed reaction (kind `7`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reaction {
+    /// Reacted event (last `e` tag)
+    pub event_id: EventId,
+    /// Author of the reacted event (last `p` tag)
+    pub public_key: PublicKey,
+    /// Coordinate of the reacted event, if it's a parameterized replaceable event (last `a` tag)
+    pub coordinate: Option<Coordinate>,
+    /// Kind of the reacted event (`k` tag)
+    pub kind: Option<Kind>,
+    /// Reaction
+    pub content: ReactionContent,
+}
+
+impl TryFrom<&Event> for Reaction {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::Reaction {
+            return Err(Error::WrongKind);
+        }
+
+        // Other `e` and `p` tags may be present: the target is the last one
+        let mut event_id: Option<EventId> = None;
+        let mut public_key: Option<PublicKey> = None;
+        let mut coordinate: Option<Coordinate> = None;
+        let mut kind: Option<Kind> = None;
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Event { event_id: id, .. } => event_id = Some(*id),
+                Tag::PublicKey {
+                    public_key: p,
+                    uppercase: false,
+                    ..
+                } => public_key = Some(*p),
+                Tag::A { coordinate: c, .. } => coordinate = Some(c.clone()),
+                Tag::Kind(k) => kind = Some(*k),
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            event_id: event_id.ok_or(Error::MissingEventTag)?,
+            public_key: public_key.ok_or(Error::MissingPublicKeyTag)?,
+            coordinate,
+            kind,
+            content: ReactionContent::parse(event.content(), event.iter_tags()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_reaction_content() {
+        assert_eq!(ReactionContent::parse("", []), ReactionContent::Like);
+        assert_eq!(ReactionContent::parse("+", []), ReactionContent::Like);
+        assert_eq!(ReactionContent::parse("-", []), ReactionContent::Dislike);
+        assert_eq!(
+            ReactionContent::parse(":soapbox:", []),
+            ReactionContent::Emoji(String::from(":soapbox:"))
+        );
+
+        let tag = Tag::Emoji {
+            shortcode: String::from("soapbox"),
+            url: UncheckedUrl::from("https://gleasonator.com/emoji/Gleasonator/soapbox.png"),
+        };
+        let emoji = ReactionContent::parse(":soapbox:", [&tag]);
+        assert_eq!(emoji.content(), ":soapbox:");
+        assert_eq!(emoji.emoji_tag(), Some(tag));
+    }
+
+    #[test]
+    fn test_reaction_target() {
+        let keys = Keys::generate();
+        let author = Keys::generate().public_key();
+        let root = EventId::all_zeros();
+        let target =
+            EventId::from_hex("2be17aa3031bdcb006f0fce80c146dea9c1c0268b0af2398bb673365c6444d45")
+                .unwrap();
+
+        let event = EventBuilder::new(
+            Kind::Reaction,
+            "🤙",
+            [
+                Tag::event(root),
+                Tag::public_key(keys.public_key()),
+                Tag::event(target),
+                Tag::public_key(author),
+                Tag::Kind(Kind::TextNote),
+            ],
+        )
+        .to_event(&keys)
+        .unwrap();
+
+        let reaction = Reaction::try_from(&event).unwrap();
+        assert_eq!(reaction.event_id, target);
+        assert_eq!(reaction.public_key, author);
+        assert_eq!(reaction.kind, Some(Kind::TextNote));
+        assert_eq!(reaction.content, ReactionContent::Emoji(String::from("🤙")));
+    }
+}
@@ -24,7 +24,7 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{json, Value};
 
 use super::nip21;
-use crate::event::Event;
+use crate::event::{Event, Tag};
 use crate::key::{self, Keys};
 use crate::PublicKey;
 #[cfg(feature = "std")]
@@ -369,6 +369,35 @@ impl FromStr for DelegationTag {
     }
 }
 
+impl TryFrom<Tag> for DelegationTag {
+    type Error = Error;
+
+    fn try_from(tag: Tag) -> Result<Self, Self::Error> {
+        match tag {
+            Tag::Delegation {
+                delegator,
+                conditions,
+                sig,
+            } => Ok(Self {
+                delegator_pubkey: delegator,
+                conditions,
+                signature: sig,
+            }),
+            _ => Err(Error::DelegationTagParse),
+        }
+    }
+}
+
+impl From<DelegationTag> for Tag {
+    fn from(tag: DelegationTag) -> Self {
+        Self::Delegation {
+            delegator: tag.delegator_pubkey,
+            conditions: tag.conditions,
+            sig: tag.signature,
+        }
+    }
+}
+
 /// A condition from the delegation conditions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Condition {
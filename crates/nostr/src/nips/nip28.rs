@@ -0,0 +1,268 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP28
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/28.md>
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use serde_json::Value;
+
+use crate::types::metadata;
+use crate::{Event, EventId, JsonUtil, Kind, Marker, Metadata, PublicKey, Tag, UncheckedUrl};
+
+/// NIP28 error
+#[derive(Debug)]
+pub enum Error {
+    /// Metadata error
+    Metadata(metadata::Error),
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `e` tag
+    MissingEventTag,
+    /// Missing `p` tag
+    MissingPublicKeyTag,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Metadata(e) => write!(f, "Metadata: {e}"),
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingEventTag => write!(f, "Missing `e` tag"),
+            Self::MissingPublicKeyTag => write!(f, "Missing `p` tag"),
+        }
+    }
+}
+
+impl From<metadata::Error> for Error {
+    fn from(e: metadata::Error) -> Self {
+        Self::Metadata(e)
+    }
+}
+
+/// Channel (kind `40`) or channel metadata update (kind `41`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Channel {
+    /// Channel ID (ID of the kind `40` event)
+    pub id: EventId,
+    /// Creator of the channel (or author of the metadata update)
+    pub creator: PublicKey,
+    /// Metadata (`name`, `about`, `picture` and `relays`)
+    pub metadata: Metadata,
+}
+
+impl TryFrom<&Event> for Channel {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        let id: EventId = match event.kind() {
+            Kind::ChannelCreation => event.id(),
+            Kind::ChannelMetadata => event
+                .event_ids()
+                .next()
+                .copied()
+                .ok_or(Error::MissingEventTag)?,
+            _ => return Err(Error::WrongKind),
+        };
+
+        Ok(Self {
+            id,
+            creator: event.author(),
+            metadata: Metadata::from_json(event.content())?,
+        })
+    }
+}
+
+/// Channel message (kind `42`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelMessage {
+    /// Channel ID
+    pub channel_id: EventId,
+    /// Relay hint of the channel
+    pub relay_url: Option<UncheckedUrl>,
+    /// Replied message
+    pub reply_to: Option<EventId>,
+    /// Author
+    pub author: PublicKey,
+    /// Content
+    pub content: String,
+}
+
+impl TryFrom<&Event> for ChannelMessage {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::ChannelMessage {
+            return Err(Error::WrongKind);
+        }
+
+        let mut root: Option<(EventId, Option<UncheckedUrl>)> = None;
+        let mut reply_to: Option<EventId> = None;
+        for tag in event.iter_tags() {
+            if let Tag::Event {
+                event_id,
+                relay_url,
+                marker,
+            } = tag
+            {
+                match marker {
+                    Some(Marker::Root) => root = Some((*event_id, relay_url.clone())),
+                    Some(Marker::Reply) => reply_to = Some(*event_id),
+                    // Deprecated positional `e` tags: the first one is the channel
+                    None if root.is_none() => root = Some((*event_id, relay_url.clone())),
+                    _ => (),
+                }
+            }
+        }
+
+        let (channel_id, relay_url) = root.ok_or(Error::MissingEventTag)?;
+        Ok(Self {
+            channel_id,
+            relay_url,
+            reply_to,
+            author: event.author(),
+            content: event.content().to_string(),
+        })
+    }
+}
+
+/// Hidden channel message (kind `43`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HideMessage {
+    /// Hidden message ID
+    pub message_id: EventId,
+    /// Reason
+    pub reason: Option<String>,
+}
+
+impl TryFrom<&Event> for HideMessage {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::ChannelHideMessage {
+            return Err(Error::WrongKind);
+        }
+
+        Ok(Self {
+            message_id: event
+                .event_ids()
+                .next()
+                .copied()
+                .ok_or(Error::MissingEventTag)?,
+            reason: extract_reason(event.content()),
+        })
+    }
+}
+
+/// Muted channel user (kind `44`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MuteUser {
+    /// Muted public key
+    pub public_key: PublicKey,
+    /// Reason
+    pub reason: Option<String>,
+}
+
+impl TryFrom<&Event> for MuteUser {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::ChannelMuteUser {
+            return Err(Error::WrongKind);
+        }
+
+        Ok(Self {
+            public_key: event
+                .public_keys()
+                .next()
+                .copied()
+                .ok_or(Error::MissingPublicKeyTag)?,
+            reason: extract_reason(event.content()),
+        })
+    }
+}
+
+/// Extract the `reason` from a JSON content (empty reasons are ignored)
+fn extract_reason(content: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(content).ok()?;
+    let reason: &str = value.get("reason")?.as_str()?;
+    if reason.is_empty() {
+        None
+    } else {
+        Some(reason.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys, Url};
+
+    #[test]
+    fn test_channel() {
+        let keys = Keys::generate();
+        let metadata = Metadata::new().name("rust-nostr").about("Channel");
+        let creation = EventBuilder::channel(&metadata).to_event(&keys).unwrap();
+        let channel = Channel::try_from(&creation).unwrap();
+        assert_eq!(channel.id, creation.id());
+        assert_eq!(channel.metadata, metadata);
+
+        let metadata = Metadata::new().name("rust-nostr");
+        let update = EventBuilder::channel_metadata(creation.id(), None, &metadata)
+            .to_event(&keys)
+            .unwrap();
+        let channel = Channel::try_from(&update).unwrap();
+        assert_eq!(channel.id, creation.id());
+        assert_eq!(channel.metadata, metadata);
+    }
+
+    #[test]
+    fn test_channel_message() {
+        let keys = Keys::generate();
+        let channel_id = EventId::all_zeros();
+        let relay_url = Url::parse("wss://relay.damus.io").unwrap();
+
+        let msg = EventBuilder::channel_msg(channel_id, relay_url.clone(), "Hello")
+            .to_event(&keys)
+            .unwrap();
+        let parsed = ChannelMessage::try_from(&msg).unwrap();
+        assert_eq!(parsed.channel_id, channel_id);
+        assert_eq!(parsed.relay_url, Some(relay_url.clone().into()));
+        assert_eq!(parsed.reply_to, None);
+        assert_eq!(parsed.content, "Hello");
+
+        let reply = EventBuilder::channel_msg_reply(channel_id, relay_url, &msg, "Hi")
+            .to_event(&keys)
+            .unwrap();
+        let parsed = ChannelMessage::try_from(&reply).unwrap();
+        assert_eq!(parsed.channel_id, channel_id);
+        assert_eq!(parsed.reply_to, Some(msg.id()));
+    }
+
+    #[test]
+    fn test_hide_and_mute() {
+        let keys = Keys::generate();
+        let message_id = EventId::all_zeros();
+
+        let hide = EventBuilder::hide_channel_msg(message_id, Some("Spam"))
+            .to_event(&keys)
+            .unwrap();
+        let hide = HideMessage::try_from(&hide).unwrap();
+        assert_eq!(hide.message_id, message_id);
+        assert_eq!(hide.reason.as_deref(), Some("Spam"));
+
+        let mute = EventBuilder::mute_channel_user(keys.public_key(), None::<String>)
+            .to_event(&keys)
+            .unwrap();
+        let mute = MuteUser::try_from(&mute).unwrap();
+        assert_eq!(mute.public_key, keys.public_key());
+        assert_eq!(mute.reason, None);
+    }
+}
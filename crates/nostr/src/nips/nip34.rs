@@ -0,0 +1,539 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP34
+//!
+//! git stuff
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/34.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::nips::nip01::{self, Coordinate};
+use crate::{
+    Alphabet, Event, EventId, Kind, Marker, PublicKey, SingleLetterTag, Tag, TagKind, UncheckedUrl,
+};
+
+const EUC: &str = "euc";
+
+/// NIP34 error
+#[derive(Debug)]
+pub enum Error {
+    /// NIP01 error
+    NIP01(nip01::Error),
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `d` tag identifier
+    MissingIdentifier,
+    /// Missing repository reference (`a` tag)
+    MissingRepository,
+    /// Missing root reference (`e` tag with `root` marker)
+    MissingRoot,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NIP01(e) => write!(f, "{e}"),
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingIdentifier => write!(f, "Missing identifier"),
+            Self::MissingRepository => write!(f, "Missing repository"),
+            Self::MissingRoot => write!(f, "Missing root"),
+        }
+    }
+}
+
+impl From<nip01::Error> for Error {
+    fn from(e: nip01::Error) -> Self {
+        Self::NIP01(e)
+    }
+}
+
+/// Repository announcement (kind `30617`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitRepositoryAnnouncement {
+    /// Repository ID (usually kebab-case short name)
+    pub id: String,
+    /// Human-readable project name
+    pub name: Option<String>,
+    /// Brief human-readable project description
+    pub description: Option<String>,
+    /// URLs for browsing the repository online
+    pub web: Vec<UncheckedUrl>,
+    /// URLs for git-cloning
+    pub clone: Vec<UncheckedUrl>,
+    /// Relays that this repository will monitor for patches and issues
+    pub relays: Vec<UncheckedUrl>,
+    /// Earliest unique commit ID (i.e. the root commit), to identify forks of the same repository
+    pub euc: Option<String>,
+    /// Other recognized maintainers
+    pub maintainers: Vec<PublicKey>,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+}
+
+impl GitRepositoryAnnouncement {
+    /// New repository announcement
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Coordinate of the announcement, to reference the repository
+    pub fn coordinate(&self, public_key: PublicKey) -> Coordinate {
+        Coordinate::new(Kind::GitRepoAnnouncement, public_key).identifier(&self.id)
+    }
+}
+
+impl From<GitRepositoryAnnouncement> for Vec<Tag> {
+    fn from(repo: GitRepositoryAnnouncement) -> Self {
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(repo.id)];
+
+        if let Some(name) = repo.name {
+            tags.push(Tag::Name(name));
+        }
+
+        if let Some(description) = repo.description {
+            tags.push(Tag::Description(description));
+        }
+
+        if !repo.web.is_empty() {
+            tags.push(custom("web", repo.web));
+        }
+
+        if !repo.clone.is_empty() {
+            tags.push(custom("clone", repo.clone));
+        }
+
+        if !repo.relays.is_empty() {
+            tags.push(Tag::Relays(repo.relays));
+        }
+
+        if let Some(euc) = repo.euc {
+            tags.push(euc_tag(euc));
+        }
+
+        if !repo.maintainers.is_empty() {
+            tags.push(custom(
+                "maintainers",
+                repo.maintainers.into_iter().map(|p| p.to_string()),
+            ));
+        }
+
+        tags.extend(repo.hashtags.into_iter().map(Tag::Hashtag));
+
+        tags
+    }
+}
+
+impl TryFrom<&Event> for GitRepositoryAnnouncement {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::GitRepoAnnouncement {
+            return Err(Error::WrongKind);
+        }
+
+        let id: &str = event.identifier().ok_or(Error::MissingIdentifier)?;
+        let mut repo = Self::new(id);
+
+        for tag in event.iter_tags().map(|t| t.as_vec()) {
+            let (kind, values) = match tag.split_first() {
+                Some((kind, values)) if !values.is_empty() => (kind.as_str(), values),
+                _ => continue,
+            };
+            match kind {
+                "name" => repo.name = Some(values[0].clone()),
+                "description" => repo.description = Some(values[0].clone()),
+                "web" => repo.web.extend(values.iter().map(UncheckedUrl::from)),
+                "clone" => repo.clone.extend(values.iter().map(UncheckedUrl::from)),
+                "relays" => repo.relays.extend(values.iter().map(UncheckedUrl::from)),
+                "r" if values.get(1).map(|m| m.as_str()) == Some(EUC) => {
+                    repo.euc = Some(values[0].clone())
+                }
+                "maintainers" => repo
+                    .maintainers
+                    .extend(values.iter().filter_map(|p| PublicKey::from_hex(p).ok())),
+                "t" => repo.hashtags.push(values[0].clone()),
+                _ => (),
+            }
+        }
+
+        Ok(repo)
+    }
+}
+
+/// Patch (kind `1617`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitPatch {
+    /// Repository
+    pub repository: Coordinate,
+    /// Earliest unique commit ID of the repository
+    pub euc: Option<String>,
+    /// `git format-patch` output
+    pub content: String,
+    /// Whether this is the first patch of a patch set
+    pub root: bool,
+    /// Whether this is the first patch of a revision of a previous patch set
+    pub root_revision: bool,
+    /// Commit ID
+    pub commit: Option<String>,
+    /// Parent commit ID
+    pub parent_commit: Option<String>,
+}
+
+impl GitPatch {
+    /// New patch
+    pub fn new<S>(repository: Coordinate, content: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            repository,
+            euc: None,
+            content: content.into(),
+            root: false,
+            root_revision: false,
+            commit: None,
+            parent_commit: None,
+        }
+    }
+
+    /// Tags of the patch event (content excluded)
+    pub fn to_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = repository_tags(&self.repository);
+
+        if let Some(euc) = &self.euc {
+            tags.push(Tag::Reference(euc.clone()));
+        }
+
+        if self.root {
+            tags.push(Tag::Hashtag(String::from("root")));
+        }
+
+        if self.root_revision {
+            tags.push(Tag::Hashtag(String::from("root-revision")));
+        }
+
+        if let Some(commit) = &self.commit {
+            tags.push(custom("commit", [commit]));
+        }
+
+        if let Some(parent_commit) = &self.parent_commit {
+            tags.push(custom("parent-commit", [parent_commit]));
+        }
+
+        tags
+    }
+}
+
+impl TryFrom<&Event> for GitPatch {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::GitPatch {
+            return Err(Error::WrongKind);
+        }
+
+        let mut patch = Self::new(repository(event)?, event.content());
+
+        for tag in event.iter_tags().map(|t| t.as_vec()) {
+            match tag.as_slice() {
+                [kind, commit, ..] if kind == "r" => patch.euc = Some(commit.clone()),
+                [kind, hashtag, ..] if kind == "t" => match hashtag.as_str() {
+                    "root" => patch.root = true,
+                    "root-revision" => patch.root_revision = true,
+                    _ => (),
+                },
+                [kind, commit, ..] if kind == "commit" => patch.commit = Some(commit.clone()),
+                [kind, commit, ..] if kind == "parent-commit" => {
+                    patch.parent_commit = Some(commit.clone())
+                }
+                _ => (),
+            }
+        }
+
+        Ok(patch)
+    }
+}
+
+/// Issue (kind `1621`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitIssue {
+    /// Repository
+    pub repository: Coordinate,
+    /// Subject
+    pub subject: Option<String>,
+    /// Markdown content
+    pub content: String,
+    /// Labels
+    pub hashtags: Vec<String>,
+}
+
+impl GitIssue {
+    /// New issue
+    pub fn new<S>(repository: Coordinate, content: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            repository,
+            subject: None,
+            content: content.into(),
+            hashtags: Vec::new(),
+        }
+    }
+
+    /// Tags of the issue event (content excluded)
+    pub fn to_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = repository_tags(&self.repository);
+
+        if let Some(subject) = &self.subject {
+            tags.push(Tag::Subject(subject.clone()));
+        }
+
+        tags.extend(self.hashtags.iter().cloned().map(Tag::Hashtag));
+
+        tags
+    }
+}
+
+impl TryFrom<&Event> for GitIssue {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::GitIssue {
+            return Err(Error::WrongKind);
+        }
+
+        let mut issue = Self::new(repository(event)?, event.content());
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Subject(subject) => issue.subject = Some(subject.clone()),
+                Tag::Hashtag(hashtag) => issue.hashtags.push(hashtag.clone()),
+                _ => (),
+            }
+        }
+
+        Ok(issue)
+    }
+}
+
+/// Status of a patch or issue (kinds `1630` to `1633`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GitStatus {
+    /// Open
+    Open,
+    /// Applied / merged for patches, resolved for issues
+    Applied,
+    /// Closed
+    Closed,
+    /// Draft
+    Draft,
+}
+
+impl GitStatus {
+    /// Status event kind
+    pub fn kind(&self) -> Kind {
+        match self {
+            Self::Open => Kind::GitStatusOpen,
+            Self::Applied => Kind::GitStatusApplied,
+            Self::Closed => Kind::GitStatusClosed,
+            Self::Draft => Kind::GitStatusDraft,
+        }
+    }
+
+    /// Status from the event kind
+    pub fn from_kind(kind: Kind) -> Option<Self> {
+        match kind {
+            Kind::GitStatusOpen => Some(Self::Open),
+            Kind::GitStatusApplied => Some(Self::Applied),
+            Kind::GitStatusClosed => Some(Self::Closed),
+            Kind::GitStatusDraft => Some(Self::Draft),
+            _ => None,
+        }
+    }
+}
+
+/// Status update of a patch or issue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitStatusUpdate {
+    /// Status
+    pub status: GitStatus,
+    /// Root patch or issue
+    pub root: EventId,
+    /// Repository
+    pub repository: Option<Coordinate>,
+    /// Merge commit ID (applied patches only)
+    pub merge_commit: Option<String>,
+    /// IDs of the commits applied (applied patches only)
+    pub applied_as_commits: Vec<String>,
+    /// Markdown content
+    pub content: String,
+}
+
+impl GitStatusUpdate {
+    /// New status update
+    pub fn new(status: GitStatus, root: EventId) -> Self {
+        Self {
+            status,
+            root,
+            repository: None,
+            merge_commit: None,
+            applied_as_commits: Vec::new(),
+            content: String::new(),
+        }
+    }
+
+    /// Tags of the status event (content excluded)
+    pub fn to_tags(&self) -> Vec<Tag> {
+        let mut tags: Vec<Tag> = vec![Tag::Event {
+            event_id: self.root,
+            relay_url: None,
+            marker: Some(Marker::Root),
+        }];
+
+        if let Some(repository) = &self.repository {
+            tags.extend(repository_tags(repository));
+        }
+
+        if let Some(merge_commit) = &self.merge_commit {
+            tags.push(custom("merge-commit", [merge_commit]));
+        }
+
+        if !self.applied_as_commits.is_empty() {
+            tags.push(custom("applied-as-commits", &self.applied_as_commits));
+        }
+
+        tags
+    }
+}
+
+impl TryFrom<&Event> for GitStatusUpdate {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        let status: GitStatus = GitStatus::from_kind(event.kind()).ok_or(Error::WrongKind)?;
+        let root: EventId = event
+            .iter_tags()
+            .find_map(|t| match t {
+                Tag::Event {
+                    event_id,
+                    marker: Some(Marker::Root),
+                    ..
+                } => Some(*event_id),
+                _ => None,
+            })
+            .ok_or(Error::MissingRoot)?;
+
+        let mut update = Self::new(status, root);
+        update.repository = repository(event).ok();
+        update.content = event.content().to_string();
+
+        for tag in event.iter_tags().map(|t| t.as_vec()) {
+            match tag.as_slice() {
+                [kind, commit, ..] if kind == "merge-commit" => {
+                    update.merge_commit = Some(commit.clone())
+                }
+                [kind, commits @ ..] if kind == "applied-as-commits" => {
+                    update.applied_as_commits.extend(commits.iter().cloned())
+                }
+                _ => (),
+            }
+        }
+
+        Ok(update)
+    }
+}
+
+fn custom<I, S>(kind: &str, values: I) -> Tag
+where
+    I: IntoIterator<Item = S>,
+    S: ToString,
+{
+    Tag::custom(
+        TagKind::Custom(kind.to_string()),
+        values.into_iter().map(|v| v.to_string()),
+    )
+}
+
+fn euc_tag(commit: String) -> Tag {
+    Tag::custom(
+        TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::R)),
+        [commit, EUC.to_string()],
+    )
+}
+
+/// `a` tag of the repository and `p` tag of its owner
+fn repository_tags(repository: &Coordinate) -> Vec<Tag> {
+    vec![
+        Tag::A {
+            coordinate: repository.clone(),
+            relay_url: None,
+        },
+        Tag::public_key(repository.public_key),
+    ]
+}
+
+fn repository(event: &Event) -> Result<Coordinate, Error> {
+    event
+        .iter_tags()
+        .find_map(|t| match t {
+            Tag::A { coordinate, .. } if coordinate.kind == Kind::GitRepoAnnouncement => {
+                Some(coordinate.clone())
+            }
+            _ => None,
+        })
+        .ok_or(Error::MissingRepository)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_repository_announcement() {
+        let keys = Keys::generate();
+        let maintainer = Keys::generate().public_key();
+
+        let mut repo = GitRepositoryAnnouncement::new("rust-nostr");
+        repo.name = Some(String::from("Rust Nostr"));
+        repo.clone = vec![UncheckedUrl::from(
+            "https://github.com/rust-nostr/nostr.git",
+        )];
+        repo.relays = vec![UncheckedUrl::from("wss://relay.damus.io")];
+        repo.euc = Some(String::from("b5dd2a3b8b7ad8e6a0e1a7c5e2b7c4d1e6f3a2b1"));
+        repo.maintainers = vec![maintainer];
+
+        let event = EventBuilder::git_repository_announcement(repo.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(GitRepositoryAnnouncement::try_from(&event).unwrap(), repo);
+
+        let mut patch = GitPatch::new(repo.coordinate(keys.public_key()), "From 0000...");
+        patch.root = true;
+        patch.commit = Some(String::from("abcd"));
+        let event = EventBuilder::git_patch(&patch).to_event(&keys).unwrap();
+        assert_eq!(GitPatch::try_from(&event).unwrap(), patch);
+
+        let mut update = GitStatusUpdate::new(GitStatus::Applied, event.id());
+        update.repository = Some(repo.coordinate(keys.public_key()));
+        update.applied_as_commits = vec![String::from("abcd"), String::from("ef01")];
+        let status = EventBuilder::git_status(&update).to_event(&keys).unwrap();
+        assert_eq!(status.kind(), Kind::GitStatusApplied);
+        assert_eq!(GitStatusUpdate::try_from(&status).unwrap(), update);
+    }
+}
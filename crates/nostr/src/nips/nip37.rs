@@ -0,0 +1,151 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP37
+//!
+//! Draft events
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/37.md>
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Event, EventBuilder, JsonUtil, Kind, Tag};
+
+/// NIP37 error
+#[derive(Debug)]
+pub enum Error {
+    /// JSON error
+    Json(serde_json::Error),
+    /// Not a draft event
+    WrongKind,
+    /// Missing `d` tag
+    MissingIdentifier,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "{e}"),
+            Self::WrongKind => write!(f, "wrong event kind"),
+            Self::MissingIdentifier => write!(f, "missing identifier"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Unsigned draft of an event
+///
+/// Stored as the encrypted content of a draft event (kind `31234`),
+/// replaced at every save since the `d` tag is the draft identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Draft {
+    /// Draft identifier
+    #[serde(skip)]
+    pub identifier: String,
+    /// Kind of the drafted event
+    pub kind: Kind,
+    /// Content
+    pub content: String,
+    /// Tags
+    pub tags: Vec<Tag>,
+}
+
+impl Draft {
+    /// New draft
+    pub fn new<I, S>(identifier: I, kind: Kind, content: S) -> Self
+    where
+        I: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            identifier: identifier.into(),
+            kind,
+            content: content.into(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Set tags
+    pub fn tags<I>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = Tag>,
+    {
+        self.tags = tags.into_iter().collect();
+        self
+    }
+
+    /// Deserialize the decrypted content of a draft event
+    pub fn from_payload<I, S>(identifier: I, payload: S) -> Result<Self, Error>
+    where
+        I: Into<String>,
+        S: AsRef<str>,
+    {
+        let mut draft: Self = Self::from_json(payload.as_ref())?;
+        draft.identifier = identifier.into();
+        Ok(draft)
+    }
+
+    /// Serialize the content of a draft event (to encrypt)
+    #[inline]
+    pub fn payload(&self) -> String {
+        self.as_json()
+    }
+
+    /// Draft identifier of a draft event
+    pub fn identifier_of(event: &Event) -> Result<&str, Error> {
+        if event.kind() != Kind::Draft {
+            return Err(Error::WrongKind);
+        }
+        event.identifier().ok_or(Error::MissingIdentifier)
+    }
+
+    /// Builder of the drafted event, to publish it
+    #[inline]
+    pub fn to_event_builder(&self) -> EventBuilder {
+        EventBuilder::new(self.kind, &self.content, self.tags.clone())
+    }
+}
+
+impl JsonUtil for Draft {
+    type Err = Error;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keys, PublicKey};
+
+    #[test]
+    fn test_draft_payload() {
+        let public_key =
+            PublicKey::from_hex("68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272")
+                .unwrap();
+        let draft = Draft::new("note-1", Kind::TextNote, "Work in progress")
+            .tags([Tag::public_key(public_key)]);
+
+        let parsed = Draft::from_payload("note-1", draft.payload()).unwrap();
+        assert_eq!(parsed, draft);
+
+        let keys = Keys::generate();
+        let event = EventBuilder::event_draft(&draft, "encrypted")
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(Draft::identifier_of(&event).unwrap(), "note-1");
+        assert!(event.iter_tags().any(|t| t == &Tag::Kind(Kind::TextNote)));
+
+        let note = draft.to_event_builder().to_event(&keys).unwrap();
+        assert_eq!(note.kind(), Kind::TextNote);
+        assert!(matches!(Draft::identifier_of(&note), Err(Error::WrongKind)));
+    }
+}
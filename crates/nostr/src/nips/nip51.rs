@@ -7,11 +7,108 @@
 
 use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt;
 
 use super::nip01::Coordinate;
+#[cfg(all(feature = "std", feature = "nip44"))]
+use super::nip44::{self, Version};
+#[cfg(all(feature = "std", feature = "nip44"))]
+use crate::{key, Keys};
 use crate::{EventId, PublicKey, Tag, UncheckedUrl, Url};
 
+/// NIP51 error
+#[derive(Debug)]
+pub enum Error {
+    /// JSON error
+    Json(serde_json::Error),
+    /// Keys error
+    #[cfg(all(feature = "std", feature = "nip44"))]
+    Keys(key::Error),
+    /// NIP44 error
+    #[cfg(all(feature = "std", feature = "nip44"))]
+    NIP44(nip44::Error),
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(e) => write!(f, "Json: {e}"),
+            #[cfg(all(feature = "std", feature = "nip44"))]
+            Self::Keys(e) => write!(f, "Keys: {e}"),
+            #[cfg(all(feature = "std", feature = "nip44"))]
+            Self::NIP44(e) => write!(f, "NIP44: {e}"),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "nip44"))]
+impl From<key::Error> for Error {
+    fn from(e: key::Error) -> Self {
+        Self::Keys(e)
+    }
+}
+
+#[cfg(all(feature = "std", feature = "nip44"))]
+impl From<nip44::Error> for Error {
+    fn from(e: nip44::Error) -> Self {
+        Self::NIP44(e)
+    }
+}
+
+/// Serialize the private items of a list
+///
+/// The output must be encrypted to self and used as event content.
+pub fn private_items_to_json<I>(tags: I) -> Result<String, Error>
+where
+    I: IntoIterator<Item = Tag>,
+{
+    let tags: Vec<Tag> = tags.into_iter().collect();
+    Ok(serde_json::to_string(&tags)?)
+}
+
+/// Deserialize the (already decrypted) private items of a list
+pub fn private_items_from_json<T>(json: T) -> Result<Vec<Tag>, Error>
+where
+    T: AsRef<[u8]>,
+{
+    Ok(serde_json::from_slice(json.as_ref())?)
+}
+
+/// Encrypt the private items of a list to self
+#[cfg(all(feature = "std", feature = "nip44"))]
+pub fn encrypt_private_items<I>(keys: &Keys, tags: I) -> Result<String, Error>
+where
+    I: IntoIterator<Item = Tag>,
+{
+    Ok(nip44::encrypt(
+        keys.secret_key()?,
+        &keys.public_key(),
+        private_items_to_json(tags)?,
+        Version::default(),
+    )?)
+}
+
+/// Decrypt the private items of a list (event content)
+#[cfg(all(feature = "std", feature = "nip44"))]
+pub fn decrypt_private_items<T>(keys: &Keys, content: T) -> Result<Vec<Tag>, Error>
+where
+    T: AsRef<[u8]>,
+{
+    let json: String = nip44::decrypt(keys.secret_key()?, &keys.public_key(), content)?;
+    private_items_from_json(json)
+}
+
 /// Things the user doesn't want to see in their feeds
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct MuteList {
     /// Public Keys
     pub public_keys: Vec<PublicKey>,
@@ -44,7 +141,130 @@ impl From<MuteList> for Vec<Tag> {
     }
 }
 
+impl MuteList {
+    /// Extract [`MuteList`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut list: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::PublicKey {
+                    public_key,
+                    uppercase: false,
+                    ..
+                } => list.public_keys.push(*public_key),
+                Tag::Hashtag(hashtag) => list.hashtags.push(hashtag.clone()),
+                Tag::Event { event_id, .. } => list.event_ids.push(*event_id),
+                Tag::Word(word) => list.words.push(word.clone()),
+                _ => (),
+            }
+        }
+        list
+    }
+
+    /// Check if list is empty
+    pub fn is_empty(&self) -> bool {
+        self.public_keys.is_empty()
+            && self.hashtags.is_empty()
+            && self.event_ids.is_empty()
+            && self.words.is_empty()
+    }
+
+    /// Merge another [`MuteList`] into this one, skipping duplicates
+    pub fn merge(&mut self, other: Self) {
+        merge_unique(&mut self.public_keys, other.public_keys);
+        merge_unique(&mut self.hashtags, other.hashtags);
+        merge_unique(&mut self.event_ids, other.event_ids);
+        merge_unique(&mut self.words, other.words);
+    }
+}
+
+/// Events the user intends to showcase in their profile page
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinList {
+    /// Event IDs
+    pub event_ids: Vec<EventId>,
+}
+
+impl From<PinList> for Vec<Tag> {
+    fn from(PinList { event_ids }: PinList) -> Self {
+        event_ids.into_iter().map(Tag::event).collect()
+    }
+}
+
+impl PinList {
+    /// Extract [`PinList`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut list: Self = Self::default();
+        for tag in tags.into_iter() {
+            if let Tag::Event { event_id, .. } = tag {
+                list.event_ids.push(*event_id);
+            }
+        }
+        list
+    }
+}
+
+/// Categorized group of users a client may choose to check out in different circumstances
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FollowSet {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Public Keys
+    pub public_keys: Vec<PublicKey>,
+}
+
+impl From<FollowSet> for Vec<Tag> {
+    fn from(
+        FollowSet {
+            identifier,
+            public_keys,
+        }: FollowSet,
+    ) -> Self {
+        let mut tags = Vec::with_capacity(1 + public_keys.len());
+
+        tags.push(Tag::Identifier(identifier));
+        tags.extend(public_keys.into_iter().map(Tag::public_key));
+
+        tags
+    }
+}
+
+impl FollowSet {
+    /// Extract [`FollowSet`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut set: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::Identifier(identifier) => set.identifier = identifier.clone(),
+                Tag::PublicKey {
+                    public_key,
+                    uppercase: false,
+                    ..
+                } => set.public_keys.push(*public_key),
+                _ => (),
+            }
+        }
+        set
+    }
+}
+
 /// Uncategorized, "global" list of things a user wants to save
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Bookmarks {
     /// Event IDs
     pub event_ids: Vec<EventId>,
@@ -77,7 +297,72 @@ impl From<Bookmarks> for Vec<Tag> {
     }
 }
 
+impl Bookmarks {
+    /// Extract [`Bookmarks`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut list: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::Event { event_id, .. } => list.event_ids.push(*event_id),
+                Tag::A { coordinate, .. } => list.coordinate.push(coordinate.clone()),
+                Tag::Hashtag(hashtag) => list.hashtags.push(hashtag.clone()),
+                Tag::Url(url) => list.urls.push(url.clone()),
+                _ => (),
+            }
+        }
+        list
+    }
+}
+
+/// Categorized group of things a user wants to save
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookmarkSet {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Bookmarks
+    pub bookmarks: Bookmarks,
+}
+
+impl From<BookmarkSet> for Vec<Tag> {
+    fn from(
+        BookmarkSet {
+            identifier,
+            bookmarks,
+        }: BookmarkSet,
+    ) -> Self {
+        let bookmarks: Vec<Tag> = bookmarks.into();
+        let mut tags = Vec::with_capacity(1 + bookmarks.len());
+
+        tags.push(Tag::Identifier(identifier));
+        tags.extend(bookmarks);
+
+        tags
+    }
+}
+
+impl BookmarkSet {
+    /// Extract [`BookmarkSet`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let tags: Vec<&Tag> = tags.into_iter().collect();
+        Self {
+            identifier: extract_identifier(tags.iter().copied()),
+            bookmarks: Bookmarks::from_tags(tags),
+        }
+    }
+}
+
 /// Topics a user may be interested in and pointers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Interests {
     /// Hashtags
     pub hashtags: Vec<String>,
@@ -101,7 +386,73 @@ impl From<Interests> for Vec<Tag> {
     }
 }
 
+impl Interests {
+    /// Extract [`Interests`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut list: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::Hashtag(hashtag) => list.hashtags.push(hashtag.clone()),
+                Tag::A { coordinate, .. } => list.coordinate.push(coordinate.clone()),
+                _ => (),
+            }
+        }
+        list
+    }
+}
+
+/// Interest topics represented by a bunch of hashtags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterestSet {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Hashtags
+    pub hashtags: Vec<String>,
+}
+
+impl From<InterestSet> for Vec<Tag> {
+    fn from(
+        InterestSet {
+            identifier,
+            hashtags,
+        }: InterestSet,
+    ) -> Self {
+        let mut tags = Vec::with_capacity(1 + hashtags.len());
+
+        tags.push(Tag::Identifier(identifier));
+        tags.extend(hashtags.into_iter().map(Tag::Hashtag));
+
+        tags
+    }
+}
+
+impl InterestSet {
+    /// Extract [`InterestSet`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut set: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::Identifier(identifier) => set.identifier = identifier.clone(),
+                Tag::Hashtag(hashtag) => set.hashtags.push(hashtag.clone()),
+                _ => (),
+            }
+        }
+        set
+    }
+}
+
 /// User preferred emojis and pointers to emoji sets
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Emojis {
     /// Emojis
     pub emojis: Vec<(String, UncheckedUrl)>,
@@ -124,7 +475,72 @@ impl From<Emojis> for Vec<Tag> {
     }
 }
 
+impl Emojis {
+    /// Extract [`Emojis`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut list: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::Emoji { shortcode, url } => list.emojis.push((shortcode.clone(), url.clone())),
+                Tag::A { coordinate, .. } => list.coordinate.push(coordinate.clone()),
+                _ => (),
+            }
+        }
+        list
+    }
+}
+
+/// Categorized emoji groups
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmojiSet {
+    /// Identifier (`d` tag)
+    pub identifier: String,
+    /// Emojis
+    pub emojis: Vec<(String, UncheckedUrl)>,
+}
+
+impl From<EmojiSet> for Vec<Tag> {
+    fn from(EmojiSet { identifier, emojis }: EmojiSet) -> Self {
+        let mut tags = Vec::with_capacity(1 + emojis.len());
+
+        tags.push(Tag::Identifier(identifier));
+        tags.extend(
+            emojis
+                .into_iter()
+                .map(|(s, url)| Tag::Emoji { shortcode: s, url }),
+        );
+
+        tags
+    }
+}
+
+impl EmojiSet {
+    /// Extract [`EmojiSet`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut set: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::Identifier(identifier) => set.identifier = identifier.clone(),
+                Tag::Emoji { shortcode, url } => set.emojis.push((shortcode.clone(), url.clone())),
+                _ => (),
+            }
+        }
+        set
+    }
+}
+
 /// Groups of articles picked by users as interesting and/or belonging to the same category
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ArticlesCuration {
     /// Coordinates
     pub coordinate: Vec<Coordinate>,
@@ -147,3 +563,156 @@ impl From<ArticlesCuration> for Vec<Tag> {
         tags
     }
 }
+
+impl ArticlesCuration {
+    /// Extract [`ArticlesCuration`] from tags
+    ///
+    /// Unrelated tags are ignored.
+    pub fn from_tags<'a, I>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Tag>,
+    {
+        let mut list: Self = Self::default();
+        for tag in tags.into_iter() {
+            match tag {
+                Tag::A { coordinate, .. } => list.coordinate.push(coordinate.clone()),
+                Tag::Event { event_id, .. } => list.event_ids.push(*event_id),
+                _ => (),
+            }
+        }
+        list
+    }
+}
+
+fn extract_identifier<'a, I>(tags: I) -> String
+where
+    I: IntoIterator<Item = &'a Tag>,
+{
+    tags.into_iter()
+        .find_map(|tag| match tag {
+            Tag::Identifier(identifier) => Some(identifier.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn merge_unique<T>(vec: &mut Vec<T>, other: Vec<T>)
+where
+    T: PartialEq,
+{
+    for item in other.into_iter() {
+        if !vec.contains(&item) {
+            vec.push(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mute_list_tags_round_trip() {
+        let public_key =
+            PublicKey::from_hex("68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272")
+                .unwrap();
+        let list = MuteList {
+            public_keys: vec![public_key],
+            hashtags: vec!["nsfw".to_string()],
+            event_ids: Vec::new(),
+            words: vec!["gm".to_string()],
+        };
+
+        let tags: Vec<Tag> = list.clone().into();
+        assert_eq!(MuteList::from_tags(tags.iter()), list);
+    }
+
+    #[test]
+    fn test_mute_list_merge() {
+        let mut list = MuteList {
+            hashtags: vec!["nsfw".to_string()],
+            ..Default::default()
+        };
+        list.merge(MuteList {
+            hashtags: vec!["nsfw".to_string(), "spam".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(list.hashtags, vec!["nsfw".to_string(), "spam".to_string()]);
+    }
+
+    #[test]
+    fn test_pin_list_tags_round_trip() {
+        let event_id =
+            EventId::from_hex("7469af3be8c8e06e1b50ef1caceea30d584e3d4c2e0151d7e4dc12b4ba7f3d3b")
+                .unwrap();
+        let list = PinList {
+            event_ids: vec![event_id],
+        };
+
+        let tags: Vec<Tag> = list.clone().into();
+        assert_eq!(PinList::from_tags(tags.iter()), list);
+    }
+
+    #[test]
+    fn test_follow_set_tags_round_trip() {
+        let public_key =
+            PublicKey::from_hex("68d81165918100b7da43fc28f7d1fc12554466e1115886b9e7bb326f65ec4272")
+                .unwrap();
+        let set = FollowSet {
+            identifier: "devs".to_string(),
+            public_keys: vec![public_key],
+        };
+
+        let tags: Vec<Tag> = set.clone().into();
+        assert_eq!(tags[0], Tag::Identifier("devs".to_string()));
+        assert_eq!(FollowSet::from_tags(tags.iter()), set);
+    }
+
+    #[test]
+    fn test_sets_tags_round_trip() {
+        let event_id =
+            EventId::from_hex("7469af3be8c8e06e1b50ef1caceea30d584e3d4c2e0151d7e4dc12b4ba7f3d3b")
+                .unwrap();
+        let bookmark_set = BookmarkSet {
+            identifier: "rust".to_string(),
+            bookmarks: Bookmarks {
+                event_ids: vec![event_id],
+                hashtags: vec!["rust".to_string()],
+                ..Default::default()
+            },
+        };
+        let tags: Vec<Tag> = bookmark_set.clone().into();
+        assert_eq!(tags[0], Tag::Identifier("rust".to_string()));
+        assert_eq!(BookmarkSet::from_tags(tags.iter()), bookmark_set);
+
+        let interest_set = InterestSet {
+            identifier: "dev".to_string(),
+            hashtags: vec!["nostr".to_string(), "rust".to_string()],
+        };
+        let tags: Vec<Tag> = interest_set.clone().into();
+        assert_eq!(InterestSet::from_tags(tags.iter()), interest_set);
+
+        let emoji_set = EmojiSet {
+            identifier: "cats".to_string(),
+            emojis: vec![(
+                "cat".to_string(),
+                UncheckedUrl::from("https://example.com/cat.png"),
+            )],
+        };
+        let tags: Vec<Tag> = emoji_set.clone().into();
+        assert_eq!(EmojiSet::from_tags(tags.iter()), emoji_set);
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "nip44"))]
+    fn test_private_items() {
+        let keys = Keys::generate();
+        let tags = vec![
+            Tag::Hashtag("nostr".to_string()),
+            Tag::Word("gm".to_string()),
+        ];
+
+        let content = encrypt_private_items(&keys, tags.clone()).unwrap();
+        assert_eq!(decrypt_private_items(&keys, content).unwrap(), tags);
+    }
+}
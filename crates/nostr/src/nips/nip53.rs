@@ -13,19 +13,32 @@ use core::str::FromStr;
 
 use bitcoin::secp256k1::schnorr::Signature;
 
-use crate::{ImageDimensions, PublicKey, Tag, Timestamp, UncheckedUrl};
+use crate::nips::nip01::Coordinate;
+use crate::{Event, EventId, ImageDimensions, Kind, PublicKey, Tag, Timestamp, UncheckedUrl};
 
 /// NIP53 Error
 #[derive(Debug)]
 pub enum Error {
     /// Unknown [`LiveEventMarker`]
     UnknownLiveEventMarker(String),
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `d` tag identifier
+    MissingIdentifier,
+    /// Missing live event reference (`a` tag)
+    MissingLiveEvent,
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::UnknownLiveEventMarker(u) => write!(f, "Unknown live event marker: {u}"),
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingIdentifier => write!(f, "Missing identifier"),
+            Self::MissingLiveEvent => write!(f, "Missing live event"),
         }
     }
 }
@@ -104,7 +117,7 @@ where
 }
 
 /// Live Event Host
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LiveEventHost {
     /// Host public key
     pub public_key: PublicKey,
@@ -115,6 +128,7 @@ pub struct LiveEventHost {
 }
 
 /// Live Event
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct LiveEvent {
     /// Unique event ID
     pub id: String,
@@ -150,6 +164,262 @@ pub struct LiveEvent {
     pub participants: Vec<(PublicKey, Option<UncheckedUrl>)>,
 }
 
+impl LiveEvent {
+    /// New live event
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            title: None,
+            summary: None,
+            image: None,
+            hashtags: Vec::new(),
+            streaming: None,
+            recording: None,
+            starts: None,
+            ends: None,
+            status: None,
+            current_participants: None,
+            total_participants: None,
+            relays: Vec::new(),
+            host: None,
+            speakers: Vec::new(),
+            participants: Vec::new(),
+        }
+    }
+
+    /// Set title
+    pub fn title<S>(self, title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    /// Set summary
+    pub fn summary<S>(self, summary: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            summary: Some(summary.into()),
+            ..self
+        }
+    }
+
+    /// Set image
+    pub fn image(self, url: UncheckedUrl, dimensions: Option<ImageDimensions>) -> Self {
+        Self {
+            image: Some((url, dimensions)),
+            ..self
+        }
+    }
+
+    /// Add hashtag
+    pub fn hashtag<S>(mut self, hashtag: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.hashtags.push(hashtag.into());
+        self
+    }
+
+    /// Set streaming URL
+    pub fn streaming(self, url: UncheckedUrl) -> Self {
+        Self {
+            streaming: Some(url),
+            ..self
+        }
+    }
+
+    /// Set recording URL
+    pub fn recording(self, url: UncheckedUrl) -> Self {
+        Self {
+            recording: Some(url),
+            ..self
+        }
+    }
+
+    /// Set start time
+    pub fn starts(self, starts: Timestamp) -> Self {
+        Self {
+            starts: Some(starts),
+            ..self
+        }
+    }
+
+    /// Set end time
+    pub fn ends(self, ends: Timestamp) -> Self {
+        Self {
+            ends: Some(ends),
+            ..self
+        }
+    }
+
+    /// Set status
+    pub fn status(self, status: LiveEventStatus) -> Self {
+        Self {
+            status: Some(status),
+            ..self
+        }
+    }
+
+    /// Set current participants
+    pub fn current_participants(self, num: u64) -> Self {
+        Self {
+            current_participants: Some(num),
+            ..self
+        }
+    }
+
+    /// Set total participants
+    pub fn total_participants(self, num: u64) -> Self {
+        Self {
+            total_participants: Some(num),
+            ..self
+        }
+    }
+
+    /// Set relays
+    pub fn relays<I>(self, relays: I) -> Self
+    where
+        I: IntoIterator<Item = UncheckedUrl>,
+    {
+        Self {
+            relays: relays.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Set host
+    pub fn host(self, host: LiveEventHost) -> Self {
+        Self {
+            host: Some(host),
+            ..self
+        }
+    }
+
+    /// Add speaker
+    pub fn speaker(mut self, public_key: PublicKey, relay_url: Option<UncheckedUrl>) -> Self {
+        self.speakers.push((public_key, relay_url));
+        self
+    }
+
+    /// Add participant
+    pub fn participant(mut self, public_key: PublicKey, relay_url: Option<UncheckedUrl>) -> Self {
+        self.participants.push((public_key, relay_url));
+        self
+    }
+}
+
+impl TryFrom<&Event> for LiveEvent {
+    type Error = Error;
+
+    /// Parse live event (kind `30311`)
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::LiveEvent {
+            return Err(Error::WrongKind);
+        }
+
+        let id: &str = event.identifier().ok_or(Error::MissingIdentifier)?;
+        let mut live_event = Self::new(id);
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Title(title) => live_event.title = Some(title.clone()),
+                Tag::Summary(summary) => live_event.summary = Some(summary.clone()),
+                Tag::Image(url, dim) => live_event.image = Some((url.clone(), *dim)),
+                Tag::Hashtag(hashtag) => live_event.hashtags.push(hashtag.clone()),
+                Tag::Streaming(url) => live_event.streaming = Some(url.clone()),
+                Tag::Recording(url) => live_event.recording = Some(url.clone()),
+                Tag::Starts(starts) => live_event.starts = Some(*starts),
+                Tag::Ends(ends) => live_event.ends = Some(*ends),
+                Tag::LiveEventStatus(status) => live_event.status = Some(status.clone()),
+                Tag::CurrentParticipants(num) => live_event.current_participants = Some(*num),
+                Tag::TotalParticipants(num) => live_event.total_participants = Some(*num),
+                Tag::Relays(relays) => live_event.relays.extend(relays.iter().cloned()),
+                Tag::PubKeyLiveEvent {
+                    public_key,
+                    relay_url,
+                    marker,
+                    proof,
+                } => match marker {
+                    LiveEventMarker::Host => {
+                        live_event.host = Some(LiveEventHost {
+                            public_key: *public_key,
+                            relay_url: relay_url.clone(),
+                            proof: *proof,
+                        })
+                    }
+                    LiveEventMarker::Speaker => {
+                        live_event.speakers.push((*public_key, relay_url.clone()))
+                    }
+                    LiveEventMarker::Participant => live_event
+                        .participants
+                        .push((*public_key, relay_url.clone())),
+                },
+                _ => (),
+            }
+        }
+
+        Ok(live_event)
+    }
+}
+
+/// Live chat message (kind `1311`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveEventMessage {
+    /// Message ID
+    pub id: EventId,
+    /// Author
+    pub author: PublicKey,
+    /// Live event coordinate
+    pub live_event: Coordinate,
+    /// Relay hint of the live event
+    pub relay_url: Option<UncheckedUrl>,
+    /// Message
+    pub content: String,
+    /// Creation time
+    pub created_at: Timestamp,
+}
+
+impl TryFrom<&Event> for LiveEventMessage {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::LiveEventMessage {
+            return Err(Error::WrongKind);
+        }
+
+        let (live_event, relay_url) = event
+            .iter_tags()
+            .find_map(|tag| match tag {
+                Tag::A {
+                    coordinate,
+                    relay_url,
+                } if coordinate.kind == Kind::LiveEvent => {
+                    Some((coordinate.clone(), relay_url.clone()))
+                }
+                _ => None,
+            })
+            .ok_or(Error::MissingLiveEvent)?;
+
+        Ok(Self {
+            id: event.id(),
+            author: event.author(),
+            live_event,
+            relay_url,
+            content: event.content().to_string(),
+            created_at: event.created_at(),
+        })
+    }
+}
+
 impl From<LiveEvent> for Vec<Tag> {
     fn from(live_event: LiveEvent) -> Self {
         let mut tags = Vec::new();
@@ -258,3 +528,52 @@ impl From<LiveEvent> for Vec<Tag> {
         tags
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_live_event() {
+        let host = Keys::generate();
+        let speaker = Keys::generate();
+        let live_event = LiveEvent::new("stream")
+            .title("Nostr dev call")
+            .streaming(UncheckedUrl::from("https://example.com/stream.m3u8"))
+            .status(LiveEventStatus::Live)
+            .starts(Timestamp::from(1_700_000_000))
+            .current_participants(42)
+            .hashtag("nostr")
+            .relays([UncheckedUrl::from("wss://relay.damus.io")])
+            .host(LiveEventHost {
+                public_key: host.public_key(),
+                relay_url: None,
+                proof: None,
+            })
+            .speaker(speaker.public_key(), None);
+
+        let event = EventBuilder::live_event(live_event.clone())
+            .to_event(&host)
+            .unwrap();
+        assert_eq!(LiveEvent::try_from(&event).unwrap(), live_event);
+
+        let msg =
+            EventBuilder::live_event_msg("stream", host.public_key(), "Hello!", None, Vec::new())
+                .to_event(&speaker)
+                .unwrap();
+        let msg = LiveEventMessage::try_from(&msg).unwrap();
+        assert_eq!(msg.author, speaker.public_key());
+        assert_eq!(msg.live_event.public_key, host.public_key());
+        assert_eq!(msg.live_event.identifier, "stream");
+        assert_eq!(msg.content, "Hello!");
+
+        let note = EventBuilder::text_note("Hello!", [])
+            .to_event(&speaker)
+            .unwrap();
+        assert!(matches!(
+            LiveEvent::try_from(&note).unwrap_err(),
+            Error::WrongKind
+        ));
+    }
+}
@@ -6,10 +6,15 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/58.md>
 
+use alloc::string::String;
 use alloc::vec::Vec;
 use core::fmt;
 
-use crate::{Event, Kind, PublicKey, Tag, UncheckedUrl};
+use super::nip01::Coordinate;
+use crate::{Event, EventId, ImageDimensions, Kind, PublicKey, Tag, UncheckedUrl};
+
+/// Profile badges `d` tag identifier
+pub const PROFILE_BADGES_IDENTIFIER: &str = "profile_badges";
 
 #[derive(Debug)]
 /// [`BadgeAward`](crate::event::kind::Kind#variant.BadgeAward) error
@@ -66,3 +71,229 @@ pub(crate) fn extract_awarded_public_key(
         _ => None,
     })
 }
+
+/// Badge definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadgeDefinition {
+    /// Coordinate (issuer and badge identifier)
+    pub coordinate: Coordinate,
+    /// Name
+    pub name: Option<String>,
+    /// Description
+    pub description: Option<String>,
+    /// Image
+    pub image: Option<(UncheckedUrl, Option<ImageDimensions>)>,
+    /// Thumbnails
+    pub thumbnails: Vec<(UncheckedUrl, Option<ImageDimensions>)>,
+}
+
+impl TryFrom<&Event> for BadgeDefinition {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::BadgeDefinition {
+            return Err(Error::InvalidKind);
+        }
+
+        let identifier: &str = event.identifier().ok_or(Error::IdentifierTagNotFound)?;
+        let mut definition = Self {
+            coordinate: Coordinate::new(Kind::BadgeDefinition, event.author())
+                .identifier(identifier),
+            name: None,
+            description: None,
+            image: None,
+            thumbnails: Vec::new(),
+        };
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Name(name) => definition.name = Some(name.clone()),
+                Tag::Description(description) => definition.description = Some(description.clone()),
+                Tag::Image(url, dimensions) => definition.image = Some((url.clone(), *dimensions)),
+                Tag::Thumb(url, dimensions) => {
+                    definition.thumbnails.push((url.clone(), *dimensions))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(definition)
+    }
+}
+
+/// Badge displayed on a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileBadge {
+    /// Badge definition
+    pub definition: BadgeDefinition,
+    /// Badge award event ID
+    pub award: EventId,
+}
+
+/// Resolve the badges to display from a profile badges event
+///
+/// Each `a`/`e` tag pair of the profile badges event is kept only if:
+/// * the badge award is found in `awards`, is issued by the badge author, points to the badge and awards the profile owner;
+/// * the badge definition is found in `definitions` (the most recent one is used).
+///
+/// Invalid or unresolved pairs are skipped, keeping the order chosen by the user.
+pub fn resolve_profile_badges(
+    profile_badges: &Event,
+    definitions: &[Event],
+    awards: &[Event],
+) -> Result<Vec<ProfileBadge>, Error> {
+    if profile_badges.kind() != Kind::ProfileBadges {
+        return Err(Error::InvalidKind);
+    }
+
+    if profile_badges.identifier() != Some(PROFILE_BADGES_IDENTIFIER) {
+        return Err(Error::IdentifierTagNotFound);
+    }
+
+    let owner: PublicKey = profile_badges.author();
+    let mut badges: Vec<ProfileBadge> = Vec::new();
+
+    // `a` and `e` tags must be consecutive
+    let mut tags = profile_badges.iter_tags().peekable();
+    while let Some(tag) = tags.next() {
+        let coordinate: &Coordinate = match tag {
+            Tag::A { coordinate, .. } if coordinate.kind == Kind::BadgeDefinition => coordinate,
+            _ => continue,
+        };
+
+        let award_id: EventId = match tags.peek() {
+            Some(Tag::Event { event_id, .. }) => *event_id,
+            _ => continue,
+        };
+        tags.next();
+
+        let award: &Event = match awards.iter().find(|e| e.id() == award_id) {
+            Some(award) => award,
+            None => continue,
+        };
+
+        if !is_valid_award(award, coordinate, &owner) {
+            continue;
+        }
+
+        let definition: Option<BadgeDefinition> = definitions
+            .iter()
+            .filter(|e| {
+                e.kind() == Kind::BadgeDefinition
+                    && e.author() == coordinate.public_key
+                    && e.identifier() == Some(coordinate.identifier.as_str())
+            })
+            .max_by_key(|e| e.created_at())
+            .and_then(|e| BadgeDefinition::try_from(e).ok());
+
+        if let Some(definition) = definition {
+            badges.push(ProfileBadge {
+                definition,
+                award: award_id,
+            });
+        }
+    }
+
+    Ok(badges)
+}
+
+fn is_valid_award(award: &Event, coordinate: &Coordinate, owner: &PublicKey) -> bool {
+    award.kind() == Kind::BadgeAward
+        && award.author() == coordinate.public_key
+        && award.iter_tags().any(|t| match t {
+            Tag::A { coordinate: c, .. } => {
+                c.kind == coordinate.kind
+                    && c.public_key == coordinate.public_key
+                    && c.identifier == coordinate.identifier
+            }
+            _ => false,
+        })
+        && extract_awarded_public_key(award.tags(), owner).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::slice;
+
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_resolve_profile_badges() {
+        let issuer = Keys::generate();
+        let owner = Keys::generate();
+        let other = Keys::generate();
+
+        let definition = EventBuilder::define_badge(
+            "bravery",
+            Some("Medal of Bravery"),
+            None,
+            None,
+            None,
+            Vec::new(),
+        )
+        .to_event(&issuer)
+        .unwrap();
+        let award = EventBuilder::award_badge(&definition, [Tag::public_key(owner.public_key())])
+            .unwrap()
+            .to_event(&issuer)
+            .unwrap();
+        let other_award =
+            EventBuilder::award_badge(&definition, [Tag::public_key(other.public_key())])
+                .unwrap()
+                .to_event(&issuer)
+                .unwrap();
+
+        let profile_badges = EventBuilder::profile_badges(
+            vec![definition.clone()],
+            vec![award.clone()],
+            &owner.public_key(),
+        )
+        .unwrap()
+        .to_event(&owner)
+        .unwrap();
+
+        let badges = resolve_profile_badges(
+            &profile_badges,
+            slice::from_ref(&definition),
+            &[award.clone(), other_award.clone()],
+        )
+        .unwrap();
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges[0].award, award.id());
+        assert_eq!(
+            badges[0].definition.name.as_deref(),
+            Some("Medal of Bravery")
+        );
+        assert_eq!(
+            badges[0].definition.coordinate.public_key,
+            issuer.public_key()
+        );
+
+        // Missing award
+        let badges =
+            resolve_profile_badges(&profile_badges, slice::from_ref(&definition), &[]).unwrap();
+        assert!(badges.is_empty());
+
+        // Award not given to the profile owner
+        let forged = EventBuilder::new(
+            Kind::ProfileBadges,
+            "",
+            [
+                Tag::Identifier(PROFILE_BADGES_IDENTIFIER.to_string()),
+                Tag::from(
+                    Coordinate::new(Kind::BadgeDefinition, issuer.public_key())
+                        .identifier("bravery"),
+                ),
+                Tag::event(other_award.id()),
+            ],
+        )
+        .to_event(&owner)
+        .unwrap();
+        let badges =
+            resolve_profile_badges(&forged, slice::from_ref(&definition), &[other_award]).unwrap();
+        assert!(badges.is_empty());
+
+        assert!(resolve_profile_badges(&definition, &[], &[]).is_err());
+    }
+}
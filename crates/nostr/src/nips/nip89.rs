@@ -0,0 +1,359 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP89
+//!
+//! Recommended Application Handlers
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/89.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use super::nip01::Coordinate;
+use crate::{Event, JsonUtil, Kind, Metadata, Tag, TagKind, UncheckedUrl};
+
+/// Placeholder replaced by the bech32 entity in handler URL templates
+pub const BECH32_PLACEHOLDER: &str = "<bech32>";
+
+/// Known handler platforms
+pub const PLATFORMS: [&str; 3] = ["web", "ios", "android"];
+
+/// NIP89 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `d` tag identifier
+    MissingIdentifier,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingIdentifier => write!(f, "Missing identifier"),
+        }
+    }
+}
+
+/// Handler URL template
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HandlerUrl {
+    /// Platform (i.e. `web`, `ios`, `android`)
+    pub platform: String,
+    /// URL template (i.e. `https://example.com/e/<bech32>`)
+    pub template: String,
+    /// Bech32 entity type the template expects (i.e. `nevent`, `naddr`, `nprofile`)
+    pub entity: Option<String>,
+}
+
+impl HandlerUrl {
+    /// New handler URL template
+    pub fn new<P, T>(platform: P, template: T, entity: Option<String>) -> Self
+    where
+        P: Into<String>,
+        T: Into<String>,
+    {
+        Self {
+            platform: platform.into(),
+            template: template.into(),
+            entity,
+        }
+    }
+
+    /// Build the URL for a bech32 entity
+    pub fn url<S>(&self, bech32: S) -> String
+    where
+        S: AsRef<str>,
+    {
+        self.template.replace(BECH32_PLACEHOLDER, bech32.as_ref())
+    }
+}
+
+impl From<HandlerUrl> for Tag {
+    fn from(url: HandlerUrl) -> Self {
+        let mut values: Vec<String> = vec![url.template];
+        values.extend(url.entity);
+        Tag::custom(TagKind::Custom(url.platform), values)
+    }
+}
+
+/// Handler information (kind `31990`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerInformation {
+    /// Unique handler ID
+    pub id: String,
+    /// Supported kinds
+    pub kinds: Vec<Kind>,
+    /// URL templates
+    pub urls: Vec<HandlerUrl>,
+    /// Application metadata (event content)
+    ///
+    /// If `None`, clients should use the author's profile metadata.
+    pub metadata: Option<Metadata>,
+}
+
+impl HandlerInformation {
+    /// New handler information
+    pub fn new<S>(id: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            kinds: Vec::new(),
+            urls: Vec::new(),
+            metadata: None,
+        }
+    }
+
+    /// Add supported kinds
+    pub fn kinds<I>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = Kind>,
+    {
+        self.kinds.extend(kinds);
+        self
+    }
+
+    /// Add URL template
+    pub fn url(mut self, url: HandlerUrl) -> Self {
+        self.urls.push(url);
+        self
+    }
+
+    /// Set application metadata
+    pub fn metadata(self, metadata: Metadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+            ..self
+        }
+    }
+
+    /// Check if the handler supports the kind
+    pub fn supports(&self, kind: &Kind) -> bool {
+        self.kinds.contains(kind)
+    }
+
+    /// Get the URL templates of a platform
+    pub fn urls_for<'a>(&'a self, platform: &'a str) -> impl Iterator<Item = &'a HandlerUrl> {
+        self.urls.iter().filter(move |u| u.platform == platform)
+    }
+}
+
+impl From<HandlerInformation> for Vec<Tag> {
+    fn from(info: HandlerInformation) -> Self {
+        let HandlerInformation {
+            id, kinds, urls, ..
+        } = info;
+
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(id)];
+        tags.extend(kinds.into_iter().map(Tag::Kind));
+        tags.extend(urls.into_iter().map(Tag::from));
+        tags
+    }
+}
+
+impl TryFrom<&Event> for HandlerInformation {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::HandlerInformation {
+            return Err(Error::WrongKind);
+        }
+
+        let id: &str = event.identifier().ok_or(Error::MissingIdentifier)?;
+        let mut info = Self::new(id);
+
+        // Invalid or empty content: fallback to the author's profile metadata
+        if !event.content().is_empty() {
+            info.metadata = Metadata::from_json(event.content()).ok();
+        }
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Kind(kind) => info.kinds.push(*kind),
+                tag => match tag.as_vec().as_slice() {
+                    [platform, template, rest @ ..] if PLATFORMS.contains(&platform.as_str()) => {
+                        info.urls.push(HandlerUrl {
+                            platform: platform.clone(),
+                            template: template.clone(),
+                            entity: rest.first().cloned(),
+                        })
+                    }
+                    _ => (),
+                },
+            }
+        }
+
+        Ok(info)
+    }
+}
+
+/// Recommended handler
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecommendedHandler {
+    /// Coordinate of the handler information event
+    pub coordinate: Coordinate,
+    /// Relay hint
+    pub relay_url: Option<UncheckedUrl>,
+    /// Platform
+    pub platform: Option<String>,
+}
+
+impl RecommendedHandler {
+    /// New recommended handler
+    pub fn new(coordinate: Coordinate) -> Self {
+        Self {
+            coordinate,
+            relay_url: None,
+            platform: None,
+        }
+    }
+}
+
+/// Handler recommendation (kind `31989`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerRecommendation {
+    /// Recommended kind
+    pub kind: Kind,
+    /// Recommended handlers
+    pub handlers: Vec<RecommendedHandler>,
+}
+
+impl HandlerRecommendation {
+    /// New handler recommendation
+    pub fn new<I>(kind: Kind, handlers: I) -> Self
+    where
+        I: IntoIterator<Item = RecommendedHandler>,
+    {
+        Self {
+            kind,
+            handlers: handlers.into_iter().collect(),
+        }
+    }
+}
+
+impl From<HandlerRecommendation> for Vec<Tag> {
+    fn from(recommendation: HandlerRecommendation) -> Self {
+        let HandlerRecommendation { kind, handlers } = recommendation;
+
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(kind.to_string())];
+        tags.extend(handlers.into_iter().map(
+            |RecommendedHandler {
+                 coordinate,
+                 relay_url,
+                 platform,
+             }| {
+                let mut values: Vec<String> = vec![coordinate.to_string()];
+                match (relay_url, platform) {
+                    (relay_url, Some(platform)) => {
+                        values.push(relay_url.map(|u| u.to_string()).unwrap_or_default());
+                        values.push(platform);
+                    }
+                    (Some(relay_url), None) => values.push(relay_url.to_string()),
+                    (None, None) => (),
+                }
+                Tag::custom(TagKind::from("a"), values)
+            },
+        ));
+        tags
+    }
+}
+
+impl TryFrom<&Event> for HandlerRecommendation {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::HandlerRecommendation {
+            return Err(Error::WrongKind);
+        }
+
+        let kind: Kind = event
+            .identifier()
+            .and_then(|id| Kind::from_str(id).ok())
+            .ok_or(Error::MissingIdentifier)?;
+
+        let handlers = event
+            .iter_tags()
+            .filter_map(|tag| match tag.as_vec().as_slice() {
+                [a, coordinate, rest @ ..] if a == "a" => Some(RecommendedHandler {
+                    coordinate: Coordinate::from_str(coordinate).ok()?,
+                    relay_url: rest
+                        .first()
+                        .filter(|u| !u.is_empty())
+                        .map(UncheckedUrl::from),
+                    platform: rest.get(1).cloned(),
+                }),
+                _ => None,
+            });
+
+        Ok(Self::new(kind, handlers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_handler_information() {
+        let keys = Keys::generate();
+        let info = HandlerInformation::new("app")
+            .kinds([Kind::Custom(31337), Kind::TextNote])
+            .url(HandlerUrl::new(
+                "web",
+                "https://example.com/a/<bech32>",
+                Some(String::from("nevent")),
+            ))
+            .url(HandlerUrl::new("ios", "app://<bech32>", None))
+            .metadata(Metadata::new().name("app"));
+
+        let event = EventBuilder::handler_information(info.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::HandlerInformation);
+        let parsed = HandlerInformation::try_from(&event).unwrap();
+        assert_eq!(parsed, info);
+        assert!(parsed.supports(&Kind::Custom(31337)));
+
+        let web = parsed.urls_for("web").next().unwrap();
+        assert_eq!(web.url("nevent1abc"), "https://example.com/a/nevent1abc");
+    }
+
+    #[test]
+    fn test_handler_recommendation() {
+        let keys = Keys::generate();
+        let coordinate =
+            Coordinate::new(Kind::HandlerInformation, keys.public_key()).identifier("app");
+        let recommendation = HandlerRecommendation::new(
+            Kind::Custom(31337),
+            [
+                RecommendedHandler {
+                    coordinate: coordinate.clone(),
+                    relay_url: Some(UncheckedUrl::from("wss://relay.example.com")),
+                    platform: Some(String::from("web")),
+                },
+                RecommendedHandler::new(coordinate),
+            ],
+        );
+
+        let event = EventBuilder::handler_recommendation(recommendation.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::HandlerRecommendation);
+        assert_eq!(event.identifier(), Some("31337"));
+        assert_eq!(
+            HandlerRecommendation::try_from(&event).unwrap(),
+            recommendation
+        );
+    }
+}
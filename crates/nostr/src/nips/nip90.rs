@@ -6,24 +6,80 @@
 //!
 //! <https://github.com/nostr-protocol/nips/blob/master/90.md>
 
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use core::fmt;
 use core::str::FromStr;
 
+#[cfg(feature = "nip04")]
+use crate::nips::nip04;
+#[cfg(feature = "nip04")]
+use crate::SecretKey;
+use crate::{
+    Alphabet, Event, EventId, JsonUtil, Kind, PublicKey, SingleLetterTag, Tag, TagKind,
+    UncheckedUrl,
+};
+
 /// DVM Error
 #[derive(Debug)]
 pub enum Error {
     /// Unknown status
     UnknownStatus,
+    /// Unknown input type
+    UnknownInputType,
+    /// Wrong event kind
+    WrongKind,
+    /// Invalid tag
+    InvalidTag,
+    /// The event doesn't reference the job request (`e` tag)
+    MissingJobRequest,
+    /// The event doesn't reference the customer (`p` tag)
+    MissingCustomer,
+    /// Missing status tag
+    MissingStatus,
+    /// Job request params are encrypted
+    Encrypted,
+    /// JSON error
+    Json(serde_json::Error),
+    /// NIP04 error
+    #[cfg(feature = "nip04")]
+    NIP04(nip04::Error),
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::UnknownStatus => write!(f, "Unknown status"),
+            Self::UnknownInputType => write!(f, "Unknown input type"),
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::InvalidTag => write!(f, "Invalid tag"),
+            Self::MissingJobRequest => write!(f, "Missing job request"),
+            Self::MissingCustomer => write!(f, "Missing customer"),
+            Self::MissingStatus => write!(f, "Missing status"),
+            Self::Encrypted => write!(f, "Job request params are encrypted"),
+            Self::Json(e) => write!(f, "Json: {e}"),
+            #[cfg(feature = "nip04")]
+            Self::NIP04(e) => write!(f, "NIP04: {e}"),
         }
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[cfg(feature = "nip04")]
+impl From<nip04::Error> for Error {
+    fn from(e: nip04::Error) -> Self {
+        Self::NIP04(e)
+    }
+}
+
 /// Data Vending Machine Status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum DataVendingMachineStatus {
@@ -65,3 +121,604 @@ impl FromStr for DataVendingMachineStatus {
         }
     }
 }
+
+/// Job input type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JobInputType {
+    /// URL to be fetched
+    Url,
+    /// Nostr event ID
+    Event,
+    /// Output of a previous job
+    Job,
+    /// Input text
+    Text,
+}
+
+impl fmt::Display for JobInputType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Url => write!(f, "url"),
+            Self::Event => write!(f, "event"),
+            Self::Job => write!(f, "job"),
+            Self::Text => write!(f, "text"),
+        }
+    }
+}
+
+impl FromStr for JobInputType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "url" => Ok(Self::Url),
+            "event" => Ok(Self::Event),
+            "job" => Ok(Self::Job),
+            "text" => Ok(Self::Text),
+            _ => Err(Error::UnknownInputType),
+        }
+    }
+}
+
+/// Job input
+///
+/// JSON: `["i", "<data>", "<input-type>", "<relay>", "<marker>"]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JobInput {
+    /// Input data
+    pub data: String,
+    /// How the data should be interpreted
+    pub input_type: JobInputType,
+    /// Relay where the referenced event or job output can be found
+    pub relay: Option<UncheckedUrl>,
+    /// How the input should be used in the job
+    pub marker: Option<String>,
+}
+
+impl JobInput {
+    /// New job input
+    pub fn new<S>(data: S, input_type: JobInputType) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            data: data.into(),
+            input_type,
+            relay: None,
+            marker: None,
+        }
+    }
+
+    /// Set relay hint
+    pub fn relay(self, relay: UncheckedUrl) -> Self {
+        Self {
+            relay: Some(relay),
+            ..self
+        }
+    }
+
+    /// Set marker
+    pub fn marker<S>(self, marker: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            marker: Some(marker.into()),
+            ..self
+        }
+    }
+
+    fn parse(tag: &[String]) -> Result<Self, Error> {
+        let data: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+        let input_type: JobInputType =
+            JobInputType::from_str(tag.get(2).ok_or(Error::InvalidTag)?)?;
+        Ok(Self {
+            data: data.clone(),
+            input_type,
+            relay: tag
+                .get(3)
+                .filter(|r| !r.is_empty())
+                .map(|r| UncheckedUrl::from(r.as_str())),
+            marker: tag.get(4).cloned(),
+        })
+    }
+}
+
+impl From<JobInput> for Tag {
+    fn from(input: JobInput) -> Self {
+        let mut values: Vec<String> = vec![input.data, input.input_type.to_string()];
+        match (input.relay, input.marker) {
+            (relay, Some(marker)) => {
+                values.push(relay.map(|r| r.to_string()).unwrap_or_default());
+                values.push(marker);
+            }
+            (Some(relay), None) => values.push(relay.to_string()),
+            (None, None) => (),
+        }
+        Tag::custom(
+            TagKind::SingleLetter(SingleLetterTag::lowercase(Alphabet::I)),
+            values,
+        )
+    }
+}
+
+/// Job request
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/90.md#job-request-kind5000-5999>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobRequest {
+    /// Job request kind (`5000-5999`)
+    pub kind: Kind,
+    /// Inputs
+    pub inputs: Vec<JobInput>,
+    /// Expected output format (MIME type)
+    pub output: Option<String>,
+    /// Job specific params (`key`, `value`)
+    pub params: Vec<(String, String)>,
+    /// Max amount the customer is willing to pay (millisats)
+    pub bid: Option<u64>,
+    /// Relays where the service providers should publish the responses
+    pub relays: Vec<UncheckedUrl>,
+    /// Service providers the customer is interested in
+    pub service_providers: Vec<PublicKey>,
+}
+
+impl JobRequest {
+    /// New job request
+    ///
+    /// Return an error if the kind isn't in the job request range (`5000-5999`).
+    pub fn new(kind: Kind) -> Result<Self, Error> {
+        if !kind.is_job_request() {
+            return Err(Error::WrongKind);
+        }
+
+        Ok(Self {
+            kind,
+            inputs: Vec::new(),
+            output: None,
+            params: Vec::new(),
+            bid: None,
+            relays: Vec::new(),
+            service_providers: Vec::new(),
+        })
+    }
+
+    /// Add input
+    pub fn input(mut self, input: JobInput) -> Self {
+        self.inputs.push(input);
+        self
+    }
+
+    /// Set expected output format (MIME type)
+    pub fn output<S>(self, output: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            output: Some(output.into()),
+            ..self
+        }
+    }
+
+    /// Add param
+    pub fn param<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set bid (millisats)
+    pub fn bid(self, millisats: u64) -> Self {
+        Self {
+            bid: Some(millisats),
+            ..self
+        }
+    }
+
+    /// Set relays where the service providers should publish the responses
+    pub fn relays<I>(self, relays: I) -> Self
+    where
+        I: IntoIterator<Item = UncheckedUrl>,
+    {
+        Self {
+            relays: relays.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Add service provider
+    pub fn service_provider(mut self, public_key: PublicKey) -> Self {
+        self.service_providers.push(public_key);
+        self
+    }
+
+    /// Kind of the job result
+    pub fn result_kind(&self) -> Kind {
+        self.kind + 1000
+    }
+
+    /// Decrypt job request event with encrypted inputs and params
+    ///
+    /// Must be called by the service provider the request is addressed to.
+    /// Unencrypted requests are parsed as usual.
+    #[cfg(feature = "nip04")]
+    pub fn decrypt(secret_key: &SecretKey, event: &Event) -> Result<Self, Error> {
+        if !is_encrypted(event) {
+            return Self::try_from(event);
+        }
+
+        let content: String = nip04::decrypt(secret_key, event.author_ref(), event.content())?;
+        let private_tags: Vec<Vec<String>> = serde_json::from_str(&content)?;
+        let tags = event.iter_tags().map(|t| t.as_vec()).chain(private_tags);
+        Self::from_tags(event.kind(), tags)
+    }
+
+    /// Split in public and private (inputs and params) tags
+    pub(crate) fn into_tags(self) -> (Vec<Tag>, Vec<Tag>) {
+        let mut public: Vec<Tag> = Vec::new();
+        let mut private: Vec<Tag> = Vec::with_capacity(self.inputs.len() + self.params.len());
+
+        private.extend(self.inputs.into_iter().map(Tag::from));
+        private.extend(
+            self.params.into_iter().map(|(key, value)| {
+                Tag::custom(TagKind::Custom(String::from("param")), [key, value])
+            }),
+        );
+
+        if let Some(output) = self.output {
+            public.push(Tag::custom(
+                TagKind::Custom(String::from("output")),
+                [output],
+            ));
+        }
+        if let Some(bid) = self.bid {
+            public.push(Tag::custom(
+                TagKind::Custom(String::from("bid")),
+                [bid.to_string()],
+            ));
+        }
+        if !self.relays.is_empty() {
+            public.push(Tag::Relays(self.relays));
+        }
+        public.extend(self.service_providers.into_iter().map(Tag::public_key));
+
+        (public, private)
+    }
+
+    fn from_tags<I>(kind: Kind, tags: I) -> Result<Self, Error>
+    where
+        I: Iterator<Item = Vec<String>>,
+    {
+        let mut request = Self::new(kind)?;
+        for tag in tags {
+            match tag.first().map(|t| t.as_str()) {
+                Some("i") => request.inputs.push(JobInput::parse(&tag)?),
+                Some("param") => {
+                    let key: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+                    let value: &String = tag.get(2).ok_or(Error::InvalidTag)?;
+                    request.params.push((key.clone(), value.clone()));
+                }
+                Some("output") => request.output = tag.get(1).cloned(),
+                Some("bid") => {
+                    let bid: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+                    request.bid = Some(bid.parse().map_err(|_| Error::InvalidTag)?);
+                }
+                Some("relays") => request
+                    .relays
+                    .extend(tag.iter().skip(1).map(|r| UncheckedUrl::from(r.as_str()))),
+                Some("p") => {
+                    let public_key: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+                    request
+                        .service_providers
+                        .push(PublicKey::from_hex(public_key).map_err(|_| Error::InvalidTag)?);
+                }
+                _ => (),
+            }
+        }
+        Ok(request)
+    }
+}
+
+impl From<JobRequest> for Vec<Tag> {
+    fn from(request: JobRequest) -> Self {
+        let (public, private) = request.into_tags();
+        [private, public].concat()
+    }
+}
+
+impl TryFrom<&Event> for JobRequest {
+    type Error = Error;
+
+    /// Parse job request event
+    ///
+    /// Return [`Error::Encrypted`] if inputs and params are encrypted: use [`JobRequest::decrypt`].
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if is_encrypted(event) {
+            return Err(Error::Encrypted);
+        }
+
+        Self::from_tags(event.kind(), event.iter_tags().map(|t| t.as_vec()))
+    }
+}
+
+/// Job result
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/90.md#job-result-kind6000-6999>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobResult {
+    /// Job result kind (`6000-6999`)
+    pub kind: Kind,
+    /// Service provider
+    pub service_provider: PublicKey,
+    /// Job request event ID
+    pub request_id: EventId,
+    /// Customer
+    pub customer: PublicKey,
+    /// Original job request event
+    pub request: Option<Event>,
+    /// Inputs of the original job request
+    pub inputs: Vec<JobInput>,
+    /// Requested amount (millisats)
+    pub amount: Option<u64>,
+    /// Optional bolt11 invoice
+    pub bolt11: Option<String>,
+    /// Whether the payload is encrypted
+    pub encrypted: bool,
+    /// Payload
+    pub payload: String,
+}
+
+impl TryFrom<&Event> for JobResult {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if !event.kind().is_job_result() {
+            return Err(Error::WrongKind);
+        }
+
+        let mut request_id: Option<EventId> = None;
+        let mut customer: Option<PublicKey> = None;
+        let mut request: Option<Event> = None;
+        let mut inputs: Vec<JobInput> = Vec::new();
+        let mut amount: Option<(u64, Option<String>)> = None;
+        for tag in event.iter_tags().map(|t| t.as_vec()) {
+            match tag.first().map(|t| t.as_str()) {
+                Some("e") if request_id.is_none() => request_id = Some(parse_event_id(&tag)?),
+                Some("p") if customer.is_none() => customer = Some(parse_public_key(&tag)?),
+                Some("request") => {
+                    let json: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+                    request = Some(Event::from_json(json).map_err(|_| Error::InvalidTag)?);
+                }
+                Some("i") => inputs.push(JobInput::parse(&tag)?),
+                Some("amount") => amount = Some(parse_amount(&tag)?),
+                _ => (),
+            }
+        }
+
+        let (amount, bolt11) = match amount {
+            Some((amount, bolt11)) => (Some(amount), bolt11),
+            None => (None, None),
+        };
+
+        Ok(Self {
+            kind: event.kind(),
+            service_provider: event.author(),
+            request_id: request_id.ok_or(Error::MissingJobRequest)?,
+            customer: customer.ok_or(Error::MissingCustomer)?,
+            request,
+            inputs,
+            amount,
+            bolt11,
+            encrypted: is_encrypted(event),
+            payload: event.content().to_string(),
+        })
+    }
+}
+
+/// Job feedback
+///
+/// <https://github.com/nostr-protocol/nips/blob/master/90.md#job-feedback>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobFeedback {
+    /// Service provider
+    pub service_provider: PublicKey,
+    /// Job request event ID
+    pub request_id: EventId,
+    /// Customer
+    pub customer: PublicKey,
+    /// Status
+    pub status: DataVendingMachineStatus,
+    /// Extra info about the status
+    pub extra_info: Option<String>,
+    /// Requested amount (millisats)
+    pub amount: Option<u64>,
+    /// Optional bolt11 invoice
+    pub bolt11: Option<String>,
+    /// Partial results (if any)
+    pub payload: String,
+}
+
+impl TryFrom<&Event> for JobFeedback {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::JobFeedback {
+            return Err(Error::WrongKind);
+        }
+
+        let mut request_id: Option<EventId> = None;
+        let mut customer: Option<PublicKey> = None;
+        let mut status: Option<(DataVendingMachineStatus, Option<String>)> = None;
+        let mut amount: Option<(u64, Option<String>)> = None;
+        for tag in event.iter_tags().map(|t| t.as_vec()) {
+            match tag.first().map(|t| t.as_str()) {
+                Some("e") if request_id.is_none() => request_id = Some(parse_event_id(&tag)?),
+                Some("p") if customer.is_none() => customer = Some(parse_public_key(&tag)?),
+                Some("status") => {
+                    let s: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+                    status = Some((DataVendingMachineStatus::from_str(s)?, tag.get(2).cloned()));
+                }
+                Some("amount") => amount = Some(parse_amount(&tag)?),
+                _ => (),
+            }
+        }
+
+        let (status, extra_info) = status.ok_or(Error::MissingStatus)?;
+        let (amount, bolt11) = match amount {
+            Some((amount, bolt11)) => (Some(amount), bolt11),
+            None => (None, None),
+        };
+
+        Ok(Self {
+            service_provider: event.author(),
+            request_id: request_id.ok_or(Error::MissingJobRequest)?,
+            customer: customer.ok_or(Error::MissingCustomer)?,
+            status,
+            extra_info,
+            amount,
+            bolt11,
+            payload: event.content().to_string(),
+        })
+    }
+}
+
+fn is_encrypted(event: &Event) -> bool {
+    event.iter_tags().any(|t| t.kind() == TagKind::Encrypted)
+}
+
+fn parse_event_id(tag: &[String]) -> Result<EventId, Error> {
+    let id: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+    EventId::from_hex(id).map_err(|_| Error::InvalidTag)
+}
+
+fn parse_public_key(tag: &[String]) -> Result<PublicKey, Error> {
+    let public_key: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+    PublicKey::from_hex(public_key).map_err(|_| Error::InvalidTag)
+}
+
+fn parse_amount(tag: &[String]) -> Result<(u64, Option<String>), Error> {
+    let millisats: &String = tag.get(1).ok_or(Error::InvalidTag)?;
+    let millisats: u64 = millisats.parse().map_err(|_| Error::InvalidTag)?;
+    Ok((millisats, tag.get(2).cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    fn request() -> JobRequest {
+        JobRequest::new(Kind::JobRequest(5001))
+            .unwrap()
+            .input(JobInput::new(
+                "What is the capital of France?",
+                JobInputType::Text,
+            ))
+            .input(
+                JobInput::new(
+                    "b2e6d9fd9fcfd0b1a5e3c1c8a5a3e5a4a8e9f7e7e0c5b8f2f0e3a5d2b6c5a1e0",
+                    JobInputType::Event,
+                )
+                .relay(UncheckedUrl::from("wss://relay.damus.io"))
+                .marker("source"),
+            )
+            .output("text/plain")
+            .param("model", "LLaMA-2")
+            .bid(5_000)
+            .relays([UncheckedUrl::from("wss://nos.lol")])
+    }
+
+    #[test]
+    fn test_job_request() {
+        let keys = Keys::generate();
+        let request = request();
+        let tags: Vec<Tag> = request.clone().into();
+        let event = EventBuilder::job_request(request.kind, tags)
+            .unwrap()
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(JobRequest::try_from(&event).unwrap(), request);
+        assert_eq!(request.result_kind(), Kind::JobResult(6001));
+
+        assert!(matches!(
+            JobRequest::new(Kind::TextNote).unwrap_err(),
+            Error::WrongKind
+        ));
+    }
+
+    #[test]
+    #[cfg(all(feature = "std", feature = "nip04"))]
+    fn test_encrypted_job_request() {
+        let customer = Keys::generate();
+        let service_provider = Keys::generate();
+        let request = request();
+        let event = EventBuilder::encrypted_job_request(
+            &customer,
+            service_provider.public_key(),
+            request.clone(),
+        )
+        .unwrap()
+        .to_event(&customer)
+        .unwrap();
+
+        assert!(!event.content().contains("LLaMA-2"));
+        assert!(matches!(
+            JobRequest::try_from(&event).unwrap_err(),
+            Error::Encrypted
+        ));
+
+        let decrypted =
+            JobRequest::decrypt(service_provider.secret_key().unwrap(), &event).unwrap();
+        assert_eq!(decrypted.params, request.params);
+        assert_eq!(decrypted.inputs, request.inputs);
+        assert_eq!(
+            decrypted.service_providers,
+            vec![service_provider.public_key()]
+        );
+    }
+
+    #[test]
+    fn test_job_result_and_feedback() {
+        let customer = Keys::generate();
+        let service_provider = Keys::generate();
+        let request = request();
+        let tags: Vec<Tag> = request.clone().into();
+        let request_event = EventBuilder::job_request(request.kind, tags)
+            .unwrap()
+            .to_event(&customer)
+            .unwrap();
+
+        let feedback = EventBuilder::job_feedback(
+            &request_event,
+            DataVendingMachineStatus::PaymentRequired,
+            None,
+            1_000,
+            Some(String::from("lnbc10n1...")),
+            None,
+        )
+        .to_event(&service_provider)
+        .unwrap();
+        let feedback = JobFeedback::try_from(&feedback).unwrap();
+        assert_eq!(feedback.request_id, request_event.id());
+        assert_eq!(feedback.customer, customer.public_key());
+        assert_eq!(feedback.status, DataVendingMachineStatus::PaymentRequired);
+        assert_eq!(feedback.amount, Some(1_000));
+        assert_eq!(feedback.bolt11.as_deref(), Some("lnbc10n1..."));
+
+        let result = EventBuilder::job_result(request_event.clone(), 1_000, None)
+            .unwrap()
+            .to_event(&service_provider)
+            .unwrap();
+        let result = JobResult::try_from(&result).unwrap();
+        assert_eq!(result.kind, Kind::JobResult(6001));
+        assert_eq!(result.service_provider, service_provider.public_key());
+        assert_eq!(result.request_id, request_event.id());
+        assert_eq!(result.customer, customer.public_key());
+        assert_eq!(result.inputs, request.inputs);
+        assert_eq!(result.request, Some(request_event));
+        assert!(!result.encrypted);
+    }
+}
@@ -11,7 +11,7 @@ use alloc::vec::Vec;
 
 use bitcoin::hashes::sha256::Hash as Sha256Hash;
 
-use crate::{ImageDimensions, Tag, Url};
+use crate::{Event, ImageDimensions, Kind, Tag, UncheckedUrl, Url};
 
 /// Potential errors returned when parsing tags into a [FileMetadata] struct
 #[derive(Debug, PartialEq, Eq)]
@@ -22,6 +22,8 @@ pub enum FileMetadataError {
     MissingMimeType,
     /// The SHA256 hash of the file is missing (no `x` tag)
     MissingSha,
+    /// The event is not a file metadata event (kind `1063`)
+    WrongKind,
 }
 
 impl core::fmt::Display for FileMetadataError {
@@ -30,6 +32,7 @@ impl core::fmt::Display for FileMetadataError {
             Self::MissingUrl => write!(f, "missing url"),
             Self::MissingMimeType => write!(f, "missing mime type"),
             Self::MissingSha => write!(f, "missing file sha256"),
+            Self::WrongKind => write!(f, "wrong event kind"),
         }
     }
 }
@@ -56,6 +59,8 @@ pub struct FileMetadata {
     pub magnet: Option<String>,
     /// Blurhash
     pub blurhash: Option<String>,
+    /// Thumbnail
+    pub thumb: Option<(UncheckedUrl, Option<ImageDimensions>)>,
 }
 
 impl FileMetadata {
@@ -73,6 +78,7 @@ impl FileMetadata {
             dim: None,
             magnet: None,
             blurhash: None,
+            thumb: None,
         }
     }
 
@@ -124,6 +130,14 @@ impl FileMetadata {
             ..self
         }
     }
+
+    /// Add thumbnail
+    pub fn thumb(self, url: UncheckedUrl, dim: Option<ImageDimensions>) -> Self {
+        Self {
+            thumb: Some((url, dim)),
+            ..self
+        }
+    }
 }
 
 impl From<FileMetadata> for Vec<Tag> {
@@ -139,6 +153,7 @@ impl From<FileMetadata> for Vec<Tag> {
             dim,
             magnet,
             blurhash,
+            thumb,
         } = metadata;
 
         tags.push(Tag::Url(url));
@@ -165,6 +180,10 @@ impl From<FileMetadata> for Vec<Tag> {
             tags.push(Tag::Blurhash(blurhash));
         }
 
+        if let Some((url, dim)) = thumb {
+            tags.push(Tag::Thumb(url, dim));
+        }
+
         tags
     }
 }
@@ -209,16 +228,33 @@ impl TryFrom<Vec<Tag>> for FileMetadata {
             metadata = metadata.blurhash(bh);
         }
 
+        if let Some(Tag::Thumb(url, dim)) = value.iter().find(|t| matches!(t, Tag::Thumb(..))) {
+            metadata = metadata.thumb(url.clone(), *dim);
+        }
+
         Ok(metadata)
     }
 }
 
+impl TryFrom<&Event> for FileMetadata {
+    type Error = FileMetadataError;
+
+    /// Extract the [`FileMetadata`] from a file metadata event (kind `1063`)
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        if event.kind() != Kind::FileMetadata {
+            return Err(FileMetadataError::WrongKind);
+        }
+
+        Self::try_from(event.tags().to_vec())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use core::str::FromStr;
 
     use super::*;
-    use crate::{ImageDimensions, Tag};
+    use crate::{EventBuilder, ImageDimensions, Keys, Tag};
 
     const IMAGE_URL: &str = "https://image.nostr.build/99a95fcb4b7a2591ad32467032c52a62d90a204d3b176bc2459ad7427a3f2b89.jpg";
     const IMAGE_HASH: &str = "1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02";
@@ -243,6 +279,38 @@ mod tests {
         assert_eq!(expected, got);
     }
 
+    #[test]
+    fn parses_file_metadata_event() {
+        let keys = Keys::generate();
+        let url = Url::parse(IMAGE_URL).unwrap();
+        let hash = Sha256Hash::from_str(IMAGE_HASH).unwrap();
+        let dim = ImageDimensions {
+            width: 640,
+            height: 640,
+        };
+        let metadata = FileMetadata::new(url, "image/jpeg", hash)
+            .size(1024)
+            .dimensions(dim)
+            .blurhash("eVF$^OI:${M{o#*0-nNFxakD-?xVM}WEWB%iNKxvR-oetmo#R-aen$")
+            .thumb(
+                UncheckedUrl::from("https://image.nostr.build/thumb.jpg"),
+                Some(dim),
+            );
+
+        let event = EventBuilder::file_metadata("A picture", metadata.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(FileMetadata::try_from(&event).unwrap(), metadata);
+
+        let event = EventBuilder::text_note("not a file", [])
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(
+            FileMetadata::try_from(&event).unwrap_err(),
+            FileMetadataError::WrongKind
+        );
+    }
+
     #[test]
     fn returns_error_with_url_missing() {
         let hash = Sha256Hash::from_str(IMAGE_HASH).unwrap();
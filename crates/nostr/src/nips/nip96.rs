@@ -0,0 +1,403 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP96
+//!
+//! HTTP File Storage Integration
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/96.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use std::net::SocketAddr;
+
+use base64::engine::{general_purpose, Engine};
+use bitcoin::hashes::sha256::Hash as Sha256Hash;
+use bitcoin::hashes::Hash;
+#[cfg(not(target_arch = "wasm32"))]
+use reqwest::Proxy;
+use reqwest::{multipart, Client, Response};
+
+use super::nip94::{FileMetadata, FileMetadataError};
+use super::nip98::{HttpData, AUTHORIZATION_SCHEME};
+use crate::event::builder;
+use crate::types::url::{ParseError, Url};
+use crate::{Event, EventBuilder, HttpMethod, JsonUtil, Keys, Tag, UncheckedUrl};
+
+/// Well-known path of the server configuration
+pub const WELL_KNOWN_PATH: &str = "/.well-known/nostr/nip96.json";
+
+/// `NIP96` error
+#[derive(Debug)]
+pub enum Error {
+    /// Reqwest error
+    Reqwest(reqwest::Error),
+    /// Url parse error
+    Url(ParseError),
+    /// Event builder error
+    EventBuilder(builder::Error),
+    /// File metadata error
+    FileMetadata(FileMetadataError),
+    /// The server configuration is invalid
+    InvalidServerConfig,
+    /// The server response is invalid
+    InvalidResponse,
+    /// The server rejected the request
+    Server {
+        /// HTTP status code
+        status: u16,
+        /// Message returned by the server
+        message: Option<String>,
+    },
+    /// The server accepted the upload but is still processing it
+    Processing {
+        /// URL to poll to know when the processing is completed
+        processing_url: Option<String>,
+    },
+    /// The server response doesn't include the NIP94 event
+    MissingFileMetadata,
+}
+
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reqwest(e) => write!(f, "{e}"),
+            Self::Url(e) => write!(f, "{e}"),
+            Self::EventBuilder(e) => write!(f, "{e}"),
+            Self::FileMetadata(e) => write!(f, "{e}"),
+            Self::InvalidServerConfig => write!(f, "The server configuration is invalid"),
+            Self::InvalidResponse => write!(f, "The server response is invalid"),
+            Self::Server { status, message } => match message {
+                Some(message) => write!(f, "Server error ({status}): {message}"),
+                None => write!(f, "Server error ({status})"),
+            },
+            Self::Processing { processing_url } => match processing_url {
+                Some(url) => write!(f, "The file is still processing: poll {url}"),
+                None => write!(f, "The file is still processing"),
+            },
+            Self::MissingFileMetadata => write!(f, "File metadata not found in the response"),
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Reqwest(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Self::Url(e)
+    }
+}
+
+impl From<builder::Error> for Error {
+    fn from(e: builder::Error) -> Self {
+        Self::EventBuilder(e)
+    }
+}
+
+impl From<FileMetadataError> for Error {
+    fn from(e: FileMetadataError) -> Self {
+        Self::FileMetadata(e)
+    }
+}
+
+/// File storage server configuration
+///
+/// Served at [`WELL_KNOWN_PATH`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerConfig {
+    /// Upload and deletion endpoint
+    pub api_url: Url,
+    /// Download base URL (default to [`ServerConfig::api_url`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub download_url: Option<Url>,
+    /// Server to which the uploads are delegated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub delegated_to_url: Option<Url>,
+    /// Supported NIPs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub supported_nips: Vec<u16>,
+    /// Terms of service URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub tos_url: Option<Url>,
+    /// Accepted MIME types (empty means any)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub content_types: Vec<String>,
+}
+
+/// Configuration of a server that delegates the uploads (empty `api_url`)
+#[derive(Deserialize)]
+struct Delegation {
+    delegated_to_url: Url,
+}
+
+impl JsonUtil for ServerConfig {
+    type Err = serde_json::Error;
+}
+
+impl ServerConfig {
+    /// Discover the configuration of the file storage server
+    ///
+    /// If the server delegates the uploads, the configuration of the delegated server is returned.
+    pub async fn discover(server_url: Url, proxy: Option<SocketAddr>) -> Result<Self, Error> {
+        let client: Client = build_client(proxy)?;
+        let json: String = Self::fetch(&client, &server_url).await?;
+        match Self::from_json(&json) {
+            Ok(config) => Ok(config),
+            Err(_) => {
+                let delegation: Delegation =
+                    serde_json::from_str(&json).map_err(|_| Error::InvalidServerConfig)?;
+                let json: String = Self::fetch(&client, &delegation.delegated_to_url).await?;
+                Self::from_json(json).map_err(|_| Error::InvalidServerConfig)
+            }
+        }
+    }
+
+    async fn fetch(client: &Client, server_url: &Url) -> Result<String, Error> {
+        let url: Url = server_url.join(WELL_KNOWN_PATH)?;
+        let json: String = client.get(url).send().await?.text().await?;
+        tracing::debug!("Response: {json}");
+        Ok(json)
+    }
+
+    /// Base URL of the uploaded files
+    pub fn download_url(&self) -> &Url {
+        self.download_url.as_ref().unwrap_or(&self.api_url)
+    }
+}
+
+/// NIP94 event embedded in the upload response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nip94Event {
+    /// Tags
+    #[serde(default)]
+    pub tags: Vec<Vec<String>>,
+    /// Content
+    #[serde(default)]
+    pub content: String,
+}
+
+/// Upload response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadResponse {
+    /// Status (`success`, `error` or `processing`)
+    pub status: String,
+    /// Human readable message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub message: Option<String>,
+    /// URL to poll while the file is processing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub processing_url: Option<String>,
+    /// NIP94 event
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub nip94_event: Option<Nip94Event>,
+}
+
+impl JsonUtil for UploadResponse {
+    type Err = serde_json::Error;
+}
+
+impl UploadResponse {
+    /// Parse the [`FileMetadata`] of the uploaded file
+    ///
+    /// Tags that can't be parsed are ignored.
+    pub fn file_metadata(&self) -> Result<FileMetadata, Error> {
+        match self.status.as_str() {
+            "success" => {}
+            "processing" => {
+                return Err(Error::Processing {
+                    processing_url: self.processing_url.clone(),
+                })
+            }
+            _ => {
+                return Err(Error::Server {
+                    status: 200,
+                    message: self.message.clone(),
+                })
+            }
+        }
+
+        let event: &Nip94Event = self
+            .nip94_event
+            .as_ref()
+            .ok_or(Error::MissingFileMetadata)?;
+        let tags: Vec<Tag> = event
+            .tags
+            .iter()
+            .filter_map(|t| Tag::parse(t).ok())
+            .collect();
+        Ok(FileMetadata::try_from(tags)?)
+    }
+}
+
+/// Upload a file to a NIP96 server
+///
+/// The request is authorized with a NIP98 event signed by `keys`.
+pub async fn upload(
+    keys: &Keys,
+    config: &ServerConfig,
+    file: Vec<u8>,
+    mime_type: Option<&str>,
+    proxy: Option<SocketAddr>,
+) -> Result<FileMetadata, Error> {
+    let client: Client = build_client(proxy)?;
+
+    let payload: Sha256Hash = Sha256Hash::hash(&file);
+    let authorization: String =
+        authorization_header(keys, &config.api_url, HttpMethod::POST, Some(payload))?;
+
+    let size: usize = file.len();
+    let mut part = multipart::Part::bytes(file).file_name("file");
+    if let Some(mime_type) = mime_type {
+        part = part.mime_str(mime_type)?;
+    }
+    let mut form = multipart::Form::new()
+        .part("file", part)
+        .text("size", size.to_string());
+    if let Some(mime_type) = mime_type {
+        form = form.text("content_type", mime_type.to_string());
+    }
+
+    let response: Response = client
+        .post(config.api_url.clone())
+        .header("Authorization", authorization)
+        .multipart(form)
+        .send()
+        .await?;
+    let status: u16 = response.status().as_u16();
+    let json: String = response.text().await?;
+    tracing::debug!("Response: {json}");
+
+    match UploadResponse::from_json(json) {
+        Ok(res) if (200..300).contains(&status) => res.file_metadata(),
+        Ok(res) => Err(Error::Server {
+            status,
+            message: res.message,
+        }),
+        Err(_) if (200..300).contains(&status) => Err(Error::InvalidResponse),
+        Err(_) => Err(Error::Server {
+            status,
+            message: None,
+        }),
+    }
+}
+
+/// Delete a file, identified by the SHA256 of the original file, from a NIP96 server
+///
+/// The request is authorized with a NIP98 event signed by `keys`.
+pub async fn delete(
+    keys: &Keys,
+    config: &ServerConfig,
+    hash: Sha256Hash,
+    proxy: Option<SocketAddr>,
+) -> Result<(), Error> {
+    let client: Client = build_client(proxy)?;
+
+    let url: Url = Url::parse(&format!(
+        "{}/{hash}",
+        config.api_url.as_str().trim_end_matches('/')
+    ))?;
+    let authorization: String = authorization_header(keys, &url, HttpMethod::DELETE, None)?;
+
+    let response: Response = client
+        .delete(url)
+        .header("Authorization", authorization)
+        .send()
+        .await?;
+    let status: u16 = response.status().as_u16();
+    if (200..300).contains(&status) {
+        Ok(())
+    } else {
+        let message: Option<String> = response
+            .text()
+            .await
+            .ok()
+            .and_then(|json| UploadResponse::from_json(json).ok())
+            .and_then(|res| res.message);
+        Err(Error::Server { status, message })
+    }
+}
+
+fn build_client(_proxy: Option<SocketAddr>) -> Result<Client, Error> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut builder = Client::builder();
+        if let Some(proxy) = _proxy {
+            let proxy = format!("socks5h://{proxy}");
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+        Ok(builder.build()?)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    Ok(Client::new())
+}
+
+/// Compose the NIP98 `Authorization` header value
+fn authorization_header(
+    keys: &Keys,
+    url: &Url,
+    method: HttpMethod,
+    payload: Option<Sha256Hash>,
+) -> Result<String, Error> {
+    let mut data = HttpData::new(UncheckedUrl::from(url.to_string()), method);
+    if let Some(payload) = payload {
+        data = data.payload(payload);
+    }
+    let event: Event = EventBuilder::http_auth(data).to_event(keys)?;
+    let encoded: String = general_purpose::STANDARD.encode(event.as_json());
+    Ok(format!("{AUTHORIZATION_SCHEME} {encoded}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_server_config() {
+        let json = r#"{"api_url":"https://nostr.build/api/v2/nip96/upload","download_url":"https://image.nostr.build","supported_nips":[94,96,98],"tos_url":"https://nostr.build/tos/","content_types":["image/jpeg","video/mp4"]}"#;
+        let config = ServerConfig::from_json(json).unwrap();
+        assert_eq!(
+            config.api_url,
+            Url::parse("https://nostr.build/api/v2/nip96/upload").unwrap()
+        );
+        assert_eq!(
+            config.download_url(),
+            &Url::parse("https://image.nostr.build").unwrap()
+        );
+        assert_eq!(config.supported_nips, vec![94, 96, 98]);
+        assert!(config.delegated_to_url.is_none());
+    }
+
+    #[test]
+    fn test_upload_response_file_metadata() {
+        let json = r#"{"status":"success","message":"Upload successful.","nip94_event":{"tags":[["url","https://image.nostr.build/1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02.jpg"],["ox","719171db19525d9d34dfaf2ff7bb9a0a7c5e0a5ed4e1d1b7d8c3eb2eaf6c5af5"],["x","1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02"],["m","image/jpeg"],["dim","640x640"]],"content":""}}"#;
+        let res = UploadResponse::from_json(json).unwrap();
+        let metadata = res.file_metadata().unwrap();
+        assert_eq!(metadata.mime_type, "image/jpeg");
+        assert_eq!(
+            metadata.hash.to_string(),
+            "1aea8e98e0e5d969b7124f553b88dfae47d1f00472ea8c0dbf4ac4577d39ef02"
+        );
+        assert_eq!(metadata.dim.unwrap().width, 640);
+
+        let json = r#"{"status":"processing","processing_url":"https://example.com/status/1"}"#;
+        let res = UploadResponse::from_json(json).unwrap();
+        assert!(matches!(res.file_metadata(), Err(Error::Processing { .. })));
+    }
+}
@@ -11,11 +11,20 @@
 
 use alloc::vec::Vec;
 use core::fmt;
+#[cfg(all(feature = "std", feature = "nip98"))]
+use core::time::Duration;
 
+#[cfg(all(feature = "std", feature = "nip98"))]
+use base64::engine::{general_purpose, Engine};
 use bitcoin::hashes::sha256::Hash as Sha256Hash;
 
+#[cfg(all(feature = "std", feature = "nip98"))]
+use crate::{Event, JsonUtil, Kind, Timestamp};
 use crate::{HttpMethod, Tag, UncheckedUrl};
 
+/// Authorization header scheme
+pub const AUTHORIZATION_SCHEME: &str = "Nostr";
+
 /// [`HttpData`] required tags
 #[derive(Debug)]
 pub enum RequiredTags {
@@ -41,6 +50,19 @@ pub enum Error {
     Hex(bitcoin::hashes::hex::HexToBytesError),
     /// Tag missing when parsing
     MissingTag(RequiredTags),
+    /// Base64 decoding error
+    #[cfg(feature = "nip98")]
+    Base64(base64::DecodeError),
+    /// Event error
+    Event(crate::event::Error),
+    /// Not an HTTP Auth event
+    WrongKind,
+    /// Event `created_at` outside of the allowed time window
+    Expired,
+    /// `u` tag not match the request URL
+    UrlMismatch,
+    /// `method` tag not match the request method
+    MethodMismatch,
 }
 
 #[cfg(feature = "std")]
@@ -51,6 +73,13 @@ impl fmt::Display for Error {
         match self {
             Self::Hex(e) => write!(f, "{e}"),
             Self::MissingTag(tag) => write!(f, r#"missing tag "{tag}""#),
+            #[cfg(feature = "nip98")]
+            Self::Base64(e) => write!(f, "{e}"),
+            Self::Event(e) => write!(f, "{e}"),
+            Self::WrongKind => write!(f, "wrong event kind"),
+            Self::Expired => write!(f, "event created_at outside of the allowed time window"),
+            Self::UrlMismatch => write!(f, "URL not match"),
+            Self::MethodMismatch => write!(f, "method not match"),
         }
     }
 }
@@ -61,6 +90,19 @@ impl From<bitcoin::hashes::hex::HexToBytesError> for Error {
     }
 }
 
+#[cfg(feature = "nip98")]
+impl From<base64::DecodeError> for Error {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::Base64(e)
+    }
+}
+
+impl From<crate::event::Error> for Error {
+    fn from(e: crate::event::Error) -> Self {
+        Self::Event(e)
+    }
+}
+
 /// HTTP Data
 pub struct HttpData {
     /// Absolute request URL
@@ -142,3 +184,89 @@ impl TryFrom<Vec<Tag>> for HttpData {
         })
     }
 }
+
+/// Verify the HTTP `Authorization` header of a request
+///
+/// The header value must be in the `Nostr <base64-encoded-event>` format.
+/// Check the event signature, the kind, that `created_at` is within `max_age` from now and
+/// that the `u` and `method` tags match the request.
+///
+/// Return the verified [`Event`]: the author is the authenticated public key.
+/// If the request has a body, compare its hash with [`HttpData::payload`].
+#[cfg(all(feature = "std", feature = "nip98"))]
+pub fn verify_auth_header<S>(
+    header: S,
+    expected_url: &UncheckedUrl,
+    method: HttpMethod,
+    max_age: Duration,
+) -> Result<Event, Error>
+where
+    S: AsRef<str>,
+{
+    let header: &str = header.as_ref().trim();
+    let encoded: &str = header
+        .strip_prefix(AUTHORIZATION_SCHEME)
+        .map(|h| h.trim_start())
+        .unwrap_or(header);
+    let json: Vec<u8> = general_purpose::STANDARD.decode(encoded)?;
+    let event: Event = Event::from_json(json)?;
+
+    if event.kind() != Kind::HttpAuth {
+        return Err(Error::WrongKind);
+    }
+
+    event.verify()?;
+
+    let now: u64 = Timestamp::now().as_u64();
+    if now.abs_diff(event.created_at().as_u64()) > max_age.as_secs() {
+        return Err(Error::Expired);
+    }
+
+    let data: HttpData = HttpData::try_from(event.tags().to_vec())?;
+    if &data.url != expected_url {
+        return Err(Error::UrlMismatch);
+    }
+    if data.method != method {
+        return Err(Error::MethodMismatch);
+    }
+
+    Ok(event)
+}
+
+#[cfg(all(test, feature = "std", feature = "nip98"))]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    fn auth_header(keys: &Keys, url: &UncheckedUrl, method: HttpMethod) -> String {
+        let event = EventBuilder::http_auth(HttpData::new(url.clone(), method))
+            .to_event(keys)
+            .unwrap();
+        format!(
+            "{AUTHORIZATION_SCHEME} {}",
+            general_purpose::STANDARD.encode(event.as_json())
+        )
+    }
+
+    #[test]
+    fn test_verify_auth_header() {
+        let keys = Keys::generate();
+        let url = UncheckedUrl::from("https://nostr.build/api/v2/upload/files");
+        let max_age = Duration::from_secs(60);
+
+        let header = auth_header(&keys, &url, HttpMethod::POST);
+        let event = verify_auth_header(&header, &url, HttpMethod::POST, max_age).unwrap();
+        assert_eq!(event.author(), keys.public_key());
+
+        assert!(matches!(
+            verify_auth_header(&header, &url, HttpMethod::GET, max_age),
+            Err(Error::MethodMismatch)
+        ));
+
+        let other_url = UncheckedUrl::from("https://nostr.build/api/v2/nip96/upload");
+        assert!(matches!(
+            verify_auth_header(&header, &other_url, HttpMethod::POST, max_age),
+            Err(Error::UrlMismatch)
+        ));
+    }
+}
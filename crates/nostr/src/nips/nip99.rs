@@ -0,0 +1,403 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! NIP99
+//!
+//! Classified Listings
+//!
+//! <https://github.com/nostr-protocol/nips/blob/master/99.md>
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Event, ImageDimensions, Kind, Tag, TagKind, Timestamp, UncheckedUrl};
+
+/// NIP99 error
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Wrong event kind
+    WrongKind,
+    /// Missing `d` tag identifier
+    MissingIdentifier,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WrongKind => write!(f, "Wrong event kind"),
+            Self::MissingIdentifier => write!(f, "Missing identifier"),
+        }
+    }
+}
+
+/// Listing price
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Price {
+    /// Amount (i.e. `50`, `15.50`)
+    pub amount: String,
+    /// ISO 4217 currency code (i.e. `EUR`, `USD`) or crypto-currency (i.e. `BTC`, `SAT`)
+    pub currency: String,
+    /// Recurring payment frequency (i.e. `hour`, `day`, `week`, `month`, `year`)
+    pub frequency: Option<String>,
+}
+
+impl Price {
+    /// New one-time price
+    pub fn new<A, C>(amount: A, currency: C) -> Self
+    where
+        A: Into<String>,
+        C: Into<String>,
+    {
+        Self {
+            amount: amount.into(),
+            currency: currency.into(),
+            frequency: None,
+        }
+    }
+
+    /// Set recurring payment frequency
+    pub fn frequency<S>(self, frequency: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            frequency: Some(frequency.into()),
+            ..self
+        }
+    }
+}
+
+/// Listing status
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ListingStatus {
+    /// Active
+    Active,
+    /// Sold
+    Sold,
+    /// Custom
+    Custom(String),
+}
+
+impl fmt::Display for ListingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Active => write!(f, "active"),
+            Self::Sold => write!(f, "sold"),
+            Self::Custom(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl<S> From<S> for ListingStatus
+where
+    S: Into<String>,
+{
+    fn from(s: S) -> Self {
+        let s: String = s.into();
+        match s.as_str() {
+            "active" => Self::Active,
+            "sold" => Self::Sold,
+            _ => Self::Custom(s),
+        }
+    }
+}
+
+/// Classified listing (kind `30402`, or `30403` for drafts and inactive listings)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassifiedListing {
+    /// Unique listing ID
+    pub id: String,
+    /// Title
+    pub title: Option<String>,
+    /// Short tagline or summary
+    pub summary: Option<String>,
+    /// Markdown description (event content)
+    pub description: String,
+    /// First published time
+    pub published_at: Option<Timestamp>,
+    /// Location
+    pub location: Option<String>,
+    /// Geohash
+    pub geohash: Option<String>,
+    /// Price
+    pub price: Option<Price>,
+    /// Status
+    pub status: Option<ListingStatus>,
+    /// Images
+    pub images: Vec<(UncheckedUrl, Option<ImageDimensions>)>,
+    /// Hashtags (categories)
+    pub hashtags: Vec<String>,
+    /// Whether the listing is a draft or inactive (kind `30403`)
+    pub draft: bool,
+}
+
+impl ClassifiedListing {
+    /// New classified listing
+    pub fn new<S, D>(id: S, description: D) -> Self
+    where
+        S: Into<String>,
+        D: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            title: None,
+            summary: None,
+            description: description.into(),
+            published_at: None,
+            location: None,
+            geohash: None,
+            price: None,
+            status: None,
+            images: Vec::new(),
+            hashtags: Vec::new(),
+            draft: false,
+        }
+    }
+
+    /// Set title
+    pub fn title<S>(self, title: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            title: Some(title.into()),
+            ..self
+        }
+    }
+
+    /// Set summary
+    pub fn summary<S>(self, summary: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            summary: Some(summary.into()),
+            ..self
+        }
+    }
+
+    /// Set published time
+    pub fn published_at(self, published_at: Timestamp) -> Self {
+        Self {
+            published_at: Some(published_at),
+            ..self
+        }
+    }
+
+    /// Set location
+    pub fn location<S>(self, location: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            location: Some(location.into()),
+            ..self
+        }
+    }
+
+    /// Set geohash
+    pub fn geohash<S>(self, geohash: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            geohash: Some(geohash.into()),
+            ..self
+        }
+    }
+
+    /// Set price
+    pub fn price(self, price: Price) -> Self {
+        Self {
+            price: Some(price),
+            ..self
+        }
+    }
+
+    /// Set status
+    pub fn status(self, status: ListingStatus) -> Self {
+        Self {
+            status: Some(status),
+            ..self
+        }
+    }
+
+    /// Add image
+    pub fn image(mut self, url: UncheckedUrl, dimensions: Option<ImageDimensions>) -> Self {
+        self.images.push((url, dimensions));
+        self
+    }
+
+    /// Add hashtag
+    pub fn hashtag<S>(mut self, hashtag: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.hashtags.push(hashtag.into());
+        self
+    }
+
+    /// Mark as draft or inactive
+    pub fn draft(self, draft: bool) -> Self {
+        Self { draft, ..self }
+    }
+
+    /// Event kind
+    pub fn kind(&self) -> Kind {
+        if self.draft {
+            Kind::DraftClassifiedListing
+        } else {
+            Kind::ClassifiedListing
+        }
+    }
+}
+
+impl From<ClassifiedListing> for Vec<Tag> {
+    fn from(listing: ClassifiedListing) -> Self {
+        let ClassifiedListing {
+            id,
+            title,
+            summary,
+            published_at,
+            location,
+            geohash,
+            price,
+            status,
+            images,
+            hashtags,
+            ..
+        } = listing;
+
+        let mut tags: Vec<Tag> = vec![Tag::Identifier(id)];
+
+        if let Some(title) = title {
+            tags.push(Tag::Title(title));
+        }
+
+        if let Some(summary) = summary {
+            tags.push(Tag::Summary(summary));
+        }
+
+        if let Some(published_at) = published_at {
+            tags.push(Tag::PublishedAt(published_at));
+        }
+
+        if let Some(location) = location {
+            tags.push(Tag::custom(
+                TagKind::Custom(String::from("location")),
+                [location],
+            ));
+        }
+
+        if let Some(geohash) = geohash {
+            tags.push(Tag::Geohash(geohash));
+        }
+
+        if let Some(Price {
+            amount,
+            currency,
+            frequency,
+        }) = price
+        {
+            let mut values: Vec<String> = vec![amount, currency];
+            values.extend(frequency);
+            tags.push(Tag::custom(TagKind::Custom(String::from("price")), values));
+        }
+
+        if let Some(status) = status {
+            tags.push(Tag::custom(
+                TagKind::Custom(String::from("status")),
+                [status.to_string()],
+            ));
+        }
+
+        tags.extend(images.into_iter().map(|(url, dim)| Tag::Image(url, dim)));
+        tags.extend(hashtags.into_iter().map(Tag::Hashtag));
+
+        tags
+    }
+}
+
+impl TryFrom<&Event> for ClassifiedListing {
+    type Error = Error;
+
+    fn try_from(event: &Event) -> Result<Self, Self::Error> {
+        let draft: bool = match event.kind() {
+            Kind::ClassifiedListing => false,
+            Kind::DraftClassifiedListing => true,
+            _ => return Err(Error::WrongKind),
+        };
+
+        let id: &str = event.identifier().ok_or(Error::MissingIdentifier)?;
+        let mut listing = Self::new(id, event.content()).draft(draft);
+
+        for tag in event.iter_tags() {
+            match tag {
+                Tag::Title(title) => listing.title = Some(title.clone()),
+                Tag::Summary(summary) => listing.summary = Some(summary.clone()),
+                Tag::PublishedAt(published_at) => listing.published_at = Some(*published_at),
+                Tag::Geohash(geohash) => listing.geohash = Some(geohash.clone()),
+                Tag::Image(url, dim) => listing.images.push((url.clone(), *dim)),
+                Tag::Hashtag(hashtag) => listing.hashtags.push(hashtag.clone()),
+                tag => match tag.as_vec().as_slice() {
+                    [kind, location, ..] if kind == "location" => {
+                        listing.location = Some(location.clone())
+                    }
+                    [kind, amount, currency, rest @ ..] if kind == "price" => {
+                        listing.price = Some(Price {
+                            amount: amount.clone(),
+                            currency: currency.clone(),
+                            frequency: rest.first().cloned(),
+                        })
+                    }
+                    [kind, status, ..] if kind == "status" => {
+                        listing.status = Some(ListingStatus::from(status.as_str()))
+                    }
+                    _ => (),
+                },
+            }
+        }
+
+        Ok(listing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventBuilder, Keys};
+
+    #[test]
+    fn test_classified_listing() {
+        let keys = Keys::generate();
+        let listing = ClassifiedListing::new("bike-1", "Lightly used road bike")
+            .title("Road bike")
+            .summary("Size 56")
+            .location("Milan, Italy")
+            .price(Price::new("350", "EUR"))
+            .status(ListingStatus::Active)
+            .image(UncheckedUrl::from("https://example.com/bike.jpg"), None)
+            .hashtag("bikes");
+
+        let event = EventBuilder::classified_listing(listing.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::ClassifiedListing);
+        assert_eq!(event.content(), "Lightly used road bike");
+        assert_eq!(ClassifiedListing::try_from(&event).unwrap(), listing);
+
+        let rent = ClassifiedListing::new("flat-1", "")
+            .price(Price::new("1000", "EUR").frequency("month"))
+            .draft(true);
+        let event = EventBuilder::classified_listing(rent.clone())
+            .to_event(&keys)
+            .unwrap();
+        assert_eq!(event.kind(), Kind::DraftClassifiedListing);
+        assert_eq!(ClassifiedListing::try_from(&event).unwrap(), rent);
+    }
+}
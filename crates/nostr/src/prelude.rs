@@ -27,6 +27,8 @@ pub use crate::key::*;
 pub use crate::message::*;
 // NIPs
 pub use crate::nips::nip01::{self, *};
+#[cfg(feature = "nip03")]
+pub use crate::nips::nip03;
 #[cfg(feature = "nip04")]
 pub use crate::nips::nip04;
 #[cfg(all(feature = "std", feature = "nip05"))]
@@ -35,13 +37,21 @@ pub use crate::nips::nip05::{self, *};
 pub use crate::nips::nip06::{self, *};
 #[cfg(all(feature = "nip07", target_arch = "wasm32"))]
 pub use crate::nips::nip07::{self, *};
+pub use crate::nips::nip10::{self, *};
 #[cfg(all(feature = "std", feature = "nip11"))]
 pub use crate::nips::nip11::{self, *};
 pub use crate::nips::nip13::{self, *};
 pub use crate::nips::nip15::{self, *};
+pub use crate::nips::nip17::{self, *};
+pub use crate::nips::nip18::{self, *};
 pub use crate::nips::nip19::{self, *};
 pub use crate::nips::nip21::{self, *};
+pub use crate::nips::nip23::{self, *};
+pub use crate::nips::nip25::{self, *};
 pub use crate::nips::nip26::{self, *};
+pub use crate::nips::nip28::{self, *};
+pub use crate::nips::nip34::{self, *};
+pub use crate::nips::nip37::{self, *};
 #[cfg(feature = "nip44")]
 pub use crate::nips::nip44::{self, *};
 #[cfg(all(feature = "std", feature = "nip46"))]
@@ -51,15 +61,21 @@ pub use crate::nips::nip47::{self, *};
 pub use crate::nips::nip48::{self, *};
 #[cfg(feature = "nip49")]
 pub use crate::nips::nip49::{self, *};
+pub use crate::nips::nip51::{self, *};
 pub use crate::nips::nip53::{self, *};
 #[cfg(feature = "nip57")]
 pub use crate::nips::nip57::{self, *};
+pub use crate::nips::nip58::{self, *};
 #[cfg(feature = "nip59")]
 pub use crate::nips::nip59::{self, *};
 pub use crate::nips::nip65::{self, *};
+pub use crate::nips::nip89::{self, *};
 pub use crate::nips::nip90::{self, *};
 pub use crate::nips::nip94::{self, *};
+#[cfg(all(feature = "std", feature = "nip96"))]
+pub use crate::nips::nip96::{self, *};
 pub use crate::nips::nip98::{self, *};
+pub use crate::nips::nip99::{self, *};
 pub use crate::types::*;
 pub use crate::util::*;
 #[cfg(feature = "std")]
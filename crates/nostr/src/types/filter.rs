@@ -358,6 +358,11 @@ pub struct Filter {
     )]
     #[serde(default)]
     pub generic_tags: GenericTags,
+    /// Match only events with (`true`) or without (`false`) a NIP36 content warning
+    ///
+    /// **Local only**: not serialized, so not sent to relays. Set it with [`Filter::sensitive`].
+    #[serde(skip)]
+    sensitive: Option<bool>,
 }
 
 impl Filter {
@@ -670,6 +675,30 @@ impl Filter {
         self
     }
 
+    /// Include only (`true`) or exclude (`false`) events with a NIP36 content warning
+    ///
+    /// **Local only**: not sent to relays and used in database queries.
+    ///
+    /// <https://github.com/nostr-protocol/nips/blob/master/36.md>
+    #[inline]
+    pub fn sensitive(mut self, sensitive: bool) -> Self {
+        self.sensitive = Some(sensitive);
+        self
+    }
+
+    /// Remove sensitive
+    #[inline]
+    pub fn remove_sensitive(mut self) -> Self {
+        self.sensitive = None;
+        self
+    }
+
+    /// Get the content warning policy set with [`Filter::sensitive`]
+    #[inline]
+    pub fn get_sensitive(&self) -> Option<bool> {
+        self.sensitive
+    }
+
     /// Add custom tag
     pub fn custom_tag<I, T>(mut self, tag: SingleLetterTag, values: I) -> Self
     where
@@ -773,6 +802,7 @@ impl Filter {
             && self.since.map_or(true, |t| event.created_at >= t)
             && self.until.map_or(true, |t| event.created_at <= t)
             && self.tag_match(event)
+            && self.sensitive.map_or(true, |s| event.is_sensitive() == s)
     }
 }
 
@@ -1152,6 +1182,34 @@ mod tests {
         assert!(!filter.match_event(&event));
         assert!(!filter.match_event(&event_with_empty_tags));
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_match_sensitive_event() {
+        let keys = crate::Keys::generate();
+        let sensitive = crate::EventBuilder::text_note("Spoiler", [])
+            .content_warning(Some("Spoiler"))
+            .to_event(&keys)
+            .unwrap();
+        let event = crate::EventBuilder::text_note("Hello", [])
+            .to_event(&keys)
+            .unwrap();
+        assert!(sensitive.is_sensitive());
+        assert_eq!(sensitive.content_warning(), Some("Spoiler"));
+        assert!(!event.is_sensitive());
+
+        let filter = Filter::new().kind(Kind::TextNote).sensitive(false);
+        assert!(!filter.match_event(&sensitive));
+        assert!(filter.match_event(&event));
+
+        let filter = filter.sensitive(true);
+        assert_eq!(filter.get_sensitive(), Some(true));
+        assert!(filter.match_event(&sensitive));
+        assert!(!filter.match_event(&event));
+
+        // Local only
+        assert_eq!(filter.as_json(), r#"{"kinds":[1]}"#);
+    }
 }
 
 #[cfg(bench)]
@@ -0,0 +1,114 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Filter templates
+//!
+//! Prebuilt filters for the common app views, ready for `subscribe` and `get_events_of`.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{EventId, Filter, Kind, PublicKey};
+
+/// Max events of the home feed
+pub const HOME_FEED_LIMIT: usize = 500;
+/// Max events of the notifications
+pub const NOTIFICATIONS_LIMIT: usize = 100;
+/// Max notes of a profile
+pub const PROFILE_NOTES_LIMIT: usize = 50;
+
+/// Kinds shown in feeds
+const FEED_KINDS: [Kind; 4] = [
+    Kind::TextNote,
+    Kind::Repost,
+    Kind::GenericRepost,
+    Kind::LongFormTextNote,
+];
+
+/// Notes, reposts and articles of the contacts
+///
+/// Return no filters if `contacts` is empty: an empty `authors` list is treated as
+/// "any author" by some relays.
+pub fn home_feed<I>(contacts: I) -> Vec<Filter>
+where
+    I: IntoIterator<Item = PublicKey>,
+{
+    let filter: Filter = Filter::new()
+        .authors(contacts)
+        .kinds(FEED_KINDS)
+        .limit(HOME_FEED_LIMIT);
+    if filter.authors.as_ref().map_or(true, |a| a.is_empty()) {
+        return Vec::new();
+    }
+    vec![filter]
+}
+
+/// Replies, mentions, reposts, reactions and zaps that reference the public key
+///
+/// The events authored by `public_key` itself (i.e. replies to own notes) are NOT excluded:
+/// filter them out before displaying.
+pub fn notifications(public_key: PublicKey) -> Vec<Filter> {
+    vec![Filter::new()
+        .pubkey(public_key)
+        .kinds([
+            Kind::TextNote,
+            Kind::Repost,
+            Kind::GenericRepost,
+            Kind::Reaction,
+            Kind::ZapReceipt,
+        ])
+        .limit(NOTIFICATIONS_LIMIT)]
+}
+
+/// Metadata, contact list and latest notes of the public key
+///
+/// Replaceable events are requested in a separate filter, so the notes `limit` can't drop them.
+pub fn profile(public_key: PublicKey) -> Vec<Filter> {
+    vec![
+        Filter::new().author(public_key).kinds([
+            Kind::Metadata,
+            Kind::ContactList,
+            Kind::RelayList,
+        ]),
+        Filter::new()
+            .author(public_key)
+            .kinds(FEED_KINDS)
+            .limit(PROFILE_NOTES_LIMIT),
+    ]
+}
+
+/// Root event and all the replies of the thread
+///
+/// Replies are matched by the `e` tag, that NIP10 requires to reference the root in every reply.
+pub fn thread(root: EventId) -> Vec<Filter> {
+    vec![
+        Filter::new().id(root),
+        Filter::new().event(root).kind(Kind::TextNote),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Keys;
+
+    #[test]
+    fn test_home_feed() {
+        assert!(home_feed([]).is_empty());
+
+        let public_key = Keys::generate().public_key();
+        let filters = home_feed([public_key]);
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].limit, Some(HOME_FEED_LIMIT));
+    }
+
+    #[test]
+    fn test_profile() {
+        let public_key = Keys::generate().public_key();
+        let filters = profile(public_key);
+        assert_eq!(filters.len(), 2);
+        assert!(filters[0].limit.is_none());
+        assert_eq!(filters[1].limit, Some(PROFILE_NOTES_LIMIT));
+    }
+}
@@ -6,6 +6,7 @@
 
 pub mod contact;
 pub mod filter;
+pub mod filters;
 pub mod metadata;
 pub mod time;
 pub mod url;
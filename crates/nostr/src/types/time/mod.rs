@@ -26,6 +26,11 @@ pub use self::supplier::{Instant, SystemTime, UNIX_EPOCH};
 pub struct Timestamp(i64);
 
 impl Timestamp {
+    /// Nostr genesis (November 1, 2020)
+    ///
+    /// The protocol didn't exist before: older timestamps are usually wrong or forged.
+    pub const GENESIS: Self = Self(1_604_188_800);
+
     /// Get UNIX timestamp
     #[cfg(feature = "std")]
     pub fn now() -> Self {
@@ -0,0 +1,53 @@
+// Copyright (c) 2022-2023 Yuki Kishimoto
+// Copyright (c) 2023-2024 Rust Nostr Developers
+// Distributed under the MIT software license
+
+//! Crypto context
+//!
+//! All the signing and verification code paths without a `_with_ctx` suffix
+//! use the global [`SECP256K1`] context, so the context (and its precomputed tables)
+//! is built only once per process.
+
+use bitcoin::secp256k1::{rand, All, Secp256k1};
+use once_cell::sync::Lazy;
+
+/// Secp256k1 global context
+pub static SECP256K1: Lazy<Secp256k1<All>> = Lazy::new(|| {
+    let mut ctx = Secp256k1::new();
+    let mut rng = rand::thread_rng();
+    ctx.randomize(&mut rng);
+    ctx
+});
+
+/// Initialize the global [`SECP256K1`] context ahead of time
+///
+/// The context is lazily built on first use: call this at app startup
+/// (i.e. on mobile) to avoid paying the setup cost on the first signature or verification.
+///
+/// Calling it more than once is a no-op.
+#[inline]
+pub fn init() {
+    Lazy::force(&SECP256K1);
+}
+
+/// Check if the global [`SECP256K1`] context has already been initialized
+#[inline]
+pub fn is_initialized() -> bool {
+    Lazy::get(&SECP256K1).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init() {
+        init();
+        assert!(is_initialized());
+
+        // Must not re-create the context
+        let ptr: *const Secp256k1<All> = &*SECP256K1;
+        init();
+        assert_eq!(ptr, &*SECP256K1 as *const Secp256k1<All>);
+    }
+}
@@ -7,13 +7,11 @@
 use alloc::string::String;
 
 use bitcoin::secp256k1::{ecdh, Parity, PublicKey as NormalizedPublicKey};
-#[cfg(feature = "std")]
-use bitcoin::secp256k1::{rand, All, Secp256k1};
-#[cfg(feature = "std")]
-use once_cell::sync::Lazy;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+#[cfg(feature = "std")]
+pub mod crypto;
 pub mod hex;
 #[cfg(feature = "nip44")]
 pub mod hkdf;
@@ -21,6 +19,9 @@ pub mod hkdf;
 use crate::nips::nip01::Coordinate;
 use crate::{EventId, PublicKey, SecretKey, Tag};
 
+#[cfg(feature = "std")]
+pub use self::crypto::SECP256K1;
+
 /// Generate shared key
 ///
 /// **Important: use of a strong cryptographic hash function may be critical to security! Do NOT use
@@ -34,15 +35,6 @@ pub fn generate_shared_key(secret_key: &SecretKey, public_key: &PublicKey) -> [u
     shared_key
 }
 
-/// Secp256k1 global context
-#[cfg(feature = "std")]
-pub static SECP256K1: Lazy<Secp256k1<All>> = Lazy::new(|| {
-    let mut ctx = Secp256k1::new();
-    let mut rng = rand::thread_rng();
-    ctx.randomize(&mut rng);
-    ctx
-});
-
 /// JSON util
 pub trait JsonUtil: Sized + Serialize + DeserializeOwned
 where